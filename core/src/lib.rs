@@ -0,0 +1,114 @@
+//! The mx knowledge store, codex and state tensors as a library
+//!
+//! mx itself is a binary with no library target; this crate compiles the
+//! parts of its source tree that the language bindings need (`python/`,
+//! `ffi/`) so they share one copy of the store and its rules. It is not a
+//! stable Rust API: modules are exported as they are in mx.
+//!
+//! The store opens like the CLI's: `$MX_HOME` (or `MX_MEMORY_PATH`),
+//! `MX_SURREAL_MODE`, profiles and the intent journal all apply.
+
+// mx's own sources, compiled in directly. Each declares what it needs from
+// the others through `crate::`. Exporting them as `pub` surfaces the
+// crate-private types some of their `pub` methods return.
+#![allow(dead_code, private_interfaces)]
+
+#[path = "../../src/codex.rs"]
+pub mod codex;
+#[path = "../../src/config.rs"]
+pub mod config;
+#[path = "../../src/content_ops.rs"]
+pub mod content_ops;
+#[path = "../../src/dates.rs"]
+pub mod dates;
+#[path = "../../src/error.rs"]
+pub mod error;
+#[path = "../../src/journal.rs"]
+pub mod journal;
+#[path = "../../src/jsonl.rs"]
+pub mod jsonl;
+#[path = "../../src/knowledge.rs"]
+pub mod knowledge;
+#[path = "../../src/output.rs"]
+pub mod output;
+#[path = "../../src/paths.rs"]
+pub mod paths;
+#[path = "../../src/progress.rs"]
+pub mod progress;
+#[path = "../../src/search.rs"]
+pub mod search;
+#[path = "../../src/session.rs"]
+pub mod session;
+#[path = "../../src/slow_log.rs"]
+pub mod slow_log;
+#[path = "../../src/state.rs"]
+pub mod state;
+#[path = "../../src/store.rs"]
+pub mod store;
+#[path = "../../src/surreal_db.rs"]
+pub mod surreal_db;
+#[path = "../../src/text.rs"]
+pub mod text;
+#[path = "../../src/tokens.rs"]
+pub mod tokens;
+#[path = "../../src/types.rs"]
+pub mod types;
+#[path = "../../src/wake_token.rs"]
+pub mod wake_token;
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use knowledge::KnowledgeEntry;
+use store::{AgentContext, KnowledgeStore};
+
+/// `kn-` prefix optional, as on the command line
+pub fn normalize_id(id: &str) -> String {
+    if id.starts_with("kn-") {
+        id.to_string()
+    } else {
+        format!("kn-{}", id)
+    }
+}
+
+/// Open the store where IndexConfig puts it
+pub fn open_store() -> Result<Box<dyn KnowledgeStore>> {
+    let base = std::env::var("MX_MEMORY_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| paths::mx_home().to_path_buf());
+    store::create_store(&base.join("memory").join("knowledge.surreal"))
+}
+
+/// What `agent` may see. `agent` defaults to MX_CURRENT_AGENT; without
+/// `include_private` only public entries are visible.
+pub fn agent_context(agent: Option<String>, include_private: bool) -> AgentContext {
+    let agent = agent.or_else(|| std::env::var("MX_CURRENT_AGENT").ok());
+    match agent.filter(|a| !a.is_empty()) {
+        Some(agent) if include_private => AgentContext::for_agent(agent),
+        Some(agent) => AgentContext::public_for_agent(agent),
+        None => AgentContext::public_only(),
+    }
+}
+
+/// An entry from its JSON fields, ready to upsert. `category_id` and `title`
+/// are required; a missing id, hash or timestamps are filled in as
+/// `mx memory add` would.
+pub fn entry_from_json(mut value: serde_json::Value) -> Result<KnowledgeEntry> {
+    if let Some(fields) = value.as_object_mut() {
+        fields.entry("id").or_insert_with(|| "".into());
+    }
+    let mut entry: KnowledgeEntry = serde_json::from_value(value)?;
+    if entry.id.is_empty() {
+        entry.id = KnowledgeEntry::generate_id(&entry.category_id, &entry.title);
+    }
+    entry.id = normalize_id(&entry.id);
+    let now = chrono::Utc::now().to_rfc3339();
+    entry.created_at.get_or_insert_with(|| now.clone());
+    entry.updated_at = Some(now);
+    if entry.content_hash.is_none() {
+        let content = entry.body.as_deref().unwrap_or(&entry.title);
+        entry.content_hash = Some(KnowledgeEntry::compute_hash(content));
+    }
+    entry.validate()?;
+    Ok(entry)
+}
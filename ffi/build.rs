@@ -0,0 +1,15 @@
+//! Regenerates include/mx.h from the `extern "C"` items in src/lib.rs
+
+fn main() {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir))
+        .expect("Failed to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{}/src/lib.rs", dir))
+        .generate()
+        .expect("Failed to generate the C header")
+        .write_to_file(format!("{}/include/mx.h", dir));
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
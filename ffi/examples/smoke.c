@@ -0,0 +1,50 @@
+/*
+ * Smoke test for libmx: add an entry, read it back and find it by search.
+ *
+ *   cargo build --release
+ *   cc examples/smoke.c -Iinclude -Ltarget/release -lmx -o target/smoke
+ *   MX_HOME=$(mktemp -d) LD_LIBRARY_PATH=target/release target/smoke
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "mx.h"
+
+static int fail(const char *what) {
+    const char *error = mx_last_error();
+    fprintf(stderr, "%s failed: %s\n", what, error ? error : "not found");
+    return 1;
+}
+
+int main(void) {
+    MxStore *store = mx_store_open("smoke", true);
+    if (!store) return fail("mx_store_open");
+
+    char *id = mx_add(store,
+        "{\"category_id\": \"pattern\", \"title\": \"Retry with backoff\","
+        " \"body\": \"Double the wait between attempts.\", \"tags\": [\"c\"]}");
+    if (!id) return fail("mx_add");
+    printf("added   %s\n", id);
+
+    char *entry = mx_get(store, id);
+    if (!entry) return fail("mx_get");
+    printf("get     %s\n", entry);
+
+    char *hits = mx_search(store, "backoff", 5);
+    if (!hits) return fail("mx_search");
+    printf("search  %s\n", hits);
+    int found = strstr(hits, id) != NULL;
+
+    mx_string_free(hits);
+    mx_string_free(entry);
+    mx_string_free(id);
+    mx_store_close(store);
+
+    if (!found) {
+        fprintf(stderr, "search did not return the new entry\n");
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
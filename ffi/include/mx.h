@@ -0,0 +1,77 @@
+/* mx: C interface to the mx knowledge store. See ffi/src/lib.rs. */
+
+#ifndef MX_H
+#define MX_H
+
+/* Generated by cbindgen from src/lib.rs when mx-ffi builds; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An open knowledge store, seen as one agent
+typedef struct MxStore MxStore;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Open the store as `agent` (NULL for MX_CURRENT_AGENT). Without
+// `include_private` only public entries are visible. Returns NULL on
+// failure; close with `mx_store_close`.
+//
+// # Safety
+//
+// `agent` is NULL or a NUL-terminated string.
+struct MxStore *mx_store_open(const char *agent, bool include_private);
+
+// Close a store from `mx_store_open`. NULL is ignored.
+//
+// # Safety
+//
+// `store` came from `mx_store_open` and is not used afterwards.
+void mx_store_close(struct MxStore *store);
+
+// Keyword search, best match first, as a JSON array of entries. A `limit`
+// of 0 returns every match. Returns NULL on failure.
+//
+// # Safety
+//
+// `store` is open and `query` is a NUL-terminated string.
+char *mx_search(const struct MxStore *store, const char *query, uintptr_t limit);
+
+// One entry as a JSON object (the `kn-` prefix of `id` is optional).
+// Returns NULL if it doesn't exist, with `mx_last_error` also NULL, or on
+// failure.
+//
+// # Safety
+//
+// `store` is open and `id` is a NUL-terminated string.
+char *mx_get(const struct MxStore *store, const char *id);
+
+// Insert or replace an entry given as a JSON object and return its id.
+// `category_id` and `title` are required; a missing id, hash or timestamps
+// are filled in as `mx memory add` would. Returns NULL on failure.
+//
+// # Safety
+//
+// `store` is open and `entry_json` is a NUL-terminated string.
+char *mx_add(const struct MxStore *store, const char *entry_json);
+
+// Free a string returned by mx. NULL is ignored.
+//
+// # Safety
+//
+// `s` came from mx and is not used afterwards.
+void mx_string_free(char *s);
+
+// Why the last call on this thread failed, or NULL if it didn't. Owned by
+// mx and valid until the next call on the thread.
+const char *mx_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MX_H */
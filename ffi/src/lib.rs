@@ -0,0 +1,245 @@
+//! C bindings: `libmx`
+//!
+//! A small C ABI over the knowledge store, so agent frameworks that aren't
+//! written in Rust can embed the memory layer instead of shelling out to the
+//! CLI. The header is `include/mx.h`, regenerated from this file by cbindgen
+//! on every build:
+//!
+//! ```bash
+//! cd ffi && cargo build --release    # target/release/libmx.{so,dylib,a}
+//! ```
+//!
+//! Entries cross the boundary as JSON with the fields of
+//! `mx memory show --json`. Strings returned by mx are owned by the caller
+//! and released with `mx_string_free`; a NULL return means failure, and
+//! `mx_last_error` says why. A store handle must only be used from one
+//! thread at a time.
+//!
+//! The store opens like the CLI's: `$MX_HOME` (or `MX_MEMORY_PATH`),
+//! `MX_SURREAL_MODE`, profiles and the intent journal all apply.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use mx_core::store::{AgentContext, KnowledgeFilter, KnowledgeStore};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An open knowledge store, seen as one agent
+pub struct MxStore {
+    db: Box<dyn KnowledgeStore>,
+    ctx: AgentContext,
+}
+
+/// Run `f` for a C caller: errors and panics become `failed` plus the
+/// thread's last error, which is cleared otherwise
+fn call<T>(failed: T, f: impl FnOnce() -> Result<T>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(anyhow!("mx panicked; see stderr")));
+    let (value, error) = match result {
+        Ok(value) => (value, None),
+        Err(e) => (failed, Some(format!("{:#}", e))),
+    };
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = error.map(|e| CString::new(e.replace('\0', " ")).unwrap());
+    });
+    value
+}
+
+/// A C string argument; NULL is None
+unsafe fn arg<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    let s = unsafe { CStr::from_ptr(s) };
+    s.to_str()
+        .map(Some)
+        .with_context(|| format!("{} is not valid UTF-8", name))
+}
+
+/// A required C string argument
+unsafe fn required<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    unsafe { arg(s, name) }?.ok_or_else(|| anyhow!("{} is NULL", name))
+}
+
+unsafe fn store<'a>(store: *const MxStore) -> Result<&'a MxStore> {
+    unsafe { store.as_ref() }.context("store is NULL")
+}
+
+/// A string handed to C, freed with `mx_string_free`
+fn owned(s: String) -> Result<*mut c_char> {
+    Ok(CString::new(s)?.into_raw())
+}
+
+/// Open the store as `agent` (NULL for MX_CURRENT_AGENT). Without
+/// `include_private` only public entries are visible. Returns NULL on
+/// failure; close with `mx_store_close`.
+///
+/// # Safety
+///
+/// `agent` is NULL or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_store_open(
+    agent: *const c_char,
+    include_private: bool,
+) -> *mut MxStore {
+    call(ptr::null_mut(), || {
+        let agent = unsafe { arg(agent, "agent") }?.map(String::from);
+        let store = MxStore {
+            db: mx_core::open_store()?,
+            ctx: mx_core::agent_context(agent, include_private),
+        };
+        Ok(Box::into_raw(Box::new(store)))
+    })
+}
+
+/// Close a store from `mx_store_open`. NULL is ignored.
+///
+/// # Safety
+///
+/// `store` came from `mx_store_open` and is not used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_store_close(store: *mut MxStore) {
+    if !store.is_null() {
+        drop(unsafe { Box::from_raw(store) });
+    }
+}
+
+/// Keyword search, best match first, as a JSON array of entries. A `limit`
+/// of 0 returns every match. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `store` is open and `query` is a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_search(
+    store: *const MxStore,
+    query: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let store = unsafe { self::store(store) }?;
+        let query = unsafe { required(query, "query") }?;
+        let mut entries = store
+            .db
+            .search(query, &store.ctx, &KnowledgeFilter::default())?;
+        if limit > 0 {
+            entries.truncate(limit);
+        }
+        owned(serde_json::to_string(&entries)?)
+    })
+}
+
+/// One entry as a JSON object (the `kn-` prefix of `id` is optional).
+/// Returns NULL if it doesn't exist, with `mx_last_error` also NULL, or on
+/// failure.
+///
+/// # Safety
+///
+/// `store` is open and `id` is a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_get(store: *const MxStore, id: *const c_char) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let store = unsafe { self::store(store) }?;
+        let id = mx_core::normalize_id(unsafe { required(id, "id") }?);
+        match store.db.get(&id, &store.ctx)? {
+            Some(entry) => owned(serde_json::to_string(&entry)?),
+            None => Ok(ptr::null_mut()),
+        }
+    })
+}
+
+/// Insert or replace an entry given as a JSON object and return its id.
+/// `category_id` and `title` are required; a missing id, hash or timestamps
+/// are filled in as `mx memory add` would. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `store` is open and `entry_json` is a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_add(store: *const MxStore, entry_json: *const c_char) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let store = unsafe { self::store(store) }?;
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { required(entry_json, "entry_json") }?)
+                .context("entry_json is not valid JSON")?;
+        if !value.is_object() {
+            bail!("entry_json is not a JSON object");
+        }
+        let entry = mx_core::entry_from_json(value)?;
+        store.db.upsert_knowledge(&entry)?;
+        owned(entry.id)
+    })
+}
+
+/// Free a string returned by mx. NULL is ignored.
+///
+/// # Safety
+///
+/// `s` came from mx and is not used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mx_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Why the last call on this thread failed, or NULL if it didn't. Owned by
+/// mx and valid until the next call on the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn mx_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: *const c_char) -> String {
+        assert!(!s.is_null(), "{:?}", unsafe {
+            CStr::from_ptr(mx_last_error())
+        });
+        unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn adds_gets_and_searches_through_the_c_interface() {
+        let home = tempfile::tempdir().unwrap();
+        unsafe { std::env::set_var("MX_HOME", home.path()) };
+
+        unsafe {
+            let store = mx_store_open(c"q".as_ptr(), true);
+            assert!(!store.is_null());
+
+            let entry = cr#"{"category_id":"pattern","title":"Retry with backoff","body":"Double the wait","tags":["rust"]}"#;
+            let id = mx_add(store, entry.as_ptr());
+            assert!(text(id).starts_with("kn-"));
+
+            let found = mx_get(store, id);
+            let json: serde_json::Value = serde_json::from_str(&text(found)).unwrap();
+            assert_eq!(json["title"], "Retry with backoff");
+            mx_string_free(found);
+
+            let hits = mx_search(store, c"backoff".as_ptr(), 5);
+            let json: serde_json::Value = serde_json::from_str(&text(hits)).unwrap();
+            assert_eq!(json[0]["id"].as_str().unwrap(), text(id));
+            mx_string_free(hits);
+            mx_string_free(id);
+
+            assert!(mx_get(store, c"kn-missing".as_ptr()).is_null());
+            assert!(mx_last_error().is_null());
+
+            assert!(mx_add(store, cr#"{"title":"no category"}"#.as_ptr()).is_null());
+            assert!(text(mx_last_error()).contains("category_id"));
+            assert!(mx_search(ptr::null(), c"x".as_ptr(), 0).is_null());
+            assert_eq!(text(mx_last_error()), "store is NULL");
+
+            mx_store_close(store);
+        }
+    }
+}
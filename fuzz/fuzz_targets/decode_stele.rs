@@ -0,0 +1,54 @@
+//! Fuzz the stele decoders against the bundled schemas:
+//!
+//! ```bash
+//! cargo +nightly fuzz run decode_stele
+//! ```
+//!
+//! Decoding must never panic, and whatever decodes must re-encode to a stele
+//! that decodes the same.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+
+#[allow(dead_code)]
+#[path = "../../src/state.rs"]
+mod state;
+
+/// `state` looks up its default schema under `crate::paths`
+#[allow(dead_code)]
+mod paths {
+    pub fn schemas_dir() -> std::path::PathBuf {
+        std::path::PathBuf::from("schemas")
+    }
+}
+
+use state::{DynamicState, EmotionalState, StateSchema};
+
+fn schemas() -> &'static [StateSchema] {
+    static SCHEMAS: OnceLock<Vec<StateSchema>> = OnceLock::new();
+    SCHEMAS.get_or_init(|| {
+        [
+            include_str!("../../schemas/example-q-state.json"),
+            include_str!("../../schemas/example-soren-state.json"),
+        ]
+        .iter()
+        .map(|json| serde_json::from_str(json).expect("bundled schema parses"))
+        .collect()
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    for schema in schemas() {
+        if let Ok(state) = DynamicState::decode_stele(text, schema) {
+            let stele = state.encode_stele(schema);
+            let again = DynamicState::decode_stele(&stele, schema).expect("re-decode");
+            assert_eq!(again.encode_stele(schema), stele);
+        }
+        let _ = EmotionalState::decode_stele(text, schema);
+    }
+});
@@ -0,0 +1,249 @@
+//! Python bindings: `import mx`
+//!
+//! The supported way for Python scripts to reach mx data, instead of
+//! re-parsing JSONL exports or codex directories. Built with maturin:
+//!
+//! ```bash
+//! cd python && maturin develop --release
+//! ```
+//!
+//! ```python
+//! import mx
+//!
+//! store = mx.Store(agent="q", include_private=True)
+//! for entry in store.search("retry", category="pattern", limit=5):
+//!     print(entry["id"], entry["title"])
+//! store.upsert({"category_id": "pattern", "title": "Retry with backoff", "body": "..."})
+//!
+//! mx.codex_list()                      # archived sessions, newest first
+//! mx.codex_transcript("a1b2c3d4")      # clean transcript of one
+//! mx.state_decode("...")               # stele -> {dimension: value}
+//! ```
+//!
+//! The store opens like the CLI's: `$MX_HOME` (or `MX_MEMORY_PATH`),
+//! `MX_SURREAL_MODE`, profiles and the intent journal all apply. Entries are
+//! plain dicts with the fields of `mx memory show --json`.
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+use mx_core::store::{AgentContext, KnowledgeFilter, KnowledgeStore};
+use mx_core::{codex, normalize_id, state};
+
+fn runtime_error(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+/// A Rust value as the Python object `json.loads` would make of it
+fn to_py(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| runtime_error(e.into()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// A Python object (dicts, lists, str, numbers) as a Rust value
+fn from_py<T: DeserializeOwned>(obj: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = obj
+        .py()
+        .import("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()?;
+    serde_json::from_str(&json).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// The knowledge store, seen as one agent
+#[pyclass(unsendable, module = "mx")]
+struct Store {
+    db: Box<dyn KnowledgeStore>,
+    ctx: AgentContext,
+}
+
+#[pymethods]
+impl Store {
+    /// Open the store. `agent` defaults to MX_CURRENT_AGENT; without
+    /// `include_private` only public entries are visible.
+    #[new]
+    #[pyo3(signature = (agent=None, include_private=false))]
+    fn new(agent: Option<String>, include_private: bool) -> PyResult<Self> {
+        let db = mx_core::open_store().map_err(runtime_error)?;
+        let ctx = mx_core::agent_context(agent, include_private);
+        Ok(Self { db, ctx })
+    }
+
+    /// One entry, or None
+    fn get(&self, py: Python<'_>, id: &str) -> PyResult<Option<PyObject>> {
+        match self
+            .db
+            .get(&normalize_id(id), &self.ctx)
+            .map_err(runtime_error)?
+        {
+            Some(entry) => Ok(Some(to_py(py, &entry)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Keyword search, best match first
+    #[pyo3(signature = (query, category=None, tags=None, limit=None))]
+    fn search(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        category: Option<String>,
+        tags: Option<Vec<String>>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let filter = KnowledgeFilter {
+            categories: category.map(|c| vec![c]),
+            tags,
+            ..KnowledgeFilter::default()
+        };
+        let mut entries = self
+            .db
+            .search(query, &self.ctx, &filter)
+            .map_err(runtime_error)?;
+        if let Some(limit) = limit {
+            entries.truncate(limit);
+        }
+        to_py(py, &entries)
+    }
+
+    /// Every visible entry, or those in one category
+    #[pyo3(signature = (category=None))]
+    fn list(&self, py: Python<'_>, category: Option<&str>) -> PyResult<PyObject> {
+        let entries = match category {
+            Some(category) => {
+                self.db
+                    .list_by_category(category, &self.ctx, &KnowledgeFilter::default())
+            }
+            None => self.db.list_all(&self.ctx),
+        }
+        .map_err(runtime_error)?;
+        to_py(py, &entries)
+    }
+
+    /// Insert or replace an entry and return its id. `category_id` and
+    /// `title` are required; a missing id, hash or timestamps are filled in
+    /// as `mx memory add` would.
+    fn upsert(&self, entry: &Bound<'_, PyAny>) -> PyResult<String> {
+        let entry = mx_core::entry_from_json(from_py(entry)?)
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))?;
+        self.db.upsert_knowledge(&entry).map_err(runtime_error)?;
+        Ok(entry.id)
+    }
+
+    /// Delete an entry; False if it didn't exist
+    fn delete(&self, id: &str) -> PyResult<bool> {
+        self.db
+            .delete(&normalize_id(id), &self.ctx)
+            .map_err(runtime_error)
+    }
+
+    /// Number of entries in the store
+    fn count(&self) -> PyResult<usize> {
+        self.db.count().map_err(runtime_error)
+    }
+}
+
+/// Archived sessions, newest first (every incremental save with `all`)
+#[pyfunction]
+#[pyo3(signature = (all=false))]
+fn codex_list(py: Python<'_>, all: bool) -> PyResult<PyObject> {
+    to_py(py, &codex::archive_summaries(all).map_err(runtime_error)?)
+}
+
+/// Clean transcript of an archived session
+#[pyfunction]
+fn codex_transcript(id: &str) -> PyResult<String> {
+    Ok(codex::transcript(id).map_err(runtime_error)?.1)
+}
+
+/// The schema at `path`, or the default one `mx state` uses
+fn schema(path: Option<&str>) -> PyResult<state::StateSchema> {
+    match path {
+        Some(path) => state::load_schema(Path::new(path)),
+        None => state::load_default_schema(),
+    }
+    .map_err(runtime_error)
+}
+
+/// Encode `{dimension: value}` as a stele
+#[pyfunction]
+#[pyo3(signature = (values, schema_path=None))]
+fn state_encode(values: &Bound<'_, PyAny>, schema_path: Option<&str>) -> PyResult<String> {
+    let schema = schema(schema_path)?;
+    let state = state::DynamicState {
+        schema_id: schema.title.clone(),
+        values: from_py(values)?,
+    };
+    Ok(state.encode_stele(&schema))
+}
+
+/// Decode a stele into `{dimension: value}`
+#[pyfunction]
+#[pyo3(signature = (stele, schema_path=None))]
+fn state_decode(py: Python<'_>, stele: &str, schema_path: Option<&str>) -> PyResult<PyObject> {
+    let schema = schema(schema_path)?;
+    let state = state::DynamicState::decode_stele(stele, &schema)
+        .map_err(|e| PyValueError::new_err(format!("{:#}", e)))?;
+    to_py(py, &state.values)
+}
+
+#[pymodule]
+#[pyo3(name = "mx")]
+fn mx_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Store>()?;
+    m.add_function(wrap_pyfunction!(codex_list, m)?)?;
+    m.add_function(wrap_pyfunction!(codex_transcript, m)?)?;
+    m.add_function(wrap_pyfunction!(state_encode, m)?)?;
+    m.add_function(wrap_pyfunction!(state_decode, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    #[test]
+    fn opens_adds_and_searches_from_python() {
+        let home = tempfile::tempdir().unwrap();
+        unsafe { std::env::set_var("MX_HOME", home.path()) };
+
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "mx").unwrap();
+            mx_module(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("mx", module).unwrap();
+            let script = cr#"
+store = mx.Store(agent="q", include_private=True)
+assert store.count() == 0
+
+id = store.upsert({"category_id": "pattern", "title": "Retry with backoff",
+                   "body": "Double the wait", "tags": ["rust"]})
+assert id.startswith("kn-"), id
+entry = store.get(id[3:])
+assert (entry["id"], entry["title"], entry["tags"]) == (id, "Retry with backoff", ["rust"]), entry
+
+assert [e["id"] for e in store.search("backoff", category="pattern", limit=5)] == [id]
+assert store.search("backoff", category="insight") == []
+assert [e["id"] for e in store.list("pattern")] == [id]
+assert store.count() == 1
+
+try:
+    store.upsert({"title": "no category"})
+    raise AssertionError("upsert without category_id")
+except ValueError as e:
+    assert "category_id" in str(e), e
+
+assert store.delete(id) and not store.delete(id)
+assert store.get(id) is None
+"#;
+            if let Err(e) = py.run(script, Some(&globals), None) {
+                panic!("{}", e.value(py));
+            }
+        });
+    }
+}
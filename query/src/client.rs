@@ -0,0 +1,214 @@
+//! Requests to `mx tenant serve` and their decoding
+
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::knowledge::KnowledgeEntry;
+
+/// A server response: HTTP status and body
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// How requests reach the server
+pub trait Transport {
+    /// GET `url` with `Authorization: Bearer <token>`
+    fn get(&self, url: &str, token: &str) -> Result<Response>;
+}
+
+/// The identity a token maps to (`/api/whoami`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Caller {
+    pub tenant: String,
+    pub agent: String,
+    pub namespace: String,
+}
+
+/// Narrows an entry listing; unset fields use the server's defaults
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Read-only client for one server and token
+pub struct Client<T> {
+    base_url: String,
+    token: String,
+    transport: T,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(base_url: &str, token: &str, transport: T) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            transport,
+        }
+    }
+
+    /// The tenant and agent the token belongs to
+    pub fn whoami(&self) -> Result<Caller> {
+        decode(self.request("/api/whoami", &[])?)
+    }
+
+    /// Entries in the caller's tenant
+    pub fn list(&self, filter: &Filter) -> Result<Vec<KnowledgeEntry>> {
+        let limit = filter.limit.map(|l| l.to_string());
+        decode(self.request(
+            "/api/entries",
+            &[
+                ("category", filter.category.as_deref()),
+                ("tag", filter.tag.as_deref()),
+                ("limit", limit.as_deref()),
+            ],
+        )?)
+    }
+
+    /// Keyword search in the caller's tenant, best match first
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<KnowledgeEntry>> {
+        let limit = limit.map(|l| l.to_string());
+        decode(self.request(
+            "/api/search",
+            &[("q", Some(query)), ("limit", limit.as_deref())],
+        )?)
+    }
+
+    /// One entry, or None if it doesn't exist or the caller can't see it
+    pub fn get(&self, id: &str) -> Result<Option<KnowledgeEntry>> {
+        let response = self.request("/api/entry", &[("id", Some(id))])?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        decode(response).map(Some)
+    }
+
+    /// Public entries of `owner`, which must have granted the caller's tenant
+    /// access
+    pub fn shared(&self, owner: &str, limit: Option<usize>) -> Result<Vec<KnowledgeEntry>> {
+        let limit = limit.map(|l| l.to_string());
+        decode(self.request(
+            "/api/shared",
+            &[("tenant", Some(owner)), ("limit", limit.as_deref())],
+        )?)
+    }
+
+    /// One of `owner`'s shared entries
+    pub fn shared_entry(&self, owner: &str, id: &str) -> Result<Option<KnowledgeEntry>> {
+        let response = self.request("/api/shared", &[("tenant", Some(owner)), ("id", Some(id))])?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        decode(response).map(Some)
+    }
+
+    fn request(&self, path: &str, params: &[(&str, Option<&str>)]) -> Result<Response> {
+        let query: Vec<String> = params
+            .iter()
+            .filter_map(|(key, value)| value.map(|v| format!("{}={}", key, urlencoding::encode(v))))
+            .collect();
+        let mut url = format!("{}{}", self.base_url, path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+        self.transport.get(&url, &self.token)
+    }
+}
+
+/// The body of a successful response, or the server's `{"error": ...}` as an
+/// error
+fn decode<D: DeserializeOwned>(response: Response) -> Result<D> {
+    if !(200..300).contains(&response.status) {
+        let message = serde_json::from_str::<serde_json::Value>(&response.body)
+            .ok()
+            .and_then(|v| v["error"].as_str().map(String::from))
+            .unwrap_or(response.body);
+        bail!("Server returned {}: {}", response.status, message);
+    }
+    serde_json::from_str(&response.body).context("Unexpected response from the server")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Answers from a fixed route table and records what was asked
+    struct Canned {
+        routes: Vec<(&'static str, u16, String)>,
+        asked: RefCell<Vec<String>>,
+    }
+
+    impl Transport for &Canned {
+        fn get(&self, url: &str, token: &str) -> Result<Response> {
+            assert_eq!(token, "mxt_secret");
+            self.asked.borrow_mut().push(url.to_string());
+            let path = url.trim_start_matches("http://mx.test");
+            Ok(
+                match self.routes.iter().find(|(p, _, _)| path.starts_with(p)) {
+                    Some((_, status, body)) => Response {
+                        status: *status,
+                        body: body.clone(),
+                    },
+                    None => Response {
+                        status: 404,
+                        body: r#"{"error":"entry not found"}"#.to_string(),
+                    },
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn builds_requests_and_decodes_entries_and_errors() {
+        let entry = r#"{"id":"kn-a","category_id":"pattern","title":"Retry with backoff","tags":["rust"],"resonance":7}"#;
+        let list = format!("[{}]", entry);
+        let canned = Canned {
+            routes: vec![
+                (
+                    "/api/whoami",
+                    200,
+                    r#"{"tenant":"acme","agent":"smith","namespace":"tenant_acme"}"#.to_string(),
+                ),
+                ("/api/search", 200, list.clone()),
+                ("/api/entries", 200, list),
+                ("/api/entry?id=kn-a", 200, entry.to_string()),
+                (
+                    "/api/shared",
+                    403,
+                    r#"{"error":"no grant from that tenant"}"#.to_string(),
+                ),
+            ],
+            asked: RefCell::new(Vec::new()),
+        };
+        let client = Client::new("http://mx.test/", "mxt_secret", &canned);
+
+        assert_eq!(client.whoami().unwrap().tenant, "acme");
+        let found = client.search("retry & backoff", Some(5)).unwrap();
+        assert_eq!(found[0].title, "Retry with backoff");
+        let listed = client
+            .list(&Filter {
+                tag: Some("rust".into()),
+                ..Filter::default()
+            })
+            .unwrap();
+        assert_eq!(listed[0].resonance, 7);
+        assert_eq!(client.get("kn-a").unwrap().unwrap().tags, vec!["rust"]);
+        assert!(client.get("kn-zz").unwrap().is_none());
+        let denied = client.shared("globex", None).unwrap_err().to_string();
+        assert_eq!(denied, "Server returned 403: no grant from that tenant");
+
+        assert_eq!(
+            canned.asked.borrow()[1..3],
+            [
+                "http://mx.test/api/search?q=retry%20%26%20backoff&limit=5",
+                "http://mx.test/api/entries?tag=rust",
+            ]
+        );
+    }
+}
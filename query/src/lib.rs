@@ -0,0 +1,44 @@
+//! The mx knowledge read path, for hosts that can't run mx itself
+//!
+//! A client for the JSON API of `mx tenant serve`: list, search and read
+//! entries (and other tenants' shared ones) with an agent's bearer token.
+//! Entries come back as mx's own [`knowledge::KnowledgeEntry`].
+//!
+//! Nothing here touches the filesystem, git or a local store, so the crate
+//! builds for wasm and can be embedded in sandboxed agent runtimes and
+//! browser tooling:
+//!
+//! ```bash
+//! cargo build --release --no-default-features --target wasm32-wasip1
+//! ```
+//!
+//! Without the default `native` feature there is no HTTP stack; the host
+//! implements [`Transport`] (the browser's fetch, a runtime's wasi-http or
+//! a host call). Native callers use [`HttpTransport`]:
+//!
+//! ```no_run
+//! use mx_query::{Client, HttpTransport};
+//!
+//! let client = Client::new("http://127.0.0.1:8787", "mxt_...", HttpTransport::default());
+//! for entry in client.search("retry", Some(10))? {
+//!     println!("{}  {}", entry.id, entry.title);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+#[allow(dead_code)]
+#[path = "../../src/knowledge.rs"]
+pub mod knowledge;
+
+/// `knowledge` truncates embedding text with `crate::text`
+#[allow(dead_code)]
+#[path = "../../src/text.rs"]
+mod text;
+
+mod client;
+#[cfg(feature = "native")]
+mod native;
+
+pub use client::{Caller, Client, Filter, Response, Transport};
+#[cfg(feature = "native")]
+pub use native::HttpTransport;
@@ -0,0 +1,26 @@
+//! Blocking HTTP transport for native hosts
+
+use anyhow::{Context, Result};
+
+use crate::client::{Response, Transport};
+
+/// [`Transport`] over reqwest's blocking client
+#[derive(Default)]
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl Transport for HttpTransport {
+    fn get(&self, url: &str, token: &str) -> Result<Response> {
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(token)
+            .send()
+            .with_context(|| format!("Failed to reach {}", url))?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            body: response.text()?,
+        })
+    }
+}
@@ -0,0 +1,370 @@
+//! Command aliases: `mx alias`
+//!
+//! User-defined shorthands for long invocations, kept under `alias:` in the
+//! base directory's `config.yaml` (not a profile's: aliases are expanded
+//! before `--profile` is parsed, so every profile shares them):
+//!
+//! ```yaml
+//! alias:
+//!   morning: zion wake --limit 40 --days 14 --agent q
+//! ```
+//!
+//! `mx morning` then runs `mx zion wake --limit 40 --days 14 --agent q`, with any
+//! further arguments appended. Expansion happens before clap sees the command
+//! line, so an alias can stand for any subcommand path. Built-in commands
+//! always win over an alias of the same name, and aliases may refer to other
+//! aliases (cycles are rejected).
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+
+use crate::error::MxError;
+use crate::{config, paths};
+
+/// Key in config.yaml holding the alias table
+const ALIAS_KEY: &str = "alias";
+
+fn aliases_in(config: &Mapping) -> BTreeMap<String, String> {
+    config
+        .get(ALIAS_KEY)
+        .and_then(Value::as_mapping)
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn load_aliases() -> Result<BTreeMap<String, String>> {
+    Ok(aliases_in(&config::load(&paths::base_config_file())?))
+}
+
+/// Split an alias definition into words, honouring single and double quotes
+/// and backslash escapes (outside single quotes)
+fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.push(c),
+            (_, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        bail!("Unterminated quote in alias: {}", line);
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// A global option that may come before the subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalFlag {
+    short: Option<char>,
+    long: Option<String>,
+    takes_value: bool,
+}
+
+/// The global options declared on the top-level command
+pub fn global_flags(cli: &clap::Command) -> Vec<GlobalFlag> {
+    // Arguments only report their arity once the command is built
+    let mut cli = cli.clone();
+    cli.build();
+    cli.get_arguments()
+        .filter(|a| a.is_global_set())
+        .map(|a| GlobalFlag {
+            short: a.get_short(),
+            long: a.get_long().map(String::from),
+            takes_value: a.get_num_args().is_some_and(|n| n.takes_values()),
+        })
+        .collect()
+}
+
+/// Number of words the global flag at `arg` spans (its value included),
+/// or `None` if it isn't one
+fn global_flag_width(arg: &str, flags: &[GlobalFlag]) -> Option<usize> {
+    flags.iter().find_map(|f| {
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (long, false),
+            };
+            if f.long.as_deref() != Some(name) || (value && !f.takes_value) {
+                return None;
+            }
+            return Some(if f.takes_value && !value { 2 } else { 1 });
+        }
+        let short = f.short?;
+        let rest = arg.strip_prefix('-')?.strip_prefix(short)?;
+        match (rest.is_empty(), f.takes_value) {
+            (true, true) => Some(2),
+            (true, false) | (false, true) => Some(1),
+            (false, false) => None,
+        }
+    })
+}
+
+/// Index of the subcommand word, skipping the global flags that may precede it
+fn command_position(args: &[String], flags: &[GlobalFlag]) -> Option<usize> {
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if !arg.starts_with('-') {
+            return Some(i);
+        }
+        i += global_flag_width(arg, flags)?;
+    }
+    None
+}
+
+/// Replace an alias in command position with its definition
+fn expand_with(
+    mut args: Vec<String>,
+    aliases: &BTreeMap<String, String>,
+    builtins: &[String],
+    flags: &[GlobalFlag],
+) -> Result<Vec<String>> {
+    let mut seen: Vec<String> = Vec::new();
+    while let Some(pos) = command_position(&args, flags) {
+        let name = &args[pos];
+        if builtins.contains(name) {
+            break;
+        }
+        let Some(definition) = aliases.get(name) else {
+            break;
+        };
+        if seen.contains(name) {
+            bail!("Alias loop: {} -> {}", seen.join(" -> "), name);
+        }
+        seen.push(name.clone());
+
+        let mut words = split_words(definition)?;
+        if words.first().is_some_and(|w| w == "mx") {
+            words.remove(0);
+        }
+        if words.is_empty() {
+            bail!("Alias '{}' is empty", name);
+        }
+        args.splice(pos..=pos, words);
+    }
+    Ok(args)
+}
+
+/// Expand aliases in a raw command line. A broken config file is reported
+/// but never blocks the command itself.
+pub fn expand(args: Vec<String>, cli: &clap::Command, builtins: &[String]) -> Result<Vec<String>> {
+    match load_aliases() {
+        Ok(aliases) => expand_with(args, &aliases, builtins, &global_flags(cli)),
+        Err(e) => {
+            eprintln!("Warning: aliases not loaded: {:#}", e);
+            Ok(args)
+        }
+    }
+}
+
+pub fn list(json: bool) -> Result<()> {
+    let aliases = load_aliases()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&aliases)?);
+        return Ok(());
+    }
+    if aliases.is_empty() {
+        println!("No aliases defined. Add one with: mx alias add <name> <command...>");
+        return Ok(());
+    }
+    let width = aliases.keys().map(|k| k.len()).max().unwrap_or(0);
+    for (name, definition) in &aliases {
+        println!("{:width$}  {}", name.cyan(), definition, width = width);
+    }
+    Ok(())
+}
+
+pub fn add(name: &str, command: &[String], builtins: &[String]) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with('-')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("Alias names may only contain letters, digits, '-' and '_'");
+    }
+    if builtins.iter().any(|b| b == name) {
+        bail!("'{}' is a built-in command and cannot be aliased", name);
+    }
+    // A single argument is taken as the whole (possibly quoted) definition
+    let definition = command.join(" ");
+    if split_words(&definition)?.is_empty() {
+        bail!("Alias '{}' needs a command to expand to", name);
+    }
+
+    let path = paths::base_config_file();
+    let mut config = config::load(&path)?;
+    let table = config
+        .entry(Value::from(ALIAS_KEY))
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    let Some(table) = table.as_mapping_mut() else {
+        bail!("'{}' in {} is not a mapping", ALIAS_KEY, path.display());
+    };
+    let replaced = table
+        .insert(Value::from(name), Value::from(definition.clone()))
+        .is_some();
+    config::save(&path, &config)?;
+
+    let verb = if replaced { "Updated" } else { "Added" };
+    println!("{} alias: {} = {}", verb, name, definition);
+    Ok(())
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let path = paths::base_config_file();
+    let mut config = config::load(&path)?;
+    let removed = config
+        .get_mut(ALIAS_KEY)
+        .and_then(Value::as_mapping_mut)
+        .and_then(|table| table.remove(name));
+    if removed.is_none() {
+        bail!(MxError::not_found("Alias", name));
+    }
+    config::save(&path, &config)?;
+    println!("Removed alias: {}", name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn splits_quoted_words() {
+        assert_eq!(
+            split_words(r#"memory search "retry budget" --tags 'a b' x\ y"#).unwrap(),
+            vec!["memory", "search", "retry budget", "--tags", "a b", "x y"]
+        );
+        assert_eq!(split_words(r#"say """#).unwrap(), vec!["say", ""]);
+        assert!(split_words("broken 'quote").is_err());
+    }
+
+    #[test]
+    fn expands_aliases_in_command_position() {
+        let aliases = BTreeMap::from([
+            (
+                "morning".to_string(),
+                "mx zion wake --limit 40 --agent q".to_string(),
+            ),
+            ("m".to_string(), "morning".to_string()),
+            ("memory".to_string(), "stats".to_string()),
+            ("wake".to_string(), "stats".to_string()),
+            ("loop".to_string(), "loop".to_string()),
+        ]);
+        let builtins = crate::builtin_commands();
+        let flags = global_flags(&crate::Cli::command());
+
+        assert_eq!(
+            expand_with(
+                args("mx --profile work m --days 14"),
+                &aliases,
+                &builtins,
+                &flags
+            )
+            .unwrap(),
+            args("mx --profile work zion wake --limit 40 --agent q --days 14")
+        );
+        // Built-ins are never shadowed, and only the command word is expanded
+        for line in ["mx memory search morning", "mx wake --limit 5"] {
+            assert_eq!(
+                expand_with(args(line), &aliases, &builtins, &flags).unwrap(),
+                args(line)
+            );
+        }
+        assert!(expand_with(args("mx loop"), &aliases, &builtins, &flags).is_err());
+    }
+
+    #[test]
+    fn skips_every_global_flag_before_the_alias() {
+        let cli = crate::Cli::command();
+        let flags = global_flags(&cli);
+        let aliases = BTreeMap::from([("st".to_string(), "memory stats".to_string())]);
+        let builtins = vec!["memory".to_string()];
+        let expand = |line: &str| expand_with(args(line), &aliases, &builtins, &flags).unwrap();
+
+        let mut globals = 0;
+        for arg in cli.get_arguments().filter(|a| a.is_global_set()) {
+            globals += 1;
+            let long = arg.get_long().unwrap();
+            let value = match flags.iter().find(|f| f.long.as_deref() == Some(long)) {
+                Some(f) if f.takes_value => {
+                    let value = arg
+                        .get_possible_values()
+                        .first()
+                        .map_or("work".to_string(), |v| v.get_name().to_string());
+                    assert_eq!(
+                        expand(&format!("mx --{}={} st", long, value)),
+                        args(&format!("mx --{}={} memory stats", long, value))
+                    );
+                    format!(" {}", value)
+                }
+                _ => String::new(),
+            };
+            assert_eq!(
+                expand(&format!("mx --{}{} st -x", long, value)),
+                args(&format!("mx --{}{} memory stats -x", long, value)),
+                "--{}",
+                long
+            );
+            if let Some(short) = arg.get_short() {
+                assert_eq!(
+                    expand(&format!("mx -{}{} st", short, value)),
+                    args(&format!("mx -{}{} memory stats", short, value))
+                );
+            }
+        }
+        assert_eq!(globals, flags.len());
+
+        assert_eq!(
+            expand("mx -v --utc --no-pager --color never --error-format=json --profile work st"),
+            args(
+                "mx -v --utc --no-pager --color never --error-format=json --profile work memory stats"
+            )
+        );
+        // A value isn't mistaken for the command, and unknown flags stop the search
+        assert_eq!(expand("mx --profile st"), args("mx --profile st"));
+        assert_eq!(expand("mx --utc=yes st"), args("mx --utc=yes st"));
+        assert_eq!(expand("mx --bogus st"), args("mx --bogus st"));
+    }
+}
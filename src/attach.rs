@@ -0,0 +1,313 @@
+//! Attachments: `mx memory attach`
+//!
+//! A file attached to an entry is stored once, by its SHA-256, in the same
+//! blob store ingest uses (`$MX_HOME/blobs/<sha256>`); the entry only carries
+//! the metadata (name, hash, media type, size). Attaching identical content
+//! twice, to one entry or several, shares the blob. `mx memory show` lists
+//! an entry's attachments and `--extract` writes one back out, checked
+//! against its hash on the way.
+
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::MxError;
+use crate::ingest;
+use crate::knowledge::{Attachment, KnowledgeEntry};
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Hash prefix shown in listings, and the shortest accepted for lookup
+const SHORT_HASH: usize = 12;
+
+#[derive(clap::Args)]
+pub struct AttachArgs {
+    /// Entry ID
+    pub id: String,
+
+    /// File to attach
+    #[arg(required_unless_present_any = ["extract", "remove"])]
+    pub file: Option<PathBuf>,
+
+    /// Attach under this name instead of the file's own
+    #[arg(long, conflicts_with_all = ["extract", "remove"])]
+    pub name: Option<String>,
+
+    /// Write out the attachment with this name (or hash prefix)
+    #[arg(long, conflicts_with_all = ["file", "remove"])]
+    pub extract: Option<String>,
+
+    /// Where to write an extracted attachment ("-" for stdout; default: its
+    /// name in the current directory)
+    #[arg(short, long, requires = "extract")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite the output file if it exists
+    #[arg(long, requires = "extract")]
+    pub force: bool,
+
+    /// Detach the attachment with this name (or hash prefix); the blob stays
+    #[arg(long, conflicts_with = "file")]
+    pub remove: Option<String>,
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: AttachArgs) -> Result<()> {
+    let id = crate::normalize_id(&args.id);
+    let Some(mut entry) = db.get(&id, ctx)? else {
+        bail!(MxError::not_found("Entry", id));
+    };
+
+    if let Some(ref key) = args.extract {
+        let attachment = find(&entry, key)?;
+        let path = extract(attachment, args.output.as_deref(), args.force)?;
+        if let Some(path) = path {
+            println!(
+                "Extracted {} ({} bytes) to {}",
+                attachment.name,
+                attachment.size,
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(ref key) = args.remove {
+        let hash = find(&entry, key)?.hash.clone();
+        let name = detach(&mut entry, &hash);
+        db.upsert_knowledge(&entry)?;
+        println!("Detached {} from {}", name, entry.id);
+        return Ok(());
+    }
+
+    let Some(file) = args.file else {
+        bail!("Nothing to attach: give a file, --extract or --remove");
+    };
+    let data =
+        std::fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let name = match args.name {
+        Some(name) => name,
+        None => file
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .with_context(|| format!("{} has no file name", file.display()))?,
+    };
+    let attachment = Attachment {
+        media_type: media_type(&name).map(str::to_string),
+        hash: ingest::store_blob(&data)?,
+        size: data.len() as u64,
+        added_at: chrono::Utc::now().to_rfc3339(),
+        name,
+    };
+    let short = short_hash(&attachment.hash).to_string();
+    let replaced = add(&mut entry, attachment.clone());
+    db.upsert_knowledge(&entry)?;
+    println!(
+        "{} {} to {} ({} bytes, sha256:{})",
+        if replaced { "Replaced" } else { "Attached" },
+        attachment.name,
+        entry.id,
+        attachment.size,
+        short
+    );
+    Ok(())
+}
+
+/// Add `attachment` to `entry`, replacing any attachment of the same name.
+/// True when one was replaced.
+fn add(entry: &mut KnowledgeEntry, attachment: Attachment) -> bool {
+    let before = entry.attachments.len();
+    entry.attachments.retain(|a| a.name != attachment.name);
+    let replaced = entry.attachments.len() != before;
+    entry.attachments.push(attachment);
+    entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    replaced
+}
+
+/// Drop the attachment with `hash` from `entry`, returning its name
+fn detach(entry: &mut KnowledgeEntry, hash: &str) -> String {
+    let name = entry
+        .attachments
+        .iter()
+        .find(|a| a.hash == hash)
+        .map(|a| a.name.clone())
+        .unwrap_or_default();
+    entry.attachments.retain(|a| a.hash != hash);
+    entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    name
+}
+
+/// The attachment named `key`, or else the one whose hash starts with it
+pub fn find<'a>(entry: &'a KnowledgeEntry, key: &str) -> Result<&'a Attachment> {
+    if let Some(found) = entry.attachments.iter().find(|a| a.name == key) {
+        return Ok(found);
+    }
+    let key = key.strip_prefix("sha256:").unwrap_or(key).to_lowercase();
+    let matching: Vec<&Attachment> = if key.len() >= SHORT_HASH {
+        entry
+            .attachments
+            .iter()
+            .filter(|a| a.hash.starts_with(&key))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    match matching.as_slice() {
+        [one] => Ok(one),
+        [] => bail!(MxError::not_found("Attachment", &key)),
+        _ => bail!("Hash prefix '{}' matches more than one attachment", key),
+    }
+}
+
+/// Write `attachment` to `output` (its name in the current directory when
+/// None, stdout for "-"), returning where it went
+fn extract(attachment: &Attachment, output: Option<&Path>, force: bool) -> Result<Option<PathBuf>> {
+    let source = ingest::blob_path(&attachment.hash);
+    let data = std::fs::read(&source).with_context(|| {
+        format!(
+            "Blob for {} is missing ({})",
+            attachment.name,
+            source.display()
+        )
+    })?;
+    if ingest::blob_hash(&data) != attachment.hash {
+        bail!(
+            "Blob for {} is corrupt: {} no longer matches its hash",
+            attachment.name,
+            source.display()
+        );
+    }
+
+    if output == Some(Path::new("-")) {
+        std::io::stdout().write_all(&data)?;
+        return Ok(None);
+    }
+    // Only the final component of the stored name, so a crafted name can't
+    // write outside the current directory
+    let path =
+        match output {
+            Some(path) => path.to_path_buf(),
+            None => PathBuf::from(Path::new(&attachment.name).file_name().with_context(|| {
+                format!("Can't extract to '{}': use --output", attachment.name)
+            })?),
+        };
+    if path.exists() && !force {
+        bail!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        );
+    }
+    std::fs::write(&path, &data).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(SHORT_HASH)]
+}
+
+/// Media type for common extensions; None when unrecognized
+fn media_type(name: &str) -> Option<&'static str> {
+    let extension = Path::new(name)
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "json" => "application/json",
+        "jsonl" => "application/jsonl",
+        "yaml" | "yml" => "application/yaml",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "txt" | "log" => "text/plain",
+        _ => return None,
+    })
+}
+
+/// Lines listing `entry`'s attachments for `mx memory show`
+pub fn describe(entry: &KnowledgeEntry) -> Vec<String> {
+    entry
+        .attachments
+        .iter()
+        .map(|a| {
+            format!(
+                "{} ({}, {} bytes) sha256:{}",
+                a.name,
+                a.media_type.as_deref().unwrap_or("unknown type"),
+                a.size,
+                short_hash(&a.hash)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(name: &str, data: &[u8]) -> Attachment {
+        Attachment {
+            name: name.to_string(),
+            hash: ingest::blob_hash(data),
+            media_type: media_type(name).map(str::to_string),
+            size: data.len() as u64,
+            added_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn attachments_persist_and_resolve_by_name_or_hash() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        let mut entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-att",
+            "category_id": "pattern",
+            "title": "Diagram",
+        }))
+        .unwrap();
+
+        assert!(!add(&mut entry, attachment("flow.png", b"v1")));
+        add(&mut entry, attachment("notes.txt", b"notes"));
+        assert!(add(&mut entry, attachment("flow.png", b"v2")));
+        db.upsert_knowledge(&entry).unwrap();
+
+        let stored = db.get("kn-att", &ctx).unwrap().unwrap();
+        assert_eq!(stored.attachments.len(), 2);
+        let flow = find(&stored, "flow.png").unwrap();
+        assert_eq!(flow, &attachment("flow.png", b"v2"));
+        assert_eq!(flow.media_type.as_deref(), Some("image/png"));
+
+        let notes_hash = ingest::blob_hash(b"notes");
+        let prefix = format!("sha256:{}", &notes_hash[..SHORT_HASH]);
+        assert_eq!(find(&stored, &prefix).unwrap().name, "notes.txt");
+        assert!(find(&stored, &notes_hash[..4]).is_err());
+        assert!(find(&stored, "missing.bin").is_err());
+        assert_eq!(
+            describe(&stored)[0],
+            format!(
+                "notes.txt (text/plain, 5 bytes) sha256:{}",
+                short_hash(&notes_hash)
+            )
+        );
+
+        let mut stored = stored;
+        assert_eq!(detach(&mut stored, &notes_hash), "notes.txt");
+        db.upsert_knowledge(&stored).unwrap();
+        let names: Vec<String> = db
+            .get("kn-att", &ctx)
+            .unwrap()
+            .unwrap()
+            .attachments
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, ["flow.png"]);
+    }
+}
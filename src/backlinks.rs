@@ -0,0 +1,465 @@
+//! Backlinks: `references` relationships derived from entry bodies
+//!
+//! A body that names another entry, by id (`kn-1a2b3c4d`), by wiki link
+//! (`[[Title]]`) or by its exact title in running text, gets a `references`
+//! link to it when the entry is added or changed, and across the whole store
+//! on `mx memory rebuild`, `import` and `backlinks`. Code spans and fenced
+//! blocks are skipped, and so are one-word titles in running text, which
+//! match too much by accident.
+//!
+//! The links follow the body: when a mention is edited away its link goes
+//! too, so `references` is not for linking by hand (use `related`). Links
+//! respect visibility the way anchors do: a public entry only references
+//! public ones, a private one also its owner's private entries.
+//!
+//! Links are made without asking unless `config.yaml` says otherwise:
+//!
+//! ```yaml
+//! backlinks:
+//!   mode: suggest   # create (default), suggest, or off
+//! ```
+//!
+//! In `suggest` mode the `relate`/`unrelate` commands are printed instead.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::knowledge::KnowledgeEntry;
+use crate::links::{self, Target};
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::{config, paths};
+
+const BACKLINKS_KEY: &str = "backlinks";
+pub const REL_TYPE: &str = "references";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Create,
+    Suggest,
+    Off,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    #[serde(default)]
+    mode: Mode,
+}
+
+fn settings_from(config: &Mapping) -> Result<Settings> {
+    let Some(value) = config.get(BACKLINKS_KEY) else {
+        return Ok(Settings::default());
+    };
+    serde_yaml::from_value(value.clone()).context("Invalid 'backlinks' section in config.yaml")
+}
+
+/// How backlinks are kept, from `config.yaml`
+pub fn mode() -> Result<Mode> {
+    Ok(settings_from(&config::load(&paths::config_file())?)?.mode)
+}
+
+#[derive(clap::Args)]
+pub struct BacklinksArgs {
+    /// Only show the links that would change
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// One link to add or remove
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Change {
+    pub from: String,
+    pub to: String,
+    pub added: bool,
+}
+
+impl Change {
+    /// The command that makes this change by hand
+    fn command(&self) -> String {
+        format!(
+            "mx memory {} {} {} --type {}",
+            if self.added { "relate" } else { "unrelate" },
+            self.from,
+            self.to,
+            REL_TYPE
+        )
+    }
+}
+
+struct Candidate {
+    title: String,
+    visibility: String,
+    owner: Option<String>,
+}
+
+/// The entries a body can reference
+pub struct Catalog {
+    entries: HashMap<String, Candidate>,
+    /// lowercased title -> id, for wiki links
+    titles: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn new(entries: &[KnowledgeEntry]) -> Self {
+        Self {
+            entries: entries
+                .iter()
+                .map(|e| {
+                    let candidate = Candidate {
+                        title: e.title.trim().to_string(),
+                        visibility: e.visibility.clone(),
+                        owner: e.owner.clone(),
+                    };
+                    (e.id.clone(), candidate)
+                })
+                .collect(),
+            titles: entries
+                .iter()
+                .map(|e| (e.title.trim().to_lowercase(), e.id.clone()))
+                .collect(),
+        }
+    }
+
+    fn may_reference(&self, from: &KnowledgeEntry, to: &str) -> bool {
+        let Some(to) = self.entries.get(to) else {
+            return false;
+        };
+        to.visibility == "public"
+            || (from.visibility == "private"
+                && to.visibility == "private"
+                && to.owner == from.owner)
+    }
+
+    /// The entry a reference names, if it's an id or a wiki link
+    fn resolve(&self, target: Target) -> Option<String> {
+        match target {
+            Target::Id(id) => Some(id),
+            Target::Wiki { target, .. } if target.starts_with("kn-") => Some(target),
+            Target::Wiki { target, .. } => self.titles.get(&target.to_lowercase()).cloned(),
+            Target::File(_) => None,
+        }
+    }
+
+    fn titled(&self, id: &str) -> Option<&str> {
+        let title = self.entries.get(id)?.title.as_str();
+        (title.split_whitespace().count() > 1).then_some(title)
+    }
+
+    /// Ids of the entries `entry`'s body mentions
+    pub fn mentions(&self, entry: &KnowledgeEntry) -> BTreeSet<String> {
+        let Some(body) = entry.body.as_deref() else {
+            return BTreeSet::new();
+        };
+        let mut found: BTreeSet<String> = links::references(body)
+            .into_iter()
+            .filter_map(|r| self.resolve(r.target))
+            .collect();
+        let text = links::mask_code(body);
+        for id in self.entries.keys() {
+            if self
+                .titled(id)
+                .is_some_and(|title| mentions_title(&text, title))
+            {
+                found.insert(id.clone());
+            }
+        }
+
+        found.remove(&entry.id);
+        found.retain(|id| self.may_reference(entry, id));
+        found
+    }
+
+    /// Whether `entry`'s body mentions `id`: [`Self::mentions`] for one target
+    fn mentions_one(&self, entry: &KnowledgeEntry, id: &str) -> bool {
+        let Some(body) = entry.body.as_deref() else {
+            return false;
+        };
+        if entry.id == id || !self.may_reference(entry, id) {
+            return false;
+        }
+        links::references(body)
+            .into_iter()
+            .any(|r| self.resolve(r.target).as_deref() == Some(id))
+            || self
+                .titled(id)
+                .is_some_and(|title| mentions_title(&links::mask_code(body), title))
+    }
+}
+
+/// Whether `title` appears in `text` as whole words
+fn mentions_title(text: &str, title: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    text.match_indices(title).any(|(start, _)| {
+        let end = start + title.len();
+        !text[..start].chars().next_back().is_some_and(is_word)
+            && !text[end..].chars().next().is_some_and(is_word)
+    })
+}
+
+/// The links `entry` should gain and lose. Links to entries outside the
+/// catalog are left alone, since the caller can't see whether they're
+/// still mentioned.
+pub fn plan(
+    db: &dyn KnowledgeStore,
+    catalog: &Catalog,
+    entry: &KnowledgeEntry,
+) -> Result<Vec<Change>> {
+    let wanted = catalog.mentions(entry);
+    let present: BTreeSet<String> = db
+        .list_relationships_for_entry(&entry.id)?
+        .into_iter()
+        .filter(|r| r.relationship_type == REL_TYPE && r.from_entry_id == entry.id)
+        .map(|r| r.to_entry_id)
+        .collect();
+
+    let added = wanted.difference(&present).map(|to| Change {
+        from: entry.id.clone(),
+        to: to.clone(),
+        added: true,
+    });
+    let removed = present
+        .difference(&wanted)
+        .filter(|to| catalog.entries.contains_key(*to))
+        .map(|to| Change {
+            from: entry.id.clone(),
+            to: to.clone(),
+            added: false,
+        });
+    Ok(added.chain(removed).collect())
+}
+
+/// The links other entries should gain and lose to `entry`, say after it
+/// was added or renamed
+fn plan_incoming(
+    db: &dyn KnowledgeStore,
+    catalog: &Catalog,
+    entries: &[KnowledgeEntry],
+    entry: &KnowledgeEntry,
+) -> Result<Vec<Change>> {
+    let present: BTreeSet<String> = db
+        .list_relationships_for_entry(&entry.id)?
+        .into_iter()
+        .filter(|r| r.relationship_type == REL_TYPE && r.to_entry_id == entry.id)
+        .map(|r| r.from_entry_id)
+        .collect();
+    Ok(entries
+        .iter()
+        .filter_map(|from| {
+            let wanted = catalog.mentions_one(from, &entry.id);
+            (wanted != present.contains(&from.id)).then(|| Change {
+                from: from.id.clone(),
+                to: entry.id.clone(),
+                added: wanted,
+            })
+        })
+        .collect())
+}
+
+pub fn apply(db: &dyn KnowledgeStore, changes: &[Change]) -> Result<()> {
+    for change in changes {
+        if change.added {
+            db.add_relationship(&change.from, &change.to, REL_TYPE)?;
+        } else {
+            db.remove_relationship(&change.from, &change.to, REL_TYPE)?;
+        }
+    }
+    Ok(())
+}
+
+fn suggest(changes: &[Change]) {
+    for change in changes {
+        eprintln!("Suggest: {}", change.command());
+    }
+}
+
+/// Bring the links from and to one entry in line with the bodies, after an
+/// add or update
+pub fn sync_entry(db: &dyn KnowledgeStore, ctx: &AgentContext, id: &str) -> Result<()> {
+    let mode = mode()?;
+    if mode == Mode::Off {
+        return Ok(());
+    }
+    let Some(entry) = db.get(id, ctx)? else {
+        return Ok(());
+    };
+    let entries = db.list_all(ctx)?;
+    let catalog = Catalog::new(&entries);
+    let mut changes = plan(db, &catalog, &entry)?;
+    changes.extend(plan_incoming(db, &catalog, &entries, &entry)?);
+    match mode {
+        Mode::Create => apply(db, &changes),
+        Mode::Suggest => {
+            suggest(&changes);
+            Ok(())
+        }
+        Mode::Off => Ok(()),
+    }
+}
+
+/// Every visible entry's backlinks, applied unless `dry_run`
+pub fn sync_all(db: &dyn KnowledgeStore, ctx: &AgentContext, dry_run: bool) -> Result<Vec<Change>> {
+    let entries = db.list_all(ctx)?;
+    let catalog = Catalog::new(&entries);
+    let mut changes = Vec::new();
+    for entry in &entries {
+        changes.extend(plan(db, &catalog, entry)?);
+    }
+    if !dry_run {
+        apply(db, &changes)?;
+    }
+    Ok(changes)
+}
+
+/// After `mx memory rebuild` or `import`: create the links, or print them
+/// in suggest mode
+pub fn after_bulk(db: &dyn KnowledgeStore, ctx: &AgentContext) -> Result<()> {
+    match mode()? {
+        Mode::Off => {}
+        Mode::Create => {
+            let changes = sync_all(db, ctx, false)?;
+            let added = changes.iter().filter(|c| c.added).count();
+            println!(
+                "Backlinks: {} added, {} removed",
+                added,
+                changes.len() - added
+            );
+        }
+        Mode::Suggest => suggest(&sync_all(db, ctx, true)?),
+    }
+    Ok(())
+}
+
+/// `mx memory backlinks`
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: BacklinksArgs) -> Result<()> {
+    let changes = sync_all(db, ctx, args.dry_run)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+    if changes.is_empty() {
+        println!("Backlinks are up to date");
+        return Ok(());
+    }
+    for change in &changes {
+        let sign = if change.added { "+" } else { "-" };
+        println!("{} {} -> {}", sign, change.from, change.to);
+    }
+    let added = changes.iter().filter(|c| c.added).count();
+    println!(
+        "{} {} added, {} removed",
+        if args.dry_run {
+            "Would have"
+        } else {
+            "Backlinks:"
+        },
+        added,
+        changes.len() - added
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, title: &str, body: &str, visibility: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": title,
+            "body": body,
+            "visibility": visibility,
+            "owner": "smith",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn mentions_by_id_wiki_link_and_title() {
+        let entries = [
+            entry(
+                "kn-0000000a",
+                "Source",
+                "See kn-0000000b, [[retry budget]] and Circuit Breaker Basics. \
+                 Not `kn-0000000e`, Cache nor Circuit Breaker Basicsx.",
+                "public",
+            ),
+            entry("kn-0000000b", "By Id", "", "public"),
+            entry("kn-0000000c", "Retry Budget", "", "public"),
+            entry("kn-0000000d", "Circuit Breaker Basics", "", "public"),
+            entry("kn-0000000e", "In Code", "", "public"),
+            entry("kn-0000000f", "Cache", "", "public"),
+        ];
+        let catalog = Catalog::new(&entries);
+        let found: Vec<String> = catalog.mentions(&entries[0]).into_iter().collect();
+        assert_eq!(found, ["kn-0000000b", "kn-0000000c", "kn-0000000d"]);
+    }
+
+    #[test]
+    fn public_entries_never_reference_private_ones() {
+        let entries = [
+            entry("kn-0000000a", "Public Note", "kn-0000000b", "public"),
+            entry("kn-0000000b", "Private Note", "kn-0000000a", "private"),
+        ];
+        let catalog = Catalog::new(&entries);
+        assert!(catalog.mentions(&entries[0]).is_empty());
+        assert_eq!(catalog.mentions(&entries[1]).len(), 1);
+    }
+
+    #[test]
+    fn links_follow_the_body() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        let mut source = entry("kn-0000000a", "Source", "Builds on kn-0000000b", "public");
+        db.upsert_knowledge(&source).unwrap();
+        db.upsert_knowledge(&entry("kn-0000000b", "Target One", "", "public"))
+            .unwrap();
+        db.upsert_knowledge(&entry("kn-0000000c", "Target Two", "", "public"))
+            .unwrap();
+        let targets = |db: &dyn KnowledgeStore| -> Vec<String> {
+            db.list_relationships_for_entry("kn-0000000a")
+                .unwrap()
+                .into_iter()
+                .filter(|r| r.relationship_type == REL_TYPE)
+                .map(|r| r.to_entry_id)
+                .collect()
+        };
+
+        let changes = sync_all(&db, &ctx, true).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(targets(&db).is_empty());
+
+        sync_all(&db, &ctx, false).unwrap();
+        assert_eq!(targets(&db), ["kn-0000000b"]);
+        assert!(sync_all(&db, &ctx, false).unwrap().is_empty());
+
+        source.body = Some("Now about Target Two instead".into());
+        db.upsert_knowledge(&source).unwrap();
+        let changes = sync_all(&db, &ctx, false).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(targets(&db), ["kn-0000000c"]);
+
+        let renamed = entry("kn-0000000c", "Target Renamed", "", "public");
+        db.upsert_knowledge(&renamed).unwrap();
+        let entries = db.list_all(&ctx).unwrap();
+        let changes = plan_incoming(&db, &Catalog::new(&entries), &entries, &renamed).unwrap();
+        assert_eq!(
+            changes,
+            [Change {
+                from: "kn-0000000a".into(),
+                to: "kn-0000000c".into(),
+                added: false,
+            }]
+        );
+    }
+}
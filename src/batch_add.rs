@@ -0,0 +1,464 @@
+//! Batch add: `mx memory add --manifest entries.yaml`
+//!
+//! A manifest is a YAML or JSON file listing entries, either as a top-level
+//! list or under `entries:`. Each takes the fields `mx memory add` does:
+//!
+//! ```yaml
+//! entries:
+//!   - key: backoff                 # optional, for relationships below
+//!     category: pattern
+//!     title: Retry with backoff
+//!     content: Double the delay after each failure.
+//!     tags: [networking, retry]
+//!     applicability: [rust]
+//!   - category: technique
+//!     title: Jitter
+//!     file: notes/jitter.md        # relative to the manifest
+//!     visibility: private
+//!     relationships:
+//!       - to: backoff              # a key, or an existing kn- id
+//!         type: extends            # default: related
+//! ```
+//!
+//! The batch is all-or-nothing: every entry is built and checked (category,
+//! resonance, category rules unless `--force`, no clash with an existing id,
+//! relationship targets and types) and reported before the first write, and
+//! if a write fails partway the entries already added are deleted again.
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::{category_rules, confidence, notify};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Link {
+    pub to: String,
+    #[serde(rename = "type", default = "default_rel_type")]
+    pub rel_type: String,
+}
+
+fn default_rel_type() -> String {
+    "related".to_string()
+}
+
+/// One entry in the manifest
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spec {
+    pub key: Option<String>,
+    pub category: String,
+    pub title: String,
+    pub content: Option<String>,
+    pub file: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub applicability: Vec<String>,
+    pub project: Option<String>,
+    pub domain: Option<String>,
+    pub visibility: Option<String>,
+    pub owner: Option<String>,
+    pub resonance: Option<i32>,
+    pub resonance_type: Option<String>,
+    pub confidence: Option<f64>,
+    #[serde(default)]
+    pub anchors: Vec<String>,
+    #[serde(default)]
+    pub wake_phrases: Vec<String>,
+    pub source_type: Option<String>,
+    pub entry_type: Option<String>,
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub relationships: Vec<Link>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    List(Vec<Spec>),
+    Wrapped { entries: Vec<Spec> },
+}
+
+/// Read a manifest, as JSON when the file is `.json` and YAML otherwise
+pub fn load(path: &Path) -> Result<Vec<Spec>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+    let manifest: Manifest = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::from_str(&text).map_err(anyhow::Error::from)
+    } else {
+        serde_yaml::from_str(&text).map_err(anyhow::Error::from)
+    }
+    .with_context(|| format!("Invalid manifest {}", path.display()))?;
+    Ok(match manifest {
+        Manifest::List(specs) | Manifest::Wrapped { entries: specs } => specs,
+    })
+}
+
+/// How one manifest entry fared
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    /// 1-based position in the manifest
+    pub index: usize,
+    pub title: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// The entries and links a manifest asks for, with any problems
+struct Batch {
+    entries: Vec<KnowledgeEntry>,
+    links: Vec<(String, String, String)>,
+    outcomes: Vec<Outcome>,
+}
+
+fn build_entry(spec: &Spec, base: &Path, agent_id: &str, now: &str) -> Result<KnowledgeEntry> {
+    let body = match (&spec.content, &spec.file) {
+        (Some(_), Some(_)) => bail!("give content or file, not both"),
+        (Some(text), None) => text.clone(),
+        (None, Some(file)) => {
+            let path = base.join(file);
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+        }
+        (None, None) => bail!("content or file is required"),
+    };
+    let visibility = spec.visibility.as_deref().unwrap_or("public");
+    if visibility != "public" && visibility != "private" {
+        bail!("visibility must be 'public' or 'private'");
+    }
+    let owner = match visibility {
+        "private" => Some(spec.owner.clone().unwrap_or_else(|| agent_id.to_string())),
+        _ => spec.owner.clone(),
+    };
+    if let Some(c) = spec.confidence {
+        confidence::parse(&c.to_string()).map_err(anyhow::Error::msg)?;
+    }
+
+    let path_hint = spec.domain.as_deref().unwrap_or(&spec.category);
+    Ok(KnowledgeEntry {
+        id: KnowledgeEntry::generate_id(path_hint, &spec.title),
+        category_id: spec.category.clone(),
+        title: spec.title.clone(),
+        body: Some(body),
+        summary: None,
+        applicability: spec.applicability.clone(),
+        source_project_id: spec.project.clone(),
+        source_agent_id: Some(agent_id.to_string()),
+        file_path: None,
+        tags: spec.tags.clone(),
+        created_at: Some(now.to_string()),
+        updated_at: Some(now.to_string()),
+        content_hash: Some(KnowledgeEntry::compute_hash(&spec.title)),
+        source_type_id: Some(spec.source_type.clone().unwrap_or("manual".into())),
+        entry_type_id: Some(spec.entry_type.clone().unwrap_or("primary".into())),
+        session_id: None,
+        ephemeral: false,
+        content_type_id: Some(spec.content_type.clone().unwrap_or("text".into())),
+        owner,
+        visibility: visibility.to_string(),
+        resonance: spec.resonance.unwrap_or(0),
+        resonance_type: spec.resonance_type.clone(),
+        last_activated: None,
+        activation_count: 0,
+        decay_rate: 0.0,
+        anchors: spec.anchors.clone(),
+        wake_phrases: spec.wake_phrases.clone(),
+        wake_order: None,
+        wake_phrase: None,
+        embedding: None,
+        embedding_model: None,
+        embedded_at: None,
+        format: "markdown".to_string(),
+        effective_resonance: None,
+        token_count: None,
+        namespace: None,
+        confidence: spec.confidence,
+        confirmed_at: spec.confidence.map(|_| now.to_string()),
+        archived_at: None,
+        attachments: Vec::new(),
+    })
+}
+
+/// Build and check every entry and link without writing anything
+fn prepare(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    specs: &[Spec],
+    base: &Path,
+    agent_id: &str,
+    force: bool,
+) -> Result<Batch> {
+    let categories: HashSet<String> = db.list_categories()?.into_iter().map(|c| c.id).collect();
+    let rel_types: HashSet<String> = db
+        .list_relationship_types()?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+    let rules = category_rules::Rules::load()?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut batch = Batch {
+        entries: Vec::new(),
+        links: Vec::new(),
+        outcomes: Vec::new(),
+    };
+    let mut keys: HashMap<&str, String> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for spec in specs {
+        let id = KnowledgeEntry::generate_id(
+            spec.domain.as_deref().unwrap_or(&spec.category),
+            &spec.title,
+        );
+        if let Some(key) = spec.key.as_deref() {
+            keys.insert(key, id);
+        }
+    }
+
+    for (i, spec) in specs.iter().enumerate() {
+        let mut problems = Vec::new();
+        if !categories.contains(&spec.category) {
+            problems.push(format!("unknown category '{}'", spec.category));
+        }
+        let entry = match build_entry(spec, base, agent_id, &now) {
+            Ok(entry) => entry,
+            Err(e) => {
+                problems.push(format!("{:#}", e));
+                batch.outcomes.push(Outcome {
+                    index: i + 1,
+                    title: spec.title.clone(),
+                    id: String::new(),
+                    problems,
+                });
+                continue;
+            }
+        };
+        if !force {
+            problems.extend(entry.problems());
+            if let Err(e) = rules.check(&entry) {
+                problems.push(format!("{:#}", e));
+            }
+        }
+        if !seen.insert(entry.id.clone()) {
+            problems.push("same category and title as an earlier entry".to_string());
+        } else if db.get(&entry.id, ctx)?.is_some() {
+            problems.push(format!(
+                "{} already exists (use mx memory update)",
+                entry.id
+            ));
+        }
+
+        for link in &spec.relationships {
+            let to = match keys.get(link.to.as_str()) {
+                Some(id) => id.clone(),
+                None => {
+                    let id = crate::normalize_id(&link.to);
+                    if db.get(&id, ctx)?.is_none() {
+                        problems.push(format!("relationship target '{}' not found", link.to));
+                        continue;
+                    }
+                    id
+                }
+            };
+            if !rel_types.contains(&link.rel_type) {
+                problems.push(format!("unknown relationship type '{}'", link.rel_type));
+            } else if to == entry.id {
+                problems.push("an entry can't be related to itself".to_string());
+            } else {
+                batch
+                    .links
+                    .push((entry.id.clone(), to, link.rel_type.clone()));
+            }
+        }
+
+        batch.outcomes.push(Outcome {
+            index: i + 1,
+            title: spec.title.clone(),
+            id: entry.id.clone(),
+            problems,
+        });
+        batch.entries.push(entry);
+    }
+    Ok(batch)
+}
+
+/// Write the batch, deleting what was already added if any write fails
+fn write_all(db: &dyn KnowledgeStore, ctx: &AgentContext, batch: &Batch) -> Result<()> {
+    let mut written: Vec<&str> = Vec::new();
+    let result = (|| -> Result<()> {
+        for entry in &batch.entries {
+            db.upsert_knowledge(entry)
+                .with_context(|| format!("Failed to add {}", entry.id))?;
+            written.push(&entry.id);
+        }
+        for (from, to, rel_type) in &batch.links {
+            db.add_relationship(from, to, rel_type)
+                .with_context(|| format!("Failed to relate {} -> {}", from, to))?;
+        }
+        Ok(())
+    })();
+
+    let Err(e) = result else {
+        return Ok(());
+    };
+    let unremoved: Vec<&str> = written
+        .iter()
+        .copied()
+        .filter(|id| !db.delete(id, ctx).unwrap_or(false))
+        .collect();
+    if !unremoved.is_empty() {
+        eprintln!(
+            "Warning: could not remove {}; delete them with mx memory delete",
+            unremoved.join(", ")
+        );
+    }
+    Err(e).with_context(|| {
+        format!(
+            "Batch add failed; {} entr{} rolled back",
+            written.len() - unremoved.len(),
+            if written.len() - unremoved.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            }
+        )
+    })
+}
+
+fn report(outcomes: &[Outcome], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(outcomes)?);
+        return Ok(());
+    }
+    for outcome in outcomes {
+        if outcome.problems.is_empty() {
+            println!("{} {}  {}", "✓".green(), outcome.id, outcome.title);
+        } else {
+            println!("{} #{} {}", "✗".red(), outcome.index, outcome.title);
+            for problem in &outcome.problems {
+                println!("    - {}", problem);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add every entry in the manifest at `path`, or none of them. Returns the
+/// new ids.
+pub fn run(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    path: &Path,
+    agent_id: &str,
+    force: bool,
+    json: bool,
+) -> Result<Vec<String>> {
+    let specs = load(path)?;
+    if specs.is_empty() {
+        bail!("Manifest {} lists no entries", path.display());
+    }
+    let base = path.parent().unwrap_or(Path::new("."));
+    let batch = prepare(db, ctx, &specs, base, agent_id, force)?;
+
+    let failed = batch
+        .outcomes
+        .iter()
+        .filter(|o| !o.problems.is_empty())
+        .count();
+    report(&batch.outcomes, json)?;
+    if failed > 0 {
+        bail!(
+            "{} of {} entries failed; nothing was added{}",
+            failed,
+            specs.len(),
+            if force {
+                ""
+            } else {
+                " (--force skips resonance and category rules)"
+            }
+        );
+    }
+
+    write_all(db, ctx, &batch)?;
+    for entry in &batch.entries {
+        notify::entry_added(entry);
+    }
+    if !json {
+        println!(
+            "Added {} entr{} and {} relationship{}",
+            batch.entries.len(),
+            if batch.entries.len() == 1 { "y" } else { "ies" },
+            batch.links.len(),
+            if batch.links.len() == 1 { "" } else { "s" }
+        );
+    }
+    Ok(batch.entries.iter().map(|e| e.id.clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(dir: &Path, yaml: &str) -> std::path::PathBuf {
+        let path = dir.join("entries.yaml");
+        std::fs::write(&path, yaml).unwrap();
+        path
+    }
+
+    #[test]
+    fn adds_entries_and_relationships_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("jitter.md"), "Randomize the delay").unwrap();
+        let path = manifest(
+            dir.path(),
+            "entries:\n\
+             \x20 - key: backoff\n\
+             \x20   category: pattern\n\
+             \x20   title: Retry with backoff\n\
+             \x20   content: Double the delay\n\
+             \x20   tags: [retry]\n\
+             \x20 - category: technique\n\
+             \x20   title: Jitter\n\
+             \x20   file: jitter.md\n\
+             \x20   relationships:\n\
+             \x20     - to: backoff\n\
+             \x20       type: extends\n",
+        );
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::for_agent("smith");
+
+        let ids = run(&db, &ctx, &path, "smith", false, true).unwrap();
+        assert_eq!(ids.len(), 2);
+        let jitter = db.get(&ids[1], &ctx).unwrap().unwrap();
+        assert_eq!(jitter.body.as_deref(), Some("Randomize the delay"));
+        let rels = db.list_relationships_for_entry(&ids[1]).unwrap();
+        assert_eq!(rels.len(), 1);
+        assert_eq!(rels[0].to_entry_id, ids[0]);
+        assert_eq!(rels[0].relationship_type, "extends");
+    }
+
+    #[test]
+    fn one_bad_entry_adds_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = manifest(
+            dir.path(),
+            "- category: pattern\n  title: Fine\n  content: ok\n\
+             - category: nowhere\n  title: Lost\n  content: ok\n  resonance_type: bogus\n",
+        );
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::for_agent("smith");
+
+        let batch = prepare(&db, &ctx, &load(&path).unwrap(), dir.path(), "smith", false).unwrap();
+        assert!(batch.outcomes[0].problems.is_empty());
+        assert_eq!(batch.outcomes[1].problems.len(), 2);
+
+        assert!(run(&db, &ctx, &path, "smith", false, true).is_err());
+        assert_eq!(db.count().unwrap(), 0);
+    }
+}
@@ -0,0 +1,305 @@
+//! Store benchmarks: `mx bench store`
+//!
+//! Generates synthetic knowledge entries in a throwaway embedded store and
+//! measures the latency of the operations the CLI leans on hardest: upsert,
+//! full-text search, list, list-by-category, and the wake cascade. Cold rows
+//! (first open, first cascade) are reported separately so the savings from
+//! connection pooling and statement caching are visible.
+//!
+//! In network mode (`MX_SURREAL_MODE=network`, or `mode: network` in the
+//! `surreal:` section of config.yaml) the benchmark runs against the configured
+//! server instead; `--network` is required to opt into that, and every
+//! synthetic entry is deleted afterwards.
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{self, AgentContext, KnowledgeFilter};
+
+const CATEGORIES: [&str; 4] = ["pattern", "technique", "insight", "gotcha"];
+const RESONANCE_TYPES: [&str; 4] = ["foundational", "transformative", "operational", "ephemeral"];
+const WORDS: [&str; 16] = [
+    "async",
+    "cascade",
+    "resonance",
+    "schema",
+    "surreal",
+    "bloom",
+    "anchor",
+    "codex",
+    "session",
+    "parser",
+    "graph",
+    "token",
+    "index",
+    "wake",
+    "stele",
+    "decay",
+];
+
+/// Benchmark configuration
+pub struct BenchOptions {
+    pub entries: usize,
+    pub queries: usize,
+    pub network: bool,
+    pub json: bool,
+}
+
+/// Latency summary for one operation
+#[derive(Debug, Clone, Serialize)]
+pub struct OpStats {
+    pub operation: String,
+    pub ops: usize,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl OpStats {
+    fn from_samples(operation: &str, samples: &mut [Duration]) -> Self {
+        samples.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let total: f64 = samples.iter().map(|d| ms(*d)).sum();
+        let pick = |q: f64| {
+            if samples.is_empty() {
+                0.0
+            } else {
+                let idx = ((samples.len() - 1) as f64 * q).round() as usize;
+                ms(samples[idx])
+            }
+        };
+        Self {
+            operation: operation.to_string(),
+            ops: samples.len(),
+            total_ms: total,
+            avg_ms: if samples.is_empty() {
+                0.0
+            } else {
+                total / samples.len() as f64
+            },
+            p50_ms: pick(0.50),
+            p95_ms: pick(0.95),
+        }
+    }
+}
+
+/// Run `mx bench store`
+pub fn bench_store(opts: BenchOptions, verbose: bool) -> Result<()> {
+    if opts.entries == 0 {
+        bail!("--entries must be at least 1");
+    }
+
+    let network = crate::surreal_db::SurrealConfig::from_env().is_network();
+    if network && !opts.network {
+        bail!(
+            "Network mode would benchmark against the live server. \
+             Pass --network to confirm (synthetic entries are deleted afterwards)."
+        );
+    }
+
+    let tmp = tempfile::tempdir()?;
+    let db_path = tmp.path().join("bench.surreal");
+    let open_started = Instant::now();
+    let db = store::create_store_with_verbose(&db_path, verbose)?;
+    let mut open_samples = vec![open_started.elapsed()];
+    let backend = if network {
+        "surrealdb (network)"
+    } else {
+        "surrealdb (embedded)"
+    };
+
+    let agent = "bench-agent";
+    let ctx = AgentContext::for_agent(agent);
+    let run_id = chrono::Utc::now().format("%H%M%S").to_string();
+    let entries: Vec<KnowledgeEntry> = (0..opts.entries)
+        .map(|i| synthetic_entry(&run_id, i, agent))
+        .collect();
+
+    if !opts.json {
+        println!(
+            "Benchmarking {} with {} entries, {} queries...",
+            backend, opts.entries, opts.queries
+        );
+    }
+
+    let mut results = Vec::new();
+
+    // The first open connects and applies the schema; later opens in the
+    // same process are served from the connection pool.
+    results.push(OpStats::from_samples("open (cold)", &mut open_samples));
+    let mut samples = Vec::with_capacity(opts.queries);
+    for _ in 0..opts.queries {
+        samples.push(time(|| store::create_store(&db_path).map(|_| ()))?);
+    }
+    results.push(OpStats::from_samples("open (pooled)", &mut samples));
+
+    let mut samples = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        samples.push(time(|| db.upsert_knowledge(entry))?);
+    }
+    results.push(OpStats::from_samples("upsert", &mut samples));
+
+    let filter = KnowledgeFilter::default();
+    let mut samples = Vec::with_capacity(opts.queries);
+    for i in 0..opts.queries {
+        let query = WORDS[i % WORDS.len()];
+        samples.push(time(|| db.search(query, &ctx, &filter).map(|_| ()))?);
+    }
+    results.push(OpStats::from_samples("search", &mut samples));
+
+    let mut samples = Vec::with_capacity(opts.queries);
+    for i in 0..opts.queries {
+        let category = CATEGORIES[i % CATEGORIES.len()];
+        samples.push(time(|| {
+            db.list_by_category(category, &ctx, &filter).map(|_| ())
+        })?);
+    }
+    results.push(OpStats::from_samples("list_by_category", &mut samples));
+
+    let list_runs = opts.queries.clamp(1, 5);
+    let mut samples = Vec::with_capacity(list_runs);
+    for _ in 0..list_runs {
+        samples.push(time(|| db.list_all(&ctx).map(|_| ()))?);
+    }
+    results.push(OpStats::from_samples("list_all", &mut samples));
+
+    // The first cascade also formats and caches its three statements
+    let mut samples = vec![time(|| db.wake_cascade(&ctx, 40, None, 14).map(|_| ()))?];
+    results.push(OpStats::from_samples("wake_cascade (cold)", &mut samples));
+    let mut samples = Vec::with_capacity(opts.queries);
+    for _ in 0..opts.queries {
+        samples.push(time(|| db.wake_cascade(&ctx, 40, None, 14).map(|_| ()))?);
+    }
+    results.push(OpStats::from_samples("wake_cascade", &mut samples));
+
+    if network {
+        for entry in &entries {
+            let _ = db.delete(&entry.id, &ctx);
+        }
+    }
+
+    if opts.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "backend": backend,
+                "entries": opts.entries,
+                "queries": opts.queries,
+                "results": results,
+            }))?
+        );
+    } else {
+        print_table(&results);
+    }
+
+    Ok(())
+}
+
+fn time(mut op: impl FnMut() -> Result<()>) -> Result<Duration> {
+    let start = Instant::now();
+    op()?;
+    Ok(start.elapsed())
+}
+
+fn print_table(results: &[OpStats]) {
+    println!();
+    println!(
+        "  {:<20} {:>7} {:>11} {:>9} {:>9} {:>9}",
+        "OPERATION", "OPS", "TOTAL ms", "AVG ms", "P50 ms", "P95 ms"
+    );
+    for r in results {
+        println!(
+            "  {:<20} {:>7} {:>11.1} {:>9.2} {:>9.2} {:>9.2}",
+            r.operation, r.ops, r.total_ms, r.avg_ms, r.p50_ms, r.p95_ms
+        );
+    }
+}
+
+/// Build a deterministic synthetic entry. Varies category, resonance, and
+/// body vocabulary so search and cascade queries have realistic selectivity.
+fn synthetic_entry(run_id: &str, i: usize, agent: &str) -> KnowledgeEntry {
+    let now = chrono::Utc::now().to_rfc3339();
+    let words: Vec<&str> = (0..24)
+        .map(|k| WORDS[(i * 7 + k * 3) % WORDS.len()])
+        .collect();
+    let body = format!("{}\n\nSynthetic benchmark entry {}.", words.join(" "), i);
+
+    KnowledgeEntry {
+        id: format!("kn-bench{}{:06}", run_id, i),
+        category_id: CATEGORIES[i % CATEGORIES.len()].to_string(),
+        title: format!("Bench {} {} {}", words[0], words[1], i),
+        content_hash: Some(KnowledgeEntry::compute_hash(&body)),
+        body: Some(body),
+        summary: None,
+        applicability: vec![],
+        source_project_id: None,
+        source_agent_id: Some(agent.to_string()),
+        file_path: None,
+        tags: vec![words[2].to_string(), words[3].to_string()],
+        created_at: Some(now.clone()),
+        updated_at: Some(now.clone()),
+        source_type_id: Some("manual".to_string()),
+        entry_type_id: Some("primary".to_string()),
+        session_id: None,
+        ephemeral: false,
+        content_type_id: Some("text".to_string()),
+        owner: None,
+        visibility: "public".to_string(),
+        resonance: (i % 10) as i32 + 1,
+        resonance_type: Some(RESONANCE_TYPES[i % RESONANCE_TYPES.len()].to_string()),
+        last_activated: Some(now),
+        activation_count: 0,
+        decay_rate: 0.0,
+        anchors: vec![],
+        wake_phrases: vec![],
+        wake_order: None,
+        wake_phrase: None,
+        embedding: None,
+        embedding_model: None,
+        embedded_at: None,
+        format: "markdown".to_string(),
+        effective_resonance: None,
+        token_count: None,
+        namespace: None,
+        confidence: None,
+        confirmed_at: None,
+        archived_at: None,
+        attachments: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_from_samples() {
+        let mut samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        let stats = OpStats::from_samples("op", &mut samples);
+        assert_eq!(stats.ops, 100);
+        assert!((stats.avg_ms - 50.5).abs() < 0.01);
+        assert!((stats.p50_ms - 51.0).abs() < 1.01);
+        assert!((stats.p95_ms - 95.0).abs() < 1.01);
+    }
+
+    #[test]
+    fn empty_samples_are_zero() {
+        let stats = OpStats::from_samples("op", &mut []);
+        assert_eq!(stats.ops, 0);
+        assert_eq!(stats.avg_ms, 0.0);
+        assert_eq!(stats.p95_ms, 0.0);
+    }
+
+    #[test]
+    fn synthetic_entries_are_unique_and_varied() {
+        let a = synthetic_entry("r", 0, "agent");
+        let b = synthetic_entry("r", 1, "agent");
+        assert_ne!(a.id, b.id);
+        assert_ne!(a.category_id, b.category_id);
+        assert_ne!(a.resonance, b.resonance);
+        assert!(a.id.starts_with("kn-"));
+    }
+}
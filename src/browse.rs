@@ -0,0 +1,674 @@
+//! Interactive browser: `mx memory browse`
+//!
+//! Categories down the left, the entries in the selected one in the middle
+//! (most recently updated first) and the selected entry rendered as
+//! markdown on the right. `/` filters the entries as you type, matching
+//! id, title, tags and body.
+//!
+//! Keys: Tab or h/l switches pane, j/k or the arrows move, PgUp/PgDn scroll
+//! the preview, q quits. On an entry:
+//!
+//! - `t` edits its tags (comma separated)
+//! - `r` relates it to another entry: `<id> [type]`, type `related` by default
+//! - `d` deletes it, to the trash when `trash.soft_delete` is set
+
+use anyhow::{Result, bail};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::{self, IsTerminal};
+use termimad::minimad::{self, Composite, CompositeStyle, Compound};
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::{identity, relate, trash};
+
+/// Lines PgUp/PgDn scroll the preview by
+const PAGE: u16 = 10;
+/// Width of the category pane
+const SIDEBAR_WIDTH: u16 = 24;
+
+#[derive(clap::Args)]
+pub struct BrowseArgs {
+    /// Start in this category
+    #[arg(long)]
+    pub category: Option<String>,
+}
+
+/// An entry's body as styled lines: headings bold, code dimmed, list
+/// bullets and quote bars drawn in
+fn markdown(body: &str) -> Vec<Line<'static>> {
+    let body = star_bullets(body);
+    minimad::parse_text(&body, minimad::Options::default())
+        .lines
+        .into_iter()
+        .filter_map(|line| match line {
+            minimad::Line::Normal(composite) => Some(composite_line(&composite)),
+            minimad::Line::TableRow(row) => {
+                let cells: Vec<String> = row
+                    .cells
+                    .iter()
+                    .map(|cell| cell.compounds.iter().map(|c| c.src).collect())
+                    .collect();
+                Some(Line::from(cells.join(" │ ")))
+            }
+            minimad::Line::TableRule(_) | minimad::Line::HorizontalRule => {
+                Some(Line::from("─".repeat(SIDEBAR_WIDTH as usize)).dim())
+            }
+            minimad::Line::CodeFence(_) => None,
+        })
+        .collect()
+}
+
+/// `body` with `-` and `+` list bullets written as `*`, the only bullet
+/// minimad knows; code blocks are left alone
+fn star_bullets(body: &str) -> String {
+    let mut in_fence = false;
+    let lines: Vec<String> = body
+        .lines()
+        .map(|line| {
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            let rest = &line[indent..];
+            if rest.starts_with("```") {
+                in_fence = !in_fence;
+            } else if !in_fence
+                && indent < 4
+                && let Some(item) = rest.strip_prefix("- ").or(rest.strip_prefix("+ "))
+            {
+                return format!("{}* {}", &line[..indent], item);
+            }
+            line.to_string()
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn compound_span(compound: &Compound) -> Span<'static> {
+    let mut style = Style::new();
+    if compound.bold {
+        style = style.bold();
+    }
+    if compound.italic {
+        style = style.italic();
+    }
+    if compound.strikeout {
+        style = style.crossed_out();
+    }
+    if compound.code {
+        style = style.yellow();
+    }
+    Span::styled(compound.src.to_string(), style)
+}
+
+fn composite_line(composite: &Composite) -> Line<'static> {
+    let mut spans: Vec<Span> = match composite.style {
+        CompositeStyle::ListItem(depth) => {
+            vec![Span::raw(format!("{}• ", "  ".repeat(usize::from(depth))))]
+        }
+        CompositeStyle::Quote => vec!["▍ ".dim()],
+        _ => Vec::new(),
+    };
+    spans.extend(composite.compounds.iter().map(compound_span));
+    let line = Line::from(spans);
+    match composite.style {
+        CompositeStyle::Header(1) => line.bold().underlined(),
+        CompositeStyle::Header(_) => line.bold(),
+        CompositeStyle::Code => line.dim(),
+        _ => line,
+    }
+}
+
+/// Whether `entry` matches a lowercased filter query
+fn matches(entry: &KnowledgeEntry, query: &str) -> bool {
+    query.is_empty()
+        || entry.id.to_lowercase().contains(query)
+        || entry.title.to_lowercase().contains(query)
+        || entry.tags.iter().any(|t| t.to_lowercase().contains(query))
+        || entry
+            .body
+            .as_deref()
+            .is_some_and(|b| b.to_lowercase().contains(query))
+}
+
+/// Tags typed as a comma-separated list
+fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Focus {
+    Categories,
+    Entries,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Prompt {
+    Tags,
+    Relate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Search,
+    Prompt(Prompt, String),
+    ConfirmDelete,
+}
+
+struct App<'a> {
+    db: &'a dyn KnowledgeStore,
+    ctx: &'a AgentContext,
+    entries: Vec<KnowledgeEntry>,
+    /// Category ids with their entry counts; the sidebar puts "all" first
+    categories: Vec<(String, usize)>,
+    category: ListState,
+    /// Indices into `entries` the list shows
+    visible: Vec<usize>,
+    list: ListState,
+    query: String,
+    focus: Focus,
+    mode: Mode,
+    scroll: u16,
+    status: String,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(db: &'a dyn KnowledgeStore, ctx: &'a AgentContext) -> Result<Self> {
+        let mut app = App {
+            db,
+            ctx,
+            entries: Vec::new(),
+            categories: Vec::new(),
+            category: ListState::default().with_selected(Some(0)),
+            visible: Vec::new(),
+            list: ListState::default(),
+            query: String::new(),
+            focus: Focus::Entries,
+            mode: Mode::Normal,
+            scroll: 0,
+            status: String::new(),
+            quit: false,
+        };
+        app.reload()?;
+        Ok(app)
+    }
+
+    /// Re-read entries and categories, keeping the selection where it can
+    fn reload(&mut self) -> Result<()> {
+        let mut entries = self.db.list_all(self.ctx)?;
+        entries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        self.entries = entries;
+        self.categories = self
+            .db
+            .list_categories()?
+            .into_iter()
+            .map(|c| {
+                let count = self
+                    .entries
+                    .iter()
+                    .filter(|e| e.category_id == c.id)
+                    .count();
+                (c.id, count)
+            })
+            .collect();
+        if self.category.selected().unwrap_or(0) > self.categories.len() {
+            self.category.select(Some(0));
+        }
+        self.refilter();
+        Ok(())
+    }
+
+    fn selected_category(&self) -> Option<&str> {
+        match self.category.selected() {
+            Some(i) if i > 0 => self.categories.get(i - 1).map(|(id, _)| id.as_str()),
+            _ => None,
+        }
+    }
+
+    fn select_category(&mut self, id: &str) -> Result<()> {
+        let Some(i) = self.categories.iter().position(|(c, _)| c == id) else {
+            bail!(MxError::not_found("Category", id));
+        };
+        self.category.select(Some(i + 1));
+        self.refilter();
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&KnowledgeEntry> {
+        let i = *self.visible.get(self.list.selected()?)?;
+        self.entries.get(i)
+    }
+
+    /// Recompute the visible entries, staying on the same entry if it is
+    /// still shown
+    fn refilter(&mut self) {
+        let current = self.selected().map(|e| e.id.clone());
+        let category = self.selected_category().map(str::to_string);
+        let query = self.query.to_lowercase();
+        self.visible = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| category.as_ref().is_none_or(|c| *c == e.category_id))
+            .filter(|(_, e)| matches(e, &query))
+            .map(|(i, _)| i)
+            .collect();
+        let position = current
+            .and_then(|id| self.visible.iter().position(|&i| self.entries[i].id == id))
+            .or((!self.visible.is_empty()).then_some(0));
+        if position != self.list.selected() {
+            self.scroll = 0;
+        }
+        self.list.select(position);
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Focus::Categories => (&mut self.category, self.categories.len() + 1),
+            Focus::Entries => (&mut self.list, self.visible.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let at = state.selected().unwrap_or(0) as isize;
+        state.select(Some((at + delta).clamp(0, len as isize - 1) as usize));
+        self.scroll = 0;
+        if self.focus == Focus::Categories {
+            self.refilter();
+        }
+    }
+
+    fn handle(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal(key),
+            Mode::Search => match key.code {
+                KeyCode::Enter => {}
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.refilter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                    self.mode = Mode::Search;
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.refilter();
+                    self.mode = Mode::Search;
+                }
+                _ => self.mode = Mode::Search,
+            },
+            Mode::Prompt(prompt, mut input) => match key.code {
+                KeyCode::Enter => {
+                    let done = match prompt {
+                        Prompt::Tags => self.set_tags(&input),
+                        Prompt::Relate => self.relate(&input),
+                    };
+                    self.finish(done);
+                }
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.mode = Mode::Prompt(prompt, input);
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::Prompt(prompt, input);
+                }
+                _ => self.mode = Mode::Prompt(prompt, input),
+            },
+            Mode::ConfirmDelete => {
+                if key.code == KeyCode::Char('y') {
+                    let done = self.delete();
+                    self.finish(done);
+                }
+            }
+        }
+    }
+
+    fn handle_normal(&mut self, key: KeyEvent) {
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.refilter();
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Categories => Focus::Entries,
+                    Focus::Entries => Focus::Categories,
+                };
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Categories,
+            KeyCode::Right | KeyCode::Char('l') => self.focus = Focus::Entries,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX / 2),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(PAGE),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE),
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('t') => {
+                if let Some(entry) = self.selected() {
+                    self.mode = Mode::Prompt(Prompt::Tags, entry.tags.join(", "));
+                }
+            }
+            KeyCode::Char('r') if self.selected().is_some() => {
+                self.mode = Mode::Prompt(Prompt::Relate, String::new());
+            }
+            KeyCode::Char('d') if self.selected().is_some() => self.mode = Mode::ConfirmDelete,
+            _ => {}
+        }
+    }
+
+    /// Show what an action did, reloading after a change
+    fn finish(&mut self, done: Result<String>) {
+        self.status = match done.and_then(|message| self.reload().map(|_| message)) {
+            Ok(message) => message,
+            Err(e) => format!("Error: {:#}", e),
+        };
+    }
+
+    fn set_tags(&mut self, input: &str) -> Result<String> {
+        let Some(mut entry) = self.selected().cloned() else {
+            bail!("No entry selected");
+        };
+        entry.tags = parse_tags(input);
+        entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.db.upsert_knowledge(&entry)?;
+        Ok(format!("Tagged {}: {}", entry.id, entry.tags.join(", ")))
+    }
+
+    fn relate(&mut self, input: &str) -> Result<String> {
+        let Some(from) = self.selected().map(|e| e.id.clone()) else {
+            bail!("No entry selected");
+        };
+        let mut words = input.split_whitespace();
+        let Some(to) = words.next() else {
+            bail!("Type the ID of the entry to relate to");
+        };
+        let rel_type = words.next().unwrap_or("related");
+        relate::link(self.db, self.ctx, &from, to, rel_type)
+    }
+
+    fn delete(&mut self) -> Result<String> {
+        let Some(entry) = self.selected().cloned() else {
+            bail!("No entry selected");
+        };
+        if trash::soft_by_default()? {
+            trash::archive(self.db, self.ctx, &entry.id)?;
+            return Ok(format!("Moved {} to the trash", entry.id));
+        }
+        if identity::is_sealed(&entry) {
+            bail!(
+                "Entry {} is sealed in the identity namespace; release it first",
+                entry.id
+            );
+        }
+        let _ = self
+            .db
+            .backup_content(&entry, "delete", self.ctx.agent_id.as_deref());
+        if !self.db.delete(&entry.id, self.ctx)? {
+            bail!(MxError::not_found("Entry", &entry.id));
+        }
+        Ok(format!("Deleted {}", entry.id))
+    }
+
+    fn pane(&self, title: String, focus: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == focus {
+            block.border_style(Style::new().cyan())
+        } else {
+            block
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [sidebar, list, preview] = Layout::horizontal([
+            Constraint::Length(SIDEBAR_WIDTH),
+            Constraint::Percentage(35),
+            Constraint::Fill(1),
+        ])
+        .areas(main);
+        let highlight = Style::new().reversed();
+
+        let categories: Vec<ListItem> = std::iter::once(("all", self.entries.len()))
+            .chain(self.categories.iter().map(|(id, n)| (id.as_str(), *n)))
+            .map(|(id, n)| ListItem::new(Line::from(vec![id.into(), format!(" {}", n).dim()])))
+            .collect();
+        frame.render_stateful_widget(
+            List::new(categories)
+                .block(self.pane("Categories".to_string(), Focus::Categories))
+                .highlight_style(highlight),
+            sidebar,
+            &mut self.category,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                let mut spans = vec![Span::raw(entry.title.clone())];
+                if !entry.tags.is_empty() {
+                    spans.push(format!("  {}", entry.tags.join(", ")).dim());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let mut title = format!("Entries {}/{}", self.visible.len(), self.entries.len());
+        if !self.query.is_empty() {
+            title.push_str(&format!(" /{}", self.query));
+        }
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(self.pane(title, Focus::Entries))
+                .highlight_style(highlight),
+            list,
+            &mut self.list,
+        );
+
+        let (title, text) = match self.selected() {
+            Some(entry) => {
+                let mut meta = vec![
+                    entry.id.clone().cyan(),
+                    format!("  {}", entry.category_id).dim(),
+                ];
+                if !entry.tags.is_empty() {
+                    meta.push(format!("  #{}", entry.tags.join(" #")).dim());
+                }
+                let mut lines = vec![Line::from(meta), Line::default()];
+                lines.extend(markdown(entry.body.as_deref().unwrap_or("")));
+                (entry.title.clone(), Text::from(lines))
+            }
+            None => ("Preview".to_string(), Text::from("No entries".dim())),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(title))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            preview,
+        );
+
+        let line = match &self.mode {
+            Mode::Search => Line::from(format!("/{}", self.query)),
+            Mode::Prompt(Prompt::Tags, input) => Line::from(vec![
+                "Tags (comma separated): ".bold(),
+                input.clone().into(),
+            ]),
+            Mode::Prompt(Prompt::Relate, input) => {
+                Line::from(vec!["Relate to <id> [type]: ".bold(), input.clone().into()])
+            }
+            Mode::ConfirmDelete => Line::from(format!(
+                "Delete {}? (y/n)",
+                self.selected().map_or("", |e| e.id.as_str())
+            ))
+            .bold(),
+            Mode::Normal if !self.status.is_empty() => Line::from(self.status.clone()),
+            Mode::Normal => {
+                "/ search  t tags  r relate  d delete  Tab pane  PgUp/PgDn scroll  q quit"
+                    .dim()
+                    .into()
+            }
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+}
+
+fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            app.handle(key);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: BrowseArgs) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        bail!("browse requires an interactive terminal (use mx memory list or search instead)");
+    }
+    let mut app = App::new(db, ctx)?;
+    if let Some(category) = args.category {
+        app.select_category(&category)?;
+    }
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::style::Modifier;
+
+    fn entry(id: &str, category: &str, updated: &str, body: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": category,
+            "title": format!("Entry {}", id),
+            "body": body,
+            "tags": ["rust"],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": updated,
+        }))
+        .unwrap()
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x08' => KeyCode::Backspace,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            app.handle(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn markdown_styles_headings_lists_and_code() {
+        let lines = markdown(
+            "# Retry\n\n- **always** back off\n  + jitter\n```\n- sleep(1)\n```\n> quoted",
+        );
+        let text: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "Retry",
+                "",
+                "• always back off",
+                "    • jitter",
+                "- sleep(1)",
+                "▍ quoted"
+            ]
+        );
+        let bold = |style: Style| style.add_modifier.contains(Modifier::BOLD);
+        assert!(bold(lines[0].style));
+        assert!(bold(lines[2].spans[1].style));
+        assert!(lines[4].style.add_modifier.contains(Modifier::DIM));
+        assert_eq!(parse_tags(" a, b,,a ,c"), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn filters_by_category_and_query_then_edits() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        db.upsert_knowledge(&entry("kn-a", "pattern", "2026-01-01T00:00:00Z", "backoff"))
+            .unwrap();
+        db.upsert_knowledge(&entry("kn-b", "pattern", "2026-02-01T00:00:00Z", "jitter"))
+            .unwrap();
+        db.upsert_knowledge(&entry("kn-c", "technique", "2026-03-01T00:00:00Z", "x"))
+            .unwrap();
+
+        let mut app = App::new(&db, &ctx).unwrap();
+        let ids = |app: &App| -> Vec<String> {
+            app.visible
+                .iter()
+                .map(|&i| app.entries[i].id.clone())
+                .collect()
+        };
+        assert_eq!(ids(&app), ["kn-c", "kn-b", "kn-a"]);
+        app.select_category("pattern").unwrap();
+        assert_eq!(ids(&app), ["kn-b", "kn-a"]);
+        assert!(app.select_category("nope").is_err());
+
+        press(&mut app, "/BACK\n");
+        assert_eq!(ids(&app), ["kn-a"]);
+        assert_eq!(app.mode, Mode::Normal);
+
+        // Replace the tags, then relate to another entry
+        press(&mut app, "t\x08\x08\x08\x08retry, net\n");
+        assert_eq!(
+            db.get("kn-a", &ctx).unwrap().unwrap().tags,
+            ["net", "retry"]
+        );
+        press(&mut app, "rkn-c\n");
+        assert_eq!(app.status, "Related kn-a <-> kn-c (related)");
+        assert_eq!(db.list_relationships_for_entry("kn-a").unwrap().len(), 1);
+        press(&mut app, "rkn-zz\n");
+        assert!(app.status.starts_with("Error:"));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Entries 1/3 /BACK"));
+        assert!(screen.contains("#net #retry"));
+    }
+}
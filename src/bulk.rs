@@ -0,0 +1,413 @@
+//! Bulk edits: `mx memory bulk`
+//!
+//! Applies the same field changes to every entry matching a query, e.g.
+//! `--query 'tag:old-project' --set visibility=private --add-tag archived`.
+//!
+//! Query terms are space-separated and all must match: `tag:`, `category:`,
+//! `project:`, `visibility:`, `owner:`, and `type:` (resonance type) compare
+//! exactly; anything else is a keyword matched against title, body, and tags.
+//!
+//! The batch is all-or-nothing: every changed entry is validated before the
+//! first write, and if a write fails partway the entries already written are
+//! restored. Batches larger than the confirmation threshold are asked about
+//! first (see [`crate::confirm`]).
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::confirm;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// One query term
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Tag(String),
+    Category(String),
+    Project(String),
+    Visibility(String),
+    Owner(String),
+    ResonanceType(String),
+    Keyword(String),
+}
+
+/// Parsed `--query`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query(Vec<Term>);
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self> {
+        let terms: Vec<Term> = query
+            .split_whitespace()
+            .map(|word| match word.split_once(':') {
+                Some(("tag", v)) => Term::Tag(v.to_string()),
+                Some(("category", v)) => Term::Category(v.to_string()),
+                Some(("project", v)) => Term::Project(v.to_string()),
+                Some(("visibility", v)) => Term::Visibility(v.to_string()),
+                Some(("owner", v)) => Term::Owner(v.to_string()),
+                Some(("type", v)) => Term::ResonanceType(v.to_string()),
+                _ => Term::Keyword(word.to_lowercase()),
+            })
+            .collect();
+        if terms.is_empty() {
+            bail!("Empty query: bulk edits need at least one filter term");
+        }
+        Ok(Query(terms))
+    }
+
+    /// The plain keyword terms
+    pub fn keywords(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter_map(|term| match term {
+                Term::Keyword(word) => Some(word.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn matches(&self, entry: &KnowledgeEntry) -> bool {
+        self.0.iter().all(|term| match term {
+            Term::Tag(tag) => entry.tags.contains(tag),
+            Term::Category(cat) => entry.category_id == *cat,
+            Term::Project(p) => entry.source_project_id.as_deref() == Some(p),
+            Term::Visibility(v) => entry.visibility == *v,
+            Term::Owner(o) => entry.owner.as_deref() == Some(o),
+            Term::ResonanceType(t) => entry.resonance_type.as_deref() == Some(t),
+            Term::Keyword(word) => crate::revisions::matches_query(entry, word),
+        })
+    }
+}
+
+/// One `--set field=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Setting {
+    Visibility(String),
+    Category(String),
+    Resonance(i32),
+    ResonanceType(Option<String>),
+    Project(Option<String>),
+}
+
+impl std::str::FromStr for Setting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((field, value)) = s.split_once('=') else {
+            bail!("Expected field=value, got '{}'", s);
+        };
+        let optional = || (!value.is_empty()).then(|| value.to_string());
+        Ok(match field {
+            "visibility" => {
+                if value != "public" && value != "private" {
+                    bail!("visibility must be 'public' or 'private'");
+                }
+                Setting::Visibility(value.to_string())
+            }
+            "category" => Setting::Category(value.to_string()),
+            "resonance" => Setting::Resonance(
+                value
+                    .parse()
+                    .with_context(|| format!("resonance must be a number, got '{}'", value))?,
+            ),
+            "resonance_type" => Setting::ResonanceType(optional()),
+            "project" => Setting::Project(optional()),
+            other => bail!(
+                "Can't bulk-set '{}' (settable: visibility, category, resonance, resonance_type, project)",
+                other
+            ),
+        })
+    }
+}
+
+/// The changes to make to each matching entry
+#[derive(Debug, Default)]
+pub struct Mutation {
+    pub set: Vec<Setting>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    /// Owner for entries made private (defaults to MX_CURRENT_AGENT)
+    pub owner: Option<String>,
+    /// Allow making blooms public
+    pub force: bool,
+}
+
+impl Mutation {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.add_tags.is_empty() && self.remove_tags.is_empty()
+    }
+
+    /// Apply to `entry`, returning a description of each field that changed
+    pub fn apply(&self, entry: &mut KnowledgeEntry) -> Result<Vec<String>> {
+        let mut changes = Vec::new();
+        for setting in &self.set {
+            match setting {
+                Setting::Visibility(vis) if *vis != entry.visibility => {
+                    if vis == "public" && entry.category_id == "bloom" && !self.force {
+                        bail!(
+                            "Making bloom '{}' public will expose identity data. Use --force to confirm.",
+                            entry.id
+                        );
+                    }
+                    if vis == "private" {
+                        let Some(owner) = self.owner.clone() else {
+                            bail!(
+                                "Cannot make entries private without an owner. Provide --owner or set MX_CURRENT_AGENT."
+                            );
+                        };
+                        entry.owner = Some(owner);
+                    } else {
+                        entry.owner = None;
+                    }
+                    changes.push(format!("visibility: {} -> {}", entry.visibility, vis));
+                    entry.visibility = vis.clone();
+                }
+                Setting::Category(cat) if *cat != entry.category_id => {
+                    changes.push(format!("category: {} -> {}", entry.category_id, cat));
+                    entry.category_id = cat.clone();
+                }
+                Setting::Resonance(r) if *r != entry.resonance => {
+                    changes.push(format!("resonance: {} -> {}", entry.resonance, r));
+                    entry.resonance = *r;
+                }
+                Setting::ResonanceType(t) if *t != entry.resonance_type => {
+                    changes.push(format!(
+                        "resonance_type: {} -> {}",
+                        entry.resonance_type.as_deref().unwrap_or("(none)"),
+                        t.as_deref().unwrap_or("(none)")
+                    ));
+                    entry.resonance_type = t.clone();
+                }
+                Setting::Project(p) if *p != entry.source_project_id => {
+                    changes.push(format!(
+                        "project: {} -> {}",
+                        entry.source_project_id.as_deref().unwrap_or("(none)"),
+                        p.as_deref().unwrap_or("(none)")
+                    ));
+                    entry.source_project_id = p.clone();
+                }
+                _ => {}
+            }
+        }
+        for tag in &self.add_tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+                changes.push(format!("+tag {}", tag));
+            }
+        }
+        for tag in &self.remove_tags {
+            if let Some(pos) = entry.tags.iter().position(|t| t == tag) {
+                entry.tags.remove(pos);
+                changes.push(format!("-tag {}", tag));
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// Write every updated entry, restoring the originals if any write fails
+fn write_all(db: &dyn KnowledgeStore, batch: &[(KnowledgeEntry, KnowledgeEntry)]) -> Result<()> {
+    for (i, (_, updated)) in batch.iter().enumerate() {
+        if let Err(e) = db.upsert_knowledge(updated) {
+            let mut unrestored = Vec::new();
+            for (original, _) in &batch[..i] {
+                if db.upsert_knowledge(original).is_err() {
+                    unrestored.push(original.id.as_str());
+                }
+            }
+            if !unrestored.is_empty() {
+                eprintln!(
+                    "Warning: could not restore {}; check them with mx memory show",
+                    unrestored.join(", ")
+                );
+            }
+            return Err(e).with_context(|| {
+                format!(
+                    "Bulk edit failed at {}; {} earlier change(s) rolled back",
+                    updated.id,
+                    i - unrestored.len()
+                )
+            });
+        }
+    }
+    Ok(())
+}
+
+#[derive(clap::Args)]
+pub struct BulkArgs {
+    /// Filter terms, all of which must match: tag:, category:, project:,
+    /// visibility:, owner:, type: (resonance type), or plain keywords
+    #[arg(long, short = 'q')]
+    pub query: String,
+
+    /// Set a field on every match (visibility, category, resonance,
+    /// resonance_type, project); repeatable, empty value clears
+    #[arg(long, value_name = "FIELD=VALUE")]
+    pub set: Vec<Setting>,
+
+    /// Add a tag (repeatable)
+    #[arg(long)]
+    pub add_tag: Vec<String>,
+
+    /// Remove a tag (repeatable)
+    #[arg(long)]
+    pub remove_tag: Vec<String>,
+
+    /// Owner for entries made private (defaults to MX_CURRENT_AGENT)
+    #[arg(long)]
+    pub owner: Option<String>,
+
+    /// Allow making blooms public
+    #[arg(long)]
+    pub force: bool,
+
+    /// List the changes without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip the confirmation for large batches
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: BulkArgs) -> Result<()> {
+    let query = Query::parse(&args.query)?;
+    let mutation = Mutation {
+        set: args.set,
+        add_tags: args.add_tag,
+        remove_tags: args.remove_tag,
+        owner: args.owner.or_else(|| {
+            std::env::var("MX_CURRENT_AGENT")
+                .ok()
+                .filter(|s| !s.is_empty())
+        }),
+        force: args.force,
+    };
+    if mutation.is_empty() {
+        bail!("Nothing to change: give --set, --add-tag, or --remove-tag");
+    }
+    for setting in &mutation.set {
+        if let Setting::Category(cat) = setting
+            && db.get_category(cat)?.is_none()
+        {
+            let categories = db.list_categories()?;
+            let valid_ids: Vec<&str> = categories.iter().map(|c| c.id.as_str()).collect();
+            bail!(
+                "Invalid category '{}'. Valid categories: {}",
+                cat,
+                valid_ids.join(", ")
+            );
+        }
+    }
+
+    let matched: Vec<KnowledgeEntry> = db
+        .list_all(ctx)?
+        .into_iter()
+        .filter(|e| query.matches(e))
+        .collect();
+
+    // Build and validate the whole batch before touching the store
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut batch = Vec::new();
+    for original in &matched {
+        let mut updated = original.clone();
+        let changes = mutation.apply(&mut updated)?;
+        if changes.is_empty() {
+            continue;
+        }
+        updated.validate()?;
+        updated.updated_at = Some(now.clone());
+        println!(
+            "{} {}  {}",
+            updated.id.cyan(),
+            updated.title,
+            changes.join(", ").dimmed()
+        );
+        batch.push((original.clone(), updated));
+    }
+
+    println!(
+        "\n{} matched, {} to change, {} already up to date",
+        matched.len(),
+        batch.len(),
+        matched.len() - batch.len()
+    );
+    if batch.is_empty() {
+        return Ok(());
+    }
+    if args.dry_run {
+        println!("{}", "Dry run: nothing written".yellow());
+        return Ok(());
+    }
+    let affected = [(batch.len(), "entry", "entries")];
+    if batch.len() > confirm::threshold()? && !confirm::confirm("Change", &affected, &[], args.yes)?
+    {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    write_all(db, &batch)?;
+    println!("Updated {} entries", batch.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tags: &[&str]) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": "kn-1",
+            "category_id": "pattern",
+            "title": "Retry with backoff",
+            "body": "Use exponential backoff",
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_query_terms() {
+        let e = entry(&["old-project", "rust"]);
+        assert!(Query::parse("tag:old-project").unwrap().matches(&e));
+        assert!(
+            Query::parse("tag:rust category:pattern backoff")
+                .unwrap()
+                .matches(&e)
+        );
+        assert!(!Query::parse("tag:old").unwrap().matches(&e));
+        assert!(!Query::parse("category:bloom").unwrap().matches(&e));
+        assert!(Query::parse("  ").is_err());
+    }
+
+    #[test]
+    fn applies_mutations() {
+        let mutation = Mutation {
+            set: vec![
+                "visibility=private".parse().unwrap(),
+                "resonance_type=".parse().unwrap(),
+            ],
+            add_tags: vec!["archived".into(), "rust".into()],
+            remove_tags: vec!["old-project".into()],
+            owner: Some("smith".into()),
+            force: false,
+        };
+        let mut e = entry(&["old-project", "rust"]);
+        let changes = mutation.apply(&mut e).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                "visibility: public -> private",
+                "+tag archived",
+                "-tag old-project"
+            ]
+        );
+        assert_eq!(e.owner.as_deref(), Some("smith"));
+        assert_eq!(e.tags, vec!["rust", "archived"]);
+        // Already applied: nothing left to change
+        assert!(mutation.apply(&mut e).unwrap().is_empty());
+
+        assert!("body=x".parse::<Setting>().is_err());
+        assert!("visibility=team".parse::<Setting>().is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Quick capture: `mx capture`
+//!
+//! The lowest-friction way into the index: take whatever is on the system
+//! clipboard (or piped on stdin), ask for a title, and store it as an entry
+//! right away.
+//!
+//! Clipboard access shells out to the platform tool: `pbpaste` on macOS,
+//! `wl-paste`, `xclip`, or `xsel` on Linux, PowerShell on Windows. When no
+//! title is given and there is no terminal to prompt on, the first line of
+//! the content becomes the title.
+
+use anyhow::{Result, bail};
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::Command;
+
+use crate::index::IndexConfig;
+use crate::ingest::{self, Draft, IngestOptions};
+use crate::store;
+use crate::text;
+
+/// Longest title derived from content
+const MAX_DERIVED_TITLE: usize = 80;
+
+#[derive(clap::Args)]
+pub struct CaptureOptions {
+    /// Category for the entry
+    #[arg(long, default_value = "insight")]
+    pub category: String,
+
+    /// Entry title (prompted for, or taken from the first line, when omitted)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Comma-separated tags
+    #[arg(long)]
+    pub tags: Option<String>,
+
+    /// Only visible to the current agent (requires MX_CURRENT_AGENT)
+    #[arg(long)]
+    pub private: bool,
+
+    /// Read the content from stdin instead of the clipboard
+    #[arg(long)]
+    pub stdin: bool,
+}
+
+/// Clipboard readers to try, in order, for this platform
+fn clipboard_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    if cfg!(target_os = "macos") {
+        &[("pbpaste", &[])]
+    } else if cfg!(windows) {
+        &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+    } else {
+        &[
+            ("wl-paste", &["--no-newline"]),
+            ("xclip", &["-selection", "clipboard", "-o"]),
+            ("xsel", &["--clipboard", "--output"]),
+        ]
+    }
+}
+
+fn read_clipboard() -> Result<String> {
+    for (program, args) in clipboard_commands() {
+        if let Ok(output) = Command::new(program).args(*args).output()
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    let tools: Vec<&str> = clipboard_commands().iter().map(|(p, _)| *p).collect();
+    bail!(
+        "Could not read the clipboard (tried {}). Pipe the content on stdin instead",
+        tools.join(", ")
+    )
+}
+
+/// First non-empty line, without markdown heading marks, cut to a title length
+fn derive_title(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())?;
+    Some(text::ellipsize(line, MAX_DERIVED_TITLE))
+}
+
+pub fn capture(opts: CaptureOptions, verbose: bool) -> Result<()> {
+    let piped = !io::stdin().is_terminal();
+    let content = if piped || opts.stdin {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        buf
+    } else {
+        read_clipboard()?
+    };
+    let content = content.trim().to_string();
+    if content.is_empty() {
+        bail!("Nothing to capture: the clipboard/stdin is empty");
+    }
+
+    let derived = derive_title(&content);
+    let title = match opts.title {
+        Some(title) => title,
+        // stdin still belongs to the terminal when the content came from the clipboard
+        None if !piped && !opts.stdin => {
+            let preview = text::truncate(&content, 200);
+            println!("{}\n", preview);
+            print!(
+                "Title [{}]: ",
+                derived.as_deref().unwrap_or("untitled capture")
+            );
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            match input.trim() {
+                "" => derived.unwrap_or_else(|| "Untitled capture".to_string()),
+                typed => typed.to_string(),
+            }
+        }
+        None => derived.unwrap_or_else(|| "Untitled capture".to_string()),
+    };
+
+    let agent = std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty());
+    if opts.private && agent.is_none() {
+        bail!("--private requires MX_CURRENT_AGENT to be set");
+    }
+
+    let config = IndexConfig::default();
+    let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+    if db.get_category(&opts.category)?.is_none() {
+        let categories = db.list_categories()?;
+        let valid_ids: Vec<&str> = categories.iter().map(|c| c.id.as_str()).collect();
+        bail!(
+            "Invalid category '{}'. Valid categories: {}",
+            opts.category,
+            valid_ids.join(", ")
+        );
+    }
+
+    let draft = Draft {
+        key: format!("capture:{}", chrono::Utc::now().to_rfc3339()),
+        title,
+        body: content,
+        ..Default::default()
+    };
+    let ingest_opts = IngestOptions::new(opts.category, opts.tags, opts.private, false);
+    let mut entry = ingest::build_entry(&draft, &ingest_opts, agent.as_deref());
+    entry.source_type_id = Some("manual".to_string());
+    db.upsert_knowledge(&entry)?;
+    crate::notify::entry_added(&entry);
+
+    println!("Captured: {} {}", entry.id, entry.title);
+    if !entry.tags.is_empty() {
+        println!("  Tags: {}", entry.tags.join(", "));
+    }
+
+    // Embedding is a nice-to-have here; the entry is already saved
+    if let Err(e) = crate::auto_embed(&entry.id, db.as_ref()) {
+        eprintln!("Warning: entry saved without embedding: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_titles_from_content() {
+        assert_eq!(
+            derive_title("\n\n## Retry budget\nbody").as_deref(),
+            Some("Retry budget")
+        );
+        assert_eq!(derive_title("   \n"), None);
+        let long = derive_title(&"word ".repeat(40)).unwrap();
+        assert_eq!(long.chars().count(), MAX_DERIVED_TITLE);
+        assert!(long.ends_with('…'));
+    }
+}
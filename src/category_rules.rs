@@ -0,0 +1,199 @@
+//! Category validation profiles
+//!
+//! Categories can declare what their entries must carry, under `categories:`
+//! in config.yaml:
+//!
+//! ```yaml
+//! categories:
+//!   postmortem:
+//!     required_tags: [incident-date]
+//!     required_fields: [summary]
+//! ```
+//!
+//! A required tag is satisfied by the tag itself or a valued form of it
+//! (`incident-date:2026-03-01`). Required fields must be present and not
+//! blank; [`CHECKABLE_FIELDS`] lists the ones that can be required.
+//!
+//! `mx memory add` and `mx memory import` enforce the rules (`--force`
+//! bypasses them), and `mx memory lint` reports existing entries that break
+//! them.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_yaml::Mapping;
+use std::collections::BTreeMap;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::{config, paths};
+
+/// Key in config.yaml holding the per-category rules
+const CATEGORIES_KEY: &str = "categories";
+
+/// Fields a category can require
+pub const CHECKABLE_FIELDS: &[&str] = &[
+    "summary",
+    "body",
+    "project",
+    "resonance_type",
+    "applicability",
+    "anchors",
+    "wake_phrases",
+    "owner",
+];
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryRule {
+    #[serde(default)]
+    pub required_tags: Vec<String>,
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+}
+
+/// Rules for every category that declares any
+#[derive(Debug, Default, Clone)]
+pub struct Rules(BTreeMap<String, CategoryRule>);
+
+fn has_tag(entry: &KnowledgeEntry, required: &str) -> bool {
+    entry.tags.iter().any(|tag| {
+        tag == required
+            || tag
+                .strip_prefix(required)
+                .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
+
+fn has_field(entry: &KnowledgeEntry, field: &str) -> bool {
+    let filled = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+    match field {
+        "summary" => filled(&entry.summary),
+        "body" => filled(&entry.body),
+        "project" => filled(&entry.source_project_id),
+        "resonance_type" => filled(&entry.resonance_type),
+        "applicability" => !entry.applicability.is_empty(),
+        "anchors" => !entry.anchors.is_empty(),
+        "wake_phrases" => entry.has_any_wake_phrase(),
+        "owner" => filled(&entry.owner),
+        _ => true,
+    }
+}
+
+impl Rules {
+    /// Rules from the user's config.yaml
+    pub fn load() -> Result<Self> {
+        Self::from_config(&config::load(&paths::config_file())?)
+    }
+
+    /// Rules from the `categories:` section of a parsed config
+    pub fn from_config(config: &Mapping) -> Result<Self> {
+        let Some(value) = config.get(CATEGORIES_KEY) else {
+            return Ok(Self::default());
+        };
+        let rules: BTreeMap<String, CategoryRule> = serde_yaml::from_value(value.clone())
+            .context("Invalid 'categories' section in config.yaml")?;
+        for (category, rule) in &rules {
+            if let Some(field) = rule
+                .required_fields
+                .iter()
+                .find(|f| !CHECKABLE_FIELDS.contains(&f.as_str()))
+            {
+                bail!(
+                    "config.yaml: category '{}' requires unknown field '{}' (checkable: {})",
+                    category,
+                    field,
+                    CHECKABLE_FIELDS.join(", ")
+                );
+            }
+        }
+        Ok(Self(rules))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every rule `entry` breaks, one message each
+    pub fn problems(&self, entry: &KnowledgeEntry) -> Vec<String> {
+        let Some(rule) = self.0.get(&entry.category_id) else {
+            return Vec::new();
+        };
+        let tags = rule
+            .required_tags
+            .iter()
+            .filter(|tag| !has_tag(entry, tag))
+            .map(|tag| format!("missing required tag '{}'", tag));
+        let fields = rule
+            .required_fields
+            .iter()
+            .filter(|field| !has_field(entry, field))
+            .map(|field| format!("missing required field '{}'", field));
+        tags.chain(fields).collect()
+    }
+
+    /// Reject `entry` if it breaks its category's rules
+    pub fn check(&self, entry: &KnowledgeEntry) -> Result<()> {
+        let problems = self.problems(entry);
+        if !problems.is_empty() {
+            bail!(
+                "Entry {} breaks the '{}' category rules: {}",
+                entry.id,
+                entry.category_id,
+                problems.join("; ")
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(yaml: &str) -> Result<Rules> {
+        Rules::from_config(&serde_yaml::from_str(yaml).unwrap())
+    }
+
+    fn entry(category: &str, tags: &[&str], summary: Option<&str>) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": "kn-1",
+            "category_id": category,
+            "title": "Outage",
+            "summary": summary,
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn checks_required_tags_and_fields() {
+        let rules = rules(
+            "categories:\n  postmortem:\n    required_tags: [incident-date]\n    required_fields: [summary]\n",
+        )
+        .unwrap();
+
+        let bare = entry("postmortem", &["db"], Some("  "));
+        assert_eq!(
+            rules.problems(&bare),
+            vec![
+                "missing required tag 'incident-date'",
+                "missing required field 'summary'"
+            ]
+        );
+        assert!(rules.check(&bare).is_err());
+
+        let valued = entry("postmortem", &["incident-date:2026-03-01"], Some("DB down"));
+        assert!(rules.check(&valued).is_ok());
+        // A tag that merely starts with the name doesn't count
+        let lookalike = entry("postmortem", &["incident-dates"], Some("DB down"));
+        assert_eq!(rules.problems(&lookalike).len(), 1);
+        // Categories without rules are unconstrained
+        assert!(rules.check(&entry("pattern", &[], None)).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_config() {
+        assert!(rules("alias:\n  w: memory wake\n").unwrap().is_empty());
+        assert!(rules("categories:\n  postmortem:\n    required_fields: [color]\n").is_err());
+        assert!(rules("categories:\n  postmortem:\n    required: [summary]\n").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Change feed: `mx memory changes`
+//!
+//! Every entry created, updated or deleted and every relationship added or
+//! removed is appended to the `change` table by database events, so nothing
+//! that writes to the store can bypass it. Each change carries a `seq`
+//! cursor; downstream consumers (semantic index, static site, notifications)
+//! remember the last one they processed and ask only for what came after:
+//!
+//! ```bash
+//! mx memory changes --since 1042 --json      # JSON lines, one per change
+//! mx memory changes --since 1042 --follow    # keep polling
+//! ```
+//!
+//! The tenant server offers the same feed as server-sent events at
+//! `/api/changes` (see [`sse`]).
+
+use anyhow::Result;
+use colored::Colorize;
+use std::io::Write;
+
+use crate::dates;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::types::Change;
+
+#[derive(clap::Args)]
+pub struct ChangesArgs {
+    /// Cursor: only changes after this sequence number
+    #[arg(long, default_value = "0")]
+    pub since: i64,
+
+    /// Keep polling for new changes
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Seconds between polls with --follow
+    #[arg(long, default_value = "2")]
+    pub interval: u64,
+
+    /// Maximum changes per read
+    #[arg(short, long, default_value = "500")]
+    pub limit: usize,
+
+    /// One JSON object per line
+    #[arg(long)]
+    pub json: bool,
+}
+
+fn print_change(change: &Change, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(change)?);
+        return Ok(());
+    }
+    let kind = match change.kind.as_str() {
+        "created" | "related" => change.kind.green(),
+        "deleted" | "unrelated" => change.kind.red(),
+        _ => change.kind.yellow(),
+    };
+    let mut line = format!(
+        "{:>6}  {}  {:<9}  {}",
+        change.seq,
+        dates::display(&change.at).dimmed(),
+        kind,
+        change.entry_id.cyan()
+    );
+    if let Some(ref target) = change.target_id {
+        line.push_str(&format!(
+            " -> {} ({})",
+            target.cyan(),
+            change.relationship_type.as_deref().unwrap_or("?")
+        ));
+    }
+    println!("{}", line);
+    Ok(())
+}
+
+/// Changes as a server-sent event stream body. The stream ends after the
+/// backlog; `retry` makes an EventSource reconnect with `Last-Event-ID`, which
+/// the server reads as the next cursor.
+pub fn sse(changes: &[Change]) -> Result<String> {
+    let mut body = String::from(SSE_RETRY);
+    for change in changes {
+        body.push_str(&sse_event(change)?);
+    }
+    Ok(body)
+}
+
+/// Reconnect delay sent at the start of every stream
+pub const SSE_RETRY: &str = "retry: 2000\n\n";
+
+/// One change as a server-sent event, keyed by its cursor
+pub fn sse_event(change: &Change) -> Result<String> {
+    Ok(format!(
+        "id: {}\nevent: change\ndata: {}\n\n",
+        change.seq,
+        serde_json::to_string(change)?
+    ))
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ChangesArgs) -> Result<()> {
+    let mut cursor = args.since;
+    loop {
+        let changes = db.list_changes(cursor, args.limit, ctx)?;
+        for change in &changes {
+            print_change(change, args.json)?;
+        }
+        if let Some(last) = changes.last() {
+            cursor = last.seq;
+        }
+        std::io::stdout().flush()?;
+
+        // A full page means more is waiting; fetch it straight away
+        if changes.len() == args.limit {
+            continue;
+        }
+        if !args.follow {
+            if !args.json {
+                eprintln!("Next cursor: {}", cursor);
+            }
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_secs(args.interval.max(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knowledge::KnowledgeEntry;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn entry(id: &str, visibility: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": id,
+            "visibility": visibility,
+            "owner": "smith",
+            "created_at": "2026-03-01T09:00:00Z",
+            "updated_at": "2026-03-01T09:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn kinds(changes: &[Change]) -> Vec<(&str, &str)> {
+        changes
+            .iter()
+            .map(|c| (c.kind.as_str(), c.entry_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn feed_records_writes_in_order() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let mut a = entry("kn-a", "public");
+        db.upsert_knowledge(&a).unwrap();
+        db.upsert_knowledge(&entry("kn-b", "public")).unwrap();
+        db.upsert_knowledge(&entry("kn-secret", "private")).unwrap();
+        // Activation bumps aren't content changes
+        db.increment_activation_count(&["kn-a".to_string()])
+            .unwrap();
+        a.title = "A, revised".into();
+        a.updated_at = Some("2026-03-02T09:00:00Z".into());
+        db.upsert_knowledge(&a).unwrap();
+        db.add_relationship("kn-a", "kn-b", "related").unwrap();
+        db.remove_relationship("kn-a", "kn-b", "related").unwrap();
+        db.purge_entries(&["kn-b".to_string()]).unwrap();
+
+        let public = db
+            .list_changes(0, 100, &AgentContext::public_only())
+            .unwrap();
+        assert_eq!(
+            kinds(&public),
+            vec![
+                ("created", "kn-a"),
+                ("created", "kn-b"),
+                ("updated", "kn-a"),
+                ("related", "kn-a"),
+                ("unrelated", "kn-a"),
+                ("deleted", "kn-b"),
+            ]
+        );
+        assert_eq!(public[3].target_id.as_deref(), Some("kn-b"));
+        assert!(public.windows(2).all(|w| w[0].seq < w[1].seq));
+
+        // The owner sees their private entry; cursors resume after a seq
+        let mine = db
+            .list_changes(0, 100, &AgentContext::for_agent("smith"))
+            .unwrap();
+        assert_eq!(mine.len(), public.len() + 1);
+        let rest = db
+            .list_changes(public[2].seq, 100, &AgentContext::public_only())
+            .unwrap();
+        assert_eq!(rest.len(), 3);
+
+        let stream = sse(&rest[..1]).unwrap();
+        assert!(stream.starts_with("retry: 2000\n\n"));
+        assert!(stream.contains(&format!("id: {}\nevent: change\ndata: {{", rest[0].seq)));
+    }
+
+    #[test]
+    fn live_subscription_pushes_visible_changes() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let live = db.watch_changes(AgentContext::for_agent("neo")).unwrap();
+        db.upsert_knowledge(&entry("kn-hidden", "private")).unwrap();
+        db.upsert_knowledge(&entry("kn-shared", "public")).unwrap();
+
+        let timeout = std::time::Duration::from_secs(5);
+        let change = live.recv_timeout(timeout).unwrap();
+        assert_eq!(
+            (change.kind.as_str(), change.entry_id.as_str()),
+            ("created", "kn-shared")
+        );
+        assert_eq!(change.seq, 2);
+        assert!(
+            live.recv_timeout(std::time::Duration::from_millis(200))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! End-of-session ritual: `mx session close-out`
+//!
+//! One command for what used to be several:
+//!
+//! 1. archive the current Claude session to the codex,
+//! 2. queue the session's residue (transient entries written since the
+//!    session started, at or above `--min-resonance`) for review as
+//!    promotion candidates,
+//! 3. encode the final state tensor (`--state` or `--guided`),
+//! 4. close the open memory session record, keeping the archive path,
+//!    harvested entries, and state in its metadata,
+//! 5. print what happened.
+//!
+//! A failing step doesn't stop the others; the command exits non-zero if any
+//! step failed.
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use serde_json::json;
+
+use crate::error::MxError;
+use crate::index::IndexConfig;
+use crate::store::{self, AgentContext, KnowledgeStore};
+#[cfg(feature = "state")]
+use crate::tensor;
+use crate::types::Session;
+use crate::{codex, promote, queue};
+
+/// How far back to harvest when there's no open session to start from
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+#[derive(clap::Args)]
+pub struct CloseOutArgs {
+    /// Session JSONL to archive (defaults to the most recent non-agent session)
+    pub path: Option<String>,
+
+    /// Memory session record to close (defaults to the most recent open one)
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Archive only the clean transcript (see `mx codex save --clean`)
+    #[arg(long)]
+    pub clean: bool,
+
+    /// Skip the codex archive
+    #[arg(long)]
+    pub no_codex: bool,
+
+    /// Minimum resonance for an entry to be queued as a promotion candidate
+    #[arg(long, default_value = "5")]
+    pub min_resonance: i32,
+
+    /// Final state as named dimensions (e.g., "temp=0.8 entropy=0.75 agency=0.4")
+    #[arg(long, conflicts_with = "guided")]
+    pub state: Option<String>,
+
+    /// Capture the final state interactively
+    #[arg(long)]
+    pub guided: bool,
+
+    /// State schema ID (defaults to MX_STATE_SCHEMA or "crewu")
+    #[arg(long)]
+    pub schema: Option<String>,
+}
+
+/// Outcome of one step, for the summary
+enum Step {
+    Done(String),
+    Skipped(String),
+    Failed(String),
+}
+
+impl Step {
+    fn from_result(result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Step::Done(detail),
+            Err(e) => Step::Failed(format!("{:#}", e)),
+        }
+    }
+
+    fn print(&self, name: &str) {
+        match self {
+            Step::Done(detail) => println!("  {} {:<8} {}", "✓".green(), name, detail),
+            Step::Skipped(detail) => {
+                println!("  {} {:<8} {}", "-".dimmed(), name, detail.dimmed())
+            }
+            Step::Failed(detail) => println!("  {} {:<8} {}", "✗".red(), name, detail),
+        }
+    }
+}
+
+fn agent_context() -> AgentContext {
+    match std::env::var("MX_CURRENT_AGENT") {
+        Ok(agent) if !agent.is_empty() => AgentContext::for_agent(agent),
+        _ => AgentContext::public_only(),
+    }
+}
+
+/// The session record to close: the named one, or the newest still open
+fn open_session(db: &dyn KnowledgeStore, id: Option<&str>) -> Result<Option<Session>> {
+    match id {
+        Some(id) => match db.get_session(id)? {
+            Some(session) => Ok(Some(session)),
+            None => bail!(MxError::not_found("Session", id)),
+        },
+        None => Ok(db
+            .list_sessions(None)?
+            .into_iter()
+            .filter(|s| s.ended_at.is_none())
+            .max_by(|a, b| a.started_at.cmp(&b.started_at))),
+    }
+}
+
+/// Queue transient entries written since `since` for review
+fn harvest(
+    db: &dyn KnowledgeStore,
+    since: DateTime<Utc>,
+    min_resonance: i32,
+    session_id: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut queued = Vec::new();
+    for entry in db.list_all(&agent_context())? {
+        let recent = entry
+            .created_at
+            .as_deref()
+            .and_then(|c| DateTime::parse_from_rfc3339(c).ok())
+            .is_some_and(|c| c >= since);
+        if !recent || !promote::is_transient(&entry) || entry.resonance < min_resonance {
+            continue;
+        }
+        let note = match session_id {
+            Some(id) => format!("Promotion candidate from session {}", id),
+            None => "Promotion candidate".to_string(),
+        };
+        let priority = i64::from(entry.resonance);
+        if queue::enqueue_entry(db, &entry, priority, Some(note))?.is_some() {
+            queued.push(entry.id);
+        }
+    }
+    Ok(queued)
+}
+
+#[cfg(feature = "state")]
+fn encode_state(args: &CloseOutArgs) -> Result<Option<String>> {
+    if args.state.is_none() && !args.guided {
+        return Ok(None);
+    }
+    let schema = match args.schema {
+        Some(ref id) => tensor::TensorSchema::load_by_id(id)?,
+        None => tensor::TensorSchema::load_default()?,
+    };
+    let state = match args.state {
+        Some(ref dims) => tensor::StateTensor::parse_named_dimensions(&schema, dims)?,
+        None => tensor::guided_capture(&schema)?,
+    };
+    Ok(Some(state.encode()))
+}
+
+#[cfg(not(feature = "state"))]
+fn encode_state(args: &CloseOutArgs) -> Result<Option<String>> {
+    if args.state.is_none() && !args.guided {
+        return Ok(None);
+    }
+    anyhow::bail!("This mx was built without state tensors (the `state` feature)")
+}
+
+/// Close `session`, recording the close-out in its metadata
+fn close_session(
+    db: &dyn KnowledgeStore,
+    mut session: Session,
+    record: serde_json::Value,
+) -> Result<String> {
+    let mut metadata = session
+        .metadata
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+        .filter(|m| m.is_object())
+        .unwrap_or_else(|| json!({}));
+    metadata["close_out"] = record;
+    session.metadata = Some(metadata.to_string());
+    session.ended_at = Some(Utc::now().to_rfc3339());
+    db.upsert_session(&session)?;
+    Ok(session.id)
+}
+
+pub fn run(args: CloseOutArgs) -> Result<()> {
+    let config = IndexConfig::default();
+    let db = store::create_store(&config.db_path)?;
+    let db = db.as_ref();
+
+    let archive = if args.no_codex {
+        Step::Skipped("--no-codex".to_string())
+    } else {
+        Step::from_result(
+            codex::archive_one(args.path.clone(), args.clean, false)
+                .map(|dir| dir.display().to_string()),
+        )
+    };
+
+    let session = open_session(db, args.session.as_deref())?;
+    let since = session
+        .as_ref()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s.started_at).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc::now() - Duration::hours(DEFAULT_WINDOW_HOURS));
+    let harvested = harvest(
+        db,
+        since,
+        args.min_resonance,
+        session.as_ref().map(|s| s.id.as_str()),
+    );
+    let harvest_step = match harvested {
+        Ok(ref ids) if ids.is_empty() => Step::Done("no new promotion candidates".to_string()),
+        Ok(ref ids) => Step::Done(format!("{} queued for review (mx queue list)", ids.len())),
+        Err(ref e) => Step::Failed(format!("{:#}", e)),
+    };
+
+    let state = encode_state(&args);
+    let state_step = match state {
+        Ok(Some(ref tensor)) => Step::Done(tensor.clone()),
+        Ok(None) => Step::Skipped("no --state or --guided".to_string()),
+        Err(ref e) => Step::Failed(format!("{:#}", e)),
+    };
+
+    let session_step = match session {
+        Some(session) => {
+            let record = json!({
+                "codex_archive": match archive {
+                    Step::Done(ref dir) => Some(dir.clone()),
+                    _ => None,
+                },
+                "harvested": harvested.as_ref().ok(),
+                "state": state.as_ref().ok().cloned().flatten(),
+            });
+            Step::from_result(close_session(db, session, record).map(|id| format!("closed {}", id)))
+        }
+        None => Step::Skipped("no open session".to_string()),
+    };
+
+    println!("{}", "Session close-out".bold());
+    archive.print("codex");
+    harvest_step.print("harvest");
+    state_step.print("state");
+    session_step.print("session");
+
+    let failed = [&archive, &harvest_step, &state_step, &session_step]
+        .iter()
+        .filter(|s| matches!(s, Step::Failed(_)))
+        .count();
+    if failed > 0 {
+        bail!("Close-out finished with {} failed step(s)", failed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knowledge::KnowledgeEntry;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn entry(id: &str, resonance: i32, resonance_type: &str, created: &str) -> KnowledgeEntry {
+        serde_json::from_value(json!({
+            "id": id,
+            "category_id": "insight",
+            "title": id,
+            "resonance": resonance,
+            "resonance_type": resonance_type,
+            "created_at": created,
+            "updated_at": created,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn harvests_residue_and_closes_session() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let started = "2026-03-01T09:00:00Z";
+        db.upsert_session(&Session {
+            id: "sess-1".into(),
+            session_type_id: "manual".into(),
+            project_id: None,
+            started_at: started.into(),
+            ended_at: None,
+            metadata: Some(r#"{"topic":"retries"}"#.into()),
+        })
+        .unwrap();
+        for e in [
+            entry("kn-fresh", 6, "ephemeral", "2026-03-01T10:00:00Z"),
+            entry("kn-faint", 2, "ephemeral", "2026-03-01T10:00:00Z"),
+            entry("kn-old", 6, "session", "2026-02-01T10:00:00Z"),
+            entry("kn-durable", 8, "foundational", "2026-03-01T10:00:00Z"),
+        ] {
+            db.upsert_knowledge(&e).unwrap();
+        }
+
+        let session = open_session(&db, None).unwrap().unwrap();
+        let since = DateTime::parse_from_rfc3339(started).unwrap().into();
+        assert_eq!(
+            harvest(&db, since, 5, Some("sess-1")).unwrap(),
+            vec!["kn-fresh"]
+        );
+        // Already queued entries aren't queued twice
+        assert!(harvest(&db, since, 5, Some("sess-1")).unwrap().is_empty());
+
+        close_session(&db, session, json!({"state": "@state:crewu|0.50"})).unwrap();
+        assert!(open_session(&db, None).unwrap().is_none());
+        let closed = db.get_session("sess-1").unwrap().unwrap();
+        assert!(closed.ended_at.is_some());
+        let metadata: serde_json::Value =
+            serde_json::from_str(closed.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(metadata["topic"], "retries");
+        assert_eq!(metadata["close_out"]["state"], "@state:crewu|0.50");
+    }
+}
@@ -6,11 +6,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use std::time::SystemTime;
 
+use crate::error::MxError;
+use crate::journal::{Journal, Op};
+use crate::jsonl::{self, Line};
+use crate::progress::{self, Progress};
+use crate::{config, paths};
+
+const CODEX_KEY: &str = "codex";
+const DEFAULT_SHARD_MB: u64 = 64;
+
 static SYSTEM_REMINDER_RE: OnceLock<Regex> = OnceLock::new();
 static USER_NAME: OnceLock<String> = OnceLock::new();
 static ASSISTANT_NAME: OnceLock<String> = OnceLock::new();
@@ -84,6 +94,10 @@ fn system_reminder_re() -> &'static Regex {
         .get_or_init(|| Regex::new(r"(?s)<system-reminder>.*?</system-reminder>").unwrap())
 }
 
+/// Manifest version written by `codex save`. v1 archives kept images inline
+/// in the JSONL; `codex upgrade-manifests` brings them up to this.
+pub const MANIFEST_VERSION: u32 = 2;
+
 /// Manifest metadata for an archived session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
@@ -111,6 +125,46 @@ pub struct Manifest {
     pub user_name: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub assistant_name: Option<String>,
+    // v5 fields - session.jsonl split into shards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shards: Option<Vec<ShardInfo>>,
+}
+
+/// One part of a sharded session, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardInfo {
+    pub file: String,
+    pub lines: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    #[serde(default = "default_shard_mb")]
+    shard_mb: u64,
+}
+
+fn default_shard_mb() -> u64 {
+    DEFAULT_SHARD_MB
+}
+
+fn shard_bytes_from(config: &serde_yaml::Mapping) -> Result<Option<u64>> {
+    let shard_mb = match config.get(CODEX_KEY) {
+        Some(value) => {
+            let settings: Settings = serde_yaml::from_value(value.clone())
+                .context("Invalid 'codex' section in config.yaml")?;
+            settings.shard_mb
+        }
+        None => DEFAULT_SHARD_MB,
+    };
+    Ok((shard_mb > 0).then_some(shard_mb * 1024 * 1024))
+}
+
+/// Sessions larger than this are archived as shards (`codex: shard_mb:` in
+/// config.yaml, default 64; 0 never shards)
+fn shard_bytes() -> Result<Option<u64>> {
+    shard_bytes_from(&config::load(&paths::config_file())?)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +172,9 @@ pub struct AgentInfo {
     pub id: String,
     pub file: String,
     pub messages: usize,
+    /// tool_use_id of the parent's Agent/Task call that spawned this agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawned_by: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,32 +202,51 @@ pub fn save_session(
     Ok(())
 }
 
-/// List archived sessions
-pub fn list_sessions(all: bool, json: bool) -> Result<()> {
-    let codex_dir = get_codex_dir()?;
+/// Archive one session (the most recent if `session_path` is None) and
+/// return the archive directory
+pub fn archive_one(
+    session_path: Option<String>,
+    clean: bool,
+    include_agents: bool,
+) -> Result<PathBuf> {
+    archive_session(&resolve_session_path(session_path)?, clean, include_agents)
+}
+
+/// Clean transcript of an archived session: its conversation.md, or one
+/// generated from session.jsonl. Returns the archive directory too.
+pub fn transcript(id: &str) -> Result<(PathBuf, String)> {
+    let archive_dir = find_archive_by_id(&get_codex_dir()?, id)?;
+    let transcript_file = archive_dir.join("conversation.md");
+    if transcript_file.exists() {
+        return Ok((archive_dir, fs::read_to_string(&transcript_file)?));
+    }
+    let files = session_files(&archive_dir, load_manifest(&archive_dir).as_ref());
+    if files.is_empty() {
+        anyhow::bail!(
+            "Archive '{}' has neither conversation.md nor session.jsonl",
+            id
+        );
+    }
+    let text = clean_transcript(
+        session_lines(&files),
+        &resolve_user_name(),
+        &resolve_assistant_name(),
+    )?;
+    Ok((archive_dir, text))
+}
 
+/// Archived sessions, most recent first. Unless `all`, only the latest
+/// incremental save of each session.
+fn listed_archives(all: bool) -> Result<Vec<ArchiveEntry>> {
+    let codex_dir = get_codex_dir()?;
     if !codex_dir.exists() {
-        if json {
-            println!("[]");
-        } else {
-            println!("No archives found (codex directory doesn't exist)");
-        }
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     let mut archives = collect_archives(&codex_dir)?;
 
-    if archives.is_empty() {
-        if json {
-            println!("[]");
-        } else {
-            println!("No archives found");
-        }
-        return Ok(());
-    }
-
     // Sort by archived_at (most recent first)
-    archives.sort_by(|a, b| b.manifest.archived_at.cmp(&a.manifest.archived_at));
+    archives.sort_by_key(|a| std::cmp::Reverse(a.manifest.archived_at));
 
     // Filter incremental archives unless --all
     if !all {
@@ -192,60 +268,107 @@ pub fn list_sessions(all: bool, json: bool) -> Result<()> {
         }
 
         archives = latest_map.into_values().collect();
-        archives.sort_by(|a, b| b.manifest.archived_at.cmp(&a.manifest.archived_at));
+        archives.sort_by_key(|a| std::cmp::Reverse(a.manifest.archived_at));
     }
 
-    if json {
-        let json_archives: Vec<serde_json::Value> = archives
-            .iter()
-            .map(|a| {
-                serde_json::json!({
-                    "id": a.short_id,
-                    "dir_name": a.dir_name,
-                    "incremental": a.incremental,
-                    "archived_at": a.manifest.archived_at.to_rfc3339(),
-                    "session_id": a.manifest.session_id,
-                    "message_count": a.manifest.message_count,
-                    "agent_count": a.manifest.agent_count,
-                    "size_bytes": a.manifest.size_bytes,
-                })
+    Ok(archives)
+}
+
+/// Archived sessions as `codex list --json` prints them
+pub fn archive_summaries(all: bool) -> Result<Vec<serde_json::Value>> {
+    Ok(listed_archives(all)?
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "id": a.short_id,
+                "dir_name": a.dir_name,
+                "incremental": a.incremental,
+                "archived_at": a.manifest.archived_at.to_rfc3339(),
+                "session_id": a.manifest.session_id,
+                "message_count": a.manifest.message_count,
+                "agent_count": a.manifest.agent_count,
+                "size_bytes": a.manifest.size_bytes,
             })
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&json_archives)?);
-    } else {
-        // Print table
+        })
+        .collect())
+}
+
+/// The manifest.json of the archive matching `id`
+pub fn manifest_path(id: &str) -> Result<PathBuf> {
+    let manifest = find_archive_by_id(&get_codex_dir()?, id)?.join("manifest.json");
+    if !manifest.exists() {
+        anyhow::bail!("Archive '{}' has no manifest.json", id);
+    }
+    Ok(manifest)
+}
+
+/// (archive id, manifest.json) for each archive, most recent first; only
+/// the latest incremental save of each session unless `all`
+pub fn manifest_paths(all: bool) -> Result<Vec<(String, PathBuf)>> {
+    let codex_dir = get_codex_dir()?;
+    Ok(listed_archives(all)?
+        .into_iter()
+        .map(|a| {
+            let id = match a.incremental {
+                0 => a.short_id,
+                n => format!("{}.{}", a.short_id, n),
+            };
+            (id, codex_dir.join(&a.dir_name).join("manifest.json"))
+        })
+        .collect())
+}
+
+/// List archived sessions
+pub fn list_sessions(all: bool, json: bool) -> Result<()> {
+    if json {
         println!(
-            "{:<25} {:<20} {:<8} {:<8} {:<10}",
-            "ARCHIVE", "ARCHIVED", "MESSAGES", "AGENTS", "SIZE"
+            "{}",
+            serde_json::to_string_pretty(&archive_summaries(all)?)?
         );
-        println!("{}", "-".repeat(80));
+        return Ok(());
+    }
 
-        for archive in archives {
-            let size_kb = archive.manifest.size_bytes / 1024;
-            let incremental_suffix = if archive.incremental > 0 {
-                format!(".{}", archive.incremental)
-            } else {
-                String::new()
-            };
+    if !get_codex_dir()?.exists() {
+        println!("No archives found (codex directory doesn't exist)");
+        return Ok(());
+    }
 
-            println!(
-                "{:<25} {:<20} {:<8} {:<8} {:<10}",
-                format!("{}{}", archive.short_id, incremental_suffix),
-                archive.manifest.archived_at.format("%Y-%m-%d %H:%M:%S"),
-                archive.manifest.message_count,
-                archive.manifest.agent_count,
-                format!("{}KB", size_kb)
-            );
-        }
+    let archives = listed_archives(all)?;
+    if archives.is_empty() {
+        println!("No archives found");
+        return Ok(());
     }
 
+    let mut table =
+        crate::output::Table::new(["ARCHIVE", "ARCHIVED", "MESSAGES", "AGENTS", "SIZE"]);
+    for archive in archives {
+        let size_kb = archive.manifest.size_bytes / 1024;
+        let incremental_suffix = if archive.incremental > 0 {
+            format!(".{}", archive.incremental)
+        } else {
+            String::new()
+        };
+
+        table.row([
+            format!("{}{}", archive.short_id, incremental_suffix),
+            crate::dates::display_dt(archive.manifest.archived_at),
+            archive.manifest.message_count.to_string(),
+            archive.manifest.agent_count.to_string(),
+            format!("{}KB", size_kb),
+        ]);
+    }
+    table.print();
+
     Ok(())
 }
 
-/// Read and display an archived session
+/// Read and display an archived session. With `human`, message text is
+/// rendered as markdown unless `raw`.
+#[allow(clippy::too_many_arguments)]
 pub fn read_session(
     id: String,
     human: bool,
+    raw: bool,
     grep_pattern: Option<String>,
     include_agents: bool,
     json: bool,
@@ -256,13 +379,8 @@ pub fn read_session(
     let archive_dir = find_archive_by_id(&codex_dir, &id)?;
 
     // Load manifest once for use across all code paths that need it
-    let manifest_path = archive_dir.join("manifest.json");
-    let manifest: Option<Manifest> = if manifest_path.exists() {
-        let mc = fs::read_to_string(&manifest_path)?;
-        serde_json::from_str(&mc).ok()
-    } else {
-        None
-    };
+    let manifest = load_manifest(&archive_dir);
+    let files = session_files(&archive_dir, manifest.as_ref());
 
     if clean && !json {
         let transcript_file = archive_dir.join("conversation.md");
@@ -280,13 +398,10 @@ pub fn read_session(
             let agents_dir = archive_dir.join("agents");
             if agents_dir.exists() {
                 // Try to build agent type map from session.jsonl if available
-                let session_file = archive_dir.join("session.jsonl");
-                let agent_type_map = if session_file.exists() {
-                    let sc = fs::read_to_string(&session_file).unwrap_or_default();
-                    build_agent_type_map(&sc)
-                } else {
-                    HashMap::new()
-                };
+                let mut agent_type_map = HashMap::new();
+                for line in session_lines(&files).map_while(Result::ok) {
+                    note_agent_type(&line.text, &mut agent_type_map);
+                }
                 let mut agent_sessions = Vec::new();
                 for entry in fs::read_dir(&agents_dir)? {
                     let entry = entry?;
@@ -344,56 +459,129 @@ pub fn read_session(
         return Ok(());
     }
 
-    let session_file = archive_dir.join("session.jsonl");
-    if !session_file.exists() {
+    if files.is_empty() {
         anyhow::bail!("Session file not found in archive");
     }
 
-    let content = fs::read_to_string(&session_file)?;
+    // Shards are streamed a line at a time, never loaded whole
+    let print_line = |number: usize, line: &str| -> Result<()> {
+        if human {
+            // Pretty-print human-readable format
+            print_human_line(number, line, raw)
+        } else {
+            // Raw JSONL
+            println!("{}", line);
+            Ok(())
+        }
+    };
 
     if let Some(pattern) = grep_pattern {
         // Filter lines matching pattern
-        for line in content.lines() {
-            if line.contains(&pattern) {
-                println!("{}", line);
+        for line in session_lines(&files) {
+            let line = line?;
+            if line.text.contains(&pattern) {
+                println!("{}", line.text);
             }
         }
-    } else if human {
-        // Pretty-print human-readable format
-        print_human_readable(&content)?;
-    } else {
-        // Raw JSONL
-        print!("{}", content);
-    }
-
-    // Include agent transcripts if requested
-    if include_agents {
-        let agents_dir = archive_dir.join("agents");
-        if agents_dir.exists() {
-            for entry in fs::read_dir(&agents_dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                    println!(
-                        "\n--- Agent: {} ---\n",
-                        path.file_stem().unwrap().to_string_lossy()
-                    );
-                    let agent_content = fs::read_to_string(&path)?;
-                    if human {
-                        print_human_readable(&agent_content)?;
-                    } else {
-                        print!("{}", agent_content);
-                    }
+    } else if include_agents {
+        // Each agent transcript goes where the parent spawned it
+        let agents = spawned_agents(&archive_dir, &files, manifest.as_ref())?;
+        interleave_agents(session_lines(&files), &agents, |part| match part {
+            Part::Line(line) => print_line(line.number, &line.text),
+            Part::Agent(agent) => {
+                println!("\n--- Agent: {} ---\n", agent.name);
+                if human {
+                    print_human_readable(&agent.content, raw)?;
+                } else {
+                    print!("{}", agent.content);
                 }
+                println!("\n--- End agent: {} ---\n", agent.name);
+                Ok(())
             }
+        })?;
+    } else {
+        for line in session_lines(&files) {
+            let line = line?;
+            print_line(line.number, &line.text)?;
         }
     }
 
     Ok(())
 }
 
-/// Search all archives for a pattern
-pub fn search_archives(pattern: String, json: bool) -> Result<()> {
+/// An archive's manifest, if it has a readable one
+fn load_manifest(archive_dir: &Path) -> Option<Manifest> {
+    let content = fs::read_to_string(archive_dir.join("manifest.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// An archive's session JSONL files in order: the shards its manifest
+/// lists, or session.jsonl. Empty for clean-only archives.
+fn session_files(archive_dir: &Path, manifest: Option<&Manifest>) -> Vec<PathBuf> {
+    if let Some(shards) = manifest.and_then(|m| m.shards.as_ref()) {
+        return shards.iter().map(|s| archive_dir.join(&s.file)).collect();
+    }
+    let session_file = archive_dir.join("session.jsonl");
+    if session_file.exists() {
+        vec![session_file]
+    } else {
+        Vec::new()
+    }
+}
+
+/// The lines of a session's `files`, streamed one at a time
+fn session_lines(files: &[PathBuf]) -> impl Iterator<Item = Result<Line>> + '_ {
+    jsonl::open_all(files).map(|line| line.map(|(_, line)| line))
+}
+
+/// The agent transcripts in an archive, with their spawn points from the
+/// manifest or, for archives saved before those were recorded, the session
+fn spawned_agents(
+    archive_dir: &Path,
+    files: &[PathBuf],
+    manifest: Option<&Manifest>,
+) -> Result<Vec<SpawnedAgent>> {
+    let agents_dir = archive_dir.join("agents");
+    if !agents_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut spawns = HashMap::new();
+    let mut agent_type_map = HashMap::new();
+    let recorded = manifest.is_some_and(|m| m.agents.iter().all(|a| a.spawned_by.is_some()));
+    if !recorded {
+        for line in session_lines(files) {
+            let line = line?;
+            note_spawn(&line.text, &mut spawns);
+            note_agent_type(&line.text, &mut agent_type_map);
+        }
+    }
+    let mut agents = Vec::new();
+    for entry in fs::read_dir(&agents_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let recorded = manifest.and_then(|m| {
+            m.agents
+                .iter()
+                .find(|a| a.file == format!("agents/{}", file_name))
+                .and_then(|a| a.spawned_by.clone())
+        });
+        agents.push(SpawnedAgent {
+            name: path.file_stem().unwrap().to_string_lossy().to_string(),
+            spawned_by: recorded.or_else(|| {
+                spawning_tool_use(&agent_name_from_path(&path), &spawns, &agent_type_map)
+            }),
+            content: fs::read_to_string(&path)?,
+        });
+    }
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(agents)
+}
+
+/// Search all archives for a pattern, stopping after `limit` matching lines
+pub fn search_archives(pattern: String, json: bool, limit: Option<usize>) -> Result<()> {
     let codex_dir = get_codex_dir()?;
 
     if !codex_dir.exists() {
@@ -406,45 +594,69 @@ pub fn search_archives(pattern: String, json: bool) -> Result<()> {
     }
 
     let archives = collect_archives(&codex_dir)?;
+    let mut remaining = limit.unwrap_or(usize::MAX);
 
     if json {
         let mut results = Vec::new();
         for archive in archives {
-            let session_file = codex_dir.join(&archive.dir_name).join("session.jsonl");
-            if let Ok(content) = fs::read_to_string(&session_file)
-                && content.contains(&pattern)
-            {
-                let matching_lines: Vec<serde_json::Value> = content
-                    .lines()
-                    .enumerate()
-                    .filter(|(_, line)| line.contains(&pattern))
-                    .map(|(i, line)| {
-                        serde_json::json!({
-                            "line": i + 1,
-                            "content": line,
-                        })
-                    })
-                    .collect();
-                results.push(serde_json::json!({
-                    "archive_id": archive.short_id,
-                    "file": session_file.display().to_string(),
-                    "matches": matching_lines,
-                }));
+            if remaining == 0 {
+                break;
             }
+            let archive_dir = codex_dir.join(&archive.dir_name);
+            let files = session_files(&archive_dir, Some(&archive.manifest));
+            let matches = grep_session(&files, &pattern, remaining);
+            if matches.is_empty() {
+                continue;
+            }
+            remaining -= matches.len();
+            let sharded = archive.manifest.shards.is_some();
+            let matching_lines: Vec<serde_json::Value> = matches
+                .into_iter()
+                .map(|(number, file, line)| {
+                    let mut m = serde_json::json!({
+                        "line": number,
+                        "content": line,
+                    });
+                    if sharded {
+                        m["shard"] = file.into();
+                    }
+                    m
+                })
+                .collect();
+            let file = if sharded {
+                archive_dir
+            } else {
+                archive_dir.join("session.jsonl")
+            };
+            results.push(serde_json::json!({
+                "archive_id": archive.short_id,
+                "file": file.display().to_string(),
+                "matches": matching_lines,
+            }));
         }
         println!("{}", serde_json::to_string_pretty(&results)?);
     } else {
         for archive in archives {
-            let session_file = codex_dir.join(&archive.dir_name).join("session.jsonl");
-            if let Ok(content) = fs::read_to_string(&session_file)
-                && content.contains(&pattern)
-            {
-                println!("Match in {}: {}", archive.short_id, session_file.display());
+            if remaining == 0 {
+                break;
+            }
+            let archive_dir = codex_dir.join(&archive.dir_name);
+            let files = session_files(&archive_dir, Some(&archive.manifest));
+            let matches = grep_session(&files, &pattern, remaining);
+            if matches.is_empty() {
+                continue;
+            }
+            remaining -= matches.len();
+            if archive.manifest.shards.is_some() {
+                println!("Match in {}: {}", archive.short_id, archive_dir.display());
+                for (number, file, line) in matches {
+                    println!("  Line {} ({}): {}", number, file, line);
+                }
+            } else {
+                println!("Match in {}: {}", archive.short_id, files[0].display());
                 // Print matching lines
-                for (i, line) in content.lines().enumerate() {
-                    if line.contains(&pattern) {
-                        println!("  Line {}: {}", i + 1, line);
-                    }
+                for (number, _, line) in matches {
+                    println!("  Line {}: {}", number, line);
                 }
             }
         }
@@ -453,6 +665,24 @@ pub fn search_archives(pattern: String, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Up to `limit` lines of a session containing `pattern`, streamed across
+/// its files: (line number counting from the first file, file name, line).
+/// Nothing past the last match is read.
+fn grep_session(files: &[PathBuf], pattern: &str, limit: usize) -> Vec<(usize, String, String)> {
+    jsonl::open_all(files)
+        .map_while(Result::ok)
+        .filter(|(_, line)| line.text.contains(pattern))
+        .take(limit)
+        .map(|(path, line)| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (line.number, name, line.text)
+        })
+        .collect()
+}
+
 /// Migrate all v1 archives to v2 (extract images to files)
 pub fn migrate_archives(
     dry_run: bool,
@@ -482,7 +712,7 @@ pub fn migrate_archives(
     // Find archives that need migration (version < 2 or missing version)
     let mut to_migrate = Vec::new();
     for archive in archives {
-        if archive.manifest.version < 2 {
+        if archive.manifest.version < MANIFEST_VERSION {
             to_migrate.push(archive);
         }
     }
@@ -519,58 +749,7 @@ pub fn migrate_archives(
         }
 
         if !dry_run {
-            // Create backup of original session.jsonl
-            let backup_file = archive_dir.join("session.jsonl.bak");
-            fs::copy(&session_file, &backup_file).context("Failed to create backup")?;
-
-            // Create images directory
-            let images_dir = archive_dir.join("images");
-            fs::create_dir_all(&images_dir)?;
-
-            // Extract images from session.jsonl
-            let session_content = fs::read_to_string(&session_file)?;
-            let (modified_session_content, mut all_images) =
-                extract_images_from_jsonl(&session_content, &images_dir)?;
-
-            // Write back modified session.jsonl
-            fs::write(&session_file, modified_session_content)?;
-
-            // Process agent files if they exist
-            let agents_dir = archive_dir.join("agents");
-            if agents_dir.exists() {
-                for entry in fs::read_dir(&agents_dir)? {
-                    let entry = entry?;
-                    let path = entry.path();
-
-                    if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                        if verbose {
-                            println!(
-                                "  Processing agent file: {}",
-                                path.file_name().unwrap().to_string_lossy()
-                            );
-                        }
-
-                        // Backup agent file
-                        let backup_path = path.with_extension("jsonl.bak");
-                        fs::copy(&path, &backup_path)?;
-
-                        // Extract images from agent file
-                        let agent_content = fs::read_to_string(&path)?;
-                        let (modified_agent_content, agent_images) =
-                            extract_images_from_jsonl(&agent_content, &images_dir)?;
-
-                        // Merge agent images (deduplicate)
-                        for img in agent_images {
-                            if !all_images.iter().any(|existing| existing.hash == img.hash) {
-                                all_images.push(img);
-                            }
-                        }
-
-                        // Write back modified agent file
-                        fs::write(&path, modified_agent_content)?;
-                    }
-                }
-            }
+            let all_images = extract_archive_images(&archive_dir, &[session_file], verbose)?;
 
             // Calculate total bytes saved
             let bytes_saved: u64 = all_images.iter().map(|img| img.size_bytes).sum();
@@ -578,7 +757,7 @@ pub fn migrate_archives(
 
             // Update manifest to v2
             let mut manifest = archive.manifest.clone();
-            manifest.version = 2;
+            manifest.version = MANIFEST_VERSION;
             manifest.image_count = Some(all_images.len());
             manifest.images = Some(all_images.clone());
 
@@ -602,18 +781,9 @@ pub fn migrate_archives(
             let image_count = count_images_in_jsonl(&session_content)?;
 
             // Count images in agent files too
-            let agents_dir = archive_dir.join("agents");
             let mut total_archive_images = image_count;
-
-            if agents_dir.exists() {
-                for entry in fs::read_dir(&agents_dir)? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                        let agent_content = fs::read_to_string(&path)?;
-                        total_archive_images += count_images_in_jsonl(&agent_content)?;
-                    }
-                }
+            for path in agent_files(&archive_dir)? {
+                total_archive_images += count_images_in_jsonl(&fs::read_to_string(&path)?)?;
             }
 
             total_images += total_archive_images;
@@ -643,6 +813,262 @@ pub fn migrate_archives(
     Ok(())
 }
 
+/// Bring every archive's manifest up to date: v1 archives have their images
+/// extracted to images/, and each manifest's version, counts, agent list and
+/// spawn points are recomputed from what's on disk
+pub fn upgrade_manifests(dry_run: bool, verbose: bool) -> Result<()> {
+    let codex_dir = get_codex_dir()?;
+    if !codex_dir.exists() {
+        println!("No archives found (codex directory doesn't exist)");
+        return Ok(());
+    }
+    let mut archives = collect_archives(&codex_dir)?;
+    if archives.is_empty() {
+        println!("No archives found");
+        return Ok(());
+    }
+    archives.sort_by(|a, b| a.dir_name.cmp(&b.dir_name));
+
+    if dry_run {
+        println!("[DRY RUN MODE - No changes will be made]\n");
+    }
+
+    let mut upgraded = 0;
+    for archive in &archives {
+        let archive_dir = codex_dir.join(&archive.dir_name);
+        let changes = upgrade_archive(&archive_dir, &archive.manifest, dry_run, verbose)
+            .with_context(|| format!("Failed to upgrade {}", archive.dir_name))?;
+        if changes.is_empty() {
+            if verbose {
+                println!("  {} is up to date", archive.dir_name);
+            }
+            continue;
+        }
+        upgraded += 1;
+        println!(
+            "  {} {}: {}",
+            if dry_run {
+                "Would upgrade"
+            } else {
+                "✓ Upgraded"
+            },
+            archive.dir_name,
+            changes.join(", ")
+        );
+    }
+
+    println!(
+        "\n{} of {} archive(s) {}",
+        upgraded,
+        archives.len(),
+        if dry_run {
+            "need upgrading"
+        } else {
+            "upgraded"
+        }
+    );
+    if dry_run && upgraded > 0 {
+        println!("Run without --dry-run to upgrade them");
+    }
+    Ok(())
+}
+
+/// Upgrade one archive's manifest in place, returning what changed (nothing
+/// when it was already current). With `dry_run` nothing is written and
+/// image extraction is only counted.
+fn upgrade_archive(
+    archive_dir: &Path,
+    manifest: &Manifest,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<Vec<String>> {
+    let mut upgraded = manifest.clone();
+    let mut changes = Vec::new();
+    let files = session_files(archive_dir, Some(manifest));
+
+    if manifest.version < MANIFEST_VERSION {
+        changes.push(format!(
+            "version {} -> {}",
+            manifest.version, MANIFEST_VERSION
+        ));
+        upgraded.version = MANIFEST_VERSION;
+        if dry_run {
+            let mut inline = 0;
+            for path in files.iter().cloned().chain(agent_files(archive_dir)?) {
+                inline += count_images_in_jsonl(&fs::read_to_string(&path)?)?;
+            }
+            if inline > 0 {
+                changes.push(format!("{} inline image(s) to extract", inline));
+            }
+        } else if !files.is_empty() {
+            let images = extract_archive_images(archive_dir, &files, verbose)?;
+            if !images.is_empty() {
+                changes.push(format!("{} image(s) extracted", images.len()));
+            }
+            upgraded.images = Some(images);
+        }
+    }
+
+    // Counts, checksum and size describe the session as archived; a v1
+    // archive just had its images taken out, so its old checksum and size
+    // no longer match what's on disk
+    if !files.is_empty() {
+        let (message_count, checksum) = scan_files(&files)?;
+        upgraded.message_count = message_count;
+        if manifest.version < MANIFEST_VERSION {
+            upgraded.checksum = checksum;
+            upgraded.size_bytes = files
+                .iter()
+                .map(|f| fs::metadata(f).map(|m| m.len()))
+                .sum::<std::io::Result<u64>>()?;
+        }
+    }
+    if let Some(images) = &upgraded.images {
+        upgraded.image_count = Some(images.len());
+    }
+
+    // Agents: whatever is in agents/, with message counts and, where the
+    // manifest never recorded them, the tool calls that spawned them
+    if !files.is_empty() {
+        let mut agents = Vec::new();
+        for path in agent_files(archive_dir)? {
+            let file = format!(
+                "agents/{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            );
+            let (messages, _) = scan_session(&path)?;
+            let known = manifest.agents.iter().find(|a| a.file == file);
+            agents.push(AgentInfo {
+                id: known.map_or_else(|| path.display().to_string(), |a| a.id.clone()),
+                file,
+                messages,
+                spawned_by: known.and_then(|a| a.spawned_by.clone()),
+            });
+        }
+        if agents.iter().any(|a| a.spawned_by.is_none()) {
+            for spawned in spawned_agents(archive_dir, &files, Some(&upgraded))? {
+                if let Some(agent) = agents.iter_mut().find(|a| {
+                    a.spawned_by.is_none() && a.file == format!("agents/{}.jsonl", spawned.name)
+                }) {
+                    agent.spawned_by = spawned.spawned_by;
+                }
+            }
+        }
+        upgraded.agent_count = agents.len();
+        upgraded.agents = agents;
+    }
+
+    changes.extend(manifest_changes(manifest, &upgraded));
+    if !dry_run && !changes.is_empty() {
+        fs::write(
+            archive_dir.join("manifest.json"),
+            serde_json::to_string_pretty(&upgraded)?,
+        )?;
+    }
+    Ok(changes)
+}
+
+/// The recomputed manifest fields that differ, for reporting
+fn manifest_changes(old: &Manifest, new: &Manifest) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.message_count != new.message_count {
+        changes.push(format!(
+            "message_count {} -> {}",
+            old.message_count, new.message_count
+        ));
+    }
+    if old.agent_count != new.agent_count {
+        changes.push(format!(
+            "agent_count {} -> {}",
+            old.agent_count, new.agent_count
+        ));
+    }
+    let spawns = |m: &Manifest| m.agents.iter().filter(|a| a.spawned_by.is_some()).count();
+    if spawns(new) > spawns(old) {
+        changes.push(format!(
+            "{} spawn point(s) recorded",
+            spawns(new) - spawns(old)
+        ));
+    }
+    let messages = |m: &Manifest| {
+        m.agents
+            .iter()
+            .map(|a| (a.file.clone(), a.messages))
+            .collect::<Vec<_>>()
+    };
+    if old.agent_count == new.agent_count && messages(old) != messages(new) {
+        changes.push("agent message counts".to_string());
+    }
+    if old.image_count != new.image_count {
+        changes.push(format!(
+            "image_count {} -> {}",
+            old.image_count
+                .map_or("none".to_string(), |n| n.to_string()),
+            new.image_count
+                .map_or("none".to_string(), |n| n.to_string())
+        ));
+    }
+    if old.checksum != new.checksum {
+        changes.push("checksum".to_string());
+    }
+    if old.size_bytes != new.size_bytes {
+        changes.push(format!(
+            "size_bytes {} -> {}",
+            old.size_bytes, new.size_bytes
+        ));
+    }
+    changes
+}
+
+/// Move the inline base64 images in an archive's session `files` and agent
+/// transcripts out to images/, keeping each original as *.jsonl.bak
+fn extract_archive_images(
+    archive_dir: &Path,
+    files: &[PathBuf],
+    verbose: bool,
+) -> Result<Vec<ImageInfo>> {
+    let images_dir = archive_dir.join("images");
+    fs::create_dir_all(&images_dir)?;
+
+    let mut all_images: Vec<ImageInfo> = Vec::new();
+    for path in files.iter().cloned().chain(agent_files(archive_dir)?) {
+        if verbose {
+            println!(
+                "  Processing {}",
+                path.strip_prefix(archive_dir).unwrap_or(&path).display()
+            );
+        }
+        fs::copy(&path, path.with_extension("jsonl.bak")).context("Failed to create backup")?;
+
+        let content = fs::read_to_string(&path)?;
+        let (modified, images) = extract_images_from_jsonl(&content, &images_dir)?;
+        for img in images {
+            if !all_images.iter().any(|existing| existing.hash == img.hash) {
+                all_images.push(img);
+            }
+        }
+        fs::write(&path, modified)?;
+    }
+    Ok(all_images)
+}
+
+/// An archive's agent transcripts (agents/*.jsonl), sorted by name
+fn agent_files(archive_dir: &Path) -> Result<Vec<PathBuf>> {
+    let agents_dir = archive_dir.join("agents");
+    if !agents_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&agents_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 /// Count images in JSONL without extracting them (for dry-run)
 fn count_images_in_jsonl(content: &str) -> Result<usize> {
     let mut count = 0;
@@ -690,20 +1116,28 @@ fn extract_images_from_jsonl(content: &str, images_dir: &Path) -> Result<(String
     let mut modified_lines = Vec::new();
 
     for line in content.lines() {
-        if line.trim().is_empty() {
-            modified_lines.push(line.to_string());
-            continue;
-        }
-
-        let mut msg: Value = serde_json::from_str(line).context("Failed to parse JSONL line")?;
+        modified_lines.push(extract_images_from_line(line, images_dir, &mut images)?);
+    }
 
-        // Process the message content
-        extract_images_from_value(&mut msg, images_dir, &mut images)?;
+    Ok((modified_lines.join("\n") + "\n", images))
+}
 
-        modified_lines.push(serde_json::to_string(&msg)?);
+/// Extract and save the images in one JSONL line, returning the modified line
+fn extract_images_from_line(
+    line: &str,
+    images_dir: &Path,
+    images: &mut Vec<ImageInfo>,
+) -> Result<String> {
+    if line.trim().is_empty() {
+        return Ok(line.to_string());
     }
 
-    Ok((modified_lines.join("\n") + "\n", images))
+    let mut msg: Value = serde_json::from_str(line).context("Failed to parse JSONL line")?;
+
+    // Process the message content
+    extract_images_from_value(&mut msg, images_dir, images)?;
+
+    Ok(serde_json::to_string(&msg)?)
 }
 
 /// Recursively walk JSON value and extract images
@@ -826,12 +1260,12 @@ fn save_image(
 /// Generate a clean transcript from JSONL, including agent sub-session transcripts.
 /// Each agent's transcript is appended with a separator and heading.
 fn generate_clean_transcript_with_agents(
-    session_content: &str,
+    session: impl Iterator<Item = Result<Line>>,
     agent_sessions: &[(String, String)], // (agent_name, jsonl_content)
     user_name: &str,
     assistant_name: &str,
 ) -> Result<String> {
-    let mut output = generate_clean_transcript(session_content, user_name, assistant_name)?;
+    let mut output = clean_transcript(session, user_name, assistant_name)?;
 
     for (agent_name, agent_content) in agent_sessions {
         let agent_transcript = generate_clean_transcript(agent_content, user_name, assistant_name)?;
@@ -851,35 +1285,40 @@ fn generate_clean_transcript_with_agents(
 fn build_agent_type_map(session_content: &str) -> HashMap<String, String> {
     let mut map = HashMap::new();
     for line in session_content.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        let msg: Value = match serde_json::from_str(line) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
-        // Look for assistant messages with tool_use blocks named "Agent"
-        if msg["type"].as_str() != Some("assistant") {
-            continue;
-        }
-        if let Some(blocks) = msg["message"]["content"].as_array() {
-            for block in blocks {
-                if block["type"].as_str() == Some("tool_use")
-                    && block["name"].as_str() == Some("Agent")
-                    && let Some(input) = block["input"].as_object()
-                    && let Some(subagent_type) = input.get("subagent_type").and_then(|v| v.as_str())
-                {
-                    // The agentId may appear in the tool result as the tool_use id,
-                    // but more commonly is extracted from the agent filename.
-                    // The id field of the tool_use block is the tool_use_id.
-                    if let Some(tool_use_id) = block["id"].as_str() {
-                        map.insert(tool_use_id.to_string(), subagent_type.to_string());
-                    }
+        note_agent_type(line, &mut map);
+    }
+    map
+}
+
+/// Add one JSONL line's Agent calls to an agent type map
+fn note_agent_type(line: &str, map: &mut HashMap<String, String>) {
+    if line.trim().is_empty() || !line.contains("\"subagent_type\"") {
+        return;
+    }
+    let msg: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    // Look for assistant messages with tool_use blocks named "Agent"
+    if msg["type"].as_str() != Some("assistant") {
+        return;
+    }
+    if let Some(blocks) = msg["message"]["content"].as_array() {
+        for block in blocks {
+            if block["type"].as_str() == Some("tool_use")
+                && block["name"].as_str() == Some("Agent")
+                && let Some(input) = block["input"].as_object()
+                && let Some(subagent_type) = input.get("subagent_type").and_then(|v| v.as_str())
+            {
+                // The agentId may appear in the tool result as the tool_use id,
+                // but more commonly is extracted from the agent filename.
+                // The id field of the tool_use block is the tool_use_id.
+                if let Some(tool_use_id) = block["id"].as_str() {
+                    map.insert(tool_use_id.to_string(), subagent_type.to_string());
                 }
             }
         }
     }
-    map
 }
 
 /// Resolve an agent name using the agent type map if possible, falling back to the hex ID.
@@ -918,6 +1357,117 @@ fn agent_name_from_path(path: &Path) -> String {
         .to_string()
 }
 
+/// Map agent ids (the hex in agent-<id>.jsonl) to the tool_use_id of the
+/// parent's call that spawned them, from the `toolUseResult.agentId` Claude
+/// Code records on the call's tool result
+fn build_spawn_map(session_content: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for line in session_content.lines() {
+        note_spawn(line, &mut map);
+    }
+    map
+}
+
+/// Add one JSONL line's agent spawn, if it records one, to a spawn map
+fn note_spawn(line: &str, map: &mut HashMap<String, String>) {
+    if !line.contains("\"agentId\"") {
+        return;
+    }
+    let Ok(msg) = serde_json::from_str::<Value>(line) else {
+        return;
+    };
+    let Some(agent_id) = msg["toolUseResult"]["agentId"].as_str() else {
+        return;
+    };
+    if let Some(blocks) = msg["message"]["content"].as_array()
+        && let Some(tool_use_id) = blocks
+            .iter()
+            .find(|b| b["type"].as_str() == Some("tool_result"))
+            .and_then(|b| b["tool_use_id"].as_str())
+    {
+        map.insert(agent_id.to_string(), tool_use_id.to_string());
+    }
+}
+
+/// The tool_use_id that spawned agent `hex_id`: recorded in the spawn map,
+/// or else an Agent call whose id ends with it (see
+/// [`resolve_agent_display_name`])
+fn spawning_tool_use(
+    hex_id: &str,
+    spawns: &HashMap<String, String>,
+    agent_type_map: &HashMap<String, String>,
+) -> Option<String> {
+    if let Some(tool_use_id) = spawns.get(hex_id) {
+        return Some(tool_use_id.clone());
+    }
+    if hex_id.is_empty() {
+        return None;
+    }
+    agent_type_map
+        .keys()
+        .filter(|tool_use_id| tool_use_id.ends_with(hex_id))
+        .max_by_key(|tool_use_id| tool_use_id.len())
+        .cloned()
+}
+
+/// An agent transcript to show within its parent session
+struct SpawnedAgent {
+    name: String,
+    spawned_by: Option<String>,
+    content: String,
+}
+
+/// A line of the parent session, or an agent's transcript
+enum Part<'a> {
+    Line(Line),
+    Agent(&'a SpawnedAgent),
+}
+
+/// tool_use ids called in one JSONL line
+fn tool_use_ids(line: &str) -> Vec<String> {
+    if !line.contains("\"tool_use\"") {
+        return Vec::new();
+    }
+    let Ok(msg) = serde_json::from_str::<Value>(line) else {
+        return Vec::new();
+    };
+    msg["message"]["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|b| b["type"].as_str() == Some("tool_use"))
+        .filter_map(|b| b["id"].as_str().map(String::from))
+        .collect()
+}
+
+/// Pass the parent session's lines to `emit`, each agent right after the
+/// line whose tool call spawned it. Agents with no known spawn point come
+/// last.
+fn interleave_agents<'a>(
+    lines: impl Iterator<Item = Result<Line>>,
+    agents: &'a [SpawnedAgent],
+    mut emit: impl FnMut(Part<'a>) -> Result<()>,
+) -> Result<()> {
+    let mut placed = vec![false; agents.len()];
+    for line in lines {
+        let line = line?;
+        let ids = tool_use_ids(&line.text);
+        emit(Part::Line(line))?;
+        for (i, agent) in agents.iter().enumerate() {
+            if !placed[i] && agent.spawned_by.as_ref().is_some_and(|id| ids.contains(id)) {
+                placed[i] = true;
+                emit(Part::Agent(agent))?;
+            }
+        }
+    }
+    for (agent, placed) in agents.iter().zip(placed) {
+        if !placed {
+            emit(Part::Agent(agent))?;
+        }
+    }
+    Ok(())
+}
+
 // --- Clean transcript helpers ---
 
 /// Strip <system-reminder>...</system-reminder> blocks from a string
@@ -930,17 +1480,31 @@ fn generate_clean_transcript(
     session_content: &str,
     user_name: &str,
     assistant_name: &str,
+) -> Result<String> {
+    clean_transcript(
+        jsonl::read(session_content.as_bytes()),
+        user_name,
+        assistant_name,
+    )
+}
+
+/// [`generate_clean_transcript`] over session lines as they are read
+fn clean_transcript(
+    lines: impl Iterator<Item = Result<Line>>,
+    user_name: &str,
+    assistant_name: &str,
 ) -> Result<String> {
     let mut output = String::new();
     let user_prefix = format!("**{}:**", user_name);
     let assistant_prefix = format!("**{}:**", assistant_name);
 
-    for line in session_content.lines() {
-        if line.trim().is_empty() {
+    for line in lines {
+        let line = line?;
+        if line.is_blank() {
             continue;
         }
 
-        let msg: Value = match serde_json::from_str(line) {
+        let msg: Value = match line.parse() {
             Ok(v) => v,
             Err(_) => continue, // skip malformed lines
         };
@@ -1001,7 +1565,6 @@ fn migrate_clean_transcripts(
 
     for archive in archives {
         let archive_dir = codex_dir.join(&archive.dir_name);
-        let session_file = archive_dir.join("session.jsonl");
         let transcript_file = archive_dir.join("conversation.md");
 
         if transcript_file.exists() {
@@ -1015,7 +1578,7 @@ fn migrate_clean_transcripts(
             continue;
         }
 
-        if !session_file.exists() {
+        if session_files(&archive_dir, Some(&archive.manifest)).is_empty() {
             // Clean-only archive or missing JSONL — can't generate
             if verbose {
                 println!(
@@ -1051,15 +1614,17 @@ fn migrate_clean_transcripts(
 
     for archive in &needs_transcript {
         let archive_dir = codex_dir.join(&archive.dir_name);
-        let session_file = archive_dir.join("session.jsonl");
         let transcript_file = archive_dir.join("conversation.md");
         let manifest_path = archive_dir.join("manifest.json");
 
-        let session_content = fs::read_to_string(&session_file)?;
+        let files = session_files(&archive_dir, Some(&archive.manifest));
         let transcript = if include_agents {
             let agents_dir = archive_dir.join("agents");
             let mut agent_sessions = Vec::new();
-            let agent_type_map = build_agent_type_map(&session_content);
+            let mut agent_type_map = HashMap::new();
+            for line in session_lines(&files) {
+                note_agent_type(&line?.text, &mut agent_type_map);
+            }
             if agents_dir.exists() {
                 for entry in fs::read_dir(&agents_dir)? {
                     let entry = entry?;
@@ -1075,7 +1640,7 @@ fn migrate_clean_transcripts(
             let user_name = resolve_user_name();
             let assistant_name = resolve_assistant_name();
             generate_clean_transcript_with_agents(
-                &session_content,
+                session_lines(&files),
                 &agent_sessions,
                 &user_name,
                 &assistant_name,
@@ -1083,7 +1648,7 @@ fn migrate_clean_transcripts(
         } else {
             let user_name = resolve_user_name();
             let assistant_name = resolve_assistant_name();
-            generate_clean_transcript(&session_content, &user_name, &assistant_name)?
+            clean_transcript(session_lines(&files), &user_name, &assistant_name)?
         };
 
         fs::write(&transcript_file, &transcript)?;
@@ -1135,7 +1700,7 @@ fn resolve_session_path(path: Option<String>) -> Result<PathBuf> {
     }
 }
 
-fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Result<()> {
+fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Result<PathBuf> {
     if !session_path.exists() {
         anyhow::bail!("Session file not found: {:?}", session_path);
     }
@@ -1162,14 +1727,8 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         .and_then(|n| n.to_str())
         .map(|s| s.to_string());
 
-    // Count messages
-    let content = fs::read_to_string(session_path)?;
-    let message_count = content.lines().filter(|l| !l.trim().is_empty()).count();
-
-    // Calculate checksum
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    let checksum = format!("sha256:{:x}", hasher.finalize());
+    // Count messages and calculate checksum without holding the whole file
+    let (message_count, checksum) = scan_session(session_path)?;
 
     // Determine session start/end from file times
     let session_start: DateTime<Utc> = modified.into();
@@ -1180,12 +1739,17 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
     fs::create_dir_all(&codex_dir)?;
 
     // Generate archive directory name
-    let short_uuid = &session_id[0..8.min(session_id.len())];
+    let short_uuid = crate::text::truncate(&session_id, 8);
     let timestamp = session_start.format("%Y-%m-%d-%H%M%S");
     let base_name = format!("{}-{}", timestamp, short_uuid);
 
     // Check for existing archives and determine incremental suffix
     let archive_dir = determine_archive_dir(&codex_dir, &base_name)?;
+    // Archives without a manifest are invisible but still take a name; if we
+    // die before writing it, the journal removes the directory
+    let pending = Journal::open(crate::paths::journal_dir()).begin(Op::ArchiveSession {
+        archive_dir: archive_dir.clone(),
+    })?;
     fs::create_dir_all(&archive_dir)?;
 
     if clean {
@@ -1195,7 +1759,13 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         let images_dir = archive_dir.join("images");
         fs::create_dir_all(&images_dir)?;
 
-        let (_stripped_content, mut all_images) = extract_images_from_jsonl(&content, &images_dir)?;
+        let mut all_images = Vec::new();
+        let mut agent_type_map = HashMap::new();
+        for line in jsonl::open(session_path)? {
+            let line = line?;
+            extract_images_from_line(&line.text, &images_dir, &mut all_images)?;
+            note_agent_type(&line.text, &mut agent_type_map);
+        }
 
         // Find associated agent sessions and extract images from them too (no file copy)
         let agents = find_agent_sessions(session_path, &modified)?;
@@ -1218,7 +1788,6 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         let image_count = all_images.len();
 
         // Generate clean transcript (optionally with agent conversations)
-        let agent_type_map = build_agent_type_map(&content);
         let transcript = if include_agents && !agents.is_empty() {
             let mut agent_sessions = Vec::new();
             for agent in &agents {
@@ -1230,13 +1799,13 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
             }
             agent_sessions.sort_by(|a, b| a.0.cmp(&b.0));
             generate_clean_transcript_with_agents(
-                &content,
+                jsonl::open(session_path)?,
                 &agent_sessions,
                 &user_name,
                 &assistant_name,
             )?
         } else {
-            generate_clean_transcript(&content, &user_name, &assistant_name)?
+            clean_transcript(jsonl::open(session_path)?, &user_name, &assistant_name)?
         };
         let conversation_md_path = archive_dir.join("conversation.md");
         fs::write(&conversation_md_path, &transcript)?;
@@ -1249,7 +1818,7 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         let archive_size_bytes = md_size + images_size;
 
         let manifest = Manifest {
-            version: 2,
+            version: MANIFEST_VERSION,
             session_id: session_id.clone(),
             archived_at: Utc::now(),
             session_start,
@@ -1265,10 +1834,12 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
             has_clean_transcript: Some(true),
             user_name: Some(user_name.clone()),
             assistant_name: Some(assistant_name.clone()),
+            shards: None,
         };
 
         let manifest_json = serde_json::to_string_pretty(&manifest)?;
         fs::write(archive_dir.join("manifest.json"), manifest_json)?;
+        pending.finish()?;
 
         println!("Archived session (clean) to: {}", archive_dir.display());
         println!("  Messages: {}", message_count);
@@ -1276,25 +1847,40 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         println!("  Size: {} KB", archive_size_bytes / 1024);
         println!("  conversation.md written");
 
-        return Ok(());
+        return Ok(archive_dir);
     }
 
     // Full mode (default): find agents, extract images, copy JSONL
 
-    // Find associated agent sessions
-    let agents = find_agent_sessions(session_path, &modified)?;
-
     // Create images directory for extracted images
     let images_dir = archive_dir.join("images");
     fs::create_dir_all(&images_dir)?;
 
-    // Extract images from session file and save modified content
-    let session_content = fs::read_to_string(session_path)?;
-    let (modified_session_content, mut all_images) =
-        extract_images_from_jsonl(&session_content, &images_dir)?;
-
-    let dest_session = archive_dir.join("session.jsonl");
-    fs::write(&dest_session, modified_session_content)?;
+    // Stream the session a line at a time, extracting images and noting agent
+    // spawns; sessions over the shard size are split across numbered files
+    let shard_limit = shard_bytes()?.filter(|&limit| size_bytes > limit);
+    let mut writer = SessionWriter::new(&archive_dir, shard_limit);
+    let mut all_images = Vec::new();
+    let mut spawns = HashMap::new();
+    let mut agent_type_map = HashMap::new();
+    for line in jsonl::open(session_path)? {
+        let line = line?;
+        note_spawn(&line.text, &mut spawns);
+        note_agent_type(&line.text, &mut agent_type_map);
+        writer.write_line(&extract_images_from_line(
+            &line.text,
+            &images_dir,
+            &mut all_images,
+        )?)?;
+    }
+    let shards = writer.finish()?;
+
+    // Find associated agent sessions and the tool calls that spawned them
+    let mut agents = find_agent_sessions(session_path, &modified)?;
+    for agent in &mut agents {
+        let hex_id = agent_name_from_path(Path::new(&agent.file));
+        agent.spawned_by = spawning_tool_use(&hex_id, &spawns, &agent_type_map);
+    }
 
     // Copy agent files and extract images from them too
     if !agents.is_empty() {
@@ -1327,7 +1913,7 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
     // Create manifest (v2 with image support)
     let image_count = all_images.len();
     let manifest = Manifest {
-        version: 2,
+        version: MANIFEST_VERSION,
         session_id: session_id.clone(),
         archived_at: Utc::now(),
         session_start,
@@ -1343,18 +1929,114 @@ fn archive_session(session_path: &Path, clean: bool, include_agents: bool) -> Re
         has_clean_transcript: None,
         user_name: Some(user_name),
         assistant_name: Some(assistant_name),
+        shards: shards.clone(),
     };
 
     let manifest_json = serde_json::to_string_pretty(&manifest)?;
     fs::write(archive_dir.join("manifest.json"), manifest_json)?;
+    pending.finish()?;
 
     println!("Archived session to: {}", archive_dir.display());
     println!("  Messages: {}", message_count);
     println!("  Agents: {}", agents.len());
     println!("  Images: {}", image_count);
     println!("  Size: {} KB", size_bytes / 1024);
+    if let Some(shards) = &shards {
+        println!("  Shards: {}", shards.len());
+    }
 
-    Ok(())
+    Ok(archive_dir)
+}
+
+/// Message count and sha256 checksum of a session file, read in chunks
+fn scan_session(path: &Path) -> Result<(usize, String)> {
+    scan_files(&[path.to_path_buf()])
+}
+
+/// Message count and sha256 checksum of `files` back to back
+fn scan_files(files: &[PathBuf]) -> Result<(usize, String)> {
+    let mut hasher = Sha256::new();
+    let mut message_count = 0;
+    let mut line = Vec::new();
+    for path in files {
+        let mut reader = BufReader::new(File::open(path)?);
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            hasher.update(&line);
+            if !line.trim_ascii().is_empty() {
+                message_count += 1;
+            }
+            line.clear();
+        }
+    }
+    Ok((message_count, format!("sha256:{:x}", hasher.finalize())))
+}
+
+/// Writes an archived session's lines to session.jsonl or, given a shard
+/// size, to session.001.jsonl, session.002.jsonl, ... of at most that many
+/// bytes each (a single longer line gets a shard to itself)
+struct SessionWriter {
+    dir: PathBuf,
+    shard_bytes: Option<u64>,
+    out: Option<BufWriter<File>>,
+    shards: Vec<ShardInfo>,
+}
+
+impl SessionWriter {
+    fn new(dir: &Path, shard_bytes: Option<u64>) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            shard_bytes,
+            out: None,
+            shards: Vec::new(),
+        }
+    }
+
+    fn open_next(&mut self) -> Result<()> {
+        if let Some(mut out) = self.out.take() {
+            out.flush()?;
+        }
+        let file = match self.shard_bytes {
+            Some(_) => format!("session.{:03}.jsonl", self.shards.len() + 1),
+            None => "session.jsonl".to_string(),
+        };
+        self.out = Some(BufWriter::new(File::create(self.dir.join(&file))?));
+        self.shards.push(ShardInfo {
+            file,
+            lines: 0,
+            size_bytes: 0,
+        });
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        let full = match (self.shard_bytes, self.shards.last()) {
+            (_, None) => true,
+            (Some(limit), Some(shard)) => shard.lines > 0 && shard.size_bytes + len > limit,
+            (None, Some(_)) => false,
+        };
+        if full {
+            self.open_next()?;
+        }
+        let (Some(out), Some(shard)) = (self.out.as_mut(), self.shards.last_mut()) else {
+            unreachable!("a file is open after open_next");
+        };
+        writeln!(out, "{}", line)?;
+        shard.lines += 1;
+        shard.size_bytes += len;
+        Ok(())
+    }
+
+    /// Flush the last file; the shard index, or None when unsharded
+    fn finish(mut self) -> Result<Option<Vec<ShardInfo>>> {
+        if self.shards.is_empty() {
+            self.open_next()?;
+        }
+        if let Some(mut out) = self.out.take() {
+            out.flush()?;
+        }
+        Ok(self.shard_bytes.map(|_| self.shards))
+    }
 }
 
 fn find_agent_sessions(
@@ -1398,6 +2080,7 @@ fn find_agent_sessions(
                         id: path.to_string_lossy().to_string(), // Store full path temporarily
                         file: format!("agents/{}", name),
                         messages,
+                        spawned_by: None,
                     });
                 }
             }
@@ -1509,44 +2192,50 @@ fn find_archive_by_id(codex_dir: &Path, id: &str) -> Result<PathBuf> {
         }
     }
 
-    anyhow::bail!("Archive not found for id: {}", id)
+    anyhow::bail!(MxError::not_found("Archive", id))
 }
 
-fn print_human_readable(content: &str) -> Result<()> {
-    use serde_json::Value;
-
+fn print_human_readable(content: &str, raw: bool) -> Result<()> {
     for (i, line) in content.lines().enumerate() {
-        if line.trim().is_empty() {
-            continue;
-        }
+        print_human_line(i + 1, line, raw)?;
+    }
 
-        let msg: Value = serde_json::from_str(line)
-            .with_context(|| format!("Failed to parse line {}", i + 1))?;
+    Ok(())
+}
 
-        let msg_type = msg["type"].as_str().unwrap_or("unknown");
+/// Print line `number` of a session in the human-readable format
+fn print_human_line(number: usize, line: &str, raw: bool) -> Result<()> {
+    if line.trim().is_empty() {
+        return Ok(());
+    }
 
-        match msg_type {
-            "user" => {
-                if let Some(content) = msg["message"]["content"].as_str() {
-                    println!("--- User ---");
-                    println!("{}\n", content);
-                }
+    let msg: Value =
+        serde_json::from_str(line).with_context(|| format!("Failed to parse line {}", number))?;
+
+    let msg_type = msg["type"].as_str().unwrap_or("unknown");
+
+    match msg_type {
+        "user" => {
+            if let Some(content) = msg["message"]["content"].as_str() {
+                println!("--- User ---");
+                crate::output::print_markdown(content, raw);
+                println!();
             }
-            "assistant" => {
-                if let Some(blocks) = msg["message"]["content"].as_array() {
-                    println!("--- Assistant ---");
-                    for block in blocks {
-                        if let Some(text) = block["text"].as_str() {
-                            println!("{}", text);
-                        } else if let Some(tool) = block["name"].as_str() {
-                            println!("[Tool: {}]", tool);
-                        }
+        }
+        "assistant" => {
+            if let Some(blocks) = msg["message"]["content"].as_array() {
+                println!("--- Assistant ---");
+                for block in blocks {
+                    if let Some(text) = block["text"].as_str() {
+                        crate::output::print_markdown(text, raw);
+                    } else if let Some(tool) = block["name"].as_str() {
+                        println!("[Tool: {}]", tool);
                     }
-                    println!();
                 }
+                println!();
             }
-            _ => {}
         }
+        _ => {}
     }
 
     Ok(())
@@ -1578,8 +2267,7 @@ fn save_all_sessions(clean: bool, include_agents: bool) -> Result<()> {
     }
 
     // Scan for unarchived sessions
-    let mut archived_count = 0;
-
+    let mut pending = Vec::new();
     for entry in fs::read_dir(&projects_dir)? {
         let entry = entry?;
         let path = entry.path();
@@ -1604,14 +2292,31 @@ fn save_all_sessions(clean: bool, include_agents: bool) -> Result<()> {
 
                 let session_id = name.trim_end_matches(".jsonl");
                 if !archived_ids.contains(session_id) {
-                    println!("Archiving: {}", session_id);
-                    archive_session(&file_path, clean, include_agents)?;
-                    archived_count += 1;
+                    pending.push((session_id.to_string(), file_path.clone()));
                 }
             }
         }
     }
 
+    // Finished archives have manifests and are skipped next time, so Ctrl-C
+    // only has to wait for the one in hand
+    progress::catch_interrupts();
+    let total = pending.len();
+    let progress = Progress::new(total, "Archiving");
+    let mut archived_count = 0;
+    for (session_id, file_path) in pending {
+        if progress::interrupted() {
+            return Err(progress::stopped("codex save --all", archived_count, total));
+        }
+        progress.suspend(|| {
+            println!("Archiving: {}", session_id);
+            archive_session(&file_path, clean, include_agents)
+        })?;
+        archived_count += 1;
+        progress.inc();
+    }
+    progress.finish();
+
     println!("Archived {} new session(s)", archived_count);
 
     Ok(())
@@ -1875,9 +2580,13 @@ mod tests {
     #[test]
     fn agents_empty_list_same_as_plain() {
         let main_session = user_str("Hello");
-        let with_agents =
-            generate_clean_transcript_with_agents(&main_session, &[], "User", "Orchestrator")
-                .unwrap();
+        let with_agents = generate_clean_transcript_with_agents(
+            jsonl::read(main_session.as_bytes()),
+            &[],
+            "User",
+            "Orchestrator",
+        )
+        .unwrap();
         let plain = generate_clean_transcript(&main_session, "User", "Orchestrator").unwrap();
         assert_eq!(with_agents, plain);
     }
@@ -1892,7 +2601,7 @@ mod tests {
         );
 
         let result = generate_clean_transcript_with_agents(
-            &main_jsonl,
+            jsonl::read(main_jsonl.as_bytes()),
             &[("worker-1".to_string(), agent_jsonl)],
             "User",
             "Orchestrator",
@@ -1917,7 +2626,7 @@ mod tests {
         // Pass out of order; function receives pre-sorted in production,
         // but generate_clean_transcript_with_agents itself just appends in order
         let result = generate_clean_transcript_with_agents(
-            &main_jsonl,
+            jsonl::read(main_jsonl.as_bytes()),
             &[
                 ("alpha".to_string(), agent_a),
                 ("beta".to_string(), agent_b),
@@ -1942,7 +2651,7 @@ mod tests {
         let agent_jsonl = user_array().to_string();
 
         let result = generate_clean_transcript_with_agents(
-            &main_jsonl,
+            jsonl::read(main_jsonl.as_bytes()),
             &[("empty-agent".to_string(), agent_jsonl)],
             "User",
             "Orchestrator",
@@ -1960,7 +2669,7 @@ mod tests {
         let agent_jsonl = assistant_text("Answer from agent.");
 
         let result = generate_clean_transcript_with_agents(
-            &main_jsonl,
+            jsonl::read(main_jsonl.as_bytes()),
             &[("sub-1".to_string(), agent_jsonl)],
             "User",
             "Orchestrator",
@@ -2145,4 +2854,249 @@ mod tests {
             "map should be empty when subagent_type is missing"
         );
     }
+
+    // ---------------------------------------------------------------------------
+    // Spawn tracking
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn spawn_map_from_tool_result_agent_id() {
+        let jsonl = r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"done"}]},"toolUseResult":{"agentId":"a1b2c3"}}"#;
+        let spawns = build_spawn_map(jsonl);
+        assert_eq!(spawns.get("a1b2c3").map(|s| s.as_str()), Some("toolu_1"));
+        assert_eq!(
+            spawning_tool_use("a1b2c3", &spawns, &HashMap::new()).as_deref(),
+            Some("toolu_1")
+        );
+    }
+
+    #[test]
+    fn spawning_tool_use_falls_back_to_id_suffix() {
+        let mut map = HashMap::new();
+        map.insert("toolu_abc123".to_string(), "Builder".to_string());
+        let none = HashMap::new();
+        assert_eq!(
+            spawning_tool_use("abc123", &none, &map).as_deref(),
+            Some("toolu_abc123")
+        );
+        assert_eq!(spawning_tool_use("", &none, &map), None);
+        assert_eq!(spawning_tool_use("fff", &none, &map), None);
+    }
+
+    #[test]
+    fn agents_interleave_after_their_spawning_call() {
+        let session = [
+            user_str("start"),
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_b","name":"Agent","input":{}}]}}"#.to_string(),
+            user_str("middle"),
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"toolu_a","name":"Agent","input":{}}]}}"#.to_string(),
+            user_str("end"),
+        ];
+        let agent = |name: &str, spawned_by: Option<&str>| SpawnedAgent {
+            name: name.to_string(),
+            spawned_by: spawned_by.map(String::from),
+            content: String::new(),
+        };
+        let agents = [
+            agent("agent-a", Some("toolu_a")),
+            agent("agent-b", Some("toolu_b")),
+            agent("agent-orphan", None),
+        ];
+
+        let mut order = Vec::new();
+        let session = session.join("\n");
+        interleave_agents(jsonl::read(session.as_bytes()), &agents, |part| {
+            order.push(match part {
+                Part::Line(_) => "line".to_string(),
+                Part::Agent(a) => a.name.clone(),
+            });
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            order,
+            [
+                "line",
+                "line",
+                "agent-b",
+                "line",
+                "line",
+                "agent-a",
+                "line",
+                "agent-orphan"
+            ]
+        );
+    }
+
+    #[test]
+    fn shard_size_comes_from_config() {
+        let mb = 1024 * 1024;
+        assert_eq!(
+            shard_bytes_from(&serde_yaml::Mapping::new()).unwrap(),
+            Some(64 * mb)
+        );
+        let config: serde_yaml::Mapping = serde_yaml::from_str("codex:\n  shard_mb: 8\n").unwrap();
+        assert_eq!(shard_bytes_from(&config).unwrap(), Some(8 * mb));
+        let config: serde_yaml::Mapping = serde_yaml::from_str("codex:\n  shard_mb: 0\n").unwrap();
+        assert_eq!(shard_bytes_from(&config).unwrap(), None);
+        let config: serde_yaml::Mapping = serde_yaml::from_str("codex:\n  shards: 8\n").unwrap();
+        assert!(shard_bytes_from(&config).is_err());
+    }
+
+    #[test]
+    fn oversized_sessions_are_written_as_shards_and_read_back_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines: Vec<String> = (0..10)
+            .map(|i| user_str(&format!("message {}", i)))
+            .collect();
+        let line_bytes = lines[0].len() as u64 + 1;
+
+        let mut writer = SessionWriter::new(dir.path(), Some(line_bytes * 4));
+        for line in &lines {
+            writer.write_line(line).unwrap();
+        }
+        let shards = writer.finish().unwrap().unwrap();
+        let files: Vec<&str> = shards.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(
+            files,
+            [
+                "session.001.jsonl",
+                "session.002.jsonl",
+                "session.003.jsonl"
+            ]
+        );
+        assert_eq!(
+            shards.iter().map(|s| s.lines).collect::<Vec<_>>(),
+            [4, 4, 2]
+        );
+        assert!(!dir.path().join("session.jsonl").exists());
+
+        let manifest = Manifest {
+            version: 2,
+            session_id: "s".into(),
+            archived_at: Utc::now(),
+            session_start: Utc::now(),
+            session_end: Utc::now(),
+            project_path: None,
+            message_count: lines.len(),
+            agent_count: 0,
+            agents: Vec::new(),
+            size_bytes: line_bytes * 10,
+            checksum: String::new(),
+            image_count: None,
+            images: None,
+            has_clean_transcript: None,
+            user_name: None,
+            assistant_name: None,
+            shards: Some(shards),
+        };
+        let files = session_files(dir.path(), Some(&manifest));
+        let read: Vec<String> = session_lines(&files).map(|l| l.unwrap().text).collect();
+        assert_eq!(read, lines);
+
+        assert_eq!(grep_session(&files, "message", 3).len(), 3);
+        let matches = grep_session(&files, "message 9", usize::MAX);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, 10);
+        assert_eq!(matches[0].1, "session.003.jsonl");
+    }
+
+    #[test]
+    fn unsharded_sessions_keep_a_single_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = SessionWriter::new(dir.path(), None);
+        writer.write_line(&user_str("hello")).unwrap();
+        writer.write_line("").unwrap();
+        assert!(writer.finish().unwrap().is_none());
+
+        let path = dir.path().join("session.jsonl");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}\n\n", user_str("hello"))
+        );
+        assert_eq!(session_files(dir.path(), None), std::slice::from_ref(&path));
+        let (message_count, checksum) = scan_session(&path).unwrap();
+        assert_eq!(message_count, 1);
+        assert!(checksum.starts_with("sha256:"));
+    }
+
+    #[test]
+    fn upgrading_a_v1_archive_extracts_images_and_recounts() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path();
+        let image = serde_json::json!({
+            "type": "user",
+            "message": {"content": [{
+                "type": "image",
+                "source": {"type": "base64", "media_type": "image/png", "data": BASE64.encode(b"png")}
+            }]}
+        });
+        fs::write(
+            archive.join("session.jsonl"),
+            format!("{}\n{}\n\n{}\n", user_str("hi"), image, user_str("bye")),
+        )
+        .unwrap();
+        fs::create_dir_all(archive.join("agents")).unwrap();
+        fs::write(
+            archive.join("agents/agent-abc.jsonl"),
+            format!("{}\n{}\n", user_str("task"), user_str("done")),
+        )
+        .unwrap();
+        let v1: Manifest = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "session_id": "s",
+            "archived_at": "2025-01-01T00:00:00Z",
+            "session_start": "2025-01-01T00:00:00Z",
+            "session_end": "2025-01-01T00:00:00Z",
+            "project_path": null,
+            "message_count": 2,
+            "agent_count": 0,
+            "agents": [],
+            "size_bytes": 1,
+            "checksum": "sha256:old"
+        }))
+        .unwrap();
+        fs::write(
+            archive.join("manifest.json"),
+            serde_json::to_string(&v1).unwrap(),
+        )
+        .unwrap();
+
+        let planned = upgrade_archive(archive, &v1, true, false).unwrap();
+        assert_eq!(
+            planned[..2],
+            ["version 1 -> 2", "1 inline image(s) to extract"]
+        );
+        assert!(!archive.join("images").exists());
+        assert_eq!(load_manifest(archive).unwrap().version, 1);
+
+        let changes = upgrade_archive(archive, &v1, false, false).unwrap();
+        assert!(changes.contains(&"1 image(s) extracted".to_string()));
+        assert!(changes.contains(&"message_count 2 -> 3".to_string()));
+        let upgraded = load_manifest(archive).unwrap();
+        assert_eq!(upgraded.version, MANIFEST_VERSION);
+        assert_eq!(upgraded.image_count, Some(1));
+        assert_eq!(upgraded.message_count, 3);
+        assert_eq!(upgraded.agent_count, 1);
+        assert_eq!(upgraded.agents[0].file, "agents/agent-abc.jsonl");
+        assert_eq!(upgraded.agents[0].messages, 2);
+        let session = archive.join("session.jsonl");
+        assert_eq!(
+            upgraded.checksum,
+            scan_session(&session).unwrap().1,
+            "checksum describes the extracted session"
+        );
+        assert_eq!(upgraded.size_bytes, fs::metadata(&session).unwrap().len());
+        let hash = &upgraded.images.as_ref().unwrap()[0].hash;
+        assert!(archive.join(format!("images/{}.png", hash)).exists());
+        assert!(fs::read_to_string(&session).unwrap().contains("images/"));
+        assert!(archive.join("session.jsonl.bak").exists());
+
+        // A current archive is left alone
+        assert!(
+            upgrade_archive(archive, &upgraded, false, false)
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -441,9 +441,10 @@ pub fn upload_commit(
 }
 
 /// Get PR diff via gh
-fn get_pr_diff(number: u32) -> Result<String> {
+fn get_pr_diff(number: u32, repo: Option<&str>) -> Result<String> {
     let output = Command::new("gh")
         .args(["pr", "diff", &number.to_string()])
+        .args(repo_flag(repo))
         .output()
         .context("Failed to run gh pr diff")?;
 
@@ -457,14 +458,21 @@ fn get_pr_diff(number: u32) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// Merge a pull request with encoded commit message
-pub fn pr_merge(number: u32, rebase: bool, merge_commit: bool) -> Result<()> {
+/// `--repo <owner/repo>` for gh, when the repository is known
+fn repo_flag(repo: Option<&str>) -> Vec<&str> {
+    repo.map(|repo| vec!["--repo", repo]).unwrap_or_default()
+}
+
+/// Merge a pull request with encoded commit message. `repo` (owner/repo)
+/// is passed to gh; None lets gh pick the repository itself.
+pub fn pr_merge(number: u32, rebase: bool, merge_commit: bool, repo: Option<&str>) -> Result<()> {
     // Get PR diff for title hash
-    let diff = get_pr_diff(number)?;
+    let diff = get_pr_diff(number, repo)?;
 
     // Get PR info from gh
     let pr_info = Command::new("gh")
         .args(["pr", "view", &number.to_string(), "--json", "title,body"])
+        .args(repo_flag(repo))
         .output()
         .context("Failed to run gh pr view")?;
 
@@ -510,6 +518,7 @@ pub fn pr_merge(number: u32, rebase: bool, merge_commit: bool) -> Result<()> {
             "--body",
             &body_with_footer,
         ])
+        .args(repo_flag(repo))
         .output()
         .context("Failed to run gh pr merge")?;
 
@@ -0,0 +1,239 @@
+//! Confidence: how far an entry can be trusted
+//!
+//! An entry's `confidence` runs from 0.0 (a hunch) to 1.0 (battle-tested).
+//! It is set with `--confidence` on `memory add`/`update`; entries never
+//! rated count as [`UNRATED`]. `mx memory confirm <id>` records that the
+//! entry held up again: confidence moves a quarter of the way to 1.0.
+//!
+//! Some categories go stale. For those, confidence halves every
+//! `half_life_days` since the entry was last confirmed (or updated, or
+//! created). Keyword search scales each hit's text score by
+//! [`Settings::weight`], so trust reorders close matches without burying a
+//! much better one.
+//!
+//! ```yaml
+//! confidence:
+//!   half_life_days: 180
+//!   decaying: [reference, technique, gotcha]
+//! ```
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_yaml::Mapping;
+
+use std::collections::HashMap;
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore, TextScore};
+use crate::{config, dates, paths};
+
+const CONFIDENCE_KEY: &str = "confidence";
+
+/// Confidence of an entry nobody has rated
+pub const UNRATED: f64 = 0.5;
+
+/// Share of the remaining distance to 1.0 gained per confirmation
+const CONFIRM_BOOST: f64 = 0.25;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    #[serde(default = "default_half_life_days")]
+    half_life_days: f64,
+    #[serde(default = "default_decaying")]
+    decaying: Vec<String>,
+}
+
+fn default_half_life_days() -> f64 {
+    180.0
+}
+
+fn default_decaying() -> Vec<String> {
+    ["reference", "technique", "gotcha"]
+        .map(String::from)
+        .to_vec()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            half_life_days: default_half_life_days(),
+            decaying: default_decaying(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self> {
+        Self::from_config(&config::load(&paths::config_file())?)
+    }
+
+    fn from_config(config: &Mapping) -> Result<Self> {
+        match config.get(CONFIDENCE_KEY) {
+            Some(value) => serde_yaml::from_value(value.clone())
+                .context("Invalid 'confidence' section in config.yaml"),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Confidence after age decay, as of `now`
+    pub fn effective(&self, entry: &KnowledgeEntry, now: DateTime<Utc>) -> f64 {
+        let base = entry.confidence.unwrap_or(UNRATED);
+        if !self.decaying.contains(&entry.category_id) || self.half_life_days <= 0.0 {
+            return base;
+        }
+        let since = [&entry.confirmed_at, &entry.updated_at, &entry.created_at]
+            .into_iter()
+            .find_map(|at| at.as_deref().and_then(dates::parse_stored));
+        let Some(since) = since else {
+            return base;
+        };
+        let days = (now - since).num_seconds().max(0) as f64 / 86_400.0;
+        base * 0.5f64.powf(days / self.half_life_days)
+    }
+
+    /// Ranking multiplier on an entry's text score: 0.5 + effective
+    /// confidence, so 1.0 unrated and never more than 3x between two entries
+    pub fn weight(&self, entry: &KnowledgeEntry, now: DateTime<Utc>) -> f64 {
+        0.5 + self.effective(entry, now)
+    }
+
+    /// Best text score times [`Self::weight`] first; entries that tie (or
+    /// have no score) keep their order
+    pub fn rank(
+        &self,
+        entries: &mut [KnowledgeEntry],
+        scores: &HashMap<String, TextScore>,
+        now: DateTime<Utc>,
+    ) {
+        let score = |e: &KnowledgeEntry| {
+            scores.get(&e.id).map_or(0.0, TextScore::total) * self.weight(e, now)
+        };
+        entries.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    }
+}
+
+/// Confidence after one more confirmation
+pub fn confirmed(current: Option<f64>) -> f64 {
+    let c = current.unwrap_or(UNRATED);
+    c + (1.0 - c) * CONFIRM_BOOST
+}
+
+#[derive(clap::Args)]
+pub struct ConfirmArgs {
+    /// Entry IDs that held up again
+    #[arg(required = true)]
+    pub ids: Vec<String>,
+}
+
+/// `mx memory confirm`: boost each entry and restart its decay
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ConfirmArgs) -> Result<()> {
+    let now = Utc::now();
+    for id in &args.ids {
+        let id = crate::normalize_id(id);
+        let Some(mut entry) = db.get(&id, ctx)? else {
+            bail!(MxError::not_found("Entry", id));
+        };
+        let before = entry.confidence.unwrap_or(UNRATED);
+        entry.confidence = Some(confirmed(entry.confidence));
+        entry.confirmed_at = Some(now.to_rfc3339());
+        db.upsert_knowledge(&entry)?;
+        println!(
+            "Confirmed {} {} (confidence {:.2} -> {:.2})",
+            id.yellow(),
+            entry.title,
+            before,
+            entry.confidence.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// Clap parser for `--confidence`
+pub fn parse(value: &str) -> std::result::Result<f64, String> {
+    let c: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if (0.0..=1.0).contains(&c) {
+        Ok(c)
+    } else {
+        Err(format!("confidence must be between 0.0 and 1.0, got {}", c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(category: &str, confidence: Option<f64>, confirmed: DateTime<Utc>) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("kn-{}", category),
+            "category_id": category,
+            "title": "T",
+            "confidence": confidence,
+            "confirmed_at": confirmed.to_rfc3339(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stale_categories_decay_and_confirmation_boosts() {
+        let now = Utc::now();
+        let settings = Settings::default();
+        let half_year_ago = now - Duration::days(180);
+
+        let pattern = entry("pattern", Some(0.9), half_year_ago);
+        assert_eq!(settings.effective(&pattern, now), 0.9);
+        let reference = entry("reference", Some(0.9), half_year_ago);
+        assert!((settings.effective(&reference, now) - 0.45).abs() < 0.01);
+        assert_eq!(
+            settings.effective(&entry("insight", None, now), now),
+            UNRATED
+        );
+
+        // Equal text scores: confidence decides
+        let text = |total: f64| TextScore {
+            body: total,
+            ..TextScore::default()
+        };
+        let mut results = vec![reference, entry("insight", None, now), pattern];
+        let tied: HashMap<String, TextScore> = [
+            ("kn-pattern", 1.0),
+            ("kn-insight", 1.0),
+            ("kn-reference", 1.0),
+        ]
+        .map(|(id, total)| (id.to_string(), text(total)))
+        .into();
+        settings.rank(&mut results, &tied, now);
+        let order: Vec<&str> = results.iter().map(|e| e.category_id.as_str()).collect();
+        assert_eq!(order, ["pattern", "insight", "reference"]);
+
+        // A much better text match outranks a more trusted weak one
+        let spread: HashMap<String, TextScore> = [
+            ("kn-pattern", 1.0),
+            ("kn-insight", 1.5),
+            ("kn-reference", 4.0),
+        ]
+        .map(|(id, total)| (id.to_string(), text(total)))
+        .into();
+        settings.rank(&mut results, &spread, now);
+        let order: Vec<&str> = results.iter().map(|e| e.category_id.as_str()).collect();
+        assert_eq!(order, ["reference", "insight", "pattern"]);
+
+        assert_eq!(confirmed(None), 0.625);
+        assert_eq!(confirmed(Some(1.0)), 1.0);
+        assert!(parse("0.8").is_ok());
+        assert!(parse("1.5").is_err());
+        assert!(parse("high").is_err());
+
+        let config: Mapping =
+            serde_yaml::from_str("confidence:\n  half_life_days: 30\n  decaying: [pattern]\n")
+                .unwrap();
+        let custom = Settings::from_config(&config).unwrap();
+        assert!(custom.effective(&entry("pattern", Some(0.9), half_year_ago), now) < 0.1);
+    }
+}
@@ -0,0 +1,32 @@
+//! `config.yaml` access
+//!
+//! The user config is one YAML mapping per profile (see
+//! `paths::config_file`); each feature owns a top-level key (`categories:`,
+//! `surreal:`) and reads or rewrites only that key. Aliases are the exception:
+//! they live in the base directory's file (`paths::base_config_file`), read
+//! before the profile is known.
+
+use anyhow::{Context, Result};
+use serde_yaml::Mapping;
+use std::path::Path;
+
+/// Read the config mapping; a missing or empty file is an empty mapping
+pub fn load(path: &Path) -> Result<Mapping> {
+    if !path.exists() {
+        return Ok(Mapping::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(Mapping::new());
+    }
+    serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+pub fn save(path: &Path, config: &Mapping) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_yaml::to_string(config)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
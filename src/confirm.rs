@@ -0,0 +1,148 @@
+//! Confirmation for destructive operations
+//!
+//! Deletes and batch changes go through [`confirm`]:
+//!
+//! - `--yes` skips the question.
+//! - At a terminal, the affected counts are shown and the answer decides.
+//! - Without a terminal (scripts, cron, pipes) nobody can answer, so an
+//!   operation touching up to the threshold goes ahead and anything larger is
+//!   refused with [`MxError::Unconfirmed`].
+//!
+//! The threshold is 10 unless `config.yaml` says otherwise:
+//!
+//! ```yaml
+//! confirm:
+//!   threshold: 25
+//! ```
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_yaml::Mapping;
+use std::io::{self, IsTerminal, Write};
+
+use crate::error::MxError;
+use crate::{config, paths};
+
+const CONFIRM_KEY: &str = "confirm";
+const DEFAULT_THRESHOLD: usize = 10;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    #[serde(default = "default_threshold")]
+    threshold: usize,
+}
+
+fn default_threshold() -> usize {
+    DEFAULT_THRESHOLD
+}
+
+fn threshold_from(config: &Mapping) -> Result<usize> {
+    let Some(value) = config.get(CONFIRM_KEY) else {
+        return Ok(DEFAULT_THRESHOLD);
+    };
+    let settings: Settings = serde_yaml::from_value(value.clone())
+        .context("Invalid 'confirm' section in config.yaml")?;
+    Ok(settings.threshold)
+}
+
+/// Largest operation allowed to run without a prompt
+pub fn threshold() -> Result<usize> {
+    threshold_from(&config::load(&paths::config_file())?)
+}
+
+/// What a destructive operation affects: `(count, singular, plural)`
+pub type Affected<'a> = [(usize, &'a str, &'a str)];
+
+/// "3 entries and 1 relationship"
+pub fn describe(affected: &Affected) -> String {
+    let parts: Vec<String> = affected
+        .iter()
+        .filter(|(n, _, _)| *n > 0)
+        .map(|&(n, one, many)| format!("{} {}", n, if n == 1 { one } else { many }))
+        .collect();
+    match parts.as_slice() {
+        [] => "nothing".into(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    Proceed,
+    Ask,
+    Refuse,
+}
+
+fn decide(yes: bool, tty: bool, total: usize, threshold: usize) -> Decision {
+    match (yes, tty) {
+        (true, _) => Decision::Proceed,
+        (false, true) => Decision::Ask,
+        (false, false) if total <= threshold => Decision::Proceed,
+        (false, false) => Decision::Refuse,
+    }
+}
+
+/// Whether to go ahead with `action` (e.g. "Delete") on what's `affected`.
+/// `preview` lines are shown above the question. `Ok(false)` means the user
+/// said no; callers print "Cancelled" and return.
+pub fn confirm(action: &str, affected: &Affected, preview: &[String], yes: bool) -> Result<bool> {
+    let total: usize = affected.iter().map(|(n, _, _)| n).sum();
+    if total == 0 {
+        return Ok(true);
+    }
+    let threshold = threshold()?;
+    match decide(yes, io::stdin().is_terminal(), total, threshold) {
+        Decision::Proceed => Ok(true),
+        Decision::Refuse => bail!(MxError::Unconfirmed {
+            action: format!("{} {}", action.to_lowercase(), describe(affected)),
+            threshold,
+        }),
+        Decision::Ask => {
+            for line in preview {
+                println!("  {}", line);
+            }
+            print!("{} {}? [y/N] ", action, describe(affected));
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_follows_terminal_threshold_and_yes() {
+        assert_eq!(decide(true, false, 500, 10), Decision::Proceed);
+        assert_eq!(decide(false, true, 1, 10), Decision::Ask);
+        assert_eq!(decide(false, false, 10, 10), Decision::Proceed);
+        assert_eq!(decide(false, false, 11, 10), Decision::Refuse);
+
+        assert_eq!(
+            describe(&[
+                (3, "entry", "entries"),
+                (1, "relationship", "relationships")
+            ]),
+            "3 entries and 1 relationship"
+        );
+        assert_eq!(
+            describe(&[
+                (2, "issue", "issues"),
+                (0, "x", "xs"),
+                (1, "discussion", "discussions")
+            ]),
+            "2 issues and 1 discussion"
+        );
+
+        let config: Mapping = serde_yaml::from_str("confirm:\n  threshold: 25\n").unwrap();
+        assert_eq!(threshold_from(&config).unwrap(), 25);
+        assert_eq!(threshold_from(&Mapping::new()).unwrap(), DEFAULT_THRESHOLD);
+        let typo: Mapping = serde_yaml::from_str("confirm:\n  treshold: 25\n").unwrap();
+        assert!(threshold_from(&typo).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+//! Contradiction review: `mx memory contradictions`
+//!
+//! Candidate pairs come from two places:
+//!
+//! - entries linked by a `contradicts` relationship, and
+//! - suspects: entries in the same category sharing most of their tags whose
+//!   title or opening paragraph give opposite verdicts ("always pin the
+//!   version" against "avoid pinning").
+//!
+//! `resolve <a> <b>` settles a pair one of three ways:
+//!
+//! - `--supersede <id>`: that entry wins and gets a `supersedes` edge to the
+//!   other.
+//! - `--merge`: `b`'s body is appended to `a`, tags are unioned, and
+//!   `a -merged_from-> b` is recorded; `b` is kept for reference.
+//! - `--note <text>`: the tension is real and worth keeping. The note is
+//!   appended to both bodies, the `contradicts` link is kept (or added), and
+//!   the pair drops off the list (`--all` shows it again).
+//!
+//! Superseding and merging remove the `contradicts` link.
+
+use anyhow::{Result, bail};
+use chrono::Utc;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::merge_entry::MERGED_FROM;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::sync::merge::labels::merge_labels;
+
+pub const CONTRADICTS: &str = "contradicts";
+const SUPERSEDES: &str = "supersedes";
+
+/// Phrases that recommend something
+const AFFIRM: &[&str] = &[
+    "always",
+    "prefer",
+    "recommended",
+    "best practice",
+    "should use",
+    "works well",
+    "reliable",
+];
+
+/// Phrases that warn against something; they win over [`AFFIRM`]
+const REJECT: &[&str] = &[
+    "never",
+    "avoid",
+    "don't",
+    "do not",
+    "deprecated",
+    "broken",
+    "anti pattern",
+    "should not",
+    "shouldn't",
+    "must not",
+    "doesn't work",
+];
+
+#[derive(clap::Args)]
+pub struct ContradictionsArgs {
+    #[command(subcommand)]
+    pub action: Option<ContradictionsAction>,
+
+    /// Only pairs linked by a contradicts relationship
+    #[arg(long)]
+    pub linked: bool,
+
+    /// Include pairs whose tension has been noted
+    #[arg(long)]
+    pub all: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Subcommand)]
+pub enum ContradictionsAction {
+    /// Settle a contradicting pair
+    Resolve(ResolveArgs),
+}
+
+#[derive(clap::Args)]
+#[command(group(clap::ArgGroup::new("how").required(true).args(["supersede", "merge", "note"])))]
+pub struct ResolveArgs {
+    /// First entry of the pair (kept by --merge)
+    pub a: String,
+
+    /// Second entry of the pair
+    pub b: String,
+
+    /// The entry that is right; it supersedes the other
+    #[arg(long)]
+    pub supersede: Option<String>,
+
+    /// Fold b into a
+    #[arg(long)]
+    pub merge: bool,
+
+    /// Keep both and record why they disagree
+    #[arg(long)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Affirm,
+    Reject,
+}
+
+/// The verdict an entry gives in its title and opening paragraph
+fn verdict(entry: &KnowledgeEntry) -> Option<Verdict> {
+    let opening = entry
+        .body
+        .as_deref()
+        .and_then(|b| b.split("\n\n").map(str::trim).find(|p| !p.is_empty()))
+        .unwrap_or("");
+    let text = format!("{} {}", entry.title, opening).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let padded = format!(" {} ", words.join(" "));
+    let says = |markers: &[&str]| markers.iter().any(|m| padded.contains(&format!(" {} ", m)));
+    if says(REJECT) {
+        Some(Verdict::Reject)
+    } else if says(AFFIRM) {
+        Some(Verdict::Affirm)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pair {
+    pub a: String,
+    pub a_title: String,
+    pub b: String,
+    pub b_title: String,
+    /// "linked" or "suspected"
+    pub source: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shared_tags: Vec<String>,
+}
+
+impl Pair {
+    fn new(a: &KnowledgeEntry, b: &KnowledgeEntry, source: &'static str) -> Self {
+        Self {
+            a: a.id.clone(),
+            a_title: a.title.clone(),
+            b: b.id.clone(),
+            b_title: b.title.clone(),
+            source,
+            shared_tags: Vec::new(),
+        }
+    }
+}
+
+fn key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+fn note_marker(other: &str) -> String {
+    format!("Tension with {}:", other)
+}
+
+/// Whether `--note` has been used on this pair
+fn is_noted(a: &KnowledgeEntry, b: &KnowledgeEntry) -> bool {
+    let mentions = |e: &KnowledgeEntry, other: &str| {
+        e.body
+            .as_deref()
+            .is_some_and(|body| body.contains(&note_marker(other)))
+    };
+    mentions(a, &b.id) && mentions(b, &a.id)
+}
+
+/// Same-category entries sharing at least half of the smaller tag set and
+/// giving opposite verdicts, skipping pairs already related in `settled`
+fn suspects(
+    entries: &[KnowledgeEntry],
+    settled: &HashSet<(String, String)>,
+) -> Vec<(usize, usize, Vec<String>)> {
+    let verdicts: Vec<Option<Verdict>> = entries.iter().map(verdict).collect();
+    let mut by_tag: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (i, e) in entries.iter().enumerate() {
+        if verdicts[i].is_none() {
+            continue;
+        }
+        for tag in &e.tags {
+            by_tag
+                .entry((e.category_id.as_str(), tag.as_str()))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    let mut groups: Vec<_> = by_tag.into_values().collect();
+    groups.sort();
+    for group in groups {
+        for (n, &i) in group.iter().enumerate() {
+            for &j in &group[n + 1..] {
+                if verdicts[i] == verdicts[j] || !seen.insert((i, j)) {
+                    continue;
+                }
+                let (a, b) = (&entries[i], &entries[j]);
+                if settled.contains(&key(&a.id, &b.id)) {
+                    continue;
+                }
+                let a_tags: BTreeSet<&String> = a.tags.iter().collect();
+                let shared: Vec<String> = b
+                    .tags
+                    .iter()
+                    .filter(|t| a_tags.contains(t))
+                    .cloned()
+                    .collect();
+                if shared.len() * 2 >= a.tags.len().min(b.tags.len()) {
+                    found.push((i, j, shared));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Linked pairs, then suspects
+pub fn find(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    args: &ContradictionsArgs,
+) -> Result<Vec<Pair>> {
+    let entries = db.list_all(ctx)?;
+    let index: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.id.as_str(), i))
+        .collect();
+
+    let mut linked = HashSet::new();
+    let mut settled = HashSet::new();
+    for rel in db.list_all_relationships()? {
+        let pair = key(&rel.from_entry_id, &rel.to_entry_id);
+        match rel.relationship_type.as_str() {
+            CONTRADICTS => {
+                linked.insert(pair.clone());
+                settled.insert(pair);
+            }
+            SUPERSEDES | MERGED_FROM => {
+                settled.insert(pair);
+            }
+            _ => {}
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut linked: Vec<_> = linked.into_iter().collect();
+    linked.sort();
+    for (a, b) in &linked {
+        // Entries outside this agent's view are skipped
+        let (Some(&i), Some(&j)) = (index.get(a.as_str()), index.get(b.as_str())) else {
+            continue;
+        };
+        if args.all || !is_noted(&entries[i], &entries[j]) {
+            pairs.push(Pair::new(&entries[i], &entries[j], "linked"));
+        }
+    }
+    if !args.linked {
+        for (i, j, shared) in suspects(&entries, &settled) {
+            let mut pair = Pair::new(&entries[i], &entries[j], "suspected");
+            pair.shared_tags = shared;
+            pairs.push(pair);
+        }
+    }
+    Ok(pairs)
+}
+
+pub fn list(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &ContradictionsArgs) -> Result<()> {
+    let pairs = find(db, ctx, args)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pairs)?);
+        return Ok(());
+    }
+    if pairs.is_empty() {
+        println!("No contradictions found");
+        return Ok(());
+    }
+    for pair in &pairs {
+        let why = match pair.source {
+            "linked" => "linked as contradicting".to_string(),
+            _ => format!(
+                "opposite verdicts, shared tags: {}",
+                pair.shared_tags.join(", ")
+            ),
+        };
+        println!("{} ({})", "Pair".bold(), why.dimmed());
+        println!("  {}  {}", pair.a.yellow(), pair.a_title);
+        println!("  {}  {}", pair.b.yellow(), pair.b_title);
+        println!();
+    }
+    println!("{} pair(s). Settle one with:", pairs.len());
+    println!(
+        "  mx memory contradictions resolve <a> <b> --supersede <id> | --merge | --note \"...\""
+    );
+    Ok(())
+}
+
+pub fn resolve(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ResolveArgs) -> Result<()> {
+    let (a_id, b_id) = (crate::normalize_id(&args.a), crate::normalize_id(&args.b));
+    if a_id == b_id {
+        bail!("An entry can't contradict itself");
+    }
+    let Some(mut a) = db.get(&a_id, ctx)? else {
+        bail!(MxError::not_found("Entry", a_id));
+    };
+    let Some(mut b) = db.get(&b_id, ctx)? else {
+        bail!(MxError::not_found("Entry", b_id));
+    };
+    let agent = std::env::var("MX_CURRENT_AGENT").ok();
+    let now = Utc::now().to_rfc3339();
+
+    if let Some(note) = args.note {
+        let linked = db.list_relationships_for_entry(&a.id)?.iter().any(|r| {
+            r.relationship_type == CONTRADICTS
+                && key(&r.from_entry_id, &r.to_entry_id) == key(&a.id, &b.id)
+        });
+        for (entry, other) in [(&mut a, &b_id), (&mut b, &a_id)] {
+            db.backup_content(entry, "note", agent.as_deref())?;
+            let body = entry.body.take().unwrap_or_default();
+            let annotated = format!("{}\n\n> {} {}", body.trim_end(), note_marker(other), note);
+            entry.content_hash = Some(KnowledgeEntry::compute_hash(&annotated));
+            entry.body = Some(annotated);
+            entry.updated_at = Some(now.clone());
+            db.upsert_knowledge(entry)?;
+        }
+        if !linked {
+            db.add_relationship(&a.id, &b.id, CONTRADICTS)?;
+        }
+        println!(
+            "Noted the tension between {} and {}",
+            a.id.yellow(),
+            b.id.yellow()
+        );
+        return Ok(());
+    }
+
+    if let Some(winner) = args.supersede {
+        let winner = crate::normalize_id(&winner);
+        let loser = if winner == a.id {
+            &b.id
+        } else if winner == b.id {
+            &a.id
+        } else {
+            bail!("--supersede must name {} or {}", a.id, b.id);
+        };
+        unlink(db, &a.id, &b.id)?;
+        db.add_relationship(&winner, loser, SUPERSEDES)?;
+        println!("{} now supersedes {}", winner.yellow(), loser.yellow());
+        return Ok(());
+    }
+
+    // --merge
+    db.backup_content(&a, "merge", agent.as_deref())?;
+    let body = format!(
+        "{}\n\n## Merged from {}: {}\n\n{}",
+        a.body.as_deref().unwrap_or("").trim_end(),
+        b.id,
+        b.title,
+        b.body.as_deref().unwrap_or("").trim()
+    );
+    a.content_hash = Some(KnowledgeEntry::compute_hash(&body));
+    a.body = Some(body);
+    a.tags = merge_labels(&a.tags, &b.tags, &[]);
+    a.updated_at = Some(now);
+    db.upsert_knowledge(&a)?;
+    unlink(db, &a.id, &b.id)?;
+    db.add_relationship(&a.id, &b.id, MERGED_FROM)?;
+    println!(
+        "Merged {} into {}. {} is kept; remove it with: mx memory delete {}",
+        b.id.yellow(),
+        a.id.yellow(),
+        b.id,
+        b.id
+    );
+    Ok(())
+}
+
+/// Drop the contradicts link, whichever way it was recorded
+fn unlink(db: &dyn KnowledgeStore, a: &str, b: &str) -> Result<()> {
+    db.remove_relationship(a, b, CONTRADICTS)?;
+    db.remove_relationship(b, a, CONTRADICTS)?;
+    Ok(())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ContradictionsArgs) -> Result<()> {
+    match args.action {
+        Some(ContradictionsAction::Resolve(resolve_args)) => resolve(db, ctx, resolve_args),
+        None => list(db, ctx, &args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, category: &str, title: &str, tags: &[&str]) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": category,
+            "title": title,
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn opposite_verdicts_on_shared_tags_are_suspects() {
+        assert_eq!(
+            verdict(&entry("kn-1", "pattern", "Always pin tokio versions", &[])),
+            Some(Verdict::Affirm)
+        );
+        assert_eq!(
+            verdict(&entry(
+                "kn-2",
+                "pattern",
+                "Don't pin; it should not matter",
+                &[]
+            )),
+            Some(Verdict::Reject)
+        );
+        assert_eq!(
+            verdict(&entry("kn-3", "pattern", "Tokio runtime notes", &[])),
+            None
+        );
+
+        let entries = vec![
+            entry("kn-a", "pattern", "Always pin tokio", &["rust", "tokio"]),
+            entry(
+                "kn-b",
+                "pattern",
+                "Avoid pinning tokio",
+                &["rust", "tokio", "deps", "msrv"],
+            ),
+            entry("kn-c", "gotcha", "Avoid pinning tokio", &["rust", "tokio"]),
+            entry("kn-d", "pattern", "Never use unwrap", &["errors"]),
+            entry(
+                "kn-e",
+                "pattern",
+                "Prefer tokio mpsc",
+                &["tokio", "channels", "async", "perf"],
+            ),
+        ];
+        let found: Vec<(usize, usize, Vec<String>)> = suspects(&entries, &HashSet::new());
+        // a/b share both of a's tags and disagree; c is another category; d
+        // shares nothing; e shares one of four tags with b, under half
+        assert_eq!(
+            found,
+            vec![(0, 1, vec!["rust".to_string(), "tokio".to_string()])]
+        );
+
+        let settled = HashSet::from([key("kn-b", "kn-a")]);
+        assert!(suspects(&entries, &settled).is_empty());
+
+        let mut x = entries[0].clone();
+        let mut y = entries[1].clone();
+        assert!(!is_noted(&x, &y));
+        x.body = Some(format!(
+            "...\n\n> {} depends on the MSRV",
+            note_marker("kn-b")
+        ));
+        y.body = Some(format!("> {} see there", note_marker("kn-a")));
+        assert!(is_noted(&x, &y));
+    }
+}
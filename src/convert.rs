@@ -3,6 +3,7 @@
 use anyhow::{Context, Result};
 use chrono::DateTime;
 use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -218,23 +219,43 @@ pub fn yaml_to_markdown_file(
     repo: Option<&str>,
     dry_run: bool,
 ) -> Result<PathBuf> {
+    let yaml_data = read_yaml(input)?;
+    let filename = generate_markdown_filename(&yaml_data, input)?;
+    write_markdown(
+        input,
+        &yaml_data,
+        &filename,
+        output_dir,
+        repo,
+        &HashMap::new(),
+        dry_run,
+    )
+}
+
+fn read_yaml(input: &Path) -> Result<SyncYaml> {
     let content =
         fs::read_to_string(input).with_context(|| format!("Failed to read file: {:?}", input))?;
+    serde_yaml::from_str(&content).with_context(|| format!("Failed to parse YAML: {:?}", input))
+}
 
-    let yaml_data: SyncYaml = serde_yaml::from_str(&content)
-        .with_context(|| format!("Failed to parse YAML: {:?}", input))?;
-
+/// Write one converted file, linking `#N` references found in `links`
+fn write_markdown(
+    input: &Path,
+    yaml_data: &SyncYaml,
+    filename: &str,
+    output_dir: &Path,
+    repo: Option<&str>,
+    links: &HashMap<u64, String>,
+    dry_run: bool,
+) -> Result<PathBuf> {
     // Infer repo from parent directory name if not provided
     let repo_string = match repo {
         Some(r) => r.to_string(),
         None => infer_repo_from_path(input)?,
     };
 
-    let markdown = generate_markdown(&yaml_data, &repo_string)?;
-
-    // Generate output filename
-    let filename = generate_markdown_filename(&yaml_data, input)?;
-    let output_path = output_dir.join(&filename);
+    let markdown = generate_markdown(yaml_data, &repo_string, links)?;
+    let output_path = output_dir.join(filename);
 
     if dry_run {
         println!("Would create: {:?}", output_path);
@@ -257,7 +278,23 @@ pub fn yaml_to_markdown_file(
     Ok(output_path)
 }
 
-/// Convert directory of YAML files to markdown
+/// A converted YAML file: its parsed content and markdown filename
+struct Converted {
+    yaml: SyncYaml,
+    filename: String,
+}
+
+impl Converted {
+    /// The `#N` other files refer to it by (issues and discussions share numbers)
+    fn number(&self) -> Option<u64> {
+        self.yaml
+            .github_issue_number()
+            .or(self.yaml.metadata.github_discussion_number)
+    }
+}
+
+/// Convert directory of YAML files to markdown, linking `#N` references
+/// between the generated files and writing an `index.md` beside them
 pub fn yaml_to_markdown_directory(
     input_dir: &Path,
     output_dir: &Path,
@@ -267,31 +304,182 @@ pub fn yaml_to_markdown_directory(
     let entries = fs::read_dir(input_dir)
         .with_context(|| format!("Failed to read directory: {:?}", input_dir))?;
 
-    let mut count = 0;
-
+    let mut inputs = Vec::new();
     for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-            || path.extension().and_then(|s| s.to_str()) == Some("yml")
-        {
-            yaml_to_markdown_file(&path, output_dir, repo, dry_run)?;
-            count += 1;
+        let path = entry?.path();
+        if matches!(
+            path.extension().and_then(|s| s.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            inputs.push(path);
         }
     }
+    inputs.sort();
+
+    let mut docs = Vec::with_capacity(inputs.len());
+    for path in &inputs {
+        let yaml = read_yaml(path)?;
+        let filename = generate_markdown_filename(&yaml, path)?;
+        docs.push(Converted { yaml, filename });
+    }
+
+    let links: HashMap<u64, String> = docs
+        .iter()
+        .filter_map(|doc| Some((doc.number()?, doc.filename.clone())))
+        .collect();
+
+    for (path, doc) in inputs.iter().zip(&docs) {
+        write_markdown(
+            path,
+            &doc.yaml,
+            &doc.filename,
+            output_dir,
+            repo,
+            &links,
+            dry_run,
+        )?;
+    }
+
+    let index_path = output_dir.join(INDEX_FILENAME);
+    if docs.is_empty() {
+        // Nothing to index
+    } else if dry_run {
+        println!("Would create: {:?}", index_path);
+    } else {
+        fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create directory: {:?}", output_dir))?;
+        fs::write(&index_path, generate_index(&docs))
+            .with_context(|| format!("Failed to write file: {:?}", index_path))?;
+        println!("Created: {:?}", index_path);
+    }
 
     if dry_run {
-        println!("\nWould convert {} files", count);
+        println!("\nWould convert {} files", docs.len());
     } else {
-        println!("\nConverted {} files", count);
+        println!("\nConverted {} files", docs.len());
     }
 
     Ok(())
 }
 
+const INDEX_FILENAME: &str = "index.md";
+
+/// `index.md`: every converted file, grouped by label and by milestone
+fn generate_index(docs: &[Converted]) -> String {
+    let mut sorted: Vec<&Converted> = docs.iter().collect();
+    sorted.sort_by_key(|doc| {
+        (
+            doc.number().unwrap_or(u64::MAX),
+            doc.yaml.title().to_string(),
+        )
+    });
+
+    let mut by_label: BTreeMap<&str, Vec<&Converted>> = BTreeMap::new();
+    let mut unlabelled = Vec::new();
+    let mut by_milestone: BTreeMap<&str, Vec<&Converted>> = BTreeMap::new();
+    let mut no_milestone = Vec::new();
+    for &doc in &sorted {
+        if doc.yaml.labels().is_empty() {
+            unlabelled.push(doc);
+        }
+        for label in doc.yaml.labels() {
+            by_label.entry(label).or_default().push(doc);
+        }
+        match doc.yaml.metadata.milestone.as_deref() {
+            Some(milestone) => by_milestone.entry(milestone).or_default().push(doc),
+            None => no_milestone.push(doc),
+        }
+    }
+
+    let mut md = String::from("# Index\n\n");
+    md.push_str(&format!("{} files\n\n## By label\n\n", docs.len()));
+    for (label, group) in &by_label {
+        push_index_group(&mut md, label, group);
+    }
+    push_index_group(&mut md, "Unlabelled", &unlabelled);
+
+    if !by_milestone.is_empty() {
+        md.push_str("## By milestone\n\n");
+        for (milestone, group) in &by_milestone {
+            push_index_group(&mut md, milestone, group);
+        }
+        push_index_group(&mut md, "No milestone", &no_milestone);
+    }
+    md
+}
+
+fn push_index_group(md: &mut String, heading: &str, docs: &[&Converted]) {
+    if docs.is_empty() {
+        return;
+    }
+    md.push_str(&format!("### {}\n\n", heading));
+    for doc in docs {
+        let title = doc.yaml.title().replace('[', "\\[").replace(']', "\\]");
+        let mut notes = Vec::new();
+        if let Some(number) = doc.number() {
+            notes.push(format!("#{}", number));
+        }
+        if let Some(state) = &doc.yaml.metadata.state {
+            notes.push(state.clone());
+        }
+        md.push_str(&format!("- [{}]({})", title, doc.filename));
+        if !notes.is_empty() {
+            md.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        md.push('\n');
+    }
+    md.push('\n');
+}
+
+/// Turn `#N` into a link to N's file when it's in `links`. Code spans,
+/// fenced blocks, existing links and `owner/repo#N` are left alone.
+fn link_references(text: &str, links: &HashMap<u64, String>) -> Result<String> {
+    if links.is_empty() {
+        return Ok(text.to_string());
+    }
+    let reference = Regex::new(r"(^|[^\w/&\[#])#(\d+)\b")?;
+    let link = |prose: &str| {
+        reference
+            .replace_all(prose, |caps: &regex::Captures| {
+                let target = caps[2].parse().ok().and_then(|n: u64| links.get(&n));
+                match target {
+                    Some(file) => format!("{}[#{}]({})", &caps[1], &caps[2], file),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    };
+
+    let mut out = Vec::new();
+    let mut fenced = false;
+    for line in text.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            out.push(line.to_string());
+        } else if fenced {
+            out.push(line.to_string());
+        } else {
+            // Odd pieces between backticks are inline code
+            let pieces: Vec<String> = line
+                .split('`')
+                .enumerate()
+                .map(|(i, piece)| {
+                    if i % 2 == 0 {
+                        link(piece)
+                    } else {
+                        piece.to_string()
+                    }
+                })
+                .collect();
+            out.push(pieces.join("`"));
+        }
+    }
+    Ok(out.join("\n"))
+}
+
 /// Generate markdown content from YAML (with frontmatter for clean roundtrips)
-fn generate_markdown(yaml: &SyncYaml, repo: &str) -> Result<String> {
+fn generate_markdown(yaml: &SyncYaml, repo: &str, links: &HashMap<u64, String>) -> Result<String> {
     let mut md = String::new();
 
     // YAML frontmatter
@@ -317,6 +505,13 @@ fn generate_markdown(yaml: &SyncYaml, repo: &str) -> Result<String> {
         md.push_str(&format!("state: {}\n", state));
     }
 
+    if let Some(milestone) = &yaml.metadata.milestone {
+        md.push_str(&format!(
+            "milestone: \"{}\"\n",
+            milestone.replace('"', "\\\"")
+        ));
+    }
+
     // GitHub metadata
     if let Some(number) = yaml.github_issue_number() {
         md.push_str(&format!("github_issue: {}\n", number));
@@ -335,7 +530,7 @@ fn generate_markdown(yaml: &SyncYaml, repo: &str) -> Result<String> {
     // Body
     let body = yaml.body();
     if !body.is_empty() {
-        md.push_str(body);
+        md.push_str(&link_references(body, links)?);
         md.push_str("\n\n");
     }
 
@@ -349,7 +544,7 @@ fn generate_markdown(yaml: &SyncYaml, repo: &str) -> Result<String> {
             } else {
                 md.push_str(&format!("### {}\n", comment.author));
             }
-            md.push_str(&format!("{}\n\n", comment.body));
+            md.push_str(&format!("{}\n\n", link_references(&comment.body, links)?));
         }
     }
 
@@ -489,4 +684,32 @@ Description of the problem...
         assert!(yaml.body_markdown.contains("## Context"));
         assert!(yaml.body_markdown.contains("## Problem"));
     }
+
+    #[test]
+    fn test_directory_cross_links_and_index() {
+        let links: HashMap<u64, String> = [(12, "12-parser.md".to_string())].into();
+        let text = "Fixes #12, not #99.\nSee [#12](x) and other/repo#12 and `#12`.\n```\n#12\n```";
+        assert_eq!(
+            link_references(text, &links).unwrap(),
+            "Fixes [#12](12-parser.md), not #99.\nSee [#12](x) and other/repo#12 and `#12`.\n```\n#12\n```"
+        );
+
+        let doc = |number: u64, title: &str, labels: &[&str], milestone: Option<&str>| {
+            let mut yaml = SyncYaml::default();
+            yaml.metadata.title = Some(title.to_string());
+            yaml.metadata.labels = labels.iter().map(|l| l.to_string()).collect();
+            yaml.metadata.milestone = milestone.map(String::from);
+            yaml.metadata.github_issue_number = Some(number);
+            let filename = generate_markdown_filename(&yaml, Path::new("x.yaml")).unwrap();
+            Converted { yaml, filename }
+        };
+        let index = generate_index(&[
+            doc(12, "Parser [wip]", &["bug", "parser"], Some("v1")),
+            doc(3, "Docs", &[], None),
+        ]);
+        assert!(index.contains("### bug\n\n- [Parser \\[wip\\]](12-parser-wip.md) (#12)\n"));
+        assert!(index.contains("### Unlabelled\n\n- [Docs](3-docs.md) (#3)\n"));
+        assert!(index.contains("## By milestone\n\n### v1\n"));
+        assert!(index.contains("### No milestone\n\n- [Docs](3-docs.md)"));
+    }
 }
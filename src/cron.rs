@@ -0,0 +1,667 @@
+//! Scheduled jobs: `mx cron`
+//!
+//! Recurring mx invocations (nightly codex save, daily sync pull, ...) are
+//! declared once in `$MX_HOME/cron.yaml` and installed as native scheduler
+//! units, replacing hand-written crontab lines:
+//!
+//! - Linux: systemd user units (`~/.config/systemd/user/mx-<job>.{service,timer}`)
+//! - macOS: launchd agents (`~/Library/LaunchAgents/com.mx.<job>.plist`)
+//!
+//! Jobs run the current `mx` binary with `MX_HOME` pinned, so a job installed
+//! from a profile keeps using that profile's data.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::graph::xml_escape;
+use crate::paths;
+
+/// Built-in job definitions for the common chores
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Preset {
+    /// Nightly `mx codex save --all`
+    CodexSave,
+    /// Daily `mx sync pull <repo>` (requires --repo)
+    SyncPull,
+    /// Weekly `mx notify digest --days 7`
+    NotifyDigest,
+    /// Weekly `mx memory export --backup` (for `mx memory diff --since-backup`)
+    Backup,
+    /// Weekly `mx memory decay` (lowers resonance by each entry's decay rate)
+    Decay,
+}
+
+impl Preset {
+    fn job(self, repo: Option<&str>) -> Result<CronJob> {
+        Ok(match self {
+            Preset::CodexSave => CronJob {
+                name: "codex-save".to_string(),
+                schedule: "nightly".to_string(),
+                args: vec!["codex".into(), "save".into(), "--all".into()],
+            },
+            Preset::SyncPull => {
+                let Some(repo) = repo else {
+                    bail!("The sync-pull preset requires --repo <owner/repo>");
+                };
+                CronJob {
+                    name: format!("sync-pull-{}", repo.replace('/', "-")),
+                    schedule: "daily".to_string(),
+                    args: vec!["sync".into(), "pull".into(), repo.to_string()],
+                }
+            }
+            Preset::NotifyDigest => CronJob {
+                name: "notify-digest".to_string(),
+                schedule: "weekly mon 09:00".to_string(),
+                args: vec![
+                    "notify".into(),
+                    "digest".into(),
+                    "--days".into(),
+                    "7".into(),
+                ],
+            },
+            Preset::Backup => CronJob {
+                name: "memory-backup".to_string(),
+                schedule: "weekly sun 03:00".to_string(),
+                args: vec!["memory".into(), "export".into(), "--backup".into()],
+            },
+            Preset::Decay => CronJob {
+                name: "resonance-decay".to_string(),
+                schedule: "weekly sun 04:30".to_string(),
+                args: vec!["memory".into(), "decay".into()],
+            },
+        })
+    }
+}
+
+/// One recurring job: `mx <args...>` on `schedule`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CronJob {
+    pub name: String,
+    pub schedule: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CronConfig {
+    #[serde(default)]
+    jobs: Vec<CronJob>,
+}
+
+/// When a job fires. Parsed from `hourly`, `daily [HH:MM]`, `nightly`, or
+/// `weekly [DAY] [HH:MM]` (defaults: daily 03:00, nightly 02:00, weekly Sun 04:00)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    Hourly,
+    Daily {
+        hour: u32,
+        minute: u32,
+    },
+    /// `weekday` counts from Sunday = 0, as launchd does
+    Weekly {
+        weekday: u32,
+        hour: u32,
+        minute: u32,
+    },
+}
+
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+fn parse_time(value: &str) -> Result<(u32, u32)> {
+    let parsed = value.split_once(':').and_then(|(h, m)| {
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        (h < 24 && m < 60).then_some((h, m))
+    });
+    parsed.with_context(|| format!("Invalid time '{}': use HH:MM", value))
+}
+
+impl Schedule {
+    pub fn parse(value: &str) -> Result<Self> {
+        let lower = value.to_lowercase();
+        let mut parts = lower.split_whitespace();
+        let kind = parts.next().unwrap_or_default();
+        let rest: Vec<&str> = parts.collect();
+
+        let schedule = match (kind, rest.as_slice()) {
+            ("hourly", []) => Schedule::Hourly,
+            ("daily", []) => Schedule::Daily { hour: 3, minute: 0 },
+            ("nightly", []) => Schedule::Daily { hour: 2, minute: 0 },
+            ("daily" | "nightly", [time]) => {
+                let (hour, minute) = parse_time(time)?;
+                Schedule::Daily { hour, minute }
+            }
+            ("weekly", rest) if rest.len() <= 2 => {
+                let (mut weekday, mut hour, mut minute) = (0, 4, 0);
+                for part in rest {
+                    if part.contains(':') {
+                        (hour, minute) = parse_time(part)?;
+                    } else {
+                        weekday = WEEKDAYS
+                            .iter()
+                            .position(|d| part.starts_with(d))
+                            .with_context(|| format!("Invalid weekday '{}'", part))?
+                            as u32;
+                    }
+                }
+                Schedule::Weekly {
+                    weekday,
+                    hour,
+                    minute,
+                }
+            }
+            _ => bail!(
+                "Invalid schedule '{}': use hourly, daily [HH:MM], nightly, or weekly [DAY] [HH:MM]",
+                value
+            ),
+        };
+        Ok(schedule)
+    }
+
+    /// systemd `OnCalendar=` expression
+    pub fn on_calendar(&self) -> String {
+        match *self {
+            Schedule::Hourly => "hourly".to_string(),
+            Schedule::Daily { hour, minute } => format!("*-*-* {:02}:{:02}:00", hour, minute),
+            Schedule::Weekly {
+                weekday,
+                hour,
+                minute,
+            } => {
+                let day = WEEKDAYS[weekday as usize];
+                let mut day = day.to_string();
+                day[..1].make_ascii_uppercase();
+                format!("{} *-*-* {:02}:{:02}:00", day, hour, minute)
+            }
+        }
+    }
+
+    /// launchd `StartCalendarInterval` keys
+    fn calendar_interval(&self) -> Vec<(&'static str, u32)> {
+        match *self {
+            Schedule::Hourly => vec![("Minute", 0)],
+            Schedule::Daily { hour, minute } => vec![("Hour", hour), ("Minute", minute)],
+            Schedule::Weekly {
+                weekday,
+                hour,
+                minute,
+            } => vec![("Weekday", weekday), ("Hour", hour), ("Minute", minute)],
+        }
+    }
+}
+
+fn validate_job_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "Invalid job name '{}': use only letters, digits, '-' and '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn load_config(path: &Path) -> Result<CronConfig> {
+    if !path.exists() {
+        return Ok(CronConfig::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save_config(path: &Path, config: &CronConfig) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_yaml::to_string(config)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// ---------------------------------------------------------------------------
+// Unit generation
+// ---------------------------------------------------------------------------
+
+/// Native scheduler the jobs are installed into
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    Systemd,
+    Launchd,
+}
+
+impl Backend {
+    fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Backend::Launchd
+        } else {
+            Backend::Systemd
+        }
+    }
+
+    fn unit_dir(self) -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(match self {
+            Backend::Systemd => dirs::config_dir()
+                .unwrap_or_else(|| home.join(".config"))
+                .join("systemd")
+                .join("user"),
+            Backend::Launchd => home.join("Library").join("LaunchAgents"),
+        })
+    }
+}
+
+/// Unit base name, namespaced by profile so profiles don't clobber each other
+fn unit_name(job: &str) -> String {
+    match paths::profile() {
+        Some(profile) => format!("mx-{}-{}", profile, job),
+        None => format!("mx-{}", job),
+    }
+}
+
+/// Quote one argument for a systemd `ExecStart=` line
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if !escaped.is_empty()
+        && !escaped
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';' | '$'))
+    {
+        return escaped;
+    }
+    format!(
+        "\"{}\"",
+        escaped
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "$$")
+    )
+}
+
+fn render_service(job: &CronJob, exe: &Path, home: &Path) -> String {
+    let mut exec = systemd_quote(&exe.to_string_lossy());
+    for arg in &job.args {
+        exec.push(' ');
+        exec.push_str(&systemd_quote(arg));
+    }
+    format!(
+        "[Unit]\nDescription=mx cron: {name}\n\n[Service]\nType=oneshot\nEnvironment={env}\nExecStart={exec}\n",
+        name = job.name,
+        env = systemd_quote(&format!("MX_HOME={}", home.display())),
+        exec = exec,
+    )
+}
+
+fn render_timer(job: &CronJob, schedule: &Schedule) -> String {
+    format!(
+        "[Unit]\nDescription=mx cron timer: {}\n\n[Timer]\nOnCalendar={}\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+        job.name,
+        schedule.on_calendar()
+    )
+}
+
+fn render_plist(job: &CronJob, schedule: &Schedule, exe: &Path, home: &Path) -> String {
+    let label = format!("com.{}", unit_name(&job.name).replacen('-', ".", 1));
+    let log = home
+        .join("logs")
+        .join(format!("cron-{}.log", job.name))
+        .display()
+        .to_string();
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
+    out.push_str("<plist version=\"1.0\">\n<dict>\n");
+    out.push_str(&format!(
+        "  <key>Label</key>\n  <string>{}</string>\n",
+        xml_escape(&label)
+    ));
+    out.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+    out.push_str(&format!(
+        "    <string>{}</string>\n",
+        xml_escape(&exe.to_string_lossy())
+    ));
+    for arg in &job.args {
+        out.push_str(&format!("    <string>{}</string>\n", xml_escape(arg)));
+    }
+    out.push_str("  </array>\n");
+    out.push_str(&format!(
+        "  <key>EnvironmentVariables</key>\n  <dict>\n    <key>MX_HOME</key>\n    <string>{}</string>\n  </dict>\n",
+        xml_escape(&home.display().to_string())
+    ));
+    out.push_str("  <key>StartCalendarInterval</key>\n  <dict>\n");
+    for (key, value) in schedule.calendar_interval() {
+        out.push_str(&format!(
+            "    <key>{}</key>\n    <integer>{}</integer>\n",
+            key, value
+        ));
+    }
+    out.push_str("  </dict>\n");
+    out.push_str(&format!(
+        "  <key>StandardOutPath</key>\n  <string>{0}</string>\n  <key>StandardErrorPath</key>\n  <string>{0}</string>\n",
+        xml_escape(&log)
+    ));
+    out.push_str("</dict>\n</plist>\n");
+    out
+}
+
+/// Files to write for one job under `backend`: (file name, contents)
+fn unit_files(
+    backend: Backend,
+    job: &CronJob,
+    exe: &Path,
+    home: &Path,
+) -> Result<Vec<(String, String)>> {
+    let schedule = Schedule::parse(&job.schedule)?;
+    let base = unit_name(&job.name);
+    Ok(match backend {
+        Backend::Systemd => vec![
+            (format!("{}.service", base), render_service(job, exe, home)),
+            (format!("{}.timer", base), render_timer(job, &schedule)),
+        ],
+        Backend::Launchd => vec![(
+            format!("com.{}.plist", base.replacen('-', ".", 1)),
+            render_plist(job, &schedule, exe, home),
+        )],
+    })
+}
+
+fn run_quiet(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `mx cron add`: from a preset, or `<name> --schedule <s> -- <mx args>`
+pub fn add(
+    name: Option<String>,
+    schedule: Option<String>,
+    preset: Option<Preset>,
+    repo: Option<String>,
+    args: Vec<String>,
+) -> Result<()> {
+    let mut job = match preset {
+        Some(preset) => preset.job(repo.as_deref())?,
+        None => {
+            let Some(name) = name.clone() else {
+                bail!("Provide a job name or --preset");
+            };
+            if args.is_empty() {
+                bail!("Provide the mx command to run after `--`, e.g. `-- codex save --all`");
+            }
+            CronJob {
+                name,
+                schedule: schedule.clone().unwrap_or_else(|| "daily".to_string()),
+                args,
+            }
+        }
+    };
+    if let Some(name) = name {
+        job.name = name;
+    }
+    if let Some(schedule) = schedule {
+        job.schedule = schedule;
+    }
+    validate_job_name(&job.name)?;
+    Schedule::parse(&job.schedule)?;
+
+    let path = paths::cron_file();
+    let mut config = load_config(&path)?;
+    match config.jobs.iter_mut().find(|j| j.name == job.name) {
+        Some(existing) => *existing = job.clone(),
+        None => config.jobs.push(job.clone()),
+    }
+    save_config(&path, &config)?;
+
+    println!(
+        "Saved job '{}': mx {} ({})",
+        job.name,
+        job.args.join(" "),
+        job.schedule
+    );
+    println!("Run `mx cron install` to apply.");
+    Ok(())
+}
+
+/// `mx cron list`
+pub fn list(json: bool) -> Result<()> {
+    let config = load_config(&paths::cron_file())?;
+    let backend = Backend::current();
+    let dir = backend.unit_dir()?;
+    let installed = |job: &CronJob| {
+        let exe = PathBuf::from("mx");
+        unit_files(backend, job, &exe, paths::mx_home())
+            .map(|files| files.iter().all(|(name, _)| dir.join(name).exists()))
+            .unwrap_or(false)
+    };
+
+    if json {
+        let rows: Vec<serde_json::Value> = config
+            .jobs
+            .iter()
+            .map(|j| {
+                serde_json::json!({
+                    "name": j.name,
+                    "schedule": j.schedule,
+                    "command": j.args,
+                    "installed": installed(j),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if config.jobs.is_empty() {
+        println!("No scheduled jobs. Add one with `mx cron add --preset codex-save`.");
+        return Ok(());
+    }
+    println!(
+        "{:<24} {:<20} {:<10} COMMAND",
+        "NAME", "SCHEDULE", "INSTALLED"
+    );
+    for job in &config.jobs {
+        println!(
+            "{:<24} {:<20} {:<10} mx {}",
+            job.name,
+            job.schedule,
+            if installed(job) { "yes" } else { "no" },
+            job.args.join(" ")
+        );
+    }
+    Ok(())
+}
+
+/// `mx cron remove`: drop the job and uninstall its units
+pub fn remove(name: &str) -> Result<()> {
+    let path = paths::cron_file();
+    let mut config = load_config(&path)?;
+    let Some(idx) = config.jobs.iter().position(|j| j.name == name) else {
+        bail!("No scheduled job named '{}'", name);
+    };
+    let job = config.jobs.remove(idx);
+    save_config(&path, &config)?;
+
+    let backend = Backend::current();
+    let dir = backend.unit_dir()?;
+    let base = unit_name(&job.name);
+    if backend == Backend::Systemd {
+        run_quiet(
+            "systemctl",
+            &["--user", "disable", "--now", &format!("{}.timer", base)],
+        );
+    }
+    for (file, _) in unit_files(backend, &job, Path::new("mx"), paths::mx_home())? {
+        let unit = dir.join(&file);
+        if unit.exists() {
+            if backend == Backend::Launchd {
+                run_quiet("launchctl", &["unload", &unit.to_string_lossy()]);
+            }
+            std::fs::remove_file(&unit)
+                .with_context(|| format!("Failed to remove {}", unit.display()))?;
+        }
+    }
+    println!("Removed job '{}'", name);
+    Ok(())
+}
+
+/// `mx cron install`: write units for every job and enable them
+pub fn install(dry_run: bool) -> Result<()> {
+    let config = load_config(&paths::cron_file())?;
+    if config.jobs.is_empty() {
+        println!("No scheduled jobs to install.");
+        return Ok(());
+    }
+
+    let backend = Backend::current();
+    let dir = backend.unit_dir()?;
+    let exe = std::env::current_exe().context("Could not locate the mx binary")?;
+    let home = paths::mx_home();
+
+    let mut written = Vec::new();
+    for job in &config.jobs {
+        for (file, contents) in unit_files(backend, job, &exe, home)? {
+            let target = dir.join(&file);
+            if dry_run {
+                println!("# {}\n{}", target.display(), contents);
+            } else {
+                std::fs::create_dir_all(&dir)?;
+                std::fs::write(&target, contents)
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+                println!("Wrote {}", target.display());
+            }
+            written.push((file, target));
+        }
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    match backend {
+        Backend::Systemd => {
+            run_quiet("systemctl", &["--user", "daemon-reload"]);
+            for (file, _) in written.iter().filter(|(f, _)| f.ends_with(".timer")) {
+                if !run_quiet("systemctl", &["--user", "enable", "--now", file]) {
+                    eprintln!(
+                        "Warning: could not enable {}; run `systemctl --user enable --now {}`",
+                        file, file
+                    );
+                }
+            }
+        }
+        Backend::Launchd => {
+            std::fs::create_dir_all(home.join("logs"))?;
+            for (file, target) in &written {
+                let target = target.to_string_lossy();
+                run_quiet("launchctl", &["unload", &target]);
+                if !run_quiet("launchctl", &["load", "-w", &target]) {
+                    eprintln!(
+                        "Warning: could not load {}; run `launchctl load -w {}`",
+                        file, target
+                    );
+                }
+            }
+        }
+    }
+    println!("Installed {} job(s)", config.jobs.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> CronJob {
+        CronJob {
+            name: "codex-save".to_string(),
+            schedule: "nightly".to_string(),
+            args: vec!["codex".into(), "save".into(), "--all".into()],
+        }
+    }
+
+    #[test]
+    fn parses_schedules() {
+        assert_eq!(Schedule::parse("hourly").unwrap(), Schedule::Hourly);
+        assert_eq!(
+            Schedule::parse("nightly").unwrap(),
+            Schedule::Daily { hour: 2, minute: 0 }
+        );
+        assert_eq!(
+            Schedule::parse("daily 06:30").unwrap(),
+            Schedule::Daily {
+                hour: 6,
+                minute: 30
+            }
+        );
+        assert_eq!(
+            Schedule::parse("weekly Monday 05:15").unwrap(),
+            Schedule::Weekly {
+                weekday: 1,
+                hour: 5,
+                minute: 15
+            }
+        );
+        assert!(Schedule::parse("daily 25:00").is_err());
+        assert!(Schedule::parse("fortnightly").is_err());
+    }
+
+    #[test]
+    fn on_calendar_expressions() {
+        assert_eq!(Schedule::Hourly.on_calendar(), "hourly");
+        assert_eq!(
+            Schedule::parse("weekly").unwrap().on_calendar(),
+            "Sun *-*-* 04:00:00"
+        );
+    }
+
+    #[test]
+    fn renders_systemd_units() {
+        let exe = Path::new("/opt/my tools/mx");
+        let service = render_service(&job(), exe, Path::new("/home/u/.mx"));
+        assert!(service.contains("ExecStart=\"/opt/my tools/mx\" codex save --all"));
+        assert!(service.contains("Environment=MX_HOME=/home/u/.mx"));
+
+        let timer = render_timer(&job(), &Schedule::parse("nightly").unwrap());
+        assert!(timer.contains("OnCalendar=*-*-* 02:00:00"));
+        assert!(timer.contains("WantedBy=timers.target"));
+
+        assert_eq!(systemd_quote("100%"), "100%%");
+        assert_eq!(systemd_quote("a \"b\""), "\"a \\\"b\\\"\"");
+    }
+
+    #[test]
+    fn renders_launchd_plist() {
+        let plist = render_plist(
+            &job(),
+            &Schedule::parse("weekly tue 01:05").unwrap(),
+            Path::new("/usr/local/bin/mx"),
+            Path::new("/Users/u/.mx"),
+        );
+        assert!(plist.contains("<string>/usr/local/bin/mx</string>"));
+        assert!(plist.contains("<string>--all</string>"));
+        assert!(plist.contains("<key>Weekday</key>\n    <integer>2</integer>"));
+        assert!(plist.contains("<key>Minute</key>\n    <integer>5</integer>"));
+    }
+
+    #[test]
+    fn presets_build_jobs() {
+        assert_eq!(Preset::CodexSave.job(None).unwrap(), job());
+        let pull = Preset::SyncPull.job(Some("owner/repo")).unwrap();
+        assert_eq!(pull.name, "sync-pull-owner-repo");
+        assert_eq!(pull.args, vec!["sync", "pull", "owner/repo"]);
+        assert!(Preset::SyncPull.job(None).is_err());
+        let decay = Preset::Decay.job(None).unwrap();
+        assert_eq!(decay.args, vec!["memory", "decay"]);
+        assert!(Schedule::parse(&decay.schedule).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+//! Date display and date input
+//!
+//! Timestamps are stored as RFC 3339 UTC. For people they are shown in the
+//! local timezone with a relative hint (`2026-10-13 14:02 (2 days ago)`);
+//! the global `--utc` flag restores the raw UTC timestamps, which is also
+//! what scripts get from `--json`.
+//!
+//! Date arguments (`--as-of`, `--since`, `--until`) accept RFC 3339,
+//! `YYYY-MM-DD`, `today`, `yesterday`, `now`, and offsets such as `3d`,
+//! `2 weeks ago`, or `12h`. Calendar dates mean local midnight (UTC midnight
+//! under `--utc`).
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static UTC_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Switch display and calendar-date input to UTC (`--utc`)
+pub fn set_utc(utc: bool) {
+    UTC_OUTPUT.store(utc, Ordering::Relaxed);
+}
+
+fn utc_mode() -> bool {
+    UTC_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Parse a stored timestamp (RFC 3339, or the space-separated form older rows use)
+pub fn parse_stored(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    chrono::NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// "just now", "5 minutes ago", "yesterday", "in 3 days", ...
+fn relative(delta: Duration) -> String {
+    let future = delta < Duration::zero();
+    let secs = delta.num_seconds().abs();
+    if secs < 60 {
+        return "just now".to_string();
+    }
+    let (n, unit) = match secs {
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3600, "hour"),
+        s if s < 2 * 86_400 && !future => return "yesterday".to_string(),
+        s if s < 14 * 86_400 => (s / 86_400, "day"),
+        s if s < 60 * 86_400 => (s / (7 * 86_400), "week"),
+        s if s < 365 * 86_400 => (s / (30 * 86_400), "month"),
+        s => (s / (365 * 86_400), "year"),
+    };
+    let plural = if n == 1 { "" } else { "s" };
+    if future {
+        format!("in {} {}{}", n, unit, plural)
+    } else {
+        format!("{} {}{} ago", n, unit, plural)
+    }
+}
+
+fn display_at<Tz: TimeZone>(dt: DateTime<Utc>, now: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    format!(
+        "{} ({})",
+        dt.with_timezone(tz).format("%Y-%m-%d %H:%M"),
+        relative(now - dt)
+    )
+}
+
+/// Render a point in time for people
+pub fn display_dt(dt: DateTime<Utc>) -> String {
+    if utc_mode() {
+        return dt.to_rfc3339();
+    }
+    display_at(dt, Utc::now(), &Local)
+}
+
+/// Render a stored timestamp for people; unparseable values pass through
+pub fn display(value: &str) -> String {
+    if utc_mode() {
+        return value.to_string();
+    }
+    match parse_stored(value) {
+        Some(dt) => display_at(dt, Utc::now(), &Local),
+        None => value.to_string(),
+    }
+}
+
+/// `3d`, `12h`, `2w`, `30m`, `3 days ago`, `1 week ago`
+fn parse_offset(value: &str) -> Option<Duration> {
+    let value = value.trim_end_matches(" ago").trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let n: i64 = value[..split].parse().ok()?;
+    let unit = value[split..].trim().trim_end_matches('s');
+    match unit {
+        "m" | "min" | "minute" => Some(Duration::minutes(n)),
+        "h" | "hour" => Some(Duration::hours(n)),
+        "d" | "day" => Some(Duration::days(n)),
+        "w" | "week" => Some(Duration::weeks(n)),
+        _ => None,
+    }
+}
+
+fn midnight<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> Option<DateTime<Utc>> {
+    let naive = date.and_hms_opt(0, 0, 0)?;
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_with<Tz: TimeZone>(value: &str, now: DateTime<Utc>, tz: &Tz) -> Result<DateTime<Utc>> {
+    let trimmed = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let today = now.with_timezone(tz).date_naive();
+    let lower = trimmed.to_lowercase();
+    let date = match lower.as_str() {
+        "now" => return Ok(now),
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        _ => None,
+    };
+    let date = date.or_else(|| NaiveDate::parse_from_str(trimmed, "%Y-%m-%d").ok());
+    if let Some(date) = date {
+        return midnight(date, tz).ok_or_else(|| anyhow::anyhow!("No local midnight on {}", date));
+    }
+    if let Some(offset) = parse_offset(&lower) {
+        return Ok(now - offset);
+    }
+    bail!(
+        "Invalid date '{}': use YYYY-MM-DD, RFC 3339, today, yesterday, or an offset like 3d / 2 weeks ago",
+        value
+    )
+}
+
+/// Parse a date argument (see module docs for the accepted forms)
+pub fn parse_date(value: &str) -> Result<DateTime<Utc>> {
+    if utc_mode() {
+        parse_with(value, Utc::now(), &Utc)
+    } else {
+        parse_with(value, Utc::now(), &Local)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn relative_forms() {
+        assert_eq!(relative(Duration::seconds(20)), "just now");
+        assert_eq!(relative(Duration::minutes(1)), "1 minute ago");
+        assert_eq!(relative(Duration::hours(5)), "5 hours ago");
+        assert_eq!(relative(Duration::hours(30)), "yesterday");
+        assert_eq!(relative(Duration::days(9)), "9 days ago");
+        assert_eq!(relative(Duration::days(21)), "3 weeks ago");
+        assert_eq!(relative(Duration::days(800)), "2 years ago");
+        assert_eq!(relative(Duration::days(-3)), "in 3 days");
+    }
+
+    #[test]
+    fn displays_in_the_given_timezone() {
+        let now = at("2026-10-15T12:00:00Z");
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            display_at(at("2026-10-13T10:30:00Z"), now, &tz),
+            "2026-10-13 12:30 (2 days ago)"
+        );
+        assert_eq!(
+            parse_stored("2025-11-29 08:10:33"),
+            Some(at("2025-11-29T08:10:33Z"))
+        );
+    }
+
+    #[test]
+    fn parses_flexible_dates() {
+        let now = at("2026-10-15T01:00:00Z");
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        // 01:00 UTC is still the 14th in UTC-5
+        assert_eq!(
+            parse_with("today", now, &tz).unwrap(),
+            at("2026-10-14T05:00:00Z")
+        );
+        assert_eq!(
+            parse_with("yesterday", now, &Utc).unwrap(),
+            at("2026-10-14T00:00:00Z")
+        );
+        assert_eq!(
+            parse_with("2026-01-02", now, &Utc).unwrap(),
+            at("2026-01-02T00:00:00Z")
+        );
+        assert_eq!(
+            parse_with("2 weeks ago", now, &Utc).unwrap(),
+            now - Duration::weeks(2)
+        );
+        assert_eq!(
+            parse_with("12h", now, &Utc).unwrap(),
+            now - Duration::hours(12)
+        );
+        assert!(parse_with("someday", now, &Utc).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! Resonance decay job: `mx memory decay`
+//!
+//! Entries with a `decay_rate` lose that share of their resonance per week
+//! since they were last activated (or created). The job lowers the stored
+//! level accordingly, rounding to the nearest level and never below 1, and
+//! records when it lowered each entry in `$MX_HOME/decay.json` so the next
+//! run only counts the time since. An entry whose decay rounds to no change
+//! keeps accumulating time until it drops a whole level.
+//!
+//! With `--archive-below N`, entries whose decayed level falls under N
+//! are written to `$MX_HOME/archives/decayed-<timestamp>.jsonl` (restore
+//! with `mx memory import <file>`) and removed from the store.
+//!
+//! Foundational and transformative entries, sealed identity blooms and
+//! entries without a resonance are left alone. Meant to run from cron:
+//! `mx cron add --preset decay`.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::knowledge::KnowledgeEntry;
+use crate::paths;
+use crate::resonance::UNDECAYING;
+use crate::store::{AgentContext, KnowledgeStore};
+
+#[derive(clap::Args)]
+pub struct DecayArgs {
+    /// Archive entries whose decayed level falls below this (1 archives those that fade to 0)
+    #[arg(long)]
+    pub archive_below: Option<i32>,
+
+    /// Report what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// One entry the job lowers or archives
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Change {
+    pub id: String,
+    pub title: String,
+    pub from: i32,
+    pub to: i32,
+    /// Resonance before rounding
+    pub decayed: f64,
+    /// Weeks of decay applied
+    pub weeks: f64,
+    pub archived: bool,
+}
+
+/// What a run did
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub lowered: usize,
+    pub archived: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<PathBuf>,
+    pub changes: Vec<Change>,
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// How `entry` decays as of `now`, if it changes. `decayed_at` is when the
+/// job last lowered it.
+fn decay(
+    entry: &KnowledgeEntry,
+    decayed_at: Option<&str>,
+    now: DateTime<Utc>,
+    archive_below: Option<i32>,
+) -> Option<Change> {
+    if entry.decay_rate <= 0.0
+        || entry.resonance <= 0
+        || entry.namespace.is_some()
+        || entry
+            .resonance_type
+            .as_deref()
+            .is_some_and(|t| UNDECAYING.contains(&t))
+    {
+        return None;
+    }
+    let activated = entry
+        .last_activated
+        .as_deref()
+        .or(entry.created_at.as_deref())
+        .and_then(parse_time);
+    let since = activated.max(decayed_at.and_then(parse_time))?;
+    let weeks = (now - since).num_seconds().max(0) as f64 / 604_800.0;
+    let decayed = entry.resonance as f64 * (1.0 - entry.decay_rate).powf(weeks);
+    let rounded = decayed.round() as i32;
+    let archived = archive_below.is_some_and(|level| rounded < level);
+    let to = rounded.clamp(1, entry.resonance);
+    if to == entry.resonance && !archived {
+        return None;
+    }
+    Some(Change {
+        id: entry.id.clone(),
+        title: entry.title.clone(),
+        from: entry.resonance,
+        to,
+        decayed: (decayed * 100.0).round() / 100.0,
+        weeks: (weeks * 10.0).round() / 10.0,
+        archived,
+    })
+}
+
+fn load_state(path: &Path) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save_state(path: &Path, state: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(state)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn write_archive(path: &Path, entries: &[&KnowledgeEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Decay every entry visible to `ctx` as of `now`. Archived entries go to a
+/// file in `archive_dir`; nothing is written on a dry run.
+pub fn run_decay(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    state_path: &Path,
+    archive_dir: &Path,
+    now: DateTime<Utc>,
+    archive_below: Option<i32>,
+    dry_run: bool,
+) -> Result<Report> {
+    if archive_below.is_some_and(|level| level < 1) {
+        bail!("--archive-below must be at least 1");
+    }
+    let mut state = load_state(state_path)?;
+    let entries = db.list_all(ctx)?;
+
+    let mut report = Report::default();
+    let mut lowered = Vec::new();
+    let mut archived = Vec::new();
+    for entry in &entries {
+        let decayed_at = state.get(&entry.id).map(String::as_str);
+        if let Some(change) = decay(entry, decayed_at, now, archive_below) {
+            if change.archived {
+                archived.push(entry);
+            } else {
+                lowered.push((entry, change.to));
+            }
+            report.changes.push(change);
+        }
+    }
+    report.lowered = lowered.len();
+    report.archived = archived.len();
+    if dry_run {
+        return Ok(report);
+    }
+
+    // The archive is complete on disk before anything is removed
+    if !archived.is_empty() {
+        let path = archive_dir.join(format!("decayed-{}.jsonl", now.format("%Y%m%d-%H%M%S")));
+        write_archive(&path, &archived)?;
+        for entry in &archived {
+            db.delete(&entry.id, ctx)?;
+            state.remove(&entry.id);
+        }
+        report.archive = Some(path);
+    }
+    let stamp = now.to_rfc3339();
+    for (entry, to) in lowered {
+        let mut updated = entry.clone();
+        updated.resonance = to;
+        db.upsert_knowledge(&updated)?;
+        state.insert(entry.id.clone(), stamp.clone());
+    }
+    save_state(state_path, &state)?;
+    Ok(report)
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: DecayArgs) -> Result<()> {
+    let report = run_decay(
+        db,
+        ctx,
+        &paths::decay_state(),
+        &paths::archives_dir(),
+        Utc::now(),
+        args.archive_below,
+        args.dry_run,
+    )?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if report.changes.is_empty() {
+        println!("No entries decayed.");
+        return Ok(());
+    }
+
+    let (lower, archive) = if args.dry_run {
+        ("Would lower", "archive")
+    } else {
+        ("Lowered", "archived")
+    };
+    println!(
+        "{} {} and {} {} entries",
+        lower, report.lowered, archive, report.archived
+    );
+    for change in &report.changes {
+        let level = if change.archived {
+            format!("{:>2} -> {}", change.from, "archived".red())
+        } else {
+            format!("{:>2} -> {}", change.from, change.to)
+        };
+        println!(
+            "  {}  {}  {}",
+            change.id.yellow(),
+            level,
+            format!(
+                "{} ({:.2} after {} weeks)",
+                change.title, change.decayed, change.weeks
+            )
+            .dimmed()
+        );
+    }
+    if let Some(ref archive) = report.archive {
+        println!(
+            "\nArchived to {} (restore with: mx memory import {})",
+            archive.display(),
+            archive.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn entry(id: &str, resonance: i32, decay_rate: f64, rtype: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "bloom",
+            "title": id,
+            "resonance": resonance,
+            "resonance_type": rtype,
+            "decay_rate": decay_rate,
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_time(s).unwrap()
+    }
+
+    #[test]
+    fn decays_by_rate_since_activation_or_last_run() {
+        let e = entry("kn-a", 8, 0.1, "relational");
+        // Two weeks: 8 * 0.9^2 = 6.48
+        let change = decay(&e, None, at("2026-01-15T00:00:00Z"), None).unwrap();
+        assert_eq!((change.from, change.to, change.decayed), (8, 6, 6.48));
+
+        // Only the week since the last run counts
+        let change = decay(
+            &e,
+            Some("2026-01-08T00:00:00Z"),
+            at("2026-01-15T00:00:00Z"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(change.to, 7);
+        assert!(decay(&e, None, at("2026-01-02T00:00:00Z"), None).is_none());
+
+        assert!(
+            decay(
+                &entry("kn-b", 8, 0.0, "relational"),
+                None,
+                at("2027-01-01T00:00:00Z"),
+                None
+            )
+            .is_none()
+        );
+        assert!(
+            decay(
+                &entry("kn-c", 8, 0.5, "foundational"),
+                None,
+                at("2027-01-01T00:00:00Z"),
+                None
+            )
+            .is_none()
+        );
+
+        // Never below 1, unless archived
+        let faded = decay(
+            &entry("kn-d", 2, 0.5, "ephemeral"),
+            None,
+            at("2026-03-01T00:00:00Z"),
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!((faded.to, faded.archived), (1, true));
+    }
+
+    #[test]
+    fn lowers_archives_and_records_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("decay.json");
+        let archives = dir.path().join("archives");
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        for e in [
+            entry("kn-a", 8, 0.1, "relational"),
+            entry("kn-b", 2, 0.9, "ephemeral"),
+            entry("kn-c", 9, 0.0, "relational"),
+        ] {
+            db.upsert_knowledge(&e).unwrap();
+        }
+        let now = at("2026-01-15T00:00:00Z");
+
+        let dry = run_decay(&db, &ctx, &state, &archives, now, Some(1), true).unwrap();
+        assert_eq!((dry.lowered, dry.archived), (1, 1));
+        assert!(!state.exists());
+        assert_eq!(db.get("kn-a", &ctx).unwrap().unwrap().resonance, 8);
+
+        let report = run_decay(&db, &ctx, &state, &archives, now, Some(1), false).unwrap();
+        assert_eq!(db.get("kn-a", &ctx).unwrap().unwrap().resonance, 6);
+        assert!(db.get("kn-b", &ctx).unwrap().is_none());
+        assert_eq!(db.get("kn-c", &ctx).unwrap().unwrap().resonance, 9);
+        let archive = std::fs::read_to_string(report.archive.unwrap()).unwrap();
+        let archived: KnowledgeEntry = serde_json::from_str(archive.trim()).unwrap();
+        assert_eq!((archived.id.as_str(), archived.resonance), ("kn-b", 2));
+
+        // Running again at the same moment changes nothing
+        let again = run_decay(&db, &ctx, &state, &archives, now, Some(1), false).unwrap();
+        assert!(again.changes.is_empty());
+        assert!(load_state(&state).unwrap().contains_key("kn-a"));
+    }
+}
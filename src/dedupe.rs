@@ -0,0 +1,458 @@
+//! Duplicate detection: `mx memory dedupe` (`mx zion dedupe`)
+//!
+//! Two entries are duplicates when their bodies hash the same (exact), or when
+//! the word shingles of title and body overlap by at least `--threshold`
+//! (Jaccard similarity, near). Duplicate pairs are grouped into clusters, and
+//! each cluster keeps one entry: the highest resonance, then the oldest.
+//!
+//! `--apply` folds the rest of each cluster into its keeper:
+//!
+//! - tags, applicability and anchors are unioned,
+//! - relationships are moved over to the keeper,
+//! - body lines the keeper lacks are appended under a `Merged from` heading.
+//!
+//! The duplicates are then deleted, each backed up first as `mx memory delete`
+//! does. Sealed identity blooms are never considered.
+
+use anyhow::{Result, bail};
+use chrono::Utc;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::confirm;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::sync::merge::labels::merge_labels;
+
+/// Shingle overlap that makes two entries near-duplicates unless
+/// `--threshold` says otherwise
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// Words per shingle
+const SHINGLE: usize = 3;
+
+#[derive(clap::Args)]
+pub struct DedupeArgs {
+    /// Minimum shingle overlap (0.0-1.0) for near-duplicates
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+
+    /// Merge each cluster into its keeper and delete the duplicates
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Don't ask for confirmation
+    #[arg(long, short = 'y', requires = "apply")]
+    pub yes: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Duplicate {
+    pub id: String,
+    pub title: String,
+    /// "exact" or "near"
+    pub kind: &'static str,
+    /// Shingle overlap with the keeper (1.0 for exact duplicates)
+    pub similarity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Cluster {
+    pub keep: String,
+    pub keep_title: String,
+    pub duplicates: Vec<Duplicate>,
+}
+
+/// Lowercased word windows of title and body; short texts are one shingle
+fn shingles(entry: &KnowledgeEntry) -> HashSet<String> {
+    let text = format!("{} {}", entry.title, entry.body.as_deref().unwrap_or("")).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.len() < SHINGLE {
+        return (!words.is_empty())
+            .then(|| words.join(" "))
+            .into_iter()
+            .collect();
+    }
+    words.windows(SHINGLE).map(|w| w.join(" ")).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    let union = a.len() + b.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f64 / union as f64
+    }
+}
+
+/// Content hash of a non-empty body, for exact matching. Recomputed rather
+/// than read from the entry, as some write paths store other hashes there
+fn body_hash(entry: &KnowledgeEntry) -> Option<String> {
+    let body = entry.body.as_deref().filter(|b| !b.trim().is_empty())?;
+    Some(KnowledgeEntry::compute_hash(body))
+}
+
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Clusters of duplicates among `entries`, largest first
+pub fn find(entries: &[KnowledgeEntry], threshold: f64) -> Vec<Cluster> {
+    let entries: Vec<&KnowledgeEntry> = entries
+        .iter()
+        .filter(|e| e.namespace.as_deref() != Some("identity"))
+        .collect();
+    let hashes: Vec<Option<String>> = entries.iter().map(|e| body_hash(e)).collect();
+    let sets: Vec<HashSet<String>> = entries.iter().map(|e| shingles(e)).collect();
+
+    let mut parent: Vec<usize> = (0..entries.len()).collect();
+    let mut by_hash: HashMap<&str, usize> = HashMap::new();
+    for (i, hash) in hashes.iter().enumerate() {
+        if let Some(hash) = hash {
+            if let Some(&first) = by_hash.get(hash.as_str()) {
+                let (a, b) = (root(&mut parent, first), root(&mut parent, i));
+                parent[b] = a;
+            } else {
+                by_hash.insert(hash, i);
+            }
+        }
+    }
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let (small, large) = (
+                sets[i].len().min(sets[j].len()),
+                sets[i].len().max(sets[j].len()),
+            );
+            // Overlap can't exceed the size ratio, so skip the set work
+            if large == 0 || (small as f64) < threshold * large as f64 {
+                continue;
+            }
+            if jaccard(&sets[i], &sets[j]) >= threshold {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..entries.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+
+    let mut clusters: Vec<Cluster> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            // Highest resonance, then oldest (undated last), then id
+            members.sort_by(|&a, &b| {
+                let (x, y) = (entries[a], entries[b]);
+                y.resonance
+                    .cmp(&x.resonance)
+                    .then_with(|| match (&x.created_at, &y.created_at) {
+                        (Some(p), Some(q)) => p.cmp(q),
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => std::cmp::Ordering::Equal,
+                    })
+                    .then_with(|| x.id.cmp(&y.id))
+            });
+            let keep = members[0];
+            let mut duplicates: Vec<Duplicate> = members[1..]
+                .iter()
+                .map(|&i| {
+                    let exact = hashes[i].is_some() && hashes[i] == hashes[keep];
+                    Duplicate {
+                        id: entries[i].id.clone(),
+                        title: entries[i].title.clone(),
+                        kind: if exact { "exact" } else { "near" },
+                        similarity: if exact {
+                            1.0
+                        } else {
+                            jaccard(&sets[keep], &sets[i])
+                        },
+                    }
+                })
+                .collect();
+            duplicates.sort_by(|a, b| {
+                b.similarity
+                    .total_cmp(&a.similarity)
+                    .then_with(|| a.id.cmp(&b.id))
+            });
+            Cluster {
+                keep: entries[keep].id.clone(),
+                keep_title: entries[keep].title.clone(),
+                duplicates,
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.duplicates
+            .len()
+            .cmp(&a.duplicates.len())
+            .then_with(|| a.keep.cmp(&b.keep))
+    });
+    clusters
+}
+
+/// Fold `dupe` into `keeper`: labels and anchors unioned, the higher
+/// resonance, and body lines the keeper lacks appended
+fn fold(keeper: &mut KnowledgeEntry, dupe: &KnowledgeEntry) {
+    keeper.tags = merge_labels(&keeper.tags, &dupe.tags, &[]);
+    keeper.applicability = merge_labels(&keeper.applicability, &dupe.applicability, &[]);
+    for anchor in &dupe.anchors {
+        if *anchor != keeper.id && !keeper.anchors.contains(anchor) {
+            keeper.anchors.push(anchor.clone());
+        }
+    }
+    keeper.resonance = keeper.resonance.max(dupe.resonance);
+
+    let body = keeper.body.as_deref().unwrap_or("");
+    let known: HashSet<&str> = body.lines().map(str::trim).collect();
+    let missing: Vec<&str> = dupe
+        .body
+        .as_deref()
+        .unwrap_or("")
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !known.contains(l.trim()))
+        .collect();
+    if !missing.is_empty() {
+        keeper.body = Some(format!(
+            "{}\n\n## Merged from {}: {}\n\n{}\n",
+            body.trim_end(),
+            dupe.id,
+            dupe.title,
+            missing.join("\n")
+        ));
+    }
+}
+
+/// Merge `cluster` into its keeper and delete the duplicates
+fn apply(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    entries: &HashMap<&str, &KnowledgeEntry>,
+    cluster: &Cluster,
+    agent: Option<&str>,
+) -> Result<()> {
+    let Some(&original) = entries.get(cluster.keep.as_str()) else {
+        return Ok(());
+    };
+    let dupes: Vec<&KnowledgeEntry> = cluster
+        .duplicates
+        .iter()
+        .filter_map(|d| entries.get(d.id.as_str()).copied())
+        .collect();
+    let members: HashSet<&str> = dupes
+        .iter()
+        .map(|d| d.id.as_str())
+        .chain([cluster.keep.as_str()])
+        .collect();
+
+    db.backup_content(original, "merge", agent)?;
+    let mut keeper = original.clone();
+    for dupe in &dupes {
+        fold(&mut keeper, dupe);
+    }
+    keeper.anchors.retain(|a| !members.contains(a.as_str()));
+    keeper.content_hash = Some(KnowledgeEntry::compute_hash(
+        keeper.body.as_deref().unwrap_or(""),
+    ));
+    keeper.updated_at = Some(Utc::now().to_rfc3339());
+    db.upsert_knowledge(&keeper)?;
+
+    // Move relationships over; ones inside the cluster or already on the
+    // keeper are dropped with the duplicate
+    let mut existing: HashSet<(String, String, String)> = db
+        .list_relationships_for_entry(&keeper.id)?
+        .into_iter()
+        .map(|r| (r.from_entry_id, r.to_entry_id, r.relationship_type))
+        .collect();
+    let onto_keeper = |id: String| {
+        if members.contains(id.as_str()) {
+            keeper.id.clone()
+        } else {
+            id
+        }
+    };
+    for dupe in &dupes {
+        for rel in db.list_relationships_for_entry(&dupe.id)? {
+            let from = onto_keeper(rel.from_entry_id);
+            let to = onto_keeper(rel.to_entry_id);
+            if from == to {
+                continue;
+            }
+            let key = (from, to, rel.relationship_type);
+            if existing.insert(key.clone()) {
+                db.add_relationship(&key.0, &key.1, &key.2)?;
+            }
+        }
+        if let Err(e) = db.backup_content(dupe, "delete", agent) {
+            eprintln!("Warning: failed to create backup: {}", e);
+        }
+        db.delete(&dupe.id, ctx)?;
+    }
+    Ok(())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: DedupeArgs) -> Result<()> {
+    if !(0.0..=1.0).contains(&args.threshold) {
+        bail!("--threshold must be between 0.0 and 1.0");
+    }
+    let entries = db.list_all(ctx)?;
+    let clusters = find(&entries, args.threshold);
+    let duplicates: usize = clusters.iter().map(|c| c.duplicates.len()).sum();
+
+    if args.json && !args.apply {
+        println!("{}", serde_json::to_string_pretty(&clusters)?);
+        return Ok(());
+    }
+    if clusters.is_empty() {
+        if args.json {
+            println!("[]");
+        } else {
+            println!("No duplicates found");
+        }
+        return Ok(());
+    }
+
+    if !args.json {
+        for cluster in &clusters {
+            println!(
+                "{} ({} entries)",
+                "Cluster".bold(),
+                cluster.duplicates.len() + 1
+            );
+            println!(
+                "  {}  {}  {}",
+                "keep ".green(),
+                cluster.keep.yellow(),
+                cluster.keep_title
+            );
+            for dupe in &cluster.duplicates {
+                let similarity = match dupe.kind {
+                    "exact" => String::new(),
+                    _ => format!(" ({:.2})", dupe.similarity).dimmed().to_string(),
+                };
+                println!(
+                    "  {:5}  {}  {}{}",
+                    dupe.kind,
+                    dupe.id.yellow(),
+                    dupe.title,
+                    similarity
+                );
+            }
+            println!();
+        }
+        println!(
+            "{} cluster(s), {} duplicate(s).",
+            clusters.len(),
+            duplicates
+        );
+    }
+    if !args.apply {
+        println!("Merge them into the kept entries with: mx memory dedupe --apply");
+        return Ok(());
+    }
+
+    let affected = [(duplicates, "entry", "entries")];
+    if !confirm::confirm("Merge and delete", &affected, &[], args.yes)? {
+        println!("Cancelled");
+        return Ok(());
+    }
+    let agent = std::env::var("MX_CURRENT_AGENT").ok();
+    let by_id: HashMap<&str, &KnowledgeEntry> =
+        entries.iter().map(|e| (e.id.as_str(), e)).collect();
+    for cluster in &clusters {
+        apply(db, ctx, &by_id, cluster, agent.as_deref())?;
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&clusters)?);
+    } else {
+        println!(
+            "Merged {} duplicate(s) into {} entries",
+            duplicates,
+            clusters.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, title: &str, body: &str, tags: &[&str], resonance: i32) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": title,
+            "body": body,
+            "tags": tags,
+            "resonance": resonance,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn clusters_exact_and_near_duplicates_and_folds_them() {
+        let body = "Retry failed requests with exponential backoff and jitter.\n\
+                    Cap the delay at thirty seconds so callers are not starved.\n\
+                    Give up after five attempts and surface the last error.\n";
+        let entries = vec![
+            entry("kn-a", "Retry with backoff", body, &["net"], 3),
+            entry("kn-b", "Retry with backoff", body, &["http"], 5),
+            entry(
+                "kn-c",
+                "Retry with backoff",
+                &body.replace("five attempts", "five tries"),
+                &["go"],
+                1,
+            ),
+            entry(
+                "kn-d",
+                "Connection pooling",
+                "Reuse connections across requests.",
+                &[],
+                9,
+            ),
+        ];
+
+        let clusters = find(&entries, DEFAULT_THRESHOLD);
+        assert_eq!(clusters.len(), 1);
+        let cluster = &clusters[0];
+        // b has the highest resonance; a is b's exact copy, c is close
+        assert_eq!(cluster.keep, "kn-b");
+        assert_eq!(cluster.duplicates[0].id, "kn-a");
+        assert_eq!(cluster.duplicates[0].kind, "exact");
+        assert_eq!(cluster.duplicates[1].id, "kn-c");
+        assert_eq!(cluster.duplicates[1].kind, "near");
+        assert!(cluster.duplicates[1].similarity >= DEFAULT_THRESHOLD);
+        assert!(find(&entries, 1.0)[0].duplicates.len() == 1);
+
+        let mut keeper = entries[1].clone();
+        fold(&mut keeper, &entries[0]);
+        assert_eq!(keeper.body.as_deref(), Some(body));
+        fold(&mut keeper, &entries[2]);
+        assert_eq!(keeper.tags, ["go", "http", "net"]);
+        assert_eq!(keeper.resonance, 5);
+        assert!(keeper.body.as_deref().unwrap().ends_with(
+            "## Merged from kn-c: Retry with backoff\n\n\
+             Give up after five tries and surface the last error.\n"
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+//! `mx memory stats --deep`: how the knowledge base grows and where it
+//! comes from
+//!
+//! Adds to the per-category counts: entries created per month, tag
+//! frequencies, the agents and projects that contributed most, the resonance
+//! histogram, orphans (entries with neither tags nor relationships) and the
+//! average body length. Everything is over the entries the caller can see.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::dates;
+use crate::knowledge::KnowledgeEntry;
+use crate::types::Relationship;
+
+/// Width of the longest bar in the text histograms
+const BAR_WIDTH: usize = 30;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DeepStats {
+    /// `YYYY-MM` -> entries created that month
+    pub per_month: BTreeMap<String, usize>,
+    /// Entries with no usable `created_at`
+    pub undated: usize,
+    /// Every tag, most used first
+    pub tags: Vec<Count>,
+    pub untagged: usize,
+    /// Source agents, most entries first
+    pub agents: Vec<Count>,
+    /// Source projects, most entries first
+    pub projects: Vec<Count>,
+    /// Resonance -> entries
+    pub resonance: BTreeMap<i32, usize>,
+    /// Ids of entries with no tags and no relationships
+    pub orphans: Vec<String>,
+    /// Mean body length in characters
+    pub avg_body_chars: usize,
+}
+
+/// `counts` ranked most first, ties by name
+fn ranked(counts: HashMap<&str, usize>) -> Vec<Count> {
+    let mut ranked: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count {
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    ranked
+}
+
+pub fn compute(entries: &[KnowledgeEntry], relationships: &[Relationship]) -> DeepStats {
+    let linked: HashSet<&str> = relationships
+        .iter()
+        .flat_map(|r| [r.from_entry_id.as_str(), r.to_entry_id.as_str()])
+        .collect();
+
+    let mut per_month = BTreeMap::new();
+    let mut undated = 0;
+    let mut tags = HashMap::new();
+    let mut agents = HashMap::new();
+    let mut projects = HashMap::new();
+    let mut resonance = BTreeMap::new();
+    let mut orphans = Vec::new();
+    let mut body_chars = 0;
+
+    for entry in entries {
+        match entry.created_at.as_deref().and_then(dates::parse_stored) {
+            Some(at) => *per_month.entry(at.format("%Y-%m").to_string()).or_insert(0) += 1,
+            None => undated += 1,
+        }
+        for tag in &entry.tags {
+            *tags.entry(tag.as_str()).or_insert(0) += 1;
+        }
+        if let Some(agent) = entry.source_agent_id.as_deref() {
+            *agents.entry(agent).or_insert(0) += 1;
+        }
+        if let Some(project) = entry.source_project_id.as_deref() {
+            *projects.entry(project).or_insert(0) += 1;
+        }
+        *resonance.entry(entry.resonance).or_insert(0) += 1;
+        if entry.tags.is_empty() && !linked.contains(entry.id.as_str()) {
+            orphans.push(entry.id.clone());
+        }
+        body_chars += entry.body.as_deref().map_or(0, |b| b.chars().count());
+    }
+    orphans.sort();
+
+    DeepStats {
+        per_month,
+        undated,
+        untagged: entries.iter().filter(|e| e.tags.is_empty()).count(),
+        tags: ranked(tags),
+        agents: ranked(agents),
+        projects: ranked(projects),
+        resonance,
+        orphans,
+        avg_body_chars: body_chars.checked_div(entries.len()).unwrap_or(0),
+    }
+}
+
+fn bar(count: usize, max: usize) -> String {
+    let width = (count * BAR_WIDTH).div_ceil(max.max(1));
+    "█".repeat(width)
+}
+
+fn print_ranked(heading: &str, counts: &[Count], top: usize) {
+    if counts.is_empty() {
+        return;
+    }
+    println!("\n{}", heading);
+    for count in counts.iter().take(top) {
+        println!("  {:24} {:>5}", count.name, count.count);
+    }
+    if counts.len() > top {
+        println!("  ... and {} more", counts.len() - top);
+    }
+}
+
+/// The text report, listing at most `top` tags, agents and projects
+pub fn print(stats: &DeepStats, top: usize) {
+    if !stats.per_month.is_empty() {
+        println!("\nEntries per month");
+        let max = stats.per_month.values().copied().max().unwrap_or(0);
+        for (month, count) in &stats.per_month {
+            println!("  {}  {:>5}  {}", month, count, bar(*count, max));
+        }
+        if stats.undated > 0 {
+            println!("  undated  {:>5}", stats.undated);
+        }
+    }
+
+    print_ranked("Top tags", &stats.tags, top);
+    if !stats.tags.is_empty() {
+        println!("  (untagged)               {:>5}", stats.untagged);
+    }
+    print_ranked("Top agents", &stats.agents, top);
+    print_ranked("Top projects", &stats.projects, top);
+
+    println!("\nResonance");
+    let max = stats.resonance.values().copied().max().unwrap_or(0);
+    for (level, count) in &stats.resonance {
+        println!("  {:>3}  {:>5}  {}", level, count, bar(*count, max));
+    }
+
+    println!(
+        "\nOrphans (no tags or relationships): {}",
+        stats.orphans.len()
+    );
+    for id in stats.orphans.iter().take(top) {
+        println!("  {}", id);
+    }
+    if stats.orphans.len() > top {
+        println!("  ... and {} more", stats.orphans.len() - top);
+    }
+    println!("\nAverage body length: {} chars", stats.avg_body_chars);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, created: &str, tags: &[&str], agent: &str, body: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": id,
+            "body": body,
+            "tags": tags,
+            "source_agent_id": agent,
+            "resonance": 3,
+            "created_at": created,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn aggregates_growth_tags_agents_and_orphans() {
+        let entries = [
+            entry(
+                "kn-a",
+                "2026-01-05T00:00:00Z",
+                &["rust", "cli"],
+                "smith",
+                "abcd",
+            ),
+            entry("kn-b", "2026-01-20T00:00:00Z", &["rust"], "smith", "ab"),
+            entry("kn-c", "2026-03-01T00:00:00Z", &[], "neo", ""),
+            entry("kn-d", "2026-03-02T00:00:00Z", &[], "neo", ""),
+        ];
+        let relationships: Vec<Relationship> = serde_json::from_value(serde_json::json!([{
+            "id": "r1",
+            "from_entry_id": "kn-a",
+            "to_entry_id": "kn-d",
+            "relationship_type": "related",
+            "created_at": "2026-03-02T00:00:00Z",
+        }]))
+        .unwrap();
+
+        let stats = compute(&entries, &relationships);
+        assert_eq!(
+            stats.per_month,
+            BTreeMap::from([("2026-01".to_string(), 2), ("2026-03".to_string(), 2)])
+        );
+        assert_eq!(
+            stats.tags[0],
+            Count {
+                name: "rust".into(),
+                count: 2
+            }
+        );
+        assert_eq!(stats.untagged, 2);
+        assert_eq!(stats.agents.len(), 2);
+        assert_eq!(stats.resonance, BTreeMap::from([(3, 4)]));
+        assert_eq!(stats.orphans, ["kn-c"]);
+        assert_eq!(stats.avg_body_chars, 1);
+    }
+}
@@ -0,0 +1,67 @@
+//! Health check: `mx doctor`
+//!
+//! Runs the checks that end `mx init`, then lists operations left in the
+//! intent journal. Opening the store already settles whatever a crashed
+//! process left behind, so anything listed here either belongs to a process
+//! that is still running or could not be settled; `--discard` drops the
+//! latter.
+
+use anyhow::{Result, bail};
+
+use crate::index::IndexConfig;
+use crate::journal::{self, Journal};
+use crate::{dates, init, paths, store};
+
+#[derive(clap::Args)]
+pub struct DoctorArgs {
+    /// Drop journaled operations that can't be recovered
+    #[arg(long)]
+    pub discard: bool,
+}
+
+pub fn run(args: DoctorArgs, verbose: bool) -> Result<()> {
+    let discard = args.discard;
+    let config = IndexConfig::default();
+    let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+
+    println!("Checks");
+    let mut failures = init::run_checks(db.as_ref(), &config.db_path);
+
+    println!("Journal");
+    let journal = Journal::open(paths::journal_dir());
+    let recovery = journal::recover(&journal, db.as_ref())?;
+    if recovery.running.is_empty() && recovery.failed.is_empty() {
+        println!("      ok    no incomplete operations");
+    }
+    for intent in &recovery.running {
+        println!(
+            "      busy  {} (pid {}, started {})",
+            intent.op.describe(),
+            intent.pid,
+            dates::display_dt(intent.started_at)
+        );
+    }
+    for (intent, error) in &recovery.failed {
+        if discard {
+            journal.discard(&intent.id)?;
+            println!("      drop  {} ({})", intent.op.describe(), intent.id);
+        } else {
+            failures += 1;
+            println!(
+                "      FAIL  {} ({}): {}",
+                intent.op.describe(),
+                intent.id,
+                error
+            );
+        }
+    }
+
+    if failures > 0 {
+        if !recovery.failed.is_empty() && !discard {
+            println!();
+            println!("Fix the cause and run mx doctor again, or drop them with --discard.");
+        }
+        bail!("{} check(s) failed", failures);
+    }
+    Ok(())
+}
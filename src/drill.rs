@@ -0,0 +1,298 @@
+//! Spaced-repetition drill: `mx memory drill`
+//!
+//! An SM-2 scheduler over knowledge entries. Each entry an agent has drilled
+//! gets a review card (ease, interval, repetitions, next review) stored per
+//! agent; a drill session walks the cards that are due plus a few entries
+//! never seen before, shows the title, reveals the summary on Enter, and
+//! records a 0-5 recall grade.
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeFilter, KnowledgeStore};
+use crate::types::ReviewCard;
+
+/// Starting easiness factor for a new card
+const INITIAL_EASE: f64 = 2.5;
+/// SM-2 floor on the easiness factor
+const MIN_EASE: f64 = 1.3;
+/// Lines of the body shown when an entry has no summary
+const REVEAL_LINES: usize = 15;
+
+#[derive(clap::Args)]
+pub struct DrillArgs {
+    /// Only drill entries in this category
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Maximum cards in this session
+    #[arg(long, default_value = "20")]
+    pub limit: usize,
+
+    /// Maximum never-reviewed entries to introduce
+    #[arg(long, default_value = "5")]
+    pub new: usize,
+
+    /// List what is due instead of drilling
+    #[arg(long)]
+    pub list: bool,
+
+    /// Include private entries owned by the current agent
+    #[arg(long)]
+    pub include_private: bool,
+}
+
+/// Reviewer identity: the current agent, or `human` outside an agent session
+pub fn reviewer() -> String {
+    std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| "human".to_string())
+}
+
+fn new_card(entry_id: &str, agent: &str, now: DateTime<Utc>) -> ReviewCard {
+    ReviewCard {
+        entry_id: entry_id.to_string(),
+        agent: agent.to_string(),
+        ease: INITIAL_EASE,
+        interval_days: 0,
+        repetitions: 0,
+        next_review: now.to_rfc3339(),
+        last_reviewed: None,
+    }
+}
+
+/// Apply one SM-2 review with recall `grade` (0 = blackout, 5 = perfect)
+fn review(card: &ReviewCard, grade: u8, now: DateTime<Utc>) -> ReviewCard {
+    let q = f64::from(grade.min(5));
+    let (repetitions, interval_days) = if grade >= 3 {
+        let interval = match card.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (card.interval_days as f64 * card.ease).round() as i64,
+        };
+        (card.repetitions + 1, interval)
+    } else {
+        (0, 1)
+    };
+    let ease = (card.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+
+    ReviewCard {
+        ease,
+        interval_days,
+        repetitions,
+        next_review: (now + Duration::days(interval_days)).to_rfc3339(),
+        last_reviewed: Some(now.to_rfc3339()),
+        ..card.clone()
+    }
+}
+
+fn is_due(card: &ReviewCard, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&card.next_review)
+        .map(|next| next.with_timezone(&Utc) <= now)
+        .unwrap_or(true)
+}
+
+/// Number of `agent`'s cards due for review now
+pub fn due_count(db: &dyn KnowledgeStore, agent: &str) -> Result<usize> {
+    let now = Utc::now();
+    Ok(db
+        .list_review_cards(agent)?
+        .iter()
+        .filter(|c| is_due(c, now))
+        .count())
+}
+
+/// Due cards (oldest first), then up to `new` unseen entries by resonance
+fn select_due<'a>(
+    entries: &'a [KnowledgeEntry],
+    cards: &HashMap<String, ReviewCard>,
+    now: DateTime<Utc>,
+    opts: &DrillArgs,
+) -> Vec<(&'a KnowledgeEntry, Option<ReviewCard>)> {
+    let mut due: Vec<(&KnowledgeEntry, Option<ReviewCard>)> = entries
+        .iter()
+        .filter_map(|e| cards.get(&e.id).map(|c| (e, c)))
+        .filter(|(_, c)| is_due(c, now))
+        .map(|(e, c)| (e, Some(c.clone())))
+        .collect();
+    due.sort_by(|a, b| {
+        let next = |x: &Option<ReviewCard>| x.as_ref().map(|c| c.next_review.clone());
+        next(&a.1).cmp(&next(&b.1))
+    });
+
+    let mut unseen: Vec<&KnowledgeEntry> = entries
+        .iter()
+        .filter(|e| !cards.contains_key(&e.id))
+        .collect();
+    unseen.sort_by(|a, b| b.resonance.cmp(&a.resonance).then(a.id.cmp(&b.id)));
+    due.extend(unseen.into_iter().take(opts.new).map(|e| (e, None)));
+
+    due.truncate(opts.limit);
+    due
+}
+
+/// What to show on reveal: the summary, else the start of the body
+fn answer(entry: &KnowledgeEntry) -> String {
+    if let Some(summary) = entry.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        return summary.trim().to_string();
+    }
+    let body = entry.body.as_deref().unwrap_or("").trim();
+    let mut lines: Vec<&str> = body.lines().take(REVEAL_LINES).collect();
+    if body.lines().count() > REVEAL_LINES {
+        lines.push("…");
+    }
+    lines.join("\n")
+}
+
+enum Answer {
+    Grade(u8),
+    Skip,
+    Quit,
+}
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok("q".to_string());
+    }
+    Ok(input.trim().to_lowercase())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, opts: DrillArgs) -> Result<()> {
+    let agent = reviewer();
+    let now = Utc::now();
+
+    let entries = match opts.category {
+        Some(ref category) => db.list_by_category(category, ctx, &KnowledgeFilter::default())?,
+        None => db.list_all(ctx)?,
+    };
+    let cards: HashMap<String, ReviewCard> = db
+        .list_review_cards(&agent)?
+        .into_iter()
+        .map(|c| (c.entry_id.clone(), c))
+        .collect();
+    let queue = select_due(&entries, &cards, now, &opts);
+
+    if queue.is_empty() {
+        println!("{}", "Nothing due for review".green());
+        return Ok(());
+    }
+
+    if opts.list {
+        for (entry, card) in &queue {
+            let status = match card {
+                Some(c) => format!("due {}", crate::dates::display(&c.next_review)),
+                None => "new".to_string(),
+            };
+            println!("{} {} ({})", entry.id.cyan(), entry.title, status.dimmed());
+        }
+        return Ok(());
+    }
+
+    if !io::stdin().is_terminal() {
+        bail!("drill requires an interactive terminal (use --list to see what is due)");
+    }
+
+    println!(
+        "{} card(s) to review. Grade recall 0-5 (0 blank, 3 recalled with effort, 5 perfect); s skips, q quits.\n",
+        queue.len()
+    );
+
+    let (mut reviewed, mut lapsed) = (0, 0);
+    for (i, (entry, card)) in queue.iter().enumerate() {
+        println!(
+            "{} {} {}",
+            format!("[{}/{}]", i + 1, queue.len()).dimmed(),
+            entry.title.bold(),
+            format!("({})", entry.category_id).dimmed()
+        );
+        if prompt("  Enter to reveal… ")? == "q" {
+            break;
+        }
+        println!("\n{}\n", answer(entry));
+
+        let grade = loop {
+            match prompt("  Grade [0-5]: ")?.as_str() {
+                "q" => break Answer::Quit,
+                "s" => break Answer::Skip,
+                g => {
+                    if let Ok(n @ 0..=5) = g.parse::<u8>() {
+                        break Answer::Grade(n);
+                    }
+                }
+            }
+        };
+        let grade = match grade {
+            Answer::Quit => break,
+            Answer::Skip => {
+                println!();
+                continue;
+            }
+            Answer::Grade(n) => n,
+        };
+
+        let base = card
+            .clone()
+            .unwrap_or_else(|| new_card(&entry.id, &agent, now));
+        let updated = review(&base, grade, Utc::now());
+        db.upsert_review_card(&updated)?;
+        reviewed += 1;
+        if grade < 3 {
+            lapsed += 1;
+        }
+        println!(
+            "  {} next review in {} day(s)\n",
+            "✓".green(),
+            updated.interval_days
+        );
+    }
+
+    println!(
+        "Reviewed {} card(s), {} to relearn",
+        reviewed.to_string().bold(),
+        lapsed
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn sm2_intervals_grow_and_reset() {
+        let now = at("2026-01-01T00:00:00Z");
+        let card = new_card("kn-1", "human", now);
+
+        let first = review(&card, 4, now);
+        assert_eq!((first.repetitions, first.interval_days), (1, 1));
+        assert!((first.ease - 2.5).abs() < 1e-9);
+
+        let second = review(&first, 5, now);
+        assert_eq!(second.interval_days, 6);
+        let third = review(&second, 4, now);
+        assert_eq!(third.interval_days, (6.0 * second.ease).round() as i64);
+        assert_eq!(third.next_review, (now + Duration::days(16)).to_rfc3339());
+
+        let lapse = review(&third, 1, now);
+        assert_eq!((lapse.repetitions, lapse.interval_days), (0, 1));
+        assert!(lapse.ease < third.ease);
+
+        let mut floor = card;
+        for _ in 0..10 {
+            floor = review(&floor, 0, now);
+        }
+        assert_eq!(floor.ease, MIN_EASE);
+    }
+}
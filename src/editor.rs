@@ -0,0 +1,275 @@
+//! `mx memory edit <id>` without `--find`: edit an entry in `$EDITOR`
+//!
+//! The entry goes to a temporary markdown file, YAML frontmatter holding the
+//! editable fields, then the body. After the editor exits, the changes are
+//! shown as a diff and written back in place, so tags, relationships and
+//! everything else not in the file are kept. The old title and body are
+//! backed up like any other edit.
+//!
+//! `$VISUAL` wins over `$EDITOR`; with neither set, `vi`. The command runs
+//! through `sh`, so values like `code --wait` work.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::line_diff;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Unchanged body lines shown around each change
+const DIFF_CONTEXT: usize = 2;
+
+/// The editable fields, as they appear in the frontmatter
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Frontmatter {
+    title: String,
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    resonance: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resonance_type: Option<String>,
+}
+
+/// The file the editor opens for `entry`
+fn render(entry: &KnowledgeEntry) -> Result<String> {
+    let front = Frontmatter {
+        title: entry.title.clone(),
+        category: entry.category_id.clone(),
+        tags: entry.tags.clone(),
+        summary: entry.summary.clone(),
+        resonance: entry.resonance,
+        resonance_type: entry.resonance_type.clone(),
+    };
+    Ok(format!(
+        "---\n{}---\n\n{}",
+        serde_yaml::to_string(&front)?,
+        entry.body.as_deref().unwrap_or("")
+    ))
+}
+
+/// `entry` with the fields from an edited file applied
+fn apply(entry: &KnowledgeEntry, text: &str) -> Result<KnowledgeEntry> {
+    let rest = text
+        .strip_prefix("---\n")
+        .context("Missing frontmatter: the file must start with ---")?;
+    let end = rest
+        .find("\n---")
+        .context("Invalid frontmatter: missing closing ---")?;
+    let front: Frontmatter =
+        serde_yaml::from_str(&rest[..end]).context("Failed to parse frontmatter")?;
+    let body = rest[end + 4..]
+        .strip_prefix('\n')
+        .unwrap_or(&rest[end + 4..]);
+    let body = body.strip_prefix('\n').unwrap_or(body);
+
+    let mut edited = entry.clone();
+    edited.title = front.title;
+    edited.category_id = front.category;
+    edited.tags = front.tags;
+    edited.summary = front.summary.filter(|s| !s.is_empty());
+    edited.resonance = front.resonance;
+    edited.resonance_type = front.resonance_type.filter(|s| !s.is_empty());
+    edited.body = (!body.is_empty() || entry.body.is_some()).then(|| body.to_string());
+    Ok(edited)
+}
+
+/// One line per changed field; the body as a line diff
+fn changes(before: &KnowledgeEntry, after: &KnowledgeEntry) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut field = |name: &str, a: String, b: String| {
+        if a != b {
+            out.push(format!("{}: {} -> {}", name, a, b));
+        }
+    };
+    field("title", before.title.clone(), after.title.clone());
+    field(
+        "category",
+        before.category_id.clone(),
+        after.category_id.clone(),
+    );
+    field("tags", before.tags.join(", "), after.tags.join(", "));
+    field(
+        "summary",
+        format!("{:?}", before.summary),
+        format!("{:?}", after.summary),
+    );
+    field(
+        "resonance",
+        before.resonance.to_string(),
+        after.resonance.to_string(),
+    );
+    field(
+        "resonance_type",
+        format!("{:?}", before.resonance_type),
+        format!("{:?}", after.resonance_type),
+    );
+
+    let (a, b) = (
+        before.body.as_deref().unwrap_or(""),
+        after.body.as_deref().unwrap_or(""),
+    );
+    if a != b {
+        out.push("body:".to_string());
+        let ops = line_diff::diff_lines(a, b);
+        out.extend(
+            line_diff::render(&ops, DIFF_CONTEXT)
+                .into_iter()
+                .map(|l| format!("  {}", l)),
+        );
+    }
+    out
+}
+
+/// Open `path` in the user's editor and wait for it to exit
+pub fn open_editor(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}; nothing saved", editor, status);
+    }
+    Ok(())
+}
+
+/// Edit entry `id` in `$EDITOR` and write back what changed. Returns the
+/// saved entry, or None if nothing changed.
+pub fn run(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    agent: Option<&str>,
+    id: &str,
+    json: bool,
+) -> Result<Option<KnowledgeEntry>> {
+    let Some(entry) = db.get(id, ctx)? else {
+        bail!(MxError::not_found("Entry", id));
+    };
+
+    let file = tempfile::Builder::new()
+        .prefix("mx-edit-")
+        .suffix(".md")
+        .tempfile()?;
+    let original = render(&entry)?;
+    std::fs::write(file.path(), &original)?;
+    open_editor(file.path())?;
+    let text = std::fs::read_to_string(file.path())?;
+
+    // Keep the file when it can't be saved, so the edits aren't lost
+    let edited = match apply(&entry, &text).and_then(|edited| {
+        if db.get_category(&edited.category_id)?.is_none() {
+            bail!(
+                "Unknown category '{}' (see `mx memory categories list`)",
+                edited.category_id
+            );
+        }
+        edited.validate()?;
+        Ok(edited)
+    }) {
+        Ok(edited) => edited,
+        Err(e) => {
+            let (_, path) = file.keep()?;
+            bail!("{:#}\nYour edits are kept in {}", e, path.display());
+        }
+    };
+
+    let changed = changes(&entry, &edited);
+    if changed.is_empty() {
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "id": id, "changed": false }))?
+            );
+        } else {
+            println!("No changes to {}", id);
+        }
+        return Ok(None);
+    }
+
+    if let Err(e) = db.backup_content(&entry, "edit", agent) {
+        eprintln!("Warning: failed to create backup: {}", e);
+    }
+    let mut edited = edited;
+    edited.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    edited.content_hash = Some(KnowledgeEntry::compute_hash(
+        edited.body.as_deref().unwrap_or(""),
+    ));
+    db.upsert_knowledge(&edited)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "id": id,
+                "changed": true,
+                "changes": changed,
+            }))?
+        );
+    } else {
+        println!("Edited entry: {}", id);
+        for line in &changed {
+            println!("  {}", line);
+        }
+    }
+    Ok(Some(edited))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontmatter_round_trips_and_applies_edits() {
+        let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-x",
+            "category_id": "pattern",
+            "title": "Retry: with backoff",
+            "body": "Line one\nLine two\n",
+            "tags": ["go", "net"],
+            "resonance": 5,
+        }))
+        .unwrap();
+
+        let text = render(&entry).unwrap();
+        assert!(text.starts_with("---\ntitle: 'Retry: with backoff'\n"));
+        let same = apply(&entry, &text).unwrap();
+        assert_eq!(same.body, entry.body);
+        assert!(changes(&entry, &same).is_empty());
+
+        let edited = text
+            .replace("- net", "- net\n- http")
+            .replace("resonance: 5", "resonance: 7")
+            .replace("Line two", "Line 2");
+        let after = apply(&entry, &edited).unwrap();
+        assert_eq!(after.tags, ["go", "net", "http"]);
+        assert_eq!(after.body.as_deref(), Some("Line one\nLine 2\n"));
+        assert_eq!(
+            changes(&entry, &after),
+            [
+                "tags: go, net -> go, net, http",
+                "resonance: 5 -> 7",
+                "body:",
+                "    Line one",
+                "  - Line two",
+                "  + Line 2",
+            ]
+        );
+
+        assert!(apply(&entry, "no frontmatter").is_err());
+        assert!(apply(&entry, &text.replace("resonance:", "loudness:")).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use colored::Colorize;
 use rand::Rng;
 use std::io::{self, IsTerminal, Write};
 
+use crate::error::MxError;
 use crate::knowledge::KnowledgeEntry;
 use crate::store::{KnowledgeStore, WakeCascade};
 
@@ -208,8 +209,8 @@ fn print_hint(phrase: &str, attempt: usize) {
             } else {
                 // Single word - show first few letters
                 let first_word = words[0];
-                if first_word.chars().count() > 3 {
-                    let prefix: String = first_word.chars().take(3).collect();
+                if crate::text::len(first_word) > 3 {
+                    let prefix = crate::text::truncate(first_word, 3);
                     println!("  {}: \"{}...\"", "hint".yellow(), prefix);
                 }
             }
@@ -370,7 +371,7 @@ fn update_wake_phrase(db: &dyn KnowledgeStore, id: &str, phrase: &str) -> Result
     let ctx = crate::store::AgentContext::public_only(); // TODO: use proper context
     let mut entry = db
         .get(id, &ctx)?
-        .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+        .ok_or_else(|| MxError::not_found("Entry", id))?;
 
     // Write to wake_phrases (canonical field), not deprecated wake_phrase
     entry.wake_phrases = vec![phrase.to_string()];
@@ -0,0 +1,246 @@
+//! Failures worth telling apart: [`MxError`]
+//!
+//! Most errors are plain `anyhow` chains, and that stays the way to add
+//! context. Failures a caller can act on differently (retry later, fix a
+//! token, pick another id) are raised as an `MxError` somewhere in the chain
+//! instead; at exit, [`report`] finds it, prints its hint and exits with its
+//! code. Anything else exits 1.
+//!
+//! | code | kind              |
+//! |------|-------------------|
+//! | 1    | other             |
+//! | 2    | usage (clap)      |
+//! | 3    | `not_found`       |
+//! | 4    | `conflict`        |
+//! | 5    | `auth_failure`    |
+//! | 6    | `rate_limited`    |
+//! | 7    | `schema_mismatch` |
+//! | 8    | `unconfirmed`     |
+//! | 130  | `interrupted`     |
+//!
+//! With `--error-format json` (or `MX_ERROR_FORMAT=json`) the failure is one
+//! JSON object on stderr instead:
+//!
+//! ```json
+//! {"error":{"kind":"not_found","code":3,"message":"Entry 'kn-x' not found",
+//!   "causes":[],"hint":"Find ids with `mx memory search` or `mx memory list`"}}
+//! ```
+
+use clap::ValueEnum;
+use colored::Colorize;
+use std::process::ExitCode;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MxError {
+    #[error("{kind} '{id}' not found")]
+    NotFound { kind: &'static str, id: String },
+
+    #[error("{message}")]
+    Conflict {
+        message: String,
+        hint: Option<String>,
+    },
+
+    #[error("{service} authentication failed: {message}")]
+    AuthFailure { service: String, message: String },
+
+    #[error("{service} rate limit reached")]
+    RateLimited {
+        service: String,
+        /// Seconds until the limit resets, when the service says
+        retry_after: Option<u64>,
+    },
+
+    #[error("{what} uses schema '{found}', expected '{expected}'")]
+    SchemaMismatch {
+        what: String,
+        found: String,
+        expected: String,
+    },
+
+    #[error("Refusing to {action} without confirmation")]
+    Unconfirmed { action: String, threshold: usize },
+
+    #[error("{what} interrupted after {done} of {total} items")]
+    Interrupted {
+        what: String,
+        done: usize,
+        total: usize,
+    },
+}
+
+impl MxError {
+    pub fn not_found(kind: &'static str, id: impl Into<String>) -> Self {
+        MxError::NotFound {
+            kind,
+            id: id.into(),
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        MxError::Conflict {
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    /// Stable name for scripts
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MxError::NotFound { .. } => "not_found",
+            MxError::Conflict { .. } => "conflict",
+            MxError::AuthFailure { .. } => "auth_failure",
+            MxError::RateLimited { .. } => "rate_limited",
+            MxError::SchemaMismatch { .. } => "schema_mismatch",
+            MxError::Unconfirmed { .. } => "unconfirmed",
+            MxError::Interrupted { .. } => "interrupted",
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            MxError::NotFound { .. } => 3,
+            MxError::Conflict { .. } => 4,
+            MxError::AuthFailure { .. } => 5,
+            MxError::RateLimited { .. } => 6,
+            MxError::SchemaMismatch { .. } => 7,
+            MxError::Unconfirmed { .. } => 8,
+            MxError::Interrupted { .. } => 130,
+        }
+    }
+
+    /// What to do about it
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            MxError::NotFound { kind, .. } => Some(match *kind {
+                "Entry" => "Find ids with `mx memory search` or `mx memory list`".into(),
+                "Category" => "List categories with `mx memory categories list`".into(),
+                "Project" => "List projects with `mx memory projects list`".into(),
+                "Agent" => "List agents with `mx memory agents list`".into(),
+                "Session" => "List sessions with `mx memory sessions list`".into(),
+                "Alias" => "List aliases with `mx alias list`".into(),
+                "Tag" => "List tags with `mx memory tags list`".into(),
+                "Archive" => "List archives with `mx codex list`".into(),
+                _ => return None,
+            }),
+            MxError::Conflict { hint, .. } => hint.clone(),
+            MxError::AuthFailure { service, .. } if service == "GitHub" => Some(
+                "Check the token in ~/.claude.json (mcpServers.github.env.GITHUB_PERSONAL_ACCESS_TOKEN) \
+                 hasn't expired and has repo scope"
+                    .into(),
+            ),
+            MxError::AuthFailure { .. } => Some("Check the configured credentials".into()),
+            MxError::RateLimited { retry_after, .. } => Some(match retry_after {
+                Some(secs) => format!("Try again in {}", wait(*secs)),
+                None => "Wait a while and try again".into(),
+            }),
+            MxError::SchemaMismatch { expected, .. } => Some(format!(
+                "Pass the matching schema with --schema, or re-encode for '{}'",
+                expected
+            )),
+            MxError::Unconfirmed { threshold, .. } => Some(format!(
+                "Pass --yes, or raise confirm.threshold in config.yaml (now {})",
+                threshold
+            )),
+            MxError::Interrupted { .. } => {
+                Some("Run the same command again to pick up where it stopped".into())
+            }
+        }
+    }
+}
+
+fn wait(secs: u64) -> String {
+    match secs {
+        0..=90 => format!("{}s", secs.max(1)),
+        _ => format!("{}m", secs.div_ceil(60)),
+    }
+}
+
+/// How a failure is printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl ErrorFormat {
+    /// The flag if given, else `MX_ERROR_FORMAT`
+    pub fn resolve(flag: Option<ErrorFormat>) -> Self {
+        flag.or_else(|| {
+            std::env::var("MX_ERROR_FORMAT")
+                .ok()
+                .and_then(|v| ErrorFormat::from_str(&v, true).ok())
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// The [`MxError`] anywhere in the chain
+pub fn classify(err: &anyhow::Error) -> Option<&MxError> {
+    err.chain().find_map(|e| e.downcast_ref::<MxError>())
+}
+
+/// The JSON error object
+fn to_json(err: &anyhow::Error) -> serde_json::Value {
+    let mx = classify(err);
+    let causes: Vec<String> = err.chain().skip(1).map(|e| e.to_string()).collect();
+    serde_json::json!({
+        "error": {
+            "kind": mx.map_or("error", MxError::kind),
+            "code": mx.map_or(1, MxError::exit_code),
+            "message": err.to_string(),
+            "causes": causes,
+            "hint": mx.and_then(MxError::hint),
+        }
+    })
+}
+
+/// Print a failure to stderr and give the exit code for it
+pub fn report(err: &anyhow::Error, format: ErrorFormat) -> ExitCode {
+    let mx = classify(err);
+    match format {
+        ErrorFormat::Json => eprintln!("{}", to_json(err)),
+        ErrorFormat::Text => {
+            eprintln!("{} {:#}", "Error:".red().bold(), err);
+            if let Some(hint) = mx.and_then(MxError::hint) {
+                eprintln!("{} {}", "hint:".yellow(), hint);
+            }
+        }
+    }
+    ExitCode::from(mx.map_or(1, MxError::exit_code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn typed_failures_survive_context() {
+        let err = Err::<(), _>(MxError::not_found("Entry", "kn-gone"))
+            .context("Failed to promote entry")
+            .unwrap_err();
+        let mx = classify(&err).unwrap();
+        assert_eq!(mx.exit_code(), 3);
+        assert!(mx.hint().unwrap().contains("mx memory search"));
+
+        let json = to_json(&err);
+        assert_eq!(json["error"]["kind"], "not_found");
+        assert_eq!(json["error"]["code"], 3);
+        assert_eq!(json["error"]["message"], "Failed to promote entry");
+        assert_eq!(json["error"]["causes"][0], "Entry 'kn-gone' not found");
+
+        let plain = to_json(&anyhow::anyhow!("disk full"));
+        assert_eq!(plain["error"]["kind"], "error");
+        assert_eq!(plain["error"]["code"], 1);
+        assert!(plain["error"]["hint"].is_null());
+
+        let limited = MxError::RateLimited {
+            service: "GitHub".into(),
+            retry_after: Some(600),
+        };
+        assert_eq!(limited.hint().unwrap(), "Try again in 10m");
+        assert_eq!(limited.exit_code(), 6);
+    }
+}
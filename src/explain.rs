@@ -0,0 +1,332 @@
+//! Search explanations: why each hit ranked where it did
+//!
+//! `mx memory search --explain` keeps the usual keyword ranking (text score
+//! scaled by effective confidence, see [`crate::confidence`]) and shows,
+//! per hit, which searched fields held a query term with the terms
+//! highlighted, plus each ranking component: the weighted text score per
+//! field (see [`crate::search`]), the rated confidence and how much of it
+//! age decay left. Resonance only filters keyword results, so it isn't a
+//! component.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::confidence::{self, UNRATED};
+use crate::knowledge::KnowledgeEntry;
+use crate::search::{self, SearchField};
+use crate::store::{AgentContext, KnowledgeFilter, KnowledgeStore, TextScore};
+
+/// Characters of context kept before the first match in a long field
+const LEAD: usize = 40;
+
+/// Characters kept from the first match on
+const SPAN: usize = 120;
+
+#[derive(clap::Args)]
+pub struct ExplainArgs {
+    /// Show per hit which fields matched (terms highlighted) and the ranking components
+    #[arg(long, conflicts_with_all = ["semantic", "as_of"])]
+    pub explain: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    /// Fields holding a query term
+    pub matched: Vec<&'static str>,
+    /// Weighted text score per field
+    pub text: TextScore,
+    /// Confidence as rated (unrated entries count as 0.5)
+    pub rated: f64,
+    /// Share of `rated` left after age decay (1.0 for categories that don't decay)
+    pub age_decay: f64,
+    /// Effective confidence: `rated * age_decay`
+    pub confidence: f64,
+    /// What ranking sorts on: total text score times `0.5 + confidence`
+    pub score: f64,
+}
+
+#[derive(Serialize)]
+struct Hit<'a> {
+    #[serde(flatten)]
+    entry: &'a KnowledgeEntry,
+    explain: Explanation,
+}
+
+/// Lowercased words of `query`, split the way the search analyzer splits them
+pub fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (_, word) in words(query) {
+        let word = word.to_lowercase();
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Runs of letters and digits with their byte offsets
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                out.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        out.push((s, &text[s..]));
+    }
+    out
+}
+
+fn is_term(word: &str, terms: &[String]) -> bool {
+    terms.contains(&word.to_lowercase())
+}
+
+/// `text` with every query term passed through `mark`, or None if no term occurs
+fn highlight(text: &str, terms: &[String], mark: impl Fn(&str) -> String) -> Option<String> {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut found = false;
+    for (at, word) in words(text) {
+        if is_term(word, terms) {
+            out.push_str(&text[last..at]);
+            out.push_str(&mark(word));
+            last = at + word.len();
+            found = true;
+        }
+    }
+    out.push_str(&text[last..]);
+    found.then_some(out)
+}
+
+/// One line of `text` around its first query term
+fn snippet(text: &str, terms: &[String]) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some(at) = words(&flat)
+        .into_iter()
+        .find(|(_, w)| is_term(w, terms))
+        .map(|(at, _)| at)
+    else {
+        return flat;
+    };
+    let start = flat[..at]
+        .char_indices()
+        .rev()
+        .nth(LEAD - 1)
+        .map_or(0, |(i, _)| i);
+    let end = flat[at..]
+        .char_indices()
+        .nth(SPAN)
+        .map_or(flat.len(), |(i, _)| at + i);
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        &flat[start..end],
+        if end < flat.len() { "..." } else { "" }
+    )
+}
+
+/// The searched fields of `entry` holding a query term, each as a
+/// highlighted line
+fn matched_fields(
+    entry: &KnowledgeEntry,
+    fields: &[SearchField],
+    terms: &[String],
+    mark: impl Fn(&str) -> String,
+) -> Vec<(&'static str, String)> {
+    let text = |text: Option<&str>| highlight(&snippet(text?, terms), terms, &mark);
+    fields
+        .iter()
+        .filter_map(|&field| {
+            let line = match field {
+                SearchField::Title => text(Some(&entry.title)),
+                SearchField::Body => text(entry.body.as_deref()),
+                SearchField::Summary => text(entry.summary.as_deref()),
+                SearchField::Tags => {
+                    let tags: Vec<String> = entry
+                        .tags
+                        .iter()
+                        .filter(|tag| terms.contains(&tag.to_lowercase()))
+                        .map(|tag| mark(tag))
+                        .collect();
+                    (!tags.is_empty()).then(|| tags.join(", "))
+                }
+            };
+            line.map(|line| (field.name(), line))
+        })
+        .collect()
+}
+
+pub fn explain(
+    entry: &KnowledgeEntry,
+    text: TextScore,
+    fields: &[SearchField],
+    terms: &[String],
+    settings: &confidence::Settings,
+    now: DateTime<Utc>,
+) -> Explanation {
+    let rated = entry.confidence.unwrap_or(UNRATED);
+    let effective = settings.effective(entry, now);
+    Explanation {
+        matched: matched_fields(entry, fields, terms, str::to_string)
+            .into_iter()
+            .map(|(field, _)| field)
+            .collect(),
+        rated,
+        age_decay: if rated > 0.0 { effective / rated } else { 1.0 },
+        confidence: effective,
+        score: text.total() * settings.weight(entry, now),
+        text,
+    }
+}
+
+fn print(entry: &KnowledgeEntry, why: &Explanation, fields: &[SearchField], terms: &[String]) {
+    println!("  {} [{}]", entry.id, entry.category_id);
+    let mark = |w: &str| w.yellow().bold().to_string();
+    for (field, line) in matched_fields(entry, fields, terms, mark) {
+        println!("    {:<8} {}", format!("{}:", field), line);
+    }
+    let scores: Vec<String> = [
+        ("title", why.text.title),
+        ("body", why.text.body),
+        ("summary", why.text.summary),
+        ("tags", why.text.tags),
+    ]
+    .into_iter()
+    .filter(|(_, score)| *score != 0.0)
+    .map(|(field, score)| format!("{} {:.2}", field, score))
+    .collect();
+    println!(
+        "    {:<8} score {:.2} = text {:.2} ({}) x (0.5 + confidence {:.2}, rated {:.2} x {:.2} age decay)",
+        "rank:",
+        why.score,
+        why.text.total(),
+        if scores.is_empty() {
+            "no field scored".to_string()
+        } else {
+            scores.join(", ")
+        },
+        why.confidence,
+        why.rated,
+        why.age_decay
+    );
+    println!();
+}
+
+/// Print keyword search `hits` (already ranked) with their explanations
+pub fn report(
+    db: &dyn KnowledgeStore,
+    query: &str,
+    ctx: &AgentContext,
+    filter: &KnowledgeFilter,
+    hits: &[KnowledgeEntry],
+    json: bool,
+) -> Result<()> {
+    let mut scores: HashMap<String, TextScore> = db.search_scores(query, ctx, filter)?;
+    let settings = confidence::Settings::load()?;
+    let now = Utc::now();
+    let analyzer = search::Settings::load()?;
+    let fields = filter
+        .search_fields
+        .as_deref()
+        .unwrap_or(&SearchField::TEXT);
+    let terms: Vec<String> = terms(query)
+        .into_iter()
+        .filter(|t| !analyzer.is_stop_word(t))
+        .collect();
+
+    let explained: Vec<Hit> = hits
+        .iter()
+        .map(|entry| Hit {
+            entry,
+            explain: explain(
+                entry,
+                scores.remove(&entry.id).unwrap_or_default(),
+                fields,
+                &terms,
+                &settings,
+                now,
+            ),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&explained)?);
+    } else if explained.is_empty() {
+        println!("No results for '{}'", query);
+    } else {
+        println!(
+            "Found {} results (ranked by text score x confidence):\n",
+            explained.len()
+        );
+        for hit in &explained {
+            print(hit.entry, &hit.explain, fields, &terms);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_terms_and_splits_score_components() {
+        let terms = terms("Retry, backoff retry");
+        assert_eq!(terms, ["retry", "backoff"]);
+        let mark = |w: &str| format!("[{}]", w);
+        assert_eq!(
+            highlight("Retry with exponential backoff", &terms, mark).unwrap(),
+            "[Retry] with exponential [backoff]"
+        );
+        assert!(highlight("retrying later", &terms, mark).is_none());
+
+        let long = format!("{} then retry {}", "word ".repeat(30), "tail ".repeat(40));
+        let line = snippet(&long, &terms);
+        assert!(line.starts_with("...") && line.ends_with("..."));
+        assert!(line.contains("then retry"));
+
+        let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-x",
+            "category_id": "reference",
+            "title": "Retry with backoff",
+            "body": "Nothing relevant",
+            "summary": "When to RETRY",
+            "tags": ["Backoff", "go"],
+            "confidence": 0.8,
+            "confirmed_at": (Utc::now() - chrono::Duration::days(180)).to_rfc3339(),
+        }))
+        .unwrap();
+        let text = TextScore {
+            title: 1.2,
+            body: 0.0,
+            summary: 0.3,
+            tags: 0.0,
+        };
+        let why = explain(
+            &entry,
+            text,
+            &SearchField::TEXT,
+            &terms,
+            &confidence::Settings::default(),
+            Utc::now(),
+        );
+        assert_eq!(why.matched, ["title", "summary"]);
+        let tagged = matched_fields(&entry, &[SearchField::Tags], &terms, mark);
+        assert_eq!(tagged, [("tags", "[Backoff]".to_string())]);
+        assert_eq!(why.rated, 0.8);
+        assert!((why.age_decay - 0.5).abs() < 0.01);
+        assert!((why.confidence - 0.4).abs() < 0.01);
+        assert!((why.text.total() - 1.5).abs() < 1e-9);
+        assert!((why.score - 1.35).abs() < 0.02);
+    }
+}
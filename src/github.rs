@@ -3,6 +3,7 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::confirm;
 use crate::sync::github::app_auth::get_installation_token;
 use crate::sync::github::auth::get_github_token;
 use crate::sync::github::graphql::{DiscussionCommentCreated, GraphQLClient};
@@ -27,6 +28,7 @@ pub fn cleanup(
     issues: Option<String>,
     discussions: Option<String>,
     dry_run: bool,
+    yes: bool,
 ) -> Result<()> {
     // Parse repo
     let parts: Vec<&str> = repo.split('/').collect();
@@ -53,6 +55,28 @@ pub fn cleanup(
         return Ok(());
     }
 
+    if !dry_run {
+        let list = |prefix: &str, numbers: &[u64]| {
+            let numbers: Vec<String> = numbers.iter().map(|n| format!("{}{}", prefix, n)).collect();
+            numbers.join(", ")
+        };
+        let mut preview = vec![format!("{}/{}", owner, repo_name)];
+        if !issue_numbers.is_empty() {
+            preview.push(format!("close {}", list("#", &issue_numbers)));
+        }
+        if !discussion_numbers.is_empty() {
+            preview.push(format!("delete {}", list("D#", &discussion_numbers)));
+        }
+        let affected = [
+            (issue_numbers.len(), "issue", "issues"),
+            (discussion_numbers.len(), "discussion", "discussions"),
+        ];
+        if !confirm::confirm("Clean up", &affected, &preview, yes)? {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
     println!("Cleaning up {}/{}", owner, repo_name);
     if dry_run {
         println!("[DRY RUN MODE]");
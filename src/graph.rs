@@ -0,0 +1,1081 @@
+//! Knowledge graph analytics: `mx memory analyze-graph`
+//!
+//! Treats visible entries as nodes and relationships plus anchors as
+//! undirected edges, then computes degree and betweenness centrality and a
+//! label-propagation community split. The report surfaces hub entries, small
+//! clusters disconnected from the main graph, and bridge entries that connect
+//! otherwise separate communities — the places curation attention pays off.
+//!
+//! The same graph backs `mx memory export-graph`, which writes GraphML
+//! (Gephi, yEd), Cypher (Neo4j), DOT (Graphviz), or D3 force-layout JSON,
+//! and `mx memory graph`, which walks out from one entry along its links in
+//! both directions and prints the neighbourhood as a tree, DOT, or JSON.
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::error::MxError;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Edge kind used for anchor links (relationships use their type id)
+pub const ANCHOR_EDGE: &str = "anchor";
+
+/// A knowledge entry as a graph node
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub resonance: i32,
+    pub tags: Vec<String>,
+}
+
+/// A directed edge between two entries
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+}
+
+/// Entries and the links between them, restricted to what the caller can see
+#[derive(Debug, Clone, Default)]
+pub struct KnowledgeGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl KnowledgeGraph {
+    /// Load every visible entry plus its relationship and anchor edges.
+    /// Edges that touch an entry outside `ctx` are dropped.
+    pub fn load(db: &dyn KnowledgeStore, ctx: &AgentContext) -> Result<Self> {
+        let entries = db.list_all(ctx)?;
+        let relationships = db.list_all_relationships()?;
+
+        let nodes: Vec<GraphNode> = entries
+            .iter()
+            .map(|e| GraphNode {
+                id: e.id.clone(),
+                title: e.title.clone(),
+                category: e.category_id.clone(),
+                resonance: e.resonance,
+                tags: e.tags.clone(),
+            })
+            .collect();
+
+        let mut edges: Vec<GraphEdge> = relationships
+            .into_iter()
+            .map(|r| GraphEdge {
+                source: r.from_entry_id,
+                target: r.to_entry_id,
+                kind: r.relationship_type,
+            })
+            .collect();
+
+        for entry in &entries {
+            for anchor in &entry.anchors {
+                let target = if anchor.starts_with("kn-") {
+                    anchor.clone()
+                } else {
+                    format!("kn-{}", anchor)
+                };
+                edges.push(GraphEdge {
+                    source: entry.id.clone(),
+                    target,
+                    kind: ANCHOR_EDGE.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::from_parts(nodes, edges))
+    }
+
+    /// Build a graph, dropping self-loops, duplicate edges, and edges whose
+    /// endpoints are not among `nodes`
+    pub fn from_parts(nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> Self {
+        let known: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        let mut seen = HashSet::new();
+        let edges = edges
+            .into_iter()
+            .filter(|e| {
+                e.source != e.target
+                    && known.contains(e.source.as_str())
+                    && known.contains(e.target.as_str())
+            })
+            .filter(|e| seen.insert((e.source.clone(), e.target.clone(), e.kind.clone())))
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Keep only entries matching every given filter, and the edges between them
+    pub fn filtered(
+        &self,
+        category: Option<&str>,
+        tag: Option<&str>,
+        min_resonance: Option<i32>,
+    ) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|n| category.is_none_or(|c| n.category == c))
+            .filter(|n| tag.is_none_or(|t| n.tags.iter().any(|nt| nt == t)))
+            .filter(|n| min_resonance.is_none_or(|r| n.resonance >= r))
+            .cloned()
+            .collect();
+        Self::from_parts(nodes, self.edges.clone())
+    }
+
+    /// Undirected adjacency lists indexed like `nodes`
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        let index: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+
+        let mut adj: Vec<HashSet<usize>> = vec![HashSet::new(); self.nodes.len()];
+        for edge in &self.edges {
+            if let (Some(&a), Some(&b)) = (
+                index.get(edge.source.as_str()),
+                index.get(edge.target.as_str()),
+            ) {
+                adj[a].insert(b);
+                adj[b].insert(a);
+            }
+        }
+
+        adj.into_iter()
+            .map(|set| {
+                let mut v: Vec<usize> = set.into_iter().collect();
+                v.sort_unstable();
+                v
+            })
+            .collect()
+    }
+}
+
+/// Betweenness centrality (Brandes), normalized to 0.0-1.0 for undirected graphs
+pub fn betweenness_centrality(adj: &[Vec<usize>]) -> Vec<f64> {
+    let n = adj.len();
+    let mut centrality = vec![0.0; n];
+
+    for s in 0..n {
+        let mut stack = Vec::new();
+        let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut sigma = vec![0.0f64; n];
+        let mut dist = vec![-1i64; n];
+        sigma[s] = 1.0;
+        dist[s] = 0;
+
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            for &w in &adj[v] {
+                if dist[w] < 0 {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    sigma[w] += sigma[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+
+        let mut delta = vec![0.0f64; n];
+        while let Some(w) = stack.pop() {
+            for &v in &preds[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != s {
+                centrality[w] += delta[w];
+            }
+        }
+    }
+
+    // Each pair is counted from both ends in an undirected graph
+    if n > 2 {
+        let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+        for c in &mut centrality {
+            *c *= scale;
+        }
+    }
+    centrality
+}
+
+/// Connected component id per node (ids are dense, in order of first node)
+pub fn connected_components(adj: &[Vec<usize>]) -> Vec<usize> {
+    let mut component = vec![usize::MAX; adj.len()];
+    let mut next = 0;
+    for start in 0..adj.len() {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        let mut queue = VecDeque::from([start]);
+        component[start] = next;
+        while let Some(v) = queue.pop_front() {
+            for &w in &adj[v] {
+                if component[w] == usize::MAX {
+                    component[w] = next;
+                    queue.push_back(w);
+                }
+            }
+        }
+        next += 1;
+    }
+    component
+}
+
+/// Community label per node via deterministic label propagation.
+///
+/// Nodes adopt the label with the highest total edge weight among their
+/// neighbors, where each edge is weighted by the overlap of its endpoints'
+/// neighborhoods, so tightly knit groups hold together instead of leaking
+/// along chains. Ties keep the current label, otherwise go to the smallest.
+/// Isolated nodes keep their own label.
+pub fn label_propagation(adj: &[Vec<usize>]) -> Vec<usize> {
+    let mut labels: Vec<usize> = (0..adj.len()).collect();
+    let weight = |a: usize, b: usize| {
+        let na: HashSet<usize> = adj[a].iter().copied().chain([a]).collect();
+        let nb: HashSet<usize> = adj[b].iter().copied().chain([b]).collect();
+        na.intersection(&nb).count() as f64 / na.union(&nb).count() as f64
+    };
+    let weights: Vec<Vec<f64>> = (0..adj.len())
+        .map(|v| adj[v].iter().map(|&w| weight(v, w)).collect())
+        .collect();
+
+    for _ in 0..50 {
+        let mut changed = false;
+        for v in 0..adj.len() {
+            if adj[v].is_empty() {
+                continue;
+            }
+            let mut scores: BTreeMap<usize, f64> = BTreeMap::new();
+            for (&w, &weight) in adj[v].iter().zip(&weights[v]) {
+                *scores.entry(labels[w]).or_default() += weight;
+            }
+            let best_score = scores.values().copied().fold(0.0, f64::max);
+            let is_best = |score: f64| (score - best_score).abs() < 1e-9;
+            if scores.get(&labels[v]).is_some_and(|s| is_best(*s)) {
+                continue;
+            }
+            if let Some((&best, _)) = scores.iter().find(|(_, s)| is_best(**s)) {
+                labels[v] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+/// A node and its centrality scores
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeScore {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub degree: usize,
+    pub betweenness: f64,
+    /// Number of distinct communities among this node's neighbors
+    pub communities_touched: usize,
+}
+
+/// A connected component outside the main graph
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterSummary {
+    pub size: usize,
+    pub members: Vec<String>,
+}
+
+/// Full analysis report
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphAnalysis {
+    pub nodes: usize,
+    pub edges: usize,
+    pub isolated_entries: usize,
+    pub components: usize,
+    pub largest_component: usize,
+    pub communities: usize,
+    pub hubs: Vec<NodeScore>,
+    pub bridges: Vec<NodeScore>,
+    pub isolated_clusters: Vec<ClusterSummary>,
+}
+
+/// Analyze a graph, keeping the `top` entries in each ranked list
+pub fn analyze(graph: &KnowledgeGraph, top: usize) -> GraphAnalysis {
+    let adj = graph.adjacency();
+    let betweenness = betweenness_centrality(&adj);
+    let components = connected_components(&adj);
+    let communities = label_propagation(&adj);
+
+    let score = |i: usize| {
+        let touched: HashSet<usize> = adj[i].iter().map(|&w| communities[w]).collect();
+        NodeScore {
+            id: graph.nodes[i].id.clone(),
+            title: graph.nodes[i].title.clone(),
+            category: graph.nodes[i].category.clone(),
+            degree: adj[i].len(),
+            betweenness: betweenness[i],
+            communities_touched: touched.len(),
+        }
+    };
+
+    let mut hubs: Vec<NodeScore> = (0..adj.len())
+        .filter(|&i| !adj[i].is_empty())
+        .map(score)
+        .collect();
+    hubs.sort_by(|a, b| {
+        b.degree
+            .cmp(&a.degree)
+            .then(b.betweenness.total_cmp(&a.betweenness))
+            .then(a.id.cmp(&b.id))
+    });
+    hubs.truncate(top);
+
+    let mut bridges: Vec<NodeScore> = (0..adj.len())
+        .map(score)
+        .filter(|s| s.communities_touched >= 2)
+        .collect();
+    bridges.sort_by(|a, b| {
+        b.betweenness
+            .total_cmp(&a.betweenness)
+            .then(a.id.cmp(&b.id))
+    });
+    bridges.truncate(top);
+
+    // Group connected components with at least one edge
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &c) in components.iter().enumerate() {
+        if !adj[i].is_empty() {
+            groups.entry(c).or_default().push(i);
+        }
+    }
+    let largest = groups.values().map(Vec::len).max().unwrap_or(0);
+    let mut clusters: Vec<&Vec<usize>> = groups.values().collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    let isolated_clusters = clusters
+        .into_iter()
+        .skip(1)
+        .take(top)
+        .map(|members| ClusterSummary {
+            size: members.len(),
+            members: members.iter().map(|&i| graph.nodes[i].id.clone()).collect(),
+        })
+        .collect();
+
+    let community_count = (0..adj.len())
+        .filter(|&i| !adj[i].is_empty())
+        .map(|i| communities[i])
+        .collect::<HashSet<_>>()
+        .len();
+
+    GraphAnalysis {
+        nodes: graph.nodes.len(),
+        edges: graph.edges.len(),
+        isolated_entries: adj.iter().filter(|a| a.is_empty()).count(),
+        components: groups.len(),
+        largest_component: largest,
+        communities: community_count,
+        hubs,
+        bridges,
+        isolated_clusters,
+    }
+}
+
+/// Print the analysis as a human-readable report
+pub fn print_analysis(analysis: &GraphAnalysis) {
+    println!("Knowledge graph");
+    println!("  Entries:            {}", analysis.nodes);
+    println!("  Edges:              {}", analysis.edges);
+    println!("  Unlinked entries:   {}", analysis.isolated_entries);
+    println!(
+        "  Linked components:  {} (largest: {} entries)",
+        analysis.components, analysis.largest_component
+    );
+    println!("  Communities:        {}", analysis.communities);
+
+    if !analysis.hubs.is_empty() {
+        println!("\nHub entries (most connected):");
+        for s in &analysis.hubs {
+            println!(
+                "  {:<24} degree {:>3}  betweenness {:.3}  [{}] {}",
+                s.id, s.degree, s.betweenness, s.category, s.title
+            );
+        }
+    }
+
+    if !analysis.bridges.is_empty() {
+        println!("\nBridge entries (connect separate communities):");
+        for s in &analysis.bridges {
+            println!(
+                "  {:<24} betweenness {:.3}  communities {:>2}  [{}] {}",
+                s.id, s.betweenness, s.communities_touched, s.category, s.title
+            );
+        }
+    }
+
+    if !analysis.isolated_clusters.is_empty() {
+        println!("\nIsolated clusters (not connected to the main graph):");
+        for c in &analysis.isolated_clusters {
+            println!("  {} entries: {}", c.size, c.members.join(", "));
+        }
+    }
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+#[derive(clap::Args)]
+pub struct AnalyzeArgs {
+    /// Number of entries to show per ranking
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+
+    /// Include private entries owned by the current agent
+    #[arg(long)]
+    pub include_private: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "graphml")]
+    pub format: GraphFormat,
+
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Leave tags out (by default each tag is a node linked to its entries)
+    #[arg(long)]
+    pub no_tags: bool,
+
+    /// Include private entries owned by the current agent
+    #[arg(long)]
+    pub include_private: bool,
+}
+
+/// Output format for `mx memory export-graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// GraphML XML (Gephi, yEd, networkx)
+    Graphml,
+    /// Cypher CREATE statements (Neo4j)
+    Cypher,
+    /// D3 force-layout JSON ({nodes, links})
+    D3,
+    /// Graphviz DOT
+    Dot,
+}
+
+/// Edge kind for entry -> tag links
+pub const TAG_EDGE: &str = "tagged";
+
+/// Node id used for a tag when tags are exported as nodes
+fn tag_node_id(tag: &str) -> String {
+    format!("tag:{}", tag)
+}
+
+/// Render the graph in the requested format. With `include_tags`, every tag
+/// becomes a node linked from the entries that carry it.
+pub fn render(graph: &KnowledgeGraph, format: GraphFormat, include_tags: bool) -> Result<String> {
+    let tags: Vec<&str> = if include_tags {
+        let mut tags: Vec<&str> = graph
+            .nodes
+            .iter()
+            .flat_map(|n| n.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    } else {
+        Vec::new()
+    };
+    let mut edges: Vec<GraphEdge> = graph.edges.clone();
+    if include_tags {
+        for node in &graph.nodes {
+            for tag in &node.tags {
+                edges.push(GraphEdge {
+                    source: node.id.clone(),
+                    target: tag_node_id(tag),
+                    kind: TAG_EDGE.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(match format {
+        GraphFormat::Graphml => render_graphml(&graph.nodes, &tags, &edges),
+        GraphFormat::Cypher => render_cypher(&graph.nodes, &tags, &edges),
+        GraphFormat::D3 => render_d3(&graph.nodes, &tags, &edges)?,
+        GraphFormat::Dot => render_dot(&graph.nodes, &tags, &edges),
+    })
+}
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn render_graphml(nodes: &[GraphNode], tags: &[&str], edges: &[GraphEdge]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    out.push_str(
+        "  <key id=\"category\" for=\"node\" attr.name=\"category\" attr.type=\"string\"/>\n",
+    );
+    out.push_str(
+        "  <key id=\"resonance\" for=\"node\" attr.name=\"resonance\" attr.type=\"int\"/>\n",
+    );
+    out.push_str("  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>\n");
+    out.push_str("  <graph id=\"knowledge\" edgedefault=\"directed\">\n");
+
+    for node in nodes {
+        out.push_str(&format!(
+            "    <node id=\"{}\">\n      <data key=\"kind\">entry</data>\n      <data key=\"label\">{}</data>\n      <data key=\"category\">{}</data>\n      <data key=\"resonance\">{}</data>\n    </node>\n",
+            xml_escape(&node.id),
+            xml_escape(&node.title),
+            xml_escape(&node.category),
+            node.resonance
+        ));
+    }
+    for tag in tags {
+        out.push_str(&format!(
+            "    <node id=\"{}\">\n      <data key=\"kind\">tag</data>\n      <data key=\"label\">{}</data>\n    </node>\n",
+            xml_escape(&tag_node_id(tag)),
+            xml_escape(tag)
+        ));
+    }
+    for (i, edge) in edges.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"type\">{}</data>\n    </edge>\n",
+            i,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            xml_escape(&edge.kind)
+        ));
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Quote a string as a Cypher literal
+fn cypher_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Relationship types become upper-case Cypher relationship labels
+fn cypher_rel_type(kind: &str) -> String {
+    kind.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn render_cypher(nodes: &[GraphNode], tags: &[&str], edges: &[GraphEdge]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        out.push_str(&format!(
+            "MERGE (n:Entry {{id: {}}}) SET n.title = {}, n.category = {}, n.resonance = {};\n",
+            cypher_string(&node.id),
+            cypher_string(&node.title),
+            cypher_string(&node.category),
+            node.resonance
+        ));
+    }
+    for tag in tags {
+        out.push_str(&format!(
+            "MERGE (:Tag {{id: {}, name: {}}});\n",
+            cypher_string(&tag_node_id(tag)),
+            cypher_string(tag)
+        ));
+    }
+    for edge in edges {
+        let target_label = if edge.kind == TAG_EDGE {
+            "Tag"
+        } else {
+            "Entry"
+        };
+        out.push_str(&format!(
+            "MATCH (a:Entry {{id: {}}}), (b:{} {{id: {}}}) MERGE (a)-[:{}]->(b);\n",
+            cypher_string(&edge.source),
+            target_label,
+            cypher_string(&edge.target),
+            cypher_rel_type(&edge.kind)
+        ));
+    }
+    out
+}
+
+fn render_d3(nodes: &[GraphNode], tags: &[&str], edges: &[GraphEdge]) -> Result<String> {
+    let mut d3_nodes: Vec<serde_json::Value> = nodes
+        .iter()
+        .map(|n| {
+            serde_json::json!({
+                "id": n.id,
+                "kind": "entry",
+                "label": n.title,
+                "category": n.category,
+                "resonance": n.resonance,
+                "tags": n.tags,
+            })
+        })
+        .collect();
+    d3_nodes.extend(tags.iter().map(|t| {
+        serde_json::json!({
+            "id": tag_node_id(t),
+            "kind": "tag",
+            "label": t,
+        })
+    }));
+    let links: Vec<serde_json::Value> = edges
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "source": e.source,
+                "target": e.target,
+                "type": e.kind,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "nodes": d3_nodes,
+        "links": links,
+    }))?)
+}
+
+fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_dot(nodes: &[GraphNode], tags: &[&str], edges: &[GraphEdge]) -> String {
+    let mut out = String::from("digraph knowledge {\n");
+    for n in nodes {
+        out.push_str(&format!(
+            "  {} [label={}, category={}];\n",
+            dot_string(&n.id),
+            dot_string(&n.title),
+            dot_string(&n.category)
+        ));
+    }
+    for t in tags {
+        out.push_str(&format!(
+            "  {} [label={}, shape=box];\n",
+            dot_string(&tag_node_id(t)),
+            dot_string(t)
+        ));
+    }
+    for e in edges {
+        out.push_str(&format!(
+            "  {} -> {} [label={}];\n",
+            dot_string(&e.source),
+            dot_string(&e.target),
+            dot_string(&e.kind)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Output format for `mx memory graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TraversalFormat {
+    /// Indented tree from the starting entry
+    Tree,
+    /// Graphviz DOT
+    Dot,
+    /// {root, nodes (with depth), edges}
+    Json,
+}
+
+#[derive(clap::Args)]
+pub struct GraphArgs {
+    /// Entry to start from
+    pub id: String,
+
+    /// How many links to follow out from it
+    #[arg(long, default_value = "2")]
+    pub depth: usize,
+
+    /// Only follow these relationship types (comma-separated; `anchor` for anchors)
+    #[arg(long, value_delimiter = ',')]
+    pub rel_type: Vec<String>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "tree")]
+    pub format: TraversalFormat,
+
+    /// Include private entries owned by the current agent
+    #[arg(long)]
+    pub include_private: bool,
+}
+
+/// What a walk from one entry reached
+#[derive(Debug, Serialize)]
+pub struct Traversal {
+    pub root: String,
+    /// Reached entries in the order found
+    pub nodes: Vec<ReachedNode>,
+    /// Every followed link between reached entries
+    pub edges: Vec<GraphEdge>,
+    /// How each entry past the root was first reached: (entry, from, link)
+    #[serde(skip)]
+    tree: Vec<(String, String, GraphEdge)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReachedNode {
+    #[serde(flatten)]
+    pub node: GraphNode,
+    /// Links from the root
+    pub depth: usize,
+}
+
+/// Breadth-first walk from `root` along edges of the given kinds (any kind
+/// when empty), following each in both directions, up to `depth` links
+pub fn traverse(graph: &KnowledgeGraph, root: &str, depth: usize, kinds: &[String]) -> Traversal {
+    let follow: Vec<&GraphEdge> = graph
+        .edges
+        .iter()
+        .filter(|e| kinds.is_empty() || kinds.contains(&e.kind))
+        .collect();
+
+    let mut found: HashMap<String, usize> = HashMap::from([(root.to_string(), 0)]);
+    let mut order = vec![root.to_string()];
+    let mut tree = Vec::new();
+    let mut queue = VecDeque::from([root.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        let distance = found[&current];
+        if distance == depth {
+            continue;
+        }
+        for &edge in &follow {
+            let next = if edge.source == current {
+                &edge.target
+            } else if edge.target == current {
+                &edge.source
+            } else {
+                continue;
+            };
+            if !found.contains_key(next) {
+                found.insert(next.clone(), distance + 1);
+                order.push(next.clone());
+                tree.push((next.clone(), current.clone(), edge.clone()));
+                queue.push_back(next.clone());
+            }
+        }
+    }
+
+    let by_id: HashMap<&str, &GraphNode> = graph.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    Traversal {
+        root: root.to_string(),
+        nodes: order
+            .iter()
+            .filter_map(|id| {
+                by_id.get(id.as_str()).map(|n| ReachedNode {
+                    node: (*n).clone(),
+                    depth: found[id],
+                })
+            })
+            .collect(),
+        edges: follow
+            .into_iter()
+            .filter(|e| found.contains_key(&e.source) && found.contains_key(&e.target))
+            .cloned()
+            .collect(),
+        tree,
+    }
+}
+
+impl Traversal {
+    /// The walk as an indented tree: each entry under the one it was reached
+    /// from, with `->` for links pointing away from that one and `<-` for
+    /// links pointing back at it
+    pub fn render_tree(&self) -> String {
+        let titles: HashMap<&str, &str> = self
+            .nodes
+            .iter()
+            .map(|n| (n.node.id.as_str(), n.node.title.as_str()))
+            .collect();
+        let mut children: Children = HashMap::new();
+        for (id, from, edge) in &self.tree {
+            children
+                .entry(from.as_str())
+                .or_default()
+                .push((id.as_str(), edge));
+        }
+
+        let mut out = format!(
+            "{}  {}\n",
+            self.root,
+            titles.get(self.root.as_str()).unwrap_or(&"")
+        );
+        tree_lines(&mut out, &self.root, "", &children, &titles);
+        out
+    }
+}
+
+/// `mx memory graph`: walk out from one entry and print what it reaches
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: GraphArgs) -> Result<()> {
+    let root = crate::normalize_id(&args.id);
+    let graph = KnowledgeGraph::load(db, ctx)?;
+    if !graph.nodes.iter().any(|n| n.id == root) {
+        bail!(MxError::not_found("Entry", root));
+    }
+    let known: Vec<String> = db
+        .list_relationship_types()?
+        .into_iter()
+        .map(|t| t.id)
+        .chain([ANCHOR_EDGE.to_string()])
+        .collect();
+    for kind in &args.rel_type {
+        if !known.contains(kind) {
+            bail!(
+                "Unknown relationship type '{}'. Valid types: {}",
+                kind,
+                known.join(", ")
+            );
+        }
+    }
+
+    let walk = traverse(&graph, &root, args.depth, &args.rel_type);
+    match args.format {
+        TraversalFormat::Tree => print!("{}", walk.render_tree()),
+        TraversalFormat::Dot => {
+            let nodes: Vec<GraphNode> = walk.nodes.iter().map(|n| n.node.clone()).collect();
+            print!("{}", render_dot(&nodes, &[], &walk.edges));
+        }
+        TraversalFormat::Json => println!("{}", serde_json::to_string_pretty(&walk)?),
+    }
+    Ok(())
+}
+
+type Children<'a> = HashMap<&'a str, Vec<(&'a str, &'a GraphEdge)>>;
+
+fn tree_lines(
+    out: &mut String,
+    parent: &str,
+    prefix: &str,
+    children: &Children,
+    titles: &HashMap<&str, &str>,
+) {
+    let Some(kids) = children.get(parent) else {
+        return;
+    };
+    for (k, (id, edge)) in kids.iter().enumerate() {
+        let last = k + 1 == kids.len();
+        out.push_str(&format!(
+            "{}{} {} {} {}  {}\n",
+            prefix,
+            if last { "└──" } else { "├──" },
+            if edge.source == parent { "->" } else { "<-" },
+            edge.kind,
+            id,
+            titles.get(id).unwrap_or(&"")
+        ));
+        let deeper = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        tree_lines(out, id, &deeper, children, titles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: "pattern".to_string(),
+            resonance: 5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(a: &str, b: &str) -> GraphEdge {
+        GraphEdge {
+            source: a.to_string(),
+            target: b.to_string(),
+            kind: "related".to_string(),
+        }
+    }
+
+    /// Two triangles joined through `m`, plus a detached pair and a loner
+    fn sample_graph() -> KnowledgeGraph {
+        let nodes = ["a", "b", "c", "m", "x", "y", "z", "p", "q", "lone"]
+            .into_iter()
+            .map(node)
+            .collect();
+        let edges = vec![
+            edge("a", "b"),
+            edge("b", "c"),
+            edge("c", "a"),
+            edge("c", "m"),
+            edge("m", "x"),
+            edge("x", "y"),
+            edge("y", "z"),
+            edge("z", "x"),
+            edge("p", "q"),
+            edge("a", "b"),       // duplicate
+            edge("a", "missing"), // dangling
+        ];
+        KnowledgeGraph::from_parts(nodes, edges)
+    }
+
+    #[test]
+    fn from_parts_drops_duplicates_and_dangling_edges() {
+        let graph = sample_graph();
+        assert_eq!(graph.edges.len(), 9);
+    }
+
+    #[test]
+    fn betweenness_peaks_at_the_bridge() {
+        let graph = sample_graph();
+        let adj = graph.adjacency();
+        let bc = betweenness_centrality(&adj);
+        let m = graph.nodes.iter().position(|n| n.id == "m").unwrap();
+        let a = graph.nodes.iter().position(|n| n.id == "a").unwrap();
+        assert!(bc[m] > bc[a]);
+        assert_eq!(bc[graph.nodes.len() - 1], 0.0);
+    }
+
+    #[test]
+    fn path_betweenness_is_normalized() {
+        // a - b - c: b sits on the only a..c path
+        let adj = vec![vec![1], vec![0, 2], vec![1]];
+        let bc = betweenness_centrality(&adj);
+        assert!((bc[1] - 1.0).abs() < 1e-9);
+        assert_eq!(bc[0], 0.0);
+    }
+
+    #[test]
+    fn analysis_reports_structure() {
+        let analysis = analyze(&sample_graph(), 5);
+        assert_eq!(analysis.nodes, 10);
+        assert_eq!(analysis.isolated_entries, 1);
+        assert_eq!(analysis.components, 2);
+        assert_eq!(analysis.largest_component, 7);
+        assert_eq!(analysis.isolated_clusters.len(), 1);
+        assert_eq!(analysis.isolated_clusters[0].members, vec!["p", "q"]);
+        assert!(analysis.communities >= 3);
+        assert!(analysis.bridges.iter().any(|b| b.id == "m" || b.id == "c"));
+    }
+
+    #[test]
+    fn label_propagation_separates_triangles() {
+        let graph = sample_graph();
+        let labels = label_propagation(&graph.adjacency());
+        let pos = |id: &str| graph.nodes.iter().position(|n| n.id == id).unwrap();
+        assert_eq!(labels[pos("a")], labels[pos("b")]);
+        assert_eq!(labels[pos("x")], labels[pos("z")]);
+        assert_ne!(labels[pos("a")], labels[pos("x")]);
+    }
+
+    fn tagged_graph() -> KnowledgeGraph {
+        let mut a = node("kn-a");
+        a.title = "Use <T> & 'friends'".to_string();
+        a.tags = vec!["rust".to_string()];
+        let mut b = node("kn-b");
+        b.tags = vec!["rust".to_string(), "db".to_string()];
+        KnowledgeGraph::from_parts(vec![a, b], vec![edge("kn-a", "kn-b")])
+    }
+
+    #[test]
+    fn graphml_escapes_and_includes_tags() {
+        let out = render(&tagged_graph(), GraphFormat::Graphml, true).unwrap();
+        assert!(out.contains("Use &lt;T&gt; &amp; &apos;friends&apos;"));
+        assert!(out.contains("<node id=\"tag:rust\">"));
+        assert_eq!(out.matches("<edge ").count(), 4);
+    }
+
+    #[test]
+    fn cypher_quotes_and_labels_edges() {
+        let out = render(&tagged_graph(), GraphFormat::Cypher, false).unwrap();
+        assert!(out.contains("n.title = 'Use <T> & \\'friends\\''"));
+        assert!(out.contains("MERGE (a)-[:RELATED]->(b)"));
+        assert!(!out.contains(":Tag"));
+    }
+
+    #[test]
+    fn d3_has_nodes_and_links() {
+        let out = render(&tagged_graph(), GraphFormat::D3, true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(value["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(value["links"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn filtered_keeps_matching_nodes_and_their_edges() {
+        let graph = tagged_graph();
+        let only_db = graph.filtered(None, Some("db"), None);
+        assert_eq!(only_db.nodes.len(), 1);
+        assert!(only_db.edges.is_empty());
+
+        let all = graph.filtered(Some("pattern"), Some("rust"), Some(5));
+        assert_eq!(all.nodes.len(), 2);
+        assert_eq!(all.edges.len(), 1);
+        assert!(graph.filtered(None, None, Some(6)).nodes.is_empty());
+    }
+
+    #[test]
+    fn traversal_walks_both_ways_to_depth_and_by_kind() {
+        let mut edges = vec![
+            edge("a", "b"),
+            edge("c", "a"),
+            edge("b", "x"),
+            edge("x", "y"),
+        ];
+        edges.push(GraphEdge {
+            source: "a".to_string(),
+            target: "p".to_string(),
+            kind: "builds_on".to_string(),
+        });
+        let nodes = ["a", "b", "c", "x", "y", "p", "lone"]
+            .into_iter()
+            .map(node)
+            .collect();
+        let graph = KnowledgeGraph::from_parts(nodes, edges);
+
+        let walk = traverse(&graph, "a", 2, &[]);
+        let reached: Vec<(&str, usize)> = walk
+            .nodes
+            .iter()
+            .map(|n| (n.node.id.as_str(), n.depth))
+            .collect();
+        assert_eq!(reached, [("a", 0), ("b", 1), ("c", 1), ("p", 1), ("x", 2)]);
+        assert_eq!(walk.edges.len(), 4);
+        assert_eq!(
+            walk.render_tree(),
+            "a  a\n├── -> related b  b\n│   └── -> related x  x\n├── <- related c  c\n└── -> builds_on p  p\n"
+        );
+
+        let built = traverse(&graph, "a", 2, &["builds_on".to_string()]);
+        assert_eq!(built.nodes.len(), 2);
+
+        let dot = render(&graph, GraphFormat::Dot, false).unwrap();
+        assert!(dot.starts_with("digraph knowledge {"));
+        assert!(dot.contains("\"a\" -> \"p\" [label=\"builds_on\"];"));
+    }
+}
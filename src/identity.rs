@@ -0,0 +1,210 @@
+//! Identity namespace: `mx memory identity seal|release|list`
+//!
+//! Foundational blooms can be sealed into a separate storage namespace
+//! (the `namespace` field on knowledge, guarded in the schema). The store
+//! itself decides what counts as identity:
+//!
+//! - Only blooms with resonance 8+ and a foundational or transformative
+//!   resonance type can be sealed (a field assertion).
+//! - While sealed, an entry's title, body and resonance type are frozen, its
+//!   resonance can only rise, and it cannot be deleted or moved out (the
+//!   `identity_guard` event). Every write path hits the same wall, including
+//!   `mx memory update`, import and sync.
+//! - Releasing is the one way out; it unlocks the guard for that statement.
+//!
+//! The wake cascade reads sealed entries first, ahead of the other core
+//! blooms.
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Value of the `namespace` field for sealed entries
+pub const IDENTITY_NAMESPACE: &str = "identity";
+
+/// Resonance an entry needs before it can be sealed
+pub const MIN_RESONANCE: i32 = 8;
+
+/// Resonance types that can be sealed
+pub const RESONANCE_TYPES: &[&str] = &["foundational", "transformative"];
+
+#[derive(clap::Args)]
+pub struct IdentityArgs {
+    #[command(subcommand)]
+    pub command: IdentityCommands,
+}
+
+#[derive(clap::Subcommand)]
+pub enum IdentityCommands {
+    /// Seal a foundational bloom into the identity namespace
+    Seal {
+        /// Entry ID
+        id: String,
+    },
+
+    /// Release a sealed entry so it can be edited or deleted again
+    Release {
+        /// Entry ID
+        id: String,
+    },
+
+    /// List sealed entries in wake order
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn is_sealed(entry: &KnowledgeEntry) -> bool {
+    entry.namespace.as_deref() == Some(IDENTITY_NAMESPACE)
+}
+
+/// Why `entry` can't be sealed, mirroring the schema's assertion so the
+/// user gets a readable message rather than the database's
+pub fn seal_problems(entry: &KnowledgeEntry) -> Vec<String> {
+    let mut problems = Vec::new();
+    if entry.category_id != "bloom" {
+        problems.push(format!("category is '{}', not 'bloom'", entry.category_id));
+    }
+    if entry.resonance < MIN_RESONANCE {
+        problems.push(format!(
+            "resonance is {}, needs {}+",
+            entry.resonance, MIN_RESONANCE
+        ));
+    }
+    let resonance_type = entry.resonance_type.as_deref().unwrap_or("none");
+    if !RESONANCE_TYPES.contains(&resonance_type) {
+        problems.push(format!(
+            "resonance type is '{}', needs {}",
+            resonance_type,
+            RESONANCE_TYPES.join(" or ")
+        ));
+    }
+    problems
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: IdentityArgs) -> Result<()> {
+    match args.command {
+        IdentityCommands::Seal { id } => {
+            let id = crate::normalize_id(&id);
+            let Some(entry) = db.get(&id, ctx)? else {
+                bail!(MxError::not_found("Entry", id));
+            };
+            if is_sealed(&entry) {
+                println!("{} is already sealed", id);
+                return Ok(());
+            }
+            let problems = seal_problems(&entry);
+            if !problems.is_empty() {
+                bail!("Cannot seal {}: {}", id, problems.join("; "));
+            }
+            db.set_identity(&id, true)?;
+            println!("{} Sealed {} into the identity namespace", "✓".green(), id);
+        }
+
+        IdentityCommands::Release { id } => {
+            let id = crate::normalize_id(&id);
+            let Some(entry) = db.get(&id, ctx)? else {
+                bail!(MxError::not_found("Entry", id));
+            };
+            if !is_sealed(&entry) {
+                println!("{} is not sealed", id);
+                return Ok(());
+            }
+            db.set_identity(&id, false)?;
+            println!(
+                "{} Released {} from the identity namespace",
+                "✓".green(),
+                id
+            );
+        }
+
+        IdentityCommands::List { json } => {
+            let mut entries: Vec<KnowledgeEntry> =
+                db.list_all(ctx)?.into_iter().filter(is_sealed).collect();
+            entries.sort_by_key(|e| (e.wake_order.unwrap_or(i32::MAX), -e.resonance));
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                println!("No sealed identity entries");
+            } else {
+                for entry in &entries {
+                    println!(
+                        "{} {} {}",
+                        entry.id.yellow(),
+                        entry.title,
+                        format!(
+                            "[r{} {}]",
+                            entry.resonance,
+                            entry.resonance_type.as_deref().unwrap_or("")
+                        )
+                        .dimmed()
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn bloom(id: &str, resonance: i32, resonance_type: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "bloom",
+            "title": "Who I am",
+            "body": "Curious first",
+            "resonance": resonance,
+            "resonance_type": resonance_type,
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn store_guards_sealed_entries() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        db.upsert_knowledge(&bloom("kn-core", 9, "foundational"))
+            .unwrap();
+        db.upsert_knowledge(&bloom("kn-minor", 5, "operational"))
+            .unwrap();
+
+        // The schema rejects entries that don't qualify, even without the CLI check
+        assert!(!seal_problems(&bloom("kn-minor", 5, "operational")).is_empty());
+        assert!(db.set_identity("kn-minor", true).is_err());
+        assert!(db.set_identity("kn-core", true).unwrap());
+        assert!(!db.set_identity("kn-missing", true).unwrap());
+
+        let sealed = db.get("kn-core", &ctx).unwrap().unwrap();
+        assert!(is_sealed(&sealed));
+
+        // Frozen while sealed: body edits and deletes fail, resonance can rise
+        let mut edited = sealed.clone();
+        edited.body = Some("Rewritten".into());
+        assert!(db.upsert_knowledge(&edited).is_err());
+        assert!(db.delete_knowledge("kn-core", &ctx).is_err());
+        let mut raised = sealed.clone();
+        raised.resonance = 10;
+        db.upsert_knowledge(&raised).unwrap();
+
+        let cascade = db.wake_cascade(&ctx, 5, None, 7).unwrap();
+        assert_eq!(cascade.core[0].id, "kn-core");
+
+        assert!(db.set_identity("kn-core", false).unwrap());
+        let mut released = db.get("kn-core", &ctx).unwrap().unwrap();
+        assert!(!is_sealed(&released));
+        released.body = Some("Rewritten".into());
+        db.upsert_knowledge(&released).unwrap();
+        assert!(db.delete_knowledge("kn-core", &ctx).unwrap());
+    }
+}
@@ -1,11 +1,16 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::Path;
 use walkdir::WalkDir;
 
+use crate::jsonl;
 use crate::knowledge::KnowledgeEntry;
 use crate::store::KnowledgeStore;
+use crate::text;
+use crate::types::{Agent, ApplicabilityType, Category, Project, Relationship};
 
 /// Index configuration
 pub struct IndexConfig {
@@ -88,184 +93,477 @@ fn is_excluded(entry: &walkdir::DirEntry, excluded: &[String]) -> bool {
         .unwrap_or(false)
 }
 
-/// Export database to markdown directory structure
-pub fn export_markdown(db: &dyn KnowledgeStore, dir_path: &Path) -> Result<()> {
+/// Export database to markdown directory structure: one file per entry
+/// under a directory per category
+pub fn export_markdown(
+    db: &dyn KnowledgeStore,
+    dir_path: &Path,
+    filter: &ExportFilter,
+) -> Result<()> {
     // Create base directory
     fs::create_dir_all(dir_path)
         .with_context(|| format!("Failed to create directory {:?}", dir_path))?;
 
-    // Export all categories dynamically
-    // Respect MX_CURRENT_AGENT for private entry access
-    let ctx = match std::env::var("MX_CURRENT_AGENT") {
-        Ok(agent) if !agent.is_empty() => crate::store::AgentContext::for_agent(agent),
-        _ => crate::store::AgentContext::public_only(),
-    };
-    let filter = crate::store::KnowledgeFilter::default();
-    let categories = db.list_categories()?;
-    for category in categories {
-        let entries = db.list_by_category(&category.id, &ctx, &filter)?;
-        if entries.is_empty() {
+    let entries = export_entries(db, filter)?;
+    for (entry, relative) in entries.iter().zip(markdown_paths(&entries)) {
+        write_markdown(&dir_path.join(relative), &render_markdown(entry)?)?;
+    }
+
+    Ok(())
+}
+
+/// What an incremental markdown export did
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncStats {
+    pub written: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+}
+
+impl std::fmt::Display for SyncStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} written, {} unchanged, {} deleted",
+            self.written, self.unchanged, self.deleted
+        )
+    }
+}
+
+/// Bring a markdown export directory up to date (`--incremental`). Files
+/// are matched to entries by the `id` in their frontmatter: a file is only
+/// rewritten when its content_hash (or anything else in it) changed, files
+/// of entries that are gone (or moved after a rename) are deleted, and
+/// files mx didn't write are left alone. Unchanged entries keep their
+/// files untouched, so re-syncing a big export is quick and git only sees
+/// real changes.
+pub fn sync_markdown(
+    db: &dyn KnowledgeStore,
+    dir_path: &Path,
+    filter: &ExportFilter,
+) -> Result<SyncStats> {
+    fs::create_dir_all(dir_path)
+        .with_context(|| format!("Failed to create directory {:?}", dir_path))?;
+
+    // Exported files already there, by entry id
+    let mut existing: std::collections::HashMap<String, Vec<std::path::PathBuf>> =
+        std::collections::HashMap::new();
+    for file in WalkDir::new(dir_path) {
+        let file = file?;
+        let path = file.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("md") {
             continue;
         }
+        if let Some(id) = frontmatter_id(path)? {
+            existing.entry(id).or_default().push(path.to_path_buf());
+        }
+    }
 
-        // Create category subdirectory
-        let category_dir = dir_path.join(&category.id);
-        fs::create_dir_all(&category_dir)
-            .with_context(|| format!("Failed to create category dir {:?}", category_dir))?;
-
-        for entry in entries {
-            // Generate filename from title
-            let filename = slugify(&entry.title);
-            let file_path = category_dir.join(format!("{}.md", filename));
+    let mut stats = SyncStats::default();
+    let entries = export_entries(db, filter)?;
+    for (entry, relative) in entries.iter().zip(markdown_paths(&entries)) {
+        let path = dir_path.join(relative);
+        let content = render_markdown(entry)?;
+        let previous = existing.remove(&entry.id).unwrap_or_default();
+        for stale in previous.iter().filter(|p| **p != path) {
+            fs::remove_file(stale).with_context(|| format!("Failed to remove {:?}", stale))?;
+            stats.deleted += 1;
+        }
+        if previous.contains(&path) && fs::read(&path)? == content {
+            stats.unchanged += 1;
+        } else {
+            write_markdown(&path, &content)?;
+            stats.written += 1;
+        }
+    }
 
-            // Handle filename collisions
-            let final_path = get_unique_path(&file_path)?;
+    // Whatever is left belongs to entries that are no longer exported
+    for path in existing.into_values().flatten() {
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        stats.deleted += 1;
+        if let Some(parent) = path.parent() {
+            // Only succeeds once the category directory is empty
+            let _ = fs::remove_dir(parent);
+        }
+    }
 
-            // Write entry to individual file
-            let file = File::create(&final_path)
-                .with_context(|| format!("Failed to create {:?}", final_path))?;
-            let mut writer = BufWriter::new(file);
+    Ok(stats)
+}
 
-            // Write frontmatter
-            writeln!(writer, "---")?;
-            writeln!(writer, "id: {}", entry.id)?;
-            writeln!(writer, "title: {}", entry.title)?;
-            writeln!(writer, "category: {}", entry.category_id)?;
+/// The `id:` frontmatter field of an exported markdown file, if it has one
+fn frontmatter_id(path: &Path) -> Result<Option<String>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut lines = BufReader::new(file).lines();
+    if lines.next().transpose()?.as_deref() != Some("---") {
+        return Ok(None);
+    }
+    for line in lines {
+        let line = line?;
+        if line == "---" {
+            break;
+        }
+        if let Some(id) = line.strip_prefix("id: ") {
+            return Ok(Some(id.trim().to_string()));
+        }
+    }
+    Ok(None)
+}
 
-            if !entry.tags.is_empty() {
-                writeln!(writer, "tags: [{}]", entry.tags.join(", "))?;
+/// Where each entry's file goes, relative to the export directory:
+/// `<category>/<title slug>.md`, with `-1`, `-2`, ... appended when titles
+/// collide within a category. `entries` are in export order, so the same
+/// data always gets the same paths.
+fn markdown_paths(entries: &[KnowledgeEntry]) -> Vec<std::path::PathBuf> {
+    let mut used = HashSet::new();
+    entries
+        .iter()
+        .map(|entry| {
+            // Generate filename from title
+            let mut stem = text::slug(&entry.title, 80);
+            if stem.is_empty() {
+                stem = entry.id.clone();
             }
-
-            if !entry.applicability.is_empty() {
-                if entry.applicability.len() == 1 {
-                    writeln!(writer, "applicability: {}", entry.applicability[0])?;
-                } else {
-                    writeln!(writer, "applicability:")?;
-                    for app in &entry.applicability {
-                        writeln!(writer, "  - {}", app)?;
-                    }
-                }
+            let mut name = format!("{}/{}.md", entry.category_id, stem);
+            let mut n = 1;
+            while !used.insert(name.clone()) {
+                name = format!("{}/{}-{}.md", entry.category_id, stem, n);
+                n += 1;
             }
+            std::path::PathBuf::from(name)
+        })
+        .collect()
+}
 
-            if let Some(created) = &entry.created_at {
-                writeln!(writer, "created: {}", created)?;
-            }
+fn write_markdown(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create category dir {:?}", parent))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to create {:?}", path))
+}
 
-            if let Some(updated) = &entry.updated_at {
-                writeln!(writer, "updated: {}", updated)?;
-            }
+/// A YAML scalar for frontmatter: as-is when that reads back the same,
+/// double-quoted otherwise (a JSON string is a valid YAML one)
+fn yaml_str(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || " -_./+()'?!@".contains(c))
+        && !s.starts_with(['-', '?', '@', '\''])
+        && serde_yaml::from_str::<serde_yaml::Value>(s).ok() == Some(s.into());
+    if plain {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).unwrap()
+    }
+}
 
-            if let Some(source_project) = &entry.source_project_id {
-                writeln!(writer, "source_project: {}", source_project)?;
-            }
+/// One entry as a markdown file: YAML frontmatter, then the body. Carries
+/// everything [`import_markdown`] needs to restore the entry.
+fn render_markdown(entry: &KnowledgeEntry) -> Result<Vec<u8>> {
+    let mut writer = Vec::new();
 
-            if let Some(source_agent) = &entry.source_agent_id {
-                writeln!(writer, "source_agent: {}", source_agent)?;
-            }
+    // Write frontmatter
+    writeln!(writer, "---")?;
+    writeln!(writer, "id: {}", entry.id)?;
+    writeln!(writer, "title: {}", yaml_str(&entry.title))?;
+    writeln!(writer, "category: {}", entry.category_id)?;
 
-            // Only write resonance if it's meaningful (non-zero)
-            if entry.resonance > 0 {
-                writeln!(writer, "resonance: {}", entry.resonance)?;
-            }
+    if let Some(summary) = &entry.summary {
+        writeln!(writer, "summary: {}", yaml_str(summary))?;
+    }
 
-            if let Some(ref resonance_type) = entry.resonance_type {
-                writeln!(writer, "resonance_type: {}", resonance_type)?;
-            }
+    if !entry.tags.is_empty() {
+        let tags: Vec<String> = entry.tags.iter().map(|t| yaml_str(t)).collect();
+        writeln!(writer, "tags: [{}]", tags.join(", "))?;
+    }
 
-            let active_phrases = entry.active_wake_phrases();
-            if !active_phrases.is_empty() {
-                // Quote it because wake phrases may contain special YAML characters
-                if active_phrases.len() == 1 {
-                    writeln!(
-                        writer,
-                        "wake_phrase: \"{}\"",
-                        active_phrases[0].replace("\"", "\\\"")
-                    )?;
-                } else {
-                    writeln!(writer, "wake_phrases:")?;
-                    for phrase in active_phrases {
-                        writeln!(writer, "  - \"{}\"", phrase.replace("\"", "\\\""))?;
-                    }
-                }
+    if !entry.applicability.is_empty() {
+        if entry.applicability.len() == 1 {
+            writeln!(
+                writer,
+                "applicability: {}",
+                yaml_str(&entry.applicability[0])
+            )?;
+        } else {
+            writeln!(writer, "applicability:")?;
+            for app in &entry.applicability {
+                writeln!(writer, "  - {}", yaml_str(app))?;
             }
+        }
+    }
 
-            writeln!(writer, "---\n")?;
+    if let Some(created) = &entry.created_at {
+        writeln!(writer, "created: {}", created)?;
+    }
 
-            // Write body
-            if let Some(body) = &entry.body {
-                writeln!(writer, "{}", body)?;
-            }
+    if let Some(updated) = &entry.updated_at {
+        writeln!(writer, "updated: {}", updated)?;
+    }
 
-            writer.flush()?;
-        }
+    if let Some(hash) = &entry.content_hash {
+        writeln!(writer, "content_hash: {}", hash)?;
     }
 
-    Ok(())
-}
+    if let Some(source_project) = &entry.source_project_id {
+        writeln!(writer, "source_project: {}", yaml_str(source_project))?;
+    }
 
-/// Slugify a string for use as a filename
-fn slugify(s: &str) -> String {
-    s.to_lowercase()
-        .chars()
-        .map(|c| match c {
-            'a'..='z' | '0'..='9' => c,
-            ' ' | '-' | '_' => '-',
-            _ => '_',
-        })
-        .collect::<String>()
-        .split('-')
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>()
-        .join("-")
-}
+    if let Some(source_agent) = &entry.source_agent_id {
+        writeln!(writer, "source_agent: {}", yaml_str(source_agent))?;
+    }
 
-/// Get unique path by appending -1, -2, etc. if file exists
-fn get_unique_path(path: &Path) -> Result<std::path::PathBuf> {
-    if !path.exists() {
-        return Ok(path.to_path_buf());
+    // Public is the default; private entries keep their owner
+    if entry.visibility != "public" {
+        writeln!(writer, "visibility: {}", entry.visibility)?;
+        if let Some(owner) = &entry.owner {
+            writeln!(writer, "owner: {}", yaml_str(owner))?;
+        }
     }
 
-    let stem = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .context("Invalid file stem")?;
-    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    let parent = path.parent().context("No parent directory")?;
+    // Only write resonance if it's meaningful (non-zero)
+    if entry.resonance > 0 {
+        writeln!(writer, "resonance: {}", entry.resonance)?;
+    }
 
-    for i in 1..1000 {
-        let new_name = if ext.is_empty() {
-            format!("{}-{}", stem, i)
-        } else {
-            format!("{}-{}.{}", stem, i, ext)
-        };
+    if let Some(ref resonance_type) = entry.resonance_type {
+        writeln!(writer, "resonance_type: {}", resonance_type)?;
+    }
+
+    if !entry.anchors.is_empty() {
+        writeln!(writer, "anchors: [{}]", entry.anchors.join(", "))?;
+    }
 
-        let new_path = parent.join(new_name);
-        if !new_path.exists() {
-            return Ok(new_path);
+    let active_phrases = entry.active_wake_phrases();
+    if !active_phrases.is_empty() {
+        // Quote it because wake phrases may contain special YAML characters
+        if active_phrases.len() == 1 {
+            writeln!(
+                writer,
+                "wake_phrase: {}",
+                serde_json::to_string(active_phrases[0])?
+            )?;
+        } else {
+            writeln!(writer, "wake_phrases:")?;
+            for phrase in active_phrases {
+                writeln!(writer, "  - {}", serde_json::to_string(phrase)?)?;
+            }
         }
     }
 
-    anyhow::bail!("Could not find unique filename for {:?}", path)
+    writeln!(writer, "---\n")?;
+
+    // Write body
+    if let Some(body) = &entry.body {
+        writeln!(writer, "{}", body)?;
+    }
+
+    Ok(writer)
 }
 
-/// Export database to JSONL
-pub fn export_jsonl(db: &dyn KnowledgeStore, path: &Path) -> Result<()> {
-    let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
-    let mut writer = BufWriter::new(file);
+/// Entries in one category, optionally reconstructed at a point in time
+fn category_entries(
+    db: &dyn KnowledgeStore,
+    category: &str,
+    ctx: &crate::store::AgentContext,
+    filter: &crate::store::KnowledgeFilter,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<Vec<crate::knowledge::KnowledgeEntry>> {
+    let entries = db.list_by_category(category, ctx, filter)?;
+    match as_of {
+        Some(at) => crate::revisions::entries_as_of(db, entries, at),
+        None => Ok(entries),
+    }
+}
 
-    // Export all categories dynamically
-    // Respect MX_CURRENT_AGENT for private entry access
+/// Which entries an export writes (`mx memory export --category ...`)
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Write each entry as it stood at this time (see [`crate::revisions`]);
+    /// entries created later are skipped
+    pub as_of: Option<DateTime<Utc>>,
+    /// Categories, tags (any), project and creation date, applied by the
+    /// store; categories default to all of them
+    pub query: crate::store::KnowledgeFilter,
+    /// Only entries with this visibility (`public` or `private`). `public`
+    /// also drops MX_CURRENT_AGENT's private access, so nothing private can
+    /// reach a shareable export.
+    pub visibility: Option<String>,
+    /// Keep activation counts and last-activated times in a JSONL export.
+    /// Reading an entry moves them, so they are left out unless asked for.
+    pub activity: bool,
+}
+
+/// Entry fields that change whenever an entry is read (see
+/// [`ExportFilter::activity`])
+const ACTIVITY_FIELDS: &[&str] = &["activation_count", "last_activated"];
+
+/// Everything an export writes, in export order: by category id, then by
+/// entry id, each entry in canonical form. The same data always exports the
+/// same way, so exports and backups diff cleanly.
+///
+/// Respects MX_CURRENT_AGENT for private entry access.
+pub(crate) fn export_entries(
+    db: &dyn KnowledgeStore,
+    filter: &ExportFilter,
+) -> Result<Vec<KnowledgeEntry>> {
     let ctx = match std::env::var("MX_CURRENT_AGENT") {
+        Ok(_) if filter.visibility.as_deref() == Some("public") => {
+            crate::store::AgentContext::public_only()
+        }
         Ok(agent) if !agent.is_empty() => crate::store::AgentContext::for_agent(agent),
         _ => crate::store::AgentContext::public_only(),
     };
-    let filter = crate::store::KnowledgeFilter::default();
-    let categories = db.list_categories()?;
+    let categories = match &filter.query.categories {
+        Some(categories) => categories.clone(),
+        None => db.list_categories()?.into_iter().map(|c| c.id).collect(),
+    };
+    let mut entries = Vec::new();
     for category in categories {
-        for entry in db.list_by_category(&category.id, &ctx, &filter)? {
-            let json = serde_json::to_string(&entry)?;
-            writeln!(writer, "{}", json)?;
+        entries.extend(category_entries(
+            db,
+            &category,
+            &ctx,
+            &filter.query,
+            filter.as_of,
+        )?);
+    }
+    if let Some(visibility) = &filter.visibility {
+        entries.retain(|e| &e.visibility == visibility);
+    }
+    entries.sort_by(|a, b| (&a.category_id, &a.id).cmp(&(&b.category_id, &b.id)));
+    Ok(entries.into_iter().map(canonical).collect())
+}
+
+/// An entry in canonical form: tags and applicability sorted and
+/// deduplicated, timestamps as RFC 3339 UTC with a `Z` suffix
+fn canonical(mut entry: KnowledgeEntry) -> KnowledgeEntry {
+    for list in [&mut entry.tags, &mut entry.applicability] {
+        list.sort();
+        list.dedup();
+    }
+    for timestamp in [
+        &mut entry.created_at,
+        &mut entry.updated_at,
+        &mut entry.last_activated,
+        &mut entry.embedded_at,
+        &mut entry.confirmed_at,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if let Some(dt) = crate::dates::parse_stored(timestamp) {
+            *timestamp = dt.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        }
+    }
+    entry
+}
+
+/// One JSONL line for a record, keys sorted at every level
+pub fn canonical_json(record: &impl serde::Serialize) -> Result<String> {
+    fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut fields: Vec<_> = map.into_iter().collect();
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+                serde_json::Value::Object(
+                    fields.into_iter().map(|(k, v)| (k, sort_keys(v))).collect(),
+                )
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+            }
+            other => other,
+        }
+    }
+    Ok(serde_json::to_string(&sort_keys(serde_json::to_value(
+        record,
+    )?))?)
+}
+
+/// One line of a JSONL dump, tagged by `kind`: the record's own fields plus
+/// `"kind": "entry"` (or `category`, `applicability_type`, `agent`,
+/// `project`, `relationship`). Lines without a `kind` are entries, as in
+/// dumps written before lookup tables and relationships were included.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+    Category(Category),
+    ApplicabilityType(ApplicabilityType),
+    Agent(Agent),
+    Project(Project),
+    Entry(Box<KnowledgeEntry>),
+    Relationship(Relationship),
+}
+
+impl Record {
+    /// Parse one line, treating an untagged object as an entry
+    pub fn parse(line: &str) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(line)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.entry("kind").or_insert_with(|| "entry".into());
         }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Export database to JSONL: lookup tables (categories, applicability types,
+/// agents, projects) first, then entries, then the relationships between
+/// exported entries, so an import can apply the lines in order. Each line is
+/// in canonical form (see [`canonical_json`]) and each section is sorted by
+/// id, so the same data always exports the same way. Entries leave out
+/// their activation fields unless `filter.activity` is set.
+pub fn export_jsonl(db: &dyn KnowledgeStore, path: &Path, filter: &ExportFilter) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut records = Vec::new();
+    let mut categories = db.list_categories()?;
+    categories.sort_by(|a, b| a.id.cmp(&b.id));
+    records.extend(categories.into_iter().map(Record::Category));
+    let mut applicability = db.list_applicability_types()?;
+    applicability.sort_by(|a, b| a.id.cmp(&b.id));
+    records.extend(applicability.into_iter().map(Record::ApplicabilityType));
+    let mut agents = db.list_agents()?;
+    agents.sort_by(|a, b| a.id.cmp(&b.id));
+    records.extend(agents.into_iter().map(Record::Agent));
+    let mut projects = db.list_projects(false)?;
+    projects.sort_by(|a, b| a.id.cmp(&b.id));
+    records.extend(projects.into_iter().map(Record::Project));
+
+    let entries = export_entries(db, filter)?;
+    let exported: HashSet<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    let mut relationships: Vec<Relationship> = db
+        .list_all_relationships()?
+        .into_iter()
+        .filter(|r| {
+            exported.contains(r.from_entry_id.as_str()) && exported.contains(r.to_entry_id.as_str())
+        })
+        .collect();
+    relationships.sort_by(|a, b| {
+        (&a.from_entry_id, &a.to_entry_id, &a.relationship_type).cmp(&(
+            &b.from_entry_id,
+            &b.to_entry_id,
+            &b.relationship_type,
+        ))
+    });
+    records.extend(entries.iter().cloned().map(|e| Record::Entry(Box::new(e))));
+    records.extend(relationships.into_iter().map(Record::Relationship));
+
+    for record in &records {
+        let line = match record {
+            Record::Entry(_) if !filter.activity => {
+                let mut value = serde_json::to_value(record)?;
+                if let Some(fields) = value.as_object_mut() {
+                    for field in ACTIVITY_FIELDS {
+                        fields.remove(*field);
+                    }
+                }
+                canonical_json(&value)?
+            }
+            _ => canonical_json(record)?,
+        };
+        writeln!(writer, "{}", line)?;
     }
 
     writer.flush()?;
@@ -273,7 +571,7 @@ pub fn export_jsonl(db: &dyn KnowledgeStore, path: &Path) -> Result<()> {
 }
 
 /// Export database to CSV (metadata only, no body)
-pub fn export_csv(db: &dyn KnowledgeStore, path: &Path) -> Result<()> {
+pub fn export_csv(db: &dyn KnowledgeStore, path: &Path, filter: &ExportFilter) -> Result<()> {
     let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
     let mut writer = BufWriter::new(file);
 
@@ -283,61 +581,166 @@ pub fn export_csv(db: &dyn KnowledgeStore, path: &Path) -> Result<()> {
         "id,category_id,title,tags,applicability,source_project_id,created_at,updated_at"
     )?;
 
-    // Export all categories dynamically
-    // Respect MX_CURRENT_AGENT for private entry access
-    let ctx = match std::env::var("MX_CURRENT_AGENT") {
-        Ok(agent) if !agent.is_empty() => crate::store::AgentContext::for_agent(agent),
-        _ => crate::store::AgentContext::public_only(),
-    };
-    let filter = crate::store::KnowledgeFilter::default();
-    let categories = db.list_categories()?;
-    for category in categories {
-        for entry in db.list_by_category(&category.id, &ctx, &filter)? {
-            let tags = entry.tags.join(";"); // Use semicolon to avoid comma collision
-            let applicability = entry.applicability.join(";");
-            let source_project = entry.source_project_id.as_deref().unwrap_or("");
-            let created = entry.created_at.as_deref().unwrap_or("");
-            let updated = entry.updated_at.as_deref().unwrap_or("");
-
-            writeln!(
-                writer,
-                "{},{},\"{}\",\"{}\",\"{}\",{},{},{}",
-                entry.id,
-                entry.category_id,
-                entry.title,
-                tags,
-                applicability,
-                source_project,
-                created,
-                updated
-            )?;
-        }
+    for entry in export_entries(db, filter)? {
+        let tags = entry.tags.join(";"); // Use semicolon to avoid comma collision
+        let applicability = entry.applicability.join(";");
+        let source_project = entry.source_project_id.as_deref().unwrap_or("");
+        let created = entry.created_at.as_deref().unwrap_or("");
+        let updated = entry.updated_at.as_deref().unwrap_or("");
+
+        writeln!(
+            writer,
+            "{},{},\"{}\",\"{}\",\"{}\",{},{},{}",
+            entry.id,
+            entry.category_id,
+            entry.title,
+            tags,
+            applicability,
+            source_project,
+            created,
+            updated
+        )?;
     }
 
     writer.flush()?;
     Ok(())
 }
 
-/// Import JSONL into database
-pub fn import_jsonl(db: &dyn KnowledgeStore, path: &Path) -> Result<usize> {
-    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
-    let reader = BufReader::new(file);
-
-    let mut count = 0;
-    for line in reader.lines() {
+/// Import a JSONL dump into the database. Every line is validated
+/// (including the category rules from config.yaml) before anything is
+/// written, so a bad line doesn't leave a half-finished import (`force`
+/// skips validation). Lookup tables are upserted before entries, and
+/// relationships the database already has are skipped.
+pub fn import_jsonl(db: &dyn KnowledgeStore, path: &Path, force: bool) -> Result<ImportStats> {
+    let rules = crate::category_rules::Rules::load()?;
+    let mut records = Vec::new();
+    for line in jsonl::open(path)? {
         let line = line?;
-        if line.trim().is_empty() {
+        if line.is_blank() {
             continue;
         }
 
-        let entry: KnowledgeEntry = serde_json::from_str(&line)
-            .with_context(|| format!("Failed to parse line: {}", line))?;
+        let record = Record::parse(&line.text)
+            .with_context(|| format!("Failed to parse line: {}", line.text))?;
+        if let (Record::Entry(entry), false) = (&record, force) {
+            let rejected = || {
+                format!(
+                    "Line {} rejected (use --force to import anyway)",
+                    line.number
+                )
+            };
+            entry.validate().with_context(rejected)?;
+            rules.check(entry).with_context(rejected)?;
+        }
+        records.push(record);
+    }
 
-        db.upsert_knowledge(&entry)?;
-        count += 1;
+    // Lookup tables, then entries, then relationships, whatever the line order
+    records.sort_by_key(|record| match record {
+        Record::Entry(_) => 1,
+        Record::Relationship(_) => 2,
+        _ => 0,
+    });
+    let mut existing: HashSet<(String, String, String)> = HashSet::new();
+    if records.iter().any(|r| matches!(r, Record::Relationship(_))) {
+        existing = db
+            .list_all_relationships()?
+            .into_iter()
+            .map(|r| (r.from_entry_id, r.to_entry_id, r.relationship_type))
+            .collect();
     }
 
-    Ok(count)
+    let mut stats = ImportStats::default();
+    for record in records {
+        match record {
+            Record::Category(category) => {
+                db.upsert_category(&category)?;
+                stats.lookups += 1;
+            }
+            Record::ApplicabilityType(atype) => {
+                db.upsert_applicability_type(&atype)?;
+                stats.lookups += 1;
+            }
+            Record::Agent(agent) => {
+                db.upsert_agent(&agent)?;
+                stats.lookups += 1;
+            }
+            Record::Project(project) => {
+                db.upsert_project(&project)?;
+                stats.lookups += 1;
+            }
+            Record::Entry(entry) => {
+                db.upsert_knowledge(&entry)?;
+                stats.entries += 1;
+            }
+            Record::Relationship(r) => {
+                let key = (r.from_entry_id, r.to_entry_id, r.relationship_type);
+                if !existing.contains(&key) {
+                    db.add_relationship(&key.0, &key.1, &key.2)?;
+                    stats.relationships += 1;
+                    existing.insert(key);
+                }
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Import a directory of markdown files with YAML frontmatter, such as a
+/// markdown export: the inverse of [`export_markdown`]. Frontmatter fields
+/// are the ones the export writes; a missing category comes from the
+/// file's directory, a missing title from its first heading, a missing id
+/// is generated and a missing hash computed. Validated like
+/// [`import_jsonl`] before anything is written.
+pub fn import_markdown(db: &dyn KnowledgeStore, dir: &Path, force: bool) -> Result<ImportStats> {
+    let rules = crate::category_rules::Rules::load()?;
+    let mut entries = Vec::new();
+    for file in WalkDir::new(dir).sort_by_file_name() {
+        let file = file?;
+        let path = file.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+
+        let mut entry = KnowledgeEntry::from_markdown(path, dir)?;
+        // The files are a copy of the store, not a source it tracks
+        entry.file_path = None;
+        if !force {
+            let rejected = || format!("{} rejected (use --force to import anyway)", path.display());
+            entry.validate().with_context(rejected)?;
+            rules.check(&entry).with_context(rejected)?;
+        }
+        entries.push(entry);
+    }
+
+    for entry in &entries {
+        db.upsert_knowledge(entry)?;
+    }
+
+    Ok(ImportStats {
+        entries: entries.len(),
+        ..Default::default()
+    })
+}
+
+/// What an import wrote
+#[derive(Debug, Default)]
+pub struct ImportStats {
+    pub entries: usize,
+    pub relationships: usize,
+    /// Categories, applicability types, agents and projects
+    pub lookups: usize,
+}
+
+impl std::fmt::Display for ImportStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entries, {} relationships and {} lookup records",
+            self.entries, self.relationships, self.lookups
+        )
+    }
 }
 
 #[derive(Debug, Default)]
@@ -356,3 +759,218 @@ impl std::fmt::Display for IndexStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: &str) -> KnowledgeEntry {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn canonical_form_sorts_lists_and_normalizes_timestamps() {
+        let entry = canonical(entry(
+            r#"{"id":"kn-1","category_id":"pattern","title":"T",
+                "tags":["rust","async","rust"],"applicability":["b","a"],
+                "created_at":"2026-01-02T03:04:05+02:00",
+                "updated_at":"2026-01-02 03:04:05",
+                "last_activated":"not a date"}"#,
+        ));
+        assert_eq!(entry.tags, vec!["async", "rust"]);
+        assert_eq!(entry.applicability, vec!["a", "b"]);
+        assert_eq!(entry.created_at.as_deref(), Some("2026-01-02T01:04:05Z"));
+        assert_eq!(entry.updated_at.as_deref(), Some("2026-01-02T03:04:05Z"));
+        assert_eq!(entry.last_activated.as_deref(), Some("not a date"));
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_and_is_stable() {
+        let entry = entry(r#"{"id":"kn-1","category_id":"pattern","title":"T"}"#);
+        let line = canonical_json(&entry).unwrap();
+        let keys: Vec<String> = serde_json::from_str::<serde_json::Value>(&line)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert!(line.find("\"category_id\"").unwrap() < line.find("\"id\"").unwrap());
+        assert_eq!(line, canonical_json(&entry).unwrap());
+    }
+
+    #[test]
+    fn records_are_tagged_by_kind_and_untagged_lines_are_entries() {
+        let old = Record::parse(r#"{"id":"kn-1","category_id":"pattern","title":"T"}"#).unwrap();
+        assert!(matches!(old, Record::Entry(ref e) if e.id == "kn-1"));
+        let line = canonical_json(&old).unwrap();
+        assert!(line.contains(r#""kind":"entry""#));
+        assert!(matches!(Record::parse(&line).unwrap(), Record::Entry(_)));
+
+        let rel = Record::parse(
+            r#"{"kind":"relationship","id":"r1","from_entry_id":"kn-1","to_entry_id":"kn-2","relationship_type":"related","created_at":""}"#,
+        )
+        .unwrap();
+        assert!(matches!(rel, Record::Relationship(ref r) if r.to_entry_id == "kn-2"));
+        let category =
+            Record::parse(r#"{"kind":"category","id":"pattern","description":"","created_at":""}"#)
+                .unwrap();
+        assert!(matches!(category, Record::Category(_)));
+        assert!(Record::parse(r#"{"kind":"mystery","id":"x"}"#).is_err());
+    }
+
+    #[test]
+    fn export_filters_select_categories_tags_and_visibility() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        for (id, category, tag) in [
+            ("kn-a", "pattern", "share"),
+            ("kn-b", "pattern", "draft"),
+            ("kn-c", "technique", "share"),
+        ] {
+            let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+                "id": id,
+                "category_id": category,
+                "title": id,
+                "tags": [tag],
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z",
+            }))
+            .unwrap();
+            db.upsert_knowledge(&entry).unwrap();
+        }
+        let ids = |filter: &ExportFilter| -> Vec<String> {
+            export_entries(&db, filter)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect()
+        };
+
+        assert_eq!(ids(&ExportFilter::default()), vec!["kn-a", "kn-b", "kn-c"]);
+        let mut filter = ExportFilter::default();
+        filter.query.tags = Some(vec!["share".to_string()]);
+        assert_eq!(ids(&filter), vec!["kn-a", "kn-c"]);
+        filter.query.categories = Some(vec!["technique".to_string()]);
+        assert_eq!(ids(&filter), vec!["kn-c"]);
+        filter.visibility = Some("private".to_string());
+        assert!(ids(&filter).is_empty());
+    }
+
+    #[test]
+    fn jsonl_export_leaves_out_activity_unless_asked() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-a",
+            "category_id": "pattern",
+            "title": "Retry",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        db.upsert_knowledge(&entry).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let export = |filter: &ExportFilter| {
+            export_jsonl(&db, &path, filter).unwrap();
+            std::fs::read_to_string(&path).unwrap()
+        };
+
+        let before = export(&ExportFilter::default());
+        db.update_activations(&["kn-a".to_string()]).unwrap();
+        assert_eq!(export(&ExportFilter::default()), before);
+        assert!(!before.contains("activation_count"));
+
+        let with_activity = export(&ExportFilter {
+            activity: true,
+            ..Default::default()
+        });
+        assert!(with_activity.contains(r#""activation_count":1"#));
+    }
+
+    #[test]
+    fn incremental_markdown_rewrites_only_changes_and_deletes_removed() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = crate::store::AgentContext::public_only();
+        let put = |id: &str, title: &str, body: &str| {
+            let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+                "id": id,
+                "category_id": "pattern",
+                "title": title,
+                "body": body,
+                "content_hash": KnowledgeEntry::compute_hash(body),
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z",
+            }))
+            .unwrap();
+            db.upsert_knowledge(&entry).unwrap();
+        };
+        put("kn-a", "Retry", "one");
+        put("kn-b", "Jitter", "two");
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("pattern/NOTES.md");
+        let filter = ExportFilter::default();
+
+        let first = sync_markdown(&db, dir.path(), &filter).unwrap();
+        assert_eq!((first.written, first.unchanged, first.deleted), (2, 0, 0));
+        std::fs::write(&notes, "not an export").unwrap();
+        let again = sync_markdown(&db, dir.path(), &filter).unwrap();
+        assert_eq!((again.written, again.unchanged, again.deleted), (0, 2, 0));
+
+        put("kn-a", "Retry with backoff", "one, revised");
+        db.delete("kn-b", &ctx).unwrap();
+        let stats = sync_markdown(&db, dir.path(), &filter).unwrap();
+        assert_eq!((stats.written, stats.unchanged, stats.deleted), (1, 0, 2));
+        let renamed = std::fs::read_to_string(dir.path().join("pattern/retry-with-backoff.md"));
+        assert!(renamed.unwrap().contains("one, revised"));
+        assert!(!dir.path().join("pattern/retry.md").exists());
+        assert!(!dir.path().join("pattern/jitter.md").exists());
+        assert!(notes.exists());
+    }
+
+    #[test]
+    fn markdown_export_imports_back_into_the_same_entries() {
+        let source = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-a",
+            "category_id": "pattern",
+            "title": "Retry: with \"backoff\"",
+            "summary": "Wait longer # each time",
+            "body": "# Retry\n\nWait longer each time.\n",
+            "tags": ["async", "yes"],
+            "content_hash": KnowledgeEntry::compute_hash("x"),
+            "resonance": 3,
+            "anchors": ["kn-root"],
+            "wake_phrase": "carry: on",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-02T00:00:00Z",
+        }))
+        .unwrap();
+        source.upsert_knowledge(&entry).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        export_markdown(&source, dir.path(), &ExportFilter::default()).unwrap();
+
+        let target = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let stats = import_markdown(&target, dir.path(), true).unwrap();
+        assert_eq!(stats.entries, 1);
+        let ctx = crate::store::AgentContext::public_only();
+        let back = target.get("kn-a", &ctx).unwrap().unwrap();
+        assert_eq!(back.title, entry.title);
+        assert_eq!(back.summary, entry.summary);
+        assert_eq!(back.body, entry.body);
+        assert_eq!(back.tags, entry.tags);
+        assert_eq!(back.content_hash, entry.content_hash);
+        assert_eq!((back.resonance, &back.anchors), (3, &entry.anchors));
+        assert_eq!(back.wake_phrase, entry.wake_phrase);
+        assert_eq!(back.updated_at.as_deref(), Some("2026-01-02T00:00:00Z"));
+        assert_eq!(back.file_path, None);
+
+        // Exporting the import gives the same files
+        let again = tempfile::tempdir().unwrap();
+        export_markdown(&target, again.path(), &ExportFilter::default()).unwrap();
+        let file = |d: &Path| std::fs::read(d.join("pattern/retry-with-backoff.md")).unwrap();
+        assert_eq!(file(dir.path()), file(again.path()));
+    }
+}
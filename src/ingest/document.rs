@@ -0,0 +1,653 @@
+//! Document ingestion: `mx ingest file <path.pdf|docx|md|txt>`
+//!
+//! Pure-Rust text extraction, no external tools:
+//!
+//! - PDF: inflates content streams and reads the text-showing operators
+//!   (`Tj`, `TJ`, `'`, `"`). Works for PDFs with simple font encodings;
+//!   scanned pages and CID-keyed fonts without a plain-text layer yield
+//!   little or no text.
+//! - DOCX: reads `word/document.xml` from the zip container, keeping
+//!   paragraphs and mapping `Title`/`HeadingN` styles to headings.
+//! - Markdown and plain text are read as-is.
+//!
+//! By default the whole document becomes one entry with the original file
+//! attached via the blob store; `--split` creates one entry per section
+//! (headings, or pages for PDF) instead.
+
+use anyhow::{Context, Result, bail};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use regex::Regex;
+use std::io::Read;
+use std::path::Path;
+
+use super::Draft;
+
+/// A document reduced to text
+#[derive(Debug, Default, PartialEq)]
+struct Document {
+    title: Option<String>,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Section {
+    heading: Option<String>,
+    text: String,
+}
+
+/// Extract `path` into drafts: one for the whole document, or one per
+/// section when `split` is set. The original file is stored as a blob only
+/// when `store_blobs` is set.
+pub fn ingest(path: &str, split: bool, store_blobs: bool) -> Result<Vec<Draft>> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    let file = Path::new(path);
+    let file_name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string());
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let (kind, mime, document) = match extension.as_str() {
+        "pdf" => ("pdf", "application/pdf", extract_pdf(&data)),
+        "docx" => (
+            "docx",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            extract_docx(&data)?,
+        ),
+        "md" | "markdown" | "txt" | "text" => (
+            "text",
+            "text/plain",
+            extract_text(&String::from_utf8_lossy(&data)),
+        ),
+        other => bail!(
+            "Unsupported file type '.{}': use pdf, docx, md, or txt",
+            other
+        ),
+    };
+
+    if document.sections.iter().all(|s| s.text.trim().is_empty()) {
+        bail!(
+            "No text found in {} (scanned or image-only documents are not supported)",
+            path
+        );
+    }
+
+    let hash = if store_blobs {
+        super::store_blob(&data)?
+    } else {
+        super::blob_hash(&data)
+    };
+    let source = file
+        .canonicalize()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_string());
+    let title = document.title.clone().unwrap_or_else(|| {
+        file.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file_name.clone())
+    });
+    let attachment = format!(
+        "\n## Attachments\n\n- `{}` ({}, {} bytes) blob:{}\n",
+        file_name,
+        mime,
+        data.len(),
+        hash
+    );
+    let tags = vec!["document".to_string(), kind.to_string()];
+
+    let draft = |key: String, title: String, body: String| Draft {
+        key,
+        title,
+        body,
+        tags: tags.clone(),
+        source: Some(source.clone()),
+        session_id: None,
+    };
+
+    if !split {
+        let body = render_sections(&document.sections) + &attachment;
+        return Ok(vec![draft(format!("file:{}", hash), title, body)]);
+    }
+
+    Ok(document
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.text.trim().is_empty())
+        .map(|(i, section)| {
+            let heading = section.heading.clone().unwrap_or_else(|| match kind {
+                "pdf" => format!("Page {}", i + 1),
+                _ if i == 0 => "Introduction".to_string(),
+                _ => format!("Part {}", i + 1),
+            });
+            let body = format!("{}\n{}", section.text.trim(), attachment);
+            draft(
+                format!("file:{}#{}", hash, i),
+                format!("{}: {}", title, heading),
+                body,
+            )
+        })
+        .collect())
+}
+
+fn render_sections(sections: &[Section]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        if let Some(ref heading) = section.heading {
+            out.push_str(&format!("## {}\n\n", heading));
+        }
+        let text = section.text.trim();
+        if !text.is_empty() {
+            out.push_str(text);
+            out.push_str("\n\n");
+        }
+    }
+    out.trim_end().to_string() + "\n"
+}
+
+// ---------------------------------------------------------------------------
+// Markdown / text
+// ---------------------------------------------------------------------------
+
+/// Sections split at markdown headings; a leading `# Title` names the document
+fn extract_text(text: &str) -> Document {
+    let mut document = Document::default();
+    let mut current = Section {
+        heading: None,
+        text: String::new(),
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let heading = trimmed[level..].trim().to_string();
+            if level == 1 && document.title.is_none() && document.sections.is_empty() {
+                document.title = Some(heading);
+                continue;
+            }
+            if current.heading.is_some() || !current.text.trim().is_empty() {
+                document.sections.push(current);
+            }
+            current = Section {
+                heading: Some(heading),
+                text: String::new(),
+            };
+            continue;
+        }
+        current.text.push_str(line);
+        current.text.push('\n');
+    }
+    document.sections.push(current);
+    document
+}
+
+// ---------------------------------------------------------------------------
+// DOCX
+// ---------------------------------------------------------------------------
+
+fn extract_docx(data: &[u8]) -> Result<Document> {
+    let xml = zip_entry(data, "word/document.xml")?
+        .context("Not a DOCX file: word/document.xml is missing")?;
+    let xml = String::from_utf8_lossy(&xml);
+
+    let paragraph = Regex::new(r"(?s)<w:p[ >].*?</w:p>").expect("valid regex");
+    let style = Regex::new(r#"<w:pStyle w:val="([^"]+)""#).expect("valid regex");
+    let run = Regex::new(r"(?s)<w:t(?: [^>]*)?>(.*?)</w:t>|<w:tab/>|<w:br/>").expect("valid regex");
+
+    let mut document = Document::default();
+    let mut current = Section {
+        heading: None,
+        text: String::new(),
+    };
+    for p in paragraph.find_iter(&xml) {
+        let p = p.as_str();
+        let mut text = String::new();
+        for token in run.captures_iter(p) {
+            match token.get(1) {
+                Some(t) => text.push_str(&super::decode_entities(t.as_str())),
+                None if &token[0] == "<w:tab/>" => text.push('\t'),
+                None => text.push('\n'),
+            }
+        }
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        let style = style.captures(p).map(|c| c[1].to_lowercase());
+        match style.as_deref() {
+            Some("title") if document.title.is_none() => document.title = Some(text),
+            Some(s) if s.starts_with("heading") => {
+                if current.heading.is_some() || !current.text.is_empty() {
+                    document.sections.push(current);
+                }
+                current = Section {
+                    heading: Some(text),
+                    text: String::new(),
+                };
+            }
+            _ => {
+                current.text.push_str(&text);
+                current.text.push_str("\n\n");
+            }
+        }
+    }
+    document.sections.push(current);
+
+    if document.title.is_none()
+        && let Some(core) = zip_entry(data, "docProps/core.xml")?
+    {
+        let core = String::from_utf8_lossy(&core);
+        document.title = Regex::new(r"(?s)<dc:title>(.*?)</dc:title>")
+            .expect("valid regex")
+            .captures(&core)
+            .map(|c| super::decode_entities(c[1].trim()))
+            .filter(|t| !t.is_empty());
+    }
+    Ok(document)
+}
+
+fn le16(data: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as usize)
+}
+
+fn le32(data: &[u8], at: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+}
+
+/// Read one file from a zip archive via its central directory
+fn zip_entry(data: &[u8], name: &str) -> Result<Option<Vec<u8>>> {
+    let corrupt = || anyhow::anyhow!("Corrupt zip archive");
+
+    // End of central directory record: signature, then the directory offset at +16
+    let eocd = data
+        .windows(4)
+        .rposition(|w| w == b"PK\x05\x06")
+        .ok_or_else(|| anyhow::anyhow!("Not a zip archive"))?;
+    let entries = le16(data, eocd + 10).ok_or_else(corrupt)?;
+    let mut at = le32(data, eocd + 16).ok_or_else(corrupt)?;
+
+    for _ in 0..entries {
+        if data.get(at..at + 4) != Some(b"PK\x01\x02") {
+            return Err(corrupt());
+        }
+        let method = le16(data, at + 10).ok_or_else(corrupt)?;
+        let compressed = le32(data, at + 20).ok_or_else(corrupt)?;
+        let name_len = le16(data, at + 28).ok_or_else(corrupt)?;
+        let extra_len = le16(data, at + 30).ok_or_else(corrupt)?;
+        let comment_len = le16(data, at + 32).ok_or_else(corrupt)?;
+        let local = le32(data, at + 42).ok_or_else(corrupt)?;
+        let entry_name = data.get(at + 46..at + 46 + name_len).ok_or_else(corrupt)?;
+        at += 46 + name_len + extra_len + comment_len;
+
+        if entry_name != name.as_bytes() {
+            continue;
+        }
+
+        let local_name = le16(data, local + 26).ok_or_else(corrupt)?;
+        let local_extra = le16(data, local + 28).ok_or_else(corrupt)?;
+        let start = local + 30 + local_name + local_extra;
+        let raw = data.get(start..start + compressed).ok_or_else(corrupt)?;
+        let contents = match method {
+            0 => raw.to_vec(),
+            8 => {
+                let mut out = Vec::new();
+                DeflateDecoder::new(raw)
+                    .read_to_end(&mut out)
+                    .context("Failed to inflate zip entry")?;
+                out
+            }
+            other => bail!("Unsupported zip compression method {}", other),
+        };
+        return Ok(Some(contents));
+    }
+    Ok(None)
+}
+
+// ---------------------------------------------------------------------------
+// PDF
+// ---------------------------------------------------------------------------
+
+/// Text of every content stream, one section per stream that shows text
+/// (in practice, one per page)
+fn extract_pdf(data: &[u8]) -> Document {
+    let mut document = Document {
+        title: pdf_title(data),
+        sections: vec![],
+    };
+
+    let mut offset = 0;
+    while let Some(start) = find(data, b"stream", offset) {
+        offset = start + 6;
+        // Skip `endstream`
+        if start >= 3 && &data[start - 3..start] == b"end" {
+            continue;
+        }
+        let mut body_start = start + 6;
+        if data.get(body_start) == Some(&b'\r') {
+            body_start += 1;
+        }
+        if data.get(body_start) == Some(&b'\n') {
+            body_start += 1;
+        }
+        let Some(end) = find(data, b"endstream", body_start) else {
+            break;
+        };
+        offset = end + 9;
+
+        let dict_start = data[..start]
+            .windows(2)
+            .rposition(|w| w == b"<<")
+            .unwrap_or(0);
+        let dict = &data[dict_start..start];
+        let raw = &data[body_start..end];
+
+        let content = if find(dict, b"/FlateDecode", 0).is_some() {
+            let mut out = Vec::new();
+            if ZlibDecoder::new(raw).read_to_end(&mut out).is_err() && out.is_empty() {
+                continue;
+            }
+            out
+        } else if find(dict, b"/Filter", 0).is_some() {
+            continue; // images and other encodings carry no text
+        } else {
+            raw.to_vec()
+        };
+
+        if find(&content, b"BT", 0).is_none() {
+            continue;
+        }
+        let text = content_stream_text(&content);
+        if !text.trim().is_empty() {
+            document.sections.push(Section {
+                heading: None,
+                text,
+            });
+        }
+    }
+    document
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+/// `/Title (...)` from the document information dictionary
+fn pdf_title(data: &[u8]) -> Option<String> {
+    let at = find(data, b"/Title", 0)? + 6;
+    let rest = &data[at..];
+    let open = rest.iter().position(|&b| !b.is_ascii_whitespace())?;
+    if rest[open] != b'(' {
+        return None;
+    }
+    let (bytes, _) = literal_string(rest, open + 1);
+    let title = String::from_utf8_lossy(&bytes).trim().to_string();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Parse a literal string whose body starts at `at` (just past the `(`).
+/// Returns the decoded bytes and the index past the closing `)`.
+fn literal_string(data: &[u8], mut at: usize) -> (Vec<u8>, usize) {
+    let mut out = Vec::new();
+    let mut depth = 1;
+    while at < data.len() {
+        let b = data[at];
+        at += 1;
+        match b {
+            b'\\' => {
+                let Some(&next) = data.get(at) else { break };
+                at += 1;
+                match next {
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'b' => out.push(8),
+                    b'f' => out.push(12),
+                    b'0'..=b'7' => {
+                        let mut value = (next - b'0') as u32;
+                        for _ in 0..2 {
+                            match data.get(at) {
+                                Some(&d @ b'0'..=b'7') => {
+                                    value = value * 8 + (d - b'0') as u32;
+                                    at += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        out.push(value as u8);
+                    }
+                    b'\r' | b'\n' => {
+                        if next == b'\r' && data.get(at) == Some(&b'\n') {
+                            at += 1;
+                        }
+                    }
+                    other => out.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                out.push(b);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                out.push(b);
+            }
+            _ => out.push(b),
+        }
+    }
+    (out, at)
+}
+
+/// Decode string bytes: UTF-16BE with BOM, otherwise Latin-1
+fn pdf_string_text(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Walk a content stream and collect shown text, breaking lines on text
+/// positioning operators
+fn content_stream_text(content: &[u8]) -> String {
+    let mut out = String::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut at = 0;
+
+    let push_line_break = |out: &mut String| {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+    };
+
+    while at < content.len() {
+        let b = content[at];
+        match b {
+            b'(' => {
+                let (bytes, next) = literal_string(content, at + 1);
+                pending.push(pdf_string_text(&bytes));
+                at = next;
+            }
+            b'<' if content.get(at + 1) != Some(&b'<') => {
+                let end = content[at..]
+                    .iter()
+                    .position(|&c| c == b'>')
+                    .map_or(content.len(), |p| at + p);
+                let hex: Vec<u8> = content[at + 1..end]
+                    .iter()
+                    .copied()
+                    .filter(u8::is_ascii_hexdigit)
+                    .collect();
+                let bytes: Vec<u8> = hex
+                    .chunks(2)
+                    .filter_map(|pair| {
+                        let s = std::str::from_utf8(pair).ok()?;
+                        u8::from_str_radix(&format!("{:0<2}", s), 16).ok()
+                    })
+                    .collect();
+                let text = pdf_string_text(&bytes);
+                // Glyph ids of CID fonts decode to control characters; drop them
+                if text.chars().all(|c| !c.is_control() || c.is_whitespace()) {
+                    pending.push(text);
+                }
+                at = end + 1;
+            }
+            b'[' | b']' => at += 1,
+            b'-' | b'.' | b'0'..=b'9' => {
+                // A large negative kerning inside TJ is a word gap
+                let end = content[at..]
+                    .iter()
+                    .position(|c| !matches!(c, b'-' | b'.' | b'0'..=b'9'))
+                    .map_or(content.len(), |p| at + p);
+                if let Ok(n) = std::str::from_utf8(&content[at..end])
+                    .unwrap_or_default()
+                    .parse::<f32>()
+                    && n < -200.0
+                    && !pending.is_empty()
+                {
+                    pending.push(" ".to_string());
+                }
+                at = end.max(at + 1);
+            }
+            b'%' => {
+                at = content[at..]
+                    .iter()
+                    .position(|&c| c == b'\n' || c == b'\r')
+                    .map_or(content.len(), |p| at + p);
+            }
+            c if c.is_ascii_alphabetic() || c == b'\'' || c == b'"' || c == b'*' => {
+                let end = content[at..]
+                    .iter()
+                    .position(|c| !(c.is_ascii_alphabetic() || matches!(c, b'\'' | b'"' | b'*')))
+                    .map_or(content.len(), |p| at + p);
+                let op = &content[at..end];
+                match op {
+                    b"Tj" | b"TJ" => out.push_str(&pending.concat()),
+                    b"'" | b"\"" => {
+                        push_line_break(&mut out);
+                        out.push_str(&pending.concat());
+                    }
+                    b"Td" | b"TD" | b"T*" | b"Tm" | b"ET" => push_line_break(&mut out),
+                    _ => {}
+                }
+                pending.clear();
+                at = end;
+            }
+            _ => at += 1,
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, ZlibEncoder};
+    use std::io::Write;
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut e = DeflateEncoder::new(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    /// Minimal single-entry zip with a deflated file
+    fn zip(name: &str, contents: &[u8]) -> Vec<u8> {
+        let packed = deflate(contents);
+        let mut out = Vec::new();
+        out.extend_from_slice(b"PK\x03\x04\x14\0\0\0\x08\0\0\0\0\0\0\0\0\0");
+        out.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&packed);
+
+        let directory = out.len();
+        out.extend_from_slice(b"PK\x01\x02\x14\0\x14\0\0\0\x08\0\0\0\0\0\0\0\0\0");
+        out.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[0u8; 12]);
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+        let size = out.len() - directory;
+
+        out.extend_from_slice(b"PK\x05\x06\0\0\0\0\x01\0\x01\0");
+        out.extend_from_slice(&(size as u32).to_le_bytes());
+        out.extend_from_slice(&(directory as u32).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn extracts_docx_paragraphs_and_headings() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Storage Spec</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Intro </w:t></w:r><w:r><w:t>text &amp; more</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Design</w:t></w:r></w:p>
+<w:p><w:r><w:t>Use a log.</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        let doc = extract_docx(&zip("word/document.xml", xml.as_bytes())).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Storage Spec"));
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].text.trim(), "Intro text & more");
+        assert_eq!(doc.sections[1].heading.as_deref(), Some("Design"));
+        assert_eq!(doc.sections[1].text.trim(), "Use a log.");
+
+        assert!(
+            zip_entry(&zip("other.xml", b"x"), "word/document.xml")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn extracts_pdf_text() {
+        let page = b"BT /F1 12 Tf 72 720 Td (Hello \\(PDF\\)) Tj 0 -14 Td [(Wor) -20 (ld) -300 (again)] TJ ET";
+        let mut z = ZlibEncoder::new(Vec::new(), Compression::default());
+        z.write_all(page).unwrap();
+        let packed = z.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.4\n1 0 obj << /Title (Design Notes) >> endobj\n".to_vec();
+        pdf.extend_from_slice(b"2 0 obj << /Length 10 /Filter /FlateDecode >>\nstream\n");
+        pdf.extend_from_slice(&packed);
+        pdf.extend_from_slice(b"\nendstream endobj\n");
+        pdf.extend_from_slice(
+            b"3 0 obj << /Length 5 >>\nstream\nBT <48692e> Tj ET\nendstream endobj\n%%EOF",
+        );
+
+        let doc = extract_pdf(&pdf);
+        assert_eq!(doc.title.as_deref(), Some("Design Notes"));
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].text.trim(), "Hello (PDF)\nWorld again");
+        assert_eq!(doc.sections[1].text.trim(), "Hi.");
+    }
+
+    #[test]
+    fn splits_markdown_sections() {
+        let doc = extract_text("# Guide\n\nPreamble\n\n## Setup\nInstall it\n## Use\nRun it\n");
+        assert_eq!(doc.title.as_deref(), Some("Guide"));
+        assert_eq!(doc.sections.len(), 3);
+        assert_eq!(doc.sections[1].heading.as_deref(), Some("Setup"));
+        assert_eq!(
+            render_sections(&doc.sections),
+            "Preamble\n\n## Setup\n\nInstall it\n\n## Use\n\nRun it\n"
+        );
+    }
+}
@@ -0,0 +1,498 @@
+//! Email ingestion: `mx ingest email <mbox-or-eml>`
+//!
+//! Reads a single RFC 5322 message (`.eml`) or an mbox of them and produces
+//! one draft per message. The body is the `text/plain` part (falling back to
+//! the HTML part as text); attachments go to the blob store and are listed in
+//! the entry. Entries are tagged `email`, `from:<address>`, and
+//! `thread:<id>`, where the thread id derives from the first message in
+//! `References` so a whole conversation shares one tag.
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::Draft;
+use crate::knowledge::KnowledgeEntry;
+
+/// A parsed message or MIME part: unfolded headers (lowercase names) and raw body
+#[derive(Debug)]
+struct Part {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// A file carried by a message
+#[derive(Debug, PartialEq)]
+struct Attachment {
+    filename: String,
+    mime: String,
+    data: Vec<u8>,
+}
+
+/// Text and files found while walking a MIME tree
+#[derive(Debug, Default)]
+struct Extracted {
+    plain: Option<String>,
+    html: Option<String>,
+    attachments: Vec<Attachment>,
+}
+
+/// Parse `path` (mbox or single message) into drafts. Attachments are written
+/// to the blob store only when `store_attachments` is set.
+pub fn ingest(path: &str, store_attachments: bool) -> Result<Vec<Draft>> {
+    let raw = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    let source = Path::new(path)
+        .canonicalize()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_string());
+
+    split_mbox(&raw)
+        .iter()
+        .map(|msg| message_to_draft(msg, &source, store_attachments))
+        .collect()
+}
+
+fn message_to_draft(raw: &[u8], source: &str, store_attachments: bool) -> Result<Draft> {
+    let message = parse_part(raw);
+    let mut extracted = Extracted::default();
+    walk(&message, &mut extracted);
+
+    let subject = message
+        .header("subject")
+        .map(decode_words)
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "(no subject)".to_string());
+    let from = message.header("from").map(decode_words);
+    let message_id = message.header("message-id").map(|s| s.trim().to_string());
+
+    let mut body = String::new();
+    for name in ["from", "to", "cc", "date"] {
+        if let Some(value) = message.header(name) {
+            let label = format!("{}{}", name[..1].to_uppercase(), &name[1..]);
+            body.push_str(&format!("**{}:** {}  \n", label, decode_words(value)));
+        }
+    }
+    body.push('\n');
+    let text = extracted
+        .plain
+        .or_else(|| extracted.html.as_deref().map(super::html_to_text))
+        .unwrap_or_default();
+    body.push_str(text.trim());
+    body.push('\n');
+
+    if !extracted.attachments.is_empty() {
+        body.push_str("\n## Attachments\n\n");
+        for attachment in &extracted.attachments {
+            let hash = if store_attachments {
+                super::store_blob(&attachment.data)?
+            } else {
+                super::blob_hash(&attachment.data)
+            };
+            body.push_str(&format!(
+                "- `{}` ({}, {} bytes) blob:{}\n",
+                attachment.filename,
+                attachment.mime,
+                attachment.data.len(),
+                hash
+            ));
+        }
+    }
+
+    let mut tags = vec!["email".to_string()];
+    if let Some(address) = from.as_deref().and_then(address_of) {
+        tags.push(format!("from:{}", address));
+    }
+    tags.push(format!("thread:{}", thread_id(&message, &subject)));
+
+    Ok(Draft {
+        key: format!(
+            "email:{}",
+            message_id.unwrap_or_else(|| KnowledgeEntry::compute_hash(&body))
+        ),
+        title: subject,
+        body,
+        tags,
+        source: Some(source.to_string()),
+        session_id: None,
+    })
+}
+
+impl Part {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Lowercased MIME type and its parameters (defaults to text/plain)
+    fn content_type(&self) -> (String, HashMap<String, String>) {
+        match self.header("content-type") {
+            Some(value) => parse_header_params(value),
+            None => ("text/plain".to_string(), HashMap::new()),
+        }
+    }
+}
+
+/// Split an mbox into raw messages; anything else is a single message
+fn split_mbox(raw: &[u8]) -> Vec<Vec<u8>> {
+    if !raw.starts_with(b"From ") {
+        return vec![raw.to_vec()];
+    }
+
+    let mut messages = Vec::new();
+    let mut current: Vec<u8> = Vec::new();
+    let mut previous_blank = true;
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            if !current.is_empty() {
+                messages.push(std::mem::take(&mut current));
+            }
+            previous_blank = false;
+            continue; // the envelope line is not part of the message
+        }
+        previous_blank = line.trim_ascii().is_empty();
+        // mboxrd quoting: ">From " -> "From "
+        match line.strip_prefix(b">") {
+            Some(rest) if is_quoted_from(rest) => current.extend_from_slice(rest),
+            _ => current.extend_from_slice(line),
+        }
+    }
+    if !current.is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+fn is_quoted_from(line: &[u8]) -> bool {
+    let depth = line.iter().take_while(|&&b| b == b'>').count();
+    line[depth..].starts_with(b"From ")
+}
+
+/// Split headers from body at the first blank line and unfold headers
+fn parse_part(raw: &[u8]) -> Part {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut offset = 0;
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        offset += line.len();
+        let text = String::from_utf8_lossy(line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.is_empty() {
+            break;
+        }
+        if text.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(text.trim());
+            }
+        } else if let Some((name, value)) = text.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    Part {
+        headers,
+        body: raw.get(offset..).unwrap_or_default().to_vec(),
+    }
+}
+
+/// Parse `type/subtype; key=value; key="quoted"` into the lowercased value
+/// and its parameters
+fn parse_header_params(value: &str) -> (String, HashMap<String, String>) {
+    let mut pieces = value.split(';');
+    let main = pieces.next().unwrap_or_default().trim().to_lowercase();
+    let params = pieces
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((
+                k.trim().to_lowercase(),
+                v.trim().trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    (main, params)
+}
+
+fn walk(part: &Part, out: &mut Extracted) {
+    let (mime, params) = part.content_type();
+    let disposition = part.header("content-disposition").map(parse_header_params);
+    let filename = disposition
+        .as_ref()
+        .and_then(|(_, p)| p.get("filename").cloned())
+        .or_else(|| params.get("name").cloned())
+        .map(|f| decode_words(&f));
+    let is_attachment = disposition
+        .as_ref()
+        .is_some_and(|(kind, _)| kind == "attachment")
+        || filename.is_some();
+
+    if mime.starts_with("multipart/")
+        && let Some(boundary) = params.get("boundary")
+    {
+        for raw in split_multipart(&part.body, boundary) {
+            walk(&parse_part(&raw), out);
+        }
+        return;
+    }
+
+    let data = decode_transfer(part);
+    if is_attachment || !mime.starts_with("text/") {
+        out.attachments.push(Attachment {
+            filename: filename.unwrap_or_else(|| "attachment".to_string()),
+            mime,
+            data,
+        });
+        return;
+    }
+
+    let text = decode_charset(&data, params.get("charset").map(String::as_str));
+    match mime.as_str() {
+        "text/html" if out.html.is_none() => out.html = Some(text),
+        "text/html" => {}
+        _ if out.plain.is_none() => out.plain = Some(text),
+        _ => {}
+    }
+}
+
+fn split_multipart(body: &[u8], boundary: &str) -> Vec<Vec<u8>> {
+    let delimiter = format!("--{}", boundary);
+    let close = format!("--{}--", boundary);
+
+    let mut parts = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed == close.as_bytes() {
+            parts.extend(current.take());
+            break;
+        }
+        if trimmed == delimiter.as_bytes() {
+            parts.extend(current.replace(Vec::new()));
+            continue;
+        }
+        if let Some(ref mut p) = current {
+            p.extend_from_slice(line);
+        }
+    }
+    parts.extend(current);
+
+    // The line break before a delimiter belongs to the delimiter
+    for part in &mut parts {
+        if part.ends_with(b"\n") {
+            part.pop();
+        }
+        if part.ends_with(b"\r") {
+            part.pop();
+        }
+    }
+    parts
+}
+
+fn decode_transfer(part: &Part) -> Vec<u8> {
+    let encoding = part
+        .header("content-transfer-encoding")
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    match encoding.as_str() {
+        "base64" => {
+            let compact: Vec<u8> = part
+                .body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            BASE64.decode(compact).unwrap_or_else(|_| part.body.clone())
+        }
+        "quoted-printable" => decode_quoted_printable(&part.body, false),
+        _ => part.body.clone(),
+    }
+}
+
+/// Quoted-printable decoding; `underscores` enables the RFC 2047 `_` = space rule
+fn decode_quoted_printable(data: &[u8], underscores: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'=' if data.get(i + 1) == Some(&b'\n') => i += 2,
+            b'=' if data.get(i + 1..i + 3) == Some(b"\r\n") => i += 3,
+            b'=' => {
+                let hex = data
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if underscores => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn decode_charset(data: &[u8], charset: Option<&str>) -> String {
+    match charset.map(|c| c.to_lowercase()).as_deref() {
+        Some("iso-8859-1" | "latin1" | "latin-1" | "windows-1252" | "us-ascii") => {
+            data.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+/// Decode RFC 2047 encoded words (`=?utf-8?B?...?=`) in a header value
+fn decode_words(value: &str) -> String {
+    // Whitespace between adjacent encoded words is not part of the text
+    let joined = Regex::new(r"\?=\s+=\?")
+        .expect("valid regex")
+        .replace_all(value, "?==?");
+    Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=")
+        .expect("valid regex")
+        .replace_all(&joined, |caps: &regex::Captures| {
+            let bytes = if caps[2].eq_ignore_ascii_case("b") {
+                BASE64.decode(&caps[3]).ok()
+            } else {
+                Some(decode_quoted_printable(caps[3].as_bytes(), true))
+            };
+            match bytes {
+                Some(bytes) => decode_charset(&bytes, Some(&caps[1])),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Bare lowercase address from `Name <addr>` or `addr`
+fn address_of(from: &str) -> Option<String> {
+    let address = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from,
+    };
+    let address = address.trim();
+    address.contains('@').then(|| address.to_lowercase())
+}
+
+/// Short id shared by every message in a conversation: the root of
+/// `References`, else `In-Reply-To`, else this message, else the subject
+fn thread_id(message: &Part, subject: &str) -> String {
+    let first_id = |value: &str| {
+        value
+            .split_whitespace()
+            .find(|id| id.starts_with('<'))
+            .map(String::from)
+    };
+    let root = message
+        .header("references")
+        .and_then(first_id)
+        .or_else(|| message.header("in-reply-to").and_then(first_id))
+        .or_else(|| message.header("message-id").map(|s| s.trim().to_string()))
+        .unwrap_or_else(|| normalize_subject(subject));
+    KnowledgeEntry::compute_hash(&root)[..8].to_string()
+}
+
+fn normalize_subject(subject: &str) -> String {
+    let mut s = subject.trim();
+    loop {
+        let lower = s.to_lowercase();
+        match ["re:", "fwd:", "fw:"]
+            .iter()
+            .find(|p| lower.starts_with(*p))
+        {
+            Some(prefix) => s = s[prefix.len()..].trim_start(),
+            None => return s.to_lowercase(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPART: &str = "From: =?utf-8?B?SsO8cmdlbg==?= <Juergen@Example.org>\r\n\
+To: team@example.org\r\n\
+Subject: =?utf-8?Q?Caf=C3=A9?=\r\n  decision\r\n\
+Message-ID: <m2@example.org>\r\n\
+References: <m1@example.org> <m1b@example.org>\r\n\
+Content-Type: multipart/mixed; boundary=\"XYZ\"\r\n\
+\r\n\
+preamble\r\n\
+--XYZ\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+We go with Postgres =\r\n\
+for =E2=82=AC reasons.\r\n\
+--XYZ\r\n\
+Content-Type: application/pdf; name=\"spec.pdf\"\r\n\
+Content-Disposition: attachment; filename=\"spec.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0x\r\n\
+--XYZ--\r\n";
+
+    #[test]
+    fn parses_multipart_message() {
+        let draft = message_to_draft(MULTIPART.as_bytes(), "inbox.eml", false).unwrap();
+        assert_eq!(draft.title, "Café decision");
+        assert_eq!(draft.key, "email:<m2@example.org>");
+        assert!(
+            draft
+                .body
+                .contains("**From:** Jürgen <Juergen@Example.org>")
+        );
+        assert!(draft.body.contains("We go with Postgres for € reasons."));
+        assert!(
+            draft
+                .body
+                .contains("- `spec.pdf` (application/pdf, 6 bytes) blob:")
+        );
+        assert_eq!(draft.tags[0], "email");
+        assert_eq!(draft.tags[1], "from:juergen@example.org");
+
+        // A reply in the same thread shares the thread tag
+        let reply = "Subject: Re: Cafe decision\nReferences: <m1@example.org>\n\nok\n";
+        let reply = message_to_draft(reply.as_bytes(), "inbox.eml", false).unwrap();
+        assert_eq!(reply.tags.last(), draft.tags.last());
+        assert_eq!(reply.body.trim(), "ok");
+    }
+
+    #[test]
+    fn splits_mbox_and_unquotes_from() {
+        let mbox = b"From a@b Mon Jan  1 00:00:00 2026\n\
+Subject: one\n\n>From the start\n\n\
+From c@d Mon Jan  1 00:00:00 2026\n\
+Subject: two\n\nbody\n";
+        let messages = split_mbox(mbox);
+        assert_eq!(messages.len(), 2);
+        let first = parse_part(&messages[0]);
+        assert_eq!(first.header("subject"), Some("one"));
+        assert!(String::from_utf8_lossy(&first.body).starts_with("From the start"));
+
+        // Not an mbox: a single message
+        assert_eq!(split_mbox(b"Subject: x\n\nbody").len(), 1);
+    }
+
+    #[test]
+    fn html_only_falls_back_to_text() {
+        let raw = "Subject: hi\nContent-Type: text/html\n\n<p>Hello <b>there</b></p>";
+        let draft = message_to_draft(raw.as_bytes(), "x.eml", false).unwrap();
+        assert!(draft.body.ends_with("Hello there\n"));
+        assert_eq!(normalize_subject("Re: FWD: Plan"), "plan");
+    }
+}
@@ -0,0 +1,404 @@
+//! Ingestion of external material into knowledge entries
+//!
+//! Each source format lives in its own submodule and turns its input into
+//! [`Draft`]s; this module owns everything they share: turning drafts into
+//! entries, skipping material that was already ingested, and keeping binary
+//! payloads (email attachments, archived pages, source documents) in the blob store.
+//!
+//! - `mx ingest email <mbox-or-eml>` - messages, tagged by sender and thread
+//! - `mx ingest url <url>` - web pages, reduced to their article text
+//! - `mx ingest file <path>` - PDF, DOCX, markdown and text documents
+//! - `mx ingest transcript <vtt|srt|json>` - timed speech, linked to a session
+
+pub mod document;
+pub mod email;
+pub mod transcript;
+pub mod web;
+
+use anyhow::{Context, Result, bail};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::IngestCommands;
+use crate::index::IndexConfig;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{self, AgentContext};
+
+/// Source type recorded on every ingested entry
+const SOURCE_TYPE: &str = "ingest";
+
+/// One entry-to-be, produced by a source parser
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Draft {
+    /// Stable key for the source item (e.g. a Message-ID); together with the
+    /// title it determines the entry id, so re-ingesting is idempotent
+    pub key: String,
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    /// Where the material came from (file path or URL)
+    pub source: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// Settings shared by every `mx ingest` subcommand
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    pub category: String,
+    pub tags: Vec<String>,
+    pub private: bool,
+    pub dry_run: bool,
+}
+
+pub fn handle_ingest(cmd: IngestCommands, verbose: bool) -> Result<()> {
+    match cmd {
+        IngestCommands::Email {
+            path,
+            category,
+            tags,
+            private,
+            no_attachments,
+            dry_run,
+        } => {
+            let opts = IngestOptions::new(category, tags, private, dry_run);
+            let drafts = email::ingest(&path, !no_attachments && !dry_run)?;
+            save(drafts, &opts, verbose).map(|_| ())
+        }
+        IngestCommands::Url {
+            url,
+            category,
+            tags,
+            private,
+            archive_html,
+            dry_run,
+        } => {
+            let opts = IngestOptions::new(category, tags, private, dry_run);
+            let draft = web::ingest(&url, archive_html, !dry_run)?;
+            save(vec![draft], &opts, verbose).map(|_| ())
+        }
+        IngestCommands::File {
+            path,
+            category,
+            tags,
+            private,
+            split,
+            dry_run,
+        } => {
+            let opts = IngestOptions::new(category, tags, private, dry_run);
+            let drafts = document::ingest(&path, split, !dry_run)?;
+            save(drafts, &opts, verbose).map(|_| ())
+        }
+        IngestCommands::Transcript {
+            path,
+            title,
+            session,
+            window,
+            by_speaker,
+            category,
+            tags,
+            private,
+            dry_run,
+        } => {
+            let opts = IngestOptions::new(category, tags, private, dry_run);
+            let grouping = transcript::Grouping {
+                window: window as f64 * 60.0,
+                by_speaker,
+            };
+            let session = session.map(|s| crate::normalize_id(&s));
+            let drafts = transcript::ingest(&path, title, session, grouping)?;
+            save(drafts, &opts, verbose).map(|_| ())
+        }
+    }
+}
+
+impl IngestOptions {
+    pub fn new(category: String, tags: Option<String>, private: bool, dry_run: bool) -> Self {
+        Self {
+            category,
+            tags: split_tags(tags.as_deref().unwrap_or_default()),
+            private,
+            dry_run,
+        }
+    }
+}
+
+fn split_tags(csv: &str) -> Vec<String> {
+    csv.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Turn a draft into a full entry
+pub(crate) fn build_entry(
+    draft: &Draft,
+    opts: &IngestOptions,
+    agent: Option<&str>,
+) -> KnowledgeEntry {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut tags = draft.tags.clone();
+    for tag in &opts.tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    KnowledgeEntry {
+        id: KnowledgeEntry::generate_id(&draft.key, &draft.title),
+        category_id: opts.category.clone(),
+        title: draft.title.clone(),
+        body: Some(draft.body.clone()),
+        summary: None,
+        applicability: vec![],
+        source_project_id: None,
+        source_agent_id: agent.map(String::from),
+        file_path: draft.source.clone(),
+        tags,
+        created_at: Some(now.clone()),
+        updated_at: Some(now),
+        content_hash: Some(KnowledgeEntry::compute_hash(&draft.body)),
+        source_type_id: Some(SOURCE_TYPE.to_string()),
+        entry_type_id: Some("primary".to_string()),
+        session_id: draft.session_id.clone(),
+        ephemeral: false,
+        content_type_id: Some("text".to_string()),
+        owner: if opts.private {
+            agent.map(String::from)
+        } else {
+            None
+        },
+        visibility: if opts.private { "private" } else { "public" }.to_string(),
+        resonance: 0,
+        resonance_type: None,
+        last_activated: None,
+        activation_count: 0,
+        decay_rate: 0.0,
+        anchors: vec![],
+        wake_phrases: vec![],
+        wake_order: None,
+        wake_phrase: None,
+        embedding: None,
+        embedding_model: None,
+        embedded_at: None,
+        format: "markdown".to_string(),
+        effective_resonance: None,
+        token_count: None,
+        namespace: None,
+        confidence: None,
+        confirmed_at: None,
+        archived_at: None,
+        attachments: Vec::new(),
+    }
+}
+
+/// Store drafts as entries, skipping any that were ingested before, and
+/// link each to its session (if any) with `extracted_from`.
+/// Returns the ids of newly created entries.
+pub fn save(drafts: Vec<Draft>, opts: &IngestOptions, verbose: bool) -> Result<Vec<String>> {
+    let agent = std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty());
+    if opts.private && agent.is_none() {
+        bail!("--private requires MX_CURRENT_AGENT to be set");
+    }
+
+    if drafts.is_empty() {
+        println!("Nothing to ingest.");
+        return Ok(vec![]);
+    }
+
+    if opts.dry_run {
+        for draft in &drafts {
+            let entry = build_entry(draft, opts, agent.as_deref());
+            println!("Would add: {} {}", entry.id, entry.title);
+            if !entry.tags.is_empty() {
+                println!("  Tags: {}", entry.tags.join(", "));
+            }
+        }
+        println!("\n{} entries (dry run)", drafts.len());
+        return Ok(vec![]);
+    }
+
+    let config = IndexConfig::default();
+    let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+    if db.get_category(&opts.category)?.is_none() {
+        let categories = db.list_categories()?;
+        let valid_ids: Vec<&str> = categories.iter().map(|c| c.id.as_str()).collect();
+        bail!(
+            "Invalid category '{}'. Valid categories: {}",
+            opts.category,
+            valid_ids.join(", ")
+        );
+    }
+
+    let ctx = match agent {
+        Some(ref a) => AgentContext::for_agent(a),
+        None => AgentContext::public_only(),
+    };
+
+    let mut added = Vec::new();
+    let mut skipped = 0;
+    for draft in &drafts {
+        let entry = build_entry(draft, opts, agent.as_deref());
+        if db.get(&entry.id, &ctx)?.is_some() {
+            skipped += 1;
+            continue;
+        }
+        db.upsert_knowledge(&entry)?;
+        println!("Added entry: {} {}", entry.id, entry.title);
+
+        if let Some(ref session) = draft.session_id {
+            if db.get(session, &ctx)?.is_some() {
+                db.add_relationship(&entry.id, session, "extracted_from")?;
+            } else {
+                eprintln!(
+                    "Warning: Session {} not found - relationship not created",
+                    session
+                );
+            }
+        }
+        added.push(entry.id);
+    }
+
+    println!(
+        "\nIngested {} entries ({} already present)",
+        added.len(),
+        skipped
+    );
+    Ok(added)
+}
+
+// ---------------------------------------------------------------------------
+// Blob store
+// ---------------------------------------------------------------------------
+
+/// Hex SHA-256 of `data`, the blob's address
+pub fn blob_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Write `data` to `$MX_HOME/blobs/<sha256>` (no-op if already stored)
+/// and return its hash
+pub fn store_blob(data: &[u8]) -> Result<String> {
+    let hash = blob_hash(data);
+    let path = blob_path(&hash);
+    if !path.exists() {
+        let dir = crate::paths::blobs_dir();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(&path, data)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(hash)
+}
+
+pub fn blob_path(hash: &str) -> PathBuf {
+    crate::paths::blobs_dir().join(hash)
+}
+
+// ---------------------------------------------------------------------------
+// Shared text helpers
+// ---------------------------------------------------------------------------
+
+/// Crude HTML to text: drops scripts, styles and tags, keeps block breaks,
+/// decodes common entities
+pub fn html_to_text(html: &str) -> String {
+    let without_blocks = regex::Regex::new(r"(?is)<(script|style|head)\b.*?</(script|style|head)>")
+        .expect("valid regex")
+        .replace_all(html, "");
+    let with_breaks = regex::Regex::new(r"(?i)<(br|/p|/div|/li|/h[1-6]|/tr)\b[^>]*>")
+        .expect("valid regex")
+        .replace_all(&without_blocks, "\n");
+    let text = regex::Regex::new(r"(?s)<[^>]*>")
+        .expect("valid regex")
+        .replace_all(&with_breaks, "");
+    let text = decode_entities(&text);
+
+    let mut out = String::new();
+    let mut blank = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank += 1;
+            if blank > 1 || out.is_empty() {
+                continue;
+            }
+        } else {
+            blank = 0;
+        }
+        out.push_str(line.trim_start());
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Decode named and numeric HTML entities that commonly appear in text
+pub fn decode_entities(text: &str) -> String {
+    regex::Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);")
+        .expect("valid regex")
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let decoded = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = name.strip_prefix('#') {
+                dec.parse().ok().and_then(char::from_u32)
+            } else {
+                match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "rsquo" | "lsquo" => Some('\''),
+                    "rdquo" | "ldquo" => Some('"'),
+                    _ => None,
+                }
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_entries_with_merged_tags() {
+        let draft = Draft {
+            key: "email:<a@b>".to_string(),
+            title: "Decision".to_string(),
+            body: "We chose X".to_string(),
+            tags: vec!["from:a@b".to_string()],
+            source: Some("inbox.mbox".to_string()),
+            session_id: None,
+        };
+        let opts = IngestOptions::new(
+            "decision".to_string(),
+            Some("email, from:a@b".to_string()),
+            true,
+            false,
+        );
+        let entry = build_entry(&draft, &opts, Some("neo"));
+        assert_eq!(entry.tags, vec!["from:a@b", "email"]);
+        assert_eq!(entry.visibility, "private");
+        assert_eq!(entry.owner.as_deref(), Some("neo"));
+        assert_eq!(entry.file_path.as_deref(), Some("inbox.mbox"));
+        assert_eq!(entry.source_type_id.as_deref(), Some("ingest"));
+        // Same source key and title -> same id
+        assert_eq!(entry.id, build_entry(&draft, &opts, None).id);
+    }
+
+    #[test]
+    fn html_to_text_keeps_structure() {
+        let html = "<html><head><title>x</title></head><body><script>var a;</script>\
+                    <h1>Title</h1><p>One &amp; two</p><p>Three&nbsp;&#8212; four</p></body></html>";
+        assert_eq!(html_to_text(html), "Title\nOne & two\nThree — four");
+    }
+}
@@ -0,0 +1,330 @@
+//! Transcript ingestion: `mx ingest transcript <vtt|srt|json>`
+//!
+//! Parses WebVTT, SRT, and Whisper-style JSON (`{"segments": [{start, end,
+//! text, speaker?}]}`, as written by whisper and whisperx) into timed cues.
+//! Speakers come from VTT voice tags (`<v Ann>`), a `speaker` field, or a
+//! `Name:` / `[Name]` prefix on the cue text.
+//!
+//! Cues are grouped into chronicle entries by time window (default five
+//! minutes) or, with `--by-speaker`, one entry per speaker turn. Each entry
+//! carries `[hh:mm:ss]` stamps, `transcript` and `speaker:<name>` tags, and
+//! can be linked to a session entry.
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::Draft;
+
+static SPEAKER_PREFIX_RE: OnceLock<Regex> = OnceLock::new();
+
+/// One timed line of speech
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    start: f64,
+    end: f64,
+    speaker: Option<String>,
+    text: String,
+}
+
+/// How cues are grouped into entries
+#[derive(Debug, Clone, Copy)]
+pub struct Grouping {
+    /// Maximum seconds per entry
+    pub window: f64,
+    /// Start a new entry whenever the speaker changes
+    pub by_speaker: bool,
+}
+
+pub fn ingest(
+    path: &str,
+    title: Option<String>,
+    session: Option<String>,
+    grouping: Grouping,
+) -> Result<Vec<Draft>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let file = Path::new(path);
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let mut cues = match extension.as_str() {
+        "json" => parse_json(&content)?,
+        "srt" | "vtt" => parse_timed_text(&content),
+        _ if content.trim_start().starts_with("WEBVTT") => parse_timed_text(&content),
+        other => bail!(
+            "Unsupported transcript type '.{}': use vtt, srt, or json",
+            other
+        ),
+    };
+    if cues.is_empty() {
+        bail!("No cues found in {}", path);
+    }
+    for cue in &mut cues {
+        if cue.speaker.is_none() {
+            split_speaker_prefix(cue);
+        }
+    }
+
+    let name = title.unwrap_or_else(|| {
+        file.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string())
+    });
+    let source = file
+        .canonicalize()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_string());
+
+    Ok(group(&cues, grouping)
+        .into_iter()
+        .map(|chunk| {
+            let mut draft = chunk_to_draft(&name, chunk);
+            draft.key = format!("transcript:{}:{}", source, draft.key);
+            draft.source = Some(source.clone());
+            draft.session_id = session.clone();
+            draft
+        })
+        .collect())
+}
+
+/// `hh:mm:ss.mmm`, `mm:ss.mmm`, or SRT's `hh:mm:ss,mmm` in seconds
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        total / 3600,
+        (total % 3600) / 60,
+        total % 60
+    )
+}
+
+/// WebVTT and SRT: blocks of an optional id, a `start --> end` line, and text
+fn parse_timed_text(content: &str) -> Vec<Cue> {
+    let voice = Regex::new(r"<v(?:\.[^ >]*)?\s+([^>]+)>").expect("valid regex");
+    let tags = Regex::new(r"</?[^>]+>").expect("valid regex");
+
+    let normalized = content.replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    for block in normalized.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else { continue };
+        let mut times = timing.split("-->");
+        let start = times.next().and_then(parse_timestamp);
+        // VTT cue settings may follow the end time
+        let end = times
+            .next()
+            .and_then(|t| t.split_whitespace().next())
+            .and_then(parse_timestamp);
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+
+        let raw: Vec<&str> = lines.collect();
+        let raw = raw.join(" ");
+        let speaker = voice.captures(&raw).map(|c| c[1].trim().to_string());
+        let text = super::decode_entities(&tags.replace_all(&raw, ""));
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            cues.push(Cue {
+                start,
+                end,
+                speaker,
+                text,
+            });
+        }
+    }
+    cues
+}
+
+/// Whisper JSON: `{"segments": [...]}` or a bare array of segments
+fn parse_json(content: &str) -> Result<Vec<Cue>> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).context("Failed to parse transcript JSON")?;
+    let segments = value
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .or_else(|| value.as_array())
+        .context("Transcript JSON has no \"segments\" array")?;
+
+    Ok(segments
+        .iter()
+        .filter_map(|segment| {
+            let text = segment.get("text")?.as_str()?.trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+            Some(Cue {
+                start: segment.get("start")?.as_f64()?,
+                end: segment.get("end").and_then(|e| e.as_f64()).unwrap_or(0.0),
+                speaker: segment
+                    .get("speaker")
+                    .and_then(|s| s.as_str())
+                    .map(String::from),
+                text,
+            })
+        })
+        .collect())
+}
+
+/// Move a `Name: text` or `[Name] text` prefix into the speaker field
+fn split_speaker_prefix(cue: &mut Cue) {
+    let prefix = SPEAKER_PREFIX_RE.get_or_init(|| {
+        Regex::new(r"^(?:\[([^\]]{1,40})\]|([A-Z][\w .'-]{0,30}):)\s+(.+)$").unwrap()
+    });
+    if let Some(caps) = prefix.captures(&cue.text) {
+        let speaker = caps
+            .get(1)
+            .or(caps.get(2))
+            .map(|m| m.as_str().trim().to_string());
+        cue.text = caps[3].to_string();
+        cue.speaker = speaker;
+    }
+}
+
+fn group(cues: &[Cue], grouping: Grouping) -> Vec<&[Cue]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for i in 1..cues.len() {
+        let too_long = cues[i].start - cues[start].start >= grouping.window;
+        let new_speaker = grouping.by_speaker && cues[i].speaker != cues[i - 1].speaker;
+        if too_long || new_speaker {
+            chunks.push(&cues[start..i]);
+            start = i;
+        }
+    }
+    if !cues.is_empty() {
+        chunks.push(&cues[start..]);
+    }
+    chunks
+}
+
+fn speaker_tag(speaker: &str) -> String {
+    let slug: String = speaker
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("speaker:{}", slug.trim_matches('-'))
+}
+
+fn chunk_to_draft(name: &str, chunk: &[Cue]) -> Draft {
+    let first = &chunk[0];
+    let last = &chunk[chunk.len() - 1];
+    let span = format!(
+        "{}–{}",
+        format_timestamp(first.start),
+        format_timestamp(last.end.max(last.start))
+    );
+
+    // Merge consecutive cues from the same speaker into one paragraph
+    let mut body = String::new();
+    let mut tags = vec!["transcript".to_string()];
+    let mut previous: Option<&Option<String>> = None;
+    for cue in chunk {
+        if previous == Some(&cue.speaker) {
+            body.push(' ');
+            body.push_str(&cue.text);
+            continue;
+        }
+        if previous.is_some() {
+            body.push_str("\n\n");
+        }
+        body.push_str(&format!("[{}] ", format_timestamp(cue.start)));
+        if let Some(ref speaker) = cue.speaker {
+            body.push_str(&format!("**{}:** ", speaker));
+            let tag = speaker_tag(speaker);
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        body.push_str(&cue.text);
+        previous = Some(&cue.speaker);
+    }
+    body.push('\n');
+
+    Draft {
+        key: first.start.to_string(),
+        title: format!("{} [{}]", name, span),
+        body,
+        tags,
+        source: None,
+        session_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VTT: &str = "WEBVTT\n\nNOTE recorded standup\n\n1\n00:00:01.000 --> 00:00:04.000 align:start\n<v Ann>We ship on Friday.</v>\n\n00:00:04.500 --> 00:00:06.000\n<v Ann>Tests are green.</v>\n\n00:06:00.000 --> 00:06:03.000\n<v Bob>Agreed &amp; noted.</v>\n";
+
+    const WINDOW: Grouping = Grouping {
+        window: 300.0,
+        by_speaker: false,
+    };
+
+    #[test]
+    fn parses_vtt_and_groups_by_window() {
+        let cues = parse_timed_text(VTT);
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[0].speaker.as_deref(), Some("Ann"));
+        assert_eq!(cues[2].text, "Agreed & noted.");
+        assert_eq!(cues[2].start, 360.0);
+
+        let chunks = group(&cues, WINDOW);
+        assert_eq!(chunks.len(), 2);
+        let draft = chunk_to_draft("standup", chunks[0]);
+        assert_eq!(draft.title, "standup [00:00:01–00:00:06]");
+        assert_eq!(
+            draft.body,
+            "[00:00:01] **Ann:** We ship on Friday. Tests are green.\n"
+        );
+        assert_eq!(draft.tags, vec!["transcript", "speaker:ann"]);
+    }
+
+    #[test]
+    fn parses_srt_with_speaker_prefixes() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:03,000\r\nSPEAKER_00: Hello there\r\n\r\n2\r\n00:00:03,000 --> 00:00:05,000\r\n[Dr. Lee] Hi\r\n";
+        let mut cues = parse_timed_text(srt);
+        cues.iter_mut().for_each(split_speaker_prefix);
+        assert_eq!(cues[0].start, 1.5);
+        assert_eq!(cues[0].speaker.as_deref(), Some("SPEAKER_00"));
+        assert_eq!(cues[0].text, "Hello there");
+        assert_eq!(cues[1].speaker.as_deref(), Some("Dr. Lee"));
+
+        let by_speaker = Grouping {
+            window: 300.0,
+            by_speaker: true,
+        };
+        assert_eq!(group(&cues, by_speaker).len(), 2);
+        assert_eq!(speaker_tag("Dr. Lee"), "speaker:dr--lee");
+    }
+
+    #[test]
+    fn parses_whisper_json() {
+        let json = r#"{"text": "...", "segments": [
+            {"id": 0, "start": 0.0, "end": 2.5, "text": " First point."},
+            {"id": 1, "start": 2.5, "end": 4.0, "text": " ", "speaker": "A"},
+            {"id": 2, "start": 4.0, "end": 6.0, "text": "Second.", "speaker": "SPEAKER_01"}
+        ]}"#;
+        let cues = parse_json(json).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "First point.");
+        assert_eq!(cues[1].speaker.as_deref(), Some("SPEAKER_01"));
+        assert!(parse_json(r#"{"text": "no segments"}"#).is_err());
+        assert_eq!(parse_timestamp("01:02:03.5"), Some(3723.5));
+    }
+}
@@ -0,0 +1,251 @@
+//! Web clip ingestion: `mx ingest url <url>`
+//!
+//! Fetches a page and keeps only its article text, in the spirit of
+//! readability: prefer `<article>` (then `<main>`, then `<body>`), drop
+//! navigation, headers, footers, sidebars, forms and scripts, and keep
+//! headings, paragraphs, list items, quotes and code blocks. Short fragments
+//! (bylines, share buttons, "read more" links) are filtered out.
+//!
+//! The entry records the source URL and retrieval time, and is tagged `web`
+//! and `site:<host>`. With `--archive-html` the raw page goes to the blob
+//! store and is listed in the entry.
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use super::Draft;
+
+/// Paragraphs shorter than this are treated as page chrome
+const MIN_PARAGRAPH_CHARS: usize = 40;
+
+/// Fetch `url` and turn it into a draft
+pub fn ingest(url: &str, archive_html: bool, store_blobs: bool) -> Result<Draft> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        bail!("Only http(s) URLs can be ingested: {}", url);
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent("mx-ingest/0.1")
+        .build()
+        .context("Failed to create HTTP client")?;
+    let response = client
+        .get(url)
+        .send()
+        .with_context(|| format!("Failed to fetch {}", url))?
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    let final_url = response.url().to_string();
+    let html = response.text().context("Failed to read response body")?;
+
+    let archive = if archive_html {
+        Some(if store_blobs {
+            super::store_blob(html.as_bytes())?
+        } else {
+            super::blob_hash(html.as_bytes())
+        })
+    } else {
+        None
+    };
+
+    Ok(page_to_draft(
+        &html,
+        &final_url,
+        &chrono::Utc::now().to_rfc3339(),
+        archive.as_deref(),
+    ))
+}
+
+fn page_to_draft(html: &str, url: &str, retrieved: &str, archive: Option<&str>) -> Draft {
+    let title = page_title(html).unwrap_or_else(|| url.to_string());
+    let article = extract_article(html);
+
+    let mut body = format!("**Source:** {}  \n**Retrieved:** {}\n\n", url, retrieved);
+    body.push_str(&article);
+    body.push('\n');
+    if let Some(hash) = archive {
+        body.push_str(&format!(
+            "\n## Attachments\n\n- `page.html` (text/html, {} bytes) blob:{}\n",
+            html.len(),
+            hash
+        ));
+    }
+
+    let mut tags = vec!["web".to_string()];
+    if let Some(host) = host_of(url) {
+        tags.push(format!("site:{}", host));
+    }
+
+    Draft {
+        key: format!("url:{}", url),
+        title,
+        body,
+        tags,
+        source: Some(url.to_string()),
+        session_id: None,
+    }
+}
+
+/// `og:title`, else `<title>`, else the first `<h1>`
+fn page_title(html: &str) -> Option<String> {
+    let patterns = [
+        r#"(?is)<meta[^>]+property=["']og:title["'][^>]+content=["']([^"']+)["']"#,
+        r#"(?is)<meta[^>]+content=["']([^"']+)["'][^>]+property=["']og:title["']"#,
+        r"(?is)<title[^>]*>(.*?)</title>",
+        r"(?is)<h1[^>]*>(.*?)</h1>",
+    ];
+    patterns.iter().find_map(|p| {
+        let caps = Regex::new(p).expect("valid regex").captures(html)?;
+        let title = super::html_to_text(&caps[1]).replace('\n', " ");
+        let title = title.trim();
+        (!title.is_empty()).then(|| title.to_string())
+    })
+}
+
+/// The main content region: first `<article>`, else `<main>`, else `<body>`
+fn content_region(html: &str) -> &str {
+    for tag in ["article", "main", "body"] {
+        let open = Regex::new(&format!(r"(?is)<{}\b[^>]*>", tag)).expect("valid regex");
+        let close = Regex::new(&format!(r"(?i)</{}\s*>", tag)).expect("valid regex");
+        if let Some(m) = open.find(html) {
+            let rest = &html[m.end()..];
+            let end = close
+                .find_iter(rest)
+                .last()
+                .map_or(rest.len(), |c| c.start());
+            return &rest[..end];
+        }
+    }
+    html
+}
+
+/// Readable article text as light markdown
+fn extract_article(html: &str) -> String {
+    let region = content_region(html);
+    let cleaned = Regex::new(
+        r"(?is)<(script|style|noscript|nav|header|footer|aside|form|svg|figure|iframe)\b.*?</(script|style|noscript|nav|header|footer|aside|form|svg|figure|iframe)>",
+    )
+    .expect("valid regex")
+    .replace_all(region, "");
+    let cleaned = Regex::new(r"(?s)<!--.*?-->")
+        .expect("valid regex")
+        .replace_all(&cleaned, "");
+
+    let blocks = Regex::new(
+        r"(?is)<(h[1-6]|p|li|pre|blockquote)\b[^>]*>(.*?)</(h[1-6]|p|li|pre|blockquote)>",
+    )
+    .expect("valid regex");
+    let tags = Regex::new(r"(?s)<[^>]*>").expect("valid regex");
+
+    let mut out: Vec<String> = Vec::new();
+    for caps in blocks.captures_iter(&cleaned) {
+        let tag = caps[1].to_lowercase();
+        let inner = &caps[2];
+        if tag == "pre" {
+            let code = super::decode_entities(&tags.replace_all(inner, ""));
+            out.push(format!("```\n{}\n```", code.trim_end()));
+            continue;
+        }
+
+        let text = super::html_to_text(inner).replace('\n', " ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        match tag.as_str() {
+            h if h.starts_with('h') => {
+                let level = h[1..].parse::<usize>().unwrap_or(2).max(2);
+                out.push(format!("{} {}", "#".repeat(level), text));
+            }
+            "li" => out.push(format!("- {}", text)),
+            "blockquote" => out.push(format!("> {}", text)),
+            _ if text.chars().count() >= MIN_PARAGRAPH_CHARS => out.push(text),
+            _ => {}
+        }
+    }
+
+    // Fall back to plain text when the page has no block markup
+    if out.is_empty() {
+        return super::html_to_text(&cleaned);
+    }
+    // Keep consecutive list items in one list
+    let mut text = String::new();
+    for (i, block) in out.iter().enumerate() {
+        if i > 0 {
+            let tight = block.starts_with("- ") && out[i - 1].starts_with("- ");
+            text.push_str(if tight { "\n" } else { "\n\n" });
+        }
+        text.push_str(block);
+    }
+    text
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    let host = host.trim_start_matches("www.");
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><head>
+<title>Fallback title</title>
+<meta property="og:title" content="Retries &amp; Backoff">
+<script>track()</script></head>
+<body>
+<nav><p>Home | About | A very long navigation paragraph that should vanish</p></nav>
+<article>
+<h1>Retries</h1>
+<p class="byline">By Ann</p>
+<p>Exponential backoff spreads retries out so a struggling service can recover.</p>
+<ul><li>Add jitter</li><li>Cap the delay</li></ul>
+<pre><code>sleep(base * 2^n)</code></pre>
+<aside><p>Subscribe to our newsletter for more great articles every week!</p></aside>
+</article>
+<footer><p>Copyright notice that is long enough to count as a paragraph</p></footer>
+</body></html>"#;
+
+    #[test]
+    fn extracts_article_content() {
+        let draft = page_to_draft(
+            PAGE,
+            "https://www.example.org/posts/retries",
+            "2026-10-15T00:00:00+00:00",
+            Some("abc"),
+        );
+        assert_eq!(draft.title, "Retries & Backoff");
+        assert_eq!(draft.tags, vec!["web", "site:example.org"]);
+        assert_eq!(
+            draft.source.as_deref(),
+            Some("https://www.example.org/posts/retries")
+        );
+
+        let body = &draft.body;
+        assert!(body.starts_with("**Source:** https://www.example.org/posts/retries"));
+        assert!(body.contains("## Retries"));
+        assert!(body.contains("Exponential backoff spreads retries out"));
+        assert!(body.contains("- Add jitter\n- Cap the delay"));
+        assert!(body.contains("```\nsleep(base * 2^n)\n```"));
+        assert!(body.contains("blob:abc"));
+        for chrome in ["By Ann", "navigation", "newsletter", "Copyright", "track()"] {
+            assert!(!body.contains(chrome), "kept page chrome: {}", chrome);
+        }
+    }
+
+    #[test]
+    fn title_and_host_fallbacks() {
+        assert_eq!(
+            page_title("<title> Plain </title>").as_deref(),
+            Some("Plain")
+        );
+        assert_eq!(page_title("<p>none</p>"), None);
+        assert_eq!(
+            host_of("https://user@Docs.Example.com:8443/a?b").as_deref(),
+            Some("docs.example.com")
+        );
+    }
+}
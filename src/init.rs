@@ -0,0 +1,439 @@
+//! First-run setup: `mx init`
+//!
+//! Walks a new machine from nothing to a working store: creates the `$MX_HOME`
+//! directory layout, picks the store mode (embedded, or a network SurrealDB
+//! server) and records it in the `surreal:` section of config.yaml, opens the
+//! knowledge store (which applies the schema and seeds the default categories
+//! and lookup types), optionally registers the first agent and project, and
+//! finishes with a health check. `MX_SURREAL_*` variables still override
+//! what config.yaml says.
+
+use anyhow::{Context, Result};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+use crate::index::IndexConfig;
+use crate::store::{self, KnowledgeStore};
+use crate::surreal_db::{SurrealConfig, SurrealFileConfig};
+use crate::types::{Agent, Project};
+
+/// Options for `mx init`
+#[derive(clap::Args)]
+pub struct InitOptions {
+    /// Accept defaults without prompting
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Register this agent ID
+    #[arg(long)]
+    pub agent: Option<String>,
+
+    /// Register this project ID (path defaults to the current directory)
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Store mode to record in config.yaml
+    #[arg(long, value_parser = ["embedded", "network"])]
+    pub mode: Option<String>,
+
+    /// SurrealDB server URL for network mode
+    #[arg(long)]
+    pub url: Option<String>,
+}
+
+/// Run the init wizard
+pub fn run_init(opts: InitOptions, verbose: bool) -> Result<()> {
+    let interactive = !opts.yes && io::stdin().is_terminal();
+    let home = crate::paths::mx_home();
+
+    println!("mx init");
+    println!("  Home:    {}", home.display());
+    if let Some(profile) = crate::paths::profile() {
+        println!("  Profile: {}", profile);
+    }
+    println!();
+
+    // 1. Directory layout
+    let config = IndexConfig::default();
+    let dirs = [
+        home.clone(),
+        config.memory_root.clone(),
+        crate::paths::codex_dir(),
+        crate::paths::schemas_dir(),
+        crate::paths::agents_dir(),
+    ];
+    for dir in &dirs {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+    println!("[1/4] Directories ready");
+
+    // 2. Backend + schema. Opening the store applies the schema and seeds
+    //    default categories, types, and relationship types.
+    configure_mode(opts.mode, opts.url, interactive)?;
+    let backend = if SurrealConfig::from_env().is_network() {
+        "SurrealDB (network)"
+    } else {
+        "SurrealDB (embedded)"
+    };
+    let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+    println!("[2/4] Store initialized: {}", backend);
+    println!("      {}", config.db_path.display());
+
+    // 3. First agent and project
+    let agent_id = match opts.agent {
+        Some(id) => Some(id),
+        None if interactive => prompt_optional("Register an agent (id, blank to skip)")?,
+        None => None,
+    };
+    if let Some(ref id) = agent_id {
+        register_agent(db.as_ref(), id, interactive)?;
+    }
+
+    let project_id = match opts.project {
+        Some(id) => Some(id),
+        None if interactive => prompt_optional("Register a project (id, blank to skip)")?,
+        None => None,
+    };
+    if let Some(ref id) = project_id {
+        register_project(db.as_ref(), id, interactive)?;
+    }
+    println!("[3/4] Identity configured");
+
+    // 4. Health check
+    println!("[4/4] Checks");
+    let failures = run_checks(db.as_ref(), &config.db_path);
+
+    println!();
+    println!("Suggested environment:");
+    if std::env::var("MX_HOME").map_or(true, |v| v.is_empty()) {
+        println!("  export MX_HOME={}", home.display());
+    }
+    if let Some(profile) = crate::paths::profile() {
+        println!("  export MX_PROFILE={}", profile);
+    }
+    if let Some(ref id) = agent_id {
+        println!("  export MX_CURRENT_AGENT={}", id);
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed", failures);
+    }
+
+    println!();
+    println!("Ready. Try: mx memory add --category insight --title \"...\" --content \"...\"");
+    Ok(())
+}
+
+/// Pick the store mode (from `--mode`, or by asking) and record it, with
+/// the server settings for network mode, in the profile's config.yaml. Leaves
+/// config.yaml alone when neither was possible.
+fn configure_mode(mode: Option<String>, url: Option<String>, interactive: bool) -> Result<()> {
+    let path = crate::paths::config_file();
+    let mut file = SurrealFileConfig::load_from(&path)?;
+    let current = SurrealConfig::from_env();
+    let mut ask = prompt_optional;
+    let prompt: Option<&mut Prompt> = if interactive { Some(&mut ask) } else { None };
+    if !choose_mode(&mut file, &current, mode, url, prompt)? {
+        return Ok(());
+    }
+    file.save_to(&path)?;
+    println!(
+        "      Recorded {} mode in {}",
+        file.mode.as_deref().unwrap_or_default(),
+        path.display()
+    );
+    if std::env::var("MX_SURREAL_MODE").is_ok_and(|m| !m.is_empty()) {
+        println!("      (MX_SURREAL_MODE is set and overrides it)");
+    }
+    Ok(())
+}
+
+/// Asks one question; `None` for a blank answer
+type Prompt<'a> = dyn FnMut(&str) -> Result<Option<String>> + 'a;
+
+/// Settle the `surreal:` section from the flags and, when `prompt` is given,
+/// the answers to it (`current` supplies the defaults offered). False when
+/// there is no mode to record.
+fn choose_mode(
+    file: &mut SurrealFileConfig,
+    current: &SurrealConfig,
+    mode: Option<String>,
+    url: Option<String>,
+    mut prompt: Option<&mut Prompt>,
+) -> Result<bool> {
+    let mode = match (mode, prompt.as_deref_mut()) {
+        (Some(mode), _) => mode,
+        (None, Some(ask)) => {
+            let default = if current.is_network() {
+                "network"
+            } else {
+                "embedded"
+            };
+            loop {
+                let answer = ask(&format!("Store mode: embedded or network [{}]", default))?
+                    .unwrap_or_else(|| default.to_string())
+                    .to_lowercase();
+                if matches!(answer.as_str(), "embedded" | "network") {
+                    break answer;
+                }
+                println!("      Answer 'embedded' or 'network'");
+            }
+        }
+        (None, None) => return Ok(false),
+    };
+
+    // Interactive runs confirm each server setting (current value as the
+    // default); otherwise only --url changes what config.yaml already says
+    match (mode.as_str(), prompt) {
+        ("network", Some(ask)) => {
+            let mut setting = |label: &str, default: &str| -> Result<Option<String>> {
+                Ok(ask(&format!("  {} [{}]", label, default))?
+                    .or_else(|| Some(default.to_string())))
+            };
+            file.url = match url {
+                Some(url) => Some(url),
+                None => setting("Server URL", &current.url)?,
+            };
+            file.namespace = setting("Namespace", &current.namespace)?;
+            file.database = setting("Database", &current.database)?;
+            file.user = setting("User", &current.user)?;
+            let pass_file = ask("  Password file (blank: set MX_SURREAL_PASS)")?;
+            file.pass_file = pass_file.or(file.pass_file.take());
+        }
+        ("network", None) if url.is_some() => file.url = url,
+        _ => {}
+    }
+    file.mode = Some(mode);
+    Ok(true)
+}
+
+/// Register an agent, prompting for description/domain when interactive
+fn register_agent(db: &dyn KnowledgeStore, id: &str, interactive: bool) -> Result<()> {
+    if db.get_agent(id)?.is_some() {
+        println!("      Agent '{}' already registered", id);
+        return Ok(());
+    }
+
+    let (description, domain) = if interactive {
+        (
+            prompt_optional("  Agent description")?,
+            prompt_optional("  Agent domain")?,
+        )
+    } else {
+        (None, None)
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    db.upsert_agent(&Agent {
+        id: id.to_string(),
+        description,
+        domain,
+        created_at: Some(now.clone()),
+        updated_at: Some(now),
+    })?;
+    println!("      Registered agent: {}", id);
+    Ok(())
+}
+
+/// Register a project, defaulting its path to the current directory
+fn register_project(db: &dyn KnowledgeStore, id: &str, interactive: bool) -> Result<()> {
+    if db.get_project(id)?.is_some() {
+        println!("      Project '{}' already registered", id);
+        return Ok(());
+    }
+
+    let cwd = std::env::current_dir()
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+
+    let (name, path, repo_url) = if interactive {
+        let name = prompt_optional(&format!("  Project name [{}]", id))?;
+        let path = prompt_optional(&format!(
+            "  Project path [{}]",
+            cwd.as_deref().unwrap_or("")
+        ))?;
+        let repo_url = prompt_optional("  Repository (owner/repo)")?;
+        (name, path.or(cwd), repo_url)
+    } else {
+        (None, cwd, None)
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    db.upsert_project(&Project {
+        id: id.to_string(),
+        name: name.unwrap_or_else(|| id.to_string()),
+        path,
+        repo_url,
+        description: None,
+        active: true,
+        created_at: now.clone(),
+        updated_at: now,
+    })?;
+    println!("      Registered project: {}", id);
+    Ok(())
+}
+
+/// Run post-init health checks, printing one line per check.
+/// Returns the number of failed checks.
+pub(crate) fn run_checks(db: &dyn KnowledgeStore, db_path: &Path) -> usize {
+    let mut failures = 0;
+    let mut report = |name: &str, result: Result<String>| match result {
+        Ok(detail) => println!("      ok    {} ({})", name, detail),
+        Err(e) => {
+            failures += 1;
+            println!("      FAIL  {} ({})", name, e);
+        }
+    };
+
+    report(
+        "home writable",
+        check_writable(crate::paths::mx_home()).map(|_| "writable".to_string()),
+    );
+    report(
+        "store reachable",
+        db.count().map(|n| format!("{} entries", n)),
+    );
+    report(
+        "categories seeded",
+        db.list_categories().and_then(|c| {
+            if c.is_empty() {
+                anyhow::bail!("no categories found")
+            }
+            Ok(format!("{} categories", c.len()))
+        }),
+    );
+    report(
+        "relationship types seeded",
+        db.list_relationship_types().and_then(|t| {
+            if t.is_empty() {
+                anyhow::bail!("no relationship types found")
+            }
+            Ok(format!("{} types", t.len()))
+        }),
+    );
+    report(
+        "database path",
+        Ok(db_path.with_extension("surreal").display().to_string()),
+    );
+
+    failures
+}
+
+/// Verify a directory accepts writes by creating and removing a probe file
+fn check_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(".mx-init-probe");
+    std::fs::write(&probe, b"ok").with_context(|| format!("Cannot write to {}", dir.display()))?;
+    std::fs::remove_file(&probe).ok();
+    Ok(())
+}
+
+/// Prompt for a line of input; empty input yields `None`
+fn prompt_optional(label: &str) -> Result<Option<String>> {
+    print!("{}: ", label);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+
+    Ok(if input.is_empty() {
+        None
+    } else {
+        Some(input.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A prompt that gives `answers` in turn ("" for a blank answer)
+    fn scripted<'a>(answers: &'a [&'a str]) -> impl FnMut(&str) -> Result<Option<String>> + 'a {
+        let mut answers = answers.iter();
+        move |_| {
+            let answer = answers.next().expect("more questions than answers");
+            Ok((!answer.is_empty()).then(|| answer.to_string()))
+        }
+    }
+
+    fn embedded() -> SurrealConfig {
+        SurrealConfig {
+            mode: crate::surreal_db::SurrealMode::Embedded,
+            ..SurrealConfig::default()
+        }
+    }
+
+    #[test]
+    fn without_mode_or_prompt_nothing_is_recorded() {
+        let mut file = SurrealFileConfig::default();
+        assert!(!choose_mode(&mut file, &embedded(), None, None, None).unwrap());
+        assert_eq!(file, SurrealFileConfig::default());
+    }
+
+    #[test]
+    fn mode_flag_keeps_other_settings() {
+        let mut file = SurrealFileConfig {
+            namespace: Some("team".into()),
+            ..Default::default()
+        };
+        let url = Some("ws://db.lan:8000".to_string());
+        assert!(choose_mode(&mut file, &embedded(), Some("network".into()), url, None).unwrap());
+        assert_eq!(file.mode.as_deref(), Some("network"));
+        assert_eq!(file.url.as_deref(), Some("ws://db.lan:8000"));
+        assert_eq!(file.namespace.as_deref(), Some("team"));
+        assert_eq!(file.user, None);
+
+        assert!(choose_mode(&mut file, &embedded(), Some("embedded".into()), None, None).unwrap());
+        assert_eq!(file.mode.as_deref(), Some("embedded"));
+        assert_eq!(file.url.as_deref(), Some("ws://db.lan:8000"));
+    }
+
+    #[test]
+    fn wizard_asks_for_network_settings() {
+        let mut file = SurrealFileConfig {
+            pass_file: Some("/run/old".into()),
+            ..Default::default()
+        };
+        let answers = ["remote", "Network", "", "team", "", "mx", ""];
+        let mut ask = scripted(&answers);
+        assert!(choose_mode(&mut file, &embedded(), None, None, Some(&mut ask)).unwrap());
+        assert_eq!(
+            file,
+            SurrealFileConfig {
+                mode: Some("network".into()),
+                url: Some("ws://localhost:8000".into()),
+                user: Some("mx".into()),
+                pass_file: Some("/run/old".into()),
+                namespace: Some("team".into()),
+                database: Some("knowledge".into()),
+                auth_level: None,
+            }
+        );
+    }
+
+    #[test]
+    fn wizard_defaults_to_the_current_mode() {
+        let mut file = SurrealFileConfig::default();
+        let mut ask = scripted(&[""]);
+        assert!(choose_mode(&mut file, &embedded(), None, None, Some(&mut ask)).unwrap());
+        assert_eq!(file.mode.as_deref(), Some("embedded"));
+        assert_eq!(file.url, None);
+    }
+
+    #[test]
+    fn mode_is_saved_to_one_profile_only() {
+        let home = tempfile::tempdir().unwrap();
+        let work = home.path().join("profiles/work/config.yaml");
+        let personal = home.path().join("config.yaml");
+        let mut file = SurrealFileConfig::default();
+        choose_mode(&mut file, &embedded(), Some("network".into()), None, None).unwrap();
+        file.save_to(&work).unwrap();
+
+        assert_eq!(SurrealFileConfig::load_from(&work).unwrap(), file);
+        assert_eq!(
+            SurrealFileConfig::load_from(&personal).unwrap(),
+            SurrealFileConfig::default()
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Write-ahead intent journal: `$MX_HOME/journal/`
+//!
+//! Some writes take several steps that can't share a transaction: an entry
+//! upsert is the record, then its tag edges, then its applicability edges; a
+//! codex archive is a directory of copies finished off by its manifest. A crash
+//! between steps leaves half an operation behind.
+//!
+//! Before such an operation starts, its intent is written here (one JSON file
+//! each) and removed once the last step lands. Whatever is left belongs to a
+//! process that died mid-way, and is settled the next time the store opens:
+//!
+//! - an entry upsert is replayed from the recorded entry (upserts are
+//!   idempotent, edges included)
+//! - an archive directory that never got its manifest is removed
+//!
+//! `mx doctor` lists anything still pending.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::KnowledgeStore;
+
+/// A multi-step operation, with what's needed to finish or undo it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op {
+    /// Record, tag edges and applicability edges; replayed
+    UpsertEntry { entry: Box<KnowledgeEntry> },
+    /// Archive directory, then its manifest; rolled back
+    ArchiveSession { archive_dir: PathBuf },
+}
+
+impl Op {
+    pub fn describe(&self) -> String {
+        match self {
+            Op::UpsertEntry { entry } => format!("upsert {}", entry.id),
+            Op::ArchiveSession { archive_dir } => format!("archive {}", archive_dir.display()),
+        }
+    }
+}
+
+/// A journaled operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Intent {
+    pub id: String,
+    /// Process that started it; its intents are left alone while it runs
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub op: Op,
+}
+
+/// An operation in flight. Call [`Pending::finish`] once its last step has
+/// landed; dropped unfinished (an error part-way), the intent stays behind to
+/// be settled like a crash.
+#[must_use]
+pub struct Pending {
+    path: PathBuf,
+}
+
+impl Pending {
+    pub fn finish(self) -> Result<()> {
+        fs::remove_file(&self.path)
+            .with_context(|| format!("Failed to clear intent {}", self.path.display()))
+    }
+}
+
+/// What [`recover`] did with the journal
+#[derive(Debug, Default)]
+pub struct Recovery {
+    pub settled: Vec<Intent>,
+    pub failed: Vec<(Intent, String)>,
+    /// Intents of processes still running
+    pub running: Vec<Intent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Record an operation before its first step
+    pub fn begin(&self, op: Op) -> Result<Pending> {
+        let intent = Intent {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            pid: std::process::id(),
+            started_at: Utc::now(),
+            op,
+        };
+        self.write(&intent)
+    }
+
+    /// Write an intent atomically, so a crash never leaves a torn record
+    fn write(&self, intent: &Intent) -> Result<Pending> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(format!("{}.json", intent.id));
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(intent)?)
+            .with_context(|| format!("Failed to write intent {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(Pending { path })
+    }
+
+    /// Intents on disk, oldest first
+    pub fn pending(&self) -> Result<Vec<Intent>> {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return Ok(Vec::new());
+        };
+        let mut intents = Vec::new();
+        for file in dir {
+            let path = file?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let raw = fs::read(&path)?;
+                let intent: Intent = serde_json::from_slice(&raw)
+                    .with_context(|| format!("Unreadable intent {}", path.display()))?;
+                intents.push(intent);
+            }
+        }
+        intents.sort_by_key(|i| i.started_at);
+        Ok(intents)
+    }
+
+    /// Drop an intent without settling it
+    pub fn discard(&self, id: &str) -> Result<()> {
+        fs::remove_file(self.dir.join(format!("{}.json", id)))
+            .with_context(|| format!("Failed to discard intent {}", id))
+    }
+}
+
+/// Whether the process that wrote an intent is still running
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+/// Settle the intents of processes that died mid-operation
+pub fn recover(journal: &Journal, db: &dyn KnowledgeStore) -> Result<Recovery> {
+    let mut recovery = Recovery::default();
+    for intent in journal.pending()? {
+        if process_alive(intent.pid) {
+            recovery.running.push(intent);
+            continue;
+        }
+        let settled = match &intent.op {
+            Op::UpsertEntry { entry } => db.upsert_knowledge(entry),
+            Op::ArchiveSession { archive_dir } => {
+                if archive_dir.exists() && !archive_dir.join("manifest.json").exists() {
+                    fs::remove_dir_all(archive_dir)
+                        .with_context(|| format!("Failed to remove {}", archive_dir.display()))
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        match settled.and_then(|_| journal.discard(&intent.id)) {
+            Ok(()) => recovery.settled.push(intent),
+            Err(e) => recovery.failed.push((intent, format!("{:#}", e))),
+        }
+    }
+    Ok(recovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::AgentContext;
+    use crate::surreal_db::SurrealDatabase;
+
+    /// A pid that has certainly exited
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    fn orphan(journal: &Journal, op: Op) {
+        let intent = Intent {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            pid: dead_pid(),
+            started_at: Utc::now(),
+            op,
+        };
+        let _ = journal.write(&intent).unwrap();
+    }
+
+    #[test]
+    fn interrupted_operations_are_settled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let journal = Journal::open(tmp.path().join("journal"));
+        let db = SurrealDatabase::open_in_memory().unwrap();
+
+        let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-half",
+            "category_id": "pattern",
+            "title": "Half written",
+            "tags": ["crash"],
+            "visibility": "public",
+            "created_at": "2026-03-01T09:00:00Z",
+            "updated_at": "2026-03-01T09:00:00Z",
+        }))
+        .unwrap();
+        orphan(
+            &journal,
+            Op::UpsertEntry {
+                entry: Box::new(entry),
+            },
+        );
+
+        let torn = tmp.path().join("2026-03-01-090000-abc12345");
+        fs::create_dir_all(torn.join("images")).unwrap();
+        orphan(
+            &journal,
+            Op::ArchiveSession {
+                archive_dir: torn.clone(),
+            },
+        );
+
+        let whole = tmp.path().join("2026-03-01-090000-def67890");
+        fs::create_dir_all(&whole).unwrap();
+        fs::write(whole.join("manifest.json"), "{}").unwrap();
+        orphan(
+            &journal,
+            Op::ArchiveSession {
+                archive_dir: whole.clone(),
+            },
+        );
+
+        // Still in progress here, so left alone
+        let _running = journal
+            .begin(Op::ArchiveSession {
+                archive_dir: tmp.path().join("live"),
+            })
+            .unwrap();
+
+        let recovery = recover(&journal, &db).unwrap();
+        assert_eq!(recovery.settled.len(), 3);
+        assert!(recovery.failed.is_empty());
+        assert_eq!(recovery.running.len(), 1);
+
+        let replayed = db
+            .get_knowledge("kn-half", &AgentContext::public_only())
+            .unwrap()
+            .unwrap();
+        assert_eq!(replayed.tags, vec!["crash".to_string()]);
+        assert!(!torn.exists());
+        assert!(whole.exists());
+        assert_eq!(journal.pending().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! Streaming JSONL
+//!
+//! Sessions, codex archives and dumps are read through a buffered reader a
+//! line at a time rather than `read_to_string` + `lines()`, so memory stays
+//! flat however large the file and a caller can stop as soon as it has what
+//! it needs. Lines are framed before they are parsed (instead of handing the
+//! reader to serde_json's `StreamDeserializer`) because a session with one
+//! malformed line must still read past it.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One line, without its line ending
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// 1-based, counting blank lines and, across files, every earlier file
+    pub number: usize,
+    pub text: String,
+}
+
+impl Line {
+    pub fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_str(&self.text)
+    }
+}
+
+/// The lines of `reader`
+pub struct Lines<R> {
+    reader: R,
+    number: usize,
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = Result<Line>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = String::new();
+        match self.reader.read_line(&mut text) {
+            Ok(0) => None,
+            Ok(_) => {
+                if text.ends_with('\n') {
+                    text.pop();
+                    if text.ends_with('\r') {
+                        text.pop();
+                    }
+                }
+                self.number += 1;
+                Some(Ok(Line {
+                    number: self.number,
+                    text,
+                }))
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+/// Every line of `reader`, numbered from 1
+pub fn read<R: BufRead>(reader: R) -> Lines<R> {
+    Lines { reader, number: 0 }
+}
+
+/// Every line of the file at `path`
+pub fn open(path: &Path) -> Result<Lines<BufReader<File>>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(read(BufReader::new(file)))
+}
+
+/// The lines of `files` back to back, numbered straight through, each with
+/// the file it came from. Files are opened as they are reached.
+pub fn open_all(files: &[PathBuf]) -> AllLines<'_> {
+    AllLines {
+        files: files.iter(),
+        current: None,
+        number: 0,
+    }
+}
+
+/// See [`open_all`]
+pub struct AllLines<'a> {
+    files: std::slice::Iter<'a, PathBuf>,
+    current: Option<(&'a Path, Lines<BufReader<File>>)>,
+    number: usize,
+}
+
+impl<'a> Iterator for AllLines<'a> {
+    type Item = Result<(&'a Path, Line)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((path, lines)) = self.current.as_mut() {
+                match lines.next() {
+                    Some(line) => return Some(line.map(|line| (*path, line))),
+                    None => {
+                        self.number = lines.number;
+                        self.current = None;
+                    }
+                }
+            }
+            let path = self.files.next()?;
+            match open(path) {
+                Ok(mut lines) => {
+                    lines.number = self.number;
+                    self.current = Some((path.as_path(), lines));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_numbered_lines_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        std::fs::write(&a, "{\"n\":1}\r\n\n{\"n\":3}\n").unwrap();
+        std::fs::write(&b, "not json\n{\"n\":5}").unwrap();
+        let files = [a.clone(), b.clone()];
+
+        let lines: Vec<(&Path, Line)> = open_all(&files).map(Result::unwrap).collect();
+        let numbers: Vec<usize> = lines.iter().map(|(_, l)| l.number).collect();
+        assert_eq!(numbers, [1, 2, 3, 4, 5]);
+        assert_eq!(lines[0].1.text, "{\"n\":1}");
+        assert!(lines[1].1.is_blank());
+        assert_eq!(lines[4].0, b.as_path());
+
+        let parsed: Vec<u64> = lines
+            .iter()
+            .filter_map(|(_, l)| l.parse::<serde_json::Value>().ok())
+            .filter_map(|v| v["n"].as_u64())
+            .collect();
+        assert_eq!(parsed, [1, 3, 5]);
+
+        // Stopping early never reads the rest
+        let first = open_all(&files).next().unwrap().unwrap();
+        assert_eq!(first.1.number, 1);
+        let missing = [dir.path().join("gone.jsonl")];
+        assert!(open_all(&missing).next().unwrap().is_err());
+    }
+}
@@ -1,9 +1,23 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use base_d::{DictionaryRegistry, HashAlgorithm, encode, hash};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+/// Allowed values for `resonance_type` (mirrors the schema ASSERT)
+pub const RESONANCE_TYPES: [&str; 6] = [
+    "foundational",
+    "transformative",
+    "relational",
+    "operational",
+    "ephemeral",
+    "session",
+];
+
+/// Ceiling for resonance. 1-10 is the normal scale and anything above is
+/// overflow for transcendent memories; past this it's almost surely a typo.
+pub const MAX_RESONANCE: i32 = 100;
+
 /// A knowledge entry from Zion
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeEntry {
@@ -102,6 +116,47 @@ pub struct KnowledgeEntry {
     // raw `resonance` does not account for age.
     #[serde(default)]
     pub effective_resonance: Option<f64>,
+
+    // Estimated tokens for title + body, stored on write (see tokens.rs).
+    // None on rows written before estimates existed.
+    #[serde(default)]
+    pub token_count: Option<i32>,
+
+    // Storage namespace: Some("identity") for blooms sealed into the identity
+    // namespace (see identity.rs), None for everything else.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+
+    // Trust in the entry, 0.0 (hunch) to 1.0 (battle-tested); None when never
+    // rated. See confidence.rs for decay and confirmation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmed_at: Option<String>, // RFC3339, last confirm or explicit rating
+
+    // Set while the entry is in the trash (see trash.rs); queries leave
+    // trashed entries out until they're restored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>, // RFC3339
+
+    // Files attached with `mx memory attach`; the content lives in the blob
+    // store, only the metadata travels with the entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to an entry, addressed by content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name as attached (used when extracting)
+    pub name: String,
+    /// Hex SHA-256 of the content; the file is `$MX_HOME/blobs/<hash>`
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub size: u64,
+    pub added_at: String, // RFC3339
 }
 
 fn default_format() -> String {
@@ -157,9 +212,65 @@ pub struct Frontmatter {
     pub source_agent: Option<String>,
     #[serde(default)]
     pub created: Option<String>,
+    // Written by `mx memory export --format md` so an import restores them
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub updated: Option<String>,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub visibility: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub resonance: Option<i32>,
+    #[serde(default)]
+    pub resonance_type: Option<String>,
+    #[serde(default)]
+    pub anchors: Vec<String>,
+    #[serde(default)]
+    pub wake_phrase: Option<String>,
+    #[serde(default)]
+    pub wake_phrases: Vec<String>,
 }
 
 impl KnowledgeEntry {
+    /// Check the resonance fields before a write: resonance within
+    /// 0..=MAX_RESONANCE (0 = unset), decay_rate within 0.0-1.0, and a known
+    /// resonance_type. All problems are reported together.
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            bail!("Invalid entry {}: {}", self.id, problems.join("; "));
+        }
+        Ok(())
+    }
+
+    /// Everything `validate` would reject, one message per problem
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !(0..=MAX_RESONANCE).contains(&self.resonance) {
+            problems.push(format!(
+                "resonance {} is outside 0-{} (1-10, higher for transcendent)",
+                self.resonance, MAX_RESONANCE
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.decay_rate) {
+            problems.push(format!("decay_rate {} is outside 0.0-1.0", self.decay_rate));
+        }
+        if let Some(ref rtype) = self.resonance_type
+            && !RESONANCE_TYPES.contains(&rtype.as_str())
+        {
+            problems.push(format!(
+                "unknown resonance type '{}' (valid: {})",
+                rtype,
+                RESONANCE_TYPES.join(", ")
+            ));
+        }
+        problems
+    }
+
     /// Returns active wake phrases, preferring wake_phrases over deprecated wake_phrase.
     pub fn active_wake_phrases(&self) -> Vec<&str> {
         if !self.wake_phrases.is_empty() {
@@ -185,7 +296,7 @@ impl KnowledgeEntry {
             parts.push(summary.clone());
         } else if let Some(body) = &self.body {
             // Truncate body to avoid overwhelming the embedding model
-            parts.push(body.chars().take(2000).collect());
+            parts.push(crate::text::truncate(body, 2000).to_string());
         }
 
         if !self.tags.is_empty() {
@@ -267,8 +378,11 @@ impl KnowledgeEntry {
             })
         });
 
-        // Extract summary (first paragraph after heading)
-        let summary = extract_summary(&body);
+        // Summary from frontmatter, or the first paragraph after a heading
+        let summary = frontmatter
+            .summary
+            .clone()
+            .or_else(|| extract_summary(&body));
 
         // Generate ID if not provided
         let path_str = relative.to_string_lossy().to_string();
@@ -290,26 +404,30 @@ impl KnowledgeEntry {
             file_path: Some(path_str),
             tags: frontmatter.tags,
             created_at: frontmatter.created.or_else(|| Some(now.clone())),
-            updated_at: Some(now),
-            content_hash: Some(Self::compute_hash(&content)),
+            updated_at: frontmatter.updated.or(Some(now)),
+            content_hash: frontmatter
+                .content_hash
+                .or_else(|| Some(Self::compute_hash(&content))),
             // Markdown files are manual, primary knowledge
             source_type_id: Some("manual".to_string()),
             entry_type_id: Some("primary".to_string()),
             session_id: None,
             ephemeral: false,
             content_type_id: Some("text".to_string()),
-            owner: None,
-            visibility: "public".to_string(),
-            // Resonance fields - initialized to defaults
-            resonance: 0,
-            resonance_type: None,
+            owner: frontmatter.owner,
+            visibility: frontmatter
+                .visibility
+                .unwrap_or_else(|| "public".to_string()),
+            // Resonance fields - defaults unless the frontmatter has them
+            resonance: frontmatter.resonance.unwrap_or(0),
+            resonance_type: frontmatter.resonance_type,
             last_activated: None,
             activation_count: 0,
             decay_rate: 0.0,
-            anchors: vec![],
-            wake_phrases: vec![],
+            anchors: frontmatter.anchors,
+            wake_phrases: frontmatter.wake_phrases,
             wake_order: None,
-            wake_phrase: None,
+            wake_phrase: frontmatter.wake_phrase,
             // Embeddings - not generated from markdown
             embedding: None,
             embedding_model: None,
@@ -317,6 +435,12 @@ impl KnowledgeEntry {
             // Format - markdown files are markdown
             format: "markdown".to_string(),
             effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
         })
     }
 }
@@ -335,7 +459,9 @@ fn parse_frontmatter(content: &str) -> Result<(Frontmatter, String)> {
     match end {
         Some(pos) => {
             let yaml = &rest[..pos];
-            let body = rest[pos + 4..].trim_start_matches(['\n', '\r']).to_string();
+            let body = rest[pos + 4..].trim_start_matches(['\n', '\r']);
+            // The newline the file ends with isn't part of the body
+            let body = body.strip_suffix('\n').unwrap_or(body).to_string();
 
             let frontmatter: Frontmatter = serde_yaml::from_str(yaml).unwrap_or_default();
 
@@ -546,6 +672,12 @@ Body"#;
             embedded_at: None,
             format: "markdown".to_string(),
             effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
         };
 
         let text = entry.embedding_text();
@@ -591,6 +723,12 @@ Body"#;
             embedded_at: None,
             format: "markdown".to_string(),
             effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
         };
 
         let text = entry.embedding_text();
@@ -599,4 +737,31 @@ Body"#;
         // Summary takes precedence over body
         assert!(!text.contains("Long body"));
     }
+
+    #[test]
+    fn test_validate_resonance_fields() {
+        let mut entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-test",
+            "category_id": "pattern",
+            "title": "Test Entry",
+            "resonance": 12,
+            "resonance_type": "foundational",
+            "decay_rate": 0.5
+        }))
+        .unwrap();
+        assert!(entry.validate().is_ok());
+
+        entry.resonance = -1;
+        entry.decay_rate = 1.5;
+        entry.resonance_type = Some("bloom".to_string());
+        let err = entry.validate().unwrap_err().to_string();
+        assert!(err.contains("resonance -1"));
+        assert!(err.contains("decay_rate 1.5"));
+        assert!(err.contains("unknown resonance type 'bloom'"));
+
+        entry.resonance = 5;
+        entry.decay_rate = f64::NAN;
+        entry.resonance_type = None;
+        assert!(entry.validate().is_err());
+    }
 }
@@ -0,0 +1,216 @@
+//! Line-level text diff and three-way merge
+//!
+//! A plain LCS diff over lines: entry bodies are at most a few thousand lines,
+//! so the quadratic table is fine and keeps this dependency-free. Common
+//! leading and trailing lines are stripped before the table is built.
+//! [`merge3`] is the classic diff3 merge on top of it.
+
+/// One step of an edit script turning `a` into `b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Diff two texts line by line
+pub fn diff_lines<'a>(a: &'a str, b: &'a str) -> Vec<Op<'a>> {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (am, bm) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    // lcs[i][j] = LCS length of am[i..] and bm[j..]
+    let mut lcs = vec![vec![0usize; bm.len() + 1]; am.len() + 1];
+    for i in (0..am.len()).rev() {
+        for j in (0..bm.len()).rev() {
+            lcs[i][j] = if am[i] == bm[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op> = a[..prefix].iter().map(|l| Op::Equal(l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < am.len() && j < bm.len() {
+        if am[i] == bm[j] {
+            ops.push(Op::Equal(am[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Delete(am[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(bm[j]));
+            j += 1;
+        }
+    }
+    ops.extend(am[i..].iter().map(|l| Op::Delete(l)));
+    ops.extend(bm[j..].iter().map(|l| Op::Insert(l)));
+    ops.extend(a[a.len() - suffix..].iter().map(|l| Op::Equal(l)));
+    ops
+}
+
+/// `-`/`+`/` ` prefixed lines, keeping `context` unchanged lines around each
+/// change and collapsing longer unchanged runs to `…`
+pub fn render(ops: &[Op], context: usize) -> Vec<String> {
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| changed.iter().any(|&c| c.abs_diff(i) <= context);
+
+    let mut out: Vec<String> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        match op {
+            Op::Delete(line) => out.push(format!("- {}", line)),
+            Op::Insert(line) => out.push(format!("+ {}", line)),
+            Op::Equal(line) if near_change(i) => out.push(format!("  {}", line)),
+            Op::Equal(_) => {
+                if out.last().is_none_or(|l| l != "  …") {
+                    out.push("  …".to_string());
+                }
+            }
+        }
+    }
+    out
+}
+
+/// For each line of `base`, the index of the matching line in `other`
+fn matches(base: &str, other: &str) -> Vec<Option<usize>> {
+    let mut out = Vec::new();
+    let mut j = 0;
+    for op in diff_lines(base, other) {
+        match op {
+            Op::Equal(_) => {
+                out.push(Some(j));
+                j += 1;
+            }
+            Op::Delete(_) => out.push(None),
+            Op::Insert(_) => j += 1,
+        }
+    }
+    out
+}
+
+/// Result of a three-way merge
+#[derive(Debug, PartialEq, Eq)]
+pub struct Merged {
+    pub text: String,
+    /// Number of conflict blocks (marked with `<<<<<<<` / `>>>>>>>`)
+    pub conflicts: usize,
+}
+
+/// Merge `local` and `remote` against their common `base`, diff3 style.
+/// Regions changed on one side take that side; regions changed identically
+/// take either; regions changed differently become conflict blocks.
+pub fn merge3(base: &str, local: &str, remote: &str) -> Merged {
+    let b: Vec<&str> = base.lines().collect();
+    let l: Vec<&str> = local.lines().collect();
+    let r: Vec<&str> = remote.lines().collect();
+    let (ml, mr) = (matches(base, local), matches(base, remote));
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut conflicts = 0;
+    let (mut ib, mut il, mut ir) = (0, 0, 0);
+    loop {
+        // Next base line kept, in place, by both sides
+        let stable = (ib..b.len()).find_map(|i| match (ml[i], mr[i]) {
+            (Some(jl), Some(jr)) if jl >= il && jr >= ir => Some((i, jl, jr)),
+            _ => None,
+        });
+        let (eb, el, er) = stable.unwrap_or((b.len(), l.len(), r.len()));
+
+        let (cb, cl, cr) = (&b[ib..eb], &l[il..el], &r[ir..er]);
+        if cl == cb {
+            out.extend(cr);
+        } else if cr == cb || cl == cr {
+            out.extend(cl);
+        } else {
+            conflicts += 1;
+            out.push("<<<<<<< local");
+            out.extend(cl);
+            out.push("||||||| base");
+            out.extend(cb);
+            out.push("=======");
+            out.extend(cr);
+            out.push(">>>>>>> remote");
+        }
+
+        if stable.is_none() {
+            break;
+        }
+        out.push(b[eb]);
+        (ib, il, ir) = (eb + 1, el + 1, er + 1);
+    }
+
+    let mut text = out.join("\n");
+    if [base, local, remote].iter().any(|t| t.ends_with('\n')) && !text.is_empty() {
+        text.push('\n');
+    }
+    Merged { text, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_and_renders_with_context() {
+        let a = "one\ntwo\nthree\nfour\nfive\nsix";
+        let b = "one\ntwo\nthree\nFOUR\nfive\nsix\nseven";
+        let ops = diff_lines(a, b);
+        assert_eq!(
+            ops.iter()
+                .filter(|op| !matches!(op, Op::Equal(_)))
+                .collect::<Vec<_>>(),
+            vec![
+                &Op::Delete("four"),
+                &Op::Insert("FOUR"),
+                &Op::Insert("seven")
+            ]
+        );
+        assert_eq!(
+            render(&ops, 1),
+            vec![
+                "  …", "  three", "- four", "+ FOUR", "  five", "  six", "+ seven"
+            ]
+        );
+        assert!(
+            diff_lines("same", "same")
+                .iter()
+                .all(|op| matches!(op, Op::Equal(_)))
+        );
+    }
+
+    #[test]
+    fn merges_three_ways() {
+        let base = "a\nb\nc\nd\n";
+        // Non-overlapping edits on each side merge cleanly
+        let clean = merge3(base, "a\nB\nc\nd\n", "a\nb\nc\nD\ne\n");
+        assert_eq!(clean.text, "a\nB\nc\nD\ne\n");
+        assert_eq!(clean.conflicts, 0);
+
+        // The same line changed differently is a conflict
+        let conflicted = merge3(base, "a\nX\nc\nd\n", "a\nY\nc\nd\n");
+        assert_eq!(conflicted.conflicts, 1);
+        assert_eq!(
+            conflicted.text,
+            "a\n<<<<<<< local\nX\n||||||| base\nb\n=======\nY\n>>>>>>> remote\nc\nd\n"
+        );
+
+        // Identical changes are not conflicts
+        assert_eq!(merge3(base, "a\nZ\n", "a\nZ\n").text, "a\nZ\n");
+    }
+}
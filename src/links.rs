@@ -0,0 +1,420 @@
+//! References in entry bodies, checked by `mx memory lint --links`
+//!
+//! Bodies point at other entries by id (`kn-1a2b3c4d`) or with wiki links
+//! (`[[Title]]`, `[[kn-1a2b3c4d]]`, `[[Title|label]]`), and at files with
+//! markdown links (`[notes](../notes.md)`). Code spans and fenced blocks are
+//! skipped. A reference is dead when:
+//!
+//! - no visible entry has the id or title: it was deleted, trashed or
+//!   renamed, or never existed
+//! - the file doesn't exist. Relative paths resolve against the entry's
+//!   file under the memory root, or the memory root itself.
+//!
+//! `--fix` rewrites the wiki links it safely can: a link to a title an entry
+//! used to have (from its revision history) gets the current title, and a
+//! link to something gone for good becomes plain text. Links into the trash
+//! are left for `mx memory trash restore`; bare ids and file paths are only
+//! reported.
+
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+
+static WIKI_RE: OnceLock<Regex> = OnceLock::new();
+static FILE_RE: OnceLock<Regex> = OnceLock::new();
+static ID_RE: OnceLock<Regex> = OnceLock::new();
+static CODE_SPAN_RE: OnceLock<Regex> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// An id in running text
+    Id(String),
+    /// `[[target]]` or `[[target|label]]`, where target is an id or a title
+    Wiki {
+        target: String,
+        label: Option<String>,
+    },
+    /// A markdown link to a local file
+    File(String),
+}
+
+/// A reference and where it sits in the body (byte offsets)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub target: Target,
+    pub span: Range<usize>,
+}
+
+/// `text` with code spans and fenced blocks blanked out, same byte offsets
+pub fn mask_code(text: &str) -> String {
+    let code_span = CODE_SPAN_RE.get_or_init(|| Regex::new(r"`[^`\n]*`").unwrap());
+    let mut masked = String::with_capacity(text.len());
+    let mut fenced = false;
+    for line in text.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+        if fenced || fence {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            masked.push_str(&" ".repeat(content.len()));
+            masked.push_str(&line[content.len()..]);
+        } else {
+            masked.push_str(
+                &code_span.replace_all(line, |c: &regex::Captures| " ".repeat(c[0].len())),
+            );
+        }
+        if fence {
+            fenced = !fenced;
+        }
+    }
+    masked
+}
+
+/// Every reference in `body`, in order
+pub fn references(body: &str) -> Vec<Reference> {
+    let wiki =
+        WIKI_RE.get_or_init(|| Regex::new(r"\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap());
+    let file = FILE_RE.get_or_init(|| Regex::new(r"\[[^\]\n]*\]\(([^)\s]+)\)").unwrap());
+    let id = ID_RE.get_or_init(|| Regex::new(r"\bkn-[0-9a-f]{8}\b").unwrap());
+
+    let text = mask_code(body);
+    let mut found = Vec::new();
+    for c in wiki.captures_iter(&text) {
+        found.push(Reference {
+            target: Target::Wiki {
+                target: c[1].trim().to_string(),
+                label: c.get(2).map(|l| l.as_str().trim().to_string()),
+            },
+            span: c.get(0).unwrap().range(),
+        });
+    }
+    for c in file.captures_iter(&text) {
+        let target = &c[1];
+        if target.contains("://") || target.starts_with('#') || target.starts_with("mailto:") {
+            continue;
+        }
+        found.push(Reference {
+            target: Target::File(target.to_string()),
+            span: c.get(0).unwrap().range(),
+        });
+    }
+    for m in id.find_iter(&text) {
+        if found.iter().any(|r| r.span.contains(&m.start())) {
+            continue;
+        }
+        found.push(Reference {
+            target: Target::Id(m.as_str().to_string()),
+            span: m.range(),
+        });
+    }
+    found.sort_by_key(|r| r.span.start);
+    found
+}
+
+/// Why a reference doesn't resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dead {
+    /// The entry is in the trash
+    Trashed { id: String },
+    /// A wiki link to a title the entry used to have
+    Renamed { id: String, title: String },
+    /// No such entry, in the store or its history
+    Missing,
+    /// No such file
+    NoFile(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLink {
+    pub reference: Reference,
+    /// The reference as written
+    pub text: String,
+    pub dead: Dead,
+}
+
+impl DeadLink {
+    pub fn describe(&self) -> String {
+        match &self.dead {
+            Dead::Trashed { id } => format!("{} points into the trash ({})", self.text, id),
+            Dead::Renamed { id, title } => {
+                format!(
+                    "{} points at a former title of {} ('{}')",
+                    self.text, id, title
+                )
+            }
+            Dead::Missing => format!("{} points at no entry", self.text),
+            Dead::NoFile(path) => format!(
+                "{} points at a missing file ({})",
+                self.text,
+                path.display()
+            ),
+        }
+    }
+
+    /// The text `--fix` replaces the reference with, if any
+    pub fn fix(&self) -> Option<String> {
+        let Target::Wiki { target, label } = &self.reference.target else {
+            return None;
+        };
+        match &self.dead {
+            Dead::Renamed { title, .. } => Some(match label {
+                Some(label) => format!("[[{}|{}]]", title, label),
+                None => format!("[[{}]]", title),
+            }),
+            Dead::Missing => Some(label.clone().unwrap_or_else(|| target.clone())),
+            Dead::Trashed { .. } | Dead::NoFile(_) => None,
+        }
+    }
+}
+
+/// `body` with every fixable dead link rewritten
+pub fn apply_fixes(body: &str, dead: &[DeadLink]) -> String {
+    let mut fixed = body.to_string();
+    let mut fixes: Vec<(&Range<usize>, String)> = dead
+        .iter()
+        .filter_map(|d| d.fix().map(|f| (&d.reference.span, f)))
+        .collect();
+    fixes.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+    for (span, text) in fixes {
+        fixed.replace_range(span.clone(), &text);
+    }
+    fixed
+}
+
+/// What references resolve against: the visible entries, the trash, and
+/// (once loaded) former titles
+#[derive(Debug, Default)]
+pub struct Index {
+    /// id -> title
+    live: HashMap<String, String>,
+    /// lowercased title -> id
+    titles: HashMap<String, String>,
+    /// id -> title, and lowercased title -> id, for the trash
+    trashed: HashMap<String, String>,
+    trashed_titles: HashMap<String, String>,
+    /// lowercased former title -> id of the live entry that had it
+    former: Option<HashMap<String, String>>,
+    memory_root: PathBuf,
+}
+
+impl Index {
+    pub fn new(
+        live: &[KnowledgeEntry],
+        trashed: &[KnowledgeEntry],
+        memory_root: impl Into<PathBuf>,
+    ) -> Self {
+        let by_title = |entries: &[KnowledgeEntry]| {
+            entries
+                .iter()
+                .map(|e| (e.title.trim().to_lowercase(), e.id.clone()))
+                .collect()
+        };
+        let by_id = |entries: &[KnowledgeEntry]| {
+            entries
+                .iter()
+                .map(|e| (e.id.clone(), e.title.clone()))
+                .collect()
+        };
+        Self {
+            live: by_id(live),
+            titles: by_title(live),
+            trashed: by_id(trashed),
+            trashed_titles: by_title(trashed),
+            former: None,
+            memory_root: memory_root.into(),
+        }
+    }
+
+    /// Index what `ctx` can see
+    pub fn load(db: &dyn KnowledgeStore, ctx: &AgentContext, memory_root: &Path) -> Result<Self> {
+        Ok(Self::new(
+            &db.list_all(ctx)?,
+            &db.list_archived(ctx)?,
+            memory_root,
+        ))
+    }
+
+    /// Read every live entry's former titles from its revision history, so
+    /// links to renamed entries can be told apart from dead ones
+    pub fn load_history(&mut self, db: &dyn KnowledgeStore) -> Result<()> {
+        let mut former = HashMap::new();
+        for id in self.live.keys() {
+            for backup in db.list_backups(id)? {
+                let title = backup.title.trim().to_lowercase();
+                if !self.titles.contains_key(&title) {
+                    former.entry(title).or_insert_with(|| id.clone());
+                }
+            }
+        }
+        self.former = Some(former);
+        Ok(())
+    }
+
+    pub fn has_history(&self) -> bool {
+        self.former.is_some()
+    }
+
+    fn resolve_entry(&self, target: &str, wiki: bool) -> Option<Dead> {
+        if target.starts_with("kn-") {
+            if self.live.contains_key(target) {
+                return None;
+            }
+            return Some(match self.trashed.contains_key(target) {
+                true => Dead::Trashed {
+                    id: target.to_string(),
+                },
+                false => Dead::Missing,
+            });
+        }
+        if !wiki {
+            return None;
+        }
+        let title = target.to_lowercase();
+        if self.titles.contains_key(&title) {
+            return None;
+        }
+        if let Some(id) = self.trashed_titles.get(&title) {
+            return Some(Dead::Trashed { id: id.clone() });
+        }
+        match self.former.as_ref().and_then(|f| f.get(&title)) {
+            Some(id) => Some(Dead::Renamed {
+                id: id.clone(),
+                title: self.live[id].clone(),
+            }),
+            None => Some(Dead::Missing),
+        }
+    }
+
+    fn resolve_file(&self, entry: &KnowledgeEntry, target: &str) -> Option<Dead> {
+        let target = target.split(['#', '?']).next().unwrap_or(target);
+        if target.is_empty() {
+            return None;
+        }
+        let path = if let Some(rest) = target.strip_prefix("~/") {
+            dirs::home_dir()?.join(rest)
+        } else if Path::new(target).is_absolute() {
+            PathBuf::from(target)
+        } else {
+            let dir = entry
+                .file_path
+                .as_deref()
+                .and_then(|f| Path::new(f).parent())
+                .map(|d| self.memory_root.join(d))
+                .unwrap_or_else(|| self.memory_root.clone());
+            dir.join(target)
+        };
+        (!path.exists()).then_some(Dead::NoFile(path))
+    }
+
+    /// Dead references in `entry`'s body
+    pub fn check(&self, entry: &KnowledgeEntry) -> Vec<DeadLink> {
+        let Some(body) = entry.body.as_deref() else {
+            return Vec::new();
+        };
+        references(body)
+            .into_iter()
+            .filter(|r| !matches!(&r.target, Target::Id(id) if *id == entry.id))
+            .filter_map(|reference| {
+                let dead = match &reference.target {
+                    Target::Id(id) => self.resolve_entry(id, false),
+                    Target::Wiki { target, .. } => self.resolve_entry(target, true),
+                    Target::File(path) => self.resolve_file(entry, path),
+                }?;
+                Some(DeadLink {
+                    text: body[reference.span.clone()].to_string(),
+                    reference,
+                    dead,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, title: &str, body: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": title,
+            "body": body,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_ids_wiki_links_and_files_outside_code() {
+        let body = "See kn-0000000a and [[Retry|retries]], [[kn-0000000b]].\n\
+                    [notes](notes.md) [site](https://x.io) `kn-0000000c`\n\
+                    ```\n[[Hidden]]\n```\n";
+        let targets: Vec<Target> = references(body).into_iter().map(|r| r.target).collect();
+        assert_eq!(
+            targets,
+            vec![
+                Target::Id("kn-0000000a".into()),
+                Target::Wiki {
+                    target: "Retry".into(),
+                    label: Some("retries".into())
+                },
+                Target::Wiki {
+                    target: "kn-0000000b".into(),
+                    label: None
+                },
+                Target::File("notes.md".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_dead_references_and_fixes_wiki_links() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("here.md"), "").unwrap();
+        let me = entry(
+            "kn-00000001",
+            "Me",
+            "[[backoff]] [[Old Name|it]] [[Gone]] [[Binned]] kn-00000009 kn-00000003 \
+             [a](here.md) [b](missing.md#top)",
+        );
+        let live = vec![
+            me.clone(),
+            entry("kn-00000002", "Backoff", ""),
+            entry("kn-00000003", "New Name", ""),
+        ];
+        let trashed = vec![entry("kn-00000004", "Binned", "")];
+        let mut index = Index::new(&live, &trashed, dir.path());
+        index.former = Some(HashMap::from([(
+            "old name".to_string(),
+            "kn-00000003".to_string(),
+        )]));
+
+        let dead = index.check(&me);
+        let found: Vec<(&str, &Dead)> = dead.iter().map(|d| (d.text.as_str(), &d.dead)).collect();
+        assert_eq!(found.len(), 5);
+        assert_eq!(
+            found[0],
+            (
+                "[[Old Name|it]]",
+                &Dead::Renamed {
+                    id: "kn-00000003".into(),
+                    title: "New Name".into()
+                }
+            )
+        );
+        assert_eq!(found[1], ("[[Gone]]", &Dead::Missing));
+        assert!(matches!(found[2].1, Dead::Trashed { id } if id == "kn-00000004"));
+        assert_eq!(found[3], ("kn-00000009", &Dead::Missing));
+        assert!(matches!(found[4].1, Dead::NoFile(p) if p.ends_with("missing.md")));
+
+        assert_eq!(
+            apply_fixes(me.body.as_deref().unwrap(), &dead),
+            "[[backoff]] [[New Name|it]] Gone [[Binned]] kn-00000009 kn-00000003 \
+             [a](here.md) [b](missing.md#top)"
+        );
+    }
+}
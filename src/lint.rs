@@ -0,0 +1,137 @@
+//! Store-wide entry checks: `mx memory lint`
+//!
+//! Runs the write-time checks over entries already in the store: resonance
+//! field validation and the category rules from config.yaml. Entries written
+//! with `--force`, imported before a rule existed, or moved between
+//! categories show up here. `--links` also checks the references in bodies
+//! (see links.rs), and `--fix` repairs the wiki links it can. Exits non-zero
+//! when anything is reported.
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::category_rules::Rules;
+use crate::index::IndexConfig;
+use crate::knowledge::KnowledgeEntry;
+use crate::links::{self, Dead, Index, Target};
+use crate::store::{AgentContext, KnowledgeStore};
+
+#[derive(clap::Args)]
+pub struct LintArgs {
+    /// Only check entries in this category
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Also report dead references in bodies: entry ids, [[wiki links]] and file paths
+    #[arg(long)]
+    pub links: bool,
+
+    /// Rewrite dead wiki links: to a renamed entry's current title, or to plain text
+    #[arg(long, requires = "links")]
+    pub fix: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub id: String,
+    pub title: String,
+    pub category: String,
+    pub problems: Vec<String>,
+    /// Dead links `--fix` rewrote
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<String>,
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: LintArgs) -> Result<()> {
+    let rules = Rules::load()?;
+    let entries: Vec<_> = db
+        .list_all(ctx)?
+        .into_iter()
+        .filter(|e| args.category.as_ref().is_none_or(|c| e.category_id == *c))
+        .collect();
+    let checked = entries.len();
+
+    let mut index = None;
+    if args.links {
+        let mut links = Index::load(db, ctx, &IndexConfig::default().memory_root)?;
+        // Revision history only matters once a wiki link fails to resolve
+        let unresolved_wiki = entries
+            .iter()
+            .flat_map(|e| links.check(e))
+            .any(|d| d.dead == Dead::Missing && matches!(d.reference.target, Target::Wiki { .. }));
+        if unresolved_wiki {
+            links.load_history(db)?;
+        }
+        index = Some(links);
+    }
+
+    let mut findings = Vec::new();
+    for mut entry in entries {
+        let mut problems = entry.problems();
+        problems.extend(rules.problems(&entry));
+        let mut fixed = Vec::new();
+        if let Some(index) = &index {
+            let dead = index.check(&entry);
+            if args.fix && dead.iter().any(|d| d.fix().is_some()) {
+                let body = links::apply_fixes(entry.body.as_deref().unwrap_or(""), &dead);
+                if let Err(e) = db.backup_content(&entry, "edit", ctx.agent_id.as_deref()) {
+                    eprintln!("Warning: failed to create backup: {}", e);
+                }
+                entry.content_hash = Some(KnowledgeEntry::compute_hash(&body));
+                entry.body = Some(body);
+                entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+                db.upsert_knowledge(&entry)?;
+            }
+            for link in dead {
+                match link.fix().filter(|_| args.fix) {
+                    Some(to) => fixed.push(format!("{} -> {}", link.text, to)),
+                    None => problems.push(link.describe()),
+                }
+            }
+        }
+        if !problems.is_empty() || !fixed.is_empty() {
+            findings.push(Finding {
+                id: entry.id,
+                title: entry.title,
+                category: entry.category_id,
+                problems,
+                fixed,
+            });
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        for finding in &findings {
+            println!(
+                "{} {} {}",
+                finding.id.yellow(),
+                finding.title,
+                format!("[{}]", finding.category).dimmed()
+            );
+            for problem in &finding.problems {
+                println!("    - {}", problem);
+            }
+            for fix in &finding.fixed {
+                println!("    {} fixed {}", "✓".green(), fix);
+            }
+        }
+        if findings.is_empty() {
+            println!("{} entries checked, no problems", checked);
+        } else {
+            println!();
+        }
+    }
+
+    let failing = findings.iter().filter(|f| !f.problems.is_empty()).count();
+    if failing > 0 {
+        bail!("{} of {} entries have problems", failing, checked);
+    }
+    Ok(())
+}
@@ -1,30 +1,103 @@
 #![allow(dead_code)]
 
+mod alias;
+mod attach;
+mod backlinks;
+mod batch_add;
+mod bench;
+#[cfg(feature = "tui")]
+mod browse;
+mod bulk;
+mod capture;
+mod category_rules;
+mod changes;
+mod closeout;
 mod codex;
 mod commit;
+mod confidence;
+mod config;
+mod confirm;
 mod content_ops;
+mod contradictions;
 mod convert;
+mod cron;
+mod dates;
+mod decay;
+mod dedupe;
+mod deep_stats;
+mod doctor;
+mod drill;
+mod editor;
 mod embeddings;
 mod engage;
+mod error;
+mod explain;
+#[cfg(feature = "github")]
 mod github;
+mod graph;
+mod identity;
 mod index;
+mod ingest;
+mod init;
+mod journal;
+mod jsonl;
 mod knowledge;
+mod line_diff;
+mod links;
+mod lint;
+mod manifest_query;
+mod merge_entry;
+mod morning;
+mod neighbors;
+mod notify;
+mod output;
+mod pack;
 pub mod paths;
+mod progress;
+mod project_archive;
+mod promote;
+mod queue;
+mod recent;
+mod relate;
+mod replicate;
+mod resonance;
+mod review;
+mod revisions;
+mod search;
+#[cfg(feature = "github")]
+mod self_update;
 mod session;
+mod site;
+mod slow_log;
+mod snapshot;
+mod star;
+#[cfg(feature = "state")]
 mod state;
 mod store;
 mod surreal_db;
 mod sync;
+mod templates;
+mod tenant;
+#[cfg(feature = "state")]
 mod tensor;
+mod text;
+mod tokens;
+mod trash;
 mod types;
+mod usage;
+mod viz;
+mod wake_format;
 mod wake_ritual;
 mod wake_token;
+mod workspace;
 
 use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 
+use crate::error::MxError;
 use crate::index::{
-    IndexConfig, export_csv, export_jsonl, export_markdown, import_jsonl, rebuild_index,
+    IndexConfig, export_csv, export_jsonl, export_markdown, import_jsonl, import_markdown,
+    rebuild_index,
 };
 
 #[derive(Parser)]
@@ -36,6 +109,26 @@ struct Cli {
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
 
+    /// Use an isolated profile under $MX_HOME/profiles/<name>/ (or MX_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// When to use colors (auto respects NO_COLOR and whether stdout is a terminal)
+    #[arg(long, global = true, value_enum, default_value_t = output::ColorMode::Auto)]
+    color: output::ColorMode,
+
+    /// Never pipe long output through $PAGER
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Show raw UTC timestamps instead of local time, and read dates as UTC
+    #[arg(long, global = true)]
+    utc: bool,
+
+    /// How failures are printed: text, or one JSON object on stderr (or MX_ERROR_FORMAT)
+    #[arg(long, global = true, value_enum)]
+    error_format: Option<error::ErrorFormat>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -44,11 +137,31 @@ struct Cli {
 #[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Knowledge base operations (CRUD, search, wake, facts)
+    #[command(alias = "zion")]
     Memory {
         #[command(subcommand)]
         command: MemoryCommands,
     },
 
+    /// First-run setup: create directories, initialize the store, register identity
+    Init(init::InitOptions),
+
+    /// Update mx to the newest release from GitHub
+    #[cfg(feature = "github")]
+    SelfUpdate {
+        /// Release channel to follow
+        #[arg(long, value_enum, default_value = "stable")]
+        channel: self_update::Channel,
+
+        /// Only check whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Reinstall even if already on the latest version
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Create an encoded git commit
     Commit {
         /// Commit message (human-readable, will be encoded)
@@ -88,18 +201,21 @@ enum Commands {
     },
 
     /// GitHub sync operations
+    #[cfg(feature = "github")]
     Sync {
         #[command(subcommand)]
         command: SyncCommands,
     },
 
     /// GitHub operations
+    #[cfg(feature = "github")]
     Github {
         #[command(subcommand)]
         command: GithubCommands,
     },
 
     /// Wiki operations
+    #[cfg(feature = "github")]
     Wiki {
         #[command(subcommand)]
         command: WikiCommands,
@@ -150,10 +266,418 @@ enum Commands {
     },
 
     /// Emotional state tensor operations
+    #[cfg(feature = "state")]
     State {
         #[command(subcommand)]
         command: StateCommands,
     },
+
+    /// Show the local command history (enable logging with MX_HISTORY=1)
+    History {
+        /// Number of commands to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Only show failed commands
+        #[arg(long)]
+        failed: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Usage and performance statistics
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommands,
+    },
+
+    /// Benchmarks
+    Bench {
+        #[command(subcommand)]
+        command: BenchCommands,
+    },
+
+    /// Store the clipboard (or stdin) as an entry, prompting for a title
+    Capture(capture::CaptureOptions),
+
+    /// Scheduled recurring jobs (systemd timers / launchd agents)
+    Cron {
+        #[command(subcommand)]
+        command: CronCommands,
+    },
+
+    /// Import external material (email, web pages, documents, transcripts) as knowledge entries
+    Ingest {
+        #[command(subcommand)]
+        command: IngestCommands,
+    },
+
+    /// Post knowledge events to Matrix or Discord
+    Notify {
+        #[command(subcommand)]
+        command: NotifyCommands,
+    },
+
+    /// Reading list of URLs to ingest and entries to curate
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommands,
+    },
+
+    /// Task-specific context packs from knowledge, issues, and session archives
+    Pack {
+        #[command(subcommand)]
+        command: pack::PackCommands,
+    },
+
+    /// Tenants, agent tokens, and grants for the multi-tenant server
+    Tenant {
+        #[command(subcommand)]
+        command: tenant::TenantCommands,
+    },
+
+    /// Start a session: identity block from the wake cascade, plus what's
+    /// waiting (reviews, reading queue, open sessions, last state)
+    Wake(morning::WakeArgs),
+
+    /// Check the store and list operations left incomplete by a crash
+    Doctor(doctor::DoctorArgs),
+
+    /// Show the git repo and registered project inferred from the current directory
+    Context(workspace::ContextArgs),
+
+    /// User-defined shorthands for long commands (stored in config.yaml)
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasCommands {
+    /// List defined aliases
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Define or replace an alias
+    Add {
+        /// Alias name, used as `mx <name>`
+        name: String,
+
+        /// Command it expands to, without the leading `mx`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Remove an alias
+    Remove {
+        /// Alias name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum QueueCommands {
+    /// Queue a URL or an entry id
+    Add {
+        /// URL to read later, or an entry id to curate
+        target: String,
+
+        /// Priority 1-10 (higher comes first)
+        #[arg(short, long, default_value = "5")]
+        priority: i64,
+
+        /// Title to show (entries default to their own title)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Why it's queued
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Show the next item to pick up
+    Next {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Mark an item done (defaults to the next item)
+    Done {
+        /// Queue item id (e.g. q-1a2b3c4d)
+        id: Option<String>,
+
+        /// Ingest the URL into the index before checking it off
+        #[arg(long)]
+        ingest: bool,
+
+        /// Category for --ingest
+        #[arg(long, default_value = "reference")]
+        category: String,
+    },
+
+    /// List queued items
+    List {
+        /// Include completed items
+        #[arg(long)]
+        all: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IngestCommands {
+    /// Import messages from an mbox or .eml file, tagged by sender and thread
+    Email {
+        /// Path to an mbox or a single .eml message
+        path: String,
+
+        /// Category for the new entries
+        #[arg(long, default_value = "reference")]
+        category: String,
+
+        /// Extra comma-separated tags
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Store entries as private to MX_CURRENT_AGENT
+        #[arg(long)]
+        private: bool,
+
+        /// Don't save attachments to the blob store
+        #[arg(long)]
+        no_attachments: bool,
+
+        /// Show what would be created without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Clip a web page: fetch it and keep the readable article text
+    Url {
+        /// Page URL (http or https)
+        url: String,
+
+        /// Category for the new entry
+        #[arg(long, default_value = "reference")]
+        category: String,
+
+        /// Extra comma-separated tags
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Store the entry as private to MX_CURRENT_AGENT
+        #[arg(long)]
+        private: bool,
+
+        /// Keep the raw HTML in the blob store
+        #[arg(long)]
+        archive_html: bool,
+
+        /// Show what would be created without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Extract text from a document (pdf, docx, md, txt)
+    File {
+        /// Path to the document
+        path: String,
+
+        /// Category for the new entries
+        #[arg(long, default_value = "reference")]
+        category: String,
+
+        /// Extra comma-separated tags
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Store entries as private to MX_CURRENT_AGENT
+        #[arg(long)]
+        private: bool,
+
+        /// One entry per section (headings, or pages for PDF) instead of one entry
+        #[arg(long)]
+        split: bool,
+
+        /// Show what would be created without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Import a transcript (WebVTT, SRT, or Whisper JSON) as chronicle entries
+    Transcript {
+        /// Path to the transcript
+        path: String,
+
+        /// Name used in entry titles (defaults to the file name)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Session entry to link the entries to (with or without kn- prefix)
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Minutes of speech per entry
+        #[arg(long, default_value = "5")]
+        window: u32,
+
+        /// Start a new entry at every change of speaker
+        #[arg(long)]
+        by_speaker: bool,
+
+        /// Category for the new entries
+        #[arg(long, default_value = "session")]
+        category: String,
+
+        /// Extra comma-separated tags
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Store entries as private to MX_CURRENT_AGENT
+        #[arg(long)]
+        private: bool,
+
+        /// Show what would be created without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NotifyCommands {
+    /// Post a summary of public entries added in the last N days
+    Digest {
+        /// Days to look back
+        #[arg(long, default_value = "7")]
+        days: i64,
+
+        /// Only include entries with at least this resonance
+        #[arg(long)]
+        min_resonance: Option<i32>,
+
+        /// Print the digest instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// With --dry-run, print the message as-is instead of rendering markdown
+        #[arg(long, requires = "dry_run")]
+        raw: bool,
+    },
+
+    /// Send a test message to the configured targets
+    Test {
+        /// Message text
+        message: Option<String>,
+    },
+
+    /// Show which targets are configured
+    Status,
+}
+
+#[derive(Subcommand)]
+enum CronCommands {
+    /// Add or replace a job: a preset, or `<name> --schedule <s> -- <mx args>`
+    Add {
+        /// Job name (defaults to the preset's name)
+        name: Option<String>,
+
+        /// hourly, daily [HH:MM], nightly, or weekly [DAY] [HH:MM]
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// Use a built-in job definition
+        #[arg(long, value_enum)]
+        preset: Option<cron::Preset>,
+
+        /// Repository for the sync-pull preset (owner/repo)
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// mx arguments to run, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// List scheduled jobs and whether they are installed
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove a job and uninstall its units
+    Remove {
+        /// Job name
+        name: String,
+    },
+
+    /// Write scheduler units for all jobs and enable them
+    Install {
+        /// Print the generated units without writing or enabling anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BenchCommands {
+    /// Measure upsert, search, list, and cascade latency on synthetic entries
+    Store {
+        /// Number of synthetic entries to generate
+        #[arg(long, default_value = "1000")]
+        entries: usize,
+
+        /// Number of timed runs per read operation
+        #[arg(long, default_value = "20")]
+        queries: usize,
+
+        /// Allow running against a network server (MX_SURREAL_MODE=network)
+        #[arg(long)]
+        network: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommands {
+    /// Summarize logged commands: runs, failures, and latency per command
+    Usage {
+        /// Only include commands from the last N days
+        #[arg(long)]
+        days: Option<i64>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the slowest store operations, GitHub calls and commands (MX_SLOW_LOG=1)
+    Slow {
+        /// Only include operations from the last N days
+        #[arg(long)]
+        days: Option<i64>,
+
+        /// Show at most N operations
+        #[arg(long, default_value = "10")]
+        limit: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -191,6 +715,7 @@ enum ConvertCommands {
     },
 }
 
+#[cfg(feature = "state")]
 #[derive(Subcommand)]
 enum StateCommands {
     /// Encode state tensor from dimensional values
@@ -323,15 +848,20 @@ enum PrCommands {
         /// Use standard merge commit instead of squash (mutually exclusive with --rebase)
         #[arg(long, name = "merge", conflicts_with = "rebase")]
         merge_commit: bool,
+
+        /// Repository (owner/repo format; defaults to the current git repo)
+        #[arg(long)]
+        repo: Option<String>,
     },
 }
 
+#[cfg(feature = "github")]
 #[derive(Subcommand)]
 pub enum SyncCommands {
     /// Pull issues/discussions from GitHub to local YAML
     Pull {
-        /// Repository (owner/repo format)
-        repo: String,
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
 
         /// Output directory (defaults to $MX_HOME/cache/sync/<repo>)
         #[arg(short, long)]
@@ -340,12 +870,16 @@ pub enum SyncCommands {
         /// Dry run - show what would be pulled
         #[arg(long)]
         dry_run: bool,
+
+        /// GitHub requests in flight at once (1-16)
+        #[arg(long, default_value_t = sync::github::DEFAULT_CONCURRENCY)]
+        concurrency: usize,
     },
 
     /// Push local changes to GitHub
     Push {
-        /// Repository (owner/repo format)
-        repo: String,
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
 
         /// Input directory (defaults to $MX_HOME/cache/sync/<repo>)
         #[arg(short, long)]
@@ -358,8 +892,8 @@ pub enum SyncCommands {
 
     /// Sync identity labels to repository
     Labels {
-        /// Repository (owner/repo format)
-        repo: String,
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
 
         /// Dry run - show what would be synced
         #[arg(long)]
@@ -368,13 +902,173 @@ pub enum SyncCommands {
 
     /// Sync issues bidirectionally
     Issues {
-        /// Repository (owner/repo format)
-        repo: String,
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
 
         /// Dry run - show what would be synced
         #[arg(long)]
         dry_run: bool,
     },
+
+    /// Show local sync cache state without contacting GitHub
+    Status {
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
+
+        /// Input directory (defaults to $MX_HOME/cache/sync/<repo>)
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the dependency graph of cached issues: blockers, cycles, ready work
+    Deps(sync::commands::deps::DepsArgs),
+
+    /// Render a milestone's issues as a planning doc: estimates, status, dependencies
+    Plan(sync::commands::plan::PlanArgs),
+}
+
+/// Flags for `mx memory update`
+#[derive(Debug, clap::Args)]
+struct UpdateArgs {
+    /// Entry ID to update
+    id: String,
+
+    /// Update title
+    #[arg(short, long)]
+    title: Option<String>,
+
+    /// Replace content inline (full replacement)
+    #[arg(long, conflicts_with_all = ["file", "append_content", "append_file", "prepend_content", "prepend_file", "find"])]
+    content: Option<String>,
+
+    /// Replace content from file (full replacement)
+    #[arg(short, long, visible_alias = "content-file", conflicts_with_all = ["content", "append_content", "append_file", "prepend_content", "prepend_file", "find"])]
+    file: Option<String>,
+
+    /// Append text to end of existing content
+    #[arg(long, conflicts_with_all = ["content", "file", "append_file", "prepend_content", "prepend_file", "find"])]
+    append_content: Option<String>,
+
+    /// Append content from file to end of existing content
+    #[arg(long, conflicts_with_all = ["content", "file", "append_content", "prepend_content", "prepend_file", "find"])]
+    append_file: Option<String>,
+
+    /// Prepend text to start of existing content
+    #[arg(long, conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_file", "find"])]
+    prepend_content: Option<String>,
+
+    /// Prepend content from file to start of existing content
+    #[arg(long, conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_content", "find"])]
+    prepend_file: Option<String>,
+
+    /// Find text in content (requires --replace)
+    #[arg(long, requires = "replace", conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_content", "prepend_file"])]
+    find: Option<String>,
+
+    /// Replace text found by --find
+    #[arg(long, requires = "find")]
+    replace: Option<String>,
+
+    /// Replace all occurrences (with --find/--replace)
+    #[arg(long, requires = "find")]
+    replace_all: bool,
+
+    /// Replace only the Nth occurrence (1-indexed, with --find/--replace)
+    #[arg(long, requires = "find", conflicts_with = "replace_all")]
+    nth: Option<usize>,
+
+    /// Update category
+    #[arg(long)]
+    category: Option<String>,
+
+    /// Update tags (comma-separated, replaces all)
+    #[arg(long, conflicts_with_all = ["add_tag", "remove_tag"])]
+    tags: Option<String>,
+
+    /// Add a single tag to existing tags
+    #[arg(long, conflicts_with = "tags")]
+    add_tag: Option<String>,
+
+    /// Remove a specific tag
+    #[arg(long, conflicts_with = "tags")]
+    remove_tag: Option<String>,
+
+    /// Update applicability (comma-separated, replaces all)
+    #[arg(short = 'a', long)]
+    applicability: Option<String>,
+
+    /// Update content type
+    #[arg(long)]
+    content_type: Option<String>,
+
+    /// Update resonance level (1-10, or higher for transcendent)
+    #[arg(long)]
+    resonance: Option<i32>,
+
+    /// Update resonance type (foundational, transformative, relational, operational, ephemeral, session)
+    #[arg(long)]
+    resonance_type: Option<String>,
+
+    /// Update anchors (comma-separated bloom IDs, replaces all)
+    #[arg(long, conflicts_with_all = ["add_anchor", "remove_anchor"])]
+    anchors: Option<String>,
+
+    /// Add a single anchor to existing anchors
+    #[arg(long, conflicts_with = "anchors")]
+    add_anchor: Option<String>,
+
+    /// Remove a specific anchor
+    #[arg(long, conflicts_with = "anchors")]
+    remove_anchor: Option<String>,
+
+    /// Update wake phrase for memory ritual verification
+    #[arg(long)]
+    wake_phrase: Option<String>,
+
+    /// Update multiple wake phrases (comma-separated, replaces all)
+    #[arg(long)]
+    wake_phrases: Option<String>,
+
+    /// Add a single wake phrase to existing phrases
+    #[arg(long, conflicts_with = "wake_phrases")]
+    add_wake_phrase: Option<String>,
+
+    /// Remove a specific wake phrase
+    #[arg(long, conflicts_with = "wake_phrases")]
+    remove_wake_phrase: Option<String>,
+
+    /// Update wake order (use '-' to clear)
+    #[arg(long)]
+    wake_order: Option<String>,
+
+    /// Mark as private (shorthand for --visibility private)
+    #[arg(long, conflicts_with = "visibility")]
+    private: bool,
+
+    /// Change visibility (public or private)
+    #[arg(long, conflicts_with = "private")]
+    visibility: Option<String>,
+
+    /// Update owner (only valid when visibility is private)
+    #[arg(long)]
+    owner: Option<String>,
+
+    /// Update confidence, 0.0 (hunch) to 1.0 (battle-tested)
+    #[arg(long, value_parser = confidence::parse)]
+    confidence: Option<f64>,
+
+    /// Force dangerous visibility changes (e.g., making blooms public) and
+    /// skip resonance field validation
+    #[arg(long)]
+    force: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
 }
 
 /// Shared filter flags for search/list commands (extracted from duplicated definitions)
@@ -435,13 +1129,90 @@ struct EntryFilter {
     /// Filter by tags (can specify multiple: focus,rust) (matches any)
     #[arg(long, value_delimiter = ',')]
     tags: Option<Vec<String>>,
+
+    /// Filter by source project
+    #[arg(long)]
+    project: Option<String>,
+
+    /// Only entries created on or after this date (YYYY-MM-DD, yesterday, 7d, RFC 3339)
+    #[arg(long, visible_alias = "after")]
+    since: Option<String>,
+
+    /// Only entries created before this date (same forms as --since)
+    #[arg(long, visible_alias = "before")]
+    until: Option<String>,
+
+    /// Only entries at least this trusted (0.0-1.0, after age decay)
+    #[arg(long, value_parser = confidence::parse)]
+    min_confidence: Option<f64>,
+
+    /// Only entries you starred
+    #[arg(long)]
+    starred: bool,
+}
+
+impl EntryFilter {
+    /// The part of the filter the store applies in its query: resonance,
+    /// tags, starred, project and creation dates (categories are up to the caller)
+    fn store_filter(&self, categories: Option<Vec<String>>) -> Result<store::KnowledgeFilter> {
+        Ok(store::KnowledgeFilter {
+            min_resonance: self.min_resonance,
+            max_resonance: self.max_resonance,
+            categories,
+            tags: self.tags.clone(),
+            starred_by: self.starred.then(star::agent),
+            project: self.project.clone(),
+            since: self.since.as_deref().map(dates::parse_date).transpose()?,
+            until: self.until.as_deref().map(dates::parse_date).transpose()?,
+            search_fields: None,
+        })
+    }
+
+    /// Whether in-memory filters may drop some of what the store returns
+    fn thins_results(&self) -> bool {
+        self.has_wake_phrase
+            || self.missing_wake_phrase
+            || self.has_anchors
+            || self.missing_anchors
+            || self.has_resonance_type
+            || self.missing_resonance_type
+            || self.min_confidence.is_some()
+    }
+}
+
+/// Keyword search hits, best weighted text score (scaled by confidence and,
+/// with `boost_recent`, recent views) first
+fn keyword_search(
+    db: &dyn store::KnowledgeStore,
+    query: &str,
+    ctx: &store::AgentContext,
+    filter: &store::KnowledgeFilter,
+    settings: &confidence::Settings,
+    boost_recent: bool,
+) -> Result<Vec<knowledge::KnowledgeEntry>> {
+    let mut entries = db.search(query, ctx, filter)?;
+    let scores = db.search_scores(query, ctx, filter)?;
+    let now = chrono::Utc::now();
+    if boost_recent {
+        let views = db.list_views(&recent::viewer())?;
+        recent::rank(&mut entries, &scores, &views, settings, now);
+    } else {
+        settings.rank(&mut entries, &scores, now);
+    }
+    Ok(entries)
 }
 
 /// Apply in-memory field presence filters to a list of entries
 fn apply_entry_filters(
     entries: Vec<knowledge::KnowledgeEntry>,
     filter: &EntryFilter,
-) -> Vec<knowledge::KnowledgeEntry> {
+) -> Result<Vec<knowledge::KnowledgeEntry>> {
+    let trust = filter
+        .min_confidence
+        .map(|min| confidence::Settings::load().map(|settings| (settings, min)))
+        .transpose()?;
+    let now = chrono::Utc::now();
+
     let mut entries: Vec<_> = entries
         .into_iter()
         .filter(|e| !filter.has_wake_phrase || e.has_any_wake_phrase())
@@ -455,10 +1226,9 @@ fn apply_entry_filters(
             !filter.missing_resonance_type || e.resonance_type.as_ref().is_none_or(|s| s.is_empty())
         })
         .filter(|e| {
-            filter
-                .tags
+            trust
                 .as_ref()
-                .is_none_or(|filter_tags| filter_tags.iter().any(|t| e.tags.contains(t)))
+                .is_none_or(|(settings, min)| settings.effective(e, now) >= *min)
         })
         .collect();
 
@@ -467,7 +1237,25 @@ fn apply_entry_filters(
         entries.truncate(n);
     }
 
-    entries
+    Ok(entries)
+}
+
+/// Context attached to validation failures on `memory add`/`update`
+const FORCE_HINT: &str = "Entry rejected (use --force to write anyway)";
+
+/// Reject an entry that fails validation or its category's rules
+fn check_entry(entry: &knowledge::KnowledgeEntry, rules: &category_rules::Rules) -> Result<()> {
+    use anyhow::Context;
+    entry.validate().context(FORCE_HINT)?;
+    rules.check(entry).context(FORCE_HINT)
+}
+
+/// `memory wake --phrases-only --json`: one `{id, phrase}` object per phrase
+fn wake_phrases_json(phrases: &[(String, String)]) -> serde_json::Value {
+    phrases
+        .iter()
+        .map(|(id, phrase)| serde_json::json!({ "id": id, "phrase": phrase }))
+        .collect()
 }
 
 /// Normalize a knowledge entry ID (accept both "kn-abc" and "abc", normalize to "kn-abc")
@@ -504,6 +1292,19 @@ enum MemoryCommands {
         /// Use semantic (vector) search instead of keyword search
         #[arg(long)]
         semantic: bool,
+
+        /// Search entries as they existed at a date (YYYY-MM-DD, yesterday, 7d, or RFC 3339)
+        #[arg(long, conflicts_with = "semantic")]
+        as_of: Option<String>,
+
+        #[command(flatten)]
+        fields: search::FieldsArgs,
+
+        #[command(flatten)]
+        explain: explain::ExplainArgs,
+
+        #[command(flatten)]
+        recency: recent::BoostArgs,
     },
 
     /// List entries by category
@@ -524,13 +1325,32 @@ enum MemoryCommands {
         /// Output only the body content (for piping)
         #[arg(long)]
         content_only: bool,
+
+        /// Show the entry as it existed at a date (YYYY-MM-DD, yesterday, 7d, or RFC 3339)
+        #[arg(long)]
+        as_of: Option<String>,
+
+        /// Print the body as stored instead of rendering its markdown
+        #[arg(long)]
+        raw: bool,
     },
 
+    /// Attach a file to an entry, or extract or remove one (see show)
+    Attach(attach::AttachArgs),
+
     /// Show index statistics
     Stats {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Add growth per month, tags, top agents/projects, resonance and orphans
+        #[arg(long)]
+        deep: bool,
+
+        /// How many tags, agents, projects and orphans --deep lists
+        #[arg(long, default_value = "10", requires = "deep")]
+        top: usize,
     },
 
     /// Delete an entry from the index
@@ -541,23 +1361,40 @@ enum MemoryCommands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Move the entry to the trash instead (see 'mx memory trash')
+        #[arg(long, conflicts_with = "hard")]
+        soft: bool,
+
+        /// Delete outright even when config.yaml makes soft delete the default
+        #[arg(long)]
+        hard: bool,
     },
 
-    /// Import entries from JSONL file
+    /// Import entries from a JSONL file or a markdown export directory
     Import {
-        /// Path to JSONL file (defaults to memory/index.jsonl)
+        /// JSONL file, or a directory of markdown files with YAML
+        /// frontmatter (defaults to memory/index.jsonl)
         path: Option<String>,
+
+        /// Import entries even if they fail resonance validation or category rules
+        #[arg(long)]
+        force: bool,
     },
 
     /// Add a new entry directly to the database
     Add {
         /// Category name (run 'mx memory categories list' to see available categories)
         /// When --type is provided, category is auto-determined from fact type routing
-        #[arg(long, required_unless_present = "type")]
+        #[arg(long, required_unless_present_any = ["type", "manifest", "template"])]
         category: Option<String>,
 
         /// Entry title (auto-generated from content when --type is provided)
-        #[arg(short, long, required_unless_present = "type")]
+        #[arg(short, long, required_unless_present_any = ["type", "manifest", "template"])]
         title: Option<String>,
 
         /// Content inline
@@ -581,7 +1418,7 @@ enum MemoryCommands {
         #[arg(short = 'a', long)]
         applicability: Option<String>,
 
-        /// Source project ID
+        /// Source project ID (defaults to the registered project of the current git repo)
         #[arg(short, long)]
         project: Option<String>,
 
@@ -665,161 +1502,50 @@ enum MemoryCommands {
         /// Thread ID for thread_closed operations (requires --type=thread_closed)
         #[arg(long, requires = "type")]
         thread_id: Option<String>,
-    },
-
-    /// Update an existing entry in the database
-    Update {
-        /// Entry ID to update
-        id: String,
-
-        /// Update title
-        #[arg(short, long)]
-        title: Option<String>,
-
-        /// Replace content inline (full replacement)
-        #[arg(long, conflicts_with_all = ["file", "append_content", "append_file", "prepend_content", "prepend_file", "find"])]
-        content: Option<String>,
-
-        /// Replace content from file (full replacement)
-        #[arg(short, long, visible_alias = "content-file", conflicts_with_all = ["content", "append_content", "append_file", "prepend_content", "prepend_file", "find"])]
-        file: Option<String>,
 
-        /// Append text to end of existing content
-        #[arg(long, conflicts_with_all = ["content", "file", "append_file", "prepend_content", "prepend_file", "find"])]
-        append_content: Option<String>,
-
-        /// Append content from file to end of existing content
-        #[arg(long, conflicts_with_all = ["content", "file", "append_content", "prepend_content", "prepend_file", "find"])]
-        append_file: Option<String>,
-
-        /// Prepend text to start of existing content
-        #[arg(long, conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_file", "find"])]
-        prepend_content: Option<String>,
-
-        /// Prepend content from file to start of existing content
-        #[arg(long, conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_content", "find"])]
-        prepend_file: Option<String>,
-
-        /// Find text in content (requires --replace)
-        #[arg(long, requires = "replace", conflicts_with_all = ["content", "file", "append_content", "append_file", "prepend_content", "prepend_file"])]
-        find: Option<String>,
-
-        /// Replace text found by --find
-        #[arg(long, requires = "find")]
-        replace: Option<String>,
-
-        /// Replace all occurrences (with --find/--replace)
-        #[arg(long, requires = "find")]
-        replace_all: bool,
-
-        /// Replace only the Nth occurrence (1-indexed, with --find/--replace)
-        #[arg(long, requires = "find", conflicts_with = "replace_all")]
-        nth: Option<usize>,
-
-        /// Update category
-        #[arg(long)]
-        category: Option<String>,
-
-        /// Update tags (comma-separated, replaces all)
-        #[arg(long, conflicts_with_all = ["add_tag", "remove_tag"])]
-        tags: Option<String>,
-
-        /// Add a single tag to existing tags
-        #[arg(long, conflicts_with = "tags")]
-        add_tag: Option<String>,
-
-        /// Remove a specific tag
-        #[arg(long, conflicts_with = "tags")]
-        remove_tag: Option<String>,
-
-        /// Update applicability (comma-separated, replaces all)
-        #[arg(short = 'a', long)]
-        applicability: Option<String>,
-
-        /// Update content type
-        #[arg(long)]
-        content_type: Option<String>,
-
-        /// Update resonance level (1-10, or higher for transcendent)
-        #[arg(long)]
-        resonance: Option<i32>,
-
-        /// Update resonance type (foundational, transformative, relational, operational, ephemeral, session)
-        #[arg(long)]
-        resonance_type: Option<String>,
-
-        /// Update anchors (comma-separated bloom IDs, replaces all)
-        #[arg(long, conflicts_with_all = ["add_anchor", "remove_anchor"])]
-        anchors: Option<String>,
-
-        /// Add a single anchor to existing anchors
-        #[arg(long, conflicts_with = "anchors")]
-        add_anchor: Option<String>,
-
-        /// Remove a specific anchor
-        #[arg(long, conflicts_with = "anchors")]
-        remove_anchor: Option<String>,
-
-        /// Update wake phrase for memory ritual verification
-        #[arg(long)]
-        wake_phrase: Option<String>,
-
-        /// Update multiple wake phrases (comma-separated, replaces all)
-        #[arg(long)]
-        wake_phrases: Option<String>,
-
-        /// Add a single wake phrase to existing phrases
-        #[arg(long, conflicts_with = "wake_phrases")]
-        add_wake_phrase: Option<String>,
-
-        /// Remove a specific wake phrase
-        #[arg(long, conflicts_with = "wake_phrases")]
-        remove_wake_phrase: Option<String>,
-
-        /// Update wake order (use '-' to clear)
-        #[arg(long)]
-        wake_order: Option<String>,
-
-        /// Mark as private (shorthand for --visibility private)
-        #[arg(long, conflicts_with = "visibility")]
-        private: bool,
-
-        /// Change visibility (public or private)
-        #[arg(long, conflicts_with = "private")]
-        visibility: Option<String>,
-
-        /// Update owner (only valid when visibility is private)
-        #[arg(long)]
-        owner: Option<String>,
+        /// Confidence, 0.0 (hunch) to 1.0 (battle-tested)
+        #[arg(long, value_parser = confidence::parse)]
+        confidence: Option<f64>,
 
-        /// Force dangerous visibility changes (e.g., making blooms public)
+        /// Write the entry even if it fails resonance validation or category rules
         #[arg(long)]
         force: bool,
 
-        /// Output as JSON
-        #[arg(long)]
-        json: bool,
+        /// Add every entry in a YAML/JSON manifest, all or none (see README)
+        #[arg(
+            long,
+            conflicts_with_all = ["category", "title", "content", "file", "type"]
+        )]
+        manifest: Option<String>,
+
+        /// Write a CATEGORY entry from its template: in $EDITOR, or check
+        /// --content/--file against it (see `mx memory template`)
+        #[arg(long, value_name = "CATEGORY", conflicts_with_all = ["category", "type", "manifest"])]
+        template: Option<String>,
     },
 
-    /// Edit content by finding and replacing text (shortcut for: update <id> --find ... --replace ...)
+    /// Update an existing entry in the database
+    Update(UpdateArgs),
+
+    /// Edit an entry in $EDITOR, or find and replace text in its content with --find/--replace
     Edit {
         /// Entry ID to edit
         id: String,
 
         /// Text to find in the content
-        #[arg(long, visible_alias = "old")]
-        find: String,
+        #[arg(long, visible_alias = "old", requires = "replace")]
+        find: Option<String>,
 
         /// Replacement text
-        #[arg(long, visible_alias = "new")]
-        replace: String,
+        #[arg(long, visible_alias = "new", requires = "find")]
+        replace: Option<String>,
 
         /// Replace all occurrences (default: error if multiple matches)
-        #[arg(long)]
+        #[arg(long, requires = "find")]
         replace_all: bool,
 
         /// Replace only the Nth occurrence (1-indexed)
-        #[arg(long, conflicts_with = "replace_all")]
+        #[arg(long, conflicts_with = "replace_all", requires = "find")]
         nth: Option<usize>,
 
         /// Output as JSON
@@ -887,6 +1613,21 @@ enum MemoryCommands {
         json: bool,
     },
 
+    /// Pin entries so they always lead your wake cascade
+    Star(star::StarArgs),
+
+    /// Remove entries from your pinned set
+    Unstar(star::StarArgs),
+
+    /// List your starred entries
+    Starred(star::StarredArgs),
+
+    /// List an entry's stored revisions, oldest first
+    History(revisions::HistoryArgs),
+
+    /// Restore an entry's title and body from a revision in its history
+    Rollback(revisions::RollbackArgs),
+
     /// Generate embedding for a knowledge entry
     Embed {
         /// Entry ID to embed (not used with --all)
@@ -928,15 +1669,99 @@ enum MemoryCommands {
 
     /// Export knowledge database
     Export {
-        /// Output format (md, jsonl, csv)
+        /// Output format (md, jsonl, csv, html)
         #[arg(short, long, default_value = "md")]
         format: String,
 
-        /// Output directory for md format (defaults to ./memory-export), or file for jsonl/csv (defaults to stdout)
+        /// Output directory for md format (defaults to ./memory-export) or html (./memory-site), or file for jsonl/csv (defaults to stdout)
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Export entries as they existed at a date (YYYY-MM-DD, yesterday, 7d, or RFC 3339)
+        #[arg(long)]
+        as_of: Option<String>,
+
+        /// Only entries in these categories (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        category: Option<Vec<String>>,
+
+        /// Only entries with any of these tags (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Option<Vec<String>>,
+
+        /// Only entries from this source project
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Only entries created on or after this date (YYYY-MM-DD, yesterday, 7d, RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only entries with this visibility (public or private); public
+        /// leaves out private entries even when MX_CURRENT_AGENT could see them
+        #[arg(long, value_parser = ["public", "private"])]
+        visibility: Option<String>,
+
+        /// Keep activation counts and last-activated times in a JSONL export
+        /// (left out by default: reading entries changes them)
+        #[arg(long)]
+        activity: bool,
+
+        /// Write a dated JSONL snapshot to $MX_HOME/backups/ (for `memory diff --since-backup`)
+        #[arg(long, conflicts_with_all = ["format", "output", "category", "tags", "project", "since", "visibility"])]
+        backup: bool,
+
+        /// Sync an existing md export directory: rewrite only changed entries
+        /// and delete files of removed ones
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "backup"])]
+        incremental: Option<std::path::PathBuf>,
     },
 
+    /// Compare two JSONL snapshots: added, removed, and changed entries with per-field diffs
+    Diff(snapshot::DiffOptions),
+
+    /// Apply the same changes to every entry matching a query
+    Bulk(bulk::BulkArgs),
+
+    /// Check stored entries against validation and category rules, and (--links) body references
+    Lint(lint::LintArgs),
+
+    /// Seal foundational blooms into the write-protected identity namespace
+    Identity(identity::IdentityArgs),
+
+    /// List, restore or purge soft-deleted entries
+    Trash(trash::TrashArgs),
+
+    /// Body templates for categories, used by `add --template`
+    Template(templates::TemplateArgs),
+
+    /// Promote an ephemeral or session entry to a durable bloom
+    Promote(promote::PromoteArgs),
+
+    /// Set or show an entry's resonance level, type, decay rate and anchors
+    Resonance(resonance::ResonanceArgs),
+
+    /// Lower the resonance of entries with a decay rate, optionally archiving faded ones
+    Decay(decay::DecayArgs),
+
+    /// Record that entries held up again, raising their confidence
+    Confirm(confidence::ConfirmArgs),
+
+    /// Review contradicting entries and settle them (supersede, merge, or note)
+    Contradictions(contradictions::ContradictionsArgs),
+
+    /// Find duplicate and near-duplicate entries, and merge them
+    Dedupe(dedupe::DedupeArgs),
+
+    /// Replicate the graph to a standby SurrealDB instance
+    Replicate(replicate::ReplicateArgs),
+
+    /// Read the change feed from a cursor (entries and relationships)
+    Changes(changes::ChangesArgs),
+
+    /// Resolve a conflict copy left by peer sync with a three-way field merge
+    MergeEntry(merge_entry::MergeOptions),
+
     /// Manage projects
     Projects {
         #[command(subcommand)]
@@ -961,7 +1786,7 @@ enum MemoryCommands {
         command: CategoriesCommands,
     },
 
-    /// Query tags used in memory entries
+    /// List, rename, merge and delete tags across entries
     Tags {
         #[command(subcommand)]
         command: TagsCommands,
@@ -991,6 +1816,15 @@ enum MemoryCommands {
         command: RelationshipTypesCommands,
     },
 
+    /// Link two entries (type defaults to related; see relationship-types list)
+    Relate(relate::RelateArgs),
+
+    /// Remove the links between two entries
+    Unrelate(relate::UnrelateArgs),
+
+    /// Sync `references` links with the entries each body mentions
+    Backlinks(backlinks::BacklinksArgs),
+
     /// Manage relationships between knowledge entries
     Relationships {
         #[command(subcommand)]
@@ -1017,18 +1851,36 @@ enum MemoryCommands {
         #[arg(short, long, default_value = "7")]
         days: i64,
 
-        /// Output as JSON
+        /// Wake as this agent (default: MX_CURRENT_AGENT)
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Output as JSON (same as --format json)
         #[arg(long)]
         json: bool,
 
+        /// Output format: text, prompt (token-budgeted block), md, json, stele
+        #[arg(long, value_enum, conflicts_with_all = &["json", "index", "ritual", "begin", "engage"])]
+        format: Option<wake_format::WakeFormat>,
+
+        /// Token budget (estimated): trims the cascade in priority order and
+        /// reports what each layer consumed
+        #[arg(long)]
+        max_tokens: Option<usize>,
+
         /// Output as bash ritual script (sequential reading)
         #[arg(long)]
         ritual: bool,
 
-        /// Output as compact markdown index (for identity loading)
-        #[arg(long, conflicts_with_all = &["json", "ritual", "begin", "engage"])]
+        /// Output as compact markdown index (same as --format md)
+        #[arg(long, visible_alias = "markdown", conflicts_with_all = &["json", "ritual", "begin", "engage"])]
         index: bool,
 
+        /// Output only the wake phrases, in cascade order, one per line (with
+        /// --json: an array of {id, phrase})
+        #[arg(long, conflicts_with_all = &["format", "ritual", "index", "begin", "engage"])]
+        phrases_only: bool,
+
         /// Don't update activation counts
         #[arg(long)]
         no_activate: bool,
@@ -1062,7 +1914,7 @@ enum MemoryCommands {
         session: Option<String>,
     },
 
-    /// List recent ephemeral facts with decay
+    /// List recent ephemeral facts with decay, or entries you viewed (--viewed)
     Recent {
         /// Number of days to look back
         #[arg(long, default_value = "10")]
@@ -1090,9 +1942,12 @@ enum MemoryCommands {
         #[arg(long, value_enum, default_value_t = RecentSortOrder::Chronological)]
         sort: RecentSortOrder,
 
-        /// Maximum number of results
-        #[arg(long, default_value = "100")]
-        limit: usize,
+        /// Maximum number of results [default: 100, or 20 with --viewed]
+        #[arg(long)]
+        limit: Option<usize>,
+
+        #[command(flatten)]
+        viewed: recent::ViewedArgs,
     },
 
     /// Fetch facts for the wake ritual (resonance >= 3, all types, sorted by resonance)
@@ -1155,14 +2010,45 @@ enum MemoryCommands {
         #[arg(long, default_value = "text", hide = true)]
         format: String,
     },
+
+    /// Analyze the relationship graph: hubs, bridges, and isolated clusters
+    AnalyzeGraph(graph::AnalyzeArgs),
+
+    /// Export the knowledge graph for Gephi, Neo4j, Graphviz, or a browser visualization
+    ExportGraph(graph::ExportArgs),
+
+    /// Walk the relationship graph from an entry (both directions)
+    Graph(graph::GraphArgs),
+
+    /// Serve an interactive graph visualization on localhost
+    Viz {
+        /// Port to listen on
+        #[arg(long, default_value = "8080")]
+        port: u16,
+
+        /// Include private entries owned by the current agent
+        #[arg(long)]
+        include_private: bool,
+    },
+
+    /// Spaced-repetition review of entries (SM-2, scheduled per agent)
+    Drill(drill::DrillArgs),
+
+    /// Review neglected entries: mark each still relevant, stale, or to merge
+    Review(review::ReviewArgs),
+
+    /// Browse entries in an interactive terminal UI
+    #[cfg(feature = "tui")]
+    Browse(browse::BrowseArgs),
 }
 
+#[cfg(feature = "github")]
 #[derive(Subcommand)]
 enum GithubCommands {
     /// Clean up GitHub issues and discussions
     Cleanup {
-        /// Repository (owner/repo format)
-        repo: String,
+        /// Repository (owner/repo format; defaults to the current git repo)
+        repo: Option<String>,
 
         /// Issue numbers to close (comma-separated)
         #[arg(long)]
@@ -1175,6 +2061,10 @@ enum GithubCommands {
         /// Dry run - show what would be done
         #[arg(long)]
         dry_run: bool,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 
     /// Post comments to issues or discussions
@@ -1184,18 +2074,15 @@ enum GithubCommands {
     },
 }
 
+#[cfg(feature = "github")]
 #[derive(Subcommand)]
 enum CommentCommands {
     /// Post comment to an issue
+    #[command(override_usage = "mx github comment issue [OPTIONS] [REPO] <NUMBER> <MESSAGE>")]
     Issue {
-        /// Repository (owner/repo format)
-        repo: String,
-
-        /// Issue number
-        number: u64,
-
-        /// Comment message
-        message: String,
+        /// Repository (owner/repo, optional: defaults to the current git repo), number, message
+        #[arg(num_args = 2..=3, required = true, value_names = ["REPO", "NUMBER", "MESSAGE"])]
+        args: Vec<String>,
 
         /// Identity signature (e.g., "smith", "neo")
         #[arg(long)]
@@ -1203,15 +2090,11 @@ enum CommentCommands {
     },
 
     /// Post comment to a discussion
+    #[command(override_usage = "mx github comment discussion [OPTIONS] [REPO] <NUMBER> <MESSAGE>")]
     Discussion {
-        /// Repository (owner/repo format)
-        repo: String,
-
-        /// Discussion number
-        number: u64,
-
-        /// Comment message
-        message: String,
+        /// Repository (owner/repo, optional: defaults to the current git repo), number, message
+        #[arg(num_args = 2..=3, required = true, value_names = ["REPO", "NUMBER", "MESSAGE"])]
+        args: Vec<String>,
 
         /// Identity signature (e.g., "smith", "neo")
         #[arg(long)]
@@ -1230,6 +2113,10 @@ enum SessionCommands {
         #[arg(short, long)]
         output: Option<String>,
     },
+
+    /// End-of-session ritual: archive to codex, queue promotion candidates,
+    /// record the final state, and close the open session record
+    CloseOut(closeout::CloseOutArgs),
 }
 
 #[derive(Subcommand)]
@@ -1287,6 +2174,10 @@ enum CodexCommands {
         /// Read the clean markdown transcript (conversation.md)
         #[arg(long, conflicts_with = "human")]
         clean: bool,
+
+        /// With --human, print message text as-is instead of rendering markdown
+        #[arg(long, requires = "human")]
+        raw: bool,
     },
 
     /// Search all archives for a pattern
@@ -1297,6 +2188,43 @@ enum CodexCommands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Stop after this many matching lines
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+
+    /// Print an archive's manifest, or selected fields of it
+    Manifest {
+        /// Archive ID (short UUID from list)
+        id: String,
+
+        /// Only this field (repeat for several; dotted paths reach inside)
+        #[arg(long = "field")]
+        fields: Vec<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List archives whose manifests match a query, e.g.
+    /// 'project_path~mx AND message_count>200'
+    QueryManifests {
+        /// Conditions (field =, !=, >, >=, <, <= or ~ value) joined by AND / OR
+        query: String,
+
+        /// Show only these fields (repeat for several)
+        #[arg(long = "field")]
+        fields: Vec<String>,
+
+        /// Include incremental saves, not just the latest of each session
+        #[arg(long)]
+        all: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Migrate v1 archives to v2 (extract images to files)
@@ -1317,6 +2245,18 @@ enum CodexCommands {
         #[arg(long, requires = "clean")]
         include_agents: bool,
     },
+
+    /// Bring every archive's manifest up to date: extract v1 inline images,
+    /// recompute counts and checksums, record agent spawn points
+    UpgradeManifests {
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Show detailed progress, including archives already up to date
+        #[arg(long)]
+        verbose: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -1382,6 +2322,23 @@ enum ProjectsCommands {
         #[arg(long)]
         description: Option<String>,
     },
+    /// Mark a project inactive, optionally archiving its entries
+    Close {
+        /// Project identifier
+        id: String,
+        /// Move the project's entries to $MX_HOME/archives/<id>.jsonl.gz,
+        /// leaving stubs that point at the archive
+        #[arg(long)]
+        archive: bool,
+        /// Count the entries that would be archived without changing anything
+        #[arg(long, requires = "archive")]
+        dry_run: bool,
+    },
+    /// Reactivate a closed project, restoring archived entries
+    Reopen {
+        /// Project identifier
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -1455,7 +2412,7 @@ enum CategoriesCommands {
 
 #[derive(Subcommand)]
 enum TagsCommands {
-    /// List all tags (optionally filter by category)
+    /// List all tags with how many entries use each (optionally filter by category)
     List {
         /// Filter to tags used in a specific category
         #[arg(long)]
@@ -1464,6 +2421,37 @@ enum TagsCommands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Include entry counts in JSON output (objects instead of names)
+        #[arg(long)]
+        counts: bool,
+    },
+
+    /// Rename a tag on every entry
+    Rename {
+        /// Current tag name
+        old: String,
+
+        /// New tag name (must not be in use; see merge)
+        new: String,
+    },
+
+    /// Fold tags into one, e.g. `merge Rust RUST --into rust`
+    Merge {
+        /// Tags to fold in
+        #[arg(required = true)]
+        sources: Vec<String>,
+
+        /// Tag they become (created if needed)
+        #[arg(long)]
+        into: String,
+    },
+
+    /// Remove tags from every entry
+    Delete {
+        /// Tags to remove
+        #[arg(required = true)]
+        names: Vec<String>,
     },
 }
 
@@ -1538,6 +2526,10 @@ enum RelationshipsCommands {
     Delete {
         /// Relationship ID
         id: String,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 }
 
@@ -1551,6 +2543,7 @@ enum ContentTypesCommands {
     },
 }
 
+#[cfg(feature = "github")]
 #[derive(Subcommand)]
 enum WikiCommands {
     /// Sync markdown files to GitHub wiki
@@ -1571,13 +2564,40 @@ enum WikiCommands {
     },
 }
 
-fn main() -> Result<()> {
-    paths::emit_mx_home_note();
+fn main() -> std::process::ExitCode {
+    let mut format = error::ErrorFormat::resolve(None);
+    let result = alias::expand(
+        std::env::args().collect(),
+        &Cli::command(),
+        &builtin_commands(),
+    )
+    .and_then(|args| {
+        let cli = Cli::parse_from(&args);
+        format = error::ErrorFormat::resolve(cli.error_format);
+        run(cli, &args)
+    });
+    match result {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => error::report(&e, format),
+    }
+}
 
-    let cli = Cli::parse();
+fn run(cli: Cli, args: &[String]) -> Result<()> {
+    // The profile must be fixed before any path is resolved.
+    paths::set_profile(cli.profile.as_deref())?;
+
+    let command_name = command_path(args);
+    output::init_color(cli.color);
+    dates::set_utc(cli.utc);
+    if output::should_page(&command_name, cli.no_pager) {
+        output::run_paged(args, cli.color)?;
+    }
+    paths::emit_mx_home_note();
+    let started = std::time::Instant::now();
 
-    match cli.command {
+    let result = match cli.command {
         Commands::Memory { command } => handle_memory(command, cli.verbose),
+        Commands::Init(opts) => init::run_init(opts, cli.verbose),
         Commands::Commit {
             message,
             all,
@@ -1606,17 +2626,131 @@ fn main() -> Result<()> {
             }
             Ok(())
         }
+        #[cfg(feature = "github")]
+        Commands::SelfUpdate {
+            channel,
+            check,
+            force,
+        } => self_update::self_update(channel, check, force),
         Commands::Pr { command } => handle_pr(command),
+        #[cfg(feature = "github")]
         Commands::Sync { command } => sync::handle_sync(command),
+        #[cfg(feature = "github")]
         Commands::Github { command } => handle_github(command),
+        #[cfg(feature = "github")]
         Commands::Wiki { command } => handle_wiki(command),
         Commands::Session { command } => handle_session(command),
         Commands::Codex { command } => handle_codex(command),
         Commands::Convert { command } => handle_convert(command),
         Commands::Heartbeat { since, reset } => handle_heartbeat(since, reset),
         Commands::Log { count, full, args } => handle_log(count, full, args),
+        #[cfg(feature = "state")]
         Commands::State { command } => handle_state(command),
+        Commands::History {
+            limit,
+            failed,
+            json,
+        } => usage::show_history(limit, failed, json),
+        Commands::Stats { command } => match command {
+            StatsCommands::Usage { days, json } => usage::show_usage(days, json),
+            StatsCommands::Slow { days, limit, json } => slow_log::show(days, limit, json),
+        },
+        Commands::Bench { command } => match command {
+            BenchCommands::Store {
+                entries,
+                queries,
+                network,
+                json,
+            } => bench::bench_store(
+                bench::BenchOptions {
+                    entries,
+                    queries,
+                    network,
+                    json,
+                },
+                cli.verbose,
+            ),
+        },
+        Commands::Cron { command } => match command {
+            CronCommands::Add {
+                name,
+                schedule,
+                preset,
+                repo,
+                args,
+            } => cron::add(name, schedule, preset, repo, args),
+            CronCommands::List { json } => cron::list(json),
+            CronCommands::Remove { name } => cron::remove(&name),
+            CronCommands::Install { dry_run } => cron::install(dry_run),
+        },
+        Commands::Capture(opts) => capture::capture(opts, cli.verbose),
+        Commands::Ingest { command } => ingest::handle_ingest(command, cli.verbose),
+        Commands::Notify { command } => match command {
+            NotifyCommands::Digest {
+                days,
+                min_resonance,
+                dry_run,
+                raw,
+            } => notify::digest(days, min_resonance, dry_run, raw),
+            NotifyCommands::Test { message } => notify::test(message),
+            NotifyCommands::Status => notify::status(),
+        },
+        Commands::Queue { command } => queue::handle_queue(command, cli.verbose),
+        Commands::Wake(args) => morning::run(args),
+        Commands::Doctor(args) => doctor::run(args, cli.verbose),
+        Commands::Context(args) => workspace::run(args),
+        Commands::Pack { command } => pack::run(command),
+        Commands::Tenant { command } => tenant::run(command),
+        Commands::Alias { command } => match command {
+            AliasCommands::List { json } => alias::list(json),
+            AliasCommands::Add { name, command } => {
+                alias::add(&name, &command, &builtin_commands())
+            }
+            AliasCommands::Remove { name } => alias::remove(&name),
+        },
+    };
+
+    let elapsed = started.elapsed();
+    usage::record(&command_name, elapsed, &result);
+    slow_log::record("command", || command_name.clone(), elapsed);
+    result
+}
+
+/// Top-level command names and their aliases, which user aliases may not shadow
+fn builtin_commands() -> Vec<String> {
+    Cli::command()
+        .get_subcommands()
+        .flat_map(|c| {
+            std::iter::once(c.get_name().to_string()).chain(c.get_all_aliases().map(String::from))
+        })
+        .chain(["help".to_string()])
+        .collect()
+}
+
+/// Resolve the subcommand path of an invocation (e.g. `memory search`),
+/// leaving out all arguments so nothing user-supplied ends up in the log.
+fn command_path(args: &[String]) -> String {
+    let mut cmd = Cli::command();
+    let mut path: Vec<String> = Vec::new();
+
+    for arg in args.iter().skip(1) {
+        if arg.starts_with('-') {
+            continue;
+        }
+        match cmd.find_subcommand(arg) {
+            Some(sub) => {
+                path.push(sub.get_name().to_string());
+                let sub = sub.clone();
+                cmd = sub;
+            }
+            // Before the first subcommand this is a global flag's value;
+            // after it, the first positional argument ends the path.
+            None if path.is_empty() => continue,
+            None => break,
+        }
     }
+
+    path.join(" ")
 }
 
 /// Heartbeat - calming co-regulation prompt
@@ -1651,7 +2785,7 @@ fn handle_heartbeat(since: Option<u64>, reset: bool) -> Result<()> {
         }
         Some(ms) => {
             // Calculate BPM: 60000ms / interval = beats per minute
-            let bpm = if ms > 0 { 60000 / ms } else { 999 };
+            let bpm = 60000u64.checked_div(ms).unwrap_or(999);
 
             let message = match bpm {
                 0..=59 => "Nice and slow. You're safe.",
@@ -1669,6 +2803,7 @@ fn handle_heartbeat(since: Option<u64>, reset: bool) -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "state")]
 /// Handle emotional state tensor commands
 fn handle_state(cmd: StateCommands) -> Result<()> {
     use std::io::{self, Read as IoRead};
@@ -1787,6 +2922,13 @@ fn handle_state(cmd: StateCommands) -> Result<()> {
                 Some(s) => load_tensor_schema(Some(s))?,
                 None => tensor::TensorSchema::load_by_id(&tensor.schema_id)?,
             };
+            if schema.id != tensor.schema_id {
+                bail!(MxError::SchemaMismatch {
+                    what: "Tensor".into(),
+                    found: tensor.schema_id,
+                    expected: schema.id,
+                });
+            }
 
             match format.as_str() {
                 "json" => println!("{}", serde_json::to_string_pretty(&tensor)?),
@@ -2124,16 +3266,10 @@ fn route_fact_type(fact_type: &str) -> Result<FactRouting> {
 
 /// Truncate a string to a maximum number of characters, adding "..." if truncated
 ///
-/// This is UTF-8 safe - it counts characters, not bytes, avoiding panics on
-/// multi-byte characters like emoji.
+/// Counts grapheme clusters (see [`text`]), so neither a multi-byte character
+/// nor an emoji sequence is ever cut in half.
 fn safe_truncate(s: &str, max_chars: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count > max_chars {
-        let truncated: String = s.chars().take(max_chars.saturating_sub(3)).collect();
-        format!("{}...", truncated)
-    } else {
-        s.to_string()
-    }
+    text::ellipsize_with(s, max_chars, "...")
 }
 
 /// Resolve agent context from environment and flags
@@ -2327,6 +3463,11 @@ fn auto_anchor(
     Ok(())
 }
 
+/// Sync an entry's `references` links with its body after add/update
+fn auto_backlink(entry_id: &str, db: &dyn store::KnowledgeStore) -> Result<()> {
+    backlinks::sync_entry(db, &resolve_agent_context(false, true), entry_id)
+}
+
 fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
     let config = IndexConfig::default();
 
@@ -2335,22 +3476,27 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             println!("Rebuilding Memory index...");
             let stats = rebuild_index(&config)?;
             println!("{}", stats);
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            backlinks::after_bulk(db.as_ref(), &resolve_agent_context(false, true))?;
         }
 
         MemoryCommands::Search {
             query,
             filter,
             semantic,
+            as_of,
+            fields,
+            explain,
+            recency,
         } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
             let ctx = resolve_agent_context(filter.mine, filter.include_private);
+            let as_of = as_of.as_deref().map(revisions::parse_as_of).transpose()?;
 
             // Note: Search doesn't activate facts - discovery != engagement
-            // Build filter for database query (resonance and category)
             let db_filter = store::KnowledgeFilter {
-                min_resonance: filter.min_resonance,
-                max_resonance: filter.max_resonance,
-                categories: filter.category.clone(),
+                search_fields: fields.selected(),
+                ..filter.store_filter(filter.category.clone())?
             };
 
             // Get results from database with resonance filtering
@@ -2361,27 +3507,52 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 let mut provider = FastEmbedProvider::new()?;
                 let query_embedding = provider.embed(&query)?;
 
-                // When --tags is present the in-memory filter will thin the DB results,
-                // so we over-fetch to ensure enough candidates survive the tag filter.
+                // When in-memory filters will thin the DB results, over-fetch to
+                // ensure enough candidates survive them.
                 // Tradeoff: 5x multiplier works well at typical limits (10-50) but does
                 // not scale for very large limits. The cap (limit + 200) prevents runaway
                 // fetches when the caller requests hundreds of entries.
                 let requested_limit = filter.limit.unwrap_or(20);
-                let db_limit = if filter.tags.is_some() {
+                let db_limit = if filter.thins_results() {
                     (requested_limit * 5).min(requested_limit + 200)
                 } else {
                     requested_limit
                 };
 
                 db.semantic_search(&query_embedding, &ctx, &db_filter, db_limit)?
+            } else if let Some(at) = as_of {
+                // The full-text index only covers current content, so match
+                // against each entry's reconstructed historical version instead
+                let categories = match db_filter.categories {
+                    Some(ref cats) => cats.clone(),
+                    None => db.list_categories()?.into_iter().map(|c| c.id).collect(),
+                };
+                let mut current = Vec::new();
+                for cat in &categories {
+                    current.extend(db.list_by_category(cat, &ctx, &db_filter)?);
+                }
+                revisions::entries_as_of(db.as_ref(), current, at)?
+                    .into_iter()
+                    .filter(|e| revisions::matches_query(e, &query))
+                    .collect()
             } else {
-                db.search(&query, &ctx, &db_filter)?
+                let settings = confidence::Settings::load()?;
+                keyword_search(
+                    db.as_ref(),
+                    &query,
+                    &ctx,
+                    &db_filter,
+                    &settings,
+                    recency.boost_recent,
+                )?
             };
 
             // Apply in-memory field presence filters
-            let entries = apply_entry_filters(entries, &filter);
+            let entries = apply_entry_filters(entries, &filter)?;
 
-            if filter.json {
+            if explain.explain {
+                explain::report(db.as_ref(), &query, &ctx, &db_filter, &entries, filter.json)?;
+            } else if filter.json {
                 println!("{}", serde_json::to_string_pretty(&entries)?);
             } else if entries.is_empty() {
                 println!("No results for '{}'", query);
@@ -2413,12 +3584,8 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 }
             }
 
-            // Build filter for database query (resonance only - category handled below)
-            let db_filter = store::KnowledgeFilter {
-                min_resonance: filter.min_resonance,
-                max_resonance: filter.max_resonance,
-                categories: None,
-            };
+            // Build filter for database query (category handled below)
+            let db_filter = filter.store_filter(None)?;
 
             // Get results from database with resonance filtering
             let entries = if let Some(ref cats) = filter.category {
@@ -2438,7 +3605,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             };
 
             // Apply in-memory field presence filters
-            let entries = apply_entry_filters(entries, &filter);
+            let entries = apply_entry_filters(entries, &filter)?;
 
             if filter.json {
                 println!("{}", serde_json::to_string_pretty(&entries)?);
@@ -2446,9 +3613,17 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 println!("No entries found");
             } else {
                 println!("Found {} entries:\n", entries.len());
-                for entry in entries {
-                    print_entry_summary(&entry);
+                let mut table = output::Table::new(["ID", "CATEGORY", "RES", "TITLE", "TAGS"]);
+                for entry in &entries {
+                    table.row([
+                        entry.id.clone(),
+                        entry.category_id.clone(),
+                        entry.resonance.to_string(),
+                        safe_truncate(&entry.title, 60),
+                        entry.tags.join(", "),
+                    ]);
                 }
+                table.print();
             }
         }
 
@@ -2456,6 +3631,8 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             id,
             json,
             content_only,
+            as_of,
+            raw,
         } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
             let id = normalize_id(&id);
@@ -2467,13 +3644,35 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 _ => store::AgentContext::public_only(),
             };
 
+            let as_of = as_of.as_deref().map(revisions::parse_as_of).transpose()?;
+
             match db.get(&id, &ctx)? {
                 Some(entry) => {
-                    // Activate fact when viewing details
-                    if entry.id.starts_with("kn-")
-                        && let Err(e) = db.update_activations(std::slice::from_ref(&entry.id))
-                    {
-                        eprintln!("Warning: failed to update activation: {}", e);
+                    // Reading an old revision looks at history rather than
+                    // using the entry, so it neither activates nor counts as
+                    // a recent view
+                    let current = as_of.is_none();
+                    let entry = match as_of {
+                        Some(at) => {
+                            revisions::entry_as_of(db.as_ref(), &entry, at)?.ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "Entry '{}' did not exist at {}",
+                                    id,
+                                    at.to_rfc3339()
+                                )
+                            })?
+                        }
+                        None => entry,
+                    };
+
+                    if current {
+                        // Activate fact when viewing details
+                        if entry.id.starts_with("kn-")
+                            && let Err(e) = db.update_activations(std::slice::from_ref(&entry.id))
+                        {
+                            eprintln!("Warning: failed to update activation: {}", e);
+                        }
+                        recent::record(db.as_ref(), &entry.id);
                     }
 
                     if content_only {
@@ -2483,16 +3682,22 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     } else if json {
                         println!("{}", serde_json::to_string_pretty(&entry)?);
                     } else {
-                        print_entry_full(&entry);
+                        let hood = neighbors::Neighborhood::load(db.as_ref(), &ctx, &entry)?;
+                        print_entry_full(&entry, &hood, raw);
                     }
                 }
                 None => {
-                    bail!("Entry '{}' not found", id);
+                    bail!(MxError::not_found("Entry", id));
                 }
             }
         }
 
-        MemoryCommands::Stats { json } => {
+        MemoryCommands::Attach(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            attach::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Stats { json, deep, top } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
 
             // For stats, show counts for current agent's perspective
@@ -2505,31 +3710,63 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             let categories = db.list_categories()?;
             let filter = store::KnowledgeFilter::default();
 
+            // Entry count and estimated tokens per category
+            let mut per_category = Vec::new();
+            for cat in categories {
+                let entries = db.list_by_category(&cat.id, &ctx, &filter)?;
+                let token_sum: usize = entries.iter().map(tokens::entry_tokens).sum();
+                per_category.push((cat.id, entries.len(), token_sum));
+            }
+            let total_tokens: usize = per_category.iter().map(|(_, _, t)| t).sum();
+            let deep = if deep {
+                Some(deep_stats::compute(
+                    &db.list_all(&ctx)?,
+                    &db.list_all_relationships()?,
+                ))
+            } else {
+                None
+            };
+
             if json {
                 let mut cat_counts = serde_json::Map::new();
-                for cat in categories {
-                    let count = db.list_by_category(&cat.id, &ctx, &filter)?.len();
-                    cat_counts.insert(cat.id, serde_json::Value::Number(count.into()));
+                let mut cat_tokens = serde_json::Map::new();
+                for (id, count, token_sum) in &per_category {
+                    cat_counts.insert(id.clone(), serde_json::Value::Number((*count).into()));
+                    cat_tokens.insert(id.clone(), serde_json::Value::Number((*token_sum).into()));
                 }
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&serde_json::json!({
-                        "total": total,
-                        "categories": cat_counts,
-                    }))?
-                );
+                let mut report = serde_json::json!({
+                    "total": total,
+                    "categories": cat_counts,
+                    "tokens": {
+                        "total": total_tokens,
+                        "categories": cat_tokens,
+                    },
+                });
+                if let Some(deep) = &deep {
+                    report["deep"] = serde_json::to_value(deep)?;
+                }
+                println!("{}", serde_json::to_string_pretty(&report)?);
             } else {
                 println!("Memory Index Statistics\n");
                 println!("Total entries: {}", total);
+                println!("Estimated tokens: {}", total_tokens);
                 println!();
-                for cat in categories {
-                    let count = db.list_by_category(&cat.id, &ctx, &filter)?.len();
-                    println!("  {:12} {}", cat.id, count);
+                for (id, count, token_sum) in &per_category {
+                    println!("  {:12} {:>5}  {:>8} tokens", id, count, token_sum);
+                }
+                if let Some(deep) = &deep {
+                    deep_stats::print(deep, top);
                 }
             }
         }
 
-        MemoryCommands::Delete { id, json } => {
+        MemoryCommands::Delete {
+            id,
+            json,
+            yes,
+            soft,
+            hard,
+        } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
             let id = normalize_id(&id);
 
@@ -2542,13 +3779,46 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 None => store::AgentContext::public_only(),
             };
 
-            // Backup before delete (Issue #206)
-            if let Some(entry) = db.get(&id, &ctx)? {
-                let _ = db
-                    .backup_content(&entry, "delete", current_agent.as_deref())
-                    .map_err(|e| eprintln!("Warning: failed to create backup: {}", e));
+            // The trash can be undone, so it needs no confirmation
+            if soft || (!hard && trash::soft_by_default()?) {
+                trash::archive(db.as_ref(), &ctx, &id)?;
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&serde_json::json!({
+                            "deleted": true,
+                            "trashed": true,
+                            "id": id,
+                        }))?
+                    );
+                } else {
+                    println!(
+                        "Moved entry '{}' to the trash (mx memory trash restore {})",
+                        id, id
+                    );
+                }
+                return Ok(());
+            }
+
+            let entry = db
+                .get(&id, &ctx)?
+                .ok_or_else(|| MxError::not_found("Entry", &id))?;
+            let relationships = db.list_relationships_for_entry(&id)?.len();
+            let affected = [
+                (1, "entry", "entries"),
+                (relationships, "relationship", "relationships"),
+            ];
+            let preview = [format!("{}  {}", id, entry.title)];
+            if !confirm::confirm("Delete", &affected, &preview, yes)? {
+                println!("Cancelled");
+                return Ok(());
             }
 
+            // Backup before delete (Issue #206)
+            let _ = db
+                .backup_content(&entry, "delete", current_agent.as_deref())
+                .map_err(|e| eprintln!("Warning: failed to create backup: {}", e));
+
             if db.delete(&id, &ctx)? {
                 if json {
                     println!(
@@ -2562,18 +3832,23 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     println!("Deleted entry '{}'", id);
                 }
             } else {
-                bail!("Entry '{}' not found", id);
+                bail!(MxError::not_found("Entry", id));
             }
         }
 
-        MemoryCommands::Import { path } => {
+        MemoryCommands::Import { path, force } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
             let import_path = path
                 .map(std::path::PathBuf::from)
                 .unwrap_or_else(|| config.jsonl_path.clone());
 
-            let count = import_jsonl(db.as_ref(), &import_path)?;
-            println!("Imported {} entries from {:?}", count, import_path);
+            let stats = if import_path.is_dir() {
+                import_markdown(db.as_ref(), &import_path, force)?
+            } else {
+                import_jsonl(db.as_ref(), &import_path, force)?
+            };
+            println!("Imported {} from {:?}", stats, import_path);
+            backlinks::after_bulk(db.as_ref(), &resolve_agent_context(false, true))?;
         }
 
         MemoryCommands::Add {
@@ -2604,12 +3879,60 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             r#type,
             session,
             thread_id,
+            confidence,
+            force,
+            manifest,
+            template,
         } => {
             use anyhow::Context;
             use std::fs;
 
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
 
+            if let Some(manifest) = manifest {
+                let agent_id = match source_agent.filter(|sa| !sa.is_empty()) {
+                    Some(sa) => sa,
+                    None => std::env::var("MX_CURRENT_AGENT")
+                        .ok()
+                        .filter(|a| !a.is_empty())
+                        .context("--source-agent not provided and MX_CURRENT_AGENT not set")?,
+                };
+                let ctx = store::AgentContext::for_agent(&agent_id);
+                let path = std::path::Path::new(&manifest);
+                for id in batch_add::run(db.as_ref(), &ctx, path, &agent_id, force, json)? {
+                    auto_embed(&id, db.as_ref())?;
+                    auto_anchor(&id, db.as_ref(), None)?;
+                    auto_backlink(&id, db.as_ref())?;
+                }
+                return Ok(());
+            }
+
+            // Inside a registered project's repo, entries default to that project
+            let project = workspace::project_or_current(db.as_ref(), project)?;
+
+            // A template fills in the title and body, in the editor if not given
+            let (category, title, content, tags) = match template {
+                Some(category) => {
+                    let body = match file {
+                        Some(ref file_path) => Some(
+                            fs::read_to_string(file_path)
+                                .with_context(|| format!("Failed to read file: {}", file_path))?,
+                        ),
+                        None => content,
+                    };
+                    let filled = templates::fill(&category, title, body, force)?;
+                    let tags = match tags {
+                        Some(t) if !filled.tags.is_empty() => {
+                            Some(format!("{},{}", t, filled.tags.join(",")))
+                        }
+                        Some(t) => Some(t),
+                        None => Some(filled.tags.join(",")),
+                    };
+                    (Some(category), Some(filled.title), Some(filled.body), tags)
+                }
+                None => (category, title, content, tags),
+            };
+
             // Get content from either --content or --file
             let body = if let Some(text) = content {
                 text
@@ -2676,11 +3999,11 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                         )? {
                             println!("Closed thread: {}", tid);
                         } else {
-                            bail!("Entry '{}' not found", tid);
+                            bail!(MxError::not_found("Entry", tid));
                         }
                         return Ok(());
                     } else {
-                        bail!("Thread not found: {}", tid);
+                        bail!(MxError::not_found("Thread", tid));
                     }
                 }
 
@@ -2732,6 +4055,8 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     );
                 }
 
+                let confirmed_at = confidence.map(|_| now.clone());
+
                 // Build the knowledge entry
                 let entry = knowledge::KnowledgeEntry {
                     id: id.clone(),
@@ -2768,9 +4093,18 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     embedded_at: None,
                     format: "markdown".to_string(),
                     effective_resonance: None,
+                    token_count: None,
+                    namespace: None,
+                    confidence,
+                    confirmed_at,
+                    archived_at: None,
+                    attachments: Vec::new(),
                 };
 
                 // Insert the fact
+                if !force {
+                    check_entry(&entry, &category_rules::Rules::load()?)?;
+                }
                 db.upsert_knowledge(&entry)?;
 
                 // Create EXTRACTED_FROM relationship to session if provided
@@ -2878,31 +4212,13 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 owner
             };
 
-            // Validate resonance_type if provided
-            if let Some(ref rtype) = resonance_type {
-                let valid_types = [
-                    "foundational",
-                    "transformative",
-                    "relational",
-                    "operational",
-                    "ephemeral",
-                    "session",
-                ];
-                if !valid_types.contains(&rtype.as_str()) {
-                    bail!(
-                        "Invalid resonance type '{}'. Valid types: {}",
-                        rtype,
-                        valid_types.join(", ")
-                    );
-                }
-            }
-
             // Generate ID
             let path_hint = domain.unwrap_or_else(|| category.clone());
             let id = knowledge::KnowledgeEntry::generate_id(&path_hint, &title);
 
             // Create entry
             let now = chrono::Utc::now().to_rfc3339();
+            let confirmed_at = confidence.map(|_| now.clone());
             let entry = knowledge::KnowledgeEntry {
                 id: id.clone(),
                 category_id: category.clone(),
@@ -2938,16 +4254,27 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 embedded_at: None,
                 format: "markdown".to_string(),
                 effective_resonance: None,
+                token_count: None,
+                namespace: None,
+                confidence,
+                confirmed_at,
+                archived_at: None,
+                attachments: Vec::new(),
             };
 
             // Insert into database (applicability already set in struct)
+            if !force {
+                check_entry(&entry, &category_rules::Rules::load()?)?;
+            }
             db.upsert_knowledge(&entry)?;
+            notify::entry_added(&entry);
 
             // Auto-generate embedding if in network SurrealDB mode
             auto_embed(&id, db.as_ref())?;
 
             // Auto-generate anchors if in network SurrealDB mode
             auto_anchor(&id, db.as_ref(), None)?;
+            auto_backlink(&id, db.as_ref())?;
 
             if json {
                 println!(
@@ -2996,7 +4323,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             }
         }
 
-        MemoryCommands::Update {
+        MemoryCommands::Update(UpdateArgs {
             id,
             title,
             content,
@@ -3028,9 +4355,10 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             private,
             visibility,
             owner,
+            confidence,
             force,
             json,
-        } => {
+        }) => {
             use anyhow::Context;
             use std::fs;
 
@@ -3050,7 +4378,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             // Fetch existing entry
             let mut entry = db
                 .get(&id, &ctx)?
-                .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+                .ok_or_else(|| MxError::not_found("Entry", &id))?;
 
             // Resolve --private as sugar for --visibility private
             let visibility = if private && visibility.is_none() {
@@ -3182,23 +4510,18 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 entry.resonance = new_resonance;
             }
 
+            // Update confidence if provided (a fresh rating restarts decay)
+            if let Some(new_confidence) = confidence {
+                changes.push(format!(
+                    "confidence: {:?} -> {}",
+                    entry.confidence, new_confidence
+                ));
+                entry.confidence = Some(new_confidence);
+                entry.confirmed_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+
             // Update resonance type if provided
             if let Some(ref new_type) = resonance_type {
-                let valid_types = [
-                    "foundational",
-                    "transformative",
-                    "relational",
-                    "operational",
-                    "ephemeral",
-                    "session",
-                ];
-                if !valid_types.contains(&new_type.as_str()) {
-                    bail!(
-                        "Invalid resonance type '{}'. Valid types: {}",
-                        new_type,
-                        valid_types.join(", ")
-                    );
-                }
                 changes.push(format!(
                     "resonance_type: {:?} -> {}",
                     entry.resonance_type, new_type
@@ -3400,9 +4723,6 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 }
             }
 
-            // Upsert entry (now includes updated tags)
-            db.upsert_knowledge(&entry)?;
-
             // Update applicability if provided
             if let Some(applicability_str) = applicability {
                 let applicability_list: Vec<String> = applicability_str
@@ -3412,7 +4732,6 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     .collect();
                 changes.push(format!("applicability: {}", applicability_list.join(", ")));
                 entry.applicability = applicability_list;
-                db.upsert_knowledge(&entry)?;
             }
 
             // Update content type if provided
@@ -3423,10 +4742,14 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     new_content_type
                 ));
                 entry.content_type_id = Some(new_content_type);
-                // Re-upsert to update content_type_id
-                db.upsert_knowledge(&entry)?;
             }
 
+            // Upsert the fully updated entry, held to the same rules as `add`
+            if !force {
+                check_entry(&entry, &category_rules::Rules::load()?)?;
+            }
+            db.upsert_knowledge(&entry)?;
+
             // Auto-generate embedding if in network SurrealDB mode
             auto_embed(&id, db.as_ref())?;
 
@@ -3440,6 +4763,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 Some(explicitly_removed_anchors.as_slice())
             };
             auto_anchor(&id, db.as_ref(), removed)?;
+            auto_backlink(&id, db.as_ref())?;
 
             if json {
                 println!(
@@ -3481,6 +4805,15 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 None => store::AgentContext::public_only(),
             };
 
+            let (Some(find), Some(replace)) = (find, replace) else {
+                if editor::run(db.as_ref(), &ctx, current_agent.as_deref(), &id, json)?.is_some() {
+                    auto_embed(&id, db.as_ref())?;
+                    auto_anchor(&id, db.as_ref(), None)?;
+                    auto_backlink(&id, db.as_ref())?;
+                }
+                return Ok(());
+            };
+
             // Backup before edit (Issue #206)
             if let Some(entry) = db.get(&id, &ctx)? {
                 let _ = db
@@ -3495,6 +4828,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
             // Auto-generate anchors if in network SurrealDB mode
             auto_anchor(&id, db.as_ref(), None)?;
+            auto_backlink(&id, db.as_ref())?;
 
             if json {
                 println!(
@@ -3566,6 +4900,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
             // Auto-generate anchors if in network SurrealDB mode
             auto_anchor(&id, db.as_ref(), None)?;
+            auto_backlink(&id, db.as_ref())?;
 
             if json {
                 println!(
@@ -3633,6 +4968,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
             // Auto-generate anchors if in network SurrealDB mode
             auto_anchor(&id, db.as_ref(), None)?;
+            auto_backlink(&id, db.as_ref())?;
 
             if json {
                 println!(
@@ -3733,6 +5069,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 // #3: update embeddings and anchors like all other mutation paths
                 auto_embed(&id, db.as_ref())?;
                 auto_anchor(&id, db.as_ref(), None)?;
+                auto_backlink(&id, db.as_ref())?;
 
                 if json {
                     println!(
@@ -3757,6 +5094,47 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             }
         }
 
+        MemoryCommands::Star(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, true);
+            star::star(db.as_ref(), &ctx, &args)?;
+        }
+
+        MemoryCommands::Unstar(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            star::unstar(db.as_ref(), &args)?;
+        }
+
+        MemoryCommands::Starred(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, true);
+            star::list(db.as_ref(), &ctx, &args)?;
+        }
+
+        MemoryCommands::History(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = match std::env::var("MX_CURRENT_AGENT") {
+                Ok(agent) if !agent.is_empty() => store::AgentContext::for_agent(agent),
+                _ => store::AgentContext::public_only(),
+            };
+            revisions::history(db.as_ref(), &ctx, &args)?;
+        }
+
+        MemoryCommands::Rollback(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let current_agent = std::env::var("MX_CURRENT_AGENT")
+                .ok()
+                .filter(|s| !s.is_empty());
+            let ctx = match &current_agent {
+                Some(agent) => store::AgentContext::for_agent(agent),
+                None => store::AgentContext::public_only(),
+            };
+            let entry = revisions::rollback(db.as_ref(), &ctx, current_agent.as_deref(), &args)?;
+            auto_embed(&entry.id, db.as_ref())?;
+            auto_anchor(&entry.id, db.as_ref(), None)?;
+            auto_backlink(&entry.id, db.as_ref())?;
+        }
+
         MemoryCommands::Embed { id, all } => {
             use crate::embeddings::{EmbeddingProvider, FastEmbedProvider};
 
@@ -3779,14 +5157,29 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
                 println!("Found {} entries to embed", total);
 
+                // Entries embedded before a Ctrl-C are skipped on the next run
+                progress::catch_interrupts();
+                let mut checkpoint = progress::Checkpoint::load("embed-all")?;
+                let progress = progress::Progress::new(total, "Embedding");
+                let mut resumed = 0;
                 for (idx, mut entry) in entries.into_iter().enumerate() {
+                    if progress::interrupted() {
+                        checkpoint.save()?;
+                        return Err(progress::stopped("memory embed --all", idx, total));
+                    }
+                    progress.inc();
+                    if checkpoint.contains(&entry.id) {
+                        resumed += 1;
+                        continue;
+                    }
+
                     // Construct embedding text from title + summary/body + tags
                     let mut parts = vec![entry.title.clone()];
 
                     if let Some(summary) = &entry.summary {
                         parts.push(summary.clone());
                     } else if let Some(body) = &entry.body {
-                        parts.push(body.chars().take(2000).collect());
+                        parts.push(text::truncate(body, 2000).to_string());
                     }
 
                     if !entry.tags.is_empty() {
@@ -3796,7 +5189,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     let embedding_text = parts.join("\n\n");
 
                     // Generate embedding
-                    println!("Embedded {}/{}: {}", idx + 1, total, entry.title);
+                    progress.println(format!("Embedded {}/{}: {}", idx + 1, total, entry.title));
                     let embedding = provider.embed(&embedding_text)?;
 
                     // Update entry with embedding
@@ -3807,9 +5200,15 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
                     // Save to database
                     db.upsert_knowledge(&entry)?;
+                    checkpoint.record(entry.id);
                 }
+                progress.finish();
+                checkpoint.clear()?;
 
                 println!("✓ All {} entries embedded successfully!", total);
+                if resumed > 0 {
+                    println!("  {} embedded before the interruption", resumed);
+                }
                 println!("  Model: {}", provider.model_id());
                 println!("  Dimensions: {}", provider.dimensions());
             } else {
@@ -3821,7 +5220,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 // Fetch entry
                 let mut entry = db
                     .get(&entry_id, &ctx)?
-                    .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", entry_id))?;
+                    .ok_or_else(|| MxError::not_found("Entry", &entry_id))?;
 
                 // Construct embedding text from title + summary/body + tags
                 let mut parts = vec![entry.title.clone()];
@@ -3829,7 +5228,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 if let Some(summary) = &entry.summary {
                     parts.push(summary.clone());
                 } else if let Some(body) = &entry.body {
-                    parts.push(body.chars().take(2000).collect());
+                    parts.push(text::truncate(body, 2000).to_string());
                 }
 
                 if !entry.tags.is_empty() {
@@ -3878,7 +5277,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                 // Process single entry
                 let entry = db
                     .get(&entry_id, &ctx)?
-                    .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", entry_id))?;
+                    .ok_or_else(|| MxError::not_found("Entry", &entry_id))?;
 
                 if entry.embedding.is_none() {
                     anyhow::bail!(
@@ -4042,16 +5441,72 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
 
         MemoryCommands::SessionTypes { command } => handle_session_types(command, &config)?,
 
-        MemoryCommands::RelationshipTypes { command } => {
-            handle_relationship_types(command, &config)?
+        MemoryCommands::RelationshipTypes { command } => {
+            handle_relationship_types(command, &config)?
+        }
+
+        MemoryCommands::Relate(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            relate::relate(db.as_ref(), &resolve_agent_context(false, true), &args)?;
+        }
+
+        MemoryCommands::Unrelate(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            relate::unrelate(db.as_ref(), &resolve_agent_context(false, true), &args)?;
+        }
+
+        MemoryCommands::Backlinks(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            backlinks::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
         }
 
         MemoryCommands::Relationships { command } => handle_relationships(command, &config)?,
 
         MemoryCommands::ContentTypes { command } => handle_content_types(command, &config)?,
 
-        MemoryCommands::Export { format, output } => {
+        MemoryCommands::Export {
+            format,
+            output,
+            as_of,
+            category,
+            tags,
+            project,
+            since,
+            visibility,
+            activity,
+            backup,
+            incremental,
+        } => {
             let db = store::create_store(&config.db_path)?;
+            let filter = index::ExportFilter {
+                as_of: as_of.as_deref().map(revisions::parse_as_of).transpose()?,
+                query: store::KnowledgeFilter {
+                    categories: category,
+                    tags,
+                    project,
+                    since: since.as_deref().map(dates::parse_date).transpose()?,
+                    ..Default::default()
+                },
+                visibility,
+                activity,
+            };
+
+            if backup {
+                let path = snapshot::backup_path();
+                std::fs::create_dir_all(paths::backups_dir())?;
+                export_jsonl(db.as_ref(), &path, &filter)?;
+                println!("Backup written to {}", path.display());
+                return Ok(());
+            }
+
+            if let Some(dir) = incremental {
+                if !matches!(format.as_str(), "md" | "markdown") {
+                    bail!("--incremental only works with --format md");
+                }
+                let stats = index::sync_markdown(db.as_ref(), &dir, &filter)?;
+                println!("Synced {}: {}", dir.display(), stats);
+                return Ok(());
+            }
 
             match format.as_str() {
                 "md" | "markdown" => {
@@ -4059,40 +5514,142 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     let output_dir = output.as_deref().unwrap_or("./memory-export");
 
                     let dir_path = std::path::PathBuf::from(output_dir);
-                    export_markdown(db.as_ref(), &dir_path)?;
+                    export_markdown(db.as_ref(), &dir_path, &filter)?;
                     println!("Exported to directory: {}", output_dir);
                 }
                 "jsonl" => {
                     // JSONL exports to file or stdout
                     if let Some(ref path) = output {
-                        export_jsonl(db.as_ref(), &std::path::PathBuf::from(path))?;
+                        export_jsonl(db.as_ref(), &std::path::PathBuf::from(path), &filter)?;
                         println!("Exported to {}", path);
                     } else {
-                        export_jsonl(db.as_ref(), &std::path::PathBuf::from("/dev/stdout"))?;
+                        export_jsonl(
+                            db.as_ref(),
+                            &std::path::PathBuf::from("/dev/stdout"),
+                            &filter,
+                        )?;
                     }
                 }
+                "html" => {
+                    // Static site exports to directory
+                    let output_dir = output.as_deref().unwrap_or("./memory-site");
+                    let files =
+                        site::export_html(db.as_ref(), std::path::Path::new(output_dir), &filter)?;
+                    println!(
+                        "Exported {} files to {}/ (open index.html)",
+                        files, output_dir
+                    );
+                }
                 "csv" => {
                     // CSV exports to file or stdout
                     if let Some(ref path) = output {
-                        export_csv(db.as_ref(), &std::path::PathBuf::from(path))?;
+                        export_csv(db.as_ref(), &std::path::PathBuf::from(path), &filter)?;
                         println!("Exported to {}", path);
                     } else {
-                        export_csv(db.as_ref(), &std::path::PathBuf::from("/dev/stdout"))?;
+                        export_csv(
+                            db.as_ref(),
+                            &std::path::PathBuf::from("/dev/stdout"),
+                            &filter,
+                        )?;
                     }
                 }
                 _ => {
-                    bail!("Invalid format '{}'. Valid formats: md, jsonl, csv", format);
+                    bail!(
+                        "Invalid format '{}'. Valid formats: md, jsonl, csv, html",
+                        format
+                    );
                 }
             }
         }
 
+        MemoryCommands::Diff(opts) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            snapshot::run(db.as_ref(), opts)?;
+        }
+
+        MemoryCommands::Lint(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            lint::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Identity(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            identity::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Trash(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            trash::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Template(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            templates::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Promote(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            promote::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Resonance(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            resonance::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Decay(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            decay::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Confirm(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            confidence::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Contradictions(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            contradictions::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Dedupe(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            dedupe::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Replicate(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            replicate::run(db.as_ref(), args)?;
+        }
+
+        MemoryCommands::Changes(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            changes::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::Bulk(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            bulk::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        MemoryCommands::MergeEntry(mut opts) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            opts.id = normalize_id(&opts.id);
+            opts.copy = opts.copy.as_deref().map(normalize_id);
+            merge_entry::run(db.as_ref(), &resolve_agent_context(false, true), opts)?;
+        }
+
         MemoryCommands::Wake {
             limit,
             min_resonance,
             days,
+            agent,
             json,
+            format,
+            max_tokens,
             ritual,
             index,
+            phrases_only,
             no_activate,
             engage,
             set_missing,
@@ -4105,17 +5662,26 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             let db = store::create_store(&config.db_path)?;
 
             // Get current agent context - required for wake
-            let current_agent = match std::env::var("MX_CURRENT_AGENT") {
-                Ok(agent) if !agent.is_empty() => agent,
+            let current_agent = match agent.or_else(|| std::env::var("MX_CURRENT_AGENT").ok()) {
+                Some(agent) if !agent.is_empty() => agent,
                 _ => {
-                    bail!("MX_CURRENT_AGENT not set. Cannot wake without identity.");
+                    bail!(
+                        "MX_CURRENT_AGENT not set (or pass --agent). Cannot wake without identity."
+                    );
                 }
             };
 
             let ctx = store::AgentContext::for_agent(current_agent.clone());
 
-            // Run cascade
-            let cascade = db.wake_cascade(&ctx, limit, min_resonance, days)?;
+            // Run cascade, fitted to the token budget unless the prompt
+            // renderer does its own fitting
+            let mut cascade = db.wake_cascade(&ctx, limit, min_resonance, days)?;
+            let mut dropped = 0;
+            if let Some(budget) = max_tokens
+                && format != Some(wake_format::WakeFormat::Prompt)
+            {
+                dropped = tokens::fit_cascade(&mut cascade, budget);
+            }
 
             // Increment activation counts for wake cascade entries.
             // We do NOT reset last_activated here — wake surfacing is passive, not
@@ -4155,14 +5721,49 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             } else if engage {
                 // Interactive engage mode
                 engage::run_engage_ritual(&cascade, db.as_ref(), set_missing)?;
-            } else if json {
-                println!("{}", serde_json::to_string_pretty(&cascade)?);
-            } else if index {
-                print_wake_index(&cascade);
+            } else if phrases_only {
+                let phrases = cascade.ordered_phrases();
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&wake_phrases_json(&phrases))?
+                    );
+                } else {
+                    for (_, phrase) in &phrases {
+                        println!("{}", phrase);
+                    }
+                }
             } else if ritual {
                 print_wake_ritual(&cascade, &current_agent);
             } else {
-                print_wake_cascade(&cascade);
+                use wake_format::WakeFormat;
+                let format = match format {
+                    Some(f) => f,
+                    None if json => WakeFormat::Json,
+                    None if index => WakeFormat::Md,
+                    None => WakeFormat::Text,
+                };
+                match format {
+                    WakeFormat::Text => {
+                        print_wake_cascade(&cascade);
+                        println!(
+                            "Tokens: {}",
+                            tokens::usage_report(&tokens::layer_usage(&cascade), max_tokens)
+                        );
+                        if dropped > 0 {
+                            println!("{} memories left out to fit the budget.", dropped);
+                        }
+                    }
+                    WakeFormat::Json => println!("{}", serde_json::to_string_pretty(&cascade)?),
+                    WakeFormat::Md => print_wake_index(&cascade),
+                    WakeFormat::Prompt => println!(
+                        "{}",
+                        wake_format::render_prompt(&cascade, &current_agent, max_tokens)
+                    ),
+                    WakeFormat::Stele => {
+                        println!("{}", wake_format::render_stele(&cascade, &current_agent))
+                    }
+                }
             }
         }
 
@@ -4174,9 +5775,19 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             all_types,
             sort,
             limit,
+            viewed,
         } => {
             let db = store::create_store_with_verbose(&config.db_path, verbose)?;
 
+            if viewed.viewed {
+                let ctx = match std::env::var("MX_CURRENT_AGENT") {
+                    Ok(agent) if !agent.is_empty() => store::AgentContext::for_agent(agent),
+                    _ => store::AgentContext::public_only(),
+                };
+                let limit = limit.unwrap_or(recent::DEFAULT_LIMIT);
+                return recent::run(db.as_ref(), &ctx, limit, json || format == "json");
+            }
+
             // Note: Listing doesn't activate facts - bulk view != focused access
             // Auto-enable all_types when --resonance-type is set, otherwise the
             // default ephemeral-only query would silently return nothing for
@@ -4216,7 +5827,7 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             // Default: preserve DB ordering (effective_resonance DESC). No re-sort needed.
 
             // Apply limit
-            facts.truncate(limit);
+            facts.truncate(limit.unwrap_or(100));
 
             // Support both --json flag and legacy --format json
             if json || format == "json" {
@@ -4499,6 +6110,81 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
             }
         }
 
+        MemoryCommands::AnalyzeGraph(graph::AnalyzeArgs {
+            top,
+            include_private,
+            json,
+        }) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, include_private);
+            let knowledge_graph = graph::KnowledgeGraph::load(db.as_ref(), &ctx)?;
+            let analysis = graph::analyze(&knowledge_graph, top);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&analysis)?);
+            } else {
+                graph::print_analysis(&analysis);
+            }
+        }
+
+        MemoryCommands::Graph(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, args.include_private);
+            graph::run(db.as_ref(), &ctx, args)?;
+        }
+
+        MemoryCommands::ExportGraph(graph::ExportArgs {
+            format,
+            output,
+            no_tags,
+            include_private,
+        }) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, include_private);
+            let knowledge_graph = graph::KnowledgeGraph::load(db.as_ref(), &ctx)?;
+            let rendered = graph::render(&knowledge_graph, format, !no_tags)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)
+                        .with_context(|| format!("Failed to write {}", path))?;
+                    eprintln!(
+                        "Exported {} entries and {} edges to {}",
+                        knowledge_graph.nodes.len(),
+                        knowledge_graph.edges.len(),
+                        path
+                    );
+                }
+                None => print!("{}", rendered),
+            }
+        }
+
+        MemoryCommands::Viz {
+            port,
+            include_private,
+        } => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, include_private);
+            viz::serve(db.as_ref(), &ctx, port)?;
+        }
+
+        MemoryCommands::Drill(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            let ctx = resolve_agent_context(false, args.include_private);
+            drill::run(db.as_ref(), &ctx, args)?;
+        }
+
+        MemoryCommands::Review(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            review::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
+        #[cfg(feature = "tui")]
+        MemoryCommands::Browse(args) => {
+            let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+            browse::run(db.as_ref(), &resolve_agent_context(false, true), args)?;
+        }
+
         MemoryCommands::Reinforce {
             id,
             amount,
@@ -4530,11 +6216,14 @@ fn handle_memory(cmd: MemoryCommands, verbose: bool) -> Result<()> {
                     if result.capped {
                         println!("  (Capped at {})", cap);
                     }
-                    println!("  Last activated: {}", result.last_activated);
+                    println!(
+                        "  Last activated: {}",
+                        dates::display(&result.last_activated)
+                    );
                     println!("  Activation count: {}", result.activation_count);
                 }
             } else {
-                bail!("Entry '{}' not found", normalized_id);
+                bail!(MxError::not_found("Entry", normalized_id));
             }
         }
     }
@@ -4597,14 +6286,14 @@ fn handle_agents(cmd: AgentsCommands, config: &IndexConfig) -> Result<()> {
                     println!("Domain: {}", domain);
                 }
                 if let Some(created) = &agent.created_at {
-                    println!("Created: {}", created);
+                    println!("Created: {}", dates::display(created));
                 }
                 if let Some(updated) = &agent.updated_at {
-                    println!("Updated: {}", updated);
+                    println!("Updated: {}", dates::display(updated));
                 }
             }
             None => {
-                bail!("Agent '{}' not found", id);
+                bail!(MxError::not_found("Agent", id));
             }
         },
 
@@ -4696,7 +6385,11 @@ fn handle_projects(cmd: ProjectsCommands, config: &IndexConfig) -> Result<()> {
             } else {
                 println!("Registered projects:\n");
                 for project in projects {
-                    println!("  {} - {}", project.id, project.name);
+                    if project.active {
+                        println!("  {} - {}", project.id, project.name);
+                    } else {
+                        println!("  {} - {} (closed)", project.id, project.name);
+                    }
                     if let Some(path) = &project.path {
                         println!("    Path: {}", path);
                     }
@@ -4734,6 +6427,60 @@ fn handle_projects(cmd: ProjectsCommands, config: &IndexConfig) -> Result<()> {
             println!("Added project: {}", id);
             println!("  Name: {}", name);
         }
+
+        ProjectsCommands::Close {
+            id,
+            archive,
+            dry_run,
+        } => {
+            if archive {
+                let ctx = resolve_agent_context(false, true);
+                let count = project_archive::close(db.as_ref(), &ctx, &id, dry_run)?;
+                if dry_run {
+                    println!("Would archive {} entries from project {}", count, id);
+                } else {
+                    println!("Closed project {}", id);
+                    println!(
+                        "  Archived {} entries to {}",
+                        count,
+                        project_archive::archive_path(&id).display()
+                    );
+                }
+            } else {
+                let Some(mut project) = db.get_project(&id)? else {
+                    bail!(MxError::not_found("Project", id));
+                };
+                project.active = false;
+                project.updated_at = chrono::Utc::now().to_rfc3339();
+                db.upsert_project(&project)?;
+                println!("Closed project {}", id);
+            }
+        }
+
+        ProjectsCommands::Reopen { id } => {
+            if project_archive::archive_path(&id).exists() {
+                let ctx = resolve_agent_context(false, true);
+                let result = project_archive::reopen(db.as_ref(), &ctx, &id)?;
+                println!("Reopened project {}", id);
+                println!("  Restored {} entries", result.restored);
+                if !result.skipped.is_empty() {
+                    println!(
+                        "  Skipped {} stub(s) edited since the close: {}",
+                        result.skipped.len(),
+                        result.skipped.join(", ")
+                    );
+                }
+                println!("  Archive kept as {}", result.archive.display());
+            } else {
+                let Some(mut project) = db.get_project(&id)? else {
+                    bail!(MxError::not_found("Project", id));
+                };
+                project.active = true;
+                project.updated_at = chrono::Utc::now().to_rfc3339();
+                db.upsert_project(&project)?;
+                println!("Reopened project {}", id);
+            }
+        }
     }
 
     Ok(())
@@ -4799,9 +6546,9 @@ fn handle_sessions(cmd: SessionsCommands, config: &IndexConfig) -> Result<()> {
                     if let Some(proj) = &session.project_id {
                         println!("    Project: {}", proj);
                     }
-                    println!("    Started: {}", session.started_at);
+                    println!("    Started: {}", dates::display(&session.started_at));
                     if let Some(ended) = &session.ended_at {
-                        println!("    Ended: {}", ended);
+                        println!("    Ended: {}", dates::display(ended));
                     }
                     println!();
                 }
@@ -4833,7 +6580,7 @@ fn handle_sessions(cmd: SessionsCommands, config: &IndexConfig) -> Result<()> {
                 db.upsert_session(&session)?;
                 println!("Closed session: {}", id);
             } else {
-                bail!("Session '{}' not found", id);
+                bail!(MxError::not_found("Session", id));
             }
         }
     }
@@ -4861,7 +6608,10 @@ fn handle_categories(cmd: CategoriesCommands, config: &IndexConfig) -> Result<()
         CategoriesCommands::Add { id, description } => {
             // Check if category already exists
             if db.get_category(&id)?.is_some() {
-                bail!("Category '{}' already exists", id);
+                bail!(MxError::conflict(
+                    format!("Category '{}' already exists", id),
+                    "List categories with `mx memory categories list`"
+                ));
             }
 
             let now = chrono::Utc::now().to_rfc3339();
@@ -4878,7 +6628,7 @@ fn handle_categories(cmd: CategoriesCommands, config: &IndexConfig) -> Result<()
         CategoriesCommands::Remove { id } => {
             // Check if category exists
             if db.get_category(&id)?.is_none() {
-                bail!("Category '{}' not found", id);
+                bail!(MxError::not_found("Category", id));
             }
 
             // delete_category will check if entries use it and error if so
@@ -4887,7 +6637,7 @@ fn handle_categories(cmd: CategoriesCommands, config: &IndexConfig) -> Result<()
                     println!("Deleted category: {}", id);
                 }
                 Ok(false) => {
-                    bail!("Category '{}' not found", id);
+                    bail!(MxError::not_found("Category", id));
                 }
                 Err(e) => {
                     return Err(e);
@@ -4903,7 +6653,11 @@ fn handle_tags(cmd: TagsCommands, config: &IndexConfig) -> Result<()> {
     let db = store::create_store(&config.db_path)?;
 
     match cmd {
-        TagsCommands::List { category, json } => {
+        TagsCommands::List {
+            category,
+            json,
+            counts,
+        } => {
             // Validate category if provided
             if let Some(ref cat) = category
                 && db.get_category(cat)?.is_none()
@@ -4917,9 +6671,13 @@ fn handle_tags(cmd: TagsCommands, config: &IndexConfig) -> Result<()> {
                 );
             }
 
-            let tags = db.list_all_tags(category.as_deref())?;
-            if json {
+            let tags = db.tag_counts(category.as_deref())?;
+            if json && counts {
                 println!("{}", serde_json::to_string_pretty(&tags)?);
+            } else if json {
+                let mut names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+                names.sort();
+                println!("{}", serde_json::to_string_pretty(&names)?);
             } else if tags.is_empty() {
                 if let Some(cat) = &category {
                     println!("No tags found in category '{}'", cat);
@@ -4933,10 +6691,76 @@ fn handle_tags(cmd: TagsCommands, config: &IndexConfig) -> Result<()> {
                     println!("All tags:\n");
                 }
                 for tag in tags {
-                    println!("  {}", tag);
+                    println!("  {} ({})", tag.name, tag.entries);
                 }
             }
         }
+
+        TagsCommands::Rename { old, new } => {
+            let in_use = db.list_all_tags(None)?;
+            if !in_use.contains(&old) {
+                bail!(MxError::not_found("Tag", old));
+            }
+            if old == new {
+                bail!("Tag '{}' already has that name", old);
+            }
+            if in_use.contains(&new) {
+                bail!(
+                    "Tag '{}' is already in use. To combine them: mx memory tags merge {} --into {}",
+                    new,
+                    old,
+                    new
+                );
+            }
+            let retagged = db.merge_tags(std::slice::from_ref(&old), &new)?;
+            println!(
+                "Renamed '{}' to '{}' on {} entr{}",
+                old,
+                new,
+                retagged,
+                if retagged == 1 { "y" } else { "ies" }
+            );
+        }
+
+        TagsCommands::Merge { sources, into } => {
+            let in_use = db.list_all_tags(None)?;
+            let (found, missing): (Vec<String>, Vec<String>) = sources
+                .into_iter()
+                .filter(|s| *s != into)
+                .partition(|s| in_use.contains(s));
+            for name in &missing {
+                eprintln!("Warning: tag '{}' is not in use; skipped", name);
+            }
+            if found.is_empty() {
+                bail!("Nothing to merge into '{}'", into);
+            }
+            let retagged = db.merge_tags(&found, &into)?;
+            println!(
+                "Merged {} into '{}' on {} entr{}",
+                found
+                    .iter()
+                    .map(|s| format!("'{}'", s))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                into,
+                retagged,
+                if retagged == 1 { "y" } else { "ies" }
+            );
+        }
+
+        TagsCommands::Delete { names } => {
+            let untagged = db.delete_tags(&names)?;
+            println!(
+                "Removed {} from {} entr{}",
+                names
+                    .iter()
+                    .map(|s| format!("'{}'", s))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                untagged,
+                if untagged == 1 { "y" } else { "ies" }
+            );
+        }
     }
 
     Ok(())
@@ -5067,11 +6891,16 @@ fn handle_relationships(cmd: RelationshipsCommands, config: &IndexConfig) -> Res
             println!("  Type: {}", r#type);
         }
 
-        RelationshipsCommands::Delete { id } => {
+        RelationshipsCommands::Delete { id, yes } => {
+            let affected = [(1, "relationship", "relationships")];
+            if !confirm::confirm("Delete", &affected, std::slice::from_ref(&id), yes)? {
+                println!("Cancelled");
+                return Ok(());
+            }
             if db.delete_relationship(&id)? {
                 println!("Deleted relationship: {}", id);
             } else {
-                bail!("Relationship '{}' not found", id);
+                bail!(MxError::not_found("Relationship", id));
             }
         }
     }
@@ -5110,13 +6939,16 @@ fn handle_pr(cmd: PrCommands) -> Result<()> {
             number,
             rebase,
             merge_commit,
+            repo,
         } => {
-            commit::pr_merge(number, rebase, merge_commit)?;
+            let repo = repo.or_else(|| workspace::Workspace::current().and_then(|ws| ws.repo));
+            commit::pr_merge(number, rebase, merge_commit, repo.as_deref())?;
             Ok(())
         }
     }
 }
 
+#[cfg(feature = "github")]
 fn handle_github(cmd: GithubCommands) -> Result<()> {
     match cmd {
         GithubCommands::Cleanup {
@@ -5124,8 +6956,10 @@ fn handle_github(cmd: GithubCommands) -> Result<()> {
             issues,
             discussions,
             dry_run,
+            yes,
         } => {
-            github::cleanup(&repo, issues, discussions, dry_run)?;
+            let repo = workspace::repo_or_current(repo)?;
+            github::cleanup(&repo, issues, discussions, dry_run, yes)?;
             Ok(())
         }
         GithubCommands::Comment { command } => {
@@ -5135,23 +6969,18 @@ fn handle_github(cmd: GithubCommands) -> Result<()> {
     }
 }
 
+#[cfg(feature = "github")]
 fn handle_comment(cmd: CommentCommands) -> Result<()> {
     match cmd {
-        CommentCommands::Issue {
-            repo,
-            number,
-            message,
-            identity,
-        } => {
+        CommentCommands::Issue { args, identity } => {
+            let (repo, number, message) = comment_target(args)?;
+            let repo = workspace::repo_or_current(repo)?;
             let url = github::post_issue_comment(&repo, number, &message, identity.as_deref())?;
             println!("Comment posted: {}", url);
         }
-        CommentCommands::Discussion {
-            repo,
-            number,
-            message,
-            identity,
-        } => {
+        CommentCommands::Discussion { args, identity } => {
+            let (repo, number, message) = comment_target(args)?;
+            let repo = workspace::repo_or_current(repo)?;
             let url =
                 github::post_discussion_comment(&repo, number, &message, identity.as_deref())?;
             println!("Comment posted: {}", url);
@@ -5160,12 +6989,25 @@ fn handle_comment(cmd: CommentCommands) -> Result<()> {
     Ok(())
 }
 
+/// Split `[REPO] NUMBER MESSAGE` (clap has already checked there are 2 or 3)
+#[cfg(feature = "github")]
+fn comment_target(mut args: Vec<String>) -> Result<(Option<String>, u64, String)> {
+    let message = args.pop().unwrap_or_default();
+    let number = args.pop().unwrap_or_default();
+    let number = number
+        .trim_start_matches('#')
+        .parse()
+        .with_context(|| format!("Expected an issue or discussion number, got '{}'", number))?;
+    Ok((args.pop(), number, message))
+}
+
 fn handle_session(cmd: SessionCommands) -> Result<()> {
     match cmd {
         SessionCommands::Export { path, output } => {
             session::export_session(path, output)?;
             Ok(())
         }
+        SessionCommands::CloseOut(args) => closeout::run(args),
     }
 }
 
@@ -5191,13 +7033,31 @@ fn handle_codex(cmd: CodexCommands) -> Result<()> {
             grep,
             json,
             clean,
+            raw,
         } => {
             let clean_agents = clean && agents;
-            codex::read_session(id, human, grep, agents, json, clean, clean_agents)?;
+            codex::read_session(id, human, raw, grep, agents, json, clean, clean_agents)?;
+            Ok(())
+        }
+        CodexCommands::Search {
+            pattern,
+            json,
+            limit,
+        } => {
+            codex::search_archives(pattern, json, limit)?;
+            Ok(())
+        }
+        CodexCommands::Manifest { id, fields, json } => {
+            manifest_query::show(&id, &fields, json)?;
             Ok(())
         }
-        CodexCommands::Search { pattern, json } => {
-            codex::search_archives(pattern, json)?;
+        CodexCommands::QueryManifests {
+            query,
+            fields,
+            all,
+            json,
+        } => {
+            manifest_query::query(&query, &fields, all, json)?;
             Ok(())
         }
         CodexCommands::Migrate {
@@ -5209,6 +7069,11 @@ fn handle_codex(cmd: CodexCommands) -> Result<()> {
             codex::migrate_archives(dry_run, verbose, clean, include_agents)?;
             Ok(())
         }
+
+        CodexCommands::UpgradeManifests { dry_run, verbose } => {
+            codex::upgrade_manifests(dry_run, verbose)?;
+            Ok(())
+        }
     }
 }
 
@@ -5266,6 +7131,7 @@ fn handle_convert(cmd: ConvertCommands) -> Result<()> {
     }
 }
 
+#[cfg(feature = "github")]
 fn handle_wiki(cmd: WikiCommands) -> Result<()> {
     match cmd {
         WikiCommands::Sync {
@@ -5664,7 +7530,7 @@ fn print_entry_summary(entry: &knowledge::KnowledgeEntry) {
     println!();
 }
 
-fn print_entry_full(entry: &knowledge::KnowledgeEntry) {
+fn print_entry_full(entry: &knowledge::KnowledgeEntry, hood: &neighbors::Neighborhood, raw: bool) {
     println!("ID:       {}", entry.id);
     println!("Category: {}", entry.category_id);
 
@@ -5680,6 +7546,9 @@ fn print_entry_full(entry: &knowledge::KnowledgeEntry) {
     if entry.resonance > 0 {
         println!("Resonance: {}", entry.resonance);
     }
+    if let Some(confidence) = entry.confidence {
+        println!("Confidence: {:.2}", confidence);
+    }
     if let Some(ref rtype) = entry.resonance_type {
         println!("Resonance Type: {}", rtype);
     }
@@ -5709,21 +7578,147 @@ fn print_entry_full(entry: &knowledge::KnowledgeEntry) {
         }
     }
     if let Some(created) = &entry.created_at {
-        println!("Created:  {}", created);
+        println!("Created:  {}", dates::display(created));
     }
     if let Some(updated) = &entry.updated_at {
-        println!("Updated:  {}", updated);
+        println!("Updated:  {}", dates::display(updated));
     }
     println!("Format:   {}", entry.format);
     println!();
     if let Some(body) = &entry.body {
-        println!("{}", body);
+        if entry.format == "markdown" {
+            output::print_markdown(body, raw);
+        } else {
+            println!("{}", body);
+        }
+    }
+    if !entry.attachments.is_empty() {
+        println!();
+        println!("Attachments:");
+        for line in attach::describe(entry) {
+            println!("  {}", line);
+        }
+        println!(
+            "  (extract with: mx memory attach {} --extract <name> [-o path])",
+            entry.id
+        );
+    }
+    if !hood.is_empty() {
+        println!();
+        print!("{}", hood.render());
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::KnowledgeStore;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[cfg(feature = "github")]
+    #[test]
+    fn test_comment_target_repo_is_optional() {
+        let (repo, number, message) =
+            comment_target(args(&["owner/repo", "#12", "looks good"])).unwrap();
+        assert_eq!(
+            (repo.as_deref(), number, message.as_str()),
+            (Some("owner/repo"), 12, "looks good")
+        );
+        let (repo, number, _) = comment_target(args(&["7", "lgtm"])).unwrap();
+        assert_eq!((repo, number), (None, 7));
+        assert!(comment_target(args(&["owner/repo", "lgtm"])).is_err());
+    }
+
+    #[test]
+    fn test_command_path_excludes_arguments() {
+        assert_eq!(
+            command_path(&args(&["mx", "memory", "search", "secret query", "--json"])),
+            "memory search"
+        );
+        assert_eq!(
+            command_path(&args(&["mx", "--profile", "work", "codex", "list"])),
+            "codex list"
+        );
+        assert_eq!(command_path(&args(&["mx", "log", "-n", "20"])), "log");
+        assert_eq!(command_path(&args(&["mx"])), "");
+    }
+
+    #[test]
+    fn test_check_entry_applies_category_rules() {
+        let rules = category_rules::Rules::from_config(
+            &serde_yaml::from_str(
+                "categories:\n  pattern:\n    required_tags: [reviewed]\n    required_fields: [applicability]\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let mut entry: knowledge::KnowledgeEntry = serde_json::from_value(serde_json::json!({
+            "id": "kn-1",
+            "category_id": "pattern",
+            "title": "Retry budget",
+            "tags": ["reviewed"],
+        }))
+        .unwrap();
+
+        let err = check_entry(&entry, &rules).unwrap_err();
+        assert_eq!(err.to_string(), FORCE_HINT);
+        assert!(format!("{:#}", err).contains("applicability"));
+
+        // An update that fills the field in passes, and dropping the tag fails again
+        entry.applicability = vec!["rust".to_string()];
+        check_entry(&entry, &rules).unwrap();
+        entry.tags.clear();
+        assert!(check_entry(&entry, &rules).is_err());
+        check_entry(&entry, &category_rules::Rules::default()).unwrap();
+    }
+
+    #[test]
+    fn test_wake_phrases_follow_wake_order_then_cascade_order() {
+        let entry = |id: &str, order: Option<i32>, phrases: &[&str]| -> knowledge::KnowledgeEntry {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "category_id": "bloom",
+                "title": id,
+                "wake_order": order,
+                "wake_phrases": phrases,
+            }))
+            .unwrap()
+        };
+        let cascade = store::WakeCascade {
+            core: vec![
+                entry("kn-core", None, &["core"]),
+                entry("kn-second", Some(2), &["second a", "second b"]),
+            ],
+            recent: vec![
+                entry("kn-silent", Some(0), &[]),
+                entry("kn-recent", None, &["recent"]),
+                entry("kn-first", Some(1), &["first"]),
+            ],
+            // Surfaced again as a bridge: its phrases appear once
+            bridges: vec![
+                entry("kn-bridge", None, &["bridge"]),
+                entry("kn-core", None, &["core"]),
+            ],
+        };
+
+        let phrases = cascade.ordered_phrases();
+        assert_eq!(
+            phrases.iter().map(|(_, p)| p.as_str()).collect::<Vec<_>>(),
+            vec!["first", "second a", "second b", "core", "recent", "bridge"]
+        );
+        assert_eq!(
+            wake_phrases_json(&phrases[..3]),
+            serde_json::json!([
+                { "id": "kn-first", "phrase": "first" },
+                { "id": "kn-second", "phrase": "second a" },
+                { "id": "kn-second", "phrase": "second b" },
+            ])
+        );
+        assert_eq!(wake_phrases_json(&[]), serde_json::json!([]));
+    }
 
     #[test]
     fn test_safe_truncate_short_string() {
@@ -5887,4 +7882,88 @@ mod tests {
         assert!(result.ends_with("..."));
         assert_eq!(result.chars().count(), 60);
     }
+
+    fn search_entry(
+        id: &str,
+        title: &str,
+        body: &str,
+        confidence: Option<f64>,
+    ) -> knowledge::KnowledgeEntry {
+        let now = chrono::Utc::now().to_rfc3339();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": title,
+            "body": body,
+            "confidence": confidence,
+            "created_at": now,
+            "updated_at": now,
+            "content_hash": id,
+            "source_type_id": "manual",
+            "entry_type_id": "primary",
+            "content_type_id": "text",
+            "visibility": "public",
+        }))
+        .unwrap()
+    }
+
+    /// An in-memory store holding `entries`, padded with unrelated ones so
+    /// BM25 term rarity is meaningful
+    fn search_db(entries: &[knowledge::KnowledgeEntry]) -> surreal_db::SurrealDatabase {
+        let db = surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        for (i, title) in ["Tag hygiene", "Graph layout", "Session export"]
+            .into_iter()
+            .enumerate()
+        {
+            let other = search_entry(&format!("kn-pad{}", i), title, "Nothing here", None);
+            db.upsert_knowledge(&other).unwrap();
+        }
+        for entry in entries {
+            db.upsert_knowledge(entry).unwrap();
+        }
+        db
+    }
+
+    fn search_ids(db: &dyn store::KnowledgeStore, query: &str) -> Vec<String> {
+        keyword_search(
+            db,
+            query,
+            &store::AgentContext::public_only(),
+            &store::KnowledgeFilter::default(),
+            &confidence::Settings::default(),
+            false,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|e| e.id)
+        .collect()
+    }
+
+    #[test]
+    fn test_keyword_search_keeps_bm25_order_over_modest_confidence() {
+        let db = search_db(&[
+            search_entry(
+                "kn-passing",
+                "Connection pooling",
+                "Mentions retry once among many other words",
+                Some(0.8),
+            ),
+            search_entry("kn-focused", "Notes", "Retry, then retry again", None),
+        ]);
+        assert_eq!(search_ids(&db, "retry"), ["kn-focused", "kn-passing"]);
+    }
+
+    #[test]
+    fn test_keyword_search_ranks_title_match_above_body_match() {
+        let db = search_db(&[
+            search_entry(
+                "kn-body",
+                "Connection notes",
+                "Backoff here, backoff there, backoff everywhere",
+                Some(0.7),
+            ),
+            search_entry("kn-title", "Backoff", "Nothing relevant", None),
+        ]);
+        assert_eq!(search_ids(&db, "backoff"), ["kn-title", "kn-body"]);
+    }
 }
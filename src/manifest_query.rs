@@ -0,0 +1,428 @@
+//! Archive metadata from the command line: `mx codex manifest` and
+//! `mx codex query-manifests`
+//!
+//! `manifest <id>` prints one archive's manifest.json, or just the fields
+//! named with `--field`. `query-manifests` filters every archive's manifest
+//! with conditions joined by `AND` and `OR` (`AND` binds tighter):
+//!
+//! ```text
+//! mx codex query-manifests 'project_path~mx AND message_count>200'
+//! mx codex query-manifests 'agent_count>=1 OR has_clean_transcript=true'
+//! ```
+//!
+//! A condition is `field op value`. Operators are `=`, `!=`, `>`, `>=`, `<`,
+//! `<=` and `~` (contains, ignoring case). Values compare as numbers when
+//! both sides are numbers, otherwise as text, so RFC 3339 timestamps order
+//! correctly. Dotted fields reach into objects and arrays (`agents.name`)
+//! and match when any element does; `>`/`<` on an array compare its length.
+//! `field=null` matches a missing field and `field!=null` a present one.
+//! Queries read the raw JSON, so they work across manifest versions.
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::codex;
+use crate::output::Table;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+/// One `field op value` test
+#[derive(Debug, PartialEq)]
+struct Condition {
+    path: Vec<String>,
+    op: Op,
+    value: String,
+}
+
+/// Conditions in disjunctive form: any group whose conditions all hold
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    groups: Vec<Vec<Condition>>,
+}
+
+/// Operators longest first, so `>=` isn't read as `>`
+const OPS: [(&str, Op); 7] = [
+    ("!=", Op::Ne),
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    ("=", Op::Eq),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+    ("~", Op::Contains),
+];
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self> {
+        let Some((at, symbol, op)) = OPS
+            .iter()
+            .filter_map(|&(symbol, op)| text.find(symbol).map(|at| (at, symbol, op)))
+            .min_by_key(|&(at, symbol, _)| (at, std::cmp::Reverse(symbol.len())))
+        else {
+            bail!(
+                "Condition '{}' has no operator (use =, !=, >, >=, <, <= or ~)",
+                text
+            );
+        };
+        let field = text[..at].trim();
+        if field.is_empty() {
+            bail!("Condition '{}' has no field", text);
+        }
+        let value = text[at + symbol.len()..].trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+            .unwrap_or(value);
+        Ok(Condition {
+            path: field.split('.').map(str::to_string).collect(),
+            op,
+            value: value.to_string(),
+        })
+    }
+
+    fn matches(&self, manifest: &Value) -> bool {
+        let found = lookup(manifest, &self.path);
+        if self.value == "null" {
+            let present = found.iter().any(|v| !v.is_null());
+            return match self.op {
+                Op::Eq => !present,
+                Op::Ne => present,
+                _ => false,
+            };
+        }
+        match self.op {
+            Op::Ne => !found.iter().any(|v| equals(v, &self.value)),
+            Op::Eq => found.iter().any(|v| equals(v, &self.value)),
+            Op::Contains => {
+                let needle = self.value.to_lowercase();
+                found
+                    .iter()
+                    .any(|v| text(v).is_some_and(|t| t.to_lowercase().contains(&needle)))
+            }
+            op => found.iter().any(|v| {
+                let Some(ordering) = compare(v, &self.value) else {
+                    return false;
+                };
+                match op {
+                    Op::Gt => ordering.is_gt(),
+                    Op::Ge => ordering.is_ge(),
+                    Op::Lt => ordering.is_lt(),
+                    _ => ordering.is_le(),
+                }
+            }),
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut groups = vec![Vec::new()];
+        let mut current: Vec<String> = Vec::new();
+        for word in words(text)? {
+            let keyword = word.to_ascii_uppercase();
+            if keyword != "AND" && keyword != "OR" {
+                current.push(word);
+                continue;
+            }
+            if current.is_empty() {
+                bail!("'{}' needs a condition on both sides", word);
+            }
+            let condition = Condition::parse(&current.join(" "))?;
+            groups.last_mut().unwrap().push(condition);
+            current.clear();
+            if keyword == "OR" {
+                groups.push(Vec::new());
+            }
+        }
+        if current.is_empty() {
+            bail!("Expected a condition at the end of '{}'", text.trim());
+        }
+        groups
+            .last_mut()
+            .unwrap()
+            .push(Condition::parse(&current.join(" "))?);
+        Ok(Query { groups })
+    }
+
+    pub fn matches(&self, manifest: &Value) -> bool {
+        self.groups
+            .iter()
+            .any(|group| group.iter().all(|c| c.matches(manifest)))
+    }
+}
+
+/// `text` split on whitespace outside quotes, quotes kept
+fn words(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        word.push(c);
+    }
+    if quote.is_some() {
+        bail!("Unclosed quote in '{}'", text);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Every value at `path`, stepping into each element of an array on the way
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Vec<&'a Value> {
+    let Some((key, rest)) = path.split_first() else {
+        return vec![value];
+    };
+    match value {
+        Value::Object(map) => map.get(key).map_or(Vec::new(), |v| lookup(v, rest)),
+        Value::Array(items) => items.iter().flat_map(|v| lookup(v, path)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(items) => Some(items.iter().filter_map(text).collect::<Vec<_>>().join("\n")),
+        _ => None,
+    }
+}
+
+fn equals(value: &Value, wanted: &str) -> bool {
+    match (value, wanted.parse::<f64>()) {
+        (Value::Number(n), Ok(w)) => n.as_f64() == Some(w),
+        (Value::Array(items), _) => items.iter().any(|v| equals(v, wanted)),
+        (Value::Object(_) | Value::Null, _) => false,
+        _ => text(value).as_deref() == Some(wanted),
+    }
+}
+
+fn compare(value: &Value, wanted: &str) -> Option<std::cmp::Ordering> {
+    match (value, wanted.parse::<f64>()) {
+        (Value::Number(n), Ok(w)) => n.as_f64()?.partial_cmp(&w),
+        (Value::Array(items), Ok(w)) => (items.len() as f64).partial_cmp(&w),
+        (Value::String(s), _) => Some(s.as_str().cmp(wanted)),
+        _ => None,
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<Value> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {}", path.display()))
+}
+
+/// The `fields` of `manifest`: one field's value as is, several as an
+/// object. No fields is the whole manifest.
+fn select(manifest: Value, fields: &[String]) -> Result<Value> {
+    match fields {
+        [] => Ok(manifest),
+        [field] => field_value(&manifest, field),
+        _ => {
+            let mut selected = Map::new();
+            for field in fields {
+                selected.insert(field.clone(), field_value(&manifest, field)?);
+            }
+            Ok(Value::Object(selected))
+        }
+    }
+}
+
+/// The value at `field`; a path through an array gives an array of what
+/// each element holds
+fn field_value(manifest: &Value, field: &str) -> Result<Value> {
+    let path: Vec<String> = field.split('.').map(str::to_string).collect();
+    let found = lookup(manifest, &path);
+    if found.is_empty() {
+        bail!("Manifest has no field '{}'", field);
+    }
+    let mut at = manifest;
+    for key in &path[..path.len() - 1] {
+        match at.get(key) {
+            Some(Value::Array(_)) | None => {
+                return Ok(Value::Array(found.into_iter().cloned().collect()));
+            }
+            Some(next) => at = next,
+        }
+    }
+    Ok(found[0].clone())
+}
+
+/// A value for a terminal: strings bare, everything else as JSON
+fn display(value: &Value, pretty: bool) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        _ if pretty => serde_json::to_string_pretty(value)?,
+        _ => serde_json::to_string(value)?,
+    })
+}
+
+/// `mx codex manifest`
+pub fn show(id: &str, fields: &[String], json: bool) -> Result<()> {
+    let manifest = read_manifest(&codex::manifest_path(id)?)?;
+    let selected = select(manifest, fields)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&selected)?);
+        return Ok(());
+    }
+    match (&selected, fields.len()) {
+        (Value::Object(map), n) if n != 1 => {
+            for (key, value) in map {
+                println!("{}: {}", key, display(value, true)?);
+            }
+        }
+        (value, _) => println!("{}", display(value, true)?),
+    }
+    Ok(())
+}
+
+/// `mx codex query-manifests`
+pub fn query(expression: &str, fields: &[String], all: bool, json: bool) -> Result<()> {
+    let query = Query::parse(expression)?;
+    let mut matched = Vec::new();
+    for (id, path) in codex::manifest_paths(all)? {
+        let manifest = read_manifest(&path)?;
+        if query.matches(&manifest) {
+            matched.push((id, manifest));
+        }
+    }
+
+    if json {
+        let results: Vec<Value> = matched
+            .into_iter()
+            .map(|(id, manifest)| {
+                // The whole manifest, or the selected fields beside the id
+                let mut result = Map::new();
+                result.insert("id".to_string(), Value::String(id));
+                for field in fields {
+                    let value = field_value(&manifest, field).unwrap_or(Value::Null);
+                    result.insert(field.clone(), value);
+                }
+                if fields.is_empty() {
+                    result.insert("manifest".to_string(), manifest);
+                }
+                Value::Object(result)
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    if matched.is_empty() {
+        println!("No archives match");
+        return Ok(());
+    }
+    let columns: Vec<String> = if fields.is_empty() {
+        ["archived_at", "project_path", "message_count"]
+            .map(String::from)
+            .to_vec()
+    } else {
+        fields.to_vec()
+    };
+    let mut table = Table::with_headers(
+        std::iter::once("ARCHIVE".to_string()).chain(columns.iter().map(|c| c.to_uppercase())),
+    );
+    for (id, manifest) in &matched {
+        let mut row = vec![id.clone()];
+        for column in &columns {
+            let cell = field_value(manifest, column).unwrap_or(Value::Null);
+            row.push(display(&cell, false)?);
+        }
+        table.row(row);
+    }
+    table.print();
+    println!("\n{} archive(s)", matched.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Value {
+        serde_json::json!({
+            "version": 5,
+            "session_id": "abc",
+            "archived_at": "2026-03-01T10:00:00Z",
+            "project_path": "/home/neo/mx",
+            "message_count": 240,
+            "agent_count": 2,
+            "agents": [
+                {"name": "agent-a1", "spawned_by": "toolu_1"},
+                {"name": "agent-b2"},
+            ],
+            "has_clean_transcript": true,
+        })
+    }
+
+    fn holds(query: &str) -> bool {
+        Query::parse(query).unwrap().matches(&manifest())
+    }
+
+    #[test]
+    fn conditions_combine_with_and_or() {
+        assert!(holds("project_path~MX AND message_count>200"));
+        assert!(!holds("project_path~mx AND message_count>240"));
+        assert!(holds("message_count>=240 and agent_count=2"));
+        assert!(holds("message_count<10 OR has_clean_transcript=true"));
+        assert!(holds("archived_at>2026-02-01 AND archived_at<=2026-03-02"));
+        assert!(holds("agents.name=agent-b2 AND agents>1"));
+        assert!(holds("agents.spawned_by~toolu"));
+        assert!(holds("images=null AND session_id!=null"));
+        assert!(holds("project_path = '/home/neo/mx'"));
+        assert!(!holds("project_path!=/home/neo/mx"));
+
+        for bad in [
+            "",
+            "message_count",
+            "AND message_count>1",
+            "message_count>1 OR",
+            "=5",
+            "project_path='mx",
+        ] {
+            assert!(Query::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn fields_select_from_the_manifest() {
+        assert_eq!(select(manifest(), &["message_count".into()]).unwrap(), 240);
+        assert_eq!(
+            select(manifest(), &["agents.name".into()]).unwrap(),
+            serde_json::json!(["agent-a1", "agent-b2"])
+        );
+        assert_eq!(
+            select(manifest(), &["version".into(), "agent_count".into()]).unwrap(),
+            serde_json::json!({"version": 5, "agent_count": 2})
+        );
+        assert_eq!(
+            select(manifest(), &["agents".into()]).unwrap(),
+            manifest()["agents"]
+        );
+        assert!(select(manifest(), &["images".into()]).is_err());
+    }
+}
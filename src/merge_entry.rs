@@ -0,0 +1,420 @@
+//! Conflict-copy merge: `mx memory merge-entry`
+//!
+//! When a peer sync finds an entry changed on both sides it keeps the local
+//! entry and stores the peer's version as a separate entry linked to it with
+//! a `conflict_of` relationship (copy -> original). `merge-entry` resolves one
+//! such copy field by field:
+//!
+//! - The common base is the original as it stood just before the earlier of
+//!   the two edits, rebuilt from the revision history. Only title and body
+//!   are versioned, so the other fields have no base: they merge only when
+//!   both sides agree, and are otherwise asked about. Tags are unioned.
+//! - Fields changed on one side take that side; the body is merged line by
+//!   line with diff3, and overlapping edits can be resolved in `$EDITOR`.
+//! - `--prefer local|remote` settles every conflict without prompting.
+//!
+//! The resolution is written to the original (after a `merge` backup), the
+//! `conflict_of` edge is replaced by `original -merged_from-> copy`, and the
+//! copy is left in place for reference.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use std::io::{self, IsTerminal, Write};
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::line_diff;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::sync::merge::diff::FieldChange;
+use crate::sync::merge::labels::merge_labels;
+
+/// Relationship from a conflict copy to the entry it conflicts with
+pub const CONFLICT_OF: &str = "conflict_of";
+/// Relationship from a merged entry to the copy it absorbed
+pub const MERGED_FROM: &str = "merged_from";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+#[derive(clap::Args)]
+pub struct MergeOptions {
+    /// Entry the conflict copy was split from
+    pub id: String,
+
+    /// Conflict copy to merge (required when there are several)
+    #[arg(long)]
+    pub copy: Option<String>,
+
+    /// Settle every conflict in favour of one side instead of prompting
+    #[arg(long, value_enum)]
+    pub prefer: Option<Side>,
+
+    /// Show the resolution without writing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Ids of the conflict copies pending against `id`
+pub fn conflict_copies(db: &dyn KnowledgeStore, id: &str) -> Result<Vec<String>> {
+    Ok(db
+        .list_relationships_for_entry(id)?
+        .into_iter()
+        .filter(|r| {
+            r.relationship_type == CONFLICT_OF && r.to_entry_id == id && r.from_entry_id != id
+        })
+        .map(|r| r.from_entry_id)
+        .collect())
+}
+
+fn timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    crate::dates::parse_stored(value?)
+}
+
+/// The original just before the earlier of the two edits, or None when the
+/// revision history doesn't reach back that far
+fn base_for(
+    db: &dyn KnowledgeStore,
+    local: &KnowledgeEntry,
+    remote: &KnowledgeEntry,
+) -> Result<Option<KnowledgeEntry>> {
+    let edits = [local, remote].map(|e| timestamp(e.updated_at.as_deref()));
+    let Some(split) = edits.into_iter().flatten().min() else {
+        return Ok(None);
+    };
+    // Backups are taken just before the update they precede
+    let at = split - Duration::seconds(1);
+    let backups = db.list_backups(&local.id)?;
+    if !backups
+        .iter()
+        .any(|b| timestamp(b.created_at.as_deref()).is_some_and(|t| t > at))
+    {
+        return Ok(None);
+    }
+    Ok(crate::revisions::reconstruct(local, &backups, at))
+}
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        bail!("Merge aborted");
+    }
+    Ok(input.trim().to_lowercase())
+}
+
+/// Open `text` in $VISUAL / $EDITOR and return what was saved
+fn edit_in_editor(text: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let file = tempfile::Builder::new().suffix(".md").tempfile()?;
+    std::fs::write(file.path(), text)?;
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor exited with {}", status);
+    }
+    Ok(std::fs::read_to_string(file.path())?)
+}
+
+struct Resolver {
+    prefer: Option<Side>,
+    interactive: bool,
+    changes: Vec<String>,
+}
+
+impl Resolver {
+    fn choose_side(&self, field: &str) -> Result<Side> {
+        if let Some(side) = self.prefer {
+            return Ok(side);
+        }
+        if !self.interactive {
+            bail!(
+                "'{}' conflicts; run in a terminal or pass --prefer local|remote",
+                field
+            );
+        }
+        loop {
+            match prompt(&format!("  Keep [l]ocal or [r]emote {}? ", field))?.as_str() {
+                "l" | "local" => return Ok(Side::Local),
+                "r" | "remote" => return Ok(Side::Remote),
+                _ => {}
+            }
+        }
+    }
+
+    /// Merge one field. `base` is None for fields without history.
+    fn field<T: Clone + PartialEq>(
+        &mut self,
+        name: &str,
+        local: &T,
+        remote: &T,
+        base: Option<&T>,
+        show: impl Fn(&T) -> String,
+    ) -> Result<T> {
+        let change = match base {
+            Some(base) => FieldChange::compute(local, remote, base),
+            None if local == remote => FieldChange::Unchanged,
+            None => FieldChange::Conflict {
+                local: local.clone(),
+                remote: remote.clone(),
+                base: local.clone(),
+            },
+        };
+        let value = match change {
+            FieldChange::Unchanged => return Ok(local.clone()),
+            FieldChange::Conflict { .. } => {
+                println!("{} {}", "conflict".red(), name.bold());
+                println!("  local:  {}", show(local));
+                println!("  remote: {}", show(remote));
+                match self.choose_side(name)? {
+                    Side::Local => local.clone(),
+                    Side::Remote => remote.clone(),
+                }
+            }
+            other => other
+                .resolved_value()
+                .cloned()
+                .unwrap_or_else(|| local.clone()),
+        };
+        if value != *local {
+            self.changes
+                .push(format!("{}: {} → {}", name, show(local), show(&value)));
+        }
+        Ok(value)
+    }
+
+    fn body(&mut self, local: &str, remote: &str, base: Option<&str>) -> Result<String> {
+        if local == remote {
+            return Ok(local.to_string());
+        }
+        let merged = line_diff::merge3(base.unwrap_or(""), local, remote);
+        let text = if merged.conflicts == 0 && base.is_some() {
+            merged.text
+        } else {
+            println!(
+                "{} {} ({} overlapping hunk(s))",
+                "conflict".red(),
+                "body".bold(),
+                merged.conflicts.max(1)
+            );
+            self.resolve_body(local, remote, merged.text)?
+        };
+        if text != local {
+            let ops = line_diff::diff_lines(local, &text);
+            let (added, removed) = ops.iter().fold((0, 0), |(a, r), op| match op {
+                line_diff::Op::Insert(_) => (a + 1, r),
+                line_diff::Op::Delete(_) => (a, r + 1),
+                line_diff::Op::Equal(_) => (a, r),
+            });
+            self.changes
+                .push(format!("body: +{} -{} lines", added, removed));
+        }
+        Ok(text)
+    }
+
+    fn resolve_body(&self, local: &str, remote: &str, marked: String) -> Result<String> {
+        if let Some(side) = self.prefer {
+            return Ok(match side {
+                Side::Local => local.to_string(),
+                Side::Remote => remote.to_string(),
+            });
+        }
+        if !self.interactive {
+            bail!(MxError::conflict(
+                "'body' conflicts",
+                "Run in a terminal, or pass --prefer local|remote"
+            ));
+        }
+        for line in line_diff::render(&line_diff::diff_lines(local, remote), 2) {
+            println!("    {}", line);
+        }
+        loop {
+            match prompt("  Keep [l]ocal, [r]emote, or [e]dit the merge? ")?.as_str() {
+                "l" | "local" => return Ok(local.to_string()),
+                "r" | "remote" => return Ok(remote.to_string()),
+                "e" | "edit" => {
+                    let edited = edit_in_editor(&marked)?;
+                    if edited.lines().any(|l| l.starts_with("<<<<<<<")) {
+                        println!("  Conflict markers remain; resolve them or pick a side.");
+                        continue;
+                    }
+                    return Ok(edited);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn show_opt(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "(none)".to_string())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, opts: MergeOptions) -> Result<()> {
+    let Some(local) = db.get(&opts.id, ctx)? else {
+        bail!(MxError::not_found("Entry", opts.id));
+    };
+    let copies = conflict_copies(db, &local.id)?;
+    let copy_id = match opts.copy {
+        Some(copy) if copies.contains(&copy) => copy,
+        Some(copy) => bail!("{} is not a conflict copy of {}", copy, local.id),
+        None => match copies.as_slice() {
+            [] => bail!("No conflict copies pending for {}", local.id),
+            [only] => only.clone(),
+            several => bail!(
+                "{} has {} conflict copies ({}); pick one with --copy",
+                local.id,
+                several.len(),
+                several.join(", ")
+            ),
+        },
+    };
+    let Some(remote) = db.get(&copy_id, ctx)? else {
+        bail!(MxError::not_found("Conflict copy", copy_id));
+    };
+
+    let base = base_for(db, &local, &remote)?;
+    println!(
+        "Merging {} into {} ({})",
+        remote.id.cyan(),
+        local.id.cyan(),
+        if base.is_some() {
+            "three-way"
+        } else {
+            "no common base in history: differing fields are conflicts"
+        }
+    );
+
+    let mut resolver = Resolver {
+        prefer: opts.prefer,
+        interactive: io::stdin().is_terminal(),
+        changes: Vec::new(),
+    };
+    let mut merged = local.clone();
+    merged.title = resolver.field(
+        "title",
+        &local.title,
+        &remote.title,
+        base.as_ref().map(|b| &b.title),
+        String::clone,
+    )?;
+    merged.summary = resolver.field("summary", &local.summary, &remote.summary, None, show_opt)?;
+    merged.category_id = resolver.field(
+        "category",
+        &local.category_id,
+        &remote.category_id,
+        None,
+        String::clone,
+    )?;
+    merged.resonance = resolver.field(
+        "resonance",
+        &local.resonance,
+        &remote.resonance,
+        None,
+        i32::to_string,
+    )?;
+    merged.resonance_type = resolver.field(
+        "resonance_type",
+        &local.resonance_type,
+        &remote.resonance_type,
+        None,
+        show_opt,
+    )?;
+    merged.tags = merge_labels(&local.tags, &remote.tags, &[]);
+    if merged.tags != local.tags {
+        resolver
+            .changes
+            .push(format!("tags: {}", merged.tags.join(", ")));
+    }
+    let body = resolver.body(
+        local.body.as_deref().unwrap_or(""),
+        remote.body.as_deref().unwrap_or(""),
+        base.as_ref().map(|b| b.body.as_deref().unwrap_or("")),
+    )?;
+    merged.body = Some(body);
+
+    println!();
+    if resolver.changes.is_empty() {
+        println!("Nothing to take from {}; local already has it", remote.id);
+    } else {
+        println!("{}", "Resolution:".bold());
+        for change in &resolver.changes {
+            println!("  {}", change);
+        }
+    }
+    if opts.dry_run {
+        println!("\n{}", "Dry run: nothing written".yellow());
+        return Ok(());
+    }
+
+    if !resolver.changes.is_empty() {
+        let agent = std::env::var("MX_CURRENT_AGENT").ok();
+        db.backup_content(&local, "merge", agent.as_deref())?;
+        merged.updated_at = Some(Utc::now().to_rfc3339());
+        if let Some(ref body) = merged.body {
+            merged.content_hash = Some(KnowledgeEntry::compute_hash(body));
+        }
+        db.upsert_knowledge(&merged)?;
+    }
+    db.remove_relationship(&remote.id, &local.id, CONFLICT_OF)?;
+    db.add_relationship(&local.id, &remote.id, MERGED_FROM)?;
+
+    println!(
+        "\nMerged {} into {}. The copy is kept; remove it with: mx memory delete {}",
+        remote.id, local.id, remote.id
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(prefer: Option<Side>) -> Resolver {
+        Resolver {
+            prefer,
+            interactive: false,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merges_fields_against_base() {
+        let mut r = resolver(None);
+        let s = |v: &String| v.clone();
+        let (base, changed) = ("old".to_string(), "new".to_string());
+        // One-sided change takes that side, with or without prompting
+        assert_eq!(
+            r.field("title", &base, &changed, Some(&base), s).unwrap(),
+            "new"
+        );
+        assert_eq!(r.changes, vec!["title: old → new"]);
+        // No base: agreement is fine, disagreement needs a decision
+        assert_eq!(r.field("category", &base, &base, None, s).unwrap(), "old");
+        assert!(r.field("category", &base, &changed, None, s).is_err());
+        let mut remote = resolver(Some(Side::Remote));
+        assert_eq!(
+            remote.field("category", &base, &changed, None, s).unwrap(),
+            "new"
+        );
+
+        // Non-overlapping body edits merge cleanly
+        let body = r.body("A\nb\nc\n", "a\nb\nC\n", Some("a\nb\nc\n")).unwrap();
+        assert_eq!(body, "A\nb\nC\n");
+        assert!(r.body("X\n", "Y\n", Some("a\n")).is_err());
+        let mut local = resolver(Some(Side::Local));
+        assert_eq!(local.body("X\n", "Y\n", Some("a\n")).unwrap(), "X\n");
+        assert!(local.changes.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! Session-start entry point: `mx wake`
+//!
+//! The counterpart of `mx session close-out`. Runs the wake cascade for the
+//! current agent and prints it as the prompt-formatted identity block, then
+//! what is waiting: drill cards due for review, open reading-queue items,
+//! memory sessions opened today and still open, and the state recorded by
+//! the last close-out.
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::index::IndexConfig;
+use crate::store::{self, AgentContext, KnowledgeStore};
+use crate::types::Session;
+use crate::{dates, drill, wake_format};
+
+#[derive(clap::Args)]
+pub struct WakeArgs {
+    /// Number of blooms in the cascade
+    #[arg(short, long, default_value = "20")]
+    pub limit: usize,
+
+    /// Include memories activated in last N days
+    #[arg(short, long, default_value = "7")]
+    pub days: i64,
+
+    /// Token budget (estimated) for the identity block
+    #[arg(long)]
+    pub max_tokens: Option<usize>,
+
+    /// Don't update activation counts
+    #[arg(long)]
+    pub no_activate: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The last state recorded by `mx session close-out`
+#[derive(Debug, Serialize)]
+pub struct LastState {
+    pub state: String,
+    pub session: String,
+    pub ended_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Morning {
+    pub identity: String,
+    pub review_due: usize,
+    pub reading_queue: usize,
+    pub open_sessions: Vec<Session>,
+    pub last_state: Option<LastState>,
+}
+
+fn started_today(session: &Session) -> bool {
+    DateTime::parse_from_rfc3339(&session.started_at)
+        .is_ok_and(|t| t.with_timezone(&Local).date_naive() == Local::now().date_naive())
+}
+
+/// The close-out state of the most recently ended session that has one
+fn last_state(sessions: &[Session]) -> Option<LastState> {
+    let mut closed: Vec<&Session> = sessions.iter().filter(|s| s.ended_at.is_some()).collect();
+    closed.sort_by(|a, b| b.ended_at.cmp(&a.ended_at));
+    closed.into_iter().find_map(|session| {
+        let metadata: serde_json::Value =
+            serde_json::from_str(session.metadata.as_deref()?).ok()?;
+        let state = metadata["close_out"]["state"].as_str()?;
+        Some(LastState {
+            state: state.to_string(),
+            session: session.id.clone(),
+            ended_at: session.ended_at.clone(),
+        })
+    })
+}
+
+fn gather(db: &dyn KnowledgeStore, agent: &str, args: &WakeArgs) -> Result<Morning> {
+    let ctx = AgentContext::for_agent(agent.to_string());
+    let cascade = db.wake_cascade(&ctx, args.limit, None, args.days)?;
+    // Passive surfacing, as in `mx memory wake`: count it, don't reset decay
+    if !args.no_activate {
+        let ids = cascade.all_ids();
+        if !ids.is_empty() {
+            db.increment_activation_count(&ids)?;
+        }
+    }
+
+    let sessions = db.list_sessions(None)?;
+    Ok(Morning {
+        identity: wake_format::render_prompt(&cascade, agent, args.max_tokens),
+        review_due: drill::due_count(db, agent)?,
+        reading_queue: db.list_queue(false)?.len(),
+        last_state: last_state(&sessions),
+        open_sessions: sessions
+            .into_iter()
+            .filter(|s| s.ended_at.is_none() && started_today(s))
+            .collect(),
+    })
+}
+
+pub fn run(args: WakeArgs) -> Result<()> {
+    let agent = match std::env::var("MX_CURRENT_AGENT") {
+        Ok(agent) if !agent.is_empty() => agent,
+        _ => bail!("MX_CURRENT_AGENT not set. Cannot wake without identity."),
+    };
+    let config = IndexConfig::default();
+    let db = store::create_store(&config.db_path)?;
+    let morning = gather(db.as_ref(), &agent, &args)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&morning)?);
+        return Ok(());
+    }
+
+    println!("{}", morning.identity);
+    println!();
+    println!(
+        "{} {} due {}",
+        "Review:".bold(),
+        morning.review_due,
+        "(mx memory drill)".dimmed()
+    );
+    println!(
+        "{} {} queued {}",
+        "Reading:".bold(),
+        morning.reading_queue,
+        "(mx queue next)".dimmed()
+    );
+    if morning.open_sessions.is_empty() {
+        println!("{} none open today", "Sessions:".bold());
+    } else {
+        println!("{}", "Sessions:".bold());
+        for session in &morning.open_sessions {
+            println!(
+                "  {} {} since {}",
+                session.id.cyan(),
+                session.session_type_id,
+                dates::display(&session.started_at)
+            );
+        }
+    }
+    match morning.last_state {
+        Some(ref last) => println!(
+            "{} {} {}",
+            "Last state:".bold(),
+            last.state,
+            format!(
+                "({}, {})",
+                last.session,
+                last.ended_at
+                    .as_deref()
+                    .map(dates::display)
+                    .unwrap_or_default()
+            )
+            .dimmed()
+        ),
+        None => println!("{} none recorded", "Last state:".bold()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, ended_at: Option<&str>, metadata: Option<&str>) -> Session {
+        Session {
+            id: id.into(),
+            session_type_id: "manual".into(),
+            project_id: None,
+            started_at: "2026-03-01T09:00:00Z".into(),
+            ended_at: ended_at.map(String::from),
+            metadata: metadata.map(String::from),
+        }
+    }
+
+    #[test]
+    fn last_state_comes_from_latest_close_out() {
+        let sessions = vec![
+            session(
+                "sess-old",
+                Some("2026-03-01T18:00:00Z"),
+                Some(r#"{"close_out":{"state":"@state:crewu|0.10"}}"#),
+            ),
+            session(
+                "sess-new",
+                Some("2026-03-02T18:00:00Z"),
+                Some(r#"{"close_out":{"state":"@state:crewu|0.90"}}"#),
+            ),
+            // Closed later but without a recorded state
+            session("sess-bare", Some("2026-03-03T18:00:00Z"), None),
+            session("sess-open", None, None),
+        ];
+        let last = last_state(&sessions).unwrap();
+        assert_eq!(last.session, "sess-new");
+        assert_eq!(last.state, "@state:crewu|0.90");
+        assert!(last_state(&sessions[2..]).is_none());
+    }
+}
@@ -0,0 +1,229 @@
+//! An entry's neighbourhood, listed under `mx memory show`
+//!
+//! Reading one entry should lead to the ones around it without separate
+//! relationship queries: its relationships in both directions, the blooms
+//! it anchors to and the ones anchored to it, and entries that share at
+//! least two of its tags. Everything is resolved to titles, and only
+//! entries visible to the caller are listed.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::types::Relationship;
+
+/// Entries sharing this many tags count as neighbours
+const MIN_SHARED_TAGS: usize = 2;
+
+/// At most this many tag neighbours are listed, most shared tags first
+const MAX_TAG_NEIGHBORS: usize = 5;
+
+/// Another entry, by id and title
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Neighborhood {
+    /// Relationships from this entry: (type, target)
+    pub outgoing: Vec<(String, Neighbor)>,
+    /// Relationships to this entry: (type, source)
+    pub incoming: Vec<(String, Neighbor)>,
+    /// Blooms this entry anchors to
+    pub anchors: Vec<Neighbor>,
+    /// Blooms anchored to this entry
+    pub anchored_by: Vec<Neighbor>,
+    /// Entries sharing tags with this one, with the shared tags
+    pub shared_tags: Vec<(Neighbor, Vec<String>)>,
+}
+
+impl Neighborhood {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Look up `entry`'s neighbours among what `ctx` can see
+    pub fn load(
+        db: &dyn KnowledgeStore,
+        ctx: &AgentContext,
+        entry: &KnowledgeEntry,
+    ) -> Result<Self> {
+        let visible = db.list_all(ctx)?;
+        let relationships = db.list_relationships_for_entry(&entry.id)?;
+        Ok(Self::build(entry, &visible, &relationships))
+    }
+
+    /// `entry`'s neighbours among `visible`, linked by `relationships`
+    pub fn build(
+        entry: &KnowledgeEntry,
+        visible: &[KnowledgeEntry],
+        relationships: &[Relationship],
+    ) -> Self {
+        let by_id: HashMap<&str, &KnowledgeEntry> = visible
+            .iter()
+            .filter(|e| e.id != entry.id)
+            .map(|e| (e.id.as_str(), e))
+            .collect();
+        let neighbor = |id: &str| {
+            by_id.get(id).map(|e| Neighbor {
+                id: e.id.clone(),
+                title: e.title.clone(),
+            })
+        };
+        let mut hood = Self::default();
+
+        for r in relationships {
+            if r.from_entry_id == entry.id {
+                if let Some(n) = neighbor(&r.to_entry_id) {
+                    hood.outgoing.push((r.relationship_type.clone(), n));
+                }
+            } else if r.to_entry_id == entry.id
+                && let Some(n) = neighbor(&r.from_entry_id)
+            {
+                hood.incoming.push((r.relationship_type.clone(), n));
+            }
+        }
+
+        hood.anchors = entry
+            .anchors
+            .iter()
+            .filter_map(|a| neighbor(&crate::normalize_id(a)))
+            .collect();
+        for other in by_id.values() {
+            if other
+                .anchors
+                .iter()
+                .any(|a| crate::normalize_id(a) == entry.id)
+            {
+                hood.anchored_by.push(neighbor(&other.id).unwrap());
+            }
+        }
+
+        for other in by_id.values() {
+            let shared: Vec<String> = other
+                .tags
+                .iter()
+                .filter(|t| entry.tags.contains(t))
+                .cloned()
+                .collect();
+            if shared.len() >= MIN_SHARED_TAGS {
+                hood.shared_tags
+                    .push((neighbor(&other.id).unwrap(), shared));
+            }
+        }
+
+        hood.outgoing
+            .sort_by(|a, b| (&a.0, &a.1.id).cmp(&(&b.0, &b.1.id)));
+        hood.incoming
+            .sort_by(|a, b| (&a.0, &a.1.id).cmp(&(&b.0, &b.1.id)));
+        hood.anchored_by.sort_by(|a, b| a.id.cmp(&b.id));
+        hood.shared_tags
+            .sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.id.cmp(&b.0.id)));
+        hood.shared_tags.truncate(MAX_TAG_NEIGHBORS);
+        hood
+    }
+
+    /// The section `mx memory show` prints after the body
+    pub fn render(&self) -> String {
+        let line = |n: &Neighbor| format!("{}  {}", n.id, n.title);
+        let mut out = String::new();
+        if !self.outgoing.is_empty() || !self.incoming.is_empty() {
+            out.push_str("Related:\n");
+            for (kind, n) in &self.outgoing {
+                out.push_str(&format!("  -> {:<14} {}\n", kind, line(n)));
+            }
+            for (kind, n) in &self.incoming {
+                out.push_str(&format!("  <- {:<14} {}\n", kind, line(n)));
+            }
+        }
+        if !self.anchors.is_empty() || !self.anchored_by.is_empty() {
+            out.push_str("Anchors:\n");
+            for n in &self.anchors {
+                out.push_str(&format!("  -> {}\n", line(n)));
+            }
+            for n in &self.anchored_by {
+                out.push_str(&format!("  <- {}\n", line(n)));
+            }
+        }
+        if !self.shared_tags.is_empty() {
+            out.push_str("Shared tags:\n");
+            for (n, tags) in &self.shared_tags {
+                out.push_str(&format!("  {}  ({})\n", line(n), tags.join(", ")));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, tags: &[&str], anchors: &[&str]) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": format!("Title {}", id),
+            "tags": tags,
+            "anchors": anchors,
+        }))
+        .unwrap()
+    }
+
+    fn relationship(from: &str, to: &str, kind: &str) -> Relationship {
+        Relationship {
+            id: String::new(),
+            from_entry_id: from.to_string(),
+            to_entry_id: to.to_string(),
+            relationship_type: kind.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn collects_links_anchors_and_tag_neighbors_among_visible_entries() {
+        let me = entry("kn-me", &["rust", "async", "retry"], &["root"]);
+        let visible = vec![
+            me.clone(),
+            entry("kn-root", &[], &[]),
+            entry("kn-child", &["rust"], &["kn-me"]),
+            entry("kn-twin", &["rust", "async", "retry"], &[]),
+            entry("kn-pair", &["async", "rust"], &[]),
+            entry("kn-one", &["rust"], &[]),
+        ];
+        let relationships = vec![
+            relationship("kn-me", "kn-one", "supersedes"),
+            relationship("kn-child", "kn-me", "related"),
+            relationship("kn-me", "kn-hidden", "related"),
+        ];
+
+        let hood = Neighborhood::build(&me, &visible, &relationships);
+        assert_eq!(hood.outgoing.len(), 1);
+        assert_eq!(hood.outgoing[0].0, "supersedes");
+        assert_eq!(hood.incoming[0].1.id, "kn-child");
+        assert_eq!(hood.anchors[0].id, "kn-root");
+        assert_eq!(hood.anchored_by[0].id, "kn-child");
+        let shared: Vec<&str> = hood
+            .shared_tags
+            .iter()
+            .map(|(n, _)| n.id.as_str())
+            .collect();
+        assert_eq!(shared, vec!["kn-twin", "kn-pair"]);
+
+        let text = hood.render();
+        assert!(text.contains("  -> supersedes     kn-one  Title kn-one\n"));
+        assert!(text.contains("  kn-pair  Title kn-pair  (async, rust)\n"));
+        assert!(!text.contains("kn-hidden"));
+    }
+
+    #[test]
+    fn lonely_entry_has_empty_neighborhood() {
+        let me = entry("kn-me", &["rust"], &[]);
+        let hood = Neighborhood::build(&me, std::slice::from_ref(&me), &[]);
+        assert!(hood.is_empty());
+        assert_eq!(hood.render(), "");
+    }
+}
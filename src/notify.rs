@@ -0,0 +1,404 @@
+//! Team notifications: Matrix rooms and Discord webhooks
+//!
+//! Posts knowledge events where the rest of the team will see them:
+//!
+//! - new public entries at or above a resonance threshold (`mx memory add`)
+//! - completed `mx sync pull` / `mx sync push` runs
+//! - digest summaries on demand (`mx notify digest`, schedulable via `mx cron`)
+//!
+//! Configured entirely through environment variables; with none set, every
+//! hook is a no-op:
+//!
+//! - `MX_NOTIFY_DISCORD_WEBHOOK` - Discord webhook URL
+//! - `MX_NOTIFY_MATRIX_HOMESERVER`, `MX_NOTIFY_MATRIX_ROOM`, `MX_NOTIFY_MATRIX_TOKEN`
+//! - `MX_NOTIFY_MIN_RESONANCE` - threshold for entry events (default 8)
+//!
+//! Private entries are never posted. Delivery failures from automatic hooks
+//! are reported as warnings and never fail the command that triggered them.
+
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Utc};
+use reqwest::blocking::Client;
+
+use crate::index::IndexConfig;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{self, AgentContext};
+use crate::text;
+
+const DEFAULT_MIN_RESONANCE: i32 = 8;
+/// Discord rejects message content longer than this
+const DISCORD_LIMIT: usize = 2000;
+const DIGEST_MAX_ENTRIES: usize = 20;
+
+/// A Matrix room to post into
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixTarget {
+    pub homeserver: String,
+    pub room: String,
+    pub token: String,
+}
+
+/// Where notifications go, resolved from the environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifyConfig {
+    pub discord_webhook: Option<String>,
+    pub matrix: Option<MatrixTarget>,
+    pub min_resonance: i32,
+}
+
+impl NotifyConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Pure resolution logic; takes a variable lookup so tests don't touch
+    /// process state
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let get = |key: &str| lookup(key).filter(|v| !v.trim().is_empty());
+
+        let matrix = match (
+            get("MX_NOTIFY_MATRIX_HOMESERVER"),
+            get("MX_NOTIFY_MATRIX_ROOM"),
+            get("MX_NOTIFY_MATRIX_TOKEN"),
+        ) {
+            (Some(homeserver), Some(room), Some(token)) => Some(MatrixTarget {
+                homeserver: homeserver.trim_end_matches('/').to_string(),
+                room,
+                token,
+            }),
+            _ => None,
+        };
+
+        Self {
+            discord_webhook: get("MX_NOTIFY_DISCORD_WEBHOOK"),
+            matrix,
+            min_resonance: get("MX_NOTIFY_MIN_RESONANCE")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MIN_RESONANCE),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.discord_webhook.is_some() || self.matrix.is_some()
+    }
+}
+
+/// Deliver `message` to every configured target. Errors name each failed target.
+pub fn send(config: &NotifyConfig, message: &str) -> Result<()> {
+    if !config.is_enabled() {
+        bail!(
+            "No notification target configured. Set MX_NOTIFY_DISCORD_WEBHOOK or MX_NOTIFY_MATRIX_HOMESERVER/ROOM/TOKEN"
+        );
+    }
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let mut failures = Vec::new();
+    if let Some(ref webhook) = config.discord_webhook
+        && let Err(e) = send_discord(&client, webhook, message)
+    {
+        failures.push(format!("discord: {:#}", e));
+    }
+    if let Some(ref matrix) = config.matrix
+        && let Err(e) = send_matrix(&client, matrix, message)
+    {
+        failures.push(format!("matrix: {:#}", e));
+    }
+
+    if !failures.is_empty() {
+        bail!("Notification failed ({})", failures.join("; "));
+    }
+    Ok(())
+}
+
+fn send_discord(client: &Client, webhook: &str, message: &str) -> Result<()> {
+    client
+        .post(webhook)
+        .json(&serde_json::json!({ "content": text::ellipsize(message, DISCORD_LIMIT) }))
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+fn send_matrix(client: &Client, target: &MatrixTarget, message: &str) -> Result<()> {
+    client
+        .put(matrix_send_url(target, &uuid::Uuid::new_v4().to_string()))
+        .bearer_auth(&target.token)
+        .json(&serde_json::json!({ "msgtype": "m.text", "body": message }))
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+fn matrix_send_url(target: &MatrixTarget, txn_id: &str) -> String {
+    format!(
+        "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+        target.homeserver,
+        urlencoding::encode(&target.room),
+        txn_id
+    )
+}
+
+/// Fire-and-forget delivery for automatic hooks
+fn send_best_effort(config: &NotifyConfig, message: &str) {
+    if let Err(e) = send(config, message) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Messages
+// ---------------------------------------------------------------------------
+
+fn first_line(entry: &KnowledgeEntry) -> Option<&str> {
+    entry
+        .summary
+        .as_deref()
+        .or(entry.body.as_deref())
+        .and_then(|text| text.lines().map(str::trim).find(|l| !l.is_empty()))
+}
+
+fn entry_message(entry: &KnowledgeEntry) -> String {
+    let mut msg = format!(
+        "New entry (resonance {}): {} [{}] {}",
+        entry.resonance, entry.title, entry.category_id, entry.id
+    );
+    if let Some(line) = first_line(entry) {
+        msg.push_str(&format!("\n> {}", text::ellipsize(line, 200)));
+    }
+    if !entry.tags.is_empty() {
+        msg.push_str(&format!("\ntags: {}", entry.tags.join(", ")));
+    }
+    msg
+}
+
+fn digest_message(entries: &[KnowledgeEntry], days: i64) -> String {
+    let mut msg = format!(
+        "mx digest: {} new entr{} in the last {} day{}",
+        entries.len(),
+        if entries.len() == 1 { "y" } else { "ies" },
+        days,
+        if days == 1 { "" } else { "s" }
+    );
+    for entry in entries.iter().take(DIGEST_MAX_ENTRIES) {
+        msg.push_str(&format!(
+            "\n• [{}] {} (r{}, {})",
+            entry.category_id, entry.title, entry.resonance, entry.id
+        ));
+    }
+    if entries.len() > DIGEST_MAX_ENTRIES {
+        msg.push_str(&format!(
+            "\n…and {} more",
+            entries.len() - DIGEST_MAX_ENTRIES
+        ));
+    }
+    msg
+}
+
+// ---------------------------------------------------------------------------
+// Hooks
+// ---------------------------------------------------------------------------
+
+/// Announce a newly added entry if it is public and resonant enough
+pub fn entry_added(entry: &KnowledgeEntry) {
+    let config = NotifyConfig::from_env();
+    if !config.is_enabled()
+        || entry.visibility != "public"
+        || entry.resonance < config.min_resonance
+    {
+        return;
+    }
+    send_best_effort(&config, &entry_message(entry));
+}
+
+/// Announce a finished sync run
+pub fn sync_completed(direction: &str, repo: &str) {
+    let config = NotifyConfig::from_env();
+    if !config.is_enabled() {
+        return;
+    }
+    send_best_effort(
+        &config,
+        &format!("mx sync {} completed for {}", direction, repo),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `mx notify digest`: summarize public entries created in the last `days`
+pub fn digest(days: i64, min_resonance: Option<i32>, dry_run: bool, raw: bool) -> Result<()> {
+    let config = NotifyConfig::from_env();
+    let index_config = IndexConfig::default();
+    let db = store::create_store(&index_config.db_path)?;
+
+    let cutoff = Utc::now() - Duration::days(days);
+    let min = min_resonance.unwrap_or(0);
+    let mut entries: Vec<KnowledgeEntry> = db
+        .list_all(&AgentContext::public_only())?
+        .into_iter()
+        .filter(|e| e.visibility == "public" && e.resonance >= min)
+        .filter(|e| {
+            e.created_at
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| t.with_timezone(&Utc) >= cutoff)
+        })
+        .collect();
+    entries.sort_by(|a, b| b.resonance.cmp(&a.resonance).then(a.title.cmp(&b.title)));
+
+    let message = digest_message(&entries, days);
+    if dry_run {
+        crate::output::print_markdown(&message, raw);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No new entries in the last {} days; nothing sent.", days);
+        return Ok(());
+    }
+    send(&config, &message)?;
+    println!("Sent digest of {} entries", entries.len());
+    Ok(())
+}
+
+/// `mx notify test`: send a message to verify configuration
+pub fn test(message: Option<String>) -> Result<()> {
+    let config = NotifyConfig::from_env();
+    send(
+        &config,
+        message.as_deref().unwrap_or("mx notification test"),
+    )?;
+    println!("Sent.");
+    Ok(())
+}
+
+/// `mx notify status`: show configured targets (secrets omitted)
+pub fn status() -> Result<()> {
+    let config = NotifyConfig::from_env();
+    println!(
+        "Discord: {}",
+        if config.discord_webhook.is_some() {
+            "configured"
+        } else {
+            "not configured"
+        }
+    );
+    match config.matrix {
+        Some(ref m) => println!("Matrix:  {} on {}", m.room, m.homeserver),
+        None => println!("Matrix:  not configured"),
+    }
+    println!("Entry threshold: resonance >= {}", config.min_resonance);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry(title: &str, resonance: i32) -> KnowledgeEntry {
+        KnowledgeEntry {
+            id: "kn-test".to_string(),
+            category_id: "pattern".to_string(),
+            title: title.to_string(),
+            body: Some("\nFirst real line\nsecond".to_string()),
+            summary: None,
+            applicability: vec![],
+            source_project_id: None,
+            source_agent_id: None,
+            file_path: None,
+            tags: vec!["rust".to_string()],
+            created_at: None,
+            updated_at: None,
+            content_hash: None,
+            source_type_id: None,
+            entry_type_id: None,
+            session_id: None,
+            ephemeral: false,
+            content_type_id: None,
+            owner: None,
+            visibility: "public".to_string(),
+            resonance,
+            resonance_type: None,
+            last_activated: None,
+            activation_count: 0,
+            decay_rate: 0.0,
+            anchors: vec![],
+            wake_phrases: vec![],
+            wake_order: None,
+            wake_phrase: None,
+            embedding: None,
+            embedding_model: None,
+            embedded_at: None,
+            format: "markdown".to_string(),
+            effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    fn config(vars: &[(&str, &str)]) -> NotifyConfig {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        NotifyConfig::from_lookup(|k| vars.get(k).cloned())
+    }
+
+    #[test]
+    fn config_from_env_vars() {
+        let empty = config(&[]);
+        assert!(!empty.is_enabled());
+        assert_eq!(empty.min_resonance, DEFAULT_MIN_RESONANCE);
+
+        // Matrix needs all three variables
+        let partial = config(&[("MX_NOTIFY_MATRIX_ROOM", "!room:example.org")]);
+        assert!(partial.matrix.is_none());
+
+        let full = config(&[
+            ("MX_NOTIFY_MATRIX_HOMESERVER", "https://matrix.example.org/"),
+            ("MX_NOTIFY_MATRIX_ROOM", "!room:example.org"),
+            ("MX_NOTIFY_MATRIX_TOKEN", "secret"),
+            ("MX_NOTIFY_MIN_RESONANCE", "6"),
+        ]);
+        assert!(full.is_enabled());
+        assert_eq!(full.min_resonance, 6);
+        assert_eq!(
+            matrix_send_url(full.matrix.as_ref().unwrap(), "t1"),
+            "https://matrix.example.org/_matrix/client/v3/rooms/%21room%3Aexample.org/send/m.room.message/t1"
+        );
+    }
+
+    #[test]
+    fn formats_messages() {
+        let msg = entry_message(&entry("Retry with backoff", 9));
+        assert!(msg.starts_with("New entry (resonance 9): Retry with backoff [pattern] kn-test"));
+        assert!(msg.contains("> First real line"));
+        assert!(msg.contains("tags: rust"));
+
+        let entries: Vec<_> = (0..25).map(|i| entry(&format!("e{}", i), 5)).collect();
+        let digest = digest_message(&entries, 7);
+        assert!(digest.starts_with("mx digest: 25 new entries in the last 7 days"));
+        assert!(digest.ends_with("…and 5 more"));
+        assert_eq!(
+            digest_message(&entries[..1], 1).lines().next(),
+            Some("mx digest: 1 new entry in the last 1 day")
+        );
+    }
+
+    #[test]
+    fn truncates_long_messages() {
+        assert_eq!(text::ellipsize("short", 10), "short");
+        let long = text::ellipsize(&"x".repeat(3000), DISCORD_LIMIT);
+        assert_eq!(long.chars().count(), DISCORD_LIMIT);
+        assert!(long.ends_with('…'));
+    }
+}
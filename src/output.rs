@@ -0,0 +1,300 @@
+//! Terminal output: color control, paging, and tables
+//!
+//! - `--color auto|always|never` (global). `auto` colors only a terminal and
+//!   honours `NO_COLOR` and `CLICOLOR_FORCE`.
+//! - Long outputs (list/show/search/export, codex list/read) go through a
+//!   pager when stdout is a terminal: `MX_PAGER`, else `PAGER`, else `less`
+//!   (run with `LESS=FRX`, so short output is printed as-is). `--no-pager`,
+//!   or a pager of `cat` or the empty string, turns it off.
+//! - [`Table`] renders the column listings (codex list, memory list, sync
+//!   status) with one look.
+//! - [`print_markdown`] renders entry bodies and transcripts (headings,
+//!   lists, code blocks, tables) with termimad when colors are on. `--raw`
+//!   on those commands prints the text as stored.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::Colorize;
+use std::io::{self, IsTerminal};
+use std::process::{Command, Stdio};
+
+use crate::text;
+
+/// Set in the re-executed child whose stdout feeds the pager
+const PAGER_CHILD_ENV: &str = "MX_PAGER_ACTIVE";
+
+/// Commands whose output is long enough to page
+const PAGED_COMMANDS: &[&str] = &[
+    "memory list",
+    "memory show",
+    "memory search",
+    "memory export",
+    "memory diff",
+    "codex list",
+    "codex read",
+    "codex search",
+    "log",
+    "history",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+fn env_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Decide whether to color, given the environment and whether stdout is a terminal
+fn use_color(mode: ColorMode, no_color: bool, force: bool, tty: bool) -> bool {
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto if no_color => false,
+        ColorMode::Auto => force || tty,
+    }
+}
+
+/// Apply the color mode for the rest of the process
+pub fn init_color(mode: ColorMode) {
+    let on = use_color(
+        mode,
+        std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()),
+        env_set("CLICOLOR_FORCE"),
+        io::stdout().is_terminal(),
+    );
+    colored::control::set_override(on);
+}
+
+/// The pager command line, or None when paging is disabled
+fn pager_command_with(mx_pager: Option<&str>, pager: Option<&str>) -> Option<String> {
+    let cmd = mx_pager.or(pager).unwrap_or("less").trim();
+    (!cmd.is_empty() && cmd != "cat").then(|| cmd.to_string())
+}
+
+/// Whether this invocation's output should go through the pager
+pub fn should_page(command_name: &str, no_pager: bool) -> bool {
+    !no_pager
+        && cfg!(unix)
+        && PAGED_COMMANDS.contains(&command_name)
+        && io::stdout().is_terminal()
+        && std::env::var_os(PAGER_CHILD_ENV).is_none()
+}
+
+/// Re-run this command with its stdout piped into the pager, then exit with
+/// the command's status. Falls back to running unpaged if the pager can't start.
+pub fn run_paged(args: &[String], color: ColorMode) -> Result<()> {
+    let Some(pager_cmd) = pager_command_with(
+        std::env::var("MX_PAGER").ok().as_deref(),
+        std::env::var("PAGER").ok().as_deref(),
+    ) else {
+        return Ok(());
+    };
+
+    let mut pager = Command::new("sh");
+    pager.arg("-c").arg(&pager_cmd).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let Ok(mut pager) = pager.spawn() else {
+        return Ok(());
+    };
+    let pager_stdin = pager.stdin.take().context("Pager has no stdin")?;
+
+    let mut child = Command::new(std::env::current_exe()?);
+    child
+        .args(&args[1..])
+        .env(PAGER_CHILD_ENV, "1")
+        .stdout(Stdio::from(pager_stdin));
+    // The child no longer sees a terminal; carry the color decision over
+    if use_color(
+        color,
+        std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()),
+        env_set("CLICOLOR_FORCE"),
+        true,
+    ) {
+        child.env("CLICOLOR_FORCE", "1");
+    }
+    let status = child
+        .status()
+        .context("Failed to re-run command for pager")?;
+    // Close our copy of the pipe so the pager sees end of input
+    drop(child);
+    let _ = pager.wait();
+
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// A plain column table: bold header, dashed rule, left-aligned columns
+/// separated by two spaces. Cells must not contain color codes.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<const N: usize>(headers: [&str; N]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// A table whose columns are only known at run time
+    pub fn with_headers<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        Self {
+            headers: headers.into_iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.rows
+            .push(cells.into_iter().map(|c| c.to_string()).collect());
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| text::width(h)).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate().take(widths.len()) {
+                widths[i] = widths[i].max(text::width(cell));
+            }
+        }
+        widths
+    }
+
+    fn line(cells: &[String], widths: &[usize]) -> String {
+        let last = widths.len().saturating_sub(1);
+        let mut out = String::new();
+        for (i, width) in widths.iter().enumerate() {
+            let cell = cells.get(i).map(String::as_str).unwrap_or("");
+            if i == last {
+                out.push_str(cell);
+            } else {
+                let pad = width - text::width(cell).min(*width);
+                out.push_str(cell);
+                out.push_str(&" ".repeat(pad + 2));
+            }
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Header, rule, and rows as plain text lines
+    fn render_lines(&self) -> Vec<String> {
+        let widths = self.widths();
+        let header = Self::line(&self.headers, &widths);
+        let rule_width = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+        let mut lines = vec![header, "-".repeat(rule_width)];
+        lines.extend(self.rows.iter().map(|row| Self::line(row, &widths)));
+        lines
+    }
+
+    pub fn print(&self) {
+        for (i, line) in self.render_lines().into_iter().enumerate() {
+            if i == 0 {
+                println!("{}", line.bold());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+/// `text` as the terminal should show it: rendered markdown when `styled`,
+/// else unchanged. Ends with a newline either way.
+fn markdown_text(text: &str, styled: bool) -> String {
+    if styled {
+        termimad::MadSkin::default().term_text(text).to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// Print markdown rendered for the terminal, or as-is when `raw` or when
+/// colors are off (piped output stays plain)
+pub fn print_markdown(text: &str, raw: bool) {
+    let styled = !raw && colored::control::SHOULD_COLORIZE.should_colorize();
+    print!("{}", markdown_text(text, styled));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_mode_resolution() {
+        assert!(use_color(ColorMode::Always, true, false, false));
+        assert!(!use_color(ColorMode::Never, false, true, true));
+        assert!(!use_color(ColorMode::Auto, true, true, true));
+        assert!(use_color(ColorMode::Auto, false, true, false));
+        assert!(!use_color(ColorMode::Auto, false, false, false));
+    }
+
+    #[test]
+    fn pager_resolution() {
+        assert_eq!(pager_command_with(None, None).as_deref(), Some("less"));
+        assert_eq!(
+            pager_command_with(Some("bat -p"), Some("more")).as_deref(),
+            Some("bat -p")
+        );
+        assert_eq!(pager_command_with(None, Some("cat")), None);
+        assert_eq!(pager_command_with(Some(""), Some("less")), None);
+    }
+
+    #[test]
+    fn renders_aligned_columns() {
+        let mut table = Table::new(["ID", "TITLE", "TAGS"]);
+        table.row(["kn-1", "Retries", "net"]);
+        table.row(["kn-12345", "Backoff é", ""]);
+        assert_eq!(
+            table.render_lines(),
+            vec![
+                "ID        TITLE      TAGS",
+                "-------------------------",
+                "kn-1      Retries    net",
+                "kn-12345  Backoff é",
+            ]
+        );
+    }
+
+    #[test]
+    fn aligns_wide_and_combining_characters_by_display_width() {
+        let mut table = Table::new(["TITLE", "TAGS"]);
+        table.row(["世界", "cjk"]);
+        table.row(["Cafe\u{301}", "mark"]);
+        table.row(["🇳🇿 kiwi", "flag"]);
+        assert_eq!(
+            table.render_lines(),
+            vec![
+                "TITLE    TAGS",
+                "-------------",
+                "世界     cjk",
+                "Cafe\u{301}     mark",
+                "🇳🇿 kiwi  flag",
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_renders_only_when_styled() {
+        let text = "# Retry\n\n- **backoff**\n\n```\nsleep(1)\n```";
+        assert_eq!(markdown_text(text, false), format!("{}\n", text));
+        let styled = markdown_text(text, true);
+        assert!(styled.contains('\u{1b}'));
+        assert!(!styled.contains("**"));
+        assert!(!styled.contains("```"));
+        assert!(styled.contains("sleep(1)"));
+    }
+}
@@ -0,0 +1,387 @@
+//! Task briefings: `mx pack build`
+//!
+//! Assembles a markdown context pack for handing an agent a specific task,
+//! from three sources:
+//!
+//! - knowledge entries matching `--query` (the `mx memory bulk` query
+//!   syntax), highest resonance first,
+//! - GitHub issues given as `owner/repo#123`, read from the local sync cache
+//!   (`mx sync pull` first),
+//! - excerpts of codex archives given by archive id: the turns that mention
+//!   a query keyword, or the last turns when nothing matches.
+//!
+//! Every section carries a footnote saying where it came from. Sections are
+//! added in that order until `--max-tokens` (estimated) is spent; whatever
+//! doesn't fit is left out and reported.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::bulk::Query;
+use crate::index::IndexConfig;
+use crate::store::{self, AgentContext};
+use crate::sync::yaml::store::YamlStore;
+use crate::{codex, tokens};
+
+/// Turns taken from an archive when none match the query
+const TAIL_TURNS: usize = 6;
+
+#[derive(clap::Subcommand)]
+pub enum PackCommands {
+    /// Build a context pack from knowledge, issues, and session archives
+    Build(BuildArgs),
+}
+
+#[derive(clap::Args)]
+pub struct BuildArgs {
+    /// Knowledge query (e.g. 'tag:surrealdb retry'; see `mx memory bulk`)
+    #[arg(short, long)]
+    pub query: Option<String>,
+
+    /// Issues to include, as owner/repo#number (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub issues: Vec<String>,
+
+    /// Codex archive ids to excerpt (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub sessions: Vec<String>,
+
+    /// Token budget (estimated) for the whole pack
+    #[arg(long, default_value = "8000")]
+    pub max_tokens: usize,
+
+    /// Write to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// One unit of the pack, kept or dropped whole
+struct Section {
+    heading: String,
+    body: String,
+    source: String,
+}
+
+impl Section {
+    fn tokens(&self) -> usize {
+        tokens::estimate(&self.heading)
+            + tokens::estimate(&self.body)
+            + tokens::estimate(&self.source)
+    }
+}
+
+pub fn run(cmd: PackCommands) -> Result<()> {
+    match cmd {
+        PackCommands::Build(args) => build(args),
+    }
+}
+
+fn agent_context() -> AgentContext {
+    match std::env::var("MX_CURRENT_AGENT") {
+        Ok(agent) if !agent.is_empty() => AgentContext::for_agent(agent),
+        _ => AgentContext::public_only(),
+    }
+}
+
+fn knowledge_sections(query: &Query) -> Result<Vec<Section>> {
+    let config = IndexConfig::default();
+    let db = store::create_store(&config.db_path)?;
+    let mut entries: Vec<_> = db
+        .list_all(&agent_context())?
+        .into_iter()
+        .filter(|e| query.matches(e))
+        .collect();
+    entries.sort_by(|a, b| b.resonance.cmp(&a.resonance).then(a.id.cmp(&b.id)));
+    Ok(entries
+        .into_iter()
+        .map(|e| {
+            let mut source = format!("{} ({}", e.id, e.category_id);
+            if e.resonance > 0 {
+                let _ = write!(source, ", resonance {}", e.resonance);
+            }
+            if let Some(ref updated) = e.updated_at.clone().or(e.created_at.clone()) {
+                let _ = write!(source, ", updated {}", crate::text::truncate(updated, 10));
+            }
+            source.push(')');
+            Section {
+                heading: e.title,
+                body: e.body.or(e.summary).unwrap_or_default(),
+                source,
+            }
+        })
+        .collect())
+}
+
+/// Split `owner/repo#123`
+fn parse_issue(spec: &str) -> Result<(&str, u64)> {
+    let Some((repo, number)) = spec.rsplit_once('#') else {
+        bail!("Issue '{}' must look like owner/repo#123", spec);
+    };
+    if repo.split('/').count() != 2 {
+        bail!("Issue '{}' must look like owner/repo#123", spec);
+    }
+    let number = number
+        .parse()
+        .with_context(|| format!("Issue '{}' has no valid number", spec))?;
+    Ok((repo, number))
+}
+
+fn issue_section(spec: &str) -> Result<Section> {
+    let (repo, number) = parse_issue(spec)?;
+    let store = YamlStore::new(crate::sync::default_sync_dir(repo));
+    let Some((path, yaml)) = store.find_by_issue_number(number)? else {
+        bail!(
+            "{} is not in the local sync cache; run `mx sync pull {}` first",
+            spec,
+            repo
+        );
+    };
+
+    let mut body = yaml.body().trim().to_string();
+    for comment in &yaml.comments {
+        let _ = write!(
+            body,
+            "\n\n> **{}:** {}",
+            comment.author,
+            comment.body.trim()
+        );
+    }
+    let mut source = format!("{}#{}", repo, number);
+    if let Some(ref state) = yaml.metadata.state {
+        let _ = write!(source, " ({})", state);
+    }
+    if let Some(ref synced) = yaml.metadata.last_synced {
+        let _ = write!(source, ", synced {}", synced.updated_at);
+    }
+    let _ = write!(source, ", {}", path.display());
+    Ok(Section {
+        heading: format!("{}#{}: {}", repo, number, yaml.title()),
+        body,
+        source,
+    })
+}
+
+/// A transcript split into speaker turns (paragraphs starting `**Name:**`)
+fn turns(transcript: &str) -> Vec<String> {
+    let mut turns: Vec<String> = Vec::new();
+    for paragraph in transcript.split("\n\n") {
+        let starts_turn = paragraph.starts_with("**")
+            && paragraph
+                .find(":**")
+                .is_some_and(|i| !paragraph[..i].contains('\n'));
+        match turns.last_mut() {
+            Some(turn) if !starts_turn => {
+                turn.push_str("\n\n");
+                turn.push_str(paragraph);
+            }
+            _ if !paragraph.trim().is_empty() => turns.push(paragraph.to_string()),
+            _ => {}
+        }
+    }
+    turns
+}
+
+/// Turns mentioning any keyword, or the last few when none do, with their
+/// 1-based positions
+fn excerpt(transcript: &str, keywords: &[&str]) -> Vec<(usize, String)> {
+    let all: Vec<(usize, String)> = turns(transcript)
+        .into_iter()
+        .enumerate()
+        .map(|(i, t)| (i + 1, t))
+        .collect();
+    let matching: Vec<(usize, String)> = all
+        .iter()
+        .filter(|(_, t)| {
+            let lower = t.to_lowercase();
+            keywords.iter().any(|k| lower.contains(k))
+        })
+        .cloned()
+        .collect();
+    if !matching.is_empty() {
+        return matching;
+    }
+    let skip = all.len().saturating_sub(TAIL_TURNS);
+    all.into_iter().skip(skip).collect()
+}
+
+fn session_section(id: &str, keywords: &[&str]) -> Result<Section> {
+    let (archive_dir, transcript) = codex::transcript(id)?;
+    let picked = excerpt(&transcript, keywords);
+    let numbers: Vec<String> = picked.iter().map(|(n, _)| n.to_string()).collect();
+    let name = archive_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| id.to_string());
+    Ok(Section {
+        heading: format!("Session {}", name),
+        body: picked
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        source: format!(
+            "codex archive {}, turns {}",
+            archive_dir.display(),
+            numbers.join(", ")
+        ),
+    })
+}
+
+/// Fit sections into `budget` and render the pack
+fn render(
+    groups: Vec<(&str, Vec<Section>)>,
+    query: Option<&str>,
+    budget: usize,
+) -> (String, usize) {
+    let mut out = String::from("# Context pack\n\n");
+    let _ = write!(out, "> Built {}", Utc::now().format("%Y-%m-%d %H:%M UTC"));
+    if let Some(q) = query {
+        let _ = write!(out, " for `{}`", q);
+    }
+    out.push_str("\n\n");
+
+    let mut used = tokens::estimate(&out);
+    let mut footnotes = Vec::new();
+    let mut dropped = 0;
+    for (title, sections) in groups {
+        let mut body = String::new();
+        for section in sections {
+            let cost = section.tokens() + 8;
+            if used + cost > budget {
+                dropped += 1;
+                continue;
+            }
+            used += cost;
+            footnotes.push(section.source);
+            let _ = write!(
+                body,
+                "### {} [^{}]\n\n{}\n\n",
+                section.heading,
+                footnotes.len(),
+                section.body.trim()
+            );
+        }
+        if !body.is_empty() {
+            let _ = write!(out, "## {}\n\n{}", title, body);
+        }
+    }
+
+    if !footnotes.is_empty() {
+        out.push_str("---\n\n");
+        for (i, source) in footnotes.iter().enumerate() {
+            let _ = writeln!(out, "[^{}]: {}", i + 1, source);
+        }
+    }
+    (out, dropped)
+}
+
+fn build(args: BuildArgs) -> Result<()> {
+    if args.query.is_none() && args.issues.is_empty() && args.sessions.is_empty() {
+        bail!("Nothing to pack: give --query, --issues, or --sessions");
+    }
+    let query = args.query.as_deref().map(Query::parse).transpose()?;
+    let keywords = query.as_ref().map(|q| q.keywords()).unwrap_or_default();
+
+    let knowledge = match query {
+        Some(ref q) => knowledge_sections(q)?,
+        None => Vec::new(),
+    };
+    let issues = args
+        .issues
+        .iter()
+        .map(|spec| issue_section(spec))
+        .collect::<Result<Vec<_>>>()?;
+    let sessions = args
+        .sessions
+        .iter()
+        .map(|id| session_section(id, &keywords))
+        .collect::<Result<Vec<_>>>()?;
+
+    let (pack, dropped) = render(
+        vec![
+            ("Knowledge", knowledge),
+            ("Issues", issues),
+            ("Session excerpts", sessions),
+        ],
+        args.query.as_deref(),
+        args.max_tokens,
+    );
+
+    match args.output {
+        Some(ref path) => {
+            std::fs::write(path, &pack)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "Wrote {} (~{} tokens)",
+                path.display(),
+                tokens::estimate(&pack)
+            );
+        }
+        None => print!("{}", pack),
+    }
+    if dropped > 0 {
+        eprintln!(
+            "{} section(s) left out to fit --max-tokens {}",
+            dropped, args.max_tokens
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = "**Ada:** How do we retry SurrealDB writes?\n\n\
+        **Claude:** Use backoff.\n\nStart at 100ms.\n\n\
+        **Ada:** And the tests?\n\n\
+        **Claude:** Mock the clock.\n\n";
+
+    fn section(heading: &str, body: &str) -> Section {
+        Section {
+            heading: heading.into(),
+            body: body.into(),
+            source: format!("{} source", heading),
+        }
+    }
+
+    #[test]
+    fn excerpts_matching_turns_or_tail() {
+        assert_eq!(turns(TRANSCRIPT).len(), 4);
+        let picked = excerpt(TRANSCRIPT, &["retry", "backoff"]);
+        assert_eq!(picked.len(), 2);
+        assert_eq!(picked[1].0, 2);
+        assert!(picked[1].1.contains("Start at 100ms"));
+        assert_eq!(excerpt(TRANSCRIPT, &["nothing"]).len(), 4);
+    }
+
+    #[test]
+    fn renders_footnotes_within_budget() {
+        let (pack, dropped) = render(
+            vec![
+                (
+                    "Knowledge",
+                    vec![
+                        section("Retry", "Backoff"),
+                        section("Big", &"word ".repeat(400)),
+                    ],
+                ),
+                ("Issues", vec![section("mx#1", "Flaky sync")]),
+            ],
+            Some("retry"),
+            120,
+        );
+        assert_eq!(dropped, 1);
+        assert!(pack.contains("### Retry [^1]"));
+        assert!(pack.contains("### mx#1 [^2]"));
+        assert!(pack.contains("[^2]: mx#1 source"));
+        assert!(!pack.contains("Big"));
+        assert_eq!(
+            parse_issue("coryzibell/mx#123").unwrap(),
+            ("coryzibell/mx", 123)
+        );
+        assert!(parse_issue("mx#123").is_err());
+    }
+}
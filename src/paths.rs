@@ -8,31 +8,101 @@
 //!
 //! Subsystem-specific overrides (`MX_CODEX_PATH`, `MX_MEMORY_PATH`, etc.)
 //! continue to work -- they take precedence over the derived path when set.
+//!
+//! Profiles (`--profile <name>` or `MX_PROFILE`) namespace everything under
+//! `$MX_HOME/profiles/<name>/`, so independent environments (work vs personal,
+//! test vs production memory) can coexist without sharing a database, codex,
+//! or caches. The `default` profile is the unnamespaced base directory.
 
+use anyhow::{Result, bail};
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 static MX_HOME: OnceLock<PathBuf> = OnceLock::new();
+static MX_PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Name of the implicit profile that maps to the base directory.
+pub const DEFAULT_PROFILE: &str = "default";
 
-/// Pure resolution logic for MX_HOME. Takes the env var value as a parameter
-/// so callers (especially tests) don't need to touch process state.
-fn resolve_mx_home_with(env_val: Option<&str>) -> PathBuf {
-    if let Some(val) = env_val
-        && !val.is_empty()
+/// Validate a profile name: non-empty, ASCII alphanumerics, `-` and `_` only.
+/// Keeps profile names safe to use as a single path component.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("Profile name cannot be empty");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
     {
-        return PathBuf::from(val);
+        bail!(
+            "Invalid profile name '{}': use only letters, digits, '-' and '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Pure resolution logic for the active profile. Empty values and the
+/// `default` profile resolve to `None` (no namespacing).
+fn resolve_profile_with(val: Option<&str>) -> Option<String> {
+    match val {
+        Some(name) if !name.is_empty() && name != DEFAULT_PROFILE => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Select the active profile for this process.
+///
+/// Must be called before any path is resolved (i.e. first thing in `main`);
+/// fails if the profile was already fixed, either by an earlier call or by a
+/// path lookup that fell back to `MX_PROFILE`.
+pub fn set_profile(name: Option<&str>) -> Result<()> {
+    if let Some(n) = name {
+        validate_profile_name(n)?;
+    }
+    let env_val = std::env::var("MX_PROFILE").ok();
+    let resolved = resolve_profile_with(name.or(env_val.as_deref()));
+    if let Some(ref n) = resolved {
+        validate_profile_name(n)?;
+    }
+    if MX_PROFILE.set(resolved).is_err() {
+        bail!("Profile already initialized for this process");
+    }
+    Ok(())
+}
+
+/// The active profile, if any.
+///
+/// Priority: `--profile` (via `set_profile`) > `MX_PROFILE` env var > none.
+pub fn profile() -> Option<&'static str> {
+    MX_PROFILE
+        .get_or_init(|| resolve_profile_with(std::env::var("MX_PROFILE").ok().as_deref()))
+        .as_deref()
+}
+
+/// Pure resolution logic for MX_HOME. Takes the env var value and profile as
+/// parameters so callers (especially tests) don't need to touch process state.
+fn resolve_mx_home_with(env_val: Option<&str>, profile: Option<&str>) -> PathBuf {
+    let base = match env_val {
+        Some(val) if !val.is_empty() => PathBuf::from(val),
+        _ => dirs::home_dir()
+            .expect("Could not determine home directory")
+            .join(".mx"),
+    };
+    match profile {
+        Some(name) => base.join("profiles").join(name),
+        None => base,
     }
-    dirs::home_dir()
-        .expect("Could not determine home directory")
-        .join(".mx")
 }
 
 /// Resolve the MX_HOME base directory.
 ///
-/// Priority: `MX_HOME` env var > `~/.mx/`
+/// Priority: `MX_HOME` env var > `~/.mx/`, then namespaced by the active
+/// profile (`<base>/profiles/<name>/`).
 /// Result is cached for the lifetime of the process.
 pub fn mx_home() -> &'static PathBuf {
-    MX_HOME.get_or_init(|| resolve_mx_home_with(std::env::var("MX_HOME").ok().as_deref()))
+    MX_HOME
+        .get_or_init(|| resolve_mx_home_with(std::env::var("MX_HOME").ok().as_deref(), profile()))
 }
 
 /// Emit a startup note to stderr when MX_HOME is not explicitly configured.
@@ -75,6 +145,58 @@ pub fn agents_dir() -> PathBuf {
     mx_home().join("agents")
 }
 
+/// Content-addressed blob store: `$MX_HOME/blobs/`
+pub fn blobs_dir() -> PathBuf {
+    mx_home().join("blobs")
+}
+
+/// JSONL snapshots from `mx memory export --backup`: `$MX_HOME/backups/`
+pub fn backups_dir() -> PathBuf {
+    mx_home().join("backups")
+}
+
+/// Intents of multi-step operations in flight: `$MX_HOME/journal/`
+pub fn journal_dir() -> PathBuf {
+    mx_home().join("journal")
+}
+
+/// Progress of interrupted long runs: `$MX_HOME/checkpoints/`
+pub fn checkpoint_dir() -> PathBuf {
+    mx_home().join("checkpoints")
+}
+
+/// Watermarks from `mx memory replicate`: `$MX_HOME/replication.json`
+pub fn replication_state() -> PathBuf {
+    mx_home().join("replication.json")
+}
+
+/// When `mx memory decay` last lowered each entry: `$MX_HOME/decay.json`
+pub fn decay_state() -> PathBuf {
+    mx_home().join("decay.json")
+}
+
+/// Closed-project archives from `mx memory projects close --archive`, and entries
+/// archived by `mx memory decay --archive-below`: `$MX_HOME/archives/`
+pub fn archives_dir() -> PathBuf {
+    mx_home().join("archives")
+}
+
+/// Scheduled job definitions: `$MX_HOME/cron.yaml`
+pub fn cron_file() -> PathBuf {
+    mx_home().join("cron.yaml")
+}
+
+/// User configuration for the active profile: `$MX_HOME/config.yaml`
+pub fn config_file() -> PathBuf {
+    mx_home().join("config.yaml")
+}
+
+/// The base directory's config.yaml, whatever the profile. Only aliases live
+/// here: they are expanded before `--profile` has been parsed.
+pub fn base_config_file() -> PathBuf {
+    resolve_mx_home_with(std::env::var("MX_HOME").ok().as_deref(), None).join("config.yaml")
+}
+
 /// Pure resolution logic for codex directory. Takes the `MX_CODEX_PATH` env
 /// var value as a parameter so callers (especially tests) don't need to touch
 /// process state.
@@ -108,24 +230,50 @@ mod tests {
 
     #[test]
     fn mx_home_default_when_unset() {
-        let result = resolve_mx_home_with(None);
+        let result = resolve_mx_home_with(None, None);
         let expected = dirs::home_dir().unwrap().join(".mx");
         assert_eq!(result, expected);
     }
 
     #[test]
     fn mx_home_respects_env_var() {
-        let result = resolve_mx_home_with(Some("/tmp/test-mx-home"));
+        let result = resolve_mx_home_with(Some("/tmp/test-mx-home"), None);
         assert_eq!(result, PathBuf::from("/tmp/test-mx-home"));
     }
 
     #[test]
     fn mx_home_empty_env_is_default() {
-        let result = resolve_mx_home_with(Some(""));
+        let result = resolve_mx_home_with(Some(""), None);
         let expected = dirs::home_dir().unwrap().join(".mx");
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn mx_home_namespaced_by_profile() {
+        let result = resolve_mx_home_with(Some("/tmp/test-mx-home"), Some("work"));
+        assert_eq!(result, PathBuf::from("/tmp/test-mx-home/profiles/work"));
+    }
+
+    #[test]
+    fn default_profile_is_unnamespaced() {
+        assert_eq!(resolve_profile_with(Some("default")), None);
+        assert_eq!(resolve_profile_with(Some("")), None);
+        assert_eq!(resolve_profile_with(None), None);
+        assert_eq!(
+            resolve_profile_with(Some("personal")),
+            Some("personal".to_string())
+        );
+    }
+
+    #[test]
+    fn profile_name_validation() {
+        assert!(validate_profile_name("work").is_ok());
+        assert!(validate_profile_name("test_env-2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../escape").is_err());
+        assert!(validate_profile_name("a/b").is_err());
+    }
+
     #[test]
     fn derived_dirs_under_mx_home() {
         // Test real derived-path functions against the cached mx_home().
@@ -158,6 +306,10 @@ mod tests {
 
         let sync = sync_cache_dir("owner/repo");
         assert!(sync.starts_with(home), "sync_cache_dir not under mx_home");
+
+        assert_eq!(config_file(), home.join("config.yaml"));
+        let base = resolve_mx_home_with(std::env::var("MX_HOME").ok().as_deref(), None);
+        assert_eq!(base_config_file(), base.join("config.yaml"));
     }
 
     #[test]
@@ -0,0 +1,184 @@
+//! Progress and cancellation for long network runs
+//!
+//! `sync pull/push`, `wiki sync`, `codex save --all` and `memory embed --all`
+//! work through a list one item at a time. Each shows a [`Progress`] bar on
+//! stderr (items done/total, ETA; hidden when stderr isn't a terminal) and
+//! calls [`catch_interrupts`] first, so Ctrl-C finishes the item in hand
+//! instead of killing the process halfway through a write. The loop checks
+//! [`interrupted`] between items, records where it got to, and fails with
+//! [`MxError::Interrupted`]. A second Ctrl-C quits at once.
+//!
+//! Runs whose items leave no trace of being done keep a [`Checkpoint`] under
+//! `$MX_HOME/checkpoints/`; the next run skips what it lists and removes it on
+//! completion. The others resume naturally: pushed files carry their sync
+//! state, and saved archives have manifests.
+
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::MxError;
+use crate::paths;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static HANDLER: Once = Once::new();
+
+/// Turn Ctrl-C into a request to stop after the current item
+pub fn catch_interrupts() {
+    HANDLER.call_once(|| {
+        let installed = ctrlc::set_handler(|| {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(130);
+            }
+            eprintln!("\nStopping after the current item (Ctrl-C again to quit now)");
+        });
+        if let Err(e) = installed {
+            eprintln!("Warning: Ctrl-C will stop immediately: {}", e);
+        }
+    });
+}
+
+/// Whether Ctrl-C has been pressed since [`catch_interrupts`]
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// The error that ends an interrupted run, after `done` of `total` items
+pub fn stopped(what: &str, done: usize, total: usize) -> anyhow::Error {
+    MxError::Interrupted {
+        what: what.to_string(),
+        done,
+        total,
+    }
+    .into()
+}
+
+/// A bar over a known number of items
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+    pub fn new(total: usize, label: &str) -> Self {
+        let bar = ProgressBar::new(total as u64);
+        bar.set_style(
+            ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len} ETA {eta}")
+                .expect("valid template")
+                .progress_chars("=> "),
+        );
+        bar.set_message(label.to_string());
+        Self { bar }
+    }
+
+    /// Print a line above the bar
+    pub fn println(&self, line: impl AsRef<str>) {
+        self.suspend(|| println!("{}", line.as_ref()));
+    }
+
+    /// Hide the bar while `f` writes its own output
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.bar.suspend(f)
+    }
+
+    /// One more item done
+    pub fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if !self.bar.is_finished() {
+            self.bar.abandon();
+        }
+    }
+}
+
+/// Items already done by an interrupted run of the same job
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    #[serde(skip)]
+    path: PathBuf,
+    pub done: BTreeSet<String>,
+}
+
+impl Checkpoint {
+    /// The checkpoint for `job`, empty when the last run finished
+    pub fn load(job: &str) -> Result<Self> {
+        Self::load_from(paths::checkpoint_dir().join(format!("{}.json", job)))
+    }
+
+    fn load_from(path: PathBuf) -> Result<Self> {
+        let mut checkpoint = match fs::read(&path) {
+            Ok(raw) => serde_json::from_slice::<Checkpoint>(&raw)
+                .with_context(|| format!("Unreadable checkpoint {}", path.display()))?,
+            Err(_) => Checkpoint::default(),
+        };
+        checkpoint.path = path;
+        Ok(checkpoint)
+    }
+
+    pub fn contains(&self, item: &str) -> bool {
+        self.done.contains(item)
+    }
+
+    pub fn record(&mut self, item: impl Into<String>) {
+        self.done.insert(item.into());
+    }
+
+    /// Keep what's done for the next run
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write checkpoint {}", self.path.display()))
+    }
+
+    /// The job finished; start from scratch next time
+    pub fn clear(self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)
+                .with_context(|| format!("Failed to remove checkpoint {}", self.path.display())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_carry_over_until_cleared() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("checkpoints").join("pull-o-r.json");
+
+        let mut first = Checkpoint::load_from(path.clone()).unwrap();
+        assert!(first.done.is_empty());
+        first.record("issue:1");
+        first.record("issue:2");
+        first.save().unwrap();
+
+        let resumed = Checkpoint::load_from(path.clone()).unwrap();
+        assert!(resumed.contains("issue:2"));
+        assert!(!resumed.contains("issue:3"));
+        resumed.clear().unwrap();
+        assert!(!path.exists());
+        assert!(Checkpoint::load_from(path).unwrap().done.is_empty());
+
+        let err = stopped("sync pull", 2, 5);
+        let mx = err.downcast_ref::<MxError>().unwrap();
+        assert_eq!(mx.exit_code(), 130);
+        assert_eq!(err.to_string(), "sync pull interrupted after 2 of 5 items");
+    }
+}
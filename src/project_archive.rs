@@ -0,0 +1,250 @@
+//! Project archives: `mx memory projects close --archive` / `reopen`
+//!
+//! Closing a project with `--archive` moves its entries (those whose source
+//! project is the project) out of the active store into a standalone archive,
+//! `$MX_HOME/archives/<project>.jsonl.gz`: a gzipped JSONL file holding the
+//! project record followed by the full entries. Each archived entry is
+//! replaced in the store by a stub with the same id, title, category, and
+//! tags plus [`STUB_TAG`], whose body points at the archive. Keeping the ids
+//! means relationships from other entries still resolve.
+//!
+//! Reopening restores the archived entries over their stubs and marks the
+//! project active again. A stub edited since the close (it lost its
+//! [`STUB_TAG`]) is left alone and reported. The archive is kept, renamed to
+//! `<project>.jsonl.gz.restored`.
+//!
+//! Only entries visible to the current agent are archived.
+
+use anyhow::{Context, Result, bail};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::paths;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::types::Project;
+
+/// Tag carried by the stubs left in the active store
+pub const STUB_TAG: &str = "archived-stub";
+
+/// One line of an archive
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Record {
+    Project { project: Project, closed_at: String },
+    Entry { entry: Box<KnowledgeEntry> },
+}
+
+/// Where a project's archive lives
+pub fn archive_path(project_id: &str) -> PathBuf {
+    paths::archives_dir().join(format!("{}.jsonl.gz", project_id))
+}
+
+fn write_archive(path: &Path, project: &Project, entries: &[KnowledgeEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(GzEncoder::new(file, Compression::default()));
+    let header = Record::Project {
+        project: project.clone(),
+        closed_at: chrono::Utc::now().to_rfc3339(),
+    };
+    writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+    for entry in entries {
+        let record = Record::Entry {
+            entry: Box::new(entry.clone()),
+        };
+        writeln!(writer, "{}", serde_json::to_string(&record)?)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .finish()?
+        .sync_all()?;
+    Ok(())
+}
+
+fn read_archive(path: &Path) -> Result<(Project, Vec<KnowledgeEntry>)> {
+    let file = File::open(path).with_context(|| format!("No archive at {}", path.display()))?;
+    let mut project = None;
+    let mut entries = Vec::new();
+    for (lineno, line) in BufReader::new(GzDecoder::new(file)).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = serde_json::from_str(&line)
+            .with_context(|| format!("{}:{}: invalid record", path.display(), lineno + 1))?;
+        match record {
+            Record::Project { project: p, .. } => project = Some(p),
+            Record::Entry { entry } => entries.push(*entry),
+        }
+    }
+    let Some(project) = project else {
+        bail!("{} has no project record", path.display());
+    };
+    Ok((project, entries))
+}
+
+/// The placeholder left in the store for an archived entry
+fn stub(entry: &KnowledgeEntry, archive: &Path) -> KnowledgeEntry {
+    let mut stub = entry.clone();
+    let project = entry.source_project_id.as_deref().unwrap_or("");
+    let body = format!(
+        "Archived with project {} in {}\n\nRestore with: mx memory projects reopen {}",
+        project,
+        archive.display(),
+        project
+    );
+    stub.content_hash = Some(KnowledgeEntry::compute_hash(&body));
+    stub.body = Some(body);
+    stub.summary = None;
+    stub.resonance = 0;
+    stub.resonance_type = None;
+    stub.anchors.clear();
+    stub.wake_phrases.clear();
+    stub.wake_phrase = None;
+    stub.wake_order = None;
+    stub.embedding = None;
+    stub.embedding_model = None;
+    stub.embedded_at = None;
+    if !stub.tags.iter().any(|t| t == STUB_TAG) {
+        stub.tags.push(STUB_TAG.to_string());
+    }
+    stub.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    stub
+}
+
+fn is_stub(entry: &KnowledgeEntry) -> bool {
+    entry.tags.iter().any(|t| t == STUB_TAG)
+}
+
+/// Archive every entry of `project_id` and replace it with a stub
+pub fn close(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    project_id: &str,
+    dry_run: bool,
+) -> Result<usize> {
+    let Some(mut project) = db.get_project(project_id)? else {
+        bail!(MxError::not_found("Project", project_id));
+    };
+    let path = archive_path(project_id);
+    if path.exists() {
+        bail!(
+            "{} already exists; reopen the project before archiving it again",
+            path.display()
+        );
+    }
+    let entries: Vec<KnowledgeEntry> = db
+        .list_all(ctx)?
+        .into_iter()
+        .filter(|e| e.source_project_id.as_deref() == Some(project_id) && !is_stub(e))
+        .collect();
+    if dry_run {
+        return Ok(entries.len());
+    }
+
+    // The archive is complete on disk before any entry is stubbed
+    write_archive(&path, &project, &entries)?;
+    for entry in &entries {
+        db.upsert_knowledge(&stub(entry, &path))?;
+    }
+    project.active = false;
+    project.updated_at = chrono::Utc::now().to_rfc3339();
+    db.upsert_project(&project)?;
+    Ok(entries.len())
+}
+
+/// What `reopen` did
+#[derive(Debug, Default)]
+pub struct Reopened {
+    pub restored: usize,
+    /// Stubs edited since the close, left as they are
+    pub skipped: Vec<String>,
+    pub archive: PathBuf,
+}
+
+/// Restore an archived project's entries over their stubs
+pub fn reopen(db: &dyn KnowledgeStore, ctx: &AgentContext, project_id: &str) -> Result<Reopened> {
+    let path = archive_path(project_id);
+    let (mut project, entries) = read_archive(&path)?;
+
+    let mut result = Reopened::default();
+    for entry in &entries {
+        match db.get(&entry.id, ctx)? {
+            Some(current) if !is_stub(&current) => result.skipped.push(entry.id.clone()),
+            _ => {
+                db.upsert_knowledge(entry)?;
+                result.restored += 1;
+            }
+        }
+    }
+    project.active = true;
+    project.updated_at = chrono::Utc::now().to_rfc3339();
+    db.upsert_project(&project)?;
+
+    let mut restored_name = path.as_os_str().to_owned();
+    restored_name.push(".restored");
+    result.archive = PathBuf::from(restored_name);
+    std::fs::rename(&path, &result.archive)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": "Retry with backoff",
+            "body": "Use exponential backoff",
+            "source_project_id": "akupa",
+            "resonance": 7,
+            "tags": ["rust"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn archive_round_trips_and_stubs_point_at_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("akupa.jsonl.gz");
+        let project = Project {
+            id: "akupa".into(),
+            name: "Akupa".into(),
+            path: None,
+            repo_url: None,
+            description: None,
+            active: true,
+            created_at: "2026-01-01T00:00:00Z".into(),
+            updated_at: "2026-01-01T00:00:00Z".into(),
+        };
+        write_archive(&path, &project, &[entry("kn-1"), entry("kn-2")]).unwrap();
+        let (read_project, entries) = read_archive(&path).unwrap();
+        assert_eq!(read_project.name, "Akupa");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].body.as_deref(), Some("Use exponential backoff"));
+
+        let stub = stub(&entries[0], &path);
+        assert_eq!(stub.id, "kn-1");
+        assert_eq!(stub.resonance, 0);
+        assert_eq!(stub.tags, vec!["rust", STUB_TAG]);
+        assert!(is_stub(&stub));
+        assert!(
+            stub.body
+                .unwrap()
+                .contains("mx memory projects reopen akupa")
+        );
+    }
+}
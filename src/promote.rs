@@ -0,0 +1,235 @@
+//! Ephemeral-to-durable promotion: `mx memory promote`
+//!
+//! Facts and session-scoped entries decay by design. Promoting one turns it
+//! into a durable entry (a bloom unless `--category` says otherwise): it
+//! stops being ephemeral, stops decaying, and takes a durable resonance type.
+//!
+//! A promoted entry must be able to stand on its own in a wake cascade, so
+//! it needs a summary and at least one anchor, either already on the entry or
+//! given on the command line. The pre-promotion state is kept as a `promote`
+//! backup, so `mx memory restore --list` shows when and from what it was
+//! promoted.
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Resonance types that mark an entry as session residue
+const TRANSIENT_TYPES: &[&str] = &["ephemeral", "session"];
+
+#[derive(clap::Args)]
+pub struct PromoteArgs {
+    /// Entry ID
+    pub id: String,
+
+    /// Category of the durable entry
+    #[arg(long, default_value = "bloom")]
+    pub category: String,
+
+    /// Resonance level (default: keep the current one)
+    #[arg(long)]
+    pub resonance: Option<i32>,
+
+    /// Durable resonance type (foundational, transformative, relational, operational)
+    #[arg(long = "type", default_value = "transformative")]
+    pub resonance_type: String,
+
+    /// Summary, if the entry doesn't have one yet (replaces fact metadata)
+    #[arg(long)]
+    pub summary: Option<String>,
+
+    /// Anchor to another bloom (repeatable, added to existing anchors)
+    #[arg(long = "anchor")]
+    pub anchors: Vec<String>,
+
+    /// Make the promoted entry private to the current agent
+    #[arg(long)]
+    pub private: bool,
+
+    /// Allow promoting to a public bloom
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show the result without writing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn is_transient(entry: &KnowledgeEntry) -> bool {
+    entry.ephemeral
+        || entry
+            .resonance_type
+            .as_deref()
+            .is_some_and(|t| TRANSIENT_TYPES.contains(&t))
+}
+
+/// The durable form of `entry`, or why it can't be promoted
+fn promoted(entry: &KnowledgeEntry, args: &PromoteArgs) -> Result<KnowledgeEntry> {
+    if !is_transient(entry) {
+        bail!(
+            "{} is already durable (resonance type {})",
+            entry.id,
+            entry.resonance_type.as_deref().unwrap_or("none")
+        );
+    }
+    if TRANSIENT_TYPES.contains(&args.resonance_type.as_str()) {
+        bail!(
+            "--type {} is not durable; use foundational, transformative, relational or operational",
+            args.resonance_type
+        );
+    }
+
+    let mut entry = entry.clone();
+    entry.category_id = args.category.clone();
+    entry.ephemeral = false;
+    entry.resonance_type = Some(args.resonance_type.clone());
+    entry.decay_rate = 0.0;
+    if let Some(resonance) = args.resonance {
+        entry.resonance = resonance;
+    }
+    if let Some(ref summary) = args.summary {
+        entry.summary = Some(summary.clone());
+    }
+    for anchor in args
+        .anchors
+        .iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+    {
+        let anchor = crate::normalize_id(anchor);
+        if !entry.anchors.contains(&anchor) {
+            entry.anchors.push(anchor);
+        }
+    }
+
+    let mut missing = Vec::new();
+    if entry.summary.as_deref().is_none_or(|s| s.trim().is_empty()) {
+        missing.push("a summary (--summary)");
+    }
+    if entry.anchors.is_empty() {
+        missing.push("at least one anchor (--anchor)");
+    }
+    if !missing.is_empty() {
+        bail!("Promoting {} needs {}", entry.id, missing.join(" and "));
+    }
+    let problems = entry.problems();
+    if !problems.is_empty() {
+        bail!("Cannot promote {}: {}", entry.id, problems.join("; "));
+    }
+    Ok(entry)
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: PromoteArgs) -> Result<()> {
+    let id = crate::normalize_id(&args.id);
+    let Some(original) = db.get(&id, ctx)? else {
+        bail!(MxError::not_found("Entry", id));
+    };
+    let mut entry = promoted(&original, &args)?;
+
+    let agent = std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|s| !s.is_empty());
+    if args.private {
+        let Some(ref owner) = agent else {
+            bail!("--private needs an owner; set MX_CURRENT_AGENT");
+        };
+        entry.visibility = "private".to_string();
+        entry.owner = Some(owner.clone());
+    } else if entry.visibility == "public" && entry.category_id == "bloom" && !args.force {
+        bail!(
+            "Promoting {} to a public bloom will expose identity data. Use --private, or --force to keep it public.",
+            id
+        );
+    }
+    for anchor in &entry.anchors {
+        if db.get(anchor, ctx)?.is_none() {
+            eprintln!("{} anchor {} not found", "Warning:".yellow(), anchor);
+        }
+    }
+
+    println!(
+        "{} {} -> {} [{} r{} {}]",
+        if args.dry_run {
+            "Would promote"
+        } else {
+            "Promoted"
+        },
+        id.yellow(),
+        entry.title,
+        entry.category_id,
+        entry.resonance,
+        args.resonance_type
+    );
+    if args.dry_run {
+        return Ok(());
+    }
+
+    db.backup_content(&original, "promote", agent.as_deref())?;
+    entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    db.upsert_knowledge(&entry)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: PromoteArgs,
+    }
+
+    fn args(argv: &[&str]) -> PromoteArgs {
+        Cli::parse_from(std::iter::once("promote").chain(argv.iter().copied())).args
+    }
+
+    fn fact() -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": "kn-fact",
+            "category_id": "insight",
+            "title": "Prefers short answers",
+            "ephemeral": true,
+            "resonance": 3,
+            "resonance_type": "ephemeral",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn promotion_requires_summary_and_anchor() {
+        let err = promoted(&fact(), &args(&["kn-fact"])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Promoting kn-fact needs a summary (--summary) and at least one anchor (--anchor)"
+        );
+
+        let entry = promoted(
+            &fact(),
+            &args(&[
+                "kn-fact",
+                "--summary",
+                "Keep it brief",
+                "--anchor",
+                "kn-core",
+                "--resonance",
+                "7",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(entry.category_id, "bloom");
+        assert!(!entry.ephemeral);
+        assert!(!is_transient(&entry));
+        assert_eq!(entry.resonance, 7);
+        assert_eq!(entry.resonance_type.as_deref(), Some("transformative"));
+        assert_eq!(entry.anchors, vec!["kn-core"]);
+
+        // Durable entries and transient target types are refused
+        assert!(promoted(&entry, &args(&["kn-fact"])).is_err());
+        assert!(promoted(&fact(), &args(&["kn-fact", "--type", "session"])).is_err());
+    }
+}
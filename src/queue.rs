@@ -0,0 +1,251 @@
+//! Reading list: `mx queue`
+//!
+//! A prioritized queue of things still to read or process: URLs waiting to
+//! be ingested and existing entries waiting to be curated. `next` shows what
+//! to pick up, `done` checks it off (optionally ingesting a URL on the way),
+//! so captured material doesn't sit unreviewed.
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::QueueCommands;
+use crate::index::IndexConfig;
+use crate::ingest::{self, IngestOptions};
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{self, AgentContext, KnowledgeStore};
+use crate::types::QueueItem;
+
+pub fn handle_queue(cmd: QueueCommands, verbose: bool) -> Result<()> {
+    let config = IndexConfig::default();
+    let db = store::create_store_with_verbose(&config.db_path, verbose)?;
+
+    match cmd {
+        QueueCommands::Add {
+            target,
+            priority,
+            title,
+            note,
+        } => add(db.as_ref(), &target, priority, title, note),
+        QueueCommands::Next { json } => next(db.as_ref(), json),
+        QueueCommands::Done {
+            id,
+            ingest,
+            category,
+        } => done(db.as_ref(), id, ingest.then_some(category), verbose),
+        QueueCommands::List { all, json } => list(db.as_ref(), all, json),
+    }
+}
+
+fn agent_context() -> AgentContext {
+    match std::env::var("MX_CURRENT_AGENT") {
+        Ok(agent) if !agent.is_empty() => AgentContext::for_agent(agent),
+        _ => AgentContext::public_only(),
+    }
+}
+
+fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+fn new_id() -> String {
+    format!("q-{}", &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+fn add(
+    db: &dyn KnowledgeStore,
+    target: &str,
+    priority: i64,
+    title: Option<String>,
+    note: Option<String>,
+) -> Result<()> {
+    if !(1..=10).contains(&priority) {
+        bail!("Priority must be between 1 and 10");
+    }
+
+    let (kind, target, title) = if is_url(target) {
+        ("url", target.to_string(), title)
+    } else {
+        let id = crate::normalize_id(target);
+        let Some(entry) = db.get(&id, &agent_context())? else {
+            bail!("'{}' is neither a URL nor a known entry id", target);
+        };
+        ("entry", id, title.or(Some(entry.title)))
+    };
+
+    if db
+        .list_queue(false)?
+        .iter()
+        .any(|item| item.target == target)
+    {
+        bail!("Already queued: {}", target);
+    }
+
+    let item = QueueItem {
+        id: new_id(),
+        target,
+        kind: kind.to_string(),
+        title,
+        note,
+        priority,
+        added_at: None,
+        done_at: None,
+    };
+    db.add_queue_item(&item)?;
+    println!(
+        "Queued {} {} (priority {})",
+        item.id,
+        label(&item),
+        priority
+    );
+    Ok(())
+}
+
+/// Queue an existing entry for curation. Returns None if it's already
+/// queued.
+pub fn enqueue_entry(
+    db: &dyn KnowledgeStore,
+    entry: &KnowledgeEntry,
+    priority: i64,
+    note: Option<String>,
+) -> Result<Option<QueueItem>> {
+    if db
+        .list_queue(false)?
+        .iter()
+        .any(|item| item.target == entry.id)
+    {
+        return Ok(None);
+    }
+    let item = QueueItem {
+        id: new_id(),
+        target: entry.id.clone(),
+        kind: "entry".to_string(),
+        title: Some(entry.title.clone()),
+        note,
+        priority: priority.clamp(1, 10),
+        added_at: None,
+        done_at: None,
+    };
+    db.add_queue_item(&item)?;
+    Ok(Some(item))
+}
+
+fn label(item: &QueueItem) -> String {
+    match item.title {
+        Some(ref title) => format!("{} <{}>", title, item.target),
+        None => item.target.clone(),
+    }
+}
+
+/// The follow-up command for an item
+fn hint(item: &QueueItem) -> String {
+    match item.kind.as_str() {
+        "url" => format!("mx queue done {} --ingest", item.id),
+        _ => format!("mx memory show {}", item.target),
+    }
+}
+
+fn next(db: &dyn KnowledgeStore, json: bool) -> Result<()> {
+    let item = db.list_queue(false)?.into_iter().next();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&item)?);
+        return Ok(());
+    }
+    let Some(item) = item else {
+        println!("{}", "Queue is empty".green());
+        return Ok(());
+    };
+
+    println!("{} {}", item.id.cyan(), label(&item).bold());
+    if let Some(ref note) = item.note {
+        println!("  {}", note);
+    }
+    println!("  {} {}", "next:".dimmed(), hint(&item));
+    Ok(())
+}
+
+fn done(
+    db: &dyn KnowledgeStore,
+    id: Option<String>,
+    ingest_as: Option<String>,
+    verbose: bool,
+) -> Result<()> {
+    let open = db.list_queue(false)?;
+    let item = match id {
+        Some(id) => open.into_iter().find(|item| item.id == id),
+        None => open.into_iter().next(),
+    };
+    let Some(item) = item else {
+        bail!("No open queue item to complete");
+    };
+
+    if let Some(category) = ingest_as {
+        if item.kind != "url" {
+            bail!(
+                "--ingest only applies to URL items; {} is an entry",
+                item.id
+            );
+        }
+        let opts = IngestOptions::new(category, None, false, false);
+        let draft = ingest::web::ingest(&item.target, false, true)?;
+        ingest::save(vec![draft], &opts, verbose)?;
+    }
+
+    if !db.complete_queue_item(&item.id)? {
+        bail!("Queue item {} was already completed", item.id);
+    }
+    println!("Done: {} {}", item.id, label(&item));
+    let remaining = db.list_queue(false)?.len();
+    println!("{} item(s) left in the queue", remaining);
+    Ok(())
+}
+
+fn list(db: &dyn KnowledgeStore, all: bool, json: bool) -> Result<()> {
+    let items = db.list_queue(all)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+    if items.is_empty() {
+        println!("{}", "Queue is empty".green());
+        return Ok(());
+    }
+    for item in &items {
+        let mark = if item.done_at.is_some() { "✓" } else { " " };
+        println!(
+            "{} {} [{}] {}",
+            mark,
+            item.id.cyan(),
+            item.priority,
+            label(item)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_and_hints_by_kind() {
+        let mut item = QueueItem {
+            id: "q-1a2b3c4d".to_string(),
+            target: "https://example.org/post".to_string(),
+            kind: "url".to_string(),
+            title: None,
+            note: None,
+            priority: 5,
+            added_at: None,
+            done_at: None,
+        };
+        assert_eq!(label(&item), "https://example.org/post");
+        assert_eq!(hint(&item), "mx queue done q-1a2b3c4d --ingest");
+
+        item.kind = "entry".to_string();
+        item.target = "kn-abc123".to_string();
+        item.title = Some("Retry budgets".to_string());
+        assert_eq!(label(&item), "Retry budgets <kn-abc123>");
+        assert_eq!(hint(&item), "mx memory show kn-abc123");
+        assert!(!is_url("kn-abc123"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Recently viewed entries
+//!
+//! `mx memory show` records a view per agent (the current agent, or `human`
+//! outside an agent session). `mx memory recent --viewed` lists them newest first,
+//! and `mx memory search --boost-recent` lifts entries viewed in the last
+//! few days: a fresh view doubles an entry's ranking score, and the
+//! boost halves every [`HALF_LIFE_DAYS`].
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::confidence;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore, TextScore};
+use crate::types::EntryView;
+
+/// Days for a view's boost to halve
+pub const HALF_LIFE_DAYS: f64 = 3.0;
+
+/// Entries listed by `recent --viewed` unless `--limit` says otherwise
+pub const DEFAULT_LIMIT: usize = 20;
+
+#[derive(clap::Args)]
+pub struct ViewedArgs {
+    /// List entries you viewed recently instead of recent facts
+    #[arg(long, conflicts_with_all = ["days", "resonance_type", "all_types", "sort"])]
+    pub viewed: bool,
+}
+
+#[derive(clap::Args)]
+pub struct BoostArgs {
+    /// Rank entries you viewed recently higher
+    #[arg(long, conflicts_with_all = ["semantic", "as_of", "explain"])]
+    pub boost_recent: bool,
+}
+
+#[derive(Serialize)]
+struct Viewed<'a> {
+    #[serde(flatten)]
+    entry: &'a KnowledgeEntry,
+    views: i64,
+    viewed_at: &'a str,
+}
+
+/// Viewer identity: the current agent, or `human` outside an agent session
+pub fn viewer() -> String {
+    std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| "human".to_string())
+}
+
+/// Record that the current viewer opened `entry_id`. Failures are reported
+/// but never fail the command doing the viewing.
+pub fn record(db: &dyn KnowledgeStore, entry_id: &str) {
+    if let Err(e) = db.record_view(&viewer(), entry_id) {
+        eprintln!("Warning: failed to record view: {}", e);
+    }
+}
+
+/// Ranking multiplier for an entry last viewed at `viewed_at`: 2.0 just
+/// after a view, falling toward 1.0
+pub fn boost(viewed_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+    let Some(at) = viewed_at else {
+        return 1.0;
+    };
+    let days = (now - at).num_seconds().max(0) as f64 / 86_400.0;
+    1.0 + 0.5f64.powf(days / HALF_LIFE_DAYS)
+}
+
+/// Ranked as in [`confidence::Settings::rank`], with recently viewed
+/// entries boosted; entries that tie keep their order
+pub fn rank(
+    entries: &mut [KnowledgeEntry],
+    scores: &HashMap<String, TextScore>,
+    views: &[EntryView],
+    settings: &confidence::Settings,
+    now: DateTime<Utc>,
+) {
+    let viewed: HashMap<&str, DateTime<Utc>> = views
+        .iter()
+        .filter_map(|v| {
+            Some((
+                v.entry_id.as_str(),
+                crate::dates::parse_stored(&v.viewed_at)?,
+            ))
+        })
+        .collect();
+    let score = |e: &KnowledgeEntry| {
+        scores.get(&e.id).map_or(0.0, TextScore::total)
+            * settings.weight(e, now)
+            * boost(viewed.get(e.id.as_str()).copied(), now)
+    };
+    entries.sort_by(|a, b| score(b).total_cmp(&score(a)));
+}
+
+/// `mx memory recent --viewed`: the viewer's recently viewed entries they
+/// can still see
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, limit: usize, json: bool) -> Result<()> {
+    let views = db.list_views(&viewer())?;
+    let mut found = Vec::new();
+    for view in &views {
+        if found.len() == limit {
+            break;
+        }
+        if let Some(entry) = db.get(&view.entry_id, ctx)? {
+            found.push((entry, view));
+        }
+    }
+
+    if json {
+        let out: Vec<Viewed> = found
+            .iter()
+            .map(|(entry, view)| Viewed {
+                entry,
+                views: view.views,
+                viewed_at: &view.viewed_at,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else if found.is_empty() {
+        println!("No recently viewed entries (entries opened with `mx memory show` appear here)");
+    } else {
+        for (entry, view) in &found {
+            println!("  {} [{}]  {}", entry.id, entry.category_id, entry.title);
+            println!(
+                "    viewed {} ({} view{})",
+                crate::dates::display(&view.viewed_at),
+                view.views,
+                if view.views == 1 { "" } else { "s" }
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, confidence: f64) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": id,
+            "confidence": confidence,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn recent_views_lift_ranking_and_fade() {
+        let now = Utc::now();
+        assert_eq!(boost(None, now), 1.0);
+        assert!((boost(Some(now), now) - 2.0).abs() < 1e-9);
+        let faded = boost(Some(now - chrono::Duration::days(3)), now);
+        assert!((faded - 1.5).abs() < 1e-6);
+
+        let mut entries = vec![entry("kn-a", 0.9), entry("kn-b", 0.6), entry("kn-c", 0.5)];
+        let views = vec![EntryView {
+            entry_id: "kn-b".to_string(),
+            agent: "human".to_string(),
+            views: 2,
+            viewed_at: (now - chrono::Duration::hours(12)).to_rfc3339(),
+        }];
+        let scores: HashMap<String, TextScore> = ["kn-a", "kn-b", "kn-c"]
+            .map(|id| {
+                (
+                    id.to_string(),
+                    TextScore {
+                        title: 1.0,
+                        ..TextScore::default()
+                    },
+                )
+            })
+            .into();
+        rank(
+            &mut entries,
+            &scores,
+            &views,
+            &confidence::Settings::default(),
+            now,
+        );
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["kn-b", "kn-a", "kn-c"]);
+    }
+}
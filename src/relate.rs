@@ -0,0 +1,236 @@
+//! Linking entries by hand: `mx memory relate|unrelate`
+//!
+//! The relationship type must be registered (`mx memory relationship-types
+//! list`). A directional type links `from -> to` only; a non-directional one
+//! (`related`, `contradicts`) reads the same both ways, so relating `b a`
+//! after `a b` is a no-op and `unrelate` removes the link whichever way it
+//! was recorded.
+
+use anyhow::{Result, bail};
+
+use crate::error::MxError;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::types::RelationshipType;
+
+#[derive(clap::Args)]
+pub struct RelateArgs {
+    /// Source entry ID
+    pub from: String,
+
+    /// Target entry ID
+    pub to: String,
+
+    /// Relationship type
+    #[arg(long = "type", default_value = "related")]
+    pub rel_type: String,
+}
+
+#[derive(clap::Args)]
+pub struct UnrelateArgs {
+    /// Source entry ID
+    pub from: String,
+
+    /// Target entry ID
+    pub to: String,
+
+    /// Only remove this relationship type (default: every link between them)
+    #[arg(long = "type")]
+    pub rel_type: Option<String>,
+}
+
+/// The registered type named `name`
+fn relationship_type(db: &dyn KnowledgeStore, name: &str) -> Result<RelationshipType> {
+    let types = db.list_relationship_types()?;
+    let known: Vec<&str> = types.iter().map(|t| t.id.as_str()).collect();
+    let Some(found) = types.iter().find(|t| t.id == name) else {
+        bail!(
+            "Unknown relationship type '{}' (known: {})",
+            name,
+            known.join(", ")
+        );
+    };
+    Ok(found.clone())
+}
+
+/// Both entries, normalized and visible to the caller
+fn endpoints(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    from: &str,
+    to: &str,
+) -> Result<(String, String)> {
+    let (from, to) = (crate::normalize_id(from), crate::normalize_id(to));
+    if from == to {
+        bail!("An entry can't be related to itself");
+    }
+    for id in [&from, &to] {
+        if db.get(id, ctx)?.is_none() {
+            bail!(MxError::not_found("Entry", id));
+        }
+    }
+    Ok((from, to))
+}
+
+/// Whether a link of `rel_type` already joins the pair
+fn linked(
+    db: &dyn KnowledgeStore,
+    from: &str,
+    to: &str,
+    rel_type: &RelationshipType,
+) -> Result<bool> {
+    Ok(db.list_relationships_for_entry(from)?.iter().any(|r| {
+        r.relationship_type == rel_type.id
+            && ((r.from_entry_id == from && r.to_entry_id == to)
+                || (!rel_type.directional && r.from_entry_id == to && r.to_entry_id == from))
+    }))
+}
+
+/// `mx memory relate`
+pub fn relate(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &RelateArgs) -> Result<()> {
+    println!("{}", link(db, ctx, &args.from, &args.to, &args.rel_type)?);
+    Ok(())
+}
+
+/// Relate `from` to `to` unless they already are, describing what happened
+pub fn link(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    from: &str,
+    to: &str,
+    rel_type: &str,
+) -> Result<String> {
+    let rel_type = relationship_type(db, rel_type)?;
+    let (from, to) = endpoints(db, ctx, from, to)?;
+    let arrow = if rel_type.directional { "->" } else { "<->" };
+
+    if linked(db, &from, &to, &rel_type)? {
+        return Ok(format!(
+            "{} {} {} is already {}",
+            from, arrow, to, rel_type.id
+        ));
+    }
+    db.add_relationship(&from, &to, &rel_type.id)?;
+    Ok(format!(
+        "Related {} {} {} ({})",
+        from, arrow, to, rel_type.id
+    ))
+}
+
+/// `mx memory unrelate`
+pub fn unrelate(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &UnrelateArgs) -> Result<()> {
+    let only = args
+        .rel_type
+        .as_deref()
+        .map(|name| relationship_type(db, name))
+        .transpose()?;
+    let (from, to) = endpoints(db, ctx, &args.from, &args.to)?;
+    let directional: std::collections::HashMap<String, bool> = db
+        .list_relationship_types()?
+        .into_iter()
+        .map(|t| (t.id, t.directional))
+        .collect();
+
+    let mut removed = Vec::new();
+    for rel in db.list_relationships_for_entry(&from)? {
+        if only.as_ref().is_some_and(|t| t.id != rel.relationship_type) {
+            continue;
+        }
+        let forward = rel.from_entry_id == from && rel.to_entry_id == to;
+        let backward = rel.from_entry_id == to
+            && rel.to_entry_id == from
+            && !directional
+                .get(&rel.relationship_type)
+                .copied()
+                .unwrap_or(true);
+        if (forward || backward)
+            && db.remove_relationship(
+                &rel.from_entry_id,
+                &rel.to_entry_id,
+                &rel.relationship_type,
+            )?
+        {
+            removed.push(rel.relationship_type);
+        }
+    }
+
+    if removed.is_empty() {
+        match &only {
+            Some(t) => bail!("{} is not {} {}", from, t.id, to),
+            None => bail!("{} is not related to {}", from, to),
+        }
+    }
+    removed.sort();
+    removed.dedup();
+    println!("Unrelated {} and {} ({})", from, to, removed.join(", "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knowledge::KnowledgeEntry;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn entry(id: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": id,
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn relate_args(from: &str, to: &str, rel_type: &str) -> RelateArgs {
+        RelateArgs {
+            from: from.to_string(),
+            to: to.to_string(),
+            rel_type: rel_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn relate_validates_types_and_honours_direction() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        for id in ["kn-a", "kn-b"] {
+            db.upsert_knowledge(&entry(id)).unwrap();
+        }
+        let edges = |db: &SurrealDatabase| db.list_relationships_for_entry("kn-a").unwrap().len();
+
+        assert!(relate(&db, &ctx, &relate_args("kn-a", "kn-b", "builds_on")).is_err());
+        assert!(relate(&db, &ctx, &relate_args("kn-a", "kn-a", "related")).is_err());
+        assert!(relate(&db, &ctx, &relate_args("kn-a", "kn-zz", "related")).is_err());
+
+        // related is non-directional: the reverse is the same link
+        relate(&db, &ctx, &relate_args("kn-a", "kn-b", "related")).unwrap();
+        relate(&db, &ctx, &relate_args("kn-b", "kn-a", "related")).unwrap();
+        assert_eq!(edges(&db), 1);
+
+        // extends is directional: both ways are separate links
+        relate(&db, &ctx, &relate_args("kn-a", "kn-b", "extends")).unwrap();
+        relate(&db, &ctx, &relate_args("kn-b", "kn-a", "extends")).unwrap();
+        assert_eq!(edges(&db), 3);
+
+        let unrelate_args = |from: &str, to: &str, rel_type: Option<&str>| UnrelateArgs {
+            from: from.to_string(),
+            to: to.to_string(),
+            rel_type: rel_type.map(String::from),
+        };
+        // b -> a removes the related link recorded as a -> b, and b's extends
+        unrelate(&db, &ctx, &unrelate_args("kn-b", "kn-a", None)).unwrap();
+        let left = db.list_relationships_for_entry("kn-a").unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(
+            (
+                left[0].from_entry_id.as_str(),
+                left[0].relationship_type.as_str()
+            ),
+            ("kn-a", "extends")
+        );
+        assert!(unrelate(&db, &ctx, &unrelate_args("kn-a", "kn-b", Some("related"))).is_err());
+        unrelate(&db, &ctx, &unrelate_args("kn-a", "kn-b", Some("extends"))).unwrap();
+        assert_eq!(edges(&db), 0);
+    }
+}
@@ -0,0 +1,393 @@
+//! One-way replication to a standby: `mx memory replicate`
+//!
+//! Copies the graph to a second SurrealDB instance (a `ws://`/`wss://` server,
+//! or a path for an embedded store) so there is a live disaster-recovery copy.
+//! Each pass sends:
+//!
+//! - categories (upserted whole, they are few),
+//! - entries updated since the last pass's watermark, whatever their
+//!   visibility; the watermark per target lives in `$MX_HOME/replication.json`,
+//! - deletions, found by comparing id sets,
+//! - relationship edges added or removed since the last pass.
+//!
+//! `--follow N` repeats the pass every N seconds. `--verify` changes nothing
+//! and compares the two sides instead: entry counts plus a content hash per
+//! entry.
+//!
+//! A network standby must already have the schema (as for `MX_SURREAL_MODE=
+//! network`); credentials come from `MX_REPLICA_USER` / `MX_REPLICA_PASS`,
+//! falling back to the primary's. The standby session may write sealed
+//! identity entries: their guard already ran on the primary.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::paths;
+use crate::store::KnowledgeStore;
+use crate::surreal_db::{SurrealConfig, SurrealDatabase, SurrealMode};
+
+#[derive(clap::Args)]
+pub struct ReplicateArgs {
+    /// Standby to replicate to: ws(s)://host:port, or a path for an embedded store
+    #[arg(long)]
+    pub target: String,
+
+    /// Compare counts and content hashes with the standby instead of replicating
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Keep replicating, one pass every N seconds
+    #[arg(long, value_name = "SECONDS", conflicts_with = "verify")]
+    pub follow: Option<u64>,
+
+    /// Ignore the saved watermark and send every entry
+    #[arg(long, conflicts_with = "verify")]
+    pub full: bool,
+}
+
+/// Per-target replication progress
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Watermark {
+    /// Latest `updated_at` already on the standby
+    updated_at: Option<String>,
+    last_run: Option<String>,
+}
+
+/// What one pass changed on the standby
+#[derive(Debug, Default, PartialEq)]
+struct Pass {
+    entries: usize,
+    deleted: usize,
+    relationships_added: usize,
+    relationships_removed: usize,
+    watermark: Option<String>,
+}
+
+/// Differences found by `--verify`
+#[derive(Debug, Default)]
+struct Drift {
+    source_count: usize,
+    target_count: usize,
+    missing: Vec<String>,
+    extra: Vec<String>,
+    differing: Vec<String>,
+}
+
+impl Drift {
+    fn is_clean(&self) -> bool {
+        self.source_count == self.target_count
+            && self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.differing.is_empty()
+    }
+}
+
+fn connect_target(target: &str) -> Result<SurrealDatabase> {
+    let primary = SurrealConfig::from_env();
+    let db = if target.starts_with("ws://") || target.starts_with("wss://") {
+        let config = SurrealConfig {
+            mode: SurrealMode::Network,
+            url: target.to_string(),
+            user: std::env::var("MX_REPLICA_USER").unwrap_or_else(|_| primary.user.clone()),
+            pass: std::env::var("MX_REPLICA_PASS")
+                .ok()
+                .filter(|p| !p.is_empty())
+                .or_else(|| primary.pass.clone()),
+            ..primary
+        };
+        SurrealDatabase::connect("", &config)
+    } else {
+        let config = SurrealConfig {
+            mode: SurrealMode::Embedded,
+            ..primary
+        };
+        SurrealDatabase::connect(Path::new(target), &config)
+    }
+    .with_context(|| format!("Failed to connect to standby {}", target))?;
+    db.unlock_identity()?;
+    Ok(db)
+}
+
+fn load_watermarks() -> Result<BTreeMap<String, Watermark>> {
+    let path = paths::replication_state();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save_watermarks(watermarks: &BTreeMap<String, Watermark>) -> Result<()> {
+    let path = paths::replication_state();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(watermarks)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Send everything changed since `since` to `target`
+fn replicate_once(
+    source: &dyn KnowledgeStore,
+    target: &dyn KnowledgeStore,
+    since: Option<&str>,
+) -> Result<Pass> {
+    let mut pass = Pass {
+        watermark: since.map(String::from),
+        ..Pass::default()
+    };
+
+    for category in source.list_categories()? {
+        target.upsert_category(&category)?;
+    }
+
+    // Entries at the watermark itself are sent again (upserts are
+    // idempotent) but not counted as changes
+    let boundary = since.and_then(parse_time);
+    let mut latest = boundary;
+    for entry in source.list_changed_since(since)? {
+        target.upsert_knowledge(&entry)?;
+        let updated = entry.updated_at.as_deref().and_then(parse_time);
+        if boundary.is_none() || updated > boundary {
+            pass.entries += 1;
+        }
+        if updated > latest {
+            latest = updated;
+            pass.watermark = entry.updated_at.clone();
+        }
+    }
+
+    let live: HashSet<String> = source.list_entry_ids()?.into_iter().collect();
+    let gone: Vec<String> = target
+        .list_entry_ids()?
+        .into_iter()
+        .filter(|id| !live.contains(id))
+        .collect();
+    if !gone.is_empty() {
+        pass.deleted = target.purge_entries(&gone)?;
+    }
+
+    let key = |r: &crate::types::Relationship| {
+        (
+            r.from_entry_id.clone(),
+            r.to_entry_id.clone(),
+            r.relationship_type.clone(),
+        )
+    };
+    let wanted: HashSet<_> = source.list_all_relationships()?.iter().map(key).collect();
+    let present: HashSet<_> = target.list_all_relationships()?.iter().map(key).collect();
+    for (from, to, rel_type) in wanted.difference(&present) {
+        target.add_relationship(from, to, rel_type)?;
+        pass.relationships_added += 1;
+    }
+    for (from, to, rel_type) in present.difference(&wanted) {
+        if target.remove_relationship(from, to, rel_type)? {
+            pass.relationships_removed += 1;
+        }
+    }
+    Ok(pass)
+}
+
+/// Hash of the replicated content of an entry
+fn content_hash(entry: &KnowledgeEntry) -> String {
+    let mut tags = entry.tags.clone();
+    tags.sort();
+    let canonical = serde_json::json!([
+        entry.id,
+        entry.category_id,
+        entry.title,
+        entry.body,
+        entry.summary,
+        tags,
+        entry.resonance,
+        entry.resonance_type,
+        entry.visibility,
+        entry.owner,
+        entry.anchors,
+        entry.namespace,
+        entry.updated_at.as_deref().and_then(parse_time),
+    ]);
+    Sha256::digest(canonical.to_string().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn verify(source: &dyn KnowledgeStore, target: &dyn KnowledgeStore) -> Result<Drift> {
+    let hashes = |db: &dyn KnowledgeStore| -> Result<HashMap<String, String>> {
+        Ok(db
+            .list_changed_since(None)?
+            .iter()
+            .map(|e| (e.id.clone(), content_hash(e)))
+            .collect())
+    };
+    let ours = hashes(source)?;
+    let theirs = hashes(target)?;
+
+    let mut drift = Drift {
+        source_count: source.count()?,
+        target_count: target.count()?,
+        ..Drift::default()
+    };
+    for (id, hash) in &ours {
+        match theirs.get(id) {
+            None => drift.missing.push(id.clone()),
+            Some(h) if h != hash => drift.differing.push(id.clone()),
+            Some(_) => {}
+        }
+    }
+    drift.extra = theirs
+        .keys()
+        .filter(|id| !ours.contains_key(*id))
+        .cloned()
+        .collect();
+    drift.missing.sort();
+    drift.extra.sort();
+    drift.differing.sort();
+    Ok(drift)
+}
+
+fn report_drift(target: &str, drift: &Drift) {
+    println!(
+        "{} primary {} entries, standby {} entries",
+        "Counts:".bold(),
+        drift.source_count,
+        drift.target_count
+    );
+    for (label, ids) in [
+        ("missing on standby", &drift.missing),
+        ("only on standby", &drift.extra),
+        ("content differs", &drift.differing),
+    ] {
+        if !ids.is_empty() {
+            println!("  {} {}: {}", ids.len(), label, ids.join(", "));
+        }
+    }
+    if drift.is_clean() {
+        println!("{} {} matches the primary", "✓".green(), target);
+    }
+}
+
+pub fn run(source: &dyn KnowledgeStore, args: ReplicateArgs) -> Result<()> {
+    let target = connect_target(&args.target)?;
+
+    if args.verify {
+        let drift = verify(source, &target)?;
+        report_drift(&args.target, &drift);
+        if !drift.is_clean() {
+            bail!(
+                "Standby {} has drifted; run mx memory replicate --full",
+                args.target
+            );
+        }
+        return Ok(());
+    }
+
+    let mut watermarks = load_watermarks()?;
+    let mut since = if args.full {
+        None
+    } else {
+        watermarks
+            .get(&args.target)
+            .and_then(|w| w.updated_at.clone())
+    };
+    loop {
+        let pass = replicate_once(source, &target, since.as_deref())?;
+        watermarks.insert(
+            args.target.clone(),
+            Watermark {
+                updated_at: pass.watermark.clone(),
+                last_run: Some(Utc::now().to_rfc3339()),
+            },
+        );
+        save_watermarks(&watermarks)?;
+
+        let changed =
+            pass.entries + pass.deleted + pass.relationships_added + pass.relationships_removed;
+        if changed > 0 || args.follow.is_none() {
+            println!(
+                "{} {} entries, {} deleted, relationships +{} -{} -> {}",
+                Utc::now().format("%H:%M:%S").to_string().dimmed(),
+                pass.entries,
+                pass.deleted,
+                pass.relationships_added,
+                pass.relationships_removed,
+                args.target
+            );
+        }
+
+        let Some(interval) = args.follow else {
+            return Ok(());
+        };
+        since = pass.watermark;
+        std::thread::sleep(std::time::Duration::from_secs(interval.max(1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, visibility: &str, updated: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": id,
+            "visibility": visibility,
+            "owner": "smith",
+            "created_at": "2026-03-01T09:00:00Z",
+            "updated_at": updated,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn replicates_changes_deletes_and_edges() {
+        let primary = SurrealDatabase::open_in_memory().unwrap();
+        let standby = SurrealDatabase::open_in_memory().unwrap();
+        standby.unlock_identity().unwrap();
+        for e in [
+            entry("kn-a", "public", "2026-03-01T10:00:00Z"),
+            entry("kn-b", "private", "2026-03-02T10:00:00Z"),
+        ] {
+            primary.upsert_knowledge(&e).unwrap();
+        }
+        primary.add_relationship("kn-a", "kn-b", "related").unwrap();
+
+        let first = replicate_once(&primary, &standby, None).unwrap();
+        assert_eq!(first.entries, 2);
+        assert_eq!(first.relationships_added, 1);
+        assert!(verify(&primary, &standby).unwrap().is_clean());
+
+        // Nothing new: the boundary entry is resent but not counted
+        let idle = replicate_once(&primary, &standby, first.watermark.as_deref()).unwrap();
+        assert_eq!(idle.entries, 0);
+        assert_eq!(idle.watermark, first.watermark);
+
+        primary
+            .upsert_knowledge(&entry("kn-c", "public", "2026-03-03T10:00:00Z"))
+            .unwrap();
+        primary.purge_entries(&["kn-a".to_string()]).unwrap();
+        let drift = verify(&primary, &standby).unwrap();
+        assert_eq!(drift.missing, vec!["kn-c"]);
+        assert_eq!(drift.extra, vec!["kn-a"]);
+
+        let second = replicate_once(&primary, &standby, first.watermark.as_deref()).unwrap();
+        assert_eq!((second.entries, second.deleted), (1, 1));
+        assert!(verify(&primary, &standby).unwrap().is_clean());
+        assert!(standby.list_all_relationships().unwrap().is_empty());
+    }
+}
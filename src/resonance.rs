@@ -0,0 +1,345 @@
+//! Curating a bloom's resonance: `mx memory resonance set|show`
+//!
+//! `set` changes the level, resonance type, decay rate and anchors of one
+//! entry in place; everything else on the entry is kept. Anchors must name
+//! other entries the caller can see. `show` prints those fields along with
+//! activation tracking and the decay-adjusted resonance the wake cascade and
+//! `--min-resonance` filters see.
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+
+/// Resonance types exempt from decay (see `effective_resonance_expr`)
+pub const UNDECAYING: &[&str] = &["foundational", "transformative"];
+
+#[derive(clap::Args)]
+pub struct ResonanceArgs {
+    #[command(subcommand)]
+    pub command: ResonanceCommands,
+}
+
+#[derive(clap::Subcommand)]
+pub enum ResonanceCommands {
+    /// Set an entry's resonance level, type, decay rate or anchors
+    Set(SetArgs),
+
+    /// Show an entry's resonance, decay and activation
+    Show {
+        /// Entry ID
+        id: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct SetArgs {
+    /// Entry ID
+    pub id: String,
+
+    /// Resonance level (1-10, higher for transcendent)
+    #[arg(long)]
+    pub level: Option<i32>,
+
+    /// Resonance type (foundational, transformative, relational, operational, ephemeral, session; '-' clears)
+    #[arg(long = "type")]
+    pub resonance_type: Option<String>,
+
+    /// Decay rate (0.0-1.0)
+    #[arg(long)]
+    pub decay_rate: Option<f64>,
+
+    /// Anchors (comma-separated entry IDs, replaces all; '' clears)
+    #[arg(long)]
+    pub anchors: Option<String>,
+}
+
+/// Resonance after time-based decay, as the store computes it: 10%/week at
+/// 3 and below, 5% at 4-5, 2.5% above, since the last activation (or
+/// creation). Foundational and transformative entries don't decay.
+pub fn effective(entry: &KnowledgeEntry, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    let resonance = entry.resonance as f64;
+    if entry
+        .resonance_type
+        .as_deref()
+        .is_some_and(|t| UNDECAYING.contains(&t))
+    {
+        return resonance;
+    }
+    let since = entry
+        .last_activated
+        .as_deref()
+        .or(entry.created_at.as_deref())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+    let Some(since) = since else {
+        return resonance;
+    };
+    let weeks = (now - since.with_timezone(&chrono::Utc))
+        .num_seconds()
+        .max(0) as f64
+        / 604_800.0;
+    let base = match entry.resonance {
+        ..=3 => 0.90,
+        4..=5 => 0.95,
+        _ => 0.975,
+    };
+    resonance * f64::powf(base, weeks)
+}
+
+/// `entry` with the fields from `args` applied, and one line per change
+fn apply(entry: &KnowledgeEntry, args: &SetArgs) -> Result<(KnowledgeEntry, Vec<String>)> {
+    let mut updated = entry.clone();
+    let mut changes = Vec::new();
+    if let Some(level) = args.level {
+        changes.push(format!("resonance: {} -> {}", entry.resonance, level));
+        updated.resonance = level;
+    }
+    if let Some(ref rtype) = args.resonance_type {
+        let rtype = (rtype != "-").then(|| rtype.clone());
+        changes.push(format!(
+            "resonance_type: {} -> {}",
+            entry.resonance_type.as_deref().unwrap_or("none"),
+            rtype.as_deref().unwrap_or("none")
+        ));
+        updated.resonance_type = rtype;
+    }
+    if let Some(rate) = args.decay_rate {
+        changes.push(format!("decay_rate: {} -> {}", entry.decay_rate, rate));
+        updated.decay_rate = rate;
+    }
+    if let Some(ref anchors) = args.anchors {
+        let mut list: Vec<String> = Vec::new();
+        for anchor in anchors.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            let anchor = crate::normalize_id(anchor);
+            if anchor == entry.id {
+                bail!("{} can't anchor to itself", entry.id);
+            }
+            if !list.contains(&anchor) {
+                list.push(anchor);
+            }
+        }
+        changes.push(format!(
+            "anchors: [{}] -> [{}]",
+            entry.anchors.join(", "),
+            list.join(", ")
+        ));
+        updated.anchors = list;
+    }
+    if changes.is_empty() {
+        bail!("Nothing to set: give --level, --type, --decay-rate or --anchors");
+    }
+
+    let problems = updated.problems();
+    if !problems.is_empty() {
+        bail!("Cannot update {}: {}", entry.id, problems.join("; "));
+    }
+    Ok((updated, changes))
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ResonanceArgs) -> Result<()> {
+    match args.command {
+        ResonanceCommands::Set(args) => {
+            let id = crate::normalize_id(&args.id);
+            let Some(entry) = db.get(&id, ctx)? else {
+                bail!(MxError::not_found("Entry", id));
+            };
+            let (mut updated, changes) = apply(&entry, &args)?;
+            for anchor in &updated.anchors {
+                if !entry.anchors.contains(anchor) && db.get(anchor, ctx)?.is_none() {
+                    bail!(MxError::not_found("Anchor", anchor));
+                }
+            }
+            updated.updated_at = Some(chrono::Utc::now().to_rfc3339());
+            db.upsert_knowledge(&updated)?;
+
+            println!("{} Updated resonance of {}", "✓".green(), id.yellow());
+            for line in &changes {
+                println!("  {}", line);
+            }
+        }
+
+        ResonanceCommands::Show { id, json } => {
+            let id = crate::normalize_id(&id);
+            let Some(entry) = db.get(&id, ctx)? else {
+                bail!(MxError::not_found("Entry", id));
+            };
+            let effective = effective(&entry, chrono::Utc::now());
+            let mut anchors = Vec::new();
+            for anchor in &entry.anchors {
+                anchors.push((anchor.clone(), db.get(anchor, ctx)?.map(|a| a.title)));
+            }
+
+            if json {
+                let anchors: Vec<serde_json::Value> = anchors
+                    .iter()
+                    .map(|(id, title)| serde_json::json!({ "id": id, "title": title }))
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "id": entry.id,
+                        "title": entry.title,
+                        "resonance": entry.resonance,
+                        "effective_resonance": (effective * 100.0).round() / 100.0,
+                        "resonance_type": entry.resonance_type,
+                        "decay_rate": entry.decay_rate,
+                        "anchors": anchors,
+                        "activation_count": entry.activation_count,
+                        "last_activated": entry.last_activated,
+                    }))?
+                );
+                return Ok(());
+            }
+
+            println!("{}  {}", entry.id.yellow(), entry.title.bold());
+            println!(
+                "  Resonance:   {} ({:.2} after decay)",
+                entry.resonance, effective
+            );
+            println!(
+                "  Type:        {}",
+                entry.resonance_type.as_deref().unwrap_or("none")
+            );
+            println!("  Decay rate:  {}", entry.decay_rate);
+            println!(
+                "  Activated:   {} time(s), last {}",
+                entry.activation_count,
+                entry.last_activated.as_deref().unwrap_or("never")
+            );
+            if anchors.is_empty() {
+                println!("  Anchors:     none");
+            } else {
+                println!("  Anchors:");
+                for (anchor, title) in &anchors {
+                    match title {
+                        Some(title) => println!("    {}  {}", anchor, title),
+                        None => println!("    {}  {}", anchor, "(missing)".dimmed()),
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surreal_db::SurrealDatabase;
+
+    fn entry(id: &str, resonance: i32, rtype: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "bloom",
+            "title": id,
+            "resonance": resonance,
+            "resonance_type": rtype,
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn set(id: &str) -> SetArgs {
+        SetArgs {
+            id: id.to_string(),
+            level: None,
+            resonance_type: None,
+            decay_rate: None,
+            anchors: None,
+        }
+    }
+
+    #[test]
+    fn sets_resonance_fields_and_validates() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        for e in [
+            entry("kn-a", 5, "relational"),
+            entry("kn-b", 8, "foundational"),
+        ] {
+            db.upsert_knowledge(&e).unwrap();
+        }
+        let run_set = |args: SetArgs| {
+            run(
+                &db,
+                &ctx,
+                ResonanceArgs {
+                    command: ResonanceCommands::Set(args),
+                },
+            )
+        };
+
+        assert!(run_set(set("kn-a")).is_err());
+        assert!(
+            run_set(SetArgs {
+                level: Some(101),
+                ..set("kn-a")
+            })
+            .is_err()
+        );
+        assert!(
+            run_set(SetArgs {
+                resonance_type: Some("loud".into()),
+                ..set("kn-a")
+            })
+            .is_err()
+        );
+        assert!(
+            run_set(SetArgs {
+                anchors: Some("kn-a".into()),
+                ..set("kn-a")
+            })
+            .is_err()
+        );
+        assert!(
+            run_set(SetArgs {
+                anchors: Some("kn-zz".into()),
+                ..set("kn-a")
+            })
+            .is_err()
+        );
+
+        run_set(SetArgs {
+            level: Some(9),
+            resonance_type: Some("foundational".into()),
+            decay_rate: Some(0.0),
+            anchors: Some("b, kn-b".into()),
+            ..set("a")
+        })
+        .unwrap();
+        let a = db.get("kn-a", &ctx).unwrap().unwrap();
+        assert_eq!(
+            (a.resonance, a.resonance_type.as_deref(), a.anchors.clone()),
+            (9, Some("foundational"), vec!["kn-b".to_string()])
+        );
+
+        run_set(SetArgs {
+            resonance_type: Some("-".into()),
+            anchors: Some(String::new()),
+            ..set("kn-a")
+        })
+        .unwrap();
+        let a = db.get("kn-a", &ctx).unwrap().unwrap();
+        assert_eq!((a.resonance_type, a.anchors.len()), (None, 0));
+    }
+
+    #[test]
+    fn effective_resonance_decays_by_tier() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-15T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        // Two weeks since creation
+        assert!((effective(&entry("kn-a", 3, "ephemeral"), now) - 3.0 * 0.81).abs() < 1e-9);
+        assert!(
+            (effective(&entry("kn-a", 8, "relational"), now) - 8.0 * 0.975f64.powi(2)).abs() < 1e-9
+        );
+        assert_eq!(effective(&entry("kn-a", 8, "foundational"), now), 8.0);
+    }
+}
@@ -0,0 +1,343 @@
+//! Rot review: `mx memory review`
+//!
+//! Surfaces a few entries nobody has looked at in a while (few activations,
+//! last activated long ago) and asks whether each still holds:
+//!
+//! - relevant: nothing changes beyond the activation
+//! - stale: the entry is tagged `stale`
+//! - merge: the entry goes on the reading queue (`mx queue`) to be merged
+//!
+//! Every verdict is recorded as an activation, so a reviewed entry drops to
+//! the back of the line. The pick is drawn at random from the most neglected
+//! entries, so repeated runs don't keep showing the same ones.
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use rand::seq::IndexedRandom;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeFilter, KnowledgeStore};
+use crate::{dates, queue};
+
+/// Tag put on entries marked stale
+pub const STALE_TAG: &str = "stale";
+/// Queue priority for entries marked to merge
+const MERGE_PRIORITY: i64 = 5;
+/// The pick is drawn from this many times `--limit` of the most neglected
+const POOL_FACTOR: usize = 3;
+/// Body lines shown with each entry
+const PREVIEW_LINES: usize = 8;
+
+#[derive(clap::Args)]
+pub struct ReviewArgs {
+    /// Entries to review
+    #[arg(short = 'n', long, default_value = "5")]
+    pub limit: usize,
+
+    /// Only review entries in this category
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// List the most neglected entries instead of reviewing them
+    #[arg(long)]
+    pub list: bool,
+
+    /// Output the list as JSON (implies --list)
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// An entry up for review
+#[derive(Debug, Serialize)]
+pub struct Candidate<'a> {
+    #[serde(skip)]
+    pub entry: &'a KnowledgeEntry,
+    pub id: &'a str,
+    pub title: &'a str,
+    pub activation_count: i32,
+    /// Days since last activated (or, never activated, since last updated)
+    pub idle_days: i64,
+    /// Idle days per activation; higher is more neglected
+    pub neglect: f64,
+}
+
+/// Reviewable entries, most neglected first. Ephemeral entries and ones
+/// already marked stale are left out.
+pub fn candidates(entries: &[KnowledgeEntry], now: DateTime<Utc>) -> Vec<Candidate<'_>> {
+    let mut found: Vec<Candidate> = entries
+        .iter()
+        .filter(|e| !e.ephemeral && !e.tags.iter().any(|t| t == STALE_TAG))
+        .map(|entry| {
+            let idle_since = [&entry.last_activated, &entry.updated_at, &entry.created_at]
+                .into_iter()
+                .find_map(|at| at.as_deref().and_then(dates::parse_stored));
+            let idle_days = idle_since.map_or(0, |at| (now - at).num_days().max(0));
+            Candidate {
+                entry,
+                id: &entry.id,
+                title: &entry.title,
+                activation_count: entry.activation_count,
+                idle_days,
+                neglect: idle_days as f64 / f64::from(entry.activation_count.max(0) + 1),
+            }
+        })
+        .collect();
+    found.sort_by(|a, b| b.neglect.total_cmp(&a.neglect).then_with(|| a.id.cmp(b.id)));
+    found
+}
+
+/// `limit` candidates drawn at random from the most neglected, most
+/// neglected first
+fn pick(mut candidates: Vec<Candidate<'_>>, limit: usize) -> Vec<Candidate<'_>> {
+    candidates.truncate(limit.saturating_mul(POOL_FACTOR));
+    let chosen: Vec<&str> = candidates
+        .choose_multiple(&mut rand::rng(), limit)
+        .map(|c| c.id)
+        .collect();
+    candidates.retain(|c| chosen.contains(&c.id));
+    candidates
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    Relevant,
+    Stale,
+    Merge,
+}
+
+/// Record `verdict` on `entry`: tag or queue it as needed, then count the
+/// review as an activation
+fn record(db: &dyn KnowledgeStore, entry: &KnowledgeEntry, verdict: Verdict) -> Result<()> {
+    match verdict {
+        Verdict::Relevant => {}
+        Verdict::Stale => {
+            let mut tagged = entry.clone();
+            tagged.tags.push(STALE_TAG.to_string());
+            db.upsert_knowledge(&tagged)?;
+        }
+        Verdict::Merge => {
+            let note = "review: merge with a related entry".to_string();
+            queue::enqueue_entry(db, entry, MERGE_PRIORITY, Some(note))?;
+        }
+    }
+    db.update_activations(std::slice::from_ref(&entry.id))
+}
+
+fn preview(entry: &KnowledgeEntry) -> String {
+    let text = entry
+        .summary
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+        .or(entry.body.as_deref())
+        .unwrap_or("")
+        .trim();
+    let mut lines: Vec<&str> = text.lines().take(PREVIEW_LINES).collect();
+    if text.lines().count() > PREVIEW_LINES {
+        lines.push("…");
+    }
+    lines.join("\n")
+}
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok("q".to_string());
+    }
+    Ok(input.trim().to_lowercase())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: ReviewArgs) -> Result<()> {
+    let entries = match args.category {
+        Some(ref category) => db.list_by_category(category, ctx, &KnowledgeFilter::default())?,
+        None => db.list_all(ctx)?,
+    };
+    let all = candidates(&entries, Utc::now());
+
+    if args.list || args.json {
+        let top: Vec<Candidate> = all.into_iter().take(args.limit).collect();
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&top)?);
+            return Ok(());
+        }
+        if top.is_empty() {
+            println!("Nothing to review");
+        }
+        for c in &top {
+            println!(
+                "{} {} {}",
+                c.id.cyan(),
+                c.title,
+                format!(
+                    "(idle {} day(s), {} activation(s))",
+                    c.idle_days, c.activation_count
+                )
+                .dimmed()
+            );
+        }
+        return Ok(());
+    }
+
+    let queue = pick(all, args.limit);
+    if queue.is_empty() {
+        println!("{}", "Nothing to review".green());
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        bail!("review requires an interactive terminal (use --list to see what is due)");
+    }
+
+    println!(
+        "{} entr{} to review: r still relevant, s stale, m merge; Enter skips, q quits.\n",
+        queue.len(),
+        if queue.len() == 1 { "y" } else { "ies" }
+    );
+
+    let mut counts = [0usize; 3];
+    for (i, c) in queue.iter().enumerate() {
+        println!(
+            "{} {} {} {}",
+            format!("[{}/{}]", i + 1, queue.len()).dimmed(),
+            c.title.bold(),
+            format!("({}, {})", c.entry.category_id, c.id).dimmed(),
+            format!(
+                "idle {} day(s), {} activation(s)",
+                c.idle_days, c.activation_count
+            )
+            .dimmed()
+        );
+        println!("\n{}\n", preview(c.entry));
+
+        let verdict = loop {
+            match prompt("  [r/s/m] ")?.as_str() {
+                "r" => break Some(Verdict::Relevant),
+                "s" => break Some(Verdict::Stale),
+                "m" => break Some(Verdict::Merge),
+                "" => break None,
+                "q" => {
+                    print_summary(counts);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        };
+        let Some(verdict) = verdict else {
+            println!();
+            continue;
+        };
+        record(db, c.entry, verdict)?;
+        counts[verdict as usize] += 1;
+        println!("  {}\n", "✓".green());
+    }
+
+    print_summary(counts);
+    Ok(())
+}
+
+fn print_summary([relevant, stale, merge]: [usize; 3]) {
+    println!(
+        "Reviewed {}: {} still relevant, {} stale, {} to merge",
+        (relevant + stale + merge).to_string().bold(),
+        relevant,
+        stale,
+        merge
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, updated: &str, activated: Option<&str>, count: i32) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": format!("Entry {}", id),
+            "body": "backoff",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": updated,
+            "last_activated": activated,
+            "activation_count": count,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn neglected_entries_come_first() {
+        let now = dates::parse_stored("2026-01-01T00:00:00Z").unwrap();
+        let mut stale = entry("kn-old", "2025-01-01T00:00:00Z", None, 0);
+        stale.tags.push(STALE_TAG.to_string());
+        let mut ephemeral = entry("kn-eph", "2025-01-01T00:00:00Z", None, 0);
+        ephemeral.ephemeral = true;
+        let entries = [
+            entry(
+                "kn-busy",
+                "2025-01-01T00:00:00Z",
+                Some("2025-12-01T00:00:00Z"),
+                20,
+            ),
+            entry("kn-idle", "2025-06-01T00:00:00Z", None, 0),
+            entry(
+                "kn-used",
+                "2025-01-01T00:00:00Z",
+                Some("2025-07-03T00:00:00Z"),
+                1,
+            ),
+            stale,
+            ephemeral,
+        ];
+
+        let found = candidates(&entries, now);
+        let ids: Vec<&str> = found.iter().map(|c| c.id).collect();
+        assert_eq!(ids, ["kn-idle", "kn-used", "kn-busy"]);
+        assert_eq!(found[0].idle_days, 214);
+        assert_eq!(found[1].idle_days, 182);
+        assert!((found[1].neglect - 91.0).abs() < 1e-9);
+
+        let picked = pick(candidates(&entries, now), 2);
+        assert_eq!(picked.len(), 2);
+        assert!(picked[0].neglect >= picked[1].neglect);
+    }
+
+    #[test]
+    fn verdicts_tag_queue_and_activate() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        for id in ["kn-a", "kn-b", "kn-c"] {
+            db.upsert_knowledge(&entry(id, "2025-01-01T00:00:00Z", None, 0))
+                .unwrap();
+        }
+
+        for (id, verdict) in [
+            ("kn-a", Verdict::Relevant),
+            ("kn-b", Verdict::Stale),
+            ("kn-c", Verdict::Merge),
+        ] {
+            let entry = db.get(id, &ctx).unwrap().unwrap();
+            record(&db, &entry, verdict).unwrap();
+        }
+
+        let a = db.get("kn-a", &ctx).unwrap().unwrap();
+        assert_eq!(a.activation_count, 1);
+        assert!(a.last_activated.is_some());
+        assert!(a.tags.is_empty());
+        assert_eq!(
+            db.get("kn-b", &ctx).unwrap().unwrap().tags,
+            [STALE_TAG.to_string()]
+        );
+        let queued = db.list_queue(false).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].target, "kn-c");
+
+        // Reviewed entries are fresh again; stale ones drop out
+        let entries = db.list_all(&ctx).unwrap();
+        let found = candidates(&entries, Utc::now());
+        let ids: Vec<&str> = found.iter().map(|c| c.id).collect();
+        assert_eq!(ids, ["kn-a", "kn-c"]);
+        assert!(found.iter().all(|c| c.idle_days == 0));
+    }
+}
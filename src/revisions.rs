@@ -0,0 +1,394 @@
+//! Revision history: point-in-time reconstruction (`--as-of`), plus
+//! `mx memory history` and `mx memory rollback`
+//!
+//! Every edit, append, prepend, update, delete, and upsert that changes an
+//! entry's title or body snapshots them into `memory_backup` *before* the
+//! change lands. Each snapshot gets the entry's next revision number (1 for
+//! its first) when it is taken, and keeps it: snapshots are never pruned, so
+//! the number `history` shows today still names the same revision later.
+//! `rollback --to` takes that number or a backup ID. The state of an entry
+//! at time T is therefore the snapshot taken by the first change after T,
+//! or the current content if nothing changed since.
+//!
+//! Limits: only title and body are versioned (category, tags, and resonance
+//! reflect the current entry), and deleted entries are not reconstructed.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+
+use crate::error::MxError;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::types::MemoryBackup;
+
+#[derive(clap::Args)]
+pub struct HistoryArgs {
+    /// Entry ID
+    pub id: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args)]
+pub struct RollbackArgs {
+    /// Entry ID
+    pub id: String,
+
+    /// Revision to restore: its number in `mx memory history` or its backup ID
+    #[arg(long)]
+    pub to: String,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Parse an `--as-of` value (any form `dates::parse_date` accepts)
+pub fn parse_as_of(value: &str) -> Result<DateTime<Utc>> {
+    crate::dates::parse_date(value).context("Invalid --as-of")
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    let value = value?;
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Rebuild `entry` as it stood at `at` from its backups (any order).
+/// Returns `None` if the entry did not exist yet.
+pub fn reconstruct(
+    entry: &KnowledgeEntry,
+    backups: &[MemoryBackup],
+    at: DateTime<Utc>,
+) -> Option<KnowledgeEntry> {
+    if parse_timestamp(entry.created_at.as_deref()).is_some_and(|created| created > at) {
+        return None;
+    }
+
+    let mut dated: Vec<(DateTime<Utc>, &MemoryBackup)> = backups
+        .iter()
+        .filter_map(|b| parse_timestamp(b.created_at.as_deref()).map(|t| (t, b)))
+        .collect();
+    dated.sort_by_key(|(t, _)| *t);
+
+    // The first change after `at` captured the content in effect at `at`
+    let Some(idx) = dated.iter().position(|(t, _)| *t > at) else {
+        return Some(entry.clone());
+    };
+    let (_, snapshot) = dated[idx];
+
+    let mut historical = entry.clone();
+    historical.title = snapshot.title.clone();
+    historical.body = snapshot.body.clone();
+    historical.content_hash = Some(snapshot.content_hash.clone());
+    // That version dates from the previous change, or from creation
+    historical.updated_at = match idx {
+        0 => entry.created_at.clone(),
+        _ => Some(dated[idx - 1].0.to_rfc3339()),
+    };
+    Some(historical)
+}
+
+/// Fetch backups for one entry and reconstruct it at `at`
+pub fn entry_as_of(
+    db: &dyn KnowledgeStore,
+    entry: &KnowledgeEntry,
+    at: DateTime<Utc>,
+) -> Result<Option<KnowledgeEntry>> {
+    let backups = db.list_backups(&entry.id)?;
+    Ok(reconstruct(entry, &backups, at))
+}
+
+/// Reconstruct a set of entries at `at`, dropping those created later
+pub fn entries_as_of(
+    db: &dyn KnowledgeStore,
+    entries: Vec<KnowledgeEntry>,
+    at: DateTime<Utc>,
+) -> Result<Vec<KnowledgeEntry>> {
+    let mut out = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        if let Some(historical) = entry_as_of(db, entry, at)? {
+            out.push(historical);
+        }
+    }
+    Ok(out)
+}
+
+/// Keyword match used for `search --as-of`, where the full-text index only
+/// covers current content: every query term must appear in the title, body,
+/// or tags (case-insensitive)
+pub fn matches_query(entry: &KnowledgeEntry, query: &str) -> bool {
+    let haystack = format!(
+        "{}\n{}\n{}",
+        entry.title,
+        entry.body.as_deref().unwrap_or(""),
+        entry.tags.join(" ")
+    )
+    .to_lowercase();
+    query
+        .split_whitespace()
+        .all(|term| haystack.contains(&term.to_lowercase()))
+}
+
+/// Backups oldest first (in revision number order)
+pub fn numbered(mut backups: Vec<MemoryBackup>) -> Vec<MemoryBackup> {
+    backups.sort_by_key(|b| (b.rev, parse_timestamp(b.created_at.as_deref())));
+    backups
+}
+
+/// Find `rev` (a revision number or backup ID) among `numbered` backups
+pub fn resolve<'a>(numbered: &'a [MemoryBackup], rev: &str) -> Option<&'a MemoryBackup> {
+    match rev.parse::<u32>() {
+        Ok(n) => numbered.iter().find(|b| b.rev == Some(n)),
+        Err(_) => numbered.iter().find(|b| b.id == rev),
+    }
+}
+
+fn visible(db: &dyn KnowledgeStore, id: &str, ctx: &AgentContext) -> Result<KnowledgeEntry> {
+    db.get(id, ctx)?.ok_or_else(|| {
+        MxError::NotFound {
+            kind: "Entry",
+            id: id.to_string(),
+        }
+        .into()
+    })
+}
+
+/// `mx memory history <id>`: stored revisions of an entry, oldest first
+pub fn history(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &HistoryArgs) -> Result<()> {
+    let id = crate::normalize_id(&args.id);
+    let current = visible(db, &id, ctx)?;
+    let revisions = numbered(db.list_backups(&id)?);
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "id": id,
+                "revisions": revisions,
+                "current": {
+                    "title": current.title,
+                    "content_hash": current.content_hash,
+                    "updated_at": current.updated_at,
+                },
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!("History of {}:", id);
+    if revisions.is_empty() {
+        println!("  (no earlier revisions)");
+    }
+    for b in &revisions {
+        println!(
+            "  {:>3}  {}  {:<8} {:<12} {}  ({} bytes)",
+            b.rev.map_or("-".to_string(), |rev| rev.to_string()),
+            b.created_at.as_deref().unwrap_or("unknown"),
+            b.operation,
+            b.source_agent.as_deref().unwrap_or("-"),
+            b.title,
+            b.body.as_ref().map_or(0, |s| s.len()),
+        );
+    }
+    println!(
+        "  now  {}  {}",
+        current.updated_at.as_deref().unwrap_or("unknown"),
+        current.title
+    );
+    Ok(())
+}
+
+/// `mx memory rollback <id> --to <rev>`: put a revision's title and body
+/// back, keeping the replaced content as a new revision
+pub fn rollback(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    agent: Option<&str>,
+    args: &RollbackArgs,
+) -> Result<KnowledgeEntry> {
+    let id = crate::normalize_id(&args.id);
+    let mut entry = visible(db, &id, ctx)?;
+    let revisions = numbered(db.list_backups(&id)?);
+    let Some(revision) = resolve(&revisions, &args.to) else {
+        bail!(
+            "No revision '{}' for {} ({} stored, see `mx memory history {}`)",
+            args.to,
+            id,
+            revisions.len(),
+            id
+        );
+    };
+    let revision = revision.clone();
+
+    db.backup_content(&entry, "rollback", agent)?;
+    entry.title = revision.title;
+    entry.body = revision.body;
+    entry.content_hash = Some(KnowledgeEntry::compute_hash(
+        entry.body.as_deref().unwrap_or(""),
+    ));
+    entry.updated_at = Some(Utc::now().to_rfc3339());
+    db.upsert_knowledge(&entry)?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "rolled_back": true,
+                "id": id,
+                "to": revision.id,
+                "revision_created": revision.created_at,
+            }))?
+        );
+    } else {
+        println!("Rolled back {} to {}", id, revision.id);
+        println!(
+            "  revision created: {}",
+            revision.created_at.as_deref().unwrap_or("unknown")
+        );
+        println!("  replaced content kept as the newest revision");
+    }
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(created: &str, title: &str, body: &str) -> KnowledgeEntry {
+        KnowledgeEntry {
+            id: "kn-test".to_string(),
+            category_id: "pattern".to_string(),
+            title: title.to_string(),
+            body: Some(body.to_string()),
+            summary: None,
+            applicability: vec![],
+            source_project_id: None,
+            source_agent_id: None,
+            file_path: None,
+            tags: vec![],
+            created_at: Some(created.to_string()),
+            updated_at: Some(created.to_string()),
+            content_hash: None,
+            source_type_id: None,
+            entry_type_id: None,
+            session_id: None,
+            ephemeral: false,
+            content_type_id: None,
+            owner: None,
+            visibility: "public".to_string(),
+            resonance: 5,
+            resonance_type: None,
+            last_activated: None,
+            activation_count: 0,
+            decay_rate: 0.0,
+            anchors: vec![],
+            wake_phrases: vec![],
+            wake_order: None,
+            wake_phrase: None,
+            embedding: None,
+            embedding_model: None,
+            embedded_at: None,
+            format: "markdown".to_string(),
+            effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    fn backup(at: &str, title: &str, body: &str) -> MemoryBackup {
+        MemoryBackup {
+            id: format!("test_{}", at),
+            entry_id: "kn-test".to_string(),
+            title: title.to_string(),
+            body: Some(body.to_string()),
+            content_hash: format!("hash-{}", body),
+            operation: "edit".to_string(),
+            source_agent: None,
+            created_at: Some(at.to_string()),
+            rev: None,
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_as_of(s).unwrap()
+    }
+
+    #[test]
+    fn parses_dates_and_timestamps() {
+        assert_eq!(
+            at("2026-01-01T00:00:00Z").to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            at("2026-01-01T12:30:00+02:00").to_rfc3339(),
+            "2026-01-01T10:30:00+00:00"
+        );
+        assert!(parse_as_of("last tuesday").is_err());
+    }
+
+    #[test]
+    fn reconstructs_each_version() {
+        let current = entry("2026-01-01T00:00:00Z", "v3", "third");
+        let backups = vec![
+            backup("2026-03-01T00:00:00Z", "v2", "second"),
+            backup("2026-02-01T00:00:00Z", "v1", "first"),
+        ];
+
+        assert!(reconstruct(&current, &backups, at("2025-12-31")).is_none());
+
+        let v1 = reconstruct(&current, &backups, at("2026-01-15")).unwrap();
+        assert_eq!(v1.body.as_deref(), Some("first"));
+        assert_eq!(v1.updated_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+
+        let v2 = reconstruct(&current, &backups, at("2026-02-15")).unwrap();
+        assert_eq!(v2.title, "v2");
+        assert_eq!(v2.content_hash.as_deref(), Some("hash-second"));
+
+        let v3 = reconstruct(&current, &backups, at("2026-04-01")).unwrap();
+        assert_eq!(v3.body.as_deref(), Some("third"));
+    }
+
+    #[test]
+    fn revisions_resolve_by_number_or_id() {
+        // Revision 1 was removed by hand: the others keep their numbers
+        let numbered_backup = |rev, at, title, body| MemoryBackup {
+            rev: Some(rev),
+            ..backup(at, title, body)
+        };
+        let revisions = numbered(vec![
+            numbered_backup(3, "2026-03-01T00:00:00Z", "v2", "second"),
+            numbered_backup(2, "2026-02-01T00:00:00Z", "v1", "first"),
+        ]);
+        assert_eq!(revisions[0].title, "v1");
+        assert_eq!(resolve(&revisions, "2").unwrap().title, "v1");
+        assert_eq!(resolve(&revisions, "3").unwrap().title, "v2");
+        assert!(resolve(&revisions, "0").is_none());
+        assert!(resolve(&revisions, "1").is_none());
+        assert!(resolve(&revisions, "4").is_none());
+        assert_eq!(
+            resolve(&revisions, "test_2026-03-01T00:00:00Z")
+                .unwrap()
+                .title,
+            "v2"
+        );
+    }
+
+    #[test]
+    fn query_matching_needs_all_terms() {
+        let e = entry(
+            "2026-01-01T00:00:00Z",
+            "Retry pattern",
+            "Use exponential BACKOFF",
+        );
+        assert!(matches_query(&e, "retry backoff"));
+        assert!(!matches_query(&e, "retry jitter"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Keyword search settings
+//!
+//! Keyword search tokenizes titles, bodies and summaries with the `simple`
+//! analyzer: split on whitespace and wherever characters change class
+//! (letters, digits, punctuation), then lowercase. Hits are ranked by BM25
+//! score weighted per field, so a title match counts for more than the same
+//! words in a long body. A `search` section in `config.yaml` adjusts both:
+//!
+//! ```yaml
+//! search:
+//!   stemming: true          # "retries" finds "retry"
+//!   language: english       # snowball stemmer language
+//!   stop_words: [the, a, of]
+//!   symbols: keep           # split on whitespace only: base-d, v2, ᚱᚢᚾ stay whole
+//!   weights: {title: 3, summary: 2, body: 1, tags: 2}
+//! ```
+//!
+//! The store redefines the analyzer and rebuilds the full-text indexes the
+//! first time it opens after the analyzer settings change. SurrealDB
+//! analyzers can't drop stop words, so they are left out of the query
+//! instead. Tags aren't full-text indexed: a tag scores when it equals a
+//! query word, and only counts with `--fields tags`.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_yaml::Mapping;
+
+use crate::{config, paths};
+
+const SEARCH_KEY: &str = "search";
+
+/// Languages the snowball stemmer knows
+const LANGUAGES: [&str; 17] = [
+    "arabic",
+    "danish",
+    "dutch",
+    "english",
+    "french",
+    "german",
+    "greek",
+    "hungarian",
+    "italian",
+    "norwegian",
+    "portuguese",
+    "romanian",
+    "russian",
+    "spanish",
+    "swedish",
+    "tamil",
+    "turkish",
+];
+
+/// A field keyword search can match in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SearchField {
+    Title,
+    Body,
+    Summary,
+    Tags,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 4] = [
+        SearchField::Title,
+        SearchField::Body,
+        SearchField::Summary,
+        SearchField::Tags,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchField::Title => "title",
+            SearchField::Body => "body",
+            SearchField::Summary => "summary",
+            SearchField::Tags => "tags",
+        }
+    }
+
+    /// Matched when `--fields` isn't given
+    pub const TEXT: [SearchField; 3] =
+        [SearchField::Title, SearchField::Body, SearchField::Summary];
+}
+
+#[derive(clap::Args)]
+pub struct FieldsArgs {
+    /// Only match in these fields (default: title,body,summary)
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with_all = ["semantic", "as_of"])]
+    pub fields: Vec<SearchField>,
+}
+
+impl FieldsArgs {
+    pub fn selected(&self) -> Option<Vec<SearchField>> {
+        (!self.fields.is_empty()).then(|| self.fields.clone())
+    }
+}
+
+/// How much a match in each field counts toward the text score
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Weights {
+    #[serde(default = "default_title_weight")]
+    title: f64,
+    #[serde(default = "default_summary_weight")]
+    summary: f64,
+    #[serde(default = "default_body_weight")]
+    body: f64,
+    #[serde(default = "default_tags_weight")]
+    tags: f64,
+}
+
+fn default_title_weight() -> f64 {
+    3.0
+}
+
+fn default_summary_weight() -> f64 {
+    2.0
+}
+
+fn default_body_weight() -> f64 {
+    1.0
+}
+
+fn default_tags_weight() -> f64 {
+    2.0
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            title: default_title_weight(),
+            summary: default_summary_weight(),
+            body: default_body_weight(),
+            tags: default_tags_weight(),
+        }
+    }
+}
+
+impl Weights {
+    pub fn of(&self, field: SearchField) -> f64 {
+        match field {
+            SearchField::Title => self.title,
+            SearchField::Body => self.body,
+            SearchField::Summary => self.summary,
+            SearchField::Tags => self.tags,
+        }
+    }
+}
+
+/// How words holding symbols are tokenized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Symbols {
+    /// Break words where letters, digits and punctuation meet
+    #[default]
+    Split,
+    /// Only break on whitespace (punctuation stays attached)
+    Keep,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    #[serde(default)]
+    stemming: bool,
+    #[serde(default = "default_language")]
+    language: String,
+    #[serde(default)]
+    stop_words: Vec<String>,
+    #[serde(default)]
+    symbols: Symbols,
+    #[serde(default)]
+    pub weights: Weights,
+}
+
+fn default_language() -> String {
+    "english".into()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            stemming: false,
+            language: default_language(),
+            stop_words: Vec::new(),
+            symbols: Symbols::default(),
+            weights: Weights::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self> {
+        Self::from_config(&config::load(&paths::config_file())?)
+    }
+
+    fn from_config(config: &Mapping) -> Result<Self> {
+        let Some(value) = config.get(SEARCH_KEY) else {
+            return Ok(Self::default());
+        };
+        let mut settings: Settings = serde_yaml::from_value(value.clone())
+            .context("Invalid 'search' section in config.yaml")?;
+        settings.language = settings.language.to_lowercase();
+        if !LANGUAGES.contains(&settings.language.as_str()) {
+            bail!(
+                "Unknown search.language '{}' in config.yaml. Valid languages: {}",
+                settings.language,
+                LANGUAGES.join(", ")
+            );
+        }
+        for word in &mut settings.stop_words {
+            *word = word.to_lowercase();
+        }
+        let w = &settings.weights;
+        if [w.title, w.summary, w.body, w.tags]
+            .iter()
+            .any(|w| *w < 0.0)
+        {
+            bail!("search.weights in config.yaml can't be negative");
+        }
+        Ok(settings)
+    }
+
+    /// The `DEFINE ANALYZER` statement for these settings
+    pub fn definition(&self) -> String {
+        let tokenizers = match self.symbols {
+            Symbols::Split => "blank, class",
+            Symbols::Keep => "blank",
+        };
+        let mut filters = vec!["lowercase".to_string()];
+        if self.stemming {
+            filters.push(format!("snowball({})", self.language));
+        }
+        format!(
+            "DEFINE ANALYZER OVERWRITE simple TOKENIZERS {} FILTERS {}",
+            tokenizers,
+            filters.join(", ")
+        )
+    }
+
+    pub fn is_stop_word(&self, word: &str) -> bool {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        self.stop_words.iter().any(|s| *s == word.to_lowercase())
+    }
+
+    /// `query` without its stop words (unchanged if that would leave nothing)
+    pub fn keywords(&self, query: &str) -> String {
+        let kept: Vec<&str> = query
+            .split_whitespace()
+            .filter(|word| !self.is_stop_word(word))
+            .collect();
+        if kept.is_empty() {
+            query.to_string()
+        } else {
+            kept.join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(yaml: &str) -> Result<Settings> {
+        Settings::from_config(&serde_yaml::from_str(yaml).unwrap())
+    }
+
+    #[test]
+    fn config_shapes_the_analyzer_and_query() {
+        assert_eq!(
+            Settings::default().definition(),
+            "DEFINE ANALYZER OVERWRITE simple TOKENIZERS blank, class FILTERS lowercase"
+        );
+
+        let custom = settings(
+            "search:\n  stemming: true\n  language: French\n  stop_words: [The, of]\n  symbols: keep\n",
+        )
+        .unwrap();
+        assert_eq!(
+            custom.definition(),
+            "DEFINE ANALYZER OVERWRITE simple TOKENIZERS blank FILTERS lowercase, snowball(french)"
+        );
+        assert_eq!(custom.keywords("the shape OF the rune"), "shape rune");
+        assert_eq!(custom.keywords("The, of"), "The, of");
+
+        assert!(settings("search:\n  language: klingon\n").is_err());
+        assert!(settings("search:\n  symbols: mangle\n").is_err());
+        assert!(settings("search:\n  stemmer: true\n").is_err());
+
+        let weighted = settings("search:\n  weights: {body: 0.5}\n").unwrap();
+        assert_eq!(weighted.weights.of(SearchField::Body), 0.5);
+        assert_eq!(weighted.weights.of(SearchField::Title), 3.0);
+        assert!(settings("search:\n  weights: {title: -1}\n").is_err());
+    }
+}
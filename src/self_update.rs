@@ -0,0 +1,391 @@
+//! Self-update from GitHub releases: `mx self-update`
+//!
+//! Looks up the newest release on the chosen channel, downloads the binary
+//! asset for this platform, checks it against the published SHA-256
+//! checksum, and atomically replaces the running executable.
+//!
+//! Channels:
+//! - `stable`: newest release not marked as a prerelease
+//! - `nightly`: newest release of any kind (prereleases included)
+//!
+//! Releases must publish raw binaries named with the platform (e.g.
+//! `mx-x86_64-linux`) alongside either a `<asset>.sha256` file or a
+//! `SHA256SUMS` manifest. Assets without a checksum are refused.
+//!
+//! The checksum comes from the same release as the binary, so it catches a
+//! corrupted or truncated download, not a tampered release: anyone able to
+//! replace the asset can replace its checksum too.
+
+use anyhow::{Context, Result, bail};
+use reqwest::blocking::Client;
+use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::io::Write;
+
+use crate::sync::github::CheckStatus;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/coryzibell/mx/releases";
+const USER_AGENT_VALUE: &str = "mx-self-update/0.1";
+
+/// Release channel to track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Channel {
+    /// Tagged, non-prerelease builds
+    Stable,
+    /// Newest build, prereleases included
+    Nightly,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Run `mx self-update`
+pub fn self_update(channel: Channel, check_only: bool, force: bool) -> Result<()> {
+    let client = build_client()?;
+    let current = env!("CARGO_PKG_VERSION");
+
+    let releases: Vec<Release> = client
+        .get(RELEASES_URL)
+        .send()
+        .context("Failed to fetch releases")?
+        .check()
+        .context("GitHub releases request failed")?
+        .json()
+        .context("Failed to parse releases")?;
+
+    let release = select_release(&releases, channel)
+        .ok_or_else(|| anyhow::anyhow!("No releases found on the {:?} channel", channel))?;
+
+    let latest = release.tag_name.trim_start_matches('v');
+    let newer = is_newer(latest, current);
+
+    println!("Current version: {}", current);
+    println!("Latest ({:?}):   {}", channel, latest);
+
+    if !newer && !force {
+        println!("Already up to date.");
+        return Ok(());
+    }
+    if check_only {
+        if newer {
+            println!("Update available. Run `mx self-update` to install.");
+        }
+        return Ok(());
+    }
+
+    let asset = select_asset(
+        &release.assets,
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+    )
+    .ok_or_else(|| {
+        anyhow::anyhow!(
+            "Release {} has no binary for {}-{}",
+            release.tag_name,
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        )
+    })?;
+
+    let expected = find_checksum(&client, &release.assets, &asset.name)?;
+
+    println!("Downloading {}...", asset.name);
+    let bytes = client
+        .get(&asset.browser_download_url)
+        .header(ACCEPT, "application/octet-stream")
+        .send()
+        .context("Failed to download release asset")?
+        .check()
+        .context("Release asset download failed")?
+        .bytes()
+        .context("Failed to read release asset")?;
+
+    let actual = sha256_hex(&bytes);
+    if !actual.eq_ignore_ascii_case(&expected) {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset.name,
+            expected,
+            actual
+        );
+    }
+    println!("Download intact, checksum matches ({})", &actual[..12]);
+
+    replace_current_exe(&bytes)?;
+    println!("Updated mx {} -> {}", current, latest);
+    Ok(())
+}
+
+fn build_client() -> Result<Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_VALUE));
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("application/vnd.github+json"),
+    );
+    // A token is optional; it only raises the rate limit
+    if let Ok(token) = std::env::var("GITHUB_TOKEN")
+        && !token.is_empty()
+    {
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).context("Invalid token format")?,
+        );
+    }
+    Client::builder()
+        .default_headers(headers)
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Pick the newest release for a channel. GitHub returns releases newest first.
+fn select_release(releases: &[Release], channel: Channel) -> Option<Release> {
+    releases
+        .iter()
+        .filter(|r| !r.draft)
+        .find(|r| channel == Channel::Nightly || !r.prerelease)
+        .cloned()
+}
+
+/// Pick the binary asset for this platform, ignoring checksum files
+fn select_asset<'a>(assets: &'a [Asset], arch: &str, os: &str) -> Option<&'a Asset> {
+    assets.iter().find(|a| {
+        let name = a.name.to_lowercase();
+        name.contains(arch) && name.contains(os) && !is_checksum_file(&name)
+    })
+}
+
+fn is_checksum_file(name: &str) -> bool {
+    name.ends_with(".sha256") || name.eq_ignore_ascii_case("SHA256SUMS")
+}
+
+/// Find the expected SHA-256 for `asset_name` from `<asset>.sha256` or `SHA256SUMS`
+fn find_checksum(client: &Client, assets: &[Asset], asset_name: &str) -> Result<String> {
+    let sidecar = format!("{}.sha256", asset_name);
+    let source = assets
+        .iter()
+        .find(|a| a.name == sidecar)
+        .or_else(|| {
+            assets
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case("SHA256SUMS"))
+        })
+        .ok_or_else(|| anyhow::anyhow!("Release publishes no checksum for {}", asset_name))?;
+
+    let text = client
+        .get(&source.browser_download_url)
+        .header(ACCEPT, "application/octet-stream")
+        .send()
+        .context("Failed to download checksum")?
+        .check()
+        .context("Checksum download failed")?
+        .text()
+        .context("Failed to read checksum")?;
+
+    parse_checksum(&text, asset_name)
+        .ok_or_else(|| anyhow::anyhow!("No checksum entry for {} in {}", asset_name, source.name))
+}
+
+/// Parse `sha256sum`-style output. A single bare hash is accepted as-is;
+/// otherwise the line whose file name matches `asset_name` is used.
+fn parse_checksum(text: &str, asset_name: &str) -> Option<String> {
+    let is_hash = |s: &str| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(hash) = parts.next() else { continue };
+        if !is_hash(hash) {
+            continue;
+        }
+        match parts.next() {
+            None => return Some(hash.to_lowercase()),
+            Some(name) if name.trim_start_matches('*') == asset_name => {
+                return Some(hash.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Semver precedence: dotted numeric core first (`0.1.107` > `0.1.99`), then
+/// a release above its own pre-releases, which compare identifier by
+/// identifier (`nightly.10` > `nightly.9`, numbers below words, more
+/// identifiers above fewer). Build metadata (`+...`) is ignored.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    compare_versions(candidate, current) == Ordering::Greater
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(v: &str) -> (Vec<u64>, Option<&str>) {
+        let v = v.split('+').next().unwrap_or("");
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (v, None),
+        };
+        let core = core.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        (core, pre)
+    }
+    fn identifier(a: &str, b: &str) -> Ordering {
+        match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        }
+    }
+
+    let (core_a, pre_a) = split(a);
+    let (core_b, pre_b) = split(b);
+    core_a.cmp(&core_b).then_with(|| match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let mut ids_a = a.split('.');
+            let mut ids_b = b.split('.');
+            loop {
+                match (ids_a.next(), ids_b.next()) {
+                    (Some(x), Some(y)) => match identifier(x, y) {
+                        Ordering::Equal => continue,
+                        other => return other,
+                    },
+                    (Some(_), None) => return Ordering::Greater,
+                    (None, Some(_)) => return Ordering::Less,
+                    (None, None) => return Ordering::Equal,
+                }
+            }
+        }
+    })
+}
+
+/// Write the new binary next to the current one and rename it into place,
+/// so a failed update never leaves a half-written executable.
+fn replace_current_exe(bytes: &[u8]) -> Result<()> {
+    let exe = std::env::current_exe().context("Cannot locate current executable")?;
+    let exe = exe.canonicalize().unwrap_or(exe);
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Executable has no parent directory"))?;
+
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Cannot write to {}", dir.display()))?;
+    tmp.write_all(bytes)?;
+    tmp.flush()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(tmp.path(), std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    tmp.persist(&exe)
+        .with_context(|| format!("Failed to replace {}", exe.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            prerelease,
+            draft: false,
+            assets: vec![],
+        }
+    }
+
+    fn asset(name: &str) -> Asset {
+        Asset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.invalid/{}", name),
+        }
+    }
+
+    #[test]
+    fn version_ordering() {
+        assert!(is_newer("0.1.108", "0.1.107"));
+        assert!(is_newer("0.2.0", "0.1.999"));
+        assert!(!is_newer("0.1.99", "0.1.107"));
+        assert!(!is_newer("0.1.107", "0.1.107"));
+        assert!(!is_newer("0.1.107-nightly.2", "0.1.107"));
+        assert!(is_newer("0.1.107", "0.1.107-nightly.2"));
+        assert!(is_newer("0.2.0-nightly.1", "0.2.0-nightly.0"));
+        assert!(is_newer("0.2.0-nightly.10", "0.2.0-nightly.9"));
+        assert!(is_newer("0.2.0-nightly.0", "0.1.999"));
+        assert!(is_newer("0.2.0-rc.1", "0.2.0-beta.3"));
+        assert!(is_newer("0.2.0-alpha.beta", "0.2.0-alpha.1"));
+        assert!(is_newer("0.2.0-alpha.1", "0.2.0-alpha"));
+        assert!(!is_newer("0.2.0-nightly.1+build.7", "0.2.0-nightly.1"));
+    }
+
+    #[test]
+    fn channel_selection() {
+        let releases = vec![
+            release("v0.2.0-nightly.1", true),
+            release("v0.1.108", false),
+        ];
+        assert_eq!(
+            select_release(&releases, Channel::Stable).unwrap().tag_name,
+            "v0.1.108"
+        );
+        assert_eq!(
+            select_release(&releases, Channel::Nightly)
+                .unwrap()
+                .tag_name,
+            "v0.2.0-nightly.1"
+        );
+    }
+
+    #[test]
+    fn asset_selection_skips_checksums() {
+        let assets = vec![
+            asset("mx-x86_64-linux.sha256"),
+            asset("mx-aarch64-macos"),
+            asset("mx-x86_64-linux"),
+        ];
+        let picked = select_asset(&assets, "x86_64", "linux").unwrap();
+        assert_eq!(picked.name, "mx-x86_64-linux");
+        assert!(select_asset(&assets, "riscv64", "linux").is_none());
+    }
+
+    #[test]
+    fn checksum_parsing() {
+        let hash = "a".repeat(64);
+        assert_eq!(parse_checksum(&hash, "mx"), Some(hash.clone()));
+
+        let sums = format!(
+            "{}  mx-aarch64-macos\n{} *mx-x86_64-linux\n",
+            "b".repeat(64),
+            hash
+        );
+        assert_eq!(parse_checksum(&sums, "mx-x86_64-linux"), Some(hash));
+        assert_eq!(parse_checksum(&sums, "mx-other"), None);
+        assert_eq!(parse_checksum("not a hash", "mx"), None);
+    }
+}
@@ -5,6 +5,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::jsonl;
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
@@ -43,19 +45,20 @@ pub fn export_session(path: Option<String>, output: Option<String>) -> Result<()
         anyhow::bail!("Session file not found: {:?}", session_path);
     }
 
-    // Parse JSONL
-    let content = fs::read_to_string(&session_path)
+    // Parse JSONL a line at a time
+    let lines = jsonl::open(&session_path)
         .with_context(|| format!("Failed to read session file: {:?}", session_path))?;
 
     let mut messages: Vec<(String, String)> = Vec::new(); // (role, content)
     let mut tool_counts: HashMap<String, usize> = HashMap::new();
 
-    for (line_num, line) in content.lines().enumerate() {
-        if line.trim().is_empty() {
+    for line in lines {
+        let line = line?;
+        if line.is_blank() {
             continue;
         }
 
-        match serde_json::from_str::<SessionLine>(line) {
+        match line.parse::<SessionLine>() {
             Ok(SessionLine::Human { message }) => {
                 // Filter out system reminders
                 let clean_content = filter_system_reminders(&message.content);
@@ -91,7 +94,7 @@ pub fn export_session(path: Option<String>, output: Option<String>) -> Result<()
                 }
             }
             Err(e) => {
-                eprintln!("Warning: Failed to parse line {}: {}", line_num + 1, e);
+                eprintln!("Warning: Failed to parse line {}: {}", line.number, e);
                 continue;
             }
         }
@@ -187,7 +190,7 @@ pub fn find_most_recent_session() -> Result<PathBuf> {
     }
 
     // Sort by modification time (most recent first)
-    sessions.sort_by(|a, b| b.1.cmp(&a.1));
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.1));
 
     Ok(sessions[0].0.clone())
 }
@@ -0,0 +1,401 @@
+//! Static HTML export: `mx memory export --format html`
+//!
+//! Writes a browsable site for people who won't install mx: an index of
+//! categories and tags, one page per category and per tag, and one page per
+//! entry with its relationships (both directions) and anchors linked. A
+//! search box on the index filters titles, summaries and tags client-side
+//! from `search.js`, so the site works straight from disk or any static host.
+//!
+//! Entries are the ones the JSONL and markdown exports would write (see
+//! [`crate::index`]); relationships to entries outside the export are left
+//! out so no link dangles.
+//!
+//! ```text
+//! index.html  style.css  search.js
+//! categories/<category>.html
+//! tags/<tag-slug>.html
+//! entries/<id>.html
+//! ```
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::graph::xml_escape as esc;
+use crate::index::ExportFilter;
+use crate::knowledge::KnowledgeEntry;
+use crate::store::KnowledgeStore;
+use crate::text;
+use crate::types::Relationship;
+
+const STYLE: &str = r#"body { font: 16px/1.5 system-ui, sans-serif; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+a { color: #0b5fad; text-decoration: none; }
+a:hover { text-decoration: underline; }
+nav { font-size: 0.9rem; margin-bottom: 1.5rem; }
+.meta { color: #666; font-size: 0.9rem; }
+.tag { display: inline-block; background: #eef3f8; border-radius: 3px; padding: 0 0.4rem; margin: 0 0.2rem 0.2rem 0; font-size: 0.85rem; }
+.body { white-space: pre-wrap; font-family: ui-monospace, monospace; font-size: 0.9rem; background: #f7f7f7; padding: 1rem; border-radius: 4px; }
+ul.entries li { margin-bottom: 0.4rem; }
+#search { width: 100%; font-size: 1rem; padding: 0.4rem; box-sizing: border-box; }
+"#;
+
+const SEARCH: &str = r#"
+function mxSearch(input, list) {
+  const terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+  list.innerHTML = "";
+  if (!terms.length) return;
+  const hits = MX_INDEX.filter(e => {
+    const text = (e.title + " " + e.summary + " " + e.tags.join(" ")).toLowerCase();
+    return terms.every(t => text.includes(t));
+  }).slice(0, 50);
+  for (const e of hits) {
+    const li = document.createElement("li");
+    const a = document.createElement("a");
+    a.href = "entries/" + e.id + ".html";
+    a.textContent = e.title;
+    li.appendChild(a);
+    li.appendChild(document.createTextNode(" (" + e.category + ")"));
+    list.appendChild(li);
+  }
+}
+"#;
+
+/// One generated file: path relative to the site root, and its contents
+pub(crate) struct Page {
+    pub path: String,
+    pub contents: String,
+}
+
+/// Export the entries `filter` selects as a static site in `dir`
+pub fn export_html(db: &dyn KnowledgeStore, dir: &Path, filter: &ExportFilter) -> Result<usize> {
+    let entries = crate::index::export_entries(db, filter)?;
+    let relationships = db.list_all_relationships()?;
+    let pages = build(&entries, &relationships)?;
+    for page in &pages {
+        let path = dir.join(&page.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        fs::write(&path, &page.contents).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+    Ok(pages.len())
+}
+
+/// Every page of the site. `entries` are in export order.
+pub(crate) fn build(
+    entries: &[KnowledgeEntry],
+    relationships: &[Relationship],
+) -> Result<Vec<Page>> {
+    let titles: HashMap<&str, &str> = entries
+        .iter()
+        .map(|e| (e.id.as_str(), e.title.as_str()))
+        .collect();
+
+    let mut by_category: BTreeMap<&str, Vec<&KnowledgeEntry>> = BTreeMap::new();
+    let mut by_tag: BTreeMap<&str, Vec<&KnowledgeEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_category
+            .entry(&entry.category_id)
+            .or_default()
+            .push(entry);
+        for tag in &entry.tags {
+            by_tag.entry(tag).or_default().push(entry);
+        }
+    }
+    let tag_files = tag_files(by_tag.keys().copied());
+
+    let mut pages = vec![
+        Page {
+            path: "style.css".to_string(),
+            contents: STYLE.to_string(),
+        },
+        Page {
+            path: "search.js".to_string(),
+            contents: search_js(entries)?,
+        },
+        Page {
+            path: "index.html".to_string(),
+            contents: index_page(&by_category, &by_tag, &tag_files),
+        },
+    ];
+
+    for (category, members) in &by_category {
+        pages.push(Page {
+            path: format!("categories/{}.html", file_name(category)),
+            contents: list_page(&format!("Category: {}", category), members),
+        });
+    }
+    for (tag, members) in &by_tag {
+        pages.push(Page {
+            path: format!("tags/{}.html", tag_files[tag]),
+            contents: list_page(&format!("Tag: {}", tag), members),
+        });
+    }
+    for entry in entries {
+        pages.push(Page {
+            path: format!("entries/{}.html", file_name(&entry.id)),
+            contents: entry_page(entry, relationships, &titles, &tag_files),
+        });
+    }
+    Ok(pages)
+}
+
+/// A file name that is safe on every platform and within a URL
+fn file_name(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// A distinct file name for each tag (`rust` and `Rust!` both slug to `rust`)
+fn tag_files<'a>(tags: impl Iterator<Item = &'a str>) -> HashMap<&'a str, String> {
+    let mut used = HashSet::new();
+    let mut files = HashMap::new();
+    for tag in tags {
+        let mut base = text::slug(tag, 60);
+        if base.is_empty() {
+            base = "tag".to_string();
+        }
+        let mut name = base.clone();
+        let mut n = 1;
+        while !used.insert(name.clone()) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        files.insert(tag, name);
+    }
+    files
+}
+
+/// The client-side search index: one record per entry
+fn search_js(entries: &[KnowledgeEntry]) -> Result<String> {
+    let records: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "id": file_name(&e.id),
+                "title": e.title,
+                "category": e.category_id,
+                "summary": e.summary.as_deref().unwrap_or(""),
+                "tags": e.tags,
+            })
+        })
+        .collect();
+    Ok(format!(
+        "const MX_INDEX = {};\n{}",
+        serde_json::to_string(&records)?,
+        SEARCH
+    ))
+}
+
+/// A complete HTML page; `root` is the relative path back to the site root
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{title}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n</head>\n<body>\n<nav><a href=\"{root}index.html\">Knowledge base</a></nav>\n{body}</body>\n</html>\n",
+        title = esc(title),
+        root = root,
+        body = body,
+    )
+}
+
+fn tag_links(tags: &[String], tag_files: &HashMap<&str, String>, root: &str) -> String {
+    tags.iter()
+        .map(|t| {
+            format!(
+                "<a class=\"tag\" href=\"{}tags/{}.html\">{}</a>",
+                root,
+                tag_files[t.as_str()],
+                esc(t)
+            )
+        })
+        .collect()
+}
+
+fn index_page(
+    by_category: &BTreeMap<&str, Vec<&KnowledgeEntry>>,
+    by_tag: &BTreeMap<&str, Vec<&KnowledgeEntry>>,
+    tag_files: &HashMap<&str, String>,
+) -> String {
+    let total: usize = by_category.values().map(Vec::len).sum();
+    let mut body = format!(
+        "<h1>Knowledge base</h1>\n<p class=\"meta\">{} entries</p>\n\
+         <input id=\"search\" type=\"search\" placeholder=\"Search titles, summaries and tags\" \
+         oninput=\"mxSearch(this, document.getElementById('results'))\">\n\
+         <ul id=\"results\" class=\"entries\"></ul>\n<h2>Categories</h2>\n<ul>\n",
+        total
+    );
+    for (category, members) in by_category {
+        body.push_str(&format!(
+            "<li><a href=\"categories/{}.html\">{}</a> ({})</li>\n",
+            file_name(category),
+            esc(category),
+            members.len()
+        ));
+    }
+    body.push_str("</ul>\n<h2>Tags</h2>\n<p>\n");
+    for (tag, members) in by_tag {
+        body.push_str(&format!(
+            "<a class=\"tag\" href=\"tags/{}.html\">{} ({})</a>\n",
+            tag_files[tag],
+            esc(tag),
+            members.len()
+        ));
+    }
+    body.push_str("</p>\n<script src=\"search.js\"></script>\n");
+    page("Knowledge base", "", &body)
+}
+
+fn list_page(heading: &str, entries: &[&KnowledgeEntry]) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul class=\"entries\">\n", esc(heading));
+    for entry in entries {
+        body.push_str(&format!(
+            "<li><a href=\"../entries/{}.html\">{}</a>",
+            file_name(&entry.id),
+            esc(&entry.title)
+        ));
+        if let Some(summary) = &entry.summary {
+            body.push_str(&format!(" <span class=\"meta\">{}</span>", esc(summary)));
+        }
+        body.push_str("</li>\n");
+    }
+    body.push_str("</ul>\n");
+    page(heading, "../", &body)
+}
+
+fn entry_link(id: &str, titles: &HashMap<&str, &str>) -> Option<String> {
+    let title = titles.get(id)?;
+    Some(format!(
+        "<a href=\"{}.html\">{}</a>",
+        file_name(id),
+        esc(title)
+    ))
+}
+
+fn entry_page(
+    entry: &KnowledgeEntry,
+    relationships: &[Relationship],
+    titles: &HashMap<&str, &str>,
+    tag_files: &HashMap<&str, String>,
+) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<p class=\"meta\"><code>{}</code> in <a href=\"../categories/{}.html\">{}</a>",
+        esc(&entry.title),
+        esc(&entry.id),
+        file_name(&entry.category_id),
+        esc(&entry.category_id)
+    );
+    if let Some(updated) = entry.updated_at.as_ref().or(entry.created_at.as_ref()) {
+        body.push_str(&format!(", updated {}", esc(updated)));
+    }
+    body.push_str("</p>\n");
+    if !entry.tags.is_empty() {
+        body.push_str(&format!(
+            "<p>{}</p>\n",
+            tag_links(&entry.tags, tag_files, "../")
+        ));
+    }
+    if let Some(summary) = &entry.summary {
+        body.push_str(&format!("<p><em>{}</em></p>\n", esc(summary)));
+    }
+    if let Some(text) = &entry.body {
+        body.push_str(&format!("<div class=\"body\">{}</div>\n", esc(text)));
+    }
+
+    let mut links = Vec::new();
+    for r in relationships {
+        let (other, arrow) = if r.from_entry_id == entry.id {
+            (&r.to_entry_id, "&rarr;")
+        } else if r.to_entry_id == entry.id {
+            (&r.from_entry_id, "&larr;")
+        } else {
+            continue;
+        };
+        if let Some(link) = entry_link(other, titles) {
+            links.push(format!("{} {} {}", esc(&r.relationship_type), arrow, link));
+        }
+    }
+    for anchor in &entry.anchors {
+        let id = crate::normalize_id(anchor);
+        if let Some(link) = entry_link(&id, titles) {
+            links.push(format!("anchor &rarr; {}", link));
+        }
+    }
+    links.sort();
+    links.dedup();
+    if !links.is_empty() {
+        body.push_str("<h2>Related</h2>\n<ul>\n");
+        for link in links {
+            body.push_str(&format!("<li>{}</li>\n", link));
+        }
+        body.push_str("</ul>\n");
+    }
+    page(&entry.title, "../", &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, category: &str, title: &str, tags: &[&str]) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": category,
+            "title": title,
+            "body": "Use <b>backoff</b> & jitter",
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    fn relationship(from: &str, to: &str) -> Relationship {
+        Relationship {
+            id: "rel-1".to_string(),
+            from_entry_id: from.to_string(),
+            to_entry_id: to.to_string(),
+            relationship_type: "related".to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    fn find<'a>(pages: &'a [Page], path: &str) -> &'a str {
+        &pages.iter().find(|p| p.path == path).unwrap().contents
+    }
+
+    #[test]
+    fn builds_index_category_tag_and_entry_pages() {
+        let entries = vec![
+            entry("kn-1", "pattern", "Retry", &["rust", "Rust!"]),
+            entry("kn-2", "technique", "Jitter", &["rust"]),
+        ];
+        let pages = build(
+            &entries,
+            &[
+                relationship("kn-1", "kn-2"),
+                relationship("kn-1", "kn-gone"),
+            ],
+        )
+        .unwrap();
+
+        let index = find(&pages, "index.html");
+        assert!(index.contains("href=\"categories/pattern.html\">pattern</a> (1)"));
+        assert!(index.contains("href=\"tags/rust-1.html\">rust (2)"));
+        assert!(index.contains("href=\"tags/rust.html\">Rust! (1)"));
+        assert!(find(&pages, "search.js").contains("\"title\":\"Jitter\""));
+        assert!(find(&pages, "tags/rust-1.html").contains("../entries/kn-2.html"));
+
+        let retry = find(&pages, "entries/kn-1.html");
+        assert!(retry.contains("Use &lt;b&gt;backoff&lt;/b&gt; &amp; jitter"));
+        assert!(retry.contains("related &rarr; <a href=\"kn-2.html\">Jitter</a>"));
+        assert!(!retry.contains("kn-gone"));
+        let jitter = find(&pages, "entries/kn-2.html");
+        assert!(jitter.contains("related &larr; <a href=\"kn-1.html\">Retry</a>"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Slow-operation log
+//!
+//! Opt-in via `MX_SLOW_LOG=1`. When enabled, store operations, GitHub calls
+//! and whole commands that take longer than `MX_SLOW_MS` (default 500)
+//! append a JSON line to `$MX_HOME/logs/slow.log`: when, how long, the
+//! operation and its parameters (ids, queries, URLs; never tokens or entry
+//! bodies). Commands are logged by path only, as in `mx history`.
+//!
+//! Read back with `mx stats slow`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Threshold when `MX_SLOW_MS` is unset or invalid
+pub const DEFAULT_THRESHOLD_MS: u64 = 500;
+
+/// One logged operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlowOp {
+    pub at: String,
+    pub ms: u64,
+    pub op: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub params: String,
+}
+
+/// The threshold, if slow-op logging is enabled (`MX_SLOW_LOG` = 1/true/on/yes)
+pub fn threshold() -> Option<Duration> {
+    static THRESHOLD: OnceLock<Option<Duration>> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        let enabled = std::env::var("MX_SLOW_LOG")
+            .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "on" | "yes"))
+            .unwrap_or(false);
+        enabled.then(|| {
+            let ms = std::env::var("MX_SLOW_MS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_THRESHOLD_MS);
+            Duration::from_millis(ms)
+        })
+    })
+}
+
+/// `$MX_HOME/logs/slow.log`
+pub fn log_path() -> PathBuf {
+    crate::paths::mx_home().join("logs").join("slow.log")
+}
+
+/// Run `f`, logging it as `op` if it runs over the threshold. `params` is
+/// only built for slow calls.
+pub fn time<T>(op: &str, params: impl FnOnce() -> String, f: impl FnOnce() -> T) -> T {
+    if threshold().is_none() {
+        return f();
+    }
+    let started = Instant::now();
+    let out = f();
+    record(op, params, started.elapsed());
+    out
+}
+
+/// Log `op` if `elapsed` is over the threshold. Failures to log are reported
+/// but never change the operation's own outcome.
+pub fn record(op: &str, params: impl FnOnce() -> String, elapsed: Duration) {
+    let Some(threshold) = threshold() else {
+        return;
+    };
+    if elapsed <= threshold {
+        return;
+    }
+    let entry = SlowOp {
+        at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        ms: elapsed.as_millis() as u64,
+        op: op.to_string(),
+        params: params(),
+    };
+    if let Err(e) = append(&log_path(), &entry) {
+        eprintln!("Warning: failed to write slow-op log: {}", e);
+    }
+}
+
+fn append(path: &Path, entry: &SlowOp) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Logged operations, oldest first; unreadable lines are skipped
+pub fn read(path: &Path) -> Result<Vec<SlowOp>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Aggregated slow calls of one operation
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SlowSummary {
+    pub op: String,
+    pub count: usize,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+    /// Parameters of the slowest call
+    pub worst: String,
+    pub last_at: String,
+}
+
+/// Group logged calls by operation, slowest first
+pub fn summarize(entries: &[SlowOp]) -> Vec<SlowSummary> {
+    let mut groups: BTreeMap<&str, Vec<&SlowOp>> = BTreeMap::new();
+    for entry in entries {
+        groups.entry(entry.op.as_str()).or_default().push(entry);
+    }
+
+    let mut summaries: Vec<SlowSummary> = groups
+        .into_iter()
+        .map(|(op, calls)| {
+            let total: u64 = calls.iter().map(|e| e.ms).sum();
+            let worst = calls.iter().max_by_key(|e| e.ms).unwrap();
+            SlowSummary {
+                op: op.to_string(),
+                count: calls.len(),
+                avg_ms: total / calls.len() as u64,
+                max_ms: worst.ms,
+                worst: worst.params.clone(),
+                last_at: calls
+                    .iter()
+                    .map(|e| e.at.as_str())
+                    .max()
+                    .unwrap()
+                    .to_string(),
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| b.max_ms.cmp(&a.max_ms).then(a.op.cmp(&b.op)));
+    summaries
+}
+
+/// `mx stats slow`
+pub fn show(days: Option<i64>, limit: usize, json: bool) -> Result<()> {
+    let mut entries = read(&log_path())?;
+    if let Some(d) = days {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(d);
+        entries
+            .retain(|e| chrono::DateTime::parse_from_rfc3339(&e.at).is_ok_and(|at| at >= cutoff));
+    }
+    let mut summaries = summarize(&entries);
+    summaries.truncate(limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summaries)?);
+        return Ok(());
+    }
+
+    if summaries.is_empty() {
+        println!("No slow operations logged.");
+        match threshold() {
+            Some(t) => println!("Nothing has taken longer than {}ms.", t.as_millis()),
+            None => println!("Set MX_SLOW_LOG=1 (and MX_SLOW_MS=<ms>) to log slow operations."),
+        }
+        return Ok(());
+    }
+
+    match days {
+        Some(d) => println!(
+            "Slow operations (last {} days, {} calls):\n",
+            d,
+            entries.len()
+        ),
+        None => println!("Slow operations ({} calls):\n", entries.len()),
+    }
+    println!(
+        "  {:<28} {:>6} {:>9} {:>9}  SLOWEST",
+        "OPERATION", "CALLS", "AVG ms", "MAX ms"
+    );
+    for s in &summaries {
+        println!(
+            "  {:<28} {:>6} {:>9} {:>9}  {}",
+            s.op, s.count, s.avg_ms, s.max_ms, s.worst
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(op: &str, ms: u64, params: &str, at: &str) -> SlowOp {
+        SlowOp {
+            at: at.to_string(),
+            ms,
+            op: op.to_string(),
+            params: params.to_string(),
+        }
+    }
+
+    #[test]
+    fn appends_reads_and_ranks_slow_ops() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("slow.log");
+        for entry in [
+            op("store search", 700, "query=retry", "2026-10-01T00:00:00Z"),
+            op(
+                "github",
+                2400,
+                "GET /repos/o/r/issues",
+                "2026-10-02T00:00:00Z",
+            ),
+            op(
+                "store search",
+                1100,
+                "query=backoff",
+                "2026-10-03T00:00:00Z",
+            ),
+        ] {
+            append(&path, &entry).unwrap();
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        let summary = summarize(&entries);
+        assert_eq!(
+            summary
+                .iter()
+                .map(|s| (s.op.as_str(), s.count, s.avg_ms, s.max_ms, s.worst.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("github", 1, 2400, 2400, "GET /repos/o/r/issues"),
+                ("store search", 2, 900, 1100, "query=backoff"),
+            ]
+        );
+        assert_eq!(summary[1].last_at, "2026-10-03T00:00:00Z");
+        assert!(read(&dir.path().join("missing.log")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,491 @@
+//! Snapshot diffing: `mx memory diff`
+//!
+//! Compares two JSONL exports entry by entry (matched on id) and reports what
+//! was added, removed, or changed, with per-field before/after values, a line
+//! diff for multi-line text such as bodies, and the items added to or dropped
+//! from tag-like lists. Relationships are compared too, matched on their
+//! endpoints and type (their ids are generated, so differ between databases). The second snapshot
+//! defaults to the live database, and `--since-backup` takes the first from
+//! the newest backup in `$MX_HOME/backups/` (written by
+//! `mx memory export --backup`), so weekly backups double as change review.
+//!
+//! Bookkeeping fields that move on their own or follow from other fields
+//! (activation counts, content hashes, embeddings, token estimates) are
+//! ignored unless `--all-fields` is given.
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::line_diff;
+use crate::paths;
+use crate::store::KnowledgeStore;
+
+/// Fields that change without anyone editing the entry
+const VOLATILE_FIELDS: &[&str] = &[
+    "activation_count",
+    "last_activated",
+    "content_hash",
+    "effective_resonance",
+    "embedding",
+    "embedding_model",
+    "embedded_at",
+    "token_count",
+];
+
+/// List fields whose order carries no meaning: compared as sets, and shown
+/// as the items added and removed
+const SET_FIELDS: &[&str] = &["anchors", "applicability", "tags", "wake_phrases"];
+
+/// Unchanged lines shown around each change in a text diff
+const DIFF_CONTEXT: usize = 2;
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    entries: BTreeMap<String, Map<String, Value>>,
+    relationships: BTreeSet<RelationshipRef>,
+    /// Lines carry a `kind`; older dumps held entries only, so have no
+    /// relationships to compare
+    tagged: bool,
+}
+
+/// A relationship, identified by what it links rather than its id
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, serde::Deserialize)]
+pub struct RelationshipRef {
+    pub from_entry_id: String,
+    pub relationship_type: String,
+    pub to_entry_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryRef {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryChange {
+    pub id: String,
+    pub title: String,
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SnapshotDiff {
+    pub added: Vec<EntryRef>,
+    pub removed: Vec<EntryRef>,
+    pub changed: Vec<EntryChange>,
+    pub relationships_added: Vec<RelationshipRef>,
+    pub relationships_removed: Vec<RelationshipRef>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.relationships_added.is_empty()
+            && self.relationships_removed.is_empty()
+    }
+}
+
+/// Where `mx memory export --backup` writes today's snapshot
+pub fn backup_path() -> PathBuf {
+    paths::backups_dir().join(format!("{}.jsonl", chrono::Local::now().format("%Y-%m-%d")))
+}
+
+/// The most recent backup (names sort by date)
+fn latest_backup() -> Result<PathBuf> {
+    let dir = paths::backups_dir();
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .with_context(|| format!("No backups in {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    backups.sort();
+    match backups.pop() {
+        Some(path) => Ok(path),
+        None => bail!(
+            "No backups in {} (create one with: mx memory export --backup)",
+            dir.display()
+        ),
+    }
+}
+
+fn parse_snapshot(reader: impl BufRead, source: &str) -> Result<Snapshot> {
+    let mut snapshot = Snapshot::default();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line)
+            .with_context(|| format!("{}:{}: invalid JSON", source, lineno + 1))?;
+        let Value::Object(mut fields) = value else {
+            bail!("{}:{}: expected a JSON object", source, lineno + 1);
+        };
+        // Dumps carry lookup tables too; only entries (tagged, or untagged
+        // from older dumps) and relationships are compared
+        let kind = fields.remove("kind");
+        snapshot.tagged |= kind.is_some();
+        match kind {
+            None => {}
+            Some(Value::String(kind)) if kind == "entry" => {}
+            Some(Value::String(kind)) if kind == "relationship" => {
+                let relationship: RelationshipRef = serde_json::from_value(Value::Object(fields))
+                    .with_context(|| {
+                    format!("{}:{}: invalid relationship", source, lineno + 1)
+                })?;
+                snapshot.relationships.insert(relationship);
+                continue;
+            }
+            Some(_) => continue,
+        }
+        let Some(id) = fields.get("id").and_then(Value::as_str).map(String::from) else {
+            bail!("{}:{}: entry has no id", source, lineno + 1);
+        };
+        snapshot.entries.insert(id, fields);
+    }
+    Ok(snapshot)
+}
+
+fn load_file(path: &Path) -> Result<Snapshot> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    parse_snapshot(BufReader::new(file), &path.display().to_string())
+}
+
+/// The live database, exported the same way as a backup
+fn load_live(db: &dyn KnowledgeStore) -> Result<Snapshot> {
+    let tmp = tempfile::NamedTempFile::new()?;
+    crate::index::export_jsonl(db, tmp.path(), &Default::default())?;
+    load_file(tmp.path())
+}
+
+fn title_of(fields: &Map<String, Value>) -> String {
+    fields
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Compare two snapshots
+fn diff(before: &Snapshot, after: &Snapshot, all_fields: bool) -> SnapshotDiff {
+    let mut result = SnapshotDiff::default();
+    let entry_ref = |id: &str, fields: &Map<String, Value>| EntryRef {
+        id: id.to_string(),
+        title: title_of(fields),
+    };
+
+    for (id, old) in &before.entries {
+        let Some(new) = after.entries.get(id) else {
+            result.removed.push(entry_ref(id, old));
+            continue;
+        };
+        let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        let fields: Vec<FieldChange> = names
+            .into_iter()
+            .filter(|name| all_fields || !VOLATILE_FIELDS.contains(&name.as_str()))
+            .filter_map(|name| {
+                let a = old.get(name).cloned().unwrap_or(Value::Null);
+                let b = new.get(name).cloned().unwrap_or(Value::Null);
+                let same = if SET_FIELDS.contains(&name.as_str()) {
+                    as_set(&a) == as_set(&b)
+                } else {
+                    a == b
+                };
+                (!same).then(|| FieldChange {
+                    field: name.clone(),
+                    before: a,
+                    after: b,
+                })
+            })
+            .collect();
+        if !fields.is_empty() {
+            result.changed.push(EntryChange {
+                id: id.clone(),
+                title: title_of(new),
+                fields,
+            });
+        }
+    }
+    for (id, new) in &after.entries {
+        if !before.entries.contains_key(id) {
+            result.added.push(entry_ref(id, new));
+        }
+    }
+    if !(before.tagged && after.tagged) {
+        return result;
+    }
+    result.relationships_added = after
+        .relationships
+        .difference(&before.relationships)
+        .cloned()
+        .collect();
+    result.relationships_removed = before
+        .relationships
+        .difference(&after.relationships)
+        .cloned()
+        .collect();
+    result
+}
+
+/// The items of a list field (absent counts as empty)
+fn as_set(value: &Value) -> BTreeSet<String> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect(),
+        Value::Null => BTreeSet::new(),
+        other => BTreeSet::from([other.to_string()]),
+    }
+}
+
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        Value::String(s) => format!("{:?}", s),
+        other => other.to_string(),
+    }
+}
+
+fn field_lines(change: &FieldChange) -> Vec<String> {
+    if SET_FIELDS.contains(&change.field.as_str()) {
+        let (a, b) = (as_set(&change.before), as_set(&change.after));
+        let items: Vec<String> = b
+            .difference(&a)
+            .map(|item| format!("+{}", item))
+            .chain(a.difference(&b).map(|item| format!("-{}", item)))
+            .collect();
+        return vec![format!("{}: {}", change.field, items.join(" "))];
+    }
+    if let (Some(a), Some(b)) = (change.before.as_str(), change.after.as_str())
+        && (a.contains('\n') || b.contains('\n'))
+    {
+        let ops = line_diff::diff_lines(a, b);
+        let mut lines = vec![format!("{}:", change.field)];
+        lines.extend(
+            line_diff::render(&ops, DIFF_CONTEXT)
+                .into_iter()
+                .map(|l| format!("  {}", l)),
+        );
+        return lines;
+    }
+    vec![format!(
+        "{}: {} → {}",
+        change.field,
+        inline(&change.before),
+        inline(&change.after)
+    )]
+}
+
+fn relationship_line(rel: &RelationshipRef) -> String {
+    format!(
+        "{} -[{}]-> {}",
+        rel.from_entry_id, rel.relationship_type, rel.to_entry_id
+    )
+}
+
+fn print_diff(diff: &SnapshotDiff) {
+    for entry in &diff.added {
+        println!("{} {} {}", "+".green(), entry.id.green(), entry.title);
+    }
+    for entry in &diff.removed {
+        println!("{} {} {}", "-".red(), entry.id.red(), entry.title);
+    }
+    for entry in &diff.changed {
+        println!("{} {} {}", "~".yellow(), entry.id.yellow(), entry.title);
+        for change in &entry.fields {
+            for line in field_lines(change) {
+                let line = format!("    {}", line);
+                if line.trim_start().starts_with("+ ") {
+                    println!("{}", line.green());
+                } else if line.trim_start().starts_with("- ") {
+                    println!("{}", line.red());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+    for rel in &diff.relationships_added {
+        println!("{} {}", "+".green(), relationship_line(rel).green());
+    }
+    for rel in &diff.relationships_removed {
+        println!("{} {}", "-".red(), relationship_line(rel).red());
+    }
+    println!(
+        "\n{} added, {} removed, {} changed; {} relationships added, {} removed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.relationships_added.len(),
+        diff.relationships_removed.len()
+    );
+}
+
+#[derive(clap::Args)]
+pub struct DiffOptions {
+    /// Earlier snapshot (JSONL export)
+    #[arg(required_unless_present = "since_backup")]
+    pub before: Option<PathBuf>,
+
+    /// Later snapshot (defaults to the live database)
+    pub after: Option<PathBuf>,
+
+    /// Compare the newest backup in $MX_HOME/backups/ against the database
+    #[arg(long, conflicts_with = "before")]
+    pub since_backup: bool,
+
+    /// Include bookkeeping fields (activation counts, hashes, embeddings, token estimates)
+    #[arg(long)]
+    pub all_fields: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(db: &dyn KnowledgeStore, opts: DiffOptions) -> Result<()> {
+    let before_path = match (opts.since_backup, opts.before) {
+        (true, _) => latest_backup()?,
+        (false, Some(path)) => path,
+        (false, None) => bail!("Give a snapshot to compare, or --since-backup"),
+    };
+    let before = load_file(&before_path)?;
+    let (after, after_label) = match opts.after {
+        Some(path) => (load_file(&path)?, path.display().to_string()),
+        None => (load_live(db)?, "database".to_string()),
+    };
+
+    let result = diff(&before, &after, opts.all_fields);
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+    println!(
+        "{} {} → {}\n",
+        "Comparing".bold(),
+        before_path.display(),
+        after_label
+    );
+    if result.is_empty() {
+        println!("No differences");
+        return Ok(());
+    }
+    print_diff(&result);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(jsonl: &str) -> Snapshot {
+        parse_snapshot(jsonl.as_bytes(), "test").unwrap()
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_fields() {
+        let before = snapshot(concat!(
+            r#"{"id":"kn-1","title":"Kept","body":"a\nb","activation_count":1}"#,
+            "\n",
+            r#"{"id":"kn-2","title":"Gone"}"#,
+        ));
+        let after = snapshot(concat!(
+            r#"{"id":"kn-1","title":"Kept","body":"a\nc","activation_count":9,"resonance":4}"#,
+            "\n",
+            r#"{"id":"kn-3","title":"New"}"#,
+        ));
+
+        let result = diff(&before, &after, false);
+        assert_eq!(result.added[0].id, "kn-3");
+        assert_eq!(result.removed[0].title, "Gone");
+        let fields: Vec<&str> = result.changed[0]
+            .fields
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["body", "resonance"]);
+        assert_eq!(
+            field_lines(&result.changed[0].fields[0]),
+            vec!["body:", "    a", "  - b", "  + c"]
+        );
+        assert_eq!(
+            field_lines(&result.changed[0].fields[1]),
+            vec!["resonance: (none) → 4"]
+        );
+
+        let all = diff(&before, &after, true);
+        assert_eq!(all.changed[0].fields.len(), 3);
+    }
+
+    #[test]
+    fn compares_entries_and_relationships_of_a_full_dump() {
+        let before = snapshot(concat!(
+            r#"{"kind":"category","id":"pattern","description":"","created_at":""}"#,
+            "\n",
+            r#"{"kind":"entry","id":"kn-1","title":"Kept"}"#,
+            "\n",
+            r#"{"kind":"relationship","id":"r1","from_entry_id":"kn-1","to_entry_id":"kn-2","relationship_type":"related","created_at":""}"#,
+            "\n",
+            r#"{"kind":"relationship","id":"r2","from_entry_id":"kn-1","to_entry_id":"kn-3","relationship_type":"related","created_at":""}"#,
+        ));
+        let after = snapshot(concat!(
+            r#"{"kind":"entry","id":"kn-1","title":"Kept"}"#,
+            "\n",
+            r#"{"kind":"relationship","id":"r9","from_entry_id":"kn-1","to_entry_id":"kn-2","relationship_type":"related","created_at":""}"#,
+            "\n",
+            r#"{"kind":"relationship","id":"r3","from_entry_id":"kn-1","to_entry_id":"kn-3","relationship_type":"supersedes","created_at":""}"#,
+        ));
+        assert_eq!(before.entries.len(), 1);
+        assert_eq!(before.relationships.len(), 2);
+
+        let result = diff(&before, &after, false);
+        assert!(result.added.is_empty() && result.removed.is_empty() && result.changed.is_empty());
+        assert_eq!(
+            relationship_line(&result.relationships_added[0]),
+            "kn-1 -[supersedes]-> kn-3"
+        );
+        assert_eq!(
+            relationship_line(&result.relationships_removed[0]),
+            "kn-1 -[related]-> kn-3"
+        );
+
+        let old = snapshot(r#"{"id":"kn-1","title":"Kept"}"#);
+        assert!(diff(&old, &after, false).is_empty());
+    }
+
+    #[test]
+    fn tags_compare_as_sets() {
+        let before = snapshot(r#"{"id":"kn-1","title":"T","tags":["rust","db"]}"#);
+        let reordered = snapshot(r#"{"id":"kn-1","title":"T","tags":["db","rust"]}"#);
+        let retagged = snapshot(r#"{"id":"kn-1","title":"T","tags":["db","surreal"]}"#);
+
+        assert!(diff(&before, &reordered, false).is_empty());
+        let result = diff(&before, &retagged, false);
+        assert_eq!(
+            field_lines(&result.changed[0].fields[0]),
+            vec!["tags: +surreal -rust"]
+        );
+    }
+}
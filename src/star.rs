@@ -0,0 +1,85 @@
+//! Starred entries: `mx memory star|unstar|starred`
+//!
+//! Each agent (or `human` outside an agent session) keeps its own pinned
+//! set. Starred entries lead the wake cascade, ahead of sealed identity
+//! blooms and outside the `--limit`, so they never fall out of working
+//! memory; `--starred` on list and search narrows to them.
+
+use anyhow::{Result, bail};
+
+use crate::error::MxError;
+use crate::store::{AgentContext, KnowledgeStore};
+
+#[derive(clap::Args)]
+pub struct StarArgs {
+    /// Entry IDs
+    #[arg(required = true)]
+    pub ids: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct StarredArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Whose pinned set: the current agent, or `human` outside an agent session
+pub fn agent() -> String {
+    std::env::var("MX_CURRENT_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| "human".to_string())
+}
+
+/// `mx memory star`: pin entries the current agent can see
+pub fn star(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &StarArgs) -> Result<()> {
+    let agent = agent();
+    for id in &args.ids {
+        let id = crate::normalize_id(id);
+        let Some(entry) = db.get(&id, ctx)? else {
+            bail!(MxError::not_found("Entry", id));
+        };
+        if db.star(&agent, &id)? {
+            println!("Starred {}  {}", id, entry.title);
+        } else {
+            println!("{} is already starred", id);
+        }
+    }
+    Ok(())
+}
+
+/// `mx memory unstar`
+pub fn unstar(db: &dyn KnowledgeStore, args: &StarArgs) -> Result<()> {
+    let agent = agent();
+    for id in &args.ids {
+        let id = crate::normalize_id(id);
+        if db.unstar(&agent, &id)? {
+            println!("Unstarred {}", id);
+        } else {
+            println!("{} was not starred", id);
+        }
+    }
+    Ok(())
+}
+
+/// `mx memory starred`: the pinned set in the order it was starred
+pub fn list(db: &dyn KnowledgeStore, ctx: &AgentContext, args: &StarredArgs) -> Result<()> {
+    let mut entries = Vec::new();
+    for id in db.list_starred(&agent())? {
+        if let Some(entry) = db.get(&id, ctx)? {
+            entries.push(entry);
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else if entries.is_empty() {
+        println!("No starred entries (pin one with `mx memory star <id>`)");
+    } else {
+        for entry in &entries {
+            println!("  {} [{}]  {}", entry.id, entry.category_id, entry.title);
+        }
+    }
+    Ok(())
+}
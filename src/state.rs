@@ -117,142 +117,72 @@ impl DynamicState {
     }
 
     /// Decode from stele format into DynamicState
+    ///
+    /// Mirrors [`Self::encode_stele`]: each part is matched against the
+    /// symbols of one schema level, and every nested separator descends a
+    /// level. Parts with unknown symbols are skipped (the dimension may have
+    /// left the schema); a known symbol whose value doesn't parse is an error.
     pub fn decode_stele(stele: &str, schema: &StateSchema) -> Result<Self> {
         let s = &schema.stele;
-        let sep = &s.separator;
-        let nsep = &s.nested_separator;
+        let mut values: HashMap<String, StateValue> = HashMap::new();
 
-        // Build symbol maps
-        let mut top_level_sym_to_name: HashMap<&str, &str> = HashMap::new();
-        let mut nested_parent_syms: std::collections::HashSet<&str> =
-            std::collections::HashSet::new();
-
-        for (name, dim) in &schema.dimensions {
-            if let Some(sym) = s.symbols.get(name.as_str()) {
-                top_level_sym_to_name.insert(sym.as_str(), name.as_str());
-                // Track which symbols have nested dimensions
-                if matches!(dim, Dimension::Nested { .. }) {
-                    nested_parent_syms.insert(sym.as_str());
-                }
+        // Skip header, process rest
+        for part in stele.split(s.separator.as_str()).skip(1) {
+            if !part.is_empty() {
+                Self::decode_part(part, &schema.dimensions, s, &mut values)?;
             }
         }
 
-        // Build reverse modality map
-        let mut rev_modality: HashMap<&str, &str> = HashMap::new();
-        for (name, sym) in &s.modality_values {
-            rev_modality.insert(sym.as_str(), name.as_str());
-        }
+        Ok(DynamicState {
+            schema_id: schema.title.clone(),
+            values,
+        })
+    }
 
-        let parts: Vec<&str> = stele.split(sep).collect();
-        let mut values: HashMap<String, StateValue> = HashMap::new();
+    fn decode_part(
+        part: &str,
+        dimensions: &HashMap<String, Dimension>,
+        s: &SteleConfig,
+        values: &mut HashMap<String, StateValue>,
+    ) -> Result<()> {
+        let symbols = dimensions
+            .keys()
+            .map(|name| (name.as_str(), s.symbol(name)));
+        let Some((name, value_str)) = match_symbol(part, symbols) else {
+            return Ok(());
+        };
 
-        // Skip header, process rest
-        for part in parts.iter().skip(1) {
-            if part.is_empty() {
-                continue;
+        match &dimensions[name] {
+            Dimension::Float { .. } => {
+                let v = value_str
+                    .parse::<f32>()
+                    .with_context(|| format!("Invalid value for {}: {:?}", name, value_str))?;
+                values.insert(name.to_string(), StateValue::Float(v));
             }
-
-            // Check if this starts with a nested parent symbol followed by the nested separator
-            let mut is_nested = false;
-            let mut parent_sym_len = 0;
-
-            for parent_sym in &nested_parent_syms {
-                let pattern = format!("{}{}", parent_sym, nsep);
-                if part.starts_with(&pattern) {
-                    is_nested = true;
-                    parent_sym_len = parent_sym.len();
-                    break;
-                }
+            Dimension::Enum { .. } => {
+                let enum_val = s.modality_name(value_str).to_string();
+                values.insert(name.to_string(), StateValue::Enum(enum_val));
             }
-
-            if is_nested {
-                // Nested dimension: {parent_sym}{nsep}{child_sym}{value}
-                let parent_part = &part[..parent_sym_len];
-                let child_part = &part[parent_sym_len + nsep.len()..];
-
-                if let Some(&parent_name) = top_level_sym_to_name.get(parent_part)
-                    && let Some(Dimension::Nested { dimensions, .. }) =
-                        schema.dimensions.get(parent_name)
-                {
-                    // Build child symbol map
-                    let mut child_sym_to_name: HashMap<&str, &str> = HashMap::new();
-                    for child_name in dimensions.keys() {
-                        if let Some(child_sym) = s.symbols.get(child_name.as_str()) {
-                            child_sym_to_name.insert(child_sym.as_str(), child_name.as_str());
-                        }
-                    }
-
-                    // Find which child this is
-                    for (child_sym, &child_name) in &child_sym_to_name {
-                        if let Some(value_str) = child_part.strip_prefix(child_sym) {
-                            // Get or create nested HashMap
-                            let nested = values
-                                .entry(parent_name.to_string())
-                                .or_insert_with(|| StateValue::Nested(HashMap::new()));
-
-                            if let StateValue::Nested(nested_map) = nested
-                                && let Some(child_dim) = dimensions.get(child_name)
-                            {
-                                match child_dim {
-                                    Dimension::Float { .. } => {
-                                        if let Ok(v) = value_str.parse::<f32>() {
-                                            nested_map.insert(
-                                                child_name.to_string(),
-                                                StateValue::Float(v),
-                                            );
-                                        }
-                                    }
-                                    Dimension::Enum { .. } => {
-                                        let enum_val = rev_modality
-                                            .get(value_str)
-                                            .map(|s| s.to_string())
-                                            .unwrap_or_else(|| value_str.to_string());
-                                        nested_map.insert(
-                                            child_name.to_string(),
-                                            StateValue::Enum(enum_val),
-                                        );
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            break;
-                        }
-                    }
-                }
-            } else {
-                // Simple dimension
-                for (sym, &name) in &top_level_sym_to_name {
-                    if let Some(value_str) = part.strip_prefix(sym) {
-                        if let Some(dim) = schema.dimensions.get(name) {
-                            match dim {
-                                Dimension::Float { .. } => {
-                                    if let Ok(v) = value_str.parse::<f32>() {
-                                        values.insert(name.to_string(), StateValue::Float(v));
-                                    }
-                                }
-                                Dimension::Enum { .. } => {
-                                    let enum_val = rev_modality
-                                        .get(value_str)
-                                        .map(|s| s.to_string())
-                                        .unwrap_or_else(|| value_str.to_string());
-                                    values.insert(name.to_string(), StateValue::Enum(enum_val));
-                                }
-                                Dimension::Nested { .. } => {
-                                    // Skip nested dimensions in simple branch
-                                }
-                            }
-                        }
-                        break;
+            Dimension::Nested {
+                dimensions: children,
+                ..
+            } => {
+                // Nested dimension: {parent_sym}{nsep}{child part}
+                if let Some(child_part) = value_str.strip_prefix(s.nested_separator.as_str()) {
+                    let mut nested = match values.remove(name) {
+                        Some(StateValue::Nested(nested)) => nested,
+                        _ => HashMap::new(),
+                    };
+                    Self::decode_part(child_part, children, s, &mut nested)?;
+                    if !nested.is_empty() {
+                        values.insert(name.to_string(), StateValue::Nested(nested));
                     }
                 }
             }
         }
-
-        Ok(DynamicState {
-            schema_id: schema.title.clone(),
-            values,
-        })
+        Ok(())
     }
+
     /// Create DynamicState from a discrete mode name using schema mappings
     pub fn from_mode(mode: &str, schema: &StateSchema) -> Result<Self> {
         let mapping = schema
@@ -442,6 +372,31 @@ impl DynamicState {
     }
 }
 
+/// Symbols the fixed emotional-state layout uses where a schema defines none
+const LEGACY_SYMBOLS: [(&str, &str); 10] = [
+    ("temperature", "T"),
+    ("entropy", "E"),
+    ("gravity", "G"),
+    ("depth", "D"),
+    ("energy", "N"),
+    ("toward", ">"),
+    ("agency", "A"),
+    ("flow", "F"),
+    ("distance", "I"),
+    ("modality", "M"),
+];
+
+fn legacy_symbol<'a>(s: &'a SteleConfig, name: &'a str) -> &'a str {
+    match s.symbols.get(name) {
+        Some(sym) => sym,
+        None => LEGACY_SYMBOLS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, sym)| *sym)
+            .unwrap_or(name),
+    }
+}
+
 /// Stele encoding configuration from schema
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SteleConfig {
@@ -452,6 +407,36 @@ pub struct SteleConfig {
     pub modality_values: HashMap<String, String>,
 }
 
+impl SteleConfig {
+    /// Symbol a dimension is written with: its own, or its name
+    pub fn symbol<'a>(&'a self, name: &'a str) -> &'a str {
+        self.symbols.get(name).map(|s| s.as_str()).unwrap_or(name)
+    }
+
+    /// Enum value behind an encoded one: the value a modality symbol
+    /// stands for, or the text itself
+    pub fn modality_name<'a>(&'a self, encoded: &'a str) -> &'a str {
+        self.modality_values
+            .iter()
+            .find(|(_, sym)| sym.as_str() == encoded)
+            .map(|(name, _)| name.as_str())
+            .unwrap_or(encoded)
+    }
+}
+
+/// The dimension whose symbol starts `part`, with the rest of the part.
+/// Longest symbol first, so `TE0.4` is never read as `T` with value `E0.4`.
+fn match_symbol<'p, 'n>(
+    part: &'p str,
+    symbols: impl Iterator<Item = (&'n str, &'n str)>,
+) -> Option<(&'n str, &'p str)> {
+    symbols
+        .filter(|(_, sym)| !sym.is_empty())
+        .filter_map(|(name, sym)| part.strip_prefix(sym).map(|rest| (name, sym.len(), rest)))
+        .max_by_key(|(_, len, _)| *len)
+        .map(|(name, _, rest)| (name, rest))
+}
+
 /// Dimension hint - maps word to float value
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DimensionHints {
@@ -501,6 +486,99 @@ pub struct StateSchema {
     pub mode_mappings: HashMap<String, ModeMapping>,
 }
 
+impl StateSchema {
+    /// Check that every state the schema describes encodes to a stele that
+    /// decodes back to it: separators that can't be confused, symbols that
+    /// are non-empty and prefix-free within each level, and enum values that
+    /// survive the modality symbol mapping.
+    pub fn validate(&self) -> Result<()> {
+        let s = &self.stele;
+        let sep = s.separator.as_str();
+        if sep.is_empty() || s.nested_separator.is_empty() {
+            bail!("Stele separators must not be empty");
+        }
+        if s.nested_separator.contains(sep) {
+            bail!(
+                "Nested separator {:?} contains the separator {:?}",
+                s.nested_separator,
+                sep
+            );
+        }
+        for text in [&s.header, &self.name] {
+            if text.contains(sep) {
+                bail!("{:?} contains the separator {:?}", text, sep);
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for (value, sym) in &s.modality_values {
+            if sym.is_empty() || sym.contains(sep) {
+                bail!(
+                    "Modality symbol {:?} for {} is empty or contains the separator",
+                    sym,
+                    value
+                );
+            }
+            if !seen.insert(sym) {
+                bail!("Modality symbol {:?} is used more than once", sym);
+            }
+        }
+
+        validate_level(&self.dimensions, s)
+    }
+}
+
+fn validate_level(dimensions: &HashMap<String, Dimension>, s: &SteleConfig) -> Result<()> {
+    let symbols: Vec<(&str, &str)> = dimensions
+        .keys()
+        .map(|name| (name.as_str(), s.symbol(name)))
+        .collect();
+    for &(name, sym) in &symbols {
+        if sym.is_empty() {
+            bail!("Dimension {} has an empty symbol", name);
+        }
+        if sym.contains(s.separator.as_str()) || sym.contains(s.nested_separator.as_str()) {
+            bail!("Symbol {:?} of {} contains a separator", sym, name);
+        }
+        if let Some((other, _)) = symbols
+            .iter()
+            .find(|(other, other_sym)| *other != name && other_sym.starts_with(sym))
+        {
+            bail!(
+                "Symbol {:?} of {} is a prefix of the symbol of {}",
+                sym,
+                name,
+                other
+            );
+        }
+    }
+
+    for (name, dim) in dimensions {
+        match dim {
+            Dimension::Float { .. } => {}
+            Dimension::Enum { values, .. } => {
+                for value in values {
+                    let encoded = s.modality_values.get(value).unwrap_or(value);
+                    if encoded.contains(s.separator.as_str()) {
+                        bail!("Value {:?} of {} contains the separator", value, name);
+                    }
+                    let decoded = s.modality_name(encoded);
+                    if decoded != value {
+                        bail!(
+                            "Value {:?} of {} would decode as {:?}",
+                            value,
+                            name,
+                            decoded
+                        );
+                    }
+                }
+            }
+            Dimension::Nested { dimensions, .. } => validate_level(dimensions, s)?,
+        }
+    }
+    Ok(())
+}
+
 /// An actual emotional state instance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmotionalState {
@@ -619,20 +697,16 @@ impl EmotionalState {
         let s = &schema.stele;
 
         // Get symbols
-        let sym_temp = s
-            .symbols
-            .get("temperature")
-            .map(|s| s.as_str())
-            .unwrap_or("T");
-        let sym_ent = s.symbols.get("entropy").map(|s| s.as_str()).unwrap_or("E");
-        let sym_grav = s.symbols.get("gravity").map(|s| s.as_str()).unwrap_or("G");
-        let sym_depth = s.symbols.get("depth").map(|s| s.as_str()).unwrap_or("D");
-        let sym_energy = s.symbols.get("energy").map(|s| s.as_str()).unwrap_or("N");
-        let sym_toward = s.symbols.get("toward").map(|s| s.as_str()).unwrap_or(">");
-        let sym_agency = s.symbols.get("agency").map(|s| s.as_str()).unwrap_or("A");
-        let sym_flow = s.symbols.get("flow").map(|s| s.as_str()).unwrap_or("F");
-        let sym_dist = s.symbols.get("distance").map(|s| s.as_str()).unwrap_or("I");
-        let sym_mod = s.symbols.get("modality").map(|s| s.as_str()).unwrap_or("M");
+        let sym_temp = legacy_symbol(s, "temperature");
+        let sym_ent = legacy_symbol(s, "entropy");
+        let sym_grav = legacy_symbol(s, "gravity");
+        let sym_depth = legacy_symbol(s, "depth");
+        let sym_energy = legacy_symbol(s, "energy");
+        let sym_toward = legacy_symbol(s, "toward");
+        let sym_agency = legacy_symbol(s, "agency");
+        let sym_flow = legacy_symbol(s, "flow");
+        let sym_dist = legacy_symbol(s, "distance");
+        let sym_mod = legacy_symbol(s, "modality");
 
         // Get modality symbol
         let mod_sym = s
@@ -688,30 +762,17 @@ impl EmotionalState {
     }
 
     /// Decode from stele format
+    ///
+    /// Dimensions missing from the stele keep their neutral defaults; a value
+    /// that doesn't parse is an error.
     pub fn decode_stele(stele: &str, schema: &StateSchema) -> Result<Self> {
         let s = &schema.stele;
-        let sep = &s.separator;
-        let nsep = &s.nested_separator;
-
-        // Build symbol -> name map
-        let mut sym_to_name: HashMap<&str, &str> = HashMap::new();
-        for (name, sym) in &s.symbols {
-            sym_to_name.insert(sym.as_str(), name.as_str());
-        }
-
-        // Build reverse modality map
-        let mut rev_modality: HashMap<&str, &str> = HashMap::new();
-        for (name, sym) in &s.modality_values {
-            rev_modality.insert(sym.as_str(), name.as_str());
-        }
-
-        // Get symbols we need
-        let sym_toward = s.symbols.get("toward").map(|s| s.as_str()).unwrap_or(">");
+        let top_level = ["temperature", "entropy", "gravity", "depth", "energy"]
+            .map(|name| (name, legacy_symbol(s, name)));
+        let toward_level =
+            ["agency", "flow", "distance", "modality"].map(|name| (name, legacy_symbol(s, name)));
+        let toward_prefix = format!("{}{}", legacy_symbol(s, "toward"), s.nested_separator);
 
-        // Parse the stele string
-        let parts: Vec<&str> = stele.split(sep).collect();
-
-        // Skip header, parse rest
         let mut temperature = 0.5f32;
         let mut entropy = 0.5f32;
         let mut gravity = 0.5f32;
@@ -722,61 +783,32 @@ impl EmotionalState {
         let mut distance = 0.5f32;
         let mut modality = String::from("blended");
 
-        for part in parts.iter().skip(1) {
-            // Check if this is a nested dimension (format: ᚥ.ᚦ0.3)
-            // The nested separator comes RIGHT AFTER the toward symbol
-            if part.starts_with(sym_toward) && part[sym_toward.len()..].starts_with(nsep) {
-                // Format: {toward_sym}{nsep}{sub_sym}{value}
-                // e.g., ᚥ.ᚦ0.3 -> toward.agency = 0.3
-                let after_prefix = &part[sym_toward.len() + nsep.len()..];
-
-                // Find which nested dimension this is
-                for (sym, name) in &sym_to_name {
-                    if let Some(value_str) = after_prefix.strip_prefix(sym) {
-                        match *name {
-                            "agency" => {
-                                if let Ok(v) = value_str.parse() {
-                                    agency = v;
-                                }
-                            }
-                            "flow" => {
-                                if let Ok(v) = value_str.parse() {
-                                    flow = v;
-                                }
-                            }
-                            "distance" => {
-                                if let Ok(v) = value_str.parse() {
-                                    distance = v;
-                                }
-                            }
-                            "modality" => {
-                                // Check if it's a symbol or direct name
-                                modality = rev_modality
-                                    .get(value_str)
-                                    .map(|s| s.to_string())
-                                    .unwrap_or_else(|| value_str.to_string());
-                            }
-                            _ => {}
-                        }
-                        break;
-                    }
+        let float = |name: &str, value_str: &str| {
+            value_str
+                .parse::<f32>()
+                .with_context(|| format!("Invalid value for {}: {:?}", name, value_str))
+        };
+
+        // Skip header, parse rest
+        for part in stele.split(s.separator.as_str()).skip(1) {
+            // Nested dimension (format: ᚥ.ᚦ0.3 -> toward.agency = 0.3)
+            if let Some(after_prefix) = part.strip_prefix(toward_prefix.as_str()) {
+                match match_symbol(after_prefix, toward_level.into_iter()) {
+                    Some(("agency", v)) => agency = float("agency", v)?,
+                    Some(("flow", v)) => flow = float("flow", v)?,
+                    Some(("distance", v)) => distance = float("distance", v)?,
+                    Some((_, v)) => modality = s.modality_name(v).to_string(),
+                    None => {}
                 }
             } else {
                 // Simple dimension (format: ᚠ0.6)
-                for (sym, name) in &sym_to_name {
-                    if let Some(value_str) = part.strip_prefix(sym) {
-                        if let Ok(v) = value_str.parse() {
-                            match *name {
-                                "temperature" => temperature = v,
-                                "entropy" => entropy = v,
-                                "gravity" => gravity = v,
-                                "depth" => depth = v,
-                                "energy" => energy = v,
-                                _ => {}
-                            }
-                        }
-                        break;
-                    }
+                match match_symbol(part, top_level.into_iter()) {
+                    Some(("temperature", v)) => temperature = float("temperature", v)?,
+                    Some(("entropy", v)) => entropy = float("entropy", v)?,
+                    Some(("gravity", v)) => gravity = float("gravity", v)?,
+                    Some(("depth", v)) => depth = float("depth", v)?,
+                    Some((_, v)) => energy = float("energy", v)?,
+                    None => {}
                 }
             }
         }
@@ -918,7 +950,12 @@ pub fn load_schema(path: &Path) -> Result<StateSchema> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read schema file: {:?}", path))?;
 
-    serde_json::from_str(&content).with_context(|| format!("Failed to parse schema: {:?}", path))
+    let schema: StateSchema = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse schema: {:?}", path))?;
+    schema
+        .validate()
+        .with_context(|| format!("Invalid schema: {:?}", path))?;
+    Ok(schema)
 }
 
 /// Load the default emotional state schema
@@ -1441,10 +1478,9 @@ mod dynamic_state_tests {
 
     #[test]
     fn test_soren_encode_decode_roundtrip() {
-        let schema_json = std::fs::read_to_string("schemas/example-soren-state.json")
-            .expect("Failed to read Soren schema");
+        let schema_json = include_str!("../schemas/example-soren-state.json");
         let schema: StateSchema =
-            serde_json::from_str(&schema_json).expect("Failed to parse Soren schema");
+            serde_json::from_str(schema_json).expect("Failed to parse Soren schema");
 
         // Create state from mode
         let original =
@@ -1509,10 +1545,9 @@ mod dynamic_state_tests {
 
     #[test]
     fn test_soren_from_mode() {
-        let schema_json = std::fs::read_to_string("schemas/example-soren-state.json")
-            .expect("Failed to read Soren schema");
+        let schema_json = include_str!("../schemas/example-soren-state.json");
         let schema: StateSchema =
-            serde_json::from_str(&schema_json).expect("Failed to parse Soren schema");
+            serde_json::from_str(schema_json).expect("Failed to parse Soren schema");
 
         let state =
             DynamicState::from_mode("tending", &schema).expect("Failed to create state from mode");
@@ -1534,10 +1569,9 @@ mod dynamic_state_tests {
 
     #[test]
     fn test_soren_describe() {
-        let schema_json = std::fs::read_to_string("schemas/example-soren-state.json")
-            .expect("Failed to read Soren schema");
+        let schema_json = include_str!("../schemas/example-soren-state.json");
         let schema: StateSchema =
-            serde_json::from_str(&schema_json).expect("Failed to parse Soren schema");
+            serde_json::from_str(schema_json).expect("Failed to parse Soren schema");
 
         let state =
             DynamicState::from_mode("tending", &schema).expect("Failed to create state from mode");
@@ -1552,3 +1586,269 @@ mod dynamic_state_tests {
         assert!(description.contains("carrying"));
     }
 }
+
+#[cfg(test)]
+mod stele_prop_tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn float_dim() -> Dimension {
+        Dimension::Float {
+            range: [0.0, 1.0],
+            description: String::new(),
+            prompt: String::new(),
+            hints: HashMap::new(),
+        }
+    }
+
+    /// Dimension trees up to three levels deep. Names are three letters, so
+    /// siblings that fall back to their name as symbol stay prefix-free.
+    fn dimensions() -> impl Strategy<Value = HashMap<String, Dimension>> {
+        let leaf = prop_oneof![
+            Just(float_dim()),
+            prop::collection::btree_set("[a-z]{1,6}", 1..4).prop_map(|values| Dimension::Enum {
+                values: values.into_iter().collect(),
+                description: String::new(),
+                prompt: String::new(),
+            }),
+        ];
+        let dimension = leaf.prop_recursive(2, 16, 4, |inner| {
+            prop::collection::hash_map("[a-z]{3}", inner, 1..4).prop_map(|dimensions| {
+                Dimension::Nested {
+                    description: String::new(),
+                    dimensions,
+                }
+            })
+        });
+        prop::collection::hash_map("[a-z]{3}", dimension, 1..6)
+    }
+
+    fn collect(
+        dimensions: &HashMap<String, Dimension>,
+        names: &mut BTreeSet<String>,
+        values: &mut BTreeSet<String>,
+    ) {
+        for (name, dim) in dimensions {
+            names.insert(name.clone());
+            match dim {
+                Dimension::Float { .. } => {}
+                Dimension::Enum { values: v, .. } => values.extend(v.iter().cloned()),
+                Dimension::Nested { dimensions, .. } => collect(dimensions, names, values),
+            }
+        }
+    }
+
+    /// Random schemas: separators, runic symbols for some dimensions (the
+    /// rest use their names) and modality symbols for some enum values
+    fn schema() -> impl Strategy<Value = StateSchema> {
+        (
+            dimensions(),
+            prop::sample::select(vec!["|", ";", "¦", "//"]),
+            prop::sample::select(vec![".", ":", "~>"]),
+        )
+            .prop_flat_map(|(dimensions, separator, nested_separator)| {
+                let mut names = BTreeSet::new();
+                let mut values = BTreeSet::new();
+                collect(&dimensions, &mut names, &mut values);
+                let symbols: Vec<_> = names
+                    .into_iter()
+                    .map(|n| {
+                        prop::option::of("[ᚠ-ᛪ]{2}").prop_map(move |s| s.map(|s| (n.clone(), s)))
+                    })
+                    .collect();
+                let modality: Vec<_> = values
+                    .into_iter()
+                    .map(|v| {
+                        prop::option::of("[ᛮ-ᛸ]{3}").prop_map(move |s| s.map(|s| (v.clone(), s)))
+                    })
+                    .collect();
+                (Just(dimensions), symbols, modality).prop_map(
+                    move |(dimensions, symbols, modality)| StateSchema {
+                        title: "Prop".to_string(),
+                        description: String::new(),
+                        version: "1.0.0".to_string(),
+                        schema_type: "tensor".to_string(),
+                        name: "prop".to_string(),
+                        stele: SteleConfig {
+                            header: "@state".to_string(),
+                            separator: separator.to_string(),
+                            nested_separator: nested_separator.to_string(),
+                            symbols: symbols.into_iter().flatten().collect(),
+                            modality_values: modality.into_iter().flatten().collect(),
+                        },
+                        dimensions,
+                        mode_mappings: HashMap::new(),
+                    },
+                )
+            })
+    }
+
+    /// Values for some or all of the schema's dimensions
+    fn values_for(
+        dimensions: &HashMap<String, Dimension>,
+    ) -> BoxedStrategy<HashMap<String, StateValue>> {
+        let entries: Vec<_> = dimensions
+            .iter()
+            .map(|(name, dim)| {
+                let name = name.clone();
+                let value = match dim {
+                    Dimension::Float { .. } => {
+                        (-1000.0f32..1000.0).prop_map(StateValue::Float).boxed()
+                    }
+                    Dimension::Enum { values, .. } => prop::sample::select(values.clone())
+                        .prop_map(StateValue::Enum)
+                        .boxed(),
+                    Dimension::Nested { dimensions, .. } => {
+                        values_for(dimensions).prop_map(StateValue::Nested).boxed()
+                    }
+                };
+                prop::option::of(value.prop_map(move |v| (name.clone(), v)))
+            })
+            .collect();
+        entries
+            .prop_map(|entries| entries.into_iter().flatten().collect())
+            .boxed()
+    }
+
+    /// Empty groups encode to nothing, so they can't come back
+    fn prune(values: HashMap<String, StateValue>) -> HashMap<String, StateValue> {
+        values
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                StateValue::Nested(nested) => {
+                    let nested = prune(nested);
+                    (!nested.is_empty()).then_some((name, StateValue::Nested(nested)))
+                }
+                value => Some((name, value)),
+            })
+            .collect()
+    }
+
+    /// Text assembled from a schema's own symbols, separators and values,
+    /// plus arbitrary strings
+    fn stele_text(schema: &StateSchema) -> BoxedStrategy<String> {
+        let s = &schema.stele;
+        let mut names = BTreeSet::new();
+        let mut values = BTreeSet::new();
+        collect(&schema.dimensions, &mut names, &mut values);
+        let mut tokens: Vec<String> = names.iter().map(|n| s.symbol(n).to_string()).collect();
+        tokens.extend(names);
+        tokens.extend(values);
+        tokens.extend(s.modality_values.values().cloned());
+        tokens.extend(
+            [&s.separator, &s.separator, &s.nested_separator, &s.header].map(|t| t.to_string()),
+        );
+        tokens.extend(["0.5", "-1e3", "NaN", "", "x", "ᚠ"].map(String::from));
+        prop_oneof![
+            3 => prop::collection::vec(prop::sample::select(tokens), 0..16)
+                .prop_map(|tokens| tokens.concat()),
+            1 => any::<String>(),
+        ]
+        .boxed()
+    }
+
+    proptest! {
+        #[test]
+        fn dynamic_state_roundtrips(
+            (schema, values) in schema().prop_flat_map(|schema| {
+                let values = values_for(&schema.dimensions);
+                (Just(schema), values)
+            })
+        ) {
+            prop_assume!(schema.validate().is_ok());
+            let state = DynamicState { schema_id: schema.title.clone(), values };
+            let stele = state.encode_stele(&schema);
+            let decoded = DynamicState::decode_stele(&stele, &schema).unwrap();
+            prop_assert_eq!(decoded.values, prune(state.values), "stele: {}", stele);
+        }
+
+        #[test]
+        fn decoding_any_text_is_stable(
+            (schema, text) in schema().prop_flat_map(|schema| {
+                let text = stele_text(&schema);
+                (Just(schema), text)
+            })
+        ) {
+            prop_assume!(schema.validate().is_ok());
+            // Whatever decodes must re-encode to a stele that decodes the same
+            if let Ok(state) = DynamicState::decode_stele(&text, &schema) {
+                let stele = state.encode_stele(&schema);
+                let again = DynamicState::decode_stele(&stele, &schema).unwrap();
+                prop_assert_eq!(again.encode_stele(&schema), stele);
+            }
+            let _ = EmotionalState::decode_stele(&text, &schema);
+        }
+
+        #[test]
+        fn emotional_state_roundtrips(
+            dims in prop::array::uniform8(-10.0f32..10.0),
+            modality in "[a-z]{1,8}",
+            defined in prop::collection::btree_set(0..LEGACY_SYMBOLS.len(), 0..=LEGACY_SYMBOLS.len()),
+        ) {
+            // Runic symbols for some dimensions; the rest use the defaults
+            let runes = ["ᚠ", "ᚢ", "ᚦ", "ᚨ", "ᚱ", "ᚥ", "ᚷ", "ᚹ", "ᚺ", "ᛗ"];
+            let symbols = defined
+                .iter()
+                .map(|&i| (LEGACY_SYMBOLS[i].0.to_string(), runes[i].to_string()))
+                .collect();
+            let schema = StateSchema {
+                title: "Legacy".to_string(),
+                description: String::new(),
+                version: "1.0.0".to_string(),
+                schema_type: "tensor".to_string(),
+                name: "legacy".to_string(),
+                stele: SteleConfig {
+                    header: "@state".to_string(),
+                    separator: "|".to_string(),
+                    nested_separator: ".".to_string(),
+                    symbols,
+                    modality_values: HashMap::new(),
+                },
+                dimensions: HashMap::new(),
+                mode_mappings: HashMap::new(),
+            };
+            let original = EmotionalState {
+                temperature: dims[0],
+                entropy: dims[1],
+                gravity: dims[2],
+                depth: dims[3],
+                energy: dims[4],
+                toward: TowardState {
+                    agency: dims[5],
+                    flow: dims[6],
+                    distance: dims[7],
+                    modality,
+                },
+            };
+            let stele = original.encode_stele(&schema);
+            let decoded = EmotionalState::decode_stele(&stele, &schema).unwrap();
+            prop_assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&original).unwrap(),
+                "stele: {}",
+                stele
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_schemas_are_rejected() {
+        let mut schema: StateSchema =
+            serde_json::from_str(include_str!("../schemas/example-q-state.json")).unwrap();
+        schema.validate().unwrap();
+
+        let mut overlapping = schema.clone();
+        overlapping
+            .stele
+            .symbols
+            .insert("entropy".to_string(), "ᚠᚠ".to_string());
+        assert!(overlapping.validate().is_err());
+
+        schema
+            .stele
+            .modality_values
+            .insert("emotional".to_string(), "ᛈ".to_string());
+        assert!(schema.validate().is_err());
+    }
+}
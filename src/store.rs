@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::knowledge::KnowledgeEntry;
@@ -40,20 +41,56 @@ impl AgentContext {
             include_private: false,
         }
     }
+
+    /// Whether an entry with this visibility and owner is visible
+    pub fn can_see(&self, visibility: &str, owner: Option<&str>) -> bool {
+        visibility == "public"
+            || (self.include_private
+                && self.agent_id.is_some()
+                && self.agent_id.as_deref() == owner)
+    }
 }
 
-/// Filter for resonance-based queries
+/// Filter for knowledge queries, applied by the backend
 #[derive(Debug, Clone, Default)]
 pub struct KnowledgeFilter {
     pub min_resonance: Option<i32>,
     pub max_resonance: Option<i32>,
     pub categories: Option<Vec<String>>,
+    /// Entries carrying any of these tags
+    pub tags: Option<Vec<String>>,
+    /// Entries this agent starred
+    pub starred_by: Option<String>,
+    /// Entries from this source project
+    pub project: Option<String>,
+    /// Created at or after
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Created before
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Fields keyword search matches in (title, body and summary when unset)
+    pub search_fields: Option<Vec<crate::search::SearchField>>,
+}
+
+/// Weighted score of a keyword match in each field (0 where it missed):
+/// BM25 for the indexed text fields, matching tags for `tags`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct TextScore {
+    pub title: f64,
+    pub body: f64,
+    pub summary: f64,
+    pub tags: f64,
+}
+
+impl TextScore {
+    pub fn total(&self) -> f64 {
+        self.title + self.body + self.summary + self.tags
+    }
 }
 
 /// Result of a wake-up cascade query
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct WakeCascade {
-    /// Layer 1: Foundational/transformative, resonance 8+
+    /// Layer 1: Starred entries first, then foundational/transformative, resonance 8+
     pub core: Vec<crate::knowledge::KnowledgeEntry>,
     /// Layer 2: Last N days, sorted by resonance * recency
     pub recent: Vec<crate::knowledge::KnowledgeEntry>,
@@ -70,6 +107,29 @@ impl WakeCascade {
             .map(|e| e.id.clone())
             .collect()
     }
+
+    /// Wake phrases in cascade order for prompt injection: entries with an
+    /// explicit wake_order first (ascending), then the rest as the layers
+    /// ranked them. Entries surfaced in more than one layer count once.
+    pub fn ordered_phrases(&self) -> Vec<(String, String)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut entries: Vec<&crate::knowledge::KnowledgeEntry> = self
+            .core
+            .iter()
+            .chain(self.recent.iter())
+            .chain(self.bridges.iter())
+            .filter(|e| e.has_any_wake_phrase() && seen.insert(e.id.as_str()))
+            .collect();
+        entries.sort_by_key(|e| e.wake_order.unwrap_or(i32::MAX));
+        entries
+            .into_iter()
+            .flat_map(|e| {
+                e.active_wake_phrases()
+                    .into_iter()
+                    .map(|p| (e.id.clone(), p.to_string()))
+            })
+            .collect()
+    }
 }
 
 /// Result of an edit_content operation
@@ -115,6 +175,19 @@ pub trait KnowledgeStore {
     /// Delete a knowledge entry (respects visibility: agents can only delete entries they can see)
     fn delete(&self, id: &str, ctx: &AgentContext) -> Result<bool>;
 
+    /// Move an entry to the trash (respects visibility like `delete`).
+    /// Returns Ok(false) if it doesn't exist, isn't visible, or is already trashed.
+    fn archive(&self, id: &str, ctx: &AgentContext) -> Result<bool>;
+
+    /// Take an entry out of the trash. Returns Ok(false) if it isn't there.
+    fn restore(&self, id: &str, ctx: &AgentContext) -> Result<bool>;
+
+    /// Entries in the trash, most recently trashed first
+    fn list_archived(&self, ctx: &AgentContext) -> Result<Vec<KnowledgeEntry>>;
+
+    /// Delete a trashed entry for good. Returns Ok(false) if it isn't in the trash.
+    fn purge_archived(&self, id: &str, ctx: &AgentContext) -> Result<bool>;
+
     /// Search knowledge entries
     fn search(
         &self,
@@ -123,6 +196,14 @@ pub trait KnowledgeStore {
         filter: &KnowledgeFilter,
     ) -> Result<Vec<KnowledgeEntry>>;
 
+    /// Per-field text scores behind `search`, keyed by entry id
+    fn search_scores(
+        &self,
+        query: &str,
+        ctx: &AgentContext,
+        filter: &KnowledgeFilter,
+    ) -> Result<HashMap<String, TextScore>>;
+
     /// Semantic search using vector similarity
     fn semantic_search(
         &self,
@@ -143,7 +224,7 @@ pub trait KnowledgeStore {
     /// List all entries
     fn list_all(&self, ctx: &AgentContext) -> Result<Vec<KnowledgeEntry>>;
 
-    /// Count total entries
+    /// Count entries, leaving out the trash
     fn count(&self) -> Result<usize>;
 
     /// Wake-up cascade query (three-layer resonance)
@@ -159,6 +240,11 @@ pub trait KnowledgeStore {
     /// Use for intentional single-entry access (e.g. `show`, `fact-session`).
     fn update_activations(&self, ids: &[String]) -> Result<()>;
 
+    /// Seal an entry into the identity namespace, or release it back out.
+    /// The store enforces what may be sealed and what a sealed entry may
+    /// change (see identity.rs). Returns Ok(false) if the entry doesn't exist.
+    fn set_identity(&self, id: &str, sealed: bool) -> Result<bool>;
+
     /// Update only the summary field of a knowledge entry (targeted update, bypasses SCHEMAFULL UPSERT)
     /// Respects visibility: agents can only update summaries on entries they can see.
     /// Returns Ok(false) for entries that don't exist OR that the agent can't see
@@ -265,6 +351,68 @@ pub trait KnowledgeStore {
     /// Purge old backups, keeping the most recent `keep` per entry
     fn purge_backups(&self, entry_id: &str, keep: usize) -> Result<()>;
 
+    // =========================================================================
+    // COMMAND LOG (opt-in local usage history)
+    // =========================================================================
+
+    /// Record a completed CLI command
+    fn record_command(&self, entry: &crate::types::CommandLogEntry) -> Result<()>;
+
+    /// List logged commands, newest first. `since_days` limits the window;
+    /// `failed_only` keeps just the failures.
+    fn list_command_log(
+        &self,
+        limit: Option<usize>,
+        since_days: Option<i64>,
+        failed_only: bool,
+    ) -> Result<Vec<crate::types::CommandLogEntry>>;
+
+    // =========================================================================
+    // REVIEW CARDS (spaced repetition)
+    // =========================================================================
+
+    /// All review cards for an agent
+    fn list_review_cards(&self, agent: &str) -> Result<Vec<crate::types::ReviewCard>>;
+
+    /// Insert or replace the review card for (agent, entry)
+    fn upsert_review_card(&self, card: &crate::types::ReviewCard) -> Result<()>;
+
+    // =========================================================================
+    // ENTRY VIEWS (recently viewed)
+    // =========================================================================
+
+    /// Note that `agent` viewed an entry just now
+    fn record_view(&self, agent: &str, entry_id: &str) -> Result<()>;
+
+    /// An agent's viewed entries, most recent first
+    fn list_views(&self, agent: &str) -> Result<Vec<crate::types::EntryView>>;
+
+    // =========================================================================
+    // STARRED (per-agent pinned set)
+    // =========================================================================
+
+    /// Star an entry for `agent`. Returns false if it was already starred.
+    fn star(&self, agent: &str, entry_id: &str) -> Result<bool>;
+
+    /// Unstar an entry for `agent`. Returns false if it wasn't starred.
+    fn unstar(&self, agent: &str, entry_id: &str) -> Result<bool>;
+
+    /// IDs of the entries `agent` starred, in the order they were starred
+    fn list_starred(&self, agent: &str) -> Result<Vec<String>>;
+
+    // =========================================================================
+    // QUEUE (reading list)
+    // =========================================================================
+
+    /// Add an item to the queue
+    fn add_queue_item(&self, item: &crate::types::QueueItem) -> Result<()>;
+
+    /// List queue items, highest priority then oldest first
+    fn list_queue(&self, include_done: bool) -> Result<Vec<crate::types::QueueItem>>;
+
+    /// Mark an item done. Returns false if no open item has that id.
+    fn complete_queue_item(&self, id: &str) -> Result<bool>;
+
     // =========================================================================
     // TAG OPERATIONS
     // =========================================================================
@@ -278,6 +426,18 @@ pub trait KnowledgeStore {
     /// List all distinct tags, optionally filtered by category
     fn list_all_tags(&self, category: Option<&str>) -> Result<Vec<String>>;
 
+    /// Tags in use with how many entries (in `category`, if given) carry each
+    fn tag_counts(&self, category: Option<&str>) -> Result<Vec<crate::types::TagCount>>;
+
+    /// Move every entry and project tagged with any of `sources` onto
+    /// `target` (created if needed) and drop the sources. Returns how many
+    /// entries were retagged.
+    fn merge_tags(&self, sources: &[String], target: &str) -> Result<usize>;
+
+    /// Remove tags from every entry; a tag still used by projects keeps its
+    /// record. Returns how many entries lost a tag.
+    fn delete_tags(&self, names: &[String]) -> Result<usize>;
+
     // =========================================================================
     // APPLICABILITY OPERATIONS
     // =========================================================================
@@ -355,12 +515,18 @@ pub trait KnowledgeStore {
     /// List relationships for an entry
     fn list_relationships_for_entry(&self, entry_id: &str) -> Result<Vec<Relationship>>;
 
+    /// List every relationship edge in the store
+    fn list_all_relationships(&self) -> Result<Vec<Relationship>>;
+
     /// Add relationship between entries
     fn add_relationship(&self, from: &str, to: &str, rel_type: &str) -> Result<String>;
 
     /// Delete relationship
     fn delete_relationship(&self, id: &str) -> Result<bool>;
 
+    /// Remove the edge of `rel_type` from `from` to `to`, if present
+    fn remove_relationship(&self, from: &str, to: &str, rel_type: &str) -> Result<bool>;
+
     /// Get facts extracted from a specific session
     fn get_facts_for_session(&self, session_id: &str) -> Result<Vec<String>>;
 
@@ -421,6 +587,27 @@ pub trait KnowledgeStore {
 
     /// List tables (for migration status)
     fn list_tables(&self) -> Result<Vec<String>>;
+
+    // =========================================================================
+    // CHANGE FEED & REPLICATION
+    // =========================================================================
+
+    /// Changes after cursor `since`, oldest first, filtered by visibility
+    fn list_changes(
+        &self,
+        since: i64,
+        limit: usize,
+        ctx: &AgentContext,
+    ) -> Result<Vec<crate::types::Change>>;
+
+    /// Every entry updated at or after `since`, any visibility, oldest first
+    fn list_changed_since(&self, since: Option<&str>) -> Result<Vec<KnowledgeEntry>>;
+
+    /// Ids of every entry, any visibility
+    fn list_entry_ids(&self) -> Result<Vec<String>>;
+
+    /// Delete entries without a visibility check (replication targets only)
+    fn purge_entries(&self, ids: &[String]) -> Result<usize>;
 }
 
 /// Factory function to create the SurrealDB store
@@ -429,9 +616,30 @@ pub fn create_store(db_path: &Path) -> Result<Box<dyn KnowledgeStore>> {
 }
 
 /// Factory function with verbose control
+///
+/// Opening the store also settles operations a crashed process left in the
+/// intent journal.
 pub fn create_store_with_verbose(db_path: &Path, verbose: bool) -> Result<Box<dyn KnowledgeStore>> {
     let surreal_path = db_path.with_extension("surreal");
-    Ok(Box::new(
-        crate::surreal_db::SurrealDatabase::open_with_verbose(surreal_path, verbose)?,
-    ))
+    let journal = crate::journal::Journal::open(crate::paths::journal_dir());
+    let db = crate::surreal_db::SurrealDatabase::open_with_verbose(surreal_path, verbose)?
+        .with_journal(journal.clone())
+        .with_analyzer(crate::search::Settings::load()?)?;
+
+    let recovery = crate::journal::recover(&journal, &db)?;
+    if !recovery.settled.is_empty() {
+        eprintln!(
+            "Recovered {} interrupted operation(s) from the journal",
+            recovery.settled.len()
+        );
+    }
+    for (intent, error) in &recovery.failed {
+        eprintln!(
+            "warning: could not recover {} ({}): {} (see mx doctor)",
+            intent.op.describe(),
+            intent.id,
+            error
+        );
+    }
+    Ok(Box::new(db))
 }
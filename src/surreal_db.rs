@@ -1,17 +1,28 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
 use surrealdb::RecordId as SurrealRecordId;
 use surrealdb::Surreal;
+#[cfg(feature = "surreal-embedded")]
 use surrealdb::engine::local::SurrealKv;
+#[cfg(feature = "surreal-network")]
 use surrealdb::engine::remote::ws::{Client as WsClient, Ws};
+#[cfg(feature = "surreal-network")]
 use surrealdb::opt::auth::{Database, Namespace, Root};
 use surrealdb::sql::{Thing, Value};
 use tokio::runtime::Runtime;
 
+#[cfg(not(any(feature = "surreal-embedded", feature = "surreal-network")))]
+compile_error!("mx needs a SurrealDB engine: enable `surreal-embedded` or `surreal-network`");
+
+use crate::error::MxError;
+use crate::journal::{Journal, Op};
 use crate::knowledge::KnowledgeEntry;
+use crate::search::SearchField;
+use crate::slow_log;
 use crate::store::KnowledgeStore;
 use crate::types::{
     Agent, ApplicabilityType, Category, ContentType, EntryType, Project, Relationship,
@@ -26,15 +37,18 @@ use crate::types::{
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum SurrealMode {
     /// Embedded SurrealKV (local file-based, default)
-    #[default]
+    #[cfg_attr(feature = "surreal-embedded", default)]
     Embedded,
-    /// Network connection via WebSocket
+    /// Network connection via WebSocket (the default in builds without
+    /// `surreal-embedded`)
+    #[cfg_attr(not(feature = "surreal-embedded"), default)]
     Network,
 }
 
 /// Configuration for SurrealDB connection
 ///
-/// Parsed from environment variables:
+/// Read from the `surreal:` section of config.yaml (written by `mx init`),
+/// then overridden by environment variables:
 /// - `MX_SURREAL_MODE`: "embedded" (default) or "network"
 /// - `MX_SURREAL_URL`: WebSocket URL for network mode (default: ws://localhost:8000)
 /// - `MX_SURREAL_USER`: Username for network auth (default: root)
@@ -43,6 +57,8 @@ pub enum SurrealMode {
 /// - `MX_SURREAL_AUTH_LEVEL`: Auth level for signin: "root" (default), "namespace"/"ns", or "database"/"db"
 /// - `MX_SURREAL_NS`: Namespace (default: memory)
 /// - `MX_SURREAL_DB`: Database name (default: knowledge)
+///
+/// The password itself is never read from config.yaml, only `pass_file`.
 #[derive(Debug, Clone)]
 pub struct SurrealConfig {
     /// Connection mode
@@ -64,7 +80,7 @@ pub struct SurrealConfig {
 impl Default for SurrealConfig {
     fn default() -> Self {
         Self {
-            mode: SurrealMode::Embedded,
+            mode: SurrealMode::default(),
             url: "ws://localhost:8000".to_string(),
             user: "root".to_string(),
             pass: None,
@@ -76,41 +92,52 @@ impl Default for SurrealConfig {
 }
 
 impl SurrealConfig {
-    /// Parse configuration from environment variables
+    /// Parse configuration from config.yaml and environment variables
     pub fn from_env() -> Self {
-        let mode = match std::env::var("MX_SURREAL_MODE")
-            .unwrap_or_default()
+        let file = SurrealFileConfig::load().unwrap_or_else(|e| {
+            eprintln!("Warning: {:#}", e);
+            SurrealFileConfig::default()
+        });
+        Self::resolve(&file, |name| std::env::var(name).ok())
+    }
+
+    /// `file` with each setting overridden by its variable from `env`
+    fn resolve(file: &SurrealFileConfig, env: impl Fn(&str) -> Option<String>) -> Self {
+        let setting = |name: &str, configured: &Option<String>, default: &str| {
+            env(name)
+                .or_else(|| configured.clone())
+                .unwrap_or_else(|| default.to_string())
+        };
+
+        let mode = match setting("MX_SURREAL_MODE", &file.mode, "")
             .to_lowercase()
             .as_str()
         {
             "network" => SurrealMode::Network,
-            _ => SurrealMode::Embedded,
+            "embedded" => SurrealMode::Embedded,
+            _ => SurrealMode::default(),
         };
 
-        let url =
-            std::env::var("MX_SURREAL_URL").unwrap_or_else(|_| "ws://localhost:8000".to_string());
+        let url = setting("MX_SURREAL_URL", &file.url, "ws://localhost:8000");
 
-        let user = std::env::var("MX_SURREAL_USER").unwrap_or_else(|_| "root".to_string());
+        let user = setting("MX_SURREAL_USER", &file.user, "root");
 
         // Get password: try direct value first, then file path, filter empty strings
-        let pass = std::env::var("MX_SURREAL_PASS")
-            .ok()
+        let pass = env("MX_SURREAL_PASS")
             .or_else(|| {
                 // Try reading from file path (e.g., agenix secret)
-                std::env::var("MX_SURREAL_PASS_FILE")
-                    .ok()
+                env("MX_SURREAL_PASS_FILE")
+                    .or_else(|| file.pass_file.clone())
                     .and_then(|path| std::fs::read_to_string(path).ok())
             })
             .map(|s| s.trim().to_string())
             .filter(|p| !p.is_empty());
 
-        let namespace = std::env::var("MX_SURREAL_NS").unwrap_or_else(|_| "memory".to_string());
+        let namespace = setting("MX_SURREAL_NS", &file.namespace, "memory");
 
-        let database = std::env::var("MX_SURREAL_DB").unwrap_or_else(|_| "knowledge".to_string());
+        let database = setting("MX_SURREAL_DB", &file.database, "knowledge");
 
-        let auth_level = std::env::var("MX_SURREAL_AUTH_LEVEL")
-            .unwrap_or_else(|_| "root".to_string())
-            .to_lowercase();
+        let auth_level = setting("MX_SURREAL_AUTH_LEVEL", &file.auth_level, "root").to_lowercase();
 
         Self {
             mode,
@@ -129,9 +156,65 @@ impl SurrealConfig {
     }
 }
 
+/// The `surreal:` section of config.yaml. Unset fields fall back to the
+/// environment and then the defaults; see [`SurrealConfig`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurrealFileConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// File holding the password (the password itself is never stored here)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pass_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_level: Option<String>,
+}
+
+impl SurrealFileConfig {
+    /// Key in config.yaml holding the connection settings
+    pub const KEY: &'static str = "surreal";
+
+    /// The section from the active profile's config.yaml (empty when absent)
+    pub fn load() -> Result<Self> {
+        Self::load_from(&crate::paths::config_file())
+    }
+
+    /// The section from the config.yaml at `path`
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let config = crate::config::load(path)?;
+        match config.get(Self::KEY) {
+            Some(value) => serde_yaml::from_value(value.clone())
+                .context("Invalid 'surreal' section in config.yaml"),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Write the section to the config.yaml at `path`, leaving other keys
+    /// alone
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let mut config = crate::config::load(path)?;
+        config.insert(Self::KEY.into(), serde_yaml::to_value(self)?);
+        crate::config::save(path, &config)
+    }
+}
+
 /// Embedded SurrealDB schema - applied on database open
 const SCHEMA: &str = include_str!("../schema/surrealdb-schema.surql");
 
+/// Nearest neighbours fetched from the HNSW index per result wanted
+const KNN_OVERFETCH: usize = 4;
+
+/// Fewest nearest neighbours fetched from the HNSW index
+const KNN_MIN_CANDIDATES: usize = 50;
+
 /// Tag record for SurrealDB
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
@@ -276,6 +359,29 @@ pub struct SurrealKnowledgeRecord {
     /// Content format: markdown (default), json, stele:markdown, stele:ascii, stele:light, stele:full
     #[serde(default = "default_format")]
     pub format: String,
+
+    /// Estimated token count of title + body
+    #[serde(default)]
+    pub token_count: Option<i32>,
+
+    /// Storage namespace ("identity" for sealed identity blooms)
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    /// Trust in the entry, 0.0-1.0 (None = never rated)
+    #[serde(default)]
+    pub confidence: Option<f64>,
+
+    /// Last `mx memory confirm` or explicit rating
+    #[serde(default)]
+    pub confirmed_at: Option<String>,
+    /// When the entry was moved to the trash (None = live)
+    #[serde(default)]
+    pub archived_at: Option<String>,
+
+    /// Files attached with `mx memory attach`
+    #[serde(default)]
+    pub attachments: Vec<crate::knowledge::Attachment>,
 }
 
 fn default_visibility() -> String {
@@ -328,6 +434,12 @@ impl SurrealKnowledgeRecord {
             embedded_at: self.embedded_at,
             format: self.format,
             effective_resonance: None,
+            token_count: self.token_count,
+            namespace: self.namespace,
+            confidence: self.confidence,
+            confirmed_at: self.confirmed_at,
+            archived_at: self.archived_at,
+            attachments: self.attachments,
         }
     }
 }
@@ -375,16 +487,26 @@ fn normalize_datetime(s: &str) -> String {
 }
 
 /// Connection abstraction for SurrealDB - supports both embedded and network modes
+///
+/// Both variants are cheap handles onto a shared client, so cloning a
+/// connection reuses it rather than opening a new one.
+#[derive(Clone)]
 pub enum SurrealConnection {
     /// Embedded SurrealKV database (local file-based)
+    #[cfg(feature = "surreal-embedded")]
     Embedded(Surreal<surrealdb::engine::local::Db>),
     /// Network connection via WebSocket
+    #[cfg(feature = "surreal-network")]
     Network(Surreal<WsClient>),
 }
 
 /// SurrealDB-backed knowledge store
 pub struct SurrealDatabase {
     conn: SurrealConnection,
+    /// Where entry upserts record their intent (see [`crate::journal`])
+    journal: Option<Journal>,
+    /// Full-text settings; keyword queries drop its stop words
+    analyzer: crate::search::Settings,
 }
 
 /// Macro to execute code with the appropriate database connection (embedded or network)
@@ -401,13 +523,65 @@ pub struct SurrealDatabase {
 macro_rules! with_db {
     ($self:expr, $db:ident, $body:expr) => {
         match &$self.conn {
+            #[cfg(feature = "surreal-embedded")]
             SurrealConnection::Embedded($db) => $body,
+            #[cfg(feature = "surreal-network")]
             SurrealConnection::Network($db) => $body,
         }
     };
 }
 
-impl SurrealDatabase {}
+// ============================================================================
+// CONNECTION POOL AND STATEMENT CACHE
+// ============================================================================
+
+/// Open connections keyed by target (embedded path or server/ns/db/user).
+///
+/// A single invocation may open the store several times (the command itself,
+/// command history, wake sessions). Reusing the connection skips reconnecting,
+/// re-authenticating, and re-applying the schema.
+fn connection_pool() -> &'static Mutex<HashMap<String, SurrealConnection>> {
+    static POOL: OnceLock<Mutex<HashMap<String, SurrealConnection>>> = OnceLock::new();
+    POOL.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Pool key for a connection target
+fn pool_key(path: &Path, config: &SurrealConfig) -> String {
+    match config.mode {
+        SurrealMode::Embedded => format!(
+            "kv:{}:{}/{}",
+            path.display(),
+            config.namespace,
+            config.database
+        ),
+        SurrealMode::Network => format!(
+            "ws:{}@{}:{}/{}",
+            config.user, config.url, config.namespace, config.database
+        ),
+    }
+}
+
+/// Assembled SurrealQL statements, keyed by query name and visibility scope.
+///
+/// Statements are built from static fragments (select fields, visibility
+/// clause) plus per-call bind parameters, so the text for a given key never
+/// changes and only needs formatting once per process.
+fn statement_cache() -> &'static Mutex<HashMap<(&'static str, bool), &'static str>> {
+    static CACHE: OnceLock<Mutex<HashMap<(&'static str, bool), &'static str>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Fetch a cached statement, building it on first use
+fn cached_statement(
+    key: &'static str,
+    scoped: bool,
+    build: impl FnOnce() -> String,
+) -> &'static str {
+    let mut cache = statement_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry((key, scoped))
+        .or_insert_with(|| Box::leak(build().into_boxed_str()))
+}
 
 impl SurrealDatabase {
     /// Get or initialize the global tokio runtime
@@ -421,37 +595,173 @@ impl SurrealDatabase {
     /// This method checks environment variables first - if `MX_SURREAL_MODE=network`,
     /// the path is ignored and a network connection is established instead.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let config = SurrealConfig::from_env();
-        Self::runtime().block_on(Self::open_with_config_async(path, &config, false))
+        Self::open_with_verbose(path, false)
     }
 
     /// Open database with verbose control
+    ///
+    /// Connections are pooled per process: opening the same target twice
+    /// returns a handle onto the existing connection.
     pub fn open_with_verbose<P: AsRef<Path>>(path: P, verbose: bool) -> Result<Self> {
         let config = SurrealConfig::from_env();
-        Self::runtime().block_on(Self::open_with_config_async(path, &config, verbose))
+        let key = pool_key(path.as_ref(), &config);
+
+        if let Some(conn) = connection_pool()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            if verbose {
+                eprintln!("[mx] Reusing pooled SurrealDB connection");
+            }
+            return Ok(Self {
+                conn: conn.clone(),
+                journal: None,
+                analyzer: Default::default(),
+            });
+        }
+
+        let db = Self::runtime().block_on(Self::open_with_config_async(path, &config, verbose))?;
+        connection_pool()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, db.conn.clone());
+        Ok(db)
     }
 
     /// Connect using explicit configuration
     ///
     /// For embedded mode, `path` specifies the database location.
     /// For network mode, `path` is ignored.
+    /// Always opens a fresh connection; the pool is bypassed.
     pub fn connect<P: AsRef<Path>>(path: P, config: &SurrealConfig) -> Result<Self> {
         Self::runtime().block_on(Self::open_with_config_async(path, config, false))
     }
 
+    /// Journal entry upserts, so a crash between their steps is replayed
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Search with `analyzer` (see [`crate::analyzer`]). On an embedded
+    /// store whose analyzer was last defined differently, redefine it and
+    /// rebuild the full-text indexes.
+    pub fn with_analyzer(mut self, analyzer: crate::search::Settings) -> Result<Self> {
+        #[cfg(feature = "surreal-embedded")]
+        #[allow(irrefutable_let_patterns)] // embedded-only builds
+        if let SurrealConnection::Embedded(ref db) = self.conn {
+            let definition = analyzer.definition();
+            Self::runtime().block_on(async {
+                let applied: Option<String> = db
+                    .query("SELECT VALUE definition FROM ONLY search_analyzer:current")
+                    .await
+                    .context("Failed to read the search analyzer")?
+                    .take(0)?;
+                let applied =
+                    applied.unwrap_or_else(|| crate::search::Settings::default().definition());
+                if applied == definition {
+                    return Ok(());
+                }
+                eprintln!("Search settings changed; rebuilding full-text indexes");
+                let errors = db
+                    .query(format!(
+                        "{};
+                        REBUILD INDEX knowledge_title_fts ON knowledge;
+                        REBUILD INDEX knowledge_body_fts ON knowledge;
+                        REBUILD INDEX knowledge_summary_fts ON knowledge;
+                        UPSERT search_analyzer:current SET definition = $definition;",
+                        definition
+                    ))
+                    .bind(("definition", definition.clone()))
+                    .await
+                    .context("Failed to apply the search analyzer")?
+                    .take_errors();
+                if !errors.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "Search analyzer update failed: {:?}",
+                        errors
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        self.analyzer = analyzer;
+        Ok(self)
+    }
+
+    /// Switch this connection's session to another namespace and database
+    ///
+    /// Clones share the session, so every handle onto this connection
+    /// (including pooled ones) follows the switch. The tenant server relies
+    /// on that: it holds one connection and switches it per request.
+    pub fn use_namespace(&self, namespace: &str, database: &str) -> Result<()> {
+        Self::runtime().block_on(async {
+            with_db!(self, db, {
+                db.use_ns(namespace)
+                    .use_db(database)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Failed to set namespace '{}' and database '{}'",
+                            namespace, database
+                        )
+                    })
+            })
+        })
+    }
+
+    /// Apply the schema to the current namespace (embedded only; a network
+    /// server is provisioned separately)
+    #[cfg(feature = "surreal-embedded")]
+    pub fn apply_schema(&self) -> Result<()> {
+        #[allow(irrefutable_let_patterns)] // embedded-only builds
+        let SurrealConnection::Embedded(ref db) = self.conn else {
+            return Ok(());
+        };
+        Self::runtime().block_on(async {
+            let errors = db
+                .query(SCHEMA)
+                .await
+                .context("Failed to apply database schema")?
+                .take_errors();
+            if !errors.is_empty() {
+                return Err(anyhow::anyhow!("Schema application failed: {:?}", errors));
+            }
+            Ok(())
+        })
+    }
+
+    /// Apply the schema: nothing to do without an embedded engine
+    #[cfg(not(feature = "surreal-embedded"))]
+    pub fn apply_schema(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Internal: open with config, branching on mode
     async fn open_with_config_async<P: AsRef<Path>>(
         path: P,
         config: &SurrealConfig,
         verbose: bool,
     ) -> Result<Self> {
-        match config.mode {
+        match &config.mode {
+            #[cfg(feature = "surreal-embedded")]
             SurrealMode::Embedded => Self::open_embedded_async(path, config, verbose).await,
+            #[cfg(feature = "surreal-network")]
             SurrealMode::Network => Self::open_network_async(config, verbose).await,
+            #[allow(unreachable_patterns)]
+            mode => {
+                let _ = (path, verbose);
+                anyhow::bail!(
+                    "This mx was built without {:?} SurrealDB support (see MX_SURREAL_MODE)",
+                    mode
+                )
+            }
         }
     }
 
     /// Open embedded SurrealKV database
+    #[cfg(feature = "surreal-embedded")]
     async fn open_embedded_async<P: AsRef<Path>>(
         path: P,
         config: &SurrealConfig,
@@ -514,10 +824,13 @@ impl SurrealDatabase {
 
         Ok(Self {
             conn: SurrealConnection::Embedded(db),
+            journal: None,
+            analyzer: Default::default(),
         })
     }
 
     /// Check if URL is localhost (safe for unencrypted traffic)
+    #[cfg(feature = "surreal-network")]
     fn is_localhost_url(url: &str) -> bool {
         url.contains("://localhost") || url.contains("://127.0.0.1") || url.contains("://[::1]")
     }
@@ -526,6 +839,7 @@ impl SurrealDatabase {
     ///
     /// The surrealdb crate expects just `host:port`, not `ws://host:port`.
     /// Users may provide the full URL with protocol, so we strip it if present.
+    #[cfg(feature = "surreal-network")]
     fn sanitize_ws_url(url: &str) -> String {
         url.strip_prefix("ws://")
             .or_else(|| url.strip_prefix("wss://"))
@@ -536,6 +850,7 @@ impl SurrealDatabase {
     /// Open network connection via WebSocket
     ///
     /// Authenticates with the remote SurrealDB server using credentials from config.
+    #[cfg(feature = "surreal-network")]
     async fn open_network_async(config: &SurrealConfig, verbose: bool) -> Result<Self> {
         // Diagnostic: Log connection attempt (to stderr, doesn't interfere with stdout)
         if verbose {
@@ -661,6 +976,8 @@ impl SurrealDatabase {
 
         Ok(Self {
             conn: SurrealConnection::Network(db),
+            journal: None,
+            analyzer: Default::default(),
         })
     }
 
@@ -688,9 +1005,11 @@ impl SurrealDatabase {
     /// Returns `None` if called on a network connection.
     /// Prefer using connection-agnostic methods instead.
     #[deprecated(note = "Use connection-agnostic methods instead")]
+    #[cfg(feature = "surreal-embedded")]
     pub fn inner(&self) -> Option<&Surreal<surrealdb::engine::local::Db>> {
         match &self.conn {
             SurrealConnection::Embedded(db) => Some(db),
+            #[cfg(feature = "surreal-network")]
             SurrealConnection::Network(_) => None,
         }
     }
@@ -719,11 +1038,30 @@ impl SurrealDatabase {
         IF embedding THEN embedding ELSE null END AS embedding,
         IF embedding_model THEN embedding_model ELSE null END AS embedding_model,
         IF embedded_at THEN <string>embedded_at ELSE null END AS embedded_at,
-        IF format THEN format ELSE 'markdown' END AS format"
+        IF format THEN format ELSE 'markdown' END AS format,
+        IF token_count THEN token_count ELSE null END AS token_count,
+        IF namespace THEN namespace ELSE null END AS namespace,
+        confidence,
+        IF confirmed_at THEN <string>confirmed_at ELSE null END AS confirmed_at,
+        IF archived_at THEN <string>archived_at ELSE null END AS archived_at,
+        IF attachments THEN attachments ELSE [] END AS attachments"
     }
 
-    /// Build visibility filter for privacy-aware queries
+    /// Build visibility filter for privacy-aware queries. Trashed entries
+    /// are left out; see [`Self::build_trash_filter`] for the trash itself.
     fn build_visibility_filter(ctx: &crate::store::AgentContext) -> (String, Option<String>) {
+        let (clause, agent) = Self::build_owner_filter(ctx);
+        (format!("{} AND archived_at IS NONE", clause), agent)
+    }
+
+    /// Visibility filter matching only trashed entries
+    fn build_trash_filter(ctx: &crate::store::AgentContext) -> (String, Option<String>) {
+        let (clause, agent) = Self::build_owner_filter(ctx);
+        (format!("{} AND archived_at IS NOT NONE", clause), agent)
+    }
+
+    /// Public entries, plus the agent's own private ones when allowed
+    fn build_owner_filter(ctx: &crate::store::AgentContext) -> (String, Option<String>) {
         if ctx.include_private {
             if let Some(ref agent) = ctx.agent_id {
                 (
@@ -790,6 +1128,46 @@ impl SurrealDatabase {
         }
     }
 
+    /// Build tag, starred, project and creation-date clauses. Values travel as bound
+    /// parameters (returned alongside), never inside the SQL text.
+    fn build_field_filter(
+        filter: &crate::store::KnowledgeFilter,
+    ) -> (String, Vec<(&'static str, serde_json::Value)>) {
+        let mut clauses = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some(tags) = filter.tags.as_ref().filter(|t| !t.is_empty()) {
+            clauses.push("->tagged_with->tag.name CONTAINSANY $filter_tags");
+            params.push(("filter_tags", serde_json::json!(tags)));
+        }
+        if let Some(ref agent) = filter.starred_by {
+            clauses.push(
+                "string::concat('kn-', meta::id(id)) IN
+                    (SELECT VALUE entry_id FROM starred WHERE agent = $filter_starred_by)",
+            );
+            params.push(("filter_starred_by", serde_json::json!(agent)));
+        }
+        if let Some(ref project) = filter.project {
+            clauses.push("source_project = type::thing('project', $filter_project)");
+            params.push(("filter_project", serde_json::json!(project)));
+        }
+        if let Some(since) = filter.since {
+            clauses.push("created_at >= <datetime>$filter_since");
+            params.push(("filter_since", serde_json::json!(since.to_rfc3339())));
+        }
+        if let Some(until) = filter.until {
+            clauses.push("created_at < <datetime>$filter_until");
+            params.push(("filter_until", serde_json::json!(until.to_rfc3339())));
+        }
+
+        let sql = clauses
+            .iter()
+            .map(|c| format!("AND ({})", c))
+            .collect::<Vec<_>>()
+            .join(" ");
+        (sql, params)
+    }
+
     /// Validate category name to prevent SQL injection
     /// Only allows alphanumeric characters, underscores, and hyphens
     fn is_valid_category_name(name: &str) -> bool {
@@ -843,6 +1221,11 @@ impl SurrealDatabase {
     }
 
     async fn upsert_knowledge_async(&self, entry: &KnowledgeEntry) -> Result<RecordId> {
+        // Keep the content being replaced as a revision — non-fatal
+        if let Err(e) = self.snapshot_prior_async(entry).await {
+            eprintln!("Warning: failed to record revision: {}", e);
+        }
+
         // Extract ID from "kn-xxxxx" format
         let id_part = entry.id.strip_prefix("kn-").unwrap_or(&entry.id);
         let record_id = RecordId::new("knowledge", id_part);
@@ -871,7 +1254,11 @@ impl SurrealDatabase {
             wake_phrase = $wake_phrase,
             embedding = $embedding,
             embedding_model = $embedding_model,
-            format = $format"
+            format = $format,
+            token_count = $token_count,
+            namespace = $namespace,
+            confidence = $confidence,
+            attachments = $attachments"
             .to_string();
 
         // Add optional fields
@@ -896,6 +1283,15 @@ impl SurrealDatabase {
         if entry.embedded_at.is_some() {
             query.push_str(", embedded_at = <datetime>$embedded_at");
         }
+        if entry.confirmed_at.is_some() {
+            query.push_str(", confirmed_at = <datetime>$confirmed_at");
+        }
+        // Written either way, so importing or replicating a restore clears it
+        if entry.archived_at.is_some() {
+            query.push_str(", archived_at = <datetime>$archived_at");
+        } else {
+            query.push_str(", archived_at = NONE");
+        }
 
         // Bind all parameters and execute query
         let mut response = with_db!(self, db, {
@@ -945,7 +1341,11 @@ impl SurrealDatabase {
                 .bind(("wake_phrase", entry.wake_phrase.clone()))
                 .bind(("embedding", entry.embedding.clone()))
                 .bind(("embedding_model", entry.embedding_model.clone()))
-                .bind(("format", entry.format.clone()));
+                .bind(("format", entry.format.clone()))
+                .bind(("token_count", crate::tokens::entry_estimate(entry)))
+                .bind(("namespace", entry.namespace.clone()))
+                .bind(("confidence", entry.confidence))
+                .bind(("attachments", entry.attachments.clone()));
 
             // Bind optional parameters
             if let Some(ref proj) = entry.source_project_id {
@@ -969,6 +1369,12 @@ impl SurrealDatabase {
             if let Some(ref embedded) = entry.embedded_at {
                 q = q.bind(("embedded_at", normalize_datetime(embedded)));
             }
+            if let Some(ref confirmed) = entry.confirmed_at {
+                q = q.bind(("confirmed_at", normalize_datetime(confirmed)));
+            }
+            if let Some(ref archived) = entry.archived_at {
+                q = q.bind(("archived_at", normalize_datetime(archived)));
+            }
 
             q.await.context("Failed to upsert knowledge record")
         })?;
@@ -1141,7 +1547,8 @@ impl SurrealDatabase {
         Ok(Some(record.into_knowledge_entry(tags, applicability)))
     }
 
-    /// Delete a knowledge entry (edges cascade automatically).
+    /// Delete a knowledge entry, its relationship edges in the same transaction
+    /// (other edges cascade automatically).
     /// Respects visibility: agents can only delete entries they can see.
     /// Returns Ok(false) for entries that don't exist OR that the agent can't see
     /// (to avoid leaking existence of private entries).
@@ -1190,10 +1597,8 @@ impl SurrealDatabase {
         // Delete with the same visibility filter to prevent TOCTOU race conditions.
         // Even though we checked above, re-applying the filter on the DELETE ensures
         // no bypass is possible between check and delete.
-        let delete_sql = format!(
-            "DELETE FROM knowledge WHERE meta::id(id) = $id {}",
-            visibility_clause
-        );
+        let delete_sql =
+            Self::delete_entries_sql(&format!("meta::id(id) = $id {}", visibility_clause));
 
         let mut response = with_db!(self, db, {
             let mut query = db.query(&delete_sql).bind(("id", id_part.to_string()));
@@ -1212,6 +1617,114 @@ impl SurrealDatabase {
         Ok(true)
     }
 
+    /// One transaction deleting the entries `condition` selects: their
+    /// relationship edges first, then the entries, so the change-feed rows
+    /// and counter bumps they cause commit together with the delete or not
+    /// at all. Its last statement returns the deleted ids (without `kn-`).
+    fn delete_entries_sql(condition: &str) -> String {
+        format!(
+            "BEGIN TRANSACTION;
+            LET $doomed = (SELECT VALUE id FROM knowledge WHERE {});
+            DELETE relates_to WHERE in IN $doomed OR out IN $doomed;
+            LET $deleted = (DELETE $doomed RETURN VALUE meta::id($before.id));
+            RETURN $deleted;
+            COMMIT TRANSACTION;",
+            condition
+        )
+    }
+
+    /// Move an entry to the trash: set archived_at, which every
+    /// visibility-filtered query excludes. Bumps updated_at so the change
+    /// reaches the feed and replicas.
+    pub fn archive(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        let (clause, agent) = Self::build_visibility_filter(ctx);
+        let sql = format!(
+            "UPDATE knowledge SET archived_at = time::now(), updated_at = time::now()
+             WHERE meta::id(id) = $id {}
+             RETURN VALUE meta::id(id)",
+            clause
+        );
+        Self::runtime().block_on(self.trash_statement(&sql, id, agent, "archive"))
+    }
+
+    /// Take an entry out of the trash
+    pub fn restore(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        let (clause, agent) = Self::build_trash_filter(ctx);
+        let sql = format!(
+            "UPDATE knowledge SET archived_at = NONE, updated_at = time::now()
+             WHERE meta::id(id) = $id {}
+             RETURN VALUE meta::id(id)",
+            clause
+        );
+        Self::runtime().block_on(self.trash_statement(&sql, id, agent, "restore"))
+    }
+
+    /// Delete a trashed entry for good (edges go with it, as for `delete`)
+    pub fn purge_archived(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        let (clause, agent) = Self::build_trash_filter(ctx);
+        let sql = Self::delete_entries_sql(&format!("meta::id(id) = $id {}", clause));
+        Self::runtime().block_on(self.trash_statement(&sql, id, agent, "purge"))
+    }
+
+    /// Run a single-entry trash statement (or transaction ending in one);
+    /// true if it touched the entry
+    async fn trash_statement(
+        &self,
+        sql: &str,
+        id: &str,
+        current_agent: Option<String>,
+        action: &str,
+    ) -> Result<bool> {
+        let id_part = id.strip_prefix("kn-").unwrap_or(id);
+        let mut response = with_db!(self, db, {
+            let mut query = db.query(sql).bind(("id", id_part.to_string()));
+            if let Some(ref agent) = current_agent {
+                query = query.bind(("current_agent", agent.clone()));
+            }
+            query
+                .await
+                .with_context(|| format!("Failed to {} knowledge record", action))
+        })?;
+
+        let errors = response.take_errors();
+        if let Some(error) = errors.into_values().next() {
+            return Err(anyhow::anyhow!("{}", error));
+        }
+        let last = response.num_statements() - 1;
+        let touched: Vec<String> = response.take(last)?;
+        Ok(!touched.is_empty())
+    }
+
+    /// Entries in the trash visible to `ctx`, most recently trashed first
+    pub fn list_archived(&self, ctx: &crate::store::AgentContext) -> Result<Vec<KnowledgeEntry>> {
+        Self::runtime().block_on(async {
+            let (clause, current_agent) = Self::build_trash_filter(ctx);
+            let sql = format!(
+                "SELECT {}
+                FROM knowledge
+                {}
+                ORDER BY archived_at DESC",
+                Self::knowledge_select_fields(),
+                clause.replacen("AND", "WHERE", 1)
+            );
+
+            let mut response = with_db!(self, db, {
+                let mut query = db.query(&sql);
+                if let Some(agent) = current_agent {
+                    query = query.bind(("current_agent", agent));
+                }
+                query.await.context("Failed to list the trash")
+            })?;
+
+            let results: Vec<serde_json::Value> = response.take(0)?;
+            let mut entries = Vec::new();
+            for obj in results {
+                entries.push(self.value_to_knowledge_entry(obj).await?);
+            }
+            Ok(entries)
+        })
+    }
+
     // =========================================================================
     // BACKUP OPERATIONS (Issue #206)
     // =========================================================================
@@ -1240,7 +1753,7 @@ impl SurrealDatabase {
             Utc::now().format("%Y%m%dT%H%M%S%.3f")
         );
 
-        let _response = with_db!(self, db, {
+        let response = with_db!(self, db, {
             db.query(
                 "CREATE type::thing('memory_backup', $backup_id) SET
                     entry_id = $entry_id,
@@ -1249,7 +1762,9 @@ impl SurrealDatabase {
                     content_hash = $content_hash,
                     operation = $operation,
                     source_agent = $source_agent,
-                    created_at = time::now()
+                    created_at = time::now(),
+                    rev = (math::max(SELECT VALUE rev FROM memory_backup
+                        WHERE entry_id = $entry_id AND rev IS NOT NONE) ?? 0) + 1
                 ",
             )
             .bind(("backup_id", backup_id.clone()))
@@ -1262,13 +1777,55 @@ impl SurrealDatabase {
             .await
             .context("Failed to create memory backup")
         })?;
-
-        // Purge old backups (keep 10 per entry) — non-fatal
-        let _ = self.purge_backups_async(&entry_id, 10).await;
+        response.check().context("Failed to create memory backup")?;
 
         Ok(backup_id)
     }
 
+    /// Back up the stored title and body of `entry` if the upsert is about
+    /// to change them and the newest backup doesn't already hold them (the
+    /// edit commands back up under their own operation name first)
+    async fn snapshot_prior_async(&self, entry: &KnowledgeEntry) -> Result<()> {
+        let id_part = entry.id.strip_prefix("kn-").unwrap_or(&entry.id);
+        let mut response = with_db!(self, db, {
+            db.query("SELECT title, body, content_hash FROM ONLY type::thing('knowledge', $id)")
+                .bind(("id", id_part.to_string()))
+                .await
+                .context("Failed to read prior content")
+        })?;
+
+        #[derive(Deserialize)]
+        struct Prior {
+            title: String,
+            #[serde(default)]
+            body: Option<String>,
+            #[serde(default)]
+            content_hash: Option<String>,
+        }
+        let Some(prior): Option<Prior> = response.take(0)? else {
+            return Ok(());
+        };
+        if prior.title == entry.title && prior.body == entry.body {
+            return Ok(());
+        }
+        if let Some(latest) = self.latest_backup_async(&entry.id).await?
+            && latest.title == prior.title
+            && latest.body == prior.body
+        {
+            return Ok(());
+        }
+
+        let replaced = KnowledgeEntry {
+            title: prior.title,
+            body: prior.body,
+            content_hash: prior.content_hash,
+            ..entry.clone()
+        };
+        self.backup_content_async(&replaced, "upsert", entry.source_agent_id.as_deref())
+            .await?;
+        Ok(())
+    }
+
     /// List backups for an entry, newest first
     pub fn list_backups_internal(&self, entry_id: &str) -> Result<Vec<crate::types::MemoryBackup>> {
         Self::runtime().block_on(self.list_backups_async(entry_id))
@@ -1278,7 +1835,7 @@ impl SurrealDatabase {
         let mut response = with_db!(self, db, {
             db.query(
                 "SELECT meta::id(id) AS id, entry_id, title, body, content_hash,
-                        operation, source_agent, created_at
+                        operation, source_agent, created_at, rev
                  FROM memory_backup
                  WHERE entry_id = $entry_id
                  ORDER BY created_at DESC",
@@ -1307,7 +1864,7 @@ impl SurrealDatabase {
         let mut response = with_db!(self, db, {
             db.query(
                 "SELECT meta::id(id) AS id, entry_id, title, body, content_hash,
-                        operation, source_agent, created_at
+                        operation, source_agent, created_at, rev
                  FROM memory_backup
                  WHERE entry_id = $entry_id
                  ORDER BY created_at DESC
@@ -1365,41 +1922,143 @@ impl SurrealDatabase {
         ctx: &crate::store::AgentContext,
         filter: &crate::store::KnowledgeFilter,
     ) -> Result<Vec<KnowledgeEntry>> {
-        let query_owned = query.to_string();
+        let results = self
+            .keyword_match_async(
+                |_| Self::knowledge_select_fields().to_string(),
+                query,
+                ctx,
+                filter,
+            )
+            .await?;
+
+        let mut entries = Vec::new();
+        for obj in results {
+            entries.push(self.value_to_knowledge_entry(obj).await?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Per-field weighted scores of the entries `search_knowledge` finds
+    pub fn search_knowledge_scores(
+        &self,
+        query: &str,
+        ctx: &crate::store::AgentContext,
+        filter: &crate::store::KnowledgeFilter,
+    ) -> Result<HashMap<String, crate::store::TextScore>> {
+        let select = |scored: &[(SearchField, &str)]| {
+            let mut columns = vec!["meta::id(id) AS id".to_string()];
+            for (field, score) in scored {
+                columns.push(format!("{} AS {}", score, field.name()));
+            }
+            columns.join(", ")
+        };
+        let results =
+            Self::runtime().block_on(self.keyword_match_async(select, query, ctx, filter))?;
+
+        let mut scores = HashMap::new();
+        for obj in results {
+            let score = |field: &str| obj.get(field).and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let text = crate::store::TextScore {
+                title: score("title"),
+                body: score("body"),
+                summary: score("summary"),
+                tags: score("tags"),
+            };
+            if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
+                scores.insert(format!("kn-{}", id), text);
+            }
+        }
+        Ok(scores)
+    }
+
+    /// Match predicate and weighted score expression for keyword search in `field`
+    fn keyword_clause(field: SearchField) -> (&'static str, &'static str) {
+        match field {
+            SearchField::Title => ("title @1@ $query", "search::score(1) * $weight_title"),
+            SearchField::Body => ("body @2@ $query", "search::score(2) * $weight_body"),
+            SearchField::Summary => ("summary @3@ $query", "search::score(3) * $weight_summary"),
+            SearchField::Tags => (
+                "array::len(array::intersect(array::map(->tagged_with->tag.name, |$t| string::lowercase($t)), $query_words)) > 0",
+                "array::len(array::intersect(array::map(->tagged_with->tag.name, |$t| string::lowercase($t)), $query_words)) * $weight_tags",
+            ),
+        }
+    }
+
+    /// Rows matching `query` in any of the filter's search fields, best
+    /// weighted score first. `select` picks the columns, given each searched
+    /// field with its score expression.
+    async fn keyword_match_async(
+        &self,
+        select: impl FnOnce(&[(SearchField, &'static str)]) -> String,
+        query: &str,
+        ctx: &crate::store::AgentContext,
+        filter: &crate::store::KnowledgeFilter,
+    ) -> Result<Vec<serde_json::Value>> {
+        let query_owned = self.analyzer.keywords(query);
+        let query_words: Vec<String> = query_owned
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+
+        let requested = filter
+            .search_fields
+            .as_deref()
+            .unwrap_or(&SearchField::TEXT);
+        let fields: Vec<SearchField> = SearchField::ALL
+            .into_iter()
+            .filter(|field| requested.contains(field))
+            .collect();
+        let (predicates, scored): (Vec<&str>, Vec<(SearchField, &str)>) = fields
+            .iter()
+            .map(|&field| {
+                let (predicate, score) = Self::keyword_clause(field);
+                (predicate, (field, score))
+            })
+            .unzip();
+        let total: Vec<&str> = scored.iter().map(|(_, score)| *score).collect();
 
         let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
         let resonance_clause = Self::build_resonance_filter(filter);
         let category_clause = Self::build_category_filter(filter);
+        let (field_clause, field_params) = Self::build_field_filter(filter);
 
         let sql = format!(
-            "SELECT {}
+            "SELECT {}, {} AS score
             FROM knowledge
-            WHERE (title @@ $query OR body @@ $query OR summary @@ $query) {} {} {}",
-            Self::knowledge_select_fields(),
+            WHERE ({}) {} {} {} {}
+            ORDER BY score DESC",
+            select(&scored),
+            total.join(" + "),
+            predicates.join(" OR "),
             visibility_clause,
             resonance_clause,
-            category_clause
+            category_clause,
+            field_clause
         );
 
+        let weights = &self.analyzer.weights;
         let mut response = with_db!(self, db, {
-            let mut query_builder = db.query(&sql).bind(("query", query_owned));
+            let mut query_builder = db
+                .query(&sql)
+                .bind(("query", query_owned))
+                .bind(("query_words", query_words))
+                .bind(("weight_title", weights.of(SearchField::Title)))
+                .bind(("weight_body", weights.of(SearchField::Body)))
+                .bind(("weight_summary", weights.of(SearchField::Summary)))
+                .bind(("weight_tags", weights.of(SearchField::Tags)));
             if let Some(agent) = current_agent {
                 query_builder = query_builder.bind(("current_agent", agent));
             }
+            for param in field_params {
+                query_builder = query_builder.bind(param);
+            }
             query_builder
                 .await
                 .context("Failed to execute search query")
         })?;
 
-        let results: Vec<serde_json::Value> =
-            response.take(0).context("Failed to parse search results")?;
-
-        let mut entries = Vec::new();
-        for obj in results {
-            entries.push(self.value_to_knowledge_entry(obj).await?);
-        }
-
-        Ok(entries)
+        response.take(0).context("Failed to parse search results")
     }
 
     /// Semantic search using vector similarity (brute force cosine)
@@ -1428,18 +2087,39 @@ impl SurrealDatabase {
         let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
         let resonance_clause = Self::build_resonance_filter(filter);
         let category_clause = Self::build_category_filter(filter);
-
-        // Brute force vector similarity search (no HNSW index)
+        let (field_clause, field_params) = Self::build_field_filter(filter);
+
+        // Candidates come from the HNSW index, then go through the filters
+        // and exact cosine ordering. Filters can't share a WHERE with the KNN
+        // operator (anything past a flat AND chain matches nothing), so when
+        // they leave fewer than `limit` hits out of a full candidate set the
+        // nearest matches may lie beyond it: scan instead.
+        let candidates = (limit * KNN_OVERFETCH).max(KNN_MIN_CANDIDATES);
+        let filters = format!(
+            "{} {} {} {}",
+            visibility_clause, resonance_clause, category_clause, field_clause
+        );
+        let ranked = |from: &str| {
+            format!(
+                "SELECT {}, vector::similarity::cosine(embedding, $query_vec) AS score
+                FROM {}
+                WHERE embedding IS NOT NONE {}
+                ORDER BY score DESC
+                LIMIT $limit",
+                Self::knowledge_select_fields(),
+                from,
+                filters
+            )
+        };
         let sql = format!(
-            "SELECT {}, vector::similarity::cosine(embedding, $query_vec) AS score
-            FROM knowledge
-            WHERE embedding IS NOT NONE {} {} {}
-            ORDER BY score DESC
-            LIMIT $limit",
-            Self::knowledge_select_fields(),
-            visibility_clause,
-            resonance_clause,
-            category_clause
+            "LET $candidates = (SELECT VALUE id FROM knowledge WHERE embedding <|{},{}|> $query_vec);
+            LET $hits = ({});
+            RETURN IF array::len($hits) < $limit AND array::len($candidates) = {} THEN ({}) ELSE $hits END;",
+            candidates,
+            candidates * 2,
+            ranked("$candidates"),
+            candidates,
+            ranked("knowledge")
         );
 
         let mut response = with_db!(self, db, {
@@ -1450,13 +2130,16 @@ impl SurrealDatabase {
             if let Some(agent) = current_agent {
                 query_builder = query_builder.bind(("current_agent", agent));
             }
+            for param in field_params {
+                query_builder = query_builder.bind(param);
+            }
             query_builder
                 .await
                 .context("Failed to execute semantic search query")
         })?;
 
         let results: Vec<serde_json::Value> = response
-            .take(0)
+            .take(2)
             .context("Failed to parse semantic search results")?;
 
         let mut entries = Vec::new();
@@ -1513,24 +2196,19 @@ impl SurrealDatabase {
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
-        // Fetch tags
+        // Fetch tags and applicability in one round trip, sharing the Thing binding
         let knowledge_thing = Thing::from(("knowledge", id_str));
-        let mut tags_response = with_db!(self, db, {
-            db.query("SELECT VALUE out.name FROM tagged_with WHERE in = $knowledge")
-                .bind(("knowledge", knowledge_thing.clone()))
-                .await
-                .context("Failed to query tags")
-        })?;
-        let tags: Vec<String> = tags_response.take(0).unwrap_or_default();
-
-        // Fetch applicability
-        let mut app_response = with_db!(self, db, {
-            db.query("SELECT VALUE meta::id(out) FROM applies_to WHERE in = $knowledge")
-                .bind(("knowledge", knowledge_thing))
-                .await
-                .context("Failed to query applicability")
+        let mut edges_response = with_db!(self, db, {
+            db.query(
+                "SELECT VALUE out.name FROM tagged_with WHERE in = $knowledge;
+                SELECT VALUE meta::id(out) FROM applies_to WHERE in = $knowledge",
+            )
+            .bind(("knowledge", knowledge_thing))
+            .await
+            .context("Failed to query tags and applicability")
         })?;
-        let applicability_raw: Vec<Thing> = app_response.take(0).unwrap_or_default();
+        let tags: Vec<String> = edges_response.take(0).unwrap_or_default();
+        let applicability_raw: Vec<Thing> = edges_response.take(1).unwrap_or_default();
         let applicability: Vec<String> = applicability_raw
             .into_iter()
             .map(|t| t.id.to_string())
@@ -1573,6 +2251,12 @@ impl SurrealDatabase {
             format: serde_json::from_value(obj["format"].clone())
                 .unwrap_or_else(|_| "markdown".to_string()),
             effective_resonance: obj.get("effective_resonance").and_then(|v| v.as_f64()),
+            token_count: serde_json::from_value(obj["token_count"].clone()).ok(),
+            namespace: serde_json::from_value(obj["namespace"].clone()).ok(),
+            confidence: obj.get("confidence").and_then(|v| v.as_f64()),
+            confirmed_at: serde_json::from_value(obj["confirmed_at"].clone()).ok(),
+            archived_at: serde_json::from_value(obj["archived_at"].clone()).ok(),
+            attachments: serde_json::from_value(obj["attachments"].clone()).unwrap_or_default(),
         })
     }
 
@@ -1598,11 +2282,18 @@ impl SurrealDatabase {
         min_resonance: Option<i32>,
         days: i64,
     ) -> Result<crate::store::WakeCascade> {
+        // Starred entries lead the cascade and don't count against the limit
+        let starred = self.query_starred_blooms(ctx).await?;
+        let starred_ids: std::collections::HashSet<String> =
+            starred.iter().map(|e| e.id.clone()).collect();
+
         // If min_resonance is set, use simple query for all blooms >= threshold
         if let Some(threshold) = min_resonance {
             let blooms = self.query_blooms_by_resonance(ctx, threshold).await?;
+            let mut core = starred;
+            core.extend(blooms.into_iter().filter(|e| !starred_ids.contains(&e.id)));
             return Ok(crate::store::WakeCascade {
-                core: blooms,
+                core,
                 recent: Vec::new(),
                 bridges: Vec::new(),
             });
@@ -1612,9 +2303,24 @@ impl SurrealDatabase {
         // This ensures we get the most important blooms first
 
         // Layer 1: Core foundational/transformative blooms (resonance 8+)
-        // Use full limit for core - we'll subtract what we get
-        let core = self.query_core_blooms(ctx, limit).await?;
+        // Sealed identity blooms come first; other core blooms fill the rest
+        let mut core: Vec<_> = self
+            .query_identity_blooms(ctx, limit)
+            .await?
+            .into_iter()
+            .filter(|e| !starred_ids.contains(&e.id))
+            .collect();
+        let identity_ids: std::collections::HashSet<String> =
+            core.iter().map(|e| e.id.clone()).collect();
+        let other_core = self.query_core_blooms(ctx, limit).await?;
+        core.extend(
+            other_core
+                .into_iter()
+                .filter(|e| !identity_ids.contains(&e.id) && !starred_ids.contains(&e.id))
+                .take(limit.saturating_sub(identity_ids.len())),
+        );
         let remaining = limit.saturating_sub(core.len());
+        core.splice(0..0, starred);
 
         // Layer 2: Recent blooms (last N days)
         // Exclude IDs already in core, use remaining quota
@@ -1673,19 +2379,21 @@ impl SurrealDatabase {
     ) -> Result<Vec<crate::knowledge::KnowledgeEntry>> {
         let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
 
-        let sql = format!(
-            "SELECT {}
-            FROM knowledge
-            WHERE resonance >= $threshold
-            AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
-            {}
-            ORDER BY resonance DESC",
-            Self::knowledge_select_fields(),
-            visibility_clause
-        );
+        let sql = cached_statement("blooms_by_resonance", current_agent.is_some(), || {
+            format!(
+                "SELECT {}
+                FROM knowledge
+                WHERE resonance >= $threshold
+                AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
+                {}
+                ORDER BY resonance DESC",
+                Self::knowledge_select_fields(),
+                visibility_clause
+            )
+        });
 
         let mut response = with_db!(self, db, {
-            let mut query = db.query(&sql).bind(("threshold", threshold));
+            let mut query = db.query(sql).bind(("threshold", threshold));
             if let Some(agent) = current_agent {
                 query = query.bind(("current_agent", agent));
             }
@@ -1709,28 +2417,30 @@ impl SurrealDatabase {
     ) -> Result<Vec<crate::knowledge::KnowledgeEntry>> {
         let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
 
-        let sql = format!(
-            "SELECT *,
-                (wake_order IS NOT NULL) AS has_wake_order,
-                wake_order ?? 999999 AS effective_wake_order
-            FROM (
-                SELECT {}
-                FROM knowledge
-                WHERE resonance >= 8
-                AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
-                {}
+        let sql = cached_statement("wake_core", current_agent.is_some(), || {
+            format!(
+                "SELECT *,
+                    (wake_order IS NOT NULL) AS has_wake_order,
+                    wake_order ?? 999999 AS effective_wake_order
+                FROM (
+                    SELECT {}
+                    FROM knowledge
+                    WHERE resonance >= 8
+                    AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
+                    {}
+                )
+                ORDER BY
+                    has_wake_order DESC,
+                    effective_wake_order ASC,
+                    resonance DESC
+                LIMIT $limit",
+                Self::knowledge_select_fields(),
+                visibility_clause
             )
-            ORDER BY
-                has_wake_order DESC,
-                effective_wake_order ASC,
-                resonance DESC
-            LIMIT $limit",
-            Self::knowledge_select_fields(),
-            visibility_clause
-        );
+        });
 
         let mut response = with_db!(self, db, {
-            let mut query = db.query(&sql).bind(("limit", limit as i64));
+            let mut query = db.query(sql).bind(("limit", limit as i64));
             if let Some(agent) = current_agent {
                 query = query.bind(("current_agent", agent));
             }
@@ -1746,6 +2456,95 @@ impl SurrealDatabase {
         Ok(entries)
     }
 
+    /// Layer 0: the current agent's starred entries, in the order starred
+    async fn query_starred_blooms(
+        &self,
+        ctx: &crate::store::AgentContext,
+    ) -> Result<Vec<crate::knowledge::KnowledgeEntry>> {
+        let Some(ref agent) = ctx.agent_id else {
+            return Ok(Vec::new());
+        };
+        let order = self.list_starred_async(agent).await?;
+        if order.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
+
+        let sql = cached_statement("wake_starred", current_agent.is_some(), || {
+            format!(
+                "SELECT {}
+                FROM knowledge
+                WHERE string::concat('kn-', meta::id(id)) IN $starred_ids
+                {}",
+                Self::knowledge_select_fields(),
+                visibility_clause
+            )
+        });
+
+        let mut response = with_db!(self, db, {
+            let mut query = db.query(sql).bind(("starred_ids", order.clone()));
+            if let Some(agent) = current_agent {
+                query = query.bind(("current_agent", agent));
+            }
+            query.await.context("Failed to query starred entries")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        let mut entries = Vec::new();
+        for obj in results {
+            entries.push(self.value_to_knowledge_entry(obj).await?);
+        }
+        entries.sort_by_key(|e| order.iter().position(|id| *id == e.id));
+
+        Ok(entries)
+    }
+
+    /// Layer 1a: Query blooms sealed in the identity namespace
+    async fn query_identity_blooms(
+        &self,
+        ctx: &crate::store::AgentContext,
+        limit: usize,
+    ) -> Result<Vec<crate::knowledge::KnowledgeEntry>> {
+        let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
+
+        let sql = cached_statement("wake_identity", current_agent.is_some(), || {
+            format!(
+                "SELECT *,
+                    (wake_order IS NOT NULL) AS has_wake_order,
+                    wake_order ?? 999999 AS effective_wake_order
+                FROM (
+                    SELECT {}
+                    FROM knowledge
+                    WHERE namespace = 'identity'
+                    {}
+                )
+                ORDER BY
+                    has_wake_order DESC,
+                    effective_wake_order ASC,
+                    resonance DESC
+                LIMIT $limit",
+                Self::knowledge_select_fields(),
+                visibility_clause
+            )
+        });
+
+        let mut response = with_db!(self, db, {
+            let mut query = db.query(sql).bind(("limit", limit as i64));
+            if let Some(agent) = current_agent {
+                query = query.bind(("current_agent", agent));
+            }
+            query.await.context("Failed to query identity blooms")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        let mut entries = Vec::new();
+        for obj in results {
+            entries.push(self.value_to_knowledge_entry(obj).await?);
+        }
+
+        Ok(entries)
+    }
+
     /// Layer 2: Query recent blooms (last N days, sorted by resonance)
     async fn query_recent_blooms(
         &self,
@@ -1759,29 +2558,31 @@ impl SurrealDatabase {
         let cutoff = chrono::Utc::now() - chrono::Duration::days(days);
         let cutoff_str = cutoff.to_rfc3339();
 
-        let sql = format!(
-            "SELECT *,
-                (wake_order IS NOT NULL) AS has_wake_order,
-                wake_order ?? 999999 AS effective_wake_order
-            FROM (
-                SELECT {}
-                FROM knowledge
-                WHERE last_activated > <datetime>$cutoff
-                AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
-                {}
+        let sql = cached_statement("wake_recent", current_agent.is_some(), || {
+            format!(
+                "SELECT *,
+                    (wake_order IS NOT NULL) AS has_wake_order,
+                    wake_order ?? 999999 AS effective_wake_order
+                FROM (
+                    SELECT {}
+                    FROM knowledge
+                    WHERE last_activated > <datetime>$cutoff
+                    AND (resonance_type IS NONE OR resonance_type != 'ephemeral')
+                    {}
+                )
+                ORDER BY
+                    has_wake_order DESC,
+                    effective_wake_order ASC,
+                    resonance DESC
+                LIMIT $limit",
+                Self::knowledge_select_fields(),
+                visibility_clause
             )
-            ORDER BY
-                has_wake_order DESC,
-                effective_wake_order ASC,
-                resonance DESC
-            LIMIT $limit",
-            Self::knowledge_select_fields(),
-            visibility_clause
-        );
+        });
 
         let mut response = with_db!(self, db, {
             let mut query = db
-                .query(&sql)
+                .query(sql)
                 .bind(("cutoff", cutoff_str))
                 .bind(("limit", limit as i64));
             if let Some(agent) = current_agent {
@@ -1810,29 +2611,31 @@ impl SurrealDatabase {
 
         // Use array::intersect to check if anchors array has any overlap with anchor_ids
         // If intersection is non-empty, this bloom is anchored to a core/recent bloom
-        let sql = format!(
-            "SELECT *,
-                (wake_order IS NOT NULL) AS has_wake_order,
-                wake_order ?? 999999 AS effective_wake_order
-            FROM (
-                SELECT {}
-                FROM knowledge
-                WHERE array::len(array::intersect(anchors, $anchor_ids)) > 0
-                AND resonance >= 5
-                {}
+        let sql = cached_statement("wake_bridges", current_agent.is_some(), || {
+            format!(
+                "SELECT *,
+                    (wake_order IS NOT NULL) AS has_wake_order,
+                    wake_order ?? 999999 AS effective_wake_order
+                FROM (
+                    SELECT {}
+                    FROM knowledge
+                    WHERE array::len(array::intersect(anchors, $anchor_ids)) > 0
+                    AND resonance >= 5
+                    {}
+                )
+                ORDER BY
+                    has_wake_order DESC,
+                    effective_wake_order ASC,
+                    resonance DESC
+                LIMIT $limit",
+                Self::knowledge_select_fields(),
+                visibility_clause
             )
-            ORDER BY
-                has_wake_order DESC,
-                effective_wake_order ASC,
-                resonance DESC
-            LIMIT $limit",
-            Self::knowledge_select_fields(),
-            visibility_clause
-        );
+        });
 
         let mut response = with_db!(self, db, {
             let mut query = db
-                .query(&sql)
+                .query(sql)
                 .bind(("anchor_ids", anchor_ids.to_vec()))
                 .bind(("limit", limit as i64));
             if let Some(agent) = current_agent {
@@ -1896,6 +2699,40 @@ impl SurrealDatabase {
         Ok(())
     }
 
+    /// Seal an entry into the identity namespace, or release it.
+    /// Releasing sets `$identity_unlock` so the identity_guard event lets this
+    /// one statement through.
+    pub fn set_identity(&self, id: &str, sealed: bool) -> Result<bool> {
+        Self::runtime().block_on(self.set_identity_async(id, sealed))
+    }
+
+    async fn set_identity_async(&self, id: &str, sealed: bool) -> Result<bool> {
+        let id_part = id.strip_prefix("kn-").unwrap_or(id);
+        let sql = if sealed {
+            "UPDATE type::thing('knowledge', $id) SET namespace = 'identity'
+             RETURN VALUE meta::id(id)"
+        } else {
+            "LET $identity_unlock = true;
+             UPDATE type::thing('knowledge', $id) SET namespace = NONE
+             RETURN VALUE meta::id(id)"
+        };
+
+        let mut response = with_db!(self, db, {
+            db.query(sql)
+                .bind(("id", id_part.to_string()))
+                .await
+                .context("Failed to update identity namespace")
+        })?;
+
+        let errors = response.take_errors();
+        if let Some(error) = errors.into_values().next() {
+            return Err(anyhow::anyhow!("{}", error));
+        }
+
+        let updated: Vec<String> = response.take(if sealed { 0 } else { 1 })?;
+        Ok(!updated.is_empty())
+    }
+
     /// Update only the summary field of a knowledge entry.
     /// Respects visibility: agents can only update summaries on entries they can see.
     /// Returns Ok(false) for entries that don't exist OR that the agent can't see
@@ -2038,6 +2875,7 @@ impl SurrealDatabase {
                  ({expr}) AS effective_resonance
              FROM knowledge
              WHERE resonance_type = 'ephemeral'
+             AND archived_at IS NONE
              AND created_at > time::now() - duration::from::days($days)
              AND ({expr}) > 0.5
              ORDER BY effective_resonance DESC",
@@ -2046,7 +2884,7 @@ impl SurrealDatabase {
         );
 
         let mut response = with_db!(self, db, {
-            db.query(&sql)
+            db.query(sql)
                 .bind(("days", days))
                 .await
                 .context("Failed to execute recent facts query")
@@ -2080,6 +2918,7 @@ impl SurrealDatabase {
                  ({expr}) AS effective_resonance
              FROM knowledge
              WHERE created_at > time::now() - duration::from::days($days)
+             AND archived_at IS NONE
              AND ({expr}) > 0.5
              ORDER BY effective_resonance DESC",
             Self::knowledge_select_fields(),
@@ -2087,7 +2926,7 @@ impl SurrealDatabase {
         );
 
         let mut response = with_db!(self, db, {
-            db.query(&sql)
+            db.query(sql)
                 .bind(("days", days))
                 .await
                 .context("Failed to execute recent facts (all types) query")
@@ -2231,6 +3070,485 @@ impl SurrealDatabase {
         }))
     }
 
+    // =========================================================================
+    // COMMAND LOG OPERATIONS
+    // =========================================================================
+
+    /// Record a completed CLI command in the local usage log
+    pub fn record_command(&self, entry: &crate::types::CommandLogEntry) -> Result<()> {
+        Self::runtime().block_on(self.record_command_async(entry))
+    }
+
+    async fn record_command_async(&self, entry: &crate::types::CommandLogEntry) -> Result<()> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "CREATE command_log SET
+                    command = $command,
+                    duration_ms = $duration_ms,
+                    success = $success,
+                    error = $error,
+                    created_at = time::now()
+                ",
+            )
+            .bind(("command", entry.command.clone()))
+            .bind(("duration_ms", entry.duration_ms))
+            .bind(("success", entry.success))
+            .bind(("error", entry.error.clone()))
+            .await
+            .context("Failed to record command")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to record command: {:?}", errors));
+        }
+
+        Ok(())
+    }
+
+    /// List logged commands, newest first
+    pub fn list_command_log(
+        &self,
+        limit: Option<usize>,
+        since_days: Option<i64>,
+        failed_only: bool,
+    ) -> Result<Vec<crate::types::CommandLogEntry>> {
+        Self::runtime().block_on(self.list_command_log_async(limit, since_days, failed_only))
+    }
+
+    async fn list_command_log_async(
+        &self,
+        limit: Option<usize>,
+        since_days: Option<i64>,
+        failed_only: bool,
+    ) -> Result<Vec<crate::types::CommandLogEntry>> {
+        let mut conditions = Vec::new();
+        if since_days.is_some() {
+            conditions.push("created_at > time::now() - duration::from::days($days)");
+        }
+        if failed_only {
+            conditions.push("success = false");
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let limit_clause = if limit.is_some() { "LIMIT $limit" } else { "" };
+        let sql = format!(
+            "SELECT meta::id(id) AS id, command, duration_ms, success, error,
+                    <string>created_at AS created_at
+             FROM command_log
+             {}
+             ORDER BY created_at DESC
+             {}",
+            where_clause, limit_clause
+        );
+
+        let mut response = with_db!(self, db, {
+            let mut query = db.query(&sql);
+            if let Some(days) = since_days {
+                query = query.bind(("days", days));
+            }
+            if let Some(n) = limit {
+                query = query.bind(("limit", n as i64));
+            }
+            query.await.context("Failed to list command log")
+        })?;
+
+        let entries: Vec<crate::types::CommandLogEntry> = response.take(0)?;
+        Ok(entries)
+    }
+
+    // =========================================================================
+    // REVIEW CARD OPERATIONS
+    // =========================================================================
+
+    /// List an agent's review cards, soonest due first
+    pub fn list_review_cards(&self, agent: &str) -> Result<Vec<crate::types::ReviewCard>> {
+        Self::runtime().block_on(self.list_review_cards_async(agent))
+    }
+
+    async fn list_review_cards_async(&self, agent: &str) -> Result<Vec<crate::types::ReviewCard>> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "SELECT entry_id, agent, ease, interval_days, repetitions,
+                        <string>next_review AS next_review,
+                        IF last_reviewed != NONE THEN <string>last_reviewed END AS last_reviewed
+                 FROM review_card
+                 WHERE agent = $agent
+                 ORDER BY next_review",
+            )
+            .bind(("agent", agent.to_string()))
+            .await
+            .context("Failed to list review cards")
+        })?;
+
+        let cards: Vec<crate::types::ReviewCard> = response.take(0)?;
+        Ok(cards)
+    }
+
+    /// Insert or replace the review card for (agent, entry)
+    pub fn upsert_review_card(&self, card: &crate::types::ReviewCard) -> Result<()> {
+        Self::runtime().block_on(self.upsert_review_card_async(card))
+    }
+
+    async fn upsert_review_card_async(&self, card: &crate::types::ReviewCard) -> Result<()> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "UPSERT type::thing('review_card', [$agent, $entry_id]) SET
+                    entry_id = $entry_id,
+                    agent = $agent,
+                    ease = $ease,
+                    interval_days = $interval_days,
+                    repetitions = $repetitions,
+                    next_review = <datetime>$next_review,
+                    last_reviewed = IF $last_reviewed != NONE THEN <datetime>$last_reviewed END
+                ",
+            )
+            .bind(("entry_id", card.entry_id.clone()))
+            .bind(("agent", card.agent.clone()))
+            .bind(("ease", card.ease))
+            .bind(("interval_days", card.interval_days))
+            .bind(("repetitions", card.repetitions))
+            .bind(("next_review", normalize_datetime(&card.next_review)))
+            .bind((
+                "last_reviewed",
+                card.last_reviewed.as_deref().map(normalize_datetime),
+            ))
+            .await
+            .context("Failed to save review card")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "SurrealDB error saving review card: {:?}",
+                errors
+            ));
+        }
+
+        Ok(())
+    }
+
+    // =========================================================================
+    // ENTRY VIEW OPERATIONS
+    // =========================================================================
+
+    /// Bump the view count for (agent, entry) and stamp it now
+    pub fn record_view(&self, agent: &str, entry_id: &str) -> Result<()> {
+        Self::runtime().block_on(self.record_view_async(agent, entry_id))
+    }
+
+    async fn record_view_async(&self, agent: &str, entry_id: &str) -> Result<()> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "UPSERT type::thing('entry_view', [$agent, $entry_id]) SET
+                    entry_id = $entry_id,
+                    agent = $agent,
+                    views += 1,
+                    viewed_at = time::now()
+                ",
+            )
+            .bind(("agent", agent.to_string()))
+            .bind(("entry_id", entry_id.to_string()))
+            .await
+            .context("Failed to record view")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to record view: {:?}", errors));
+        }
+
+        Ok(())
+    }
+
+    /// List an agent's viewed entries, most recent first
+    pub fn list_views(&self, agent: &str) -> Result<Vec<crate::types::EntryView>> {
+        Self::runtime().block_on(self.list_views_async(agent))
+    }
+
+    async fn list_views_async(&self, agent: &str) -> Result<Vec<crate::types::EntryView>> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "SELECT entry_id, agent, views, <string>viewed_at AS viewed_at
+                 FROM entry_view
+                 WHERE agent = $agent
+                 ORDER BY viewed_at DESC",
+            )
+            .bind(("agent", agent.to_string()))
+            .await
+            .context("Failed to list views")
+        })?;
+
+        let views: Vec<crate::types::EntryView> = response.take(0)?;
+        Ok(views)
+    }
+
+    // =========================================================================
+    // STARRED OPERATIONS
+    // =========================================================================
+
+    /// Star an entry for an agent; false if it was already starred
+    pub fn star(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        Self::runtime().block_on(self.star_async(agent, entry_id))
+    }
+
+    async fn star_async(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "IF record::exists(type::thing('starred', [$agent, $entry_id])) {
+                    RETURN false;
+                } ELSE {
+                    CREATE type::thing('starred', [$agent, $entry_id]) SET
+                        entry_id = $entry_id,
+                        agent = $agent,
+                        starred_at = time::now();
+                    RETURN true;
+                }",
+            )
+            .bind(("agent", agent.to_string()))
+            .bind(("entry_id", entry_id.to_string()))
+            .await
+            .context("Failed to star entry")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to star entry: {:?}", errors));
+        }
+        let created: Option<bool> = response.take(0)?;
+        Ok(created.unwrap_or(false))
+    }
+
+    /// Unstar an entry for an agent; false if it wasn't starred
+    pub fn unstar(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        Self::runtime().block_on(self.unstar_async(agent, entry_id))
+    }
+
+    async fn unstar_async(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        let mut response = with_db!(self, db, {
+            db.query("DELETE type::thing('starred', [$agent, $entry_id]) RETURN BEFORE")
+                .bind(("agent", agent.to_string()))
+                .bind(("entry_id", entry_id.to_string()))
+                .await
+                .context("Failed to unstar entry")
+        })?;
+
+        let removed: Option<String> = response.take((0, "entry_id"))?;
+        Ok(removed.is_some())
+    }
+
+    /// IDs an agent starred, oldest star first
+    pub fn list_starred(&self, agent: &str) -> Result<Vec<String>> {
+        Self::runtime().block_on(self.list_starred_async(agent))
+    }
+
+    async fn list_starred_async(&self, agent: &str) -> Result<Vec<String>> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "SELECT entry_id, starred_at FROM starred
+                 WHERE agent = $agent
+                 ORDER BY starred_at",
+            )
+            .bind(("agent", agent.to_string()))
+            .await
+            .context("Failed to list starred entries")
+        })?;
+
+        #[derive(Deserialize)]
+        struct Star {
+            entry_id: String,
+        }
+        let stars: Vec<Star> = response.take(0)?;
+        Ok(stars.into_iter().map(|s| s.entry_id).collect())
+    }
+
+    // =========================================================================
+    // QUEUE OPERATIONS
+    // =========================================================================
+
+    /// Add an item to the reading queue
+    pub fn add_queue_item(&self, item: &crate::types::QueueItem) -> Result<()> {
+        Self::runtime().block_on(self.add_queue_item_async(item))
+    }
+
+    async fn add_queue_item_async(&self, item: &crate::types::QueueItem) -> Result<()> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "CREATE type::thing('queue_item', $id) SET
+                    target = $target,
+                    kind = $kind,
+                    title = $title,
+                    note = $note,
+                    priority = $priority,
+                    added_at = time::now()
+                ",
+            )
+            .bind(("id", item.id.clone()))
+            .bind(("target", item.target.clone()))
+            .bind(("kind", item.kind.clone()))
+            .bind(("title", item.title.clone()))
+            .bind(("note", item.note.clone()))
+            .bind(("priority", item.priority))
+            .await
+            .context("Failed to add queue item")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "SurrealDB error adding queue item: {:?}",
+                errors
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// List queue items, highest priority then oldest first
+    pub fn list_queue(&self, include_done: bool) -> Result<Vec<crate::types::QueueItem>> {
+        Self::runtime().block_on(self.list_queue_async(include_done))
+    }
+
+    async fn list_queue_async(&self, include_done: bool) -> Result<Vec<crate::types::QueueItem>> {
+        let where_clause = if include_done {
+            ""
+        } else {
+            "WHERE done_at = NONE"
+        };
+        let sql = format!(
+            "SELECT meta::id(id) AS id, target, kind, title, note, priority,
+                    <string>added_at AS added_at,
+                    IF done_at != NONE THEN <string>done_at END AS done_at
+             FROM queue_item
+             {}
+             ORDER BY priority DESC, added_at ASC",
+            where_clause
+        );
+
+        let mut response = with_db!(self, db, {
+            db.query(&sql).await.context("Failed to list queue")
+        })?;
+
+        let items: Vec<crate::types::QueueItem> = response.take(0)?;
+        Ok(items)
+    }
+
+    /// Changes after cursor `since`, oldest first, as far as `ctx` may see
+    pub fn list_changes(
+        &self,
+        since: i64,
+        limit: usize,
+        ctx: &crate::store::AgentContext,
+    ) -> Result<Vec<crate::types::Change>> {
+        Self::runtime().block_on(self.list_changes_async(since, limit, ctx))
+    }
+
+    async fn list_changes_async(
+        &self,
+        since: i64,
+        limit: usize,
+        ctx: &crate::store::AgentContext,
+    ) -> Result<Vec<crate::types::Change>> {
+        let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
+        let sql = format!(
+            "SELECT seq, kind, entry_id, target_id, relationship_type, <string>at AS at
+             FROM change
+             WHERE seq > $since {}
+             ORDER BY seq ASC
+             LIMIT $limit",
+            visibility_clause
+        );
+
+        let mut response = with_db!(self, db, {
+            let mut query = db
+                .query(&sql)
+                .bind(("since", since))
+                .bind(("limit", limit as i64));
+            if let Some(agent) = current_agent {
+                query = query.bind(("current_agent", agent));
+            }
+            query.await.context("Failed to read change feed")
+        })?;
+
+        let changes: Vec<crate::types::Change> = response.take(0)?;
+        Ok(changes)
+    }
+
+    /// Subscribe to the change feed of the current namespace with LIVE SELECT.
+    ///
+    /// Changes visible to `ctx` arrive on the returned channel as they are
+    /// written. In network mode that includes writes from every client of the
+    /// server; an embedded store only sees this process's own writes. The
+    /// live query is killed once the receiver is dropped and the next change
+    /// fails to send.
+    pub fn watch_changes(
+        &self,
+        ctx: crate::store::AgentContext,
+    ) -> Result<std::sync::mpsc::Receiver<crate::types::Change>> {
+        use futures::StreamExt;
+        use surrealdb::{Action, Notification};
+
+        #[derive(Deserialize)]
+        struct LiveChange {
+            #[serde(flatten)]
+            change: crate::types::Change,
+            visibility: String,
+            owner: Option<String>,
+        }
+
+        let sql = "LIVE SELECT seq, kind, entry_id, target_id, relationship_type, \
+                   visibility, owner, <string>at AS at FROM change";
+        let mut stream = Self::runtime().block_on(async {
+            with_db!(self, db, {
+                db.query(sql)
+                    .await
+                    .context("Failed to start live query")?
+                    .stream::<Notification<LiveChange>>(0)
+                    .context("Failed to open live stream")
+            })
+        })?;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        Self::runtime().spawn(async move {
+            while let Some(Ok(notification)) = stream.next().await {
+                let live = notification.data;
+                if notification.action != Action::Create
+                    || !ctx.can_see(&live.visibility, live.owner.as_deref())
+                {
+                    continue;
+                }
+                if tx.send(live.change).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    /// Mark an open queue item done
+    pub fn complete_queue_item(&self, id: &str) -> Result<bool> {
+        Self::runtime().block_on(self.complete_queue_item_async(id))
+    }
+
+    async fn complete_queue_item_async(&self, id: &str) -> Result<bool> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "UPDATE type::thing('queue_item', $id) SET done_at = time::now()
+                 WHERE done_at = NONE
+                 RETURN VALUE meta::id(id)",
+            )
+            .bind(("id", id.to_string()))
+            .await
+            .context("Failed to complete queue item")
+        })?;
+
+        let updated: Vec<String> = response.take(0)?;
+        Ok(!updated.is_empty())
+    }
+
     // =========================================================================
     // LOOKUP OPERATIONS
     // =========================================================================
@@ -2391,6 +3709,124 @@ impl SurrealDatabase {
         Ok(tags)
     }
 
+    /// Tags in use with their entry counts, most used first
+    pub fn tag_counts(&self, category: Option<&str>) -> Result<Vec<crate::types::TagCount>> {
+        Self::runtime().block_on(self.tag_counts_async(category.map(str::to_string)))
+    }
+
+    async fn tag_counts_async(
+        &self,
+        category: Option<String>,
+    ) -> Result<Vec<crate::types::TagCount>> {
+        // Same reverse traversal as list_all_tags: edge-table predicates on
+        // `in.category` match nothing in SurrealDB 2.x
+        let sql = if category.is_some() {
+            "SELECT * FROM (
+                SELECT name, array::len(<-tagged_with<-knowledge[WHERE category = type::thing('category', $cat)]) AS entries
+                FROM tag
+             ) WHERE entries > 0
+             ORDER BY entries DESC, name"
+        } else {
+            "SELECT * FROM (
+                SELECT name, array::len(<-tagged_with) AS entries FROM tag
+             ) WHERE entries > 0
+             ORDER BY entries DESC, name"
+        };
+        let mut response = with_db!(self, db, {
+            db.query(sql)
+                .bind(("cat", category))
+                .await
+                .context("Failed to count tags")
+        })?;
+
+        let counts: Vec<crate::types::TagCount> = response.take(0)?;
+        Ok(counts)
+    }
+
+    /// Retag everything carrying `sources` with `target` and drop the sources,
+    /// in one transaction. Returns how many entries were retagged.
+    pub fn merge_tags(&self, sources: &[String], target: &str) -> Result<usize> {
+        Self::runtime().block_on(self.merge_tags_async(sources, target))
+    }
+
+    async fn merge_tags_async(&self, sources: &[String], target: &str) -> Result<usize> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "BEGIN TRANSACTION;
+                LET $target = type::thing('tag', $target_name);
+                LET $sources = array::filter(
+                    array::map($source_names, |$n| type::thing('tag', $n)),
+                    |$t| $t != $target
+                );
+                LET $entries = array::distinct((SELECT VALUE in FROM tagged_with WHERE out IN $sources));
+                LET $projects = array::distinct((SELECT VALUE in FROM project_tagged_with WHERE out IN $sources));
+                UPSERT $target SET name = $target_name;
+                FOR $e IN $entries {
+                    IF !(SELECT VALUE id FROM tagged_with WHERE in = $e AND out = $target) {
+                        RELATE $e->tagged_with->$target;
+                    };
+                };
+                FOR $p IN $projects {
+                    IF !(SELECT VALUE id FROM project_tagged_with WHERE in = $p AND out = $target) {
+                        RELATE $p->project_tagged_with->$target;
+                    };
+                };
+                DELETE tagged_with WHERE out IN $sources;
+                DELETE project_tagged_with WHERE out IN $sources;
+                FOR $t IN $sources { DELETE $t; };
+                RETURN array::len($entries);
+                COMMIT TRANSACTION;",
+            )
+            .bind(("source_names", sources.to_vec()))
+            .bind(("target_name", target.to_string()))
+            .await
+            .context("Failed to merge tags")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to merge tags: {:?}", errors));
+        }
+        let last = response.num_statements() - 1;
+        let retagged: Option<usize> = response.take(last)?;
+        Ok(retagged.unwrap_or(0))
+    }
+
+    /// Untag every entry carrying one of `names`; tags no project uses are
+    /// deleted outright. Returns how many entries lost a tag.
+    pub fn delete_tags(&self, names: &[String]) -> Result<usize> {
+        Self::runtime().block_on(self.delete_tags_async(names))
+    }
+
+    async fn delete_tags_async(&self, names: &[String]) -> Result<usize> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "BEGIN TRANSACTION;
+                LET $tags = array::map($names, |$n| type::thing('tag', $n));
+                LET $entries = array::distinct((SELECT VALUE in FROM tagged_with WHERE out IN $tags));
+                DELETE tagged_with WHERE out IN $tags;
+                FOR $t IN $tags {
+                    IF !(SELECT VALUE id FROM project_tagged_with WHERE out = $t) {
+                        DELETE $t;
+                    };
+                };
+                RETURN array::len($entries);
+                COMMIT TRANSACTION;",
+            )
+            .bind(("names", names.to_vec()))
+            .await
+            .context("Failed to delete tags")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to delete tags: {:?}", errors));
+        }
+        let last = response.num_statements() - 1;
+        let untagged: Option<usize> = response.take(last)?;
+        Ok(untagged.unwrap_or(0))
+    }
+
     /// List all applicability types
     pub fn list_applicability_types(&self) -> Result<Vec<ApplicabilityType>> {
         Self::runtime().block_on(self.list_applicability_types_async())
@@ -2516,7 +3952,8 @@ impl SurrealDatabase {
         // Query both outgoing and incoming relationships
         let mut response = with_db!(self, db, {
             db.query(
-                "SELECT id, in AS from_entry_id, out AS to_entry_id, relationship_type, <string>created_at AS created_at
+                "SELECT meta::id(id) AS id, meta::id(in) AS from_entry_id, meta::id(out) AS to_entry_id,
+                    meta::id(relationship_type) AS relationship_type, <string>created_at AS created_at
                  FROM relates_to
                  WHERE in = $entry OR out = $entry
                  ORDER BY created_at DESC"
@@ -2527,20 +3964,44 @@ impl SurrealDatabase {
         })?;
 
         let results: Vec<serde_json::Value> = response.take(0)?;
+        Self::values_to_relationships(results)
+    }
+
+    /// List every relationship edge in the store
+    pub fn list_all_relationships(&self) -> Result<Vec<Relationship>> {
+        Self::runtime().block_on(self.list_all_relationships_async())
+    }
+
+    async fn list_all_relationships_async(&self) -> Result<Vec<Relationship>> {
+        let mut response = with_db!(self, db, {
+            db.query(
+                "SELECT meta::id(id) AS id, meta::id(in) AS from_entry_id, meta::id(out) AS to_entry_id,
+                    meta::id(relationship_type) AS relationship_type, <string>created_at AS created_at
+                 FROM relates_to
+                 ORDER BY created_at ASC"
+            )
+            .await
+            .context("Failed to query relationships")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        Self::values_to_relationships(results)
+    }
+
+    /// Convert relates_to rows into Relationship structs
+    fn values_to_relationships(results: Vec<serde_json::Value>) -> Result<Vec<Relationship>> {
         let mut relationships = Vec::new();
 
+        // Queries return record links as plain ids via meta::id()
+        let field =
+            |obj: &serde_json::Value, key: &str| obj[key].as_str().unwrap_or_default().to_string();
         for obj in results {
-            let from_thing: Thing = serde_json::from_value(obj["from_entry_id"].clone())?;
-            let to_thing: Thing = serde_json::from_value(obj["to_entry_id"].clone())?;
-            let rel_type_thing: Thing = serde_json::from_value(obj["relationship_type"].clone())?;
-            let id_thing: Thing = serde_json::from_value(obj["id"].clone())?;
-
             relationships.push(Relationship {
-                id: id_thing.id.to_string(),
-                from_entry_id: format!("kn-{}", from_thing.id),
-                to_entry_id: format!("kn-{}", to_thing.id),
-                relationship_type: rel_type_thing.id.to_string(),
-                created_at: obj["created_at"].as_str().unwrap_or_default().to_string(),
+                id: field(&obj, "id"),
+                from_entry_id: format!("kn-{}", field(&obj, "from_entry_id")),
+                to_entry_id: format!("kn-{}", field(&obj, "to_entry_id")),
+                relationship_type: field(&obj, "relationship_type"),
+                created_at: field(&obj, "created_at"),
             });
         }
 
@@ -2569,7 +4030,7 @@ impl SurrealDatabase {
             db.query(
                 "DELETE relates_to
                  WHERE in = $from AND out = $to AND relationship_type = $rel_type
-                 RETURN BEFORE",
+                 RETURN VALUE meta::id($before.id)",
             )
             .bind(("from", from_thing))
             .bind(("to", to_thing))
@@ -2578,7 +4039,7 @@ impl SurrealDatabase {
             .context("Failed to delete relationship")
         })?;
 
-        let deleted: Vec<Value> = response.take(0)?;
+        let deleted: Vec<String> = response.take(0)?;
         Ok(!deleted.is_empty())
     }
 
@@ -2985,7 +4446,7 @@ impl SurrealDatabase {
         // Fetch entry
         let entry = self
             .get_knowledge(id, ctx)?
-            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+            .ok_or_else(|| MxError::not_found("Entry", id))?;
 
         let body = entry
             .body
@@ -3019,7 +4480,7 @@ impl SurrealDatabase {
     ) -> Result<()> {
         let entry = self
             .get_knowledge(id, ctx)?
-            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+            .ok_or_else(|| MxError::not_found("Entry", id))?;
 
         // Use shared content operation logic
         let new_body = crate::content_ops::append_content(entry.body.as_deref(), content);
@@ -3043,7 +4504,7 @@ impl SurrealDatabase {
     ) -> Result<()> {
         let entry = self
             .get_knowledge(id, ctx)?
-            .ok_or_else(|| anyhow::anyhow!("Entry not found: {}", id))?;
+            .ok_or_else(|| MxError::not_found("Entry", id))?;
 
         // Use shared content operation logic
         let new_body = crate::content_ops::prepend_content(entry.body.as_deref(), content);
@@ -3086,14 +4547,14 @@ impl SurrealDatabase {
         Ok(tables)
     }
 
-    /// Count total knowledge entries
+    /// Count knowledge entries, leaving out the trash
     pub fn count(&self) -> Result<usize> {
         Self::runtime().block_on(self.count_async())
     }
 
     async fn count_async(&self) -> Result<usize> {
         let mut response = with_db!(self, db, {
-            db.query("SELECT count() AS c FROM knowledge GROUP ALL")
+            db.query("SELECT count() AS c FROM knowledge WHERE archived_at IS NONE GROUP ALL")
                 .await
                 .context("Failed to count knowledge entries")
         })?;
@@ -3148,6 +4609,95 @@ impl SurrealDatabase {
         Ok(entries)
     }
 
+    /// Every entry updated at or after `since`, whatever its visibility,
+    /// oldest first. Replication only; nothing user-facing may call this.
+    pub fn list_changed_since(&self, since: Option<&str>) -> Result<Vec<KnowledgeEntry>> {
+        Self::runtime().block_on(self.list_changed_since_async(since))
+    }
+
+    async fn list_changed_since_async(&self, since: Option<&str>) -> Result<Vec<KnowledgeEntry>> {
+        // ORDER BY id for the same reason as list_all (coryzibell/mx#191);
+        // the updated_at ordering happens below
+        let sql = format!(
+            "SELECT {}
+            FROM knowledge
+            {}
+            ORDER BY id",
+            Self::knowledge_select_fields(),
+            if since.is_some() {
+                "WHERE updated_at >= <datetime>$since"
+            } else {
+                ""
+            }
+        );
+
+        let mut response = with_db!(self, db, {
+            let mut query = db.query(&sql);
+            if let Some(since) = since {
+                query = query.bind(("since", normalize_datetime(since)));
+            }
+            query
+                .await
+                .context("Failed to query changed knowledge entries")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        let mut entries = Vec::new();
+        for obj in results {
+            entries.push(self.value_to_knowledge_entry(obj).await?);
+        }
+        entries.sort_by(|a, b| a.updated_at.cmp(&b.updated_at));
+        Ok(entries)
+    }
+
+    /// Ids of every entry, whatever its visibility
+    pub fn list_entry_ids(&self) -> Result<Vec<String>> {
+        Self::runtime().block_on(async {
+            let mut response = with_db!(self, db, {
+                db.query("SELECT VALUE meta::id(id) FROM knowledge")
+                    .await
+                    .context("Failed to list knowledge ids")
+            })?;
+            let ids: Vec<String> = response.take(0)?;
+            Ok(ids.into_iter().map(|id| format!("kn-{}", id)).collect())
+        })
+    }
+
+    /// Delete entries without a visibility check (replication targets)
+    pub fn purge_entries(&self, ids: &[String]) -> Result<usize> {
+        let ids: Vec<String> = ids
+            .iter()
+            .map(|id| id.strip_prefix("kn-").unwrap_or(id).to_string())
+            .collect();
+        Self::runtime().block_on(async {
+            let mut response = with_db!(self, db, {
+                db.query(Self::delete_entries_sql("meta::id(id) IN $ids"))
+                    .bind(("ids", ids))
+                    .await
+                    .context("Failed to purge knowledge entries")
+            })?;
+            let errors = response.take_errors();
+            if let Some(error) = errors.into_values().next() {
+                return Err(anyhow::anyhow!("{}", error));
+            }
+            let last = response.num_statements() - 1;
+            let deleted: Vec<String> = response.take(last)?;
+            Ok(deleted.len())
+        })
+    }
+
+    /// Let this session write sealed identity entries, as a standby must:
+    /// the primary's guard already ran when the change was made there
+    pub fn unlock_identity(&self) -> Result<()> {
+        Self::runtime().block_on(async {
+            with_db!(self, db, {
+                db.set("identity_unlock", true)
+                    .await
+                    .context("Failed to set session parameter")
+            })
+        })
+    }
+
     /// List entries by category
     pub fn list_by_category(
         &self,
@@ -3168,16 +4718,18 @@ impl SurrealDatabase {
 
         let (visibility_clause, current_agent) = Self::build_visibility_filter(ctx);
         let resonance_clause = Self::build_resonance_filter(filter);
+        let (field_clause, field_params) = Self::build_field_filter(filter);
 
         // ORDER BY id instead of title — see comment in list_all_async
         let sql = format!(
             "SELECT {}
             FROM knowledge
-            WHERE category = $category {} {}
+            WHERE category = $category {} {} {}
             ORDER BY id",
             Self::knowledge_select_fields(),
             visibility_clause,
-            resonance_clause
+            resonance_clause,
+            field_clause
         );
 
         let mut response = with_db!(self, db, {
@@ -3185,6 +4737,9 @@ impl SurrealDatabase {
             if let Some(agent) = current_agent {
                 query = query.bind(("current_agent", agent));
             }
+            for param in field_params {
+                query = query.bind(param);
+            }
             query.await.context("Failed to query knowledge by category")
         })?;
 
@@ -3198,21 +4753,123 @@ impl SurrealDatabase {
         Ok(entries)
     }
 
-    /// List sessions
-    pub fn list_sessions(&self, _project_id: Option<&str>) -> Result<Vec<Session>> {
-        // Not fully implemented yet - return empty
-        Ok(vec![])
+    /// Session SELECT fields, shared by list and get
+    const SESSION_FIELDS: &'static str = "meta::id(id) AS id,
+        meta::id(session_type) AS session_type_id,
+        IF project THEN meta::id(project) ELSE null END AS project_id,
+        <string>started_at AS started_at,
+        IF ended_at THEN <string>ended_at ELSE null END AS ended_at,
+        metadata";
+
+    fn value_to_session(obj: &serde_json::Value) -> Session {
+        Session {
+            id: obj["id"].as_str().unwrap_or_default().to_string(),
+            session_type_id: obj["session_type_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            project_id: obj["project_id"].as_str().map(|s| s.to_string()),
+            started_at: obj["started_at"].as_str().unwrap_or_default().to_string(),
+            ended_at: obj["ended_at"].as_str().map(|s| s.to_string()),
+            metadata: obj
+                .get("metadata")
+                .filter(|m| m.is_object())
+                .map(|m| m.to_string()),
+        }
+    }
+
+    /// List sessions, newest first
+    pub fn list_sessions(&self, project_id: Option<&str>) -> Result<Vec<Session>> {
+        Self::runtime().block_on(self.list_sessions_async(project_id))
+    }
+
+    async fn list_sessions_async(&self, project_id: Option<&str>) -> Result<Vec<Session>> {
+        let filter = if project_id.is_some() {
+            "WHERE project = type::thing('project', $project)"
+        } else {
+            ""
+        };
+        let sql = format!(
+            "SELECT {} FROM session {} ORDER BY started_at DESC",
+            Self::SESSION_FIELDS,
+            filter
+        );
+        let mut response = with_db!(self, db, {
+            db.query(&sql)
+                .bind(("project", project_id.map(|p| p.to_string())))
+                .await
+                .context("Failed to query sessions")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        Ok(results.iter().map(Self::value_to_session).collect())
     }
 
     /// Get session by ID
-    pub fn get_session(&self, _id: &str) -> Result<Option<Session>> {
-        // Not fully implemented yet
-        Ok(None)
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        Self::runtime().block_on(self.get_session_async(id))
+    }
+
+    async fn get_session_async(&self, id: &str) -> Result<Option<Session>> {
+        let sql = format!(
+            "SELECT {} FROM session WHERE id = type::thing('session', $id)",
+            Self::SESSION_FIELDS
+        );
+        let mut response = with_db!(self, db, {
+            db.query(&sql)
+                .bind(("id", id.to_string()))
+                .await
+                .context("Failed to query session")
+        })?;
+
+        let results: Vec<serde_json::Value> = response.take(0)?;
+        Ok(results.first().map(Self::value_to_session))
+    }
+
+    /// Upsert session
+    pub fn upsert_session(&self, session: &Session) -> Result<()> {
+        Self::runtime().block_on(self.upsert_session_async(session))
     }
 
-    /// Upsert session
-    pub fn upsert_session(&self, _session: &Session) -> Result<()> {
-        // Not fully implemented yet
+    async fn upsert_session_async(&self, session: &Session) -> Result<()> {
+        let metadata: Option<serde_json::Value> = match session.metadata {
+            Some(ref m) => {
+                Some(serde_json::from_str(m).context("Session metadata must be a JSON object")?)
+            }
+            None => None,
+        };
+        let started_at = if session.started_at.is_empty() {
+            Utc::now().to_rfc3339()
+        } else {
+            session.started_at.clone()
+        };
+
+        let mut response = with_db!(self, db, {
+            db.query(
+                "UPSERT type::thing('session', $id) SET
+                session_type = type::thing('session_type', $session_type),
+                project = IF $project THEN type::thing('project', $project) ELSE NONE END,
+                started_at = <datetime>$started_at,
+                ended_at = IF $ended_at THEN <datetime>$ended_at ELSE NONE END,
+                metadata = $metadata",
+            )
+            .bind(("id", session.id.clone()))
+            .bind(("session_type", session.session_type_id.clone()))
+            .bind(("project", session.project_id.clone()))
+            .bind(("started_at", normalize_datetime(&started_at)))
+            .bind((
+                "ended_at",
+                session.ended_at.as_deref().map(normalize_datetime),
+            ))
+            .bind(("metadata", metadata))
+            .await
+            .context("Failed to upsert session")
+        })?;
+
+        let errors = response.take_errors();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Failed to upsert session: {:?}", errors));
+        }
         Ok(())
     }
 
@@ -3599,18 +5256,77 @@ impl SurrealDatabase {
 // KNOWLEDGESTORE TRAIT IMPLEMENTATION
 // ============================================================================
 
+/// Query text and the set filter fields, for the slow-op log
+fn describe_query(query: &str, filter: &crate::store::KnowledgeFilter) -> String {
+    let mut parts = vec![format!("query={:?}", query)];
+    let mut set = |name: &str, value: Option<String>| {
+        if let Some(v) = value {
+            parts.push(format!("{}={}", name, v));
+        }
+    };
+    set(
+        "categories",
+        filter.categories.as_ref().map(|c| c.join(",")),
+    );
+    set("tags", filter.tags.as_ref().map(|t| t.join(",")));
+    set("project", filter.project.clone());
+    set("min_resonance", filter.min_resonance.map(|r| r.to_string()));
+    set("max_resonance", filter.max_resonance.map(|r| r.to_string()));
+    set("since", filter.since.map(|d| d.to_rfc3339()));
+    set("until", filter.until.map(|d| d.to_rfc3339()));
+    set("starred_by", filter.starred_by.clone());
+    parts.join(" ")
+}
+
 impl KnowledgeStore for SurrealDatabase {
     fn upsert_knowledge(&self, entry: &KnowledgeEntry) -> Result<()> {
-        self.upsert_knowledge_internal(entry)?;
+        let pending = match self.journal {
+            Some(ref journal) => Some(journal.begin(Op::UpsertEntry {
+                entry: Box::new(entry.clone()),
+            })?),
+            None => None,
+        };
+        slow_log::time(
+            "store upsert",
+            || format!("id={}", entry.id),
+            || self.upsert_knowledge_internal(entry),
+        )?;
+        if let Some(pending) = pending {
+            pending.finish()?;
+        }
         Ok(())
     }
 
     fn get(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<Option<KnowledgeEntry>> {
-        self.get_knowledge(id, ctx)
+        slow_log::time(
+            "store get",
+            || format!("id={}", id),
+            || self.get_knowledge(id, ctx),
+        )
     }
 
     fn delete(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
-        self.delete_knowledge(id, ctx)
+        slow_log::time(
+            "store delete",
+            || format!("id={}", id),
+            || self.delete_knowledge(id, ctx),
+        )
+    }
+
+    fn archive(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        self.archive(id, ctx)
+    }
+
+    fn restore(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        self.restore(id, ctx)
+    }
+
+    fn list_archived(&self, ctx: &crate::store::AgentContext) -> Result<Vec<KnowledgeEntry>> {
+        self.list_archived(ctx)
+    }
+
+    fn purge_archived(&self, id: &str, ctx: &crate::store::AgentContext) -> Result<bool> {
+        self.purge_archived(id, ctx)
     }
 
     fn search(
@@ -3619,7 +5335,24 @@ impl KnowledgeStore for SurrealDatabase {
         ctx: &crate::store::AgentContext,
         filter: &crate::store::KnowledgeFilter,
     ) -> Result<Vec<KnowledgeEntry>> {
-        self.search_knowledge(query, ctx, filter)
+        slow_log::time(
+            "store search",
+            || describe_query(query, filter),
+            || self.search_knowledge(query, ctx, filter),
+        )
+    }
+
+    fn search_scores(
+        &self,
+        query: &str,
+        ctx: &crate::store::AgentContext,
+        filter: &crate::store::KnowledgeFilter,
+    ) -> Result<HashMap<String, crate::store::TextScore>> {
+        slow_log::time(
+            "store search_scores",
+            || describe_query(query, filter),
+            || self.search_knowledge_scores(query, ctx, filter),
+        )
     }
 
     fn semantic_search(
@@ -3629,7 +5362,16 @@ impl KnowledgeStore for SurrealDatabase {
         filter: &crate::store::KnowledgeFilter,
         limit: usize,
     ) -> Result<Vec<KnowledgeEntry>> {
-        self.semantic_search_knowledge(query_embedding, ctx, filter, limit)
+        slow_log::time(
+            "store semantic_search",
+            || {
+                describe_query(
+                    &format!("<{} dims> limit={}", query_embedding.len(), limit),
+                    filter,
+                )
+            },
+            || self.semantic_search_knowledge(query_embedding, ctx, filter, limit),
+        )
     }
 
     fn list_by_category(
@@ -3638,15 +5380,19 @@ impl KnowledgeStore for SurrealDatabase {
         ctx: &crate::store::AgentContext,
         filter: &crate::store::KnowledgeFilter,
     ) -> Result<Vec<KnowledgeEntry>> {
-        self.list_by_category(category, ctx, filter)
+        slow_log::time(
+            "store list_by_category",
+            || describe_query(&format!("category={}", category), filter),
+            || self.list_by_category(category, ctx, filter),
+        )
     }
 
     fn list_all(&self, ctx: &crate::store::AgentContext) -> Result<Vec<KnowledgeEntry>> {
-        self.list_all(ctx)
+        slow_log::time("store list_all", String::new, || self.list_all(ctx))
     }
 
     fn count(&self) -> Result<usize> {
-        self.count()
+        slow_log::time("store count", String::new, || self.count())
     }
 
     fn wake_cascade(
@@ -3656,13 +5402,26 @@ impl KnowledgeStore for SurrealDatabase {
         min_resonance: Option<i32>,
         days: i64,
     ) -> Result<crate::store::WakeCascade> {
-        self.wake_cascade(ctx, limit, min_resonance, days)
+        slow_log::time(
+            "store wake_cascade",
+            || {
+                format!(
+                    "limit={} min_resonance={:?} days={}",
+                    limit, min_resonance, days
+                )
+            },
+            || self.wake_cascade(ctx, limit, min_resonance, days),
+        )
     }
 
     fn update_activations(&self, ids: &[String]) -> Result<()> {
         self.update_activations(ids)
     }
 
+    fn set_identity(&self, id: &str, sealed: bool) -> Result<bool> {
+        self.set_identity(id, sealed)
+    }
+
     fn update_summary(
         &self,
         id: &str,
@@ -3706,6 +5465,18 @@ impl KnowledgeStore for SurrealDatabase {
         self.list_all_tags(category)
     }
 
+    fn tag_counts(&self, category: Option<&str>) -> Result<Vec<crate::types::TagCount>> {
+        self.tag_counts(category)
+    }
+
+    fn merge_tags(&self, sources: &[String], target: &str) -> Result<usize> {
+        self.merge_tags(sources, target)
+    }
+
+    fn delete_tags(&self, names: &[String]) -> Result<usize> {
+        self.delete_tags(names)
+    }
+
     fn get_applicability_for_entry(&self, entry_id: &str) -> Result<Vec<String>> {
         self.get_applicability_for_entry(entry_id)
     }
@@ -3781,7 +5552,17 @@ impl KnowledgeStore for SurrealDatabase {
     }
 
     fn list_relationships_for_entry(&self, entry_id: &str) -> Result<Vec<Relationship>> {
-        self.list_relationships(entry_id)
+        slow_log::time(
+            "store list_relationships",
+            || format!("id={}", entry_id),
+            || self.list_relationships(entry_id),
+        )
+    }
+
+    fn list_all_relationships(&self) -> Result<Vec<Relationship>> {
+        slow_log::time("store list_all_relationships", String::new, || {
+            self.list_all_relationships()
+        })
     }
 
     fn add_relationship(&self, from: &str, to: &str, rel_type: &str) -> Result<String> {
@@ -3797,6 +5578,10 @@ impl KnowledgeStore for SurrealDatabase {
         Ok(false)
     }
 
+    fn remove_relationship(&self, from: &str, to: &str, rel_type: &str) -> Result<bool> {
+        self.delete_relationship(from, to, rel_type)
+    }
+
     fn get_facts_for_session(&self, session_id: &str) -> Result<Vec<String>> {
         self.get_facts_for_session(session_id)
     }
@@ -3809,6 +5594,35 @@ impl KnowledgeStore for SurrealDatabase {
         self.list_tables()
     }
 
+    fn list_changes(
+        &self,
+        since: i64,
+        limit: usize,
+        ctx: &crate::store::AgentContext,
+    ) -> Result<Vec<crate::types::Change>> {
+        slow_log::time(
+            "store list_changes",
+            || format!("since={} limit={}", since, limit),
+            || self.list_changes(since, limit, ctx),
+        )
+    }
+
+    fn list_changed_since(&self, since: Option<&str>) -> Result<Vec<KnowledgeEntry>> {
+        self.list_changed_since(since)
+    }
+
+    fn list_entry_ids(&self) -> Result<Vec<String>> {
+        self.list_entry_ids()
+    }
+
+    fn purge_entries(&self, ids: &[String]) -> Result<usize> {
+        slow_log::time(
+            "store purge",
+            || format!("{} id(s)", ids.len()),
+            || self.purge_entries(ids),
+        )
+    }
+
     fn list_sessions(&self, project_id: Option<&str>) -> Result<Vec<Session>> {
         self.list_sessions(project_id)
     }
@@ -3892,6 +5706,59 @@ impl KnowledgeStore for SurrealDatabase {
         self.purge_backups_internal(entry_id, keep)
     }
 
+    fn record_command(&self, entry: &crate::types::CommandLogEntry) -> Result<()> {
+        self.record_command(entry)
+    }
+
+    fn list_command_log(
+        &self,
+        limit: Option<usize>,
+        since_days: Option<i64>,
+        failed_only: bool,
+    ) -> Result<Vec<crate::types::CommandLogEntry>> {
+        self.list_command_log(limit, since_days, failed_only)
+    }
+
+    fn list_review_cards(&self, agent: &str) -> Result<Vec<crate::types::ReviewCard>> {
+        self.list_review_cards(agent)
+    }
+
+    fn upsert_review_card(&self, card: &crate::types::ReviewCard) -> Result<()> {
+        self.upsert_review_card(card)
+    }
+
+    fn record_view(&self, agent: &str, entry_id: &str) -> Result<()> {
+        self.record_view(agent, entry_id)
+    }
+
+    fn list_views(&self, agent: &str) -> Result<Vec<crate::types::EntryView>> {
+        self.list_views(agent)
+    }
+
+    fn star(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        self.star(agent, entry_id)
+    }
+
+    fn unstar(&self, agent: &str, entry_id: &str) -> Result<bool> {
+        self.unstar(agent, entry_id)
+    }
+
+    fn list_starred(&self, agent: &str) -> Result<Vec<String>> {
+        self.list_starred(agent)
+    }
+
+    fn add_queue_item(&self, item: &crate::types::QueueItem) -> Result<()> {
+        self.add_queue_item(item)
+    }
+
+    fn list_queue(&self, include_done: bool) -> Result<Vec<crate::types::QueueItem>> {
+        self.list_queue(include_done)
+    }
+
+    fn complete_queue_item(&self, id: &str) -> Result<bool> {
+        self.complete_queue_item(id)
+    }
+
     fn create_wake_session(&self, session: &crate::wake_token::WakeSession) -> Result<String> {
         self.create_wake_session(session)
     }
@@ -3940,6 +5807,73 @@ mod tests {
         assert!(db_path.is_dir());
     }
 
+    #[test]
+    fn test_cached_statement_builds_once() {
+        let first = cached_statement("test_cached", false, || "SELECT 1".to_string());
+        let second = cached_statement("test_cached", false, || unreachable!());
+        assert!(std::ptr::eq(first, second));
+
+        let scoped = cached_statement("test_cached", true, || "SELECT 2".to_string());
+        assert_eq!(scoped, "SELECT 2");
+    }
+
+    #[test]
+    fn test_config_file_settings_yield_to_env() {
+        let file: SurrealFileConfig =
+            serde_yaml::from_str("mode: network\nurl: ws://db.lan:8000\nnamespace: team\n")
+                .unwrap();
+        let config = SurrealConfig::resolve(&file, |_| None);
+        assert!(config.is_network());
+        assert_eq!(config.url, "ws://db.lan:8000");
+        assert_eq!(config.namespace, "team");
+        assert_eq!(config.database, "knowledge");
+
+        let config = SurrealConfig::resolve(&file, |name| {
+            (name == "MX_SURREAL_MODE").then(|| "embedded".to_string())
+        });
+        assert!(!config.is_network());
+        assert_eq!(config.url, "ws://db.lan:8000");
+    }
+
+    #[test]
+    fn test_pool_key_distinguishes_targets() {
+        let config = SurrealConfig::default();
+        let a = pool_key(Path::new("/tmp/a.surreal"), &config);
+        let b = pool_key(Path::new("/tmp/b.surreal"), &config);
+        assert_ne!(a, b);
+
+        // Network mode ignores the path
+        let network = SurrealConfig {
+            mode: SurrealMode::Network,
+            ..SurrealConfig::default()
+        };
+        assert_eq!(
+            pool_key(Path::new("/tmp/a.surreal"), &network),
+            pool_key(Path::new("/tmp/b.surreal"), &network)
+        );
+    }
+
+    #[test]
+    fn test_open_reuses_pooled_connection() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("pooled.surreal");
+        let first = SurrealDatabase::open(&path).unwrap();
+        first
+            .upsert_agent(&Agent {
+                id: "pooled".to_string(),
+                description: None,
+                domain: None,
+                created_at: None,
+                updated_at: None,
+            })
+            .unwrap();
+
+        // A second open on the same path must see the same data without
+        // contending for the SurrealKV lock
+        let second = SurrealDatabase::open(&path).unwrap();
+        assert!(second.get_agent("pooled").unwrap().is_some());
+    }
+
     #[test]
     fn test_upsert_applicability_type_with_datetime() {
         use crate::types::ApplicabilityType;
@@ -4056,6 +5990,12 @@ mod tests {
             embedded_at: None,
             format: "markdown".to_string(),
             effective_resonance: None,
+            token_count: None,
+            namespace: None,
+            confidence: None,
+            confirmed_at: None,
+            archived_at: None,
+            attachments: Vec::new(),
         }
     }
 
@@ -4440,6 +6380,24 @@ mod tests {
         assert!(db.get("kn-thread2", &ctx).unwrap().is_some());
     }
 
+    #[test]
+    fn test_list_all_relationships() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        for id in ["kn-a", "kn-b", "kn-c"] {
+            db.upsert_knowledge(&make_test_entry(id, 5, 0.0)).unwrap();
+        }
+        db.add_relationship("kn-a", "kn-b", "related").unwrap();
+        db.add_relationship("kn-b", "kn-c", "related").unwrap();
+
+        let rels = db.list_all_relationships().unwrap();
+        assert_eq!(rels.len(), 2);
+        assert!(
+            rels.iter()
+                .any(|r| r.from_entry_id == "kn-a" && r.to_entry_id == "kn-b")
+        );
+        assert!(rels.iter().all(|r| r.relationship_type == "related"));
+    }
+
     #[test]
     fn test_session_linkage_round_trip() {
         // Edge case: Can we link a fact to a session and retrieve it back?
@@ -5152,6 +7110,224 @@ mod tests {
         entry
     }
 
+    #[test]
+    fn test_search_applies_tag_project_and_date_filters() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let mut old = make_tagged_entry("kn-flt1", "pattern", vec!["rust".to_string()]);
+        old.title = "Retry with backoff".to_string();
+        old.created_at = Some("2025-06-01T00:00:00Z".to_string());
+        old.source_project_id = Some("mx".to_string());
+        db.upsert_knowledge(&old).unwrap();
+        let mut new = make_tagged_entry("kn-flt2", "pattern", vec!["async".to_string()]);
+        new.title = "Retry on timeout".to_string();
+        new.created_at = Some("2026-02-01T00:00:00Z".to_string());
+        db.upsert_knowledge(&new).unwrap();
+
+        let ctx = AgentContext::public_only();
+        let ids = |filter: KnowledgeFilter| -> Vec<String> {
+            let mut ids: Vec<String> = db
+                .search_knowledge("retry", &ctx, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(KnowledgeFilter::default()), ["kn-flt1", "kn-flt2"]);
+        let tagged = KnowledgeFilter {
+            tags: Some(vec!["async".to_string(), "go".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(tagged), ["kn-flt2"]);
+        let project = KnowledgeFilter {
+            project: Some("mx".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(project), ["kn-flt1"]);
+        let after = KnowledgeFilter {
+            since: Some(crate::dates::parse_date("2026-01-01").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ids(after), ["kn-flt2"]);
+        let before = KnowledgeFilter {
+            until: Some(crate::dates::parse_date("2026-01-01").unwrap()),
+            categories: Some(vec!["pattern".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(before), ["kn-flt1"]);
+    }
+
+    #[test]
+    fn test_search_orders_by_bm25_score() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        for (i, title) in ["Tag hygiene", "Graph layout", "Session export"]
+            .iter()
+            .enumerate()
+        {
+            let mut other = make_test_entry(&format!("kn-bmx{}", i), 5, 0.0);
+            other.title = title.to_string();
+            db.upsert_knowledge(&other).unwrap();
+        }
+        let mut passing = make_test_entry("kn-bm1", 5, 0.0);
+        passing.title = "Connection pooling".to_string();
+        passing.body = Some("Mentions retry once among many other words".to_string());
+        db.upsert_knowledge(&passing).unwrap();
+        let mut focused = make_test_entry("kn-bm2", 5, 0.0);
+        focused.title = "Retry with backoff".to_string();
+        focused.body = Some("Retry, then retry again".to_string());
+        db.upsert_knowledge(&focused).unwrap();
+
+        let ids: Vec<String> = db
+            .search_knowledge(
+                "retry",
+                &AgentContext::public_only(),
+                &KnowledgeFilter::default(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, ["kn-bm2", "kn-bm1"]);
+    }
+
+    #[test]
+    fn test_search_weights_fields_and_restricts_to_chosen_ones() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        for (i, title) in ["Tag hygiene", "Graph layout", "Session export"]
+            .iter()
+            .enumerate()
+        {
+            let mut other = make_test_entry(&format!("kn-wx{}", i), 5, 0.0);
+            other.title = title.to_string();
+            db.upsert_knowledge(&other).unwrap();
+        }
+        let mut verbose = make_tagged_entry("kn-w1", "pattern", vec!["Backoff".to_string()]);
+        verbose.title = "Connection notes".to_string();
+        verbose.body = Some("Backoff here, backoff there, backoff everywhere".to_string());
+        db.upsert_knowledge(&verbose).unwrap();
+        let mut titled = make_tagged_entry("kn-w2", "pattern", vec![]);
+        titled.title = "Backoff".to_string();
+        db.upsert_knowledge(&titled).unwrap();
+
+        let ctx = AgentContext::public_only();
+        let ids = |fields: Option<Vec<SearchField>>| -> Vec<String> {
+            let filter = KnowledgeFilter {
+                search_fields: fields,
+                ..Default::default()
+            };
+            db.search_knowledge("backoff", &ctx, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect()
+        };
+        assert_eq!(ids(None), ["kn-w2", "kn-w1"]);
+        assert_eq!(ids(Some(vec![SearchField::Body])), ["kn-w1"]);
+        assert_eq!(ids(Some(vec![SearchField::Tags])), ["kn-w1"]);
+        assert_eq!(
+            ids(Some(vec![SearchField::Tags, SearchField::Title])),
+            ["kn-w2", "kn-w1"]
+        );
+
+        let scores = db
+            .search_knowledge_scores("backoff", &ctx, &KnowledgeFilter::default())
+            .unwrap();
+        assert_eq!(scores["kn-w1"].title, 0.0);
+        assert!(scores["kn-w2"].title > scores["kn-w1"].body);
+    }
+
+    #[test]
+    fn test_analyzer_settings_rebuild_indexes_and_drop_stop_words() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let mut entry = make_test_entry("kn-stem1", 5, 0.0);
+        entry.title = "Retry with backoff".to_string();
+        db.upsert_knowledge(&entry).unwrap();
+
+        let ctx = AgentContext::public_only();
+        let hits = |db: &SurrealDatabase, query: &str| {
+            db.search_knowledge(query, &ctx, &KnowledgeFilter::default())
+                .unwrap()
+                .len()
+        };
+        assert_eq!(hits(&db, "retries"), 0);
+        assert_eq!(hits(&db, "the backoff"), 0);
+
+        let settings: crate::search::Settings =
+            serde_yaml::from_str("stemming: true\nstop_words: [the]\n").unwrap();
+        let db = db.with_analyzer(settings.clone()).unwrap();
+        assert_eq!(hits(&db, "retries"), 1);
+        assert_eq!(hits(&db, "the backoff"), 1);
+
+        // Reopening with the same settings is a no-op
+        let db = db.with_analyzer(settings).unwrap();
+        assert_eq!(hits(&db, "retries"), 1);
+    }
+
+    #[test]
+    fn test_semantic_search_returns_nearest_matching_filters() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        // Unit vector along `axis`, leaning a little toward the next one
+        let vector = |axis: usize, lean: f32| {
+            let mut v = vec![0.0f32; 768];
+            v[axis] = 1.0;
+            v[axis + 1] = lean;
+            v
+        };
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        for (id, category, axis, lean) in [
+            ("kn-vec1", "pattern", 0, 0.1),
+            ("kn-vec2", "pattern", 0, 0.5),
+            ("kn-vec3", "insight", 0, 0.0),
+            ("kn-vec4", "pattern", 5, 0.0),
+        ] {
+            let mut entry = make_tagged_entry(id, category, vec![]);
+            entry.embedding = Some(vector(axis, lean));
+            db.upsert_knowledge(&entry).unwrap();
+        }
+        db.upsert_knowledge(&make_test_entry("kn-vec5", 5, 0.0))
+            .unwrap();
+
+        let ctx = AgentContext::public_only();
+        let ids = |filter: &KnowledgeFilter, limit: usize| -> Vec<String> {
+            db.semantic_search_knowledge(&vector(0, 0.0), &ctx, filter, limit)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect()
+        };
+        assert_eq!(ids(&KnowledgeFilter::default(), 2), ["kn-vec3", "kn-vec1"]);
+        let patterns = KnowledgeFilter {
+            categories: Some(vec!["pattern".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(&patterns, 3), ["kn-vec1", "kn-vec2", "kn-vec4"]);
+
+        // A selective filter misses the whole candidate set: still found
+        for i in 0..KNN_MIN_CANDIDATES {
+            let mut near = make_tagged_entry(&format!("kn-near{}", i), "pattern", vec![]);
+            near.embedding = Some(vector(0, 0.01 * i as f32));
+            db.upsert_knowledge(&near).unwrap();
+        }
+        let mut far = make_tagged_entry("kn-far", "gotcha", vec![]);
+        far.embedding = Some(vector(9, 0.0));
+        db.upsert_knowledge(&far).unwrap();
+        let gotchas = KnowledgeFilter {
+            categories: Some(vec!["gotcha".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(ids(&gotchas, 1), ["kn-far"]);
+    }
+
     #[test]
     fn test_list_all_tags_returns_distinct_tags() {
         let db = SurrealDatabase::open_in_memory().unwrap();
@@ -5202,6 +7378,182 @@ mod tests {
         assert_eq!(technique_tags, vec!["error-handling", "rust"]);
     }
 
+    #[test]
+    fn test_upsert_keeps_replaced_content_as_revision() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let mut entry = make_test_entry("kn-rev1", 5, 0.0);
+        entry.body = Some("first".to_string());
+        db.upsert_knowledge(&entry).unwrap();
+        assert!(db.list_backups("kn-rev1").unwrap().is_empty());
+
+        // Unchanged content leaves no revision
+        entry.resonance = 7;
+        db.upsert_knowledge(&entry).unwrap();
+        assert!(db.list_backups("kn-rev1").unwrap().is_empty());
+
+        entry.body = Some("second".to_string());
+        db.upsert_knowledge(&entry).unwrap();
+        let backups = db.list_backups("kn-rev1").unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].body.as_deref(), Some("first"));
+        assert_eq!(backups[0].operation, "upsert");
+
+        // Already backed up by the caller: not recorded twice
+        db.backup_content(&entry, "edit", None).unwrap();
+        entry.body = Some("third".to_string());
+        db.upsert_knowledge(&entry).unwrap();
+        let backups = db.list_backups("kn-rev1").unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].operation, "edit");
+
+        // Numbered per entry in order taken, and nothing is pruned
+        for body in ["4", "5", "6", "7", "8", "9", "10", "11", "12", "13"] {
+            entry.body = Some(body.to_string());
+            db.upsert_knowledge(&entry).unwrap();
+        }
+        db.backup_content(&make_test_entry("kn-rev2", 5, 0.0), "edit", None)
+            .unwrap();
+        // Oldest first
+        let revs = |id: &str| -> Vec<Option<u32>> {
+            db.list_backups(id)
+                .unwrap()
+                .iter()
+                .rev()
+                .map(|b| b.rev)
+                .collect()
+        };
+        assert_eq!(revs("kn-rev1"), (1..=12).map(Some).collect::<Vec<_>>());
+        assert_eq!(revs("kn-rev2"), [Some(1)]);
+
+        // Backups from before revision numbers get them on open
+        SurrealDatabase::runtime()
+            .block_on(async {
+                with_db!(db, d, {
+                    d.query("UPDATE memory_backup SET rev = NONE WHERE entry_id = 'kn-rev1'")
+                        .await
+                })
+            })
+            .unwrap();
+        assert_eq!(revs("kn-rev1")[0], None);
+        db.apply_schema().unwrap();
+        assert_eq!(revs("kn-rev1"), (1..=12).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_views_count_per_agent_newest_first() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        db.record_view("smith", "kn-a").unwrap();
+        db.record_view("smith", "kn-b").unwrap();
+        db.record_view("smith", "kn-a").unwrap();
+        db.record_view("neo", "kn-b").unwrap();
+
+        let views = db.list_views("smith").unwrap();
+        let seen: Vec<(&str, i64)> = views
+            .iter()
+            .map(|v| (v.entry_id.as_str(), v.views))
+            .collect();
+        assert_eq!(seen, [("kn-a", 2), ("kn-b", 1)]);
+        assert_eq!(db.list_views("neo").unwrap().len(), 1);
+        assert!(db.list_views("trinity").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_starred_entries_lead_the_cascade_and_filter() {
+        use crate::store::{AgentContext, KnowledgeFilter};
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let mut core = make_test_entry("kn-star-core", 9, 0.0);
+        core.resonance_type = Some("foundational".to_string());
+        db.upsert_knowledge(&core).unwrap();
+        db.upsert_knowledge(&make_test_entry("kn-star-low", 2, 0.0))
+            .unwrap();
+        db.upsert_knowledge(&make_test_entry("kn-star-mid", 4, 0.0))
+            .unwrap();
+
+        assert!(db.star("smith", "kn-star-mid").unwrap());
+        assert!(db.star("smith", "kn-star-low").unwrap());
+        assert!(!db.star("smith", "kn-star-low").unwrap());
+        assert_eq!(
+            db.list_starred("smith").unwrap(),
+            ["kn-star-mid", "kn-star-low"]
+        );
+
+        // Starred first, in star order, on top of the limit
+        let ctx = AgentContext::public_for_agent("smith");
+        let cascade = db.wake_cascade(&ctx, 1, None, 7).unwrap();
+        let ids: Vec<&str> = cascade.core.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["kn-star-mid", "kn-star-low", "kn-star-core"]);
+        let other = db
+            .wake_cascade(&AgentContext::public_for_agent("neo"), 1, None, 7)
+            .unwrap();
+        assert_eq!(other.core.len(), 1);
+
+        let starred = KnowledgeFilter {
+            starred_by: Some("smith".to_string()),
+            ..Default::default()
+        };
+        let listed = db.list_by_category("test", &ctx, &starred).unwrap();
+        assert_eq!(listed.len(), 2);
+
+        assert!(db.unstar("smith", "kn-star-mid").unwrap());
+        assert!(!db.unstar("smith", "kn-star-mid").unwrap());
+        assert_eq!(db.list_starred("smith").unwrap(), ["kn-star-low"]);
+    }
+
+    #[test]
+    fn test_merge_rename_and_delete_tags() {
+        use crate::types::TagCount;
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let tags = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        db.upsert_knowledge(&make_tagged_entry(
+            "kn-t1",
+            "pattern",
+            tags(&["rust", "async"]),
+        ))
+        .unwrap();
+        db.upsert_knowledge(&make_tagged_entry(
+            "kn-t2",
+            "pattern",
+            tags(&["Rust", "rust"]),
+        ))
+        .unwrap();
+        db.upsert_knowledge(&make_tagged_entry("kn-t3", "technique", tags(&["Rust"])))
+            .unwrap();
+        let count = |name: &str, entries: usize| TagCount {
+            name: name.to_string(),
+            entries,
+        };
+        assert_eq!(
+            db.tag_counts(None).unwrap(),
+            [count("Rust", 2), count("rust", 2), count("async", 1)]
+        );
+        assert_eq!(
+            db.tag_counts(Some("technique")).unwrap(),
+            [count("Rust", 1)]
+        );
+
+        // kn-t2 carries both spellings: it ends up tagged once
+        assert_eq!(db.merge_tags(&tags(&["Rust"]), "rust").unwrap(), 2);
+        assert_eq!(
+            db.tag_counts(None).unwrap(),
+            [count("rust", 3), count("async", 1)]
+        );
+        assert_eq!(db.get_tags_for_entry("kn-t2").unwrap(), ["rust"]);
+
+        // Renaming is merging into a tag that doesn't exist yet
+        assert_eq!(db.merge_tags(&tags(&["async"]), "concurrency").unwrap(), 1);
+        assert_eq!(db.list_all_tags(None).unwrap(), ["concurrency", "rust"]);
+
+        assert_eq!(db.delete_tags(&tags(&["rust", "missing"])).unwrap(), 3);
+        assert_eq!(db.list_all_tags(None).unwrap(), ["concurrency"]);
+        let entry = db
+            .get_knowledge("kn-t1", &crate::store::AgentContext::public_only())
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.tags, ["concurrency"]);
+    }
+
     #[test]
     fn test_list_all_tags_empty_database() {
         let db = SurrealDatabase::open_in_memory().unwrap();
@@ -5212,4 +7564,78 @@ mod tests {
         let tags = db.list_all_tags(Some("pattern")).unwrap();
         assert!(tags.is_empty());
     }
+
+    #[test]
+    fn test_schema_heals_change_counter_left_behind() {
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        let entry = |id: &str| -> KnowledgeEntry {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "category_id": "pattern",
+                "title": id,
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z",
+            }))
+            .unwrap()
+        };
+        db.upsert_knowledge(&entry("kn-a")).unwrap();
+        db.upsert_knowledge(&entry("kn-b")).unwrap();
+
+        // The state a lost counter write leaves behind
+        SurrealDatabase::runtime()
+            .block_on(async {
+                with_db!(db, d, {
+                    d.query("UPDATE change_counter:feed SET n -= 1").await
+                })
+            })
+            .unwrap();
+        assert!(db.upsert_knowledge(&entry("kn-c")).is_err());
+
+        db.apply_schema().unwrap();
+        db.upsert_knowledge(&entry("kn-c")).unwrap();
+        let seqs: Vec<i64> = db
+            .list_changes(0, 10, &crate::store::AgentContext::public_only())
+            .unwrap()
+            .iter()
+            .map(|c| c.seq)
+            .collect();
+        assert_eq!(seqs, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_delete_records_edge_changes_with_the_entry() {
+        use crate::store::AgentContext;
+
+        let db = SurrealDatabase::open_in_memory().unwrap();
+        for id in ["kn-del1", "kn-del2", "kn-del3"] {
+            db.upsert_knowledge(&make_test_entry(id, 5, 0.0)).unwrap();
+        }
+        db.add_relationship("kn-del1", "kn-del2", "related")
+            .unwrap();
+        db.add_relationship("kn-del3", "kn-del1", "related")
+            .unwrap();
+
+        let ctx = AgentContext::public_only();
+        assert!(db.delete_knowledge("kn-del1", &ctx).unwrap());
+        assert!(!db.delete_knowledge("kn-del1", &ctx).unwrap());
+
+        let changes = db.list_changes(0, 20, &ctx).unwrap();
+        let seqs: Vec<i64> = changes.iter().map(|c| c.seq).collect();
+        assert_eq!(seqs, (1..=changes.len() as i64).collect::<Vec<_>>());
+        let tail: Vec<&str> = changes[changes.len() - 3..]
+            .iter()
+            .map(|c| c.kind.as_str())
+            .collect();
+        assert_eq!(tail, ["unrelated", "unrelated", "deleted"]);
+
+        // The counter moved with the delete: the next write takes the next seq
+        let counter: Option<i64> = SurrealDatabase::runtime()
+            .block_on(async { with_db!(db, d, { d.query("RETURN change_counter:feed.n").await }) })
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(counter, seqs.last().copied());
+        db.upsert_knowledge(&make_test_entry("kn-del4", 5, 0.0))
+            .unwrap();
+    }
 }
@@ -0,0 +1,499 @@
+//! Deps command - the dependency graph of synced issues
+//!
+//! Dependencies are read from issue bodies in the local YAML cache:
+//!
+//! - `Blocked by #3`, `blocked on #3`, `depends on #3, #5`: this issue waits
+//!   on the referenced ones; `blocks #8` records the reverse.
+//! - Task-list items (`- [ ] #4`): a tracking issue waits on each item;
+//!   ticked items count as done.
+//!
+//! References to other repositories (`owner/other#4`) are ignored. Pull only
+//! fetches open issues, so a blocker missing from the cache is taken as
+//! closed. The graph is printed per issue, with cycles flagged and the open
+//! issues nothing open is blocking listed as ready to work; `--dot` emits
+//! Graphviz with edges running from blocker to blocked.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use crate::sync::default_sync_dir;
+use crate::sync::yaml::schema::{ItemType, SyncYaml};
+use crate::sync::yaml::store::YamlStore;
+
+/// Phrases after which references name this issue's blockers
+const BLOCKED_BY: &[&str] = &["blocked by", "blocked on", "depends on", "depending on"];
+
+/// Phrase after which references name issues this one blocks
+const BLOCKS: &str = "blocks";
+
+#[derive(clap::Args)]
+pub struct DepsArgs {
+    /// Repository (owner/repo format; defaults to the current git repo)
+    pub repo: Option<String>,
+
+    /// Input directory (defaults to $MX_HOME/cache/sync/<repo>)
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Print the graph in Graphviz DOT format
+    #[arg(long, conflicts_with = "json")]
+    pub dot: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// `issue` can't start until `blocked_by` is done
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub issue: u64,
+    pub blocked_by: u64,
+    /// A ticked task-list item
+    pub done: bool,
+}
+
+struct IssueNode {
+    title: String,
+    open: bool,
+}
+
+/// Same-repo issue numbers referenced in `text`
+fn references(text: &str, repo: &str) -> Vec<u64> {
+    let mut found = Vec::new();
+    for (i, _) in text.match_indices('#') {
+        let digits: String = text[i + 1..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        let Ok(number) = digits.parse() else {
+            continue;
+        };
+        // `owner/repo#N` only counts when it names this repository
+        let prefix = text[..i]
+            .rsplit(|c: char| c.is_whitespace() || "([,".contains(c))
+            .next()
+            .unwrap_or("");
+        if prefix.is_empty() || prefix.eq_ignore_ascii_case(repo) {
+            found.push(number);
+        }
+    }
+    found
+}
+
+/// Text following the first `phrase` that starts a word, if any
+fn after<'a>(line: &'a str, phrase: &str) -> Option<&'a str> {
+    line.match_indices(phrase)
+        .find(|(i, _)| {
+            !line[..*i]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+        })
+        .map(|(i, _)| &line[i + phrase.len()..])
+}
+
+/// Dependency links declared in the body of issue `number`
+pub fn parse(number: u64, body: &str, repo: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut push = |issue: u64, blocked_by: u64, done: bool| {
+        if issue != blocked_by
+            && !links
+                .iter()
+                .any(|l: &Link| l.issue == issue && l.blocked_by == blocked_by)
+        {
+            links.push(Link {
+                issue,
+                blocked_by,
+                done,
+            });
+        }
+    };
+
+    for line in body.lines() {
+        let lower = line.trim().to_lowercase();
+        let task = ["- [ ]", "* [ ]", "- [x]", "* [x]"]
+            .iter()
+            .find(|m| lower.starts_with(*m));
+        if let Some(marker) = task {
+            for n in references(&lower, repo) {
+                push(number, n, marker.contains('x'));
+            }
+            continue;
+        }
+        for phrase in BLOCKED_BY {
+            if let Some(rest) = after(&lower, phrase) {
+                for n in references(rest, repo) {
+                    push(number, n, false);
+                }
+            }
+        }
+        if let Some(rest) = after(&lower, BLOCKS) {
+            for n in references(rest, repo) {
+                push(n, number, false);
+            }
+        }
+    }
+    links
+}
+
+/// Synced issues and the links between them
+pub struct Graph {
+    issues: BTreeMap<u64, IssueNode>,
+    links: Vec<Link>,
+}
+
+impl Graph {
+    /// The graph declared by the issues in a sync cache
+    pub fn from_cache(items: &[(PathBuf, SyncYaml)], repo: &str) -> Self {
+        let mut graph = Graph {
+            issues: BTreeMap::new(),
+            links: Vec::new(),
+        };
+        for (_, yaml) in items {
+            let (ItemType::Issue, Some(number)) = (yaml.item_type(), yaml.github_issue_number())
+            else {
+                continue;
+            };
+            let open = yaml
+                .metadata
+                .state
+                .as_deref()
+                .is_none_or(|s| s.eq_ignore_ascii_case("open"));
+            graph.issues.insert(
+                number,
+                IssueNode {
+                    title: yaml.title().to_string(),
+                    open,
+                },
+            );
+            for link in parse(number, yaml.body(), repo) {
+                if !graph.links.contains(&link) {
+                    graph.links.push(link);
+                }
+            }
+        }
+        graph.links.sort_by_key(|l| (l.issue, l.blocked_by, l.done));
+        graph
+            .links
+            .dedup_by(|a, b| a.issue == b.issue && a.blocked_by == b.blocked_by);
+        graph
+    }
+
+    /// Whether a link no longer holds anything up
+    pub fn resolved(&self, link: &Link) -> bool {
+        link.done || self.issues.get(&link.blocked_by).is_none_or(|i| !i.open)
+    }
+
+    /// Issue numbers taking part in any link
+    fn members(&self) -> BTreeSet<u64> {
+        self.links
+            .iter()
+            .flat_map(|l| [l.issue, l.blocked_by])
+            .collect()
+    }
+
+    pub fn blockers(&self, issue: u64) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(move |l| l.issue == issue)
+    }
+
+    /// Groups of issues that wait on each other, each sorted
+    pub fn cycles(&self) -> Vec<Vec<u64>> {
+        struct Tarjan {
+            edges: HashMap<u64, Vec<u64>>,
+            index: HashMap<u64, usize>,
+            low: HashMap<u64, usize>,
+            stack: Vec<u64>,
+            on_stack: BTreeSet<u64>,
+            found: Vec<Vec<u64>>,
+        }
+        impl Tarjan {
+            fn visit(&mut self, v: u64) {
+                let n = self.index.len();
+                self.index.insert(v, n);
+                self.low.insert(v, n);
+                self.stack.push(v);
+                self.on_stack.insert(v);
+                for w in self.edges.get(&v).cloned().unwrap_or_default() {
+                    if !self.index.contains_key(&w) {
+                        self.visit(w);
+                        let low = self.low[&v].min(self.low[&w]);
+                        self.low.insert(v, low);
+                    } else if self.on_stack.contains(&w) {
+                        let low = self.low[&v].min(self.index[&w]);
+                        self.low.insert(v, low);
+                    }
+                }
+                if self.low[&v] == self.index[&v] {
+                    let mut group = Vec::new();
+                    while let Some(w) = self.stack.pop() {
+                        self.on_stack.remove(&w);
+                        group.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    if group.len() > 1 {
+                        group.sort();
+                        self.found.push(group);
+                    }
+                }
+            }
+        }
+
+        let mut edges: HashMap<u64, Vec<u64>> = HashMap::new();
+        for link in self.links.iter().filter(|l| !self.resolved(l)) {
+            edges.entry(link.issue).or_default().push(link.blocked_by);
+        }
+        let mut tarjan = Tarjan {
+            edges,
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            found: Vec::new(),
+        };
+        for v in self.members() {
+            if !tarjan.index.contains_key(&v) {
+                tarjan.visit(v);
+            }
+        }
+        let mut found = tarjan.found;
+        found.sort();
+        found
+    }
+
+    /// Open issues in the graph with nothing open blocking them
+    pub fn ready(&self) -> Vec<u64> {
+        self.members()
+            .into_iter()
+            .filter(|n| self.issues.get(n).is_some_and(|i| i.open))
+            .filter(|&n| self.blockers(n).all(|l| self.resolved(l)))
+            .collect()
+    }
+
+    pub fn title(&self, number: u64) -> &str {
+        self.issues
+            .get(&number)
+            .map(|i| i.title.as_str())
+            .unwrap_or("(not in cache)")
+    }
+
+    fn to_dot(&self, cycles: &[Vec<u64>], ready: &[u64]) -> String {
+        let in_cycle: BTreeSet<u64> = cycles.iter().flatten().copied().collect();
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = String::from("digraph deps {\n");
+        for n in self.members() {
+            let mut attrs = vec![format!(
+                "label={}",
+                quote(&format!("#{} {}", n, self.title(n)))
+            )];
+            if !self.issues.get(&n).is_some_and(|i| i.open) {
+                attrs.push("style=dashed".to_string());
+            }
+            if in_cycle.contains(&n) {
+                attrs.push("color=red".to_string());
+            } else if ready.contains(&n) {
+                attrs.push("color=green".to_string());
+            }
+            out.push_str(&format!("  \"#{}\" [{}];\n", n, attrs.join(", ")));
+        }
+        for link in &self.links {
+            let style = if self.resolved(link) {
+                " [style=dashed]"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  \"#{}\" -> \"#{}\"{};\n",
+                link.blocked_by, link.issue, style
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Run the deps command
+pub fn run(args: DepsArgs) -> Result<()> {
+    let repo = crate::workspace::repo_or_current(args.repo)?;
+    let input_dir = args
+        .input
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_sync_dir(&repo));
+    let store = YamlStore::new(input_dir.clone());
+    let items = if input_dir.exists() {
+        store.read_all()?
+    } else {
+        Vec::new()
+    };
+
+    let graph = Graph::from_cache(&items, &repo);
+    let cycles = graph.cycles();
+    let ready = graph.ready();
+
+    if args.dot {
+        print!("{}", graph.to_dot(&cycles, &ready));
+        return Ok(());
+    }
+    if args.json {
+        let issues: Vec<serde_json::Value> = graph
+            .members()
+            .into_iter()
+            .map(|n| {
+                let blocked_by: Vec<serde_json::Value> = graph
+                    .blockers(n)
+                    .map(|l| {
+                        serde_json::json!({
+                            "number": l.blocked_by,
+                            "resolved": graph.resolved(l),
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "number": n,
+                    "title": graph.title(n),
+                    "open": graph.issues.get(&n).is_some_and(|i| i.open),
+                    "cached": graph.issues.contains_key(&n),
+                    "blocked_by": blocked_by,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "issues": issues,
+                "cycles": cycles,
+                "ready": ready,
+            }))?
+        );
+        return Ok(());
+    }
+
+    if graph.links.is_empty() {
+        println!(
+            "No dependencies found in {} issue(s) under {}",
+            graph.issues.len(),
+            input_dir.display()
+        );
+        return Ok(());
+    }
+    println!(
+        "{} issue(s), {} dependency link(s)\n",
+        graph.members().len(),
+        graph.links.len()
+    );
+    for n in graph.members() {
+        let mut blockers = graph.blockers(n).peekable();
+        if blockers.peek().is_none() {
+            continue;
+        }
+        println!("{} {}", format!("#{}", n).yellow(), graph.title(n));
+        for link in blockers {
+            let line = format!(
+                "blocked by #{} {}",
+                link.blocked_by,
+                graph.title(link.blocked_by)
+            );
+            if graph.resolved(link) {
+                println!("    {} {}", line.dimmed(), "(done)".dimmed());
+            } else {
+                println!("    {}", line);
+            }
+        }
+    }
+
+    if !cycles.is_empty() {
+        println!("\n{}", "Cycles:".red().bold());
+        for cycle in &cycles {
+            let numbers: Vec<String> = cycle.iter().map(|n| format!("#{}", n)).collect();
+            println!("  {}", numbers.join(" <-> "));
+        }
+    }
+    println!("\n{}", "Ready to work:".green().bold());
+    if ready.is_empty() {
+        println!("  (none)");
+    }
+    for n in &ready {
+        println!("  #{} {}", n, graph.title(*n));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(issue: u64, blocked_by: u64, done: bool) -> Link {
+        Link {
+            issue,
+            blocked_by,
+            done,
+        }
+    }
+
+    #[test]
+    fn parses_blockers_and_task_lists() {
+        let body = "Blocked by #3, #4 and owner/repo#5 (not other/repo#6)\n\
+                    This blocks #9.\n\
+                    Unblocks #12, see #2\n\
+                    - [ ] #7 wire it up\n\
+                    - [x] #8\n\
+                    Depends on #3";
+        assert_eq!(
+            parse(1, body, "owner/repo"),
+            vec![
+                link(1, 3, false),
+                link(1, 4, false),
+                link(1, 5, false),
+                link(9, 1, false),
+                link(1, 7, false),
+                link(1, 8, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_cycles_and_ready_leaves() {
+        let issue = |number: u64, open: bool| {
+            (
+                number,
+                IssueNode {
+                    title: format!("Issue {}", number),
+                    open,
+                },
+            )
+        };
+        let graph = Graph {
+            issues: BTreeMap::from([
+                issue(1, true),
+                issue(2, true),
+                issue(3, false),
+                issue(4, true),
+                issue(5, true),
+                issue(6, true),
+            ]),
+            links: vec![
+                // 1 waits on 2 (open) and 3 (closed); 2 waits on nothing open
+                link(1, 2, false),
+                link(1, 3, false),
+                link(2, 3, false),
+                // 4 and 5 wait on each other
+                link(4, 5, false),
+                link(5, 4, false),
+                // 6 waits on a done task and an issue no longer cached
+                link(6, 1, true),
+                link(6, 99, false),
+            ],
+        };
+        assert_eq!(graph.cycles(), vec![vec![4, 5]]);
+        assert_eq!(graph.ready(), vec![2, 6]);
+
+        let dot = graph.to_dot(&graph.cycles(), &graph.ready());
+        assert!(dot.contains("\"#2\" -> \"#1\";"));
+        assert!(dot.contains("\"#3\" -> \"#1\" [style=dashed];"));
+        assert!(dot.contains("\"#4\" [label=\"#4 Issue 4\", color=red];"));
+    }
+}
@@ -5,6 +5,7 @@
 use anyhow::Result;
 
 use super::{pull, push};
+use crate::sync::github::DEFAULT_CONCURRENCY;
 
 /// Run bidirectional issue sync
 pub fn run(repo: &str, dry_run: bool) -> Result<()> {
@@ -13,7 +14,7 @@ pub fn run(repo: &str, dry_run: bool) -> Result<()> {
 
     // First pull to get remote changes
     println!("--- Pull (GitHub → Local) ---");
-    pull::run(repo, None, dry_run)?;
+    pull::run(repo, None, dry_run, DEFAULT_CONCURRENCY)?;
 
     println!();
     println!("--- Push (Local → GitHub) ---");
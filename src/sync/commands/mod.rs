@@ -1,6 +1,9 @@
 //! Sync command implementations
 
+pub mod deps;
 pub mod issues;
 pub mod labels;
+pub mod plan;
 pub mod pull;
 pub mod push;
+pub mod status;
@@ -0,0 +1,452 @@
+//! Plan command - a milestone's issues as a markdown planning doc
+//!
+//! Reads the local YAML cache (run `mx sync pull` first) and lists every
+//! issue in the milestone with its status, estimate, assignees and blockers
+//! (see [`super::deps`]). An issue is done when closed, blocked while an open
+//! blocker holds it up, in progress once assigned, and to do otherwise.
+//!
+//! Estimates come from an `estimate` field in the YAML metadata, else from
+//! labels: `estimate:3`, `points:3`, `sp:3`, or a t-shirt size (`size:s`,
+//! with xs/s/m/l/xl as 1/2/3/5/8 points).
+//!
+//! `--publish wiki` writes the doc to a wiki page named after the milestone;
+//! `--publish discussion` opens a discussion in `--category`.
+
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+
+use crate::sync::commands::deps::Graph;
+use crate::sync::default_sync_dir;
+use crate::sync::github::auth::get_github_token;
+use crate::sync::github::graphql::GraphQLClient;
+use crate::sync::wiki;
+use crate::sync::yaml::schema::{ItemType, SyncYaml};
+use crate::sync::yaml::store::YamlStore;
+
+/// Points per t-shirt size label
+const SIZES: &[(&str, f64)] = &[("xs", 1.0), ("s", 2.0), ("m", 3.0), ("l", 5.0), ("xl", 8.0)];
+
+/// Label prefixes that carry a number of points
+const POINT_LABELS: &[&str] = &["estimate", "points", "sp"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PlanFormat {
+    Md,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Publish {
+    Wiki,
+    Discussion,
+}
+
+#[derive(clap::Args)]
+pub struct PlanArgs {
+    /// Repository (owner/repo format; defaults to the current git repo)
+    pub repo: Option<String>,
+
+    /// Milestone title
+    #[arg(long)]
+    pub milestone: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = PlanFormat::Md)]
+    pub output: PlanFormat,
+
+    /// Input directory (defaults to $MX_HOME/cache/sync/<repo>)
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Write the doc to this file instead of stdout
+    #[arg(long)]
+    pub file: Option<String>,
+
+    /// Also publish the markdown doc to GitHub
+    #[arg(long, value_enum)]
+    pub publish: Option<Publish>,
+
+    /// Discussion category slug for --publish discussion
+    #[arg(long, default_value = "general", requires = "publish")]
+    pub category: String,
+
+    /// Show what would be published without publishing
+    #[arg(long, requires = "publish")]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    InProgress,
+    ToDo,
+    Blocked,
+    Done,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::InProgress => "In progress",
+            Status::ToDo => "To do",
+            Status::Blocked => "Blocked",
+            Status::Done => "Done",
+        }
+    }
+}
+
+/// A blocker of a planned issue
+#[derive(Debug, serde::Serialize)]
+pub struct Blocker {
+    pub number: u64,
+    pub resolved: bool,
+}
+
+/// One row of the plan
+#[derive(Debug, serde::Serialize)]
+pub struct PlannedIssue {
+    pub number: u64,
+    pub title: String,
+    pub status: Status,
+    pub estimate: Option<f64>,
+    pub assignees: Vec<String>,
+    pub blocked_by: Vec<Blocker>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Plan {
+    pub repo: String,
+    pub milestone: String,
+    pub issues: Vec<PlannedIssue>,
+    /// Issues in the milestone that can start now
+    pub ready: Vec<u64>,
+    pub cycles: Vec<Vec<u64>>,
+}
+
+/// Points from the metadata `estimate` field or an estimate label
+pub fn estimate(yaml: &SyncYaml) -> Option<f64> {
+    if let Some(points) = yaml.metadata.estimate {
+        return Some(points);
+    }
+    yaml.labels().iter().find_map(|label| {
+        let (key, value) = label.split_once(':')?;
+        let (key, value) = (key.trim().to_lowercase(), value.trim().to_lowercase());
+        if key == "size" {
+            SIZES.iter().find(|(s, _)| *s == value).map(|(_, p)| *p)
+        } else if POINT_LABELS.contains(&key.as_str()) {
+            value
+                .parse()
+                .ok()
+                .filter(|p: &f64| p.is_finite() && *p >= 0.0)
+        } else {
+            None
+        }
+    })
+}
+
+/// The milestone's issues from the cached `items`, by number
+pub fn build(items: &[(PathBuf, SyncYaml)], repo: &str, milestone: &str) -> Plan {
+    let graph = Graph::from_cache(items, repo);
+    let same = |m: &str| m.trim().eq_ignore_ascii_case(milestone.trim());
+    // The title as GitHub spells it
+    let milestone = items
+        .iter()
+        .find_map(|(_, yaml)| yaml.metadata.milestone.as_deref().filter(|m| same(m)))
+        .unwrap_or(milestone);
+    let mut issues: Vec<PlannedIssue> = items
+        .iter()
+        .filter_map(|(_, yaml)| {
+            let (ItemType::Issue, Some(number)) = (yaml.item_type(), yaml.github_issue_number())
+            else {
+                return None;
+            };
+            if yaml.metadata.milestone.as_deref().is_none_or(|m| !same(m)) {
+                return None;
+            }
+            let blocked_by: Vec<Blocker> = graph
+                .blockers(number)
+                .map(|l| Blocker {
+                    number: l.blocked_by,
+                    resolved: graph.resolved(l),
+                })
+                .collect();
+            let open = yaml
+                .metadata
+                .state
+                .as_deref()
+                .is_none_or(|s| s.eq_ignore_ascii_case("open"));
+            let status = if !open {
+                Status::Done
+            } else if blocked_by.iter().any(|b| !b.resolved) {
+                Status::Blocked
+            } else if !yaml.assignees().is_empty() {
+                Status::InProgress
+            } else {
+                Status::ToDo
+            };
+            Some(PlannedIssue {
+                number,
+                title: yaml.title().to_string(),
+                status,
+                estimate: estimate(yaml),
+                assignees: yaml.assignees().to_vec(),
+                blocked_by,
+            })
+        })
+        .collect();
+    issues.sort_by_key(|i| i.number);
+
+    let ready = issues
+        .iter()
+        .filter(|i| matches!(i.status, Status::InProgress | Status::ToDo))
+        .map(|i| i.number)
+        .collect();
+    let cycles = graph
+        .cycles()
+        .into_iter()
+        .filter(|c| c.iter().any(|n| issues.iter().any(|i| i.number == *n)))
+        .collect();
+    Plan {
+        repo: repo.to_string(),
+        milestone: milestone.to_string(),
+        issues,
+        ready,
+        cycles,
+    }
+}
+
+/// The planning doc as markdown
+pub fn render(plan: &Plan) -> String {
+    let mut out = format!("# Plan: {}\n\n", plan.milestone);
+    out.push_str(&format!(
+        "_{} issue(s) in {}, from the local sync cache._\n\n",
+        plan.issues.len(),
+        plan.repo
+    ));
+    if plan.issues.is_empty() {
+        return out;
+    }
+
+    let total: f64 = plan.issues.iter().filter_map(|i| i.estimate).sum();
+    let remaining: f64 = plan
+        .issues
+        .iter()
+        .filter(|i| i.status != Status::Done)
+        .filter_map(|i| i.estimate)
+        .sum();
+    let unestimated = plan.issues.iter().filter(|i| i.estimate.is_none()).count();
+    out.push_str("## Summary\n\n");
+    for status in [
+        Status::Done,
+        Status::InProgress,
+        Status::ToDo,
+        Status::Blocked,
+    ] {
+        let count = plan.issues.iter().filter(|i| i.status == status).count();
+        out.push_str(&format!("- {}: {}\n", status.label(), count));
+    }
+    out.push_str(&format!(
+        "- Points: {} total, {} remaining",
+        total, remaining
+    ));
+    if unestimated > 0 {
+        out.push_str(&format!(" ({} issue(s) unestimated)", unestimated));
+    }
+    out.push_str("\n\n## Issues\n\n");
+
+    out.push_str("| Issue | Title | Status | Estimate | Assignees | Blocked by |\n");
+    out.push_str("|---|---|---|---|---|---|\n");
+    for issue in &plan.issues {
+        let blockers: Vec<String> = issue
+            .blocked_by
+            .iter()
+            .map(|b| {
+                if b.resolved {
+                    format!("~~#{}~~", b.number)
+                } else {
+                    format!("#{}", b.number)
+                }
+            })
+            .collect();
+        let assignees: Vec<String> = issue.assignees.iter().map(|a| format!("@{}", a)).collect();
+        out.push_str(&format!(
+            "| #{} | {} | {} | {} | {} | {} |\n",
+            issue.number,
+            issue.title.replace('|', "\\|"),
+            issue.status.label(),
+            issue
+                .estimate
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            assignees.join(", "),
+            blockers.join(", ")
+        ));
+    }
+
+    let refs = |numbers: &[u64], sep: &str| {
+        numbers
+            .iter()
+            .map(|n| format!("#{}", n))
+            .collect::<Vec<_>>()
+            .join(sep)
+    };
+    out.push_str("\n## Dependencies\n\n");
+    if plan.ready.is_empty() {
+        out.push_str("Ready to start: none\n");
+    } else {
+        out.push_str(&format!("Ready to start: {}\n", refs(&plan.ready, ", ")));
+    }
+    for cycle in &plan.cycles {
+        out.push_str(&format!("\n> **Cycle:** {}\n", refs(cycle, " <-> ")));
+    }
+    out
+}
+
+/// Run the plan command
+pub fn run(args: PlanArgs) -> Result<()> {
+    if args.publish.is_some() && args.output == PlanFormat::Json {
+        bail!("--publish posts the markdown doc; drop --output json");
+    }
+    let repo = crate::workspace::repo_or_current(args.repo)?;
+    let (owner, repo_name) = parse_repo(&repo)?;
+    let input_dir = args
+        .input
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_sync_dir(&repo));
+    if !input_dir.exists() {
+        bail!(
+            "No sync cache at {} (run `mx sync pull {}` first)",
+            input_dir.display(),
+            repo
+        );
+    }
+    let items = YamlStore::new(input_dir).read_all()?;
+    let plan = build(&items, &repo, &args.milestone);
+    if plan.issues.is_empty() {
+        bail!(
+            "No cached issues in milestone '{}' for {}",
+            args.milestone,
+            repo
+        );
+    }
+
+    let doc = match args.output {
+        PlanFormat::Md => render(&plan),
+        PlanFormat::Json => serde_json::to_string_pretty(&plan)? + "\n",
+    };
+    match &args.file {
+        Some(file) => {
+            std::fs::write(file, &doc)?;
+            println!("Wrote plan for '{}' to {}", plan.milestone, file);
+        }
+        None => print!("{}", doc),
+    }
+
+    let title = format!("Plan: {}", plan.milestone);
+    match args.publish {
+        None => {}
+        Some(Publish::Wiki) => {
+            let file = tempfile::Builder::new()
+                .prefix("mx-plan-")
+                .suffix(".md")
+                .tempfile()?;
+            std::fs::write(file.path(), &doc)?;
+            let source = file.path().to_string_lossy().to_string();
+            wiki::sync(&repo, &source, Some(&title), args.dry_run)?;
+        }
+        Some(Publish::Discussion) => {
+            if args.dry_run {
+                println!(
+                    "[DRY RUN] Would open discussion '{}' in {}/{} ({})",
+                    title, owner, repo_name, args.category
+                );
+                return Ok(());
+            }
+            let client = GraphQLClient::new(&get_github_token()?)?;
+            let Some(category) = client
+                .list_discussion_categories(&owner, &repo_name)?
+                .into_iter()
+                .find(|c| c.slug == args.category)
+            else {
+                bail!("Discussion category '{}' not found", args.category);
+            };
+            let repo_id = client.get_repository_id(&owner, &repo_name)?;
+            let discussion = client.create_discussion(&repo_id, &category.id, &title, &doc)?;
+            println!("Opened discussion D#{}: {}", discussion.number, title);
+        }
+    }
+    Ok(())
+}
+
+/// Parse owner/repo string
+fn parse_repo(repo: &str) -> Result<(String, String)> {
+    let parts: Vec<&str> = repo.split('/').collect();
+    if parts.len() != 2 {
+        anyhow::bail!("Repository must be in format 'owner/repo', got: {}", repo);
+    }
+    Ok((parts[0].to_string(), parts[1].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(number: u64, yaml: &str) -> (PathBuf, SyncYaml) {
+        let mut yaml: SyncYaml = serde_yaml::from_str(yaml).unwrap();
+        yaml.metadata.r#type = Some("issue".to_string());
+        yaml.metadata.github_issue_number = Some(number);
+        (PathBuf::from(format!("{}.yaml", number)), yaml)
+    }
+
+    #[test]
+    fn plans_milestone_with_estimates_and_status() {
+        let items = vec![
+            issue(
+                1,
+                "metadata:\n  title: Schema\n  milestone: v1\n  state: closed\n  labels: ['size:m']\nbody_markdown: ''\n",
+            ),
+            issue(
+                2,
+                "metadata:\n  title: API | routes\n  milestone: V1\n  estimate: 2.5\n  labels: ['points:8']\nbody_markdown: 'Blocked by #1'\n",
+            ),
+            issue(
+                3,
+                "metadata:\n  title: Docs\n  milestone: v1\n  assignees: [ana]\n  labels: ['sp:5']\nbody_markdown: ''\n",
+            ),
+            issue(
+                4,
+                "metadata:\n  title: UI\n  milestone: v1\nbody_markdown: 'Depends on #2, #9'\n",
+            ),
+            issue(
+                9,
+                "metadata:\n  title: Elsewhere\n  milestone: v2\n  labels: ['estimate:3']\nbody_markdown: ''\n",
+            ),
+        ];
+        assert_eq!(estimate(&items[4].1), Some(3.0));
+
+        let plan = build(&items, "o/r", "v1");
+        let rows: Vec<(u64, Status, Option<f64>)> = plan
+            .issues
+            .iter()
+            .map(|i| (i.number, i.status, i.estimate))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (1, Status::Done, Some(3.0)),
+                (2, Status::ToDo, Some(2.5)),
+                (3, Status::InProgress, Some(5.0)),
+                (4, Status::Blocked, None),
+            ]
+        );
+        assert_eq!(plan.ready, [2, 3]);
+
+        let doc = render(&plan);
+        assert!(doc.starts_with("# Plan: v1\n"));
+        assert!(doc.contains("- Points: 10.5 total, 7.5 remaining (1 issue(s) unestimated)"));
+        assert!(doc.contains("| #2 | API \\| routes | To do | 2.5 |  | ~~#1~~ |"));
+        assert!(doc.contains("| #3 | Docs | In progress | 5 | @ana |  |"));
+        assert!(doc.contains("| #4 | UI | Blocked | - |  | #2, #9 |"));
+        assert!(doc.contains("Ready to start: #2, #3"));
+    }
+}
@@ -1,17 +1,20 @@
 //! Pull command - download issues/discussions from GitHub to YAML
 
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::progress::{self, Checkpoint, Progress};
 use crate::sync::default_sync_dir;
 use crate::sync::github::auth::get_github_token;
+use crate::sync::github::fetch_all;
 use crate::sync::github::graphql::GraphQLClient;
-use crate::sync::github::rest::RestClient;
+use crate::sync::github::rest::{self, Issue, RestClient};
 use crate::sync::yaml::schema::{Comment, LastSynced, Metadata, SyncYaml, yaml_filename};
 use crate::sync::yaml::store::YamlStore;
 
 /// Run the pull command
-pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
+pub fn run(repo: &str, output: Option<String>, dry_run: bool, concurrency: usize) -> Result<()> {
     // Parse owner/repo
     let (owner, repo_name) = parse_repo(repo)?;
 
@@ -47,15 +50,62 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
     let mut discussions_updated = 0;
     let mut discussions_unchanged = 0;
 
-    // Fetch and process issues
+    // Fetch both lists up front so progress has a total
     println!("Fetching issues...");
     let issues = rest_client.list_issues(&owner, &repo_name, "open")?;
     println!("Found {} open issues", issues.len());
+    println!("Fetching discussions...");
+    let discussions = graphql_client.list_discussions(&owner, &repo_name)?;
+    println!("Found {} discussions", discussions.len());
     println!();
 
-    println!("Issues:");
+    // Items pulled by an interrupted run are skipped
+    progress::catch_interrupts();
+    let mut checkpoint = Checkpoint::load(&format!("pull-{}-{}", owner, repo_name))?;
+    if !checkpoint.done.is_empty() {
+        println!(
+            "Resuming: {} item(s) already pulled by an interrupted run",
+            checkpoint.done.len()
+        );
+        println!();
+    }
+    // Comments are one request per issue; fetch them side by side
+    let pending: Vec<&Issue> = issues
+        .iter()
+        .filter(|i| !checkpoint.contains(&format!("issue:{}", i.number)))
+        .collect();
+    let fetching = Progress::new(pending.len(), "Fetching comments");
+    let fetched = fetch_all(
+        &pending,
+        concurrency,
+        |issue| rest_client.list_issue_comments(&owner, &repo_name, issue.number),
+        || fetching.inc(),
+    )?;
+    fetching.finish();
+    let mut comments_by_issue: HashMap<u64, Vec<rest::Comment>> =
+        pending.iter().map(|i| i.number).zip(fetched).collect();
+
+    let total = issues.len() + discussions.len();
+    let progress = Progress::new(total, "Pulling");
+    let mut done = 0;
+    let mut resumed = 0;
+
+    progress.println("Issues:");
     for issue in &issues {
-        let comments = rest_client.list_issue_comments(&owner, &repo_name, issue.number)?;
+        if progress::interrupted() {
+            if !dry_run {
+                checkpoint.save()?;
+            }
+            return Err(progress::stopped("sync pull", done, total));
+        }
+        done += 1;
+        progress.inc();
+        let key = format!("issue:{}", issue.number);
+        if checkpoint.contains(&key) {
+            resumed += 1;
+            continue;
+        }
+        let comments = comments_by_issue.remove(&issue.number).unwrap_or_default();
         let existing = store.find_by_issue_number(issue.number)?;
         let filename = yaml_filename(issue.number, &issue.title);
 
@@ -72,10 +122,10 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
             });
 
             if local_changed {
-                println!(
+                progress.println(format!(
                     "  #{} {} → local changes, skipping",
                     issue.number, issue.title
-                );
+                ));
                 issues_unchanged += 1;
             } else {
                 yaml.metadata.title = Some(issue.title.clone());
@@ -83,6 +133,7 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
                 yaml.metadata.labels = remote_labels.clone();
                 yaml.metadata.assignees = remote_assignees.clone();
                 yaml.metadata.state = Some(issue.state.clone());
+                yaml.metadata.milestone = issue.milestone_title();
                 yaml.metadata.github_updated_at = Some(issue.updated_at.clone());
                 yaml.comments = comments
                     .iter()
@@ -103,20 +154,20 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
                 ));
 
                 if dry_run {
-                    println!(
+                    progress.println(format!(
                         "  #{} {} ({} comments) → would update",
                         issue.number,
                         issue.title,
                         comments.len()
-                    );
+                    ));
                 } else {
                     store.write(&path, &yaml)?;
-                    println!(
+                    progress.println(format!(
                         "  #{} {} ({} comments) → updated",
                         issue.number,
                         issue.title,
                         comments.len()
-                    );
+                    ));
                 }
                 issues_updated += 1;
             }
@@ -132,6 +183,7 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
                     labels: remote_labels.clone(),
                     assignees: remote_assignees.clone(),
                     state: Some(issue.state.clone()),
+                    milestone: issue.milestone_title(),
                     github_issue_number: Some(issue.number),
                     github_updated_at: Some(issue.updated_at.clone()),
                     last_synced: Some(LastSynced::new(
@@ -157,40 +209,48 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
             };
 
             if dry_run {
-                println!(
+                progress.println(format!(
                     "  #{} {} ({} comments) → would create {}",
                     issue.number,
                     issue.title,
                     comments.len(),
                     filename
-                );
+                ));
             } else {
                 store.write_new(&filename, &yaml)?;
-                println!(
+                progress.println(format!(
                     "  #{} {} ({} comments) → created {}",
                     issue.number,
                     issue.title,
                     comments.len(),
                     filename
-                );
+                ));
             }
             issues_created += 1;
         }
+        checkpoint.record(key);
     }
 
-    // Fetch and process discussions
-    println!();
-    println!("Fetching discussions...");
-    let discussions = graphql_client.list_discussions(&owner, &repo_name)?;
-    println!("Found {} discussions", discussions.len());
-    println!();
-
-    println!("Discussions:");
+    progress.println("");
+    progress.println("Discussions:");
     if discussions.is_empty() {
-        println!("  (none)");
+        progress.println("  (none)");
     }
 
     for discussion in &discussions {
+        if progress::interrupted() {
+            if !dry_run {
+                checkpoint.save()?;
+            }
+            return Err(progress::stopped("sync pull", done, total));
+        }
+        done += 1;
+        progress.inc();
+        let key = format!("discussion:{}", discussion.id);
+        if checkpoint.contains(&key) {
+            resumed += 1;
+            continue;
+        }
         let existing = store.find_by_discussion_id(&discussion.id)?;
         let filename = format!(
             "d{}-{}.yaml",
@@ -210,10 +270,10 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
             });
 
             if local_changed {
-                println!(
+                progress.println(format!(
                     "  D#{} {} → local changes, skipping",
                     discussion.number, discussion.title
-                );
+                ));
                 discussions_unchanged += 1;
             } else {
                 yaml.metadata.title = Some(discussion.title.clone());
@@ -246,20 +306,20 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
                 ));
 
                 if dry_run {
-                    println!(
+                    progress.println(format!(
                         "  D#{} {} ({} comments) → would update",
                         discussion.number,
                         discussion.title,
                         discussion.comments.nodes.len()
-                    );
+                    ));
                 } else {
                     store.write(&path, &yaml)?;
-                    println!(
+                    progress.println(format!(
                         "  D#{} {} ({} comments) → updated",
                         discussion.number,
                         discussion.title,
                         discussion.comments.nodes.len()
-                    );
+                    ));
                 }
                 discussions_updated += 1;
             }
@@ -305,25 +365,31 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
             };
 
             if dry_run {
-                println!(
+                progress.println(format!(
                     "  D#{} {} ({} comments) → would create {}",
                     discussion.number,
                     discussion.title,
                     discussion.comments.nodes.len(),
                     filename
-                );
+                ));
             } else {
                 store.write_new(&filename, &yaml)?;
-                println!(
+                progress.println(format!(
                     "  D#{} {} ({} comments) → created {}",
                     discussion.number,
                     discussion.title,
                     discussion.comments.nodes.len(),
                     filename
-                );
+                ));
             }
             discussions_created += 1;
         }
+        checkpoint.record(key);
+    }
+    progress.finish();
+
+    if !dry_run {
+        checkpoint.clear()?;
     }
 
     println!();
@@ -336,6 +402,9 @@ pub fn run(repo: &str, output: Option<String>, dry_run: bool) -> Result<()> {
         "  Discussions: {} created, {} updated, {} unchanged",
         discussions_created, discussions_updated, discussions_unchanged
     );
+    if resumed > 0 {
+        println!("  Resumed: {} pulled before the interruption", resumed);
+    }
 
     Ok(())
 }
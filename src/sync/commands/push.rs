@@ -7,6 +7,7 @@ use anyhow::Result;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::progress::{self, Progress};
 use crate::sync::default_sync_dir;
 use crate::sync::github::auth::get_github_token;
 use crate::sync::github::graphql::GraphQLClient;
@@ -59,8 +60,18 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
     let mut repo_id: Option<String> = None;
     let mut categories: Option<HashMap<String, String>> = None; // slug -> id
 
-    println!("Processing:");
-    for (path, yaml) in items {
+    // Each pushed file records its sync state as it goes, so a re-run after
+    // Ctrl-C only finds what's left
+    progress::catch_interrupts();
+    let total = items.len();
+    let progress = Progress::new(total, "Pushing");
+
+    progress.println("Processing:");
+    for (done, (path, yaml)) in items.into_iter().enumerate() {
+        if progress::interrupted() {
+            return Err(progress::stopped("sync push", done, total));
+        }
+        progress.inc();
         let title = yaml.title();
         let item_type = yaml.item_type();
 
@@ -79,11 +90,11 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
 
                     if needs_update {
                         if dry_run {
-                            println!(
+                            progress.println(format!(
                                 "  D#{} {} → would update",
                                 yaml.metadata.github_discussion_number.unwrap_or(0),
                                 title
-                            );
+                            ));
                         } else {
                             graphql_client.update_discussion(
                                 discussion_id,
@@ -102,19 +113,19 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                             ));
                             store.write(&path, &updated_yaml)?;
 
-                            println!(
+                            progress.println(format!(
                                 "  D#{} {} → updated",
                                 yaml.metadata.github_discussion_number.unwrap_or(0),
                                 title
-                            );
+                            ));
                         }
                         discussions_updated += 1;
                     } else {
-                        println!(
+                        progress.println(format!(
                             "  D#{} {} → unchanged",
                             yaml.metadata.github_discussion_number.unwrap_or(0),
                             title
-                        );
+                        ));
                         discussions_unchanged += 1;
                     }
                 } else {
@@ -132,10 +143,10 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                     let category_id = categories.as_ref().unwrap().get(category_slug);
 
                     if category_id.is_none() {
-                        println!(
+                        progress.println(format!(
                             "  {} → skipped (category '{}' not found)",
                             title, category_slug
-                        );
+                        ));
                         discussions_unchanged += 1;
                         continue;
                     }
@@ -143,10 +154,10 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                     let body = yaml.body();
 
                     if dry_run {
-                        println!(
+                        progress.println(format!(
                             "  {} → would create discussion in '{}'",
                             title, category_slug
-                        );
+                        ));
                     } else {
                         let discussion = graphql_client.create_discussion(
                             repo_id.as_ref().unwrap(),
@@ -182,10 +193,10 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                             std::fs::remove_file(&path).ok();
                         }
 
-                        println!(
+                        progress.println(format!(
                             "  {} → created D#{} ({})",
                             title, discussion.number, new_filename
-                        );
+                        ));
                     }
                     discussions_created += 1;
                 }
@@ -247,7 +258,7 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
 
                     if needs_update {
                         if dry_run {
-                            println!("  #{} {} → would update", number, title);
+                            progress.println(format!("  #{} {} → would update", number, title));
                         } else {
                             let req = UpdateIssueRequest {
                                 title: Some(merged.title.clone()),
@@ -268,11 +279,11 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                             ));
                             store.write(&path, &updated_yaml)?;
 
-                            println!("  #{} {} → updated", number, title);
+                            progress.println(format!("  #{} {} → updated", number, title));
                         }
                         issues_updated += 1;
                     } else {
-                        println!("  #{} {} → unchanged", number, title);
+                        progress.println(format!("  #{} {} → unchanged", number, title));
                         issues_unchanged += 1;
                     }
                 } else {
@@ -282,7 +293,7 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                     let assignees = yaml.assignees().to_vec();
 
                     if dry_run {
-                        println!("  {} → would create new issue", title);
+                        progress.println(format!("  {} → would create new issue", title));
                     } else {
                         let req = CreateIssueRequest {
                             title: title.to_string(),
@@ -312,16 +323,17 @@ pub fn run(repo: &str, input: Option<String>, dry_run: bool) -> Result<()> {
                             std::fs::remove_file(&path).ok();
                         }
 
-                        println!(
+                        progress.println(format!(
                             "  {} → created #{} ({})",
                             title, created_issue.number, new_filename
-                        );
+                        ));
                     }
                     issues_created += 1;
                 }
             }
         }
     }
+    progress.finish();
 
     println!();
     println!("Summary:");
@@ -0,0 +1,126 @@
+//! Status command - summarize the local YAML cache without touching GitHub
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::output::Table;
+use crate::sync::default_sync_dir;
+use crate::sync::yaml::schema::{ItemType, SyncYaml};
+use crate::sync::yaml::store::YamlStore;
+
+/// Local state of one YAML item relative to its last sync
+fn item_state(yaml: &SyncYaml) -> &'static str {
+    let linked = yaml.github_issue_number().is_some() || yaml.github_discussion_id().is_some();
+    if !linked {
+        return "new";
+    }
+    match yaml.last_synced() {
+        Some(base)
+            if yaml.title() == base.title
+                && yaml.body() == base.body
+                && yaml.labels() == base.labels.as_slice() =>
+        {
+            "synced"
+        }
+        Some(_) => "modified",
+        None => "untracked",
+    }
+}
+
+/// Run the status command
+pub fn run(repo: &str, input: Option<String>, json: bool) -> Result<()> {
+    let input_dir = input
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_sync_dir(repo));
+    let store = YamlStore::new(input_dir.clone());
+    let items = if input_dir.exists() {
+        store.read_all()?
+    } else {
+        Vec::new()
+    };
+
+    let rows: Vec<(String, &str, String, &str, &str)> = items
+        .iter()
+        .map(|(path, yaml)| {
+            let file = path
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let kind = match yaml.item_type() {
+                ItemType::Issue => "issue",
+                ItemType::Idea => "discussion",
+            };
+            let number = yaml
+                .github_issue_number()
+                .or(yaml.metadata.github_discussion_number)
+                .map(|n| format!("#{}", n))
+                .unwrap_or_else(|| "-".to_string());
+            (file, kind, number, item_state(yaml), yaml.title())
+        })
+        .collect();
+
+    if json {
+        let out: Vec<serde_json::Value> = rows
+            .iter()
+            .map(|(file, kind, number, state, title)| {
+                serde_json::json!({
+                    "file": file,
+                    "type": kind,
+                    "number": number,
+                    "state": state,
+                    "title": title,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    println!("Sync cache: {}", input_dir.display());
+    if rows.is_empty() {
+        println!("No local items (run `mx sync pull {}` first)", repo);
+        return Ok(());
+    }
+    println!();
+
+    let mut table = Table::new(["NUMBER", "TYPE", "STATE", "TITLE", "FILE"]);
+    for (file, kind, number, state, title) in &rows {
+        table.row([number.as_str(), kind, state, title, file.as_str()]);
+    }
+    table.print();
+
+    let count = |s: &str| rows.iter().filter(|r| r.3 == s).count();
+    println!(
+        "\n{} synced, {} modified, {} new, {} untracked",
+        count("synced"),
+        count("modified"),
+        count("new"),
+        count("untracked")
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::yaml::schema::LastSynced;
+
+    #[test]
+    fn classifies_items_against_last_sync() {
+        let mut yaml = SyncYaml {
+            title: Some("Fix retries".to_string()),
+            body: Some("Body".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(item_state(&yaml), "new");
+
+        yaml.metadata.github_issue_number = Some(7);
+        assert_eq!(item_state(&yaml), "untracked");
+
+        yaml.metadata.last_synced = Some(LastSynced::new("Fix retries", "Body", vec![], "", None));
+        assert_eq!(item_state(&yaml), "synced");
+
+        yaml.body = Some("Edited".to_string());
+        assert_eq!(item_state(&yaml), "modified");
+    }
+}
@@ -12,6 +12,8 @@ use std::env;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use super::CheckStatus;
+
 /// JWT claims for GitHub App authentication
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
@@ -151,13 +153,8 @@ pub fn get_installation_token() -> Result<String> {
         .header("Accept", "application/vnd.github+json")
         .header("User-Agent", "mx-cli")
         .send()
-        .context("Failed to request installation token")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        anyhow::bail!("GitHub API returned error {}: {}", status, body);
-    }
+        .context("Failed to request installation token")?
+        .check()?;
 
     let token_response: InstallationToken = response
         .json()
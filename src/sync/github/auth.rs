@@ -8,6 +8,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::error::MxError;
+
 /// Claude configuration file structure (partial)
 #[derive(Debug, Deserialize)]
 struct ClaudeConfig {
@@ -64,11 +66,11 @@ pub fn get_github_token() -> Result<String> {
         }
     }
 
-    anyhow::bail!(
-        "GitHub token not found in {}\n\
-         Expected path: projects.<project>.mcpServers.github.env.GITHUB_PERSONAL_ACCESS_TOKEN",
-        config_path.display()
-    )
+    Err(MxError::AuthFailure {
+        service: "GitHub".into(),
+        message: format!("no token found in {}", config_path.display()),
+    }
+    .into())
 }
 
 #[cfg(test)]
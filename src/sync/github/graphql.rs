@@ -8,9 +8,30 @@ use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_
 use serde::Deserialize;
 use serde_json::json;
 
+use super::CheckStatus;
+use crate::slow_log;
+
 const GITHUB_GRAPHQL_URL: &str = "https://api.github.com/graphql";
 const USER_AGENT_VALUE: &str = "mx-sync/0.1";
 
+/// The first field a query selects and its variables, bodies elided, for
+/// the slow-op log
+fn describe(query: &str, variables: &serde_json::Value) -> String {
+    let field: String = query
+        .split_once('{')
+        .map_or("", |(_, rest)| rest)
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    let mut variables = variables.clone();
+    if let Some(body) = variables.get_mut("body") {
+        let len = body.as_str().map_or(0, str::len);
+        *body = json!(format!("<{} bytes>", len));
+    }
+    format!("{} {}", field, variables)
+}
+
 /// GitHub GraphQL API client
 pub struct GraphQLClient {
     client: Client,
@@ -49,20 +70,17 @@ impl GraphQLClient {
             "variables": variables
         });
 
-        let response = self
-            .client
-            .post(GITHUB_GRAPHQL_URL)
-            .json(&body)
-            .send()
-            .context("Failed to execute GraphQL query")?;
+        let response = slow_log::time(
+            "github graphql",
+            || describe(query, &body["variables"]),
+            || self.client.post(GITHUB_GRAPHQL_URL).json(&body).send(),
+        )
+        .context("Failed to execute GraphQL query")?
+        .check()
+        .context("GraphQL request failed")?;
 
-        let status = response.status();
         let text = response.text().context("Failed to read response")?;
 
-        if !status.is_success() {
-            anyhow::bail!("GraphQL request failed ({}): {}", status, text);
-        }
-
         let result: GraphQLResponse<T> = serde_json::from_str(&text)
             .with_context(|| format!("Failed to parse GraphQL response: {}", text))?;
 
@@ -4,3 +4,323 @@ pub mod app_auth;
 pub mod auth;
 pub mod graphql;
 pub mod rest;
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::HeaderMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::error::MxError;
+use crate::progress;
+use crate::slow_log;
+
+/// Requests in flight for [`fetch_all`] unless asked otherwise
+pub const DEFAULT_CONCURRENCY: usize = 8;
+/// Upper bound on requests in flight; GitHub's secondary limits punish more
+pub const MAX_CONCURRENCY: usize = 16;
+/// Longest rate-limit reset worth waiting out instead of failing
+const MAX_RATE_WAIT: Duration = Duration::from_secs(15 * 60);
+/// Wait when a limited response doesn't say for how long
+const DEFAULT_RATE_WAIT: Duration = Duration::from_secs(60);
+
+/// Status check for GitHub responses, in place of `error_for_status`
+pub trait CheckStatus: Sized {
+    /// The response if it succeeded. A rejected token becomes
+    /// [`MxError::AuthFailure`] and an exhausted quota
+    /// [`MxError::RateLimited`]; other failures carry GitHub's message.
+    fn check(self) -> Result<Self>;
+}
+
+impl CheckStatus for Response {
+    fn check(self) -> Result<Self> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+        let headers = self.headers().clone();
+        let body = self.text().unwrap_or_default();
+        Err(failure(
+            status,
+            &headers,
+            &body,
+            chrono::Utc::now().timestamp(),
+        ))
+    }
+}
+
+/// `send`, logging calls slower than the slow-op threshold by method and URL
+pub trait SendTimed {
+    fn send_timed(self) -> reqwest::Result<Response>;
+}
+
+impl SendTimed for RequestBuilder {
+    fn send_timed(self) -> reqwest::Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+        let params = format!("{} {}", request.method(), request.url());
+        slow_log::time("github", || params, || client.execute(request))
+    }
+}
+
+fn failure(status: StatusCode, headers: &HeaderMap, body: &str, now: i64) -> anyhow::Error {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string());
+
+    if status == StatusCode::UNAUTHORIZED {
+        return MxError::AuthFailure {
+            service: "GitHub".into(),
+            message,
+        }
+        .into();
+    }
+    // Primary limits zero the remaining quota; secondary ones send Retry-After
+    let exhausted = header("x-ratelimit-remaining") == Some(0);
+    let retry = header("retry-after");
+    if (status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS)
+        && (exhausted || retry.is_some())
+    {
+        let retry_after = retry
+            .or_else(|| header("x-ratelimit-reset").map(|reset| reset - now))
+            .map(|secs| secs.max(0) as u64);
+        return MxError::RateLimited {
+            service: "GitHub".into(),
+            retry_after,
+        }
+        .into();
+    }
+    anyhow::anyhow!("GitHub API returned {}: {}", status, message)
+}
+
+/// Run `fetch` over `items` with at most `concurrency` (clamped to
+/// 1..=[`MAX_CONCURRENCY`]) calls in flight, returning results in input order.
+///
+/// A rate-limited call pauses every worker until the quota resets, then is
+/// retried; a reset further away than [`MAX_RATE_WAIT`] fails the batch. Any
+/// other error stops workers from taking new items and is returned once the
+/// calls in flight finish, as is Ctrl-C (see [`progress::catch_interrupts`]).
+/// `done` is called after each item.
+pub fn fetch_all<T, R>(
+    items: &[T],
+    concurrency: usize,
+    fetch: impl Fn(&T) -> Result<R> + Sync,
+    done: impl Fn() + Sync,
+) -> Result<Vec<R>>
+where
+    T: Sync,
+    R: Send,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let paused_until: Mutex<Option<Instant>> = Mutex::new(None);
+    let results: Mutex<Vec<Option<Result<R>>>> =
+        Mutex::new(std::iter::repeat_with(|| None).take(items.len()).collect());
+
+    let worker = || {
+        loop {
+            if stop.load(Ordering::SeqCst) || progress::interrupted() {
+                return;
+            }
+            let i = next.fetch_add(1, Ordering::SeqCst);
+            let Some(item) = items.get(i) else {
+                return;
+            };
+            let result = loop {
+                let resume = *paused_until.lock().unwrap();
+                if let Some(at) = resume {
+                    std::thread::sleep(at.saturating_duration_since(Instant::now()));
+                }
+                match fetch(item) {
+                    Err(e) => match e.downcast_ref::<MxError>() {
+                        Some(MxError::RateLimited { retry_after, .. }) => {
+                            let wait = retry_after.map_or(DEFAULT_RATE_WAIT, Duration::from_secs);
+                            if wait > MAX_RATE_WAIT {
+                                break Err(e);
+                            }
+                            let at = Instant::now() + wait;
+                            let mut paused = paused_until.lock().unwrap();
+                            if paused.is_none_or(|p| p < at) {
+                                *paused = Some(at);
+                            }
+                        }
+                        _ => break Err(e),
+                    },
+                    ok => break ok,
+                }
+            };
+            if result.is_err() {
+                stop.store(true, Ordering::SeqCst);
+            }
+            results.lock().unwrap()[i] = Some(result);
+            done();
+        }
+    };
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, MAX_CONCURRENCY).min(items.len()) {
+            scope.spawn(worker);
+        }
+    });
+
+    let results = results.into_inner().unwrap();
+    let finished = results.iter().filter(|r| r.is_some()).count();
+    let mut out = Vec::with_capacity(items.len());
+    for result in results {
+        match result {
+            Some(result) => out.push(result?),
+            None if progress::interrupted() => {
+                return Err(progress::stopped("fetch", finished, items.len()));
+            }
+            None => continue,
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn failures_are_classified() {
+        let bad_token = failure(
+            StatusCode::UNAUTHORIZED,
+            &HeaderMap::new(),
+            r#"{"message":"Bad credentials"}"#,
+            0,
+        );
+        assert!(matches!(
+            bad_token.downcast_ref::<MxError>(),
+            Some(MxError::AuthFailure { message, .. }) if message == "Bad credentials"
+        ));
+
+        let quota = failure(
+            StatusCode::FORBIDDEN,
+            &headers(&[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "1700000300"),
+            ]),
+            "{}",
+            1_700_000_000,
+        );
+        assert!(matches!(
+            quota.downcast_ref::<MxError>(),
+            Some(MxError::RateLimited {
+                retry_after: Some(300),
+                ..
+            })
+        ));
+
+        let secondary = failure(
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers(&[("retry-after", "60")]),
+            "",
+            0,
+        );
+        assert_eq!(secondary.downcast_ref::<MxError>().unwrap().exit_code(), 6);
+
+        // A 403 with quota left is a permission problem, not a limit
+        let forbidden = failure(
+            StatusCode::FORBIDDEN,
+            &headers(&[("x-ratelimit-remaining", "4999")]),
+            r#"{"message":"Resource not accessible by integration"}"#,
+            0,
+        );
+        assert!(forbidden.downcast_ref::<MxError>().is_none());
+        assert!(
+            forbidden
+                .to_string()
+                .contains("Resource not accessible by integration")
+        );
+    }
+
+    #[test]
+    fn fetches_are_bounded_ordered_and_wait_out_limits() {
+        let items: Vec<u64> = (0..40).collect();
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let limited_once = AtomicBool::new(false);
+        let done = AtomicUsize::new(0);
+
+        let results = fetch_all(
+            &items,
+            4,
+            |&n| {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if n == 7 && !limited_once.swap(true, Ordering::SeqCst) {
+                    return Err(MxError::RateLimited {
+                        service: "GitHub".into(),
+                        retry_after: Some(0),
+                    }
+                    .into());
+                }
+                Ok(n * 2)
+            },
+            || {
+                done.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .unwrap();
+
+        assert_eq!(results, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert!(limited_once.load(Ordering::SeqCst));
+        assert_eq!(done.load(Ordering::SeqCst), 40);
+
+        // Other errors fail the batch
+        let failed = fetch_all(
+            &items,
+            8,
+            |&n| {
+                if n == 3 {
+                    anyhow::bail!("boom");
+                }
+                Ok(n)
+            },
+            || {},
+        );
+        assert_eq!(failed.unwrap_err().to_string(), "boom");
+
+        // A reset too far away isn't waited out
+        let far = fetch_all(
+            &items[..1],
+            1,
+            |_| -> Result<()> {
+                Err(MxError::RateLimited {
+                    service: "GitHub".into(),
+                    retry_after: Some(3600),
+                }
+                .into())
+            },
+            || {},
+        );
+        assert_eq!(
+            far.unwrap_err()
+                .downcast_ref::<MxError>()
+                .unwrap()
+                .exit_code(),
+            6
+        );
+    }
+}
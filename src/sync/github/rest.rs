@@ -7,6 +7,8 @@ use reqwest::blocking::Client;
 use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
 use serde::{Deserialize, Serialize};
 
+use super::{CheckStatus, SendTimed};
+
 const GITHUB_API_BASE: &str = "https://api.github.com";
 const USER_AGENT_VALUE: &str = "mx-sync/0.1";
 
@@ -59,9 +61,9 @@ impl RestClient {
             let response: Vec<Issue> = self
                 .client
                 .get(&url)
-                .send()
+                .send_timed()
                 .context("Failed to fetch issues")?
-                .error_for_status()
+                .check()
                 .context("GitHub API error")?
                 .json()
                 .context("Failed to parse issues response")?;
@@ -95,9 +97,9 @@ impl RestClient {
 
         self.client
             .get(&url)
-            .send()
+            .send_timed()
             .context("Failed to fetch issue")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse issue response")
@@ -110,9 +112,9 @@ impl RestClient {
         self.client
             .post(&url)
             .json(req)
-            .send()
+            .send_timed()
             .context("Failed to create issue")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse create issue response")
@@ -134,9 +136,9 @@ impl RestClient {
         self.client
             .patch(&url)
             .json(req)
-            .send()
+            .send_timed()
             .context("Failed to update issue")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse update issue response")
@@ -156,9 +158,9 @@ impl RestClient {
             let response: Vec<Label> = self
                 .client
                 .get(&url)
-                .send()
+                .send_timed()
                 .context("Failed to fetch labels")?
-                .error_for_status()
+                .check()
                 .context("GitHub API error")?
                 .json()
                 .context("Failed to parse labels response")?;
@@ -187,9 +189,9 @@ impl RestClient {
         self.client
             .post(&url)
             .json(req)
-            .send()
+            .send_timed()
             .context("Failed to create label")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse create label response")
@@ -214,9 +216,9 @@ impl RestClient {
         self.client
             .patch(&url)
             .json(req)
-            .send()
+            .send_timed()
             .context("Failed to update label")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse update label response")
@@ -241,9 +243,9 @@ impl RestClient {
             let response: Vec<Comment> = self
                 .client
                 .get(&url)
-                .send()
+                .send_timed()
                 .context("Failed to fetch comments")?
-                .error_for_status()
+                .check()
                 .context("GitHub API error")?
                 .json()
                 .context("Failed to parse comments response")?;
@@ -274,9 +276,9 @@ impl RestClient {
         self.client
             .post(url)
             .json(body)
-            .send()
+            .send_timed()
             .context("Failed to execute POST request")?
-            .error_for_status()
+            .check()
             .context("GitHub API error")?
             .json()
             .context("Failed to parse JSON response")
@@ -298,6 +300,8 @@ pub struct Issue {
     pub assignees: Vec<UserRef>,
     pub updated_at: String,
     pub pull_request: Option<PullRequestRef>,
+    #[serde(default)]
+    pub milestone: Option<MilestoneRef>,
 }
 
 impl Issue {
@@ -308,6 +312,10 @@ impl Issue {
     pub fn assignee_logins(&self) -> Vec<String> {
         self.assignees.iter().map(|a| a.login.clone()).collect()
     }
+
+    pub fn milestone_title(&self) -> Option<String> {
+        self.milestone.as_ref().map(|m| m.title.clone())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -320,6 +328,11 @@ pub struct UserRef {
     pub login: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct MilestoneRef {
+    pub title: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct PullRequestRef {
     pub url: String,
@@ -5,16 +5,24 @@
 //! - Push: YAML → GitHub
 //! - Labels: Sync identity labels
 //! - Issues: Bidirectional sync
+//! - Status: Local cache state (new / modified / synced)
+//! - Deps: Dependency graph of cached issues
+//! - Plan: Milestone planning doc
 
+#[cfg(feature = "github")]
 pub mod commands;
+#[cfg(feature = "github")]
 pub mod github;
 pub mod merge;
+#[cfg(feature = "github")]
 pub mod wiki;
 pub mod yaml;
 
+#[cfg(feature = "github")]
 use anyhow::Result;
 use std::path::PathBuf;
 
+#[cfg(feature = "github")]
 use crate::SyncCommands;
 
 /// Default sync cache directory for a repo.
@@ -29,22 +37,50 @@ pub fn artifacts_dir() -> PathBuf {
     crate::paths::artifacts_dir()
 }
 
+#[cfg(feature = "github")]
 pub fn handle_sync(cmd: SyncCommands) -> Result<()> {
     match cmd {
         SyncCommands::Pull {
             repo,
             output,
             dry_run,
-        } => commands::pull::run(&repo, output, dry_run),
+            concurrency,
+        } => {
+            let repo = crate::workspace::repo_or_current(repo)?;
+            commands::pull::run(&repo, output, dry_run, concurrency)?;
+            if !dry_run {
+                crate::notify::sync_completed("pull", &repo);
+            }
+            Ok(())
+        }
 
         SyncCommands::Push {
             repo,
             input,
             dry_run,
-        } => commands::push::run(&repo, input, dry_run),
+        } => {
+            let repo = crate::workspace::repo_or_current(repo)?;
+            commands::push::run(&repo, input, dry_run)?;
+            if !dry_run {
+                crate::notify::sync_completed("push", &repo);
+            }
+            Ok(())
+        }
 
-        SyncCommands::Labels { repo, dry_run } => commands::labels::run(&repo, dry_run),
+        SyncCommands::Labels { repo, dry_run } => {
+            commands::labels::run(&crate::workspace::repo_or_current(repo)?, dry_run)
+        }
 
-        SyncCommands::Issues { repo, dry_run } => commands::issues::run(&repo, dry_run),
+        SyncCommands::Issues { repo, dry_run } => {
+            commands::issues::run(&crate::workspace::repo_or_current(repo)?, dry_run)
+        }
+
+        SyncCommands::Status { repo, input, json } => {
+            commands::status::run(&crate::workspace::repo_or_current(repo)?, input, json)
+        }
+
+        SyncCommands::Deps(args) => commands::deps::run(args),
+
+        SyncCommands::Plan(args) => commands::plan::run(args),
     }
 }
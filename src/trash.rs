@@ -0,0 +1,261 @@
+//! Trash: `mx memory delete --soft` and `mx memory trash list|restore|purge`
+//!
+//! A soft delete sets the entry's `archived_at` instead of removing it. The
+//! store leaves trashed entries out of every visibility-filtered query, so
+//! search, list, show and the wake cascade stop seeing them, but their
+//! tags, relationships and history stay until the trash is purged.
+//!
+//! Deletes are hard unless `config.yaml` makes soft the default (`--hard`
+//! still removes an entry outright):
+//!
+//! ```yaml
+//! trash:
+//!   soft_delete: true
+//! ```
+
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Utc};
+use colored::Colorize;
+use serde::Deserialize;
+use serde_yaml::Mapping;
+
+use crate::error::MxError;
+use crate::output::Table;
+use crate::store::{AgentContext, KnowledgeStore};
+use crate::{config, confirm, dates, paths};
+
+const TRASH_KEY: &str = "trash";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    #[serde(default)]
+    soft_delete: bool,
+}
+
+fn settings_from(config: &Mapping) -> Result<Settings> {
+    let Some(value) = config.get(TRASH_KEY) else {
+        return Ok(Settings::default());
+    };
+    serde_yaml::from_value(value.clone()).context("Invalid 'trash' section in config.yaml")
+}
+
+/// Whether `mx memory delete` without `--soft` or `--hard` moves to the trash
+pub fn soft_by_default() -> Result<bool> {
+    Ok(settings_from(&config::load(&paths::config_file())?)?.soft_delete)
+}
+
+#[derive(clap::Args)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub command: TrashCommands,
+}
+
+#[derive(clap::Subcommand)]
+pub enum TrashCommands {
+    /// List trashed entries, most recently trashed first
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Put trashed entries back
+    Restore {
+        /// Entry IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+
+    /// Delete trashed entries for good (the whole trash unless IDs are given)
+    Purge {
+        /// Entry IDs
+        ids: Vec<String>,
+
+        /// Only entries trashed more than this many days ago
+        #[arg(long)]
+        older_than: Option<i64>,
+
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+/// Move one entry to the trash, refusing sealed identity entries as a
+/// hard delete would
+pub fn archive(db: &dyn KnowledgeStore, ctx: &AgentContext, id: &str) -> Result<()> {
+    let Some(entry) = db.get(id, ctx)? else {
+        bail!(MxError::not_found("Entry", id));
+    };
+    if crate::identity::is_sealed(&entry) {
+        bail!(
+            "Entry {} is sealed in the identity namespace; release it first (mx memory identity release)",
+            id
+        );
+    }
+    if !db.archive(id, ctx)? {
+        bail!(MxError::not_found("Entry", id));
+    }
+    Ok(())
+}
+
+pub fn run(db: &dyn KnowledgeStore, ctx: &AgentContext, args: TrashArgs) -> Result<()> {
+    match args.command {
+        TrashCommands::List { json } => {
+            let entries = db.list_archived(ctx)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+                return Ok(());
+            }
+            if entries.is_empty() {
+                println!("Trash is empty");
+                return Ok(());
+            }
+            let mut table = Table::new(["ID", "TRASHED", "CATEGORY", "TITLE"]);
+            for entry in &entries {
+                table.row([
+                    entry.id.clone(),
+                    entry
+                        .archived_at
+                        .as_deref()
+                        .map(dates::display)
+                        .unwrap_or_default(),
+                    entry.category_id.clone(),
+                    entry.title.clone(),
+                ]);
+            }
+            table.print();
+        }
+
+        TrashCommands::Restore { ids } => {
+            for id in ids {
+                let id = crate::normalize_id(&id);
+                if !db.restore(&id, ctx)? {
+                    bail!(MxError::not_found("Trashed entry", id));
+                }
+                println!("{} Restored {}", "✓".green(), id);
+            }
+        }
+
+        TrashCommands::Purge {
+            ids,
+            older_than,
+            yes,
+        } => {
+            let mut entries = db.list_archived(ctx)?;
+            if !ids.is_empty() {
+                let ids: Vec<String> = ids.iter().map(|id| crate::normalize_id(id)).collect();
+                if let Some(missing) = ids.iter().find(|id| !entries.iter().any(|e| &e.id == *id)) {
+                    bail!(MxError::not_found("Trashed entry", missing.clone()));
+                }
+                entries.retain(|e| ids.contains(&e.id));
+            }
+            if let Some(days) = older_than {
+                let cutoff = Utc::now() - Duration::days(days);
+                entries.retain(|e| {
+                    e.archived_at
+                        .as_deref()
+                        .and_then(dates::parse_stored)
+                        .is_some_and(|at| at < cutoff)
+                });
+            }
+            if entries.is_empty() {
+                println!("Nothing to purge");
+                return Ok(());
+            }
+
+            let preview: Vec<String> = entries
+                .iter()
+                .map(|e| format!("{}  {}", e.id, e.title))
+                .collect();
+            if !confirm::confirm(
+                "Purge",
+                &[(entries.len(), "entry", "entries")],
+                &preview,
+                yes,
+            )? {
+                println!("Cancelled");
+                return Ok(());
+            }
+
+            let agent = ctx.agent_id.as_deref();
+            let mut purged = 0;
+            for entry in &entries {
+                let _ = db
+                    .backup_content(entry, "delete", agent)
+                    .map_err(|e| eprintln!("Warning: failed to create backup: {}", e));
+                if db.purge_archived(&entry.id, ctx)? {
+                    purged += 1;
+                }
+            }
+            println!(
+                "{} Purged {} entr{} from the trash",
+                "✓".green(),
+                purged,
+                if purged == 1 { "y" } else { "ies" }
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knowledge::KnowledgeEntry;
+
+    #[test]
+    fn soft_delete_is_opt_in() {
+        assert!(!settings_from(&Mapping::new()).unwrap().soft_delete);
+        let config: Mapping = serde_yaml::from_str("trash:\n  soft_delete: true\n").unwrap();
+        assert!(settings_from(&config).unwrap().soft_delete);
+        let config: Mapping = serde_yaml::from_str("trash:\n  soft: true\n").unwrap();
+        assert!(settings_from(&config).is_err());
+    }
+
+    #[test]
+    fn trashed_entries_leave_queries_until_restored() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        let ctx = AgentContext::public_only();
+        for id in ["kn-keep", "kn-drop"] {
+            let entry: KnowledgeEntry = serde_json::from_value(serde_json::json!({
+                "id": id,
+                "category_id": "pattern",
+                "title": format!("Retry {}", id),
+                "body": "backoff",
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-01T00:00:00Z",
+            }))
+            .unwrap();
+            db.upsert_knowledge(&entry).unwrap();
+        }
+        let listed = |db: &dyn KnowledgeStore| -> Vec<String> {
+            db.list_all(&ctx)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect()
+        };
+
+        archive(&db, &ctx, "kn-drop").unwrap();
+        assert_eq!(listed(&db), vec!["kn-keep"]);
+        assert!(db.get("kn-drop", &ctx).unwrap().is_none());
+        assert_eq!(db.count().unwrap(), 1);
+        let trash = db.list_archived(&ctx).unwrap();
+        assert_eq!(trash.len(), 1);
+        assert!(trash[0].archived_at.is_some());
+        assert!(!db.archive("kn-drop", &ctx).unwrap());
+
+        assert!(db.restore("kn-drop", &ctx).unwrap());
+        assert_eq!(listed(&db), vec!["kn-drop", "kn-keep"]);
+        assert!(db.list_archived(&ctx).unwrap().is_empty());
+        assert!(!db.restore("kn-drop", &ctx).unwrap());
+
+        archive(&db, &ctx, "kn-drop").unwrap();
+        assert!(!db.purge_archived("kn-keep", &ctx).unwrap());
+        assert!(db.purge_archived("kn-drop", &ctx).unwrap());
+        assert!(db.list_archived(&ctx).unwrap().is_empty());
+        assert_eq!(listed(&db), vec!["kn-keep"]);
+    }
+}
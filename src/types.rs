@@ -85,6 +85,90 @@ pub struct MemoryBackup {
     pub source_agent: Option<String>,
     #[serde(default)]
     pub created_at: Option<String>,
+    /// Revision number within the entry (1 for its first backup); stable
+    #[serde(default)]
+    pub rev: Option<u32>,
+}
+
+/// Local command log entry (opt-in usage history, never leaves the machine)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLogEntry {
+    #[serde(default)]
+    pub id: String,
+    pub command: String,
+    pub duration_ms: i64,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// Spaced-repetition state for one entry, per agent (SM-2)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewCard {
+    pub entry_id: String,
+    pub agent: String,
+    /// SM-2 easiness factor, never below 1.3
+    pub ease: f64,
+    pub interval_days: i64,
+    /// Consecutive successful recalls
+    pub repetitions: i64,
+    pub next_review: String,
+    #[serde(default)]
+    pub last_reviewed: Option<String>,
+}
+
+/// A tag and how many entries carry it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagCount {
+    pub name: String,
+    pub entries: usize,
+}
+
+/// When an agent last viewed an entry, and how often
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntryView {
+    pub entry_id: String,
+    pub agent: String,
+    pub views: i64,
+    pub viewed_at: String,
+}
+
+/// One record of the change feed (`mx memory changes`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change {
+    /// Cursor: strictly increasing, no gaps
+    pub seq: i64,
+    /// `created`, `updated`, `deleted`, `related` or `unrelated`
+    pub kind: String,
+    pub entry_id: String,
+    /// Other end of a relationship change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationship_type: Option<String>,
+    pub at: String,
+}
+
+/// Item on the reading / processing queue: a URL to ingest or an entry to curate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueueItem {
+    pub id: String,
+    /// A URL or an entry id
+    pub target: String,
+    /// `url` or `entry`
+    pub kind: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Higher is read first
+    pub priority: i64,
+    #[serde(default)]
+    pub added_at: Option<String>,
+    #[serde(default)]
+    pub done_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,235 @@
+//! Local command history and usage statistics
+//!
+//! Opt-in via `MX_HISTORY=1`. When enabled, every CLI invocation records its
+//! command path (e.g. `memory search`, never the arguments), duration, and
+//! success/failure in the local store. A failure is recorded by its kind
+//! (`not_found`, `conflict`, ... or plain `error`), never its message, which
+//! can quote the arguments. Nothing leaves the machine.
+//!
+//! Read back with `mx history` and `mx stats usage`.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::error::{self, MxError};
+use crate::index::IndexConfig;
+use crate::store::{self, KnowledgeStore};
+use crate::types::CommandLogEntry;
+
+/// Whether command logging is enabled (`MX_HISTORY` = 1/true/on/yes)
+pub fn is_enabled() -> bool {
+    std::env::var("MX_HISTORY")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "on" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Record a finished command. Failures to log are reported but never change
+/// the command's own outcome.
+pub fn record(command: &str, elapsed: Duration, result: &Result<()>) {
+    if !is_enabled() || command.is_empty() {
+        return;
+    }
+
+    let entry = CommandLogEntry {
+        id: String::new(),
+        command: command.to_string(),
+        duration_ms: elapsed.as_millis() as i64,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| error_kind(e).to_string()),
+        created_at: None,
+    };
+
+    let config = IndexConfig::default();
+    if let Err(e) = store::create_store(&config.db_path).and_then(|db| db.record_command(&entry)) {
+        eprintln!("Warning: failed to record command history: {}", e);
+    }
+}
+
+/// What a failure is recorded as: its `MxError` kind, or "error"
+fn error_kind(err: &anyhow::Error) -> &'static str {
+    error::classify(err).map_or("error", MxError::kind)
+}
+
+/// Aggregated statistics for one command path
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UsageSummary {
+    pub command: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub avg_ms: i64,
+    pub max_ms: i64,
+}
+
+/// Group log entries by command, most-run first
+pub fn summarize(entries: &[CommandLogEntry]) -> Vec<UsageSummary> {
+    let mut groups: BTreeMap<&str, Vec<&CommandLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        groups
+            .entry(entry.command.as_str())
+            .or_default()
+            .push(entry);
+    }
+
+    let mut summaries: Vec<UsageSummary> = groups
+        .into_iter()
+        .map(|(command, runs)| {
+            let total: i64 = runs.iter().map(|e| e.duration_ms).sum();
+            UsageSummary {
+                command: command.to_string(),
+                runs: runs.len(),
+                failures: runs.iter().filter(|e| !e.success).count(),
+                avg_ms: total / runs.len() as i64,
+                max_ms: runs.iter().map(|e| e.duration_ms).max().unwrap_or(0),
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| b.runs.cmp(&a.runs).then(a.command.cmp(&b.command)));
+    summaries
+}
+
+/// `mx history`
+pub fn show_history(limit: usize, failed_only: bool, json: bool) -> Result<()> {
+    let db = open_store()?;
+    let entries = db.list_command_log(Some(limit), None, failed_only)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        print_empty_hint();
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let status = if entry.success { "ok  " } else { "FAIL" };
+        println!(
+            "{}  {}  {:>7}ms  {}",
+            entry.created_at.as_deref().unwrap_or("unknown"),
+            status,
+            entry.duration_ms,
+            entry.command
+        );
+        if let Some(ref err) = entry.error {
+            println!("      {}", err);
+        }
+    }
+    Ok(())
+}
+
+/// `mx stats usage`
+pub fn show_usage(days: Option<i64>, json: bool) -> Result<()> {
+    let db = open_store()?;
+    let entries = db.list_command_log(None, days, false)?;
+    let summaries = summarize(&entries);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summaries)?);
+        return Ok(());
+    }
+
+    if summaries.is_empty() {
+        print_empty_hint();
+        return Ok(());
+    }
+
+    match days {
+        Some(d) => println!("Command usage (last {} days, {} runs):\n", d, entries.len()),
+        None => println!("Command usage ({} runs):\n", entries.len()),
+    }
+    println!(
+        "  {:<32} {:>6} {:>6} {:>9} {:>9}",
+        "COMMAND", "RUNS", "FAILS", "AVG ms", "MAX ms"
+    );
+    for s in &summaries {
+        println!(
+            "  {:<32} {:>6} {:>6} {:>9} {:>9}",
+            s.command, s.runs, s.failures, s.avg_ms, s.max_ms
+        );
+    }
+    Ok(())
+}
+
+fn open_store() -> Result<Box<dyn KnowledgeStore>> {
+    let config = IndexConfig::default();
+    store::create_store(&config.db_path)
+}
+
+fn print_empty_hint() {
+    println!("No command history recorded.");
+    if !is_enabled() {
+        println!("Set MX_HISTORY=1 to enable local command logging.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(command: &str, ms: i64, success: bool) -> CommandLogEntry {
+        CommandLogEntry {
+            id: String::new(),
+            command: command.to_string(),
+            duration_ms: ms,
+            success,
+            error: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn summarize_groups_and_orders_by_runs() {
+        let entries = vec![
+            log("memory search", 100, true),
+            log("memory search", 300, false),
+            log("codex save", 50, true),
+            log("memory search", 200, true),
+        ];
+        let summary = summarize(&entries);
+
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].command, "memory search");
+        assert_eq!(summary[0].runs, 3);
+        assert_eq!(summary[0].failures, 1);
+        assert_eq!(summary[0].avg_ms, 200);
+        assert_eq!(summary[0].max_ms, 300);
+        assert_eq!(summary[1].command, "codex save");
+    }
+
+    #[test]
+    fn summarize_empty() {
+        assert!(summarize(&[]).is_empty());
+    }
+
+    #[test]
+    fn failures_are_recorded_by_kind_only() {
+        let missing = anyhow::Error::new(MxError::not_found("Entry", "kn-secret"))
+            .context("memory show kn-secret");
+        assert_eq!(error_kind(&missing), "not_found");
+        assert_eq!(error_kind(&anyhow::anyhow!("bad token abc123")), "error");
+    }
+
+    #[test]
+    fn history_query_filters_and_limits() {
+        let db = crate::surreal_db::SurrealDatabase::open_in_memory().unwrap();
+        for (command, success) in [
+            ("memory search", false),
+            ("memory add", true),
+            ("codex save", false),
+            ("memory show", false),
+        ] {
+            let mut entry = log(command, 10, success);
+            entry.error = (!success).then(|| "error".to_string());
+            db.record_command(&entry).unwrap();
+        }
+
+        assert_eq!(db.list_command_log(None, None, false).unwrap().len(), 4);
+        let failed = db.list_command_log(Some(2), None, true).unwrap();
+        assert_eq!(failed.len(), 2);
+        assert!(failed.iter().all(|e| !e.success));
+    }
+}
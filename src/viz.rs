@@ -0,0 +1,186 @@
+//! Local graph visualization server: `mx memory viz`
+//!
+//! Serves a single embedded page that renders the knowledge graph as a
+//! force-directed layout, plus a small JSON API over the store:
+//!
+//! - `GET /` - the visualization page
+//! - `GET /api/graph?category=&tag=&min_resonance=` - D3 `{nodes, links}` JSON
+//! - `GET /api/entry?id=kn-...` - one entry
+//! - `GET /api/categories` - category ids
+//!
+//! The server binds to 127.0.0.1 only and handles one request at a time;
+//! it is a browsing aid, not a service.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::graph::{self, GraphFormat, KnowledgeGraph};
+use crate::store::{AgentContext, KnowledgeStore};
+
+const PAGE: &str = include_str!("viz.html");
+
+/// An HTTP response: status line, content type, body
+pub(crate) struct Response {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    pub fn error(status: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+
+    /// Write the response and close the connection
+    pub fn send(&self, mut stream: &TcpStream) -> Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )?;
+        stream.flush()?;
+        Ok(())
+    }
+}
+
+/// Run the server until interrupted
+pub fn serve(db: &dyn KnowledgeStore, ctx: &AgentContext, port: u16) -> Result<()> {
+    let addr = format!("127.0.0.1:{}", port);
+    let listener = TcpListener::bind(&addr).with_context(|| format!("Failed to bind {}", addr))?;
+
+    println!("Serving knowledge graph at http://{}", addr);
+    println!("Press Ctrl-C to stop.");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(db, ctx, stream) {
+                    eprintln!("Warning: request failed: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: connection failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(db: &dyn KnowledgeStore, ctx: &AgentContext, stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain headers; no request needs a body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+
+    let response = if method != "GET" {
+        Response::error("405 Method Not Allowed", "only GET is supported")
+    } else {
+        let (path, query) = split_target(target);
+        route(db, ctx, path, &query)
+            .unwrap_or_else(|e| Response::error("500 Internal Server Error", &e.to_string()))
+    };
+
+    response.send(&stream)
+}
+
+fn route(
+    db: &dyn KnowledgeStore,
+    ctx: &AgentContext,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> Result<Response> {
+    let param = |key: &str| query.get(key).map(String::as_str).filter(|v| !v.is_empty());
+
+    Ok(match path {
+        "/" | "/index.html" => Response {
+            status: "200 OK",
+            content_type: "text/html",
+            body: PAGE.to_string(),
+        },
+        "/api/graph" => {
+            let min_resonance = match param("min_resonance") {
+                Some(v) => Some(
+                    v.parse::<i32>()
+                        .context("min_resonance must be an integer")?,
+                ),
+                None => None,
+            };
+            let full = KnowledgeGraph::load(db, ctx)?;
+            let view = full.filtered(param("category"), param("tag"), min_resonance);
+            Response::json(graph::render(&view, GraphFormat::D3, false)?)
+        }
+        "/api/entry" => match param("id") {
+            Some(id) => match db.get(id, ctx)? {
+                Some(entry) => Response::json(serde_json::to_string(&entry)?),
+                None => Response::error("404 Not Found", "entry not found"),
+            },
+            None => Response::error("400 Bad Request", "missing id"),
+        },
+        "/api/categories" => {
+            let ids: Vec<String> = db.list_categories()?.into_iter().map(|c| c.id).collect();
+            Response::json(serde_json::to_string(&ids)?)
+        }
+        _ => Response::error("404 Not Found", "not found"),
+    })
+}
+
+/// Split `/path?a=1&b=2` into the path and decoded query parameters
+pub(crate) fn split_target(target: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let decode = |s: &str| {
+        urlencoding::decode(&s.replace('+', " "))
+            .map(|d| d.into_owned())
+            .unwrap_or_else(|_| s.to_string())
+    };
+    let params = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            (decode(k), decode(v))
+        })
+        .collect();
+    (path, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_target_decodes_query() {
+        let (path, query) = split_target("/api/graph?tag=rust%20async&min_resonance=5&category=");
+        assert_eq!(path, "/api/graph");
+        assert_eq!(query["tag"], "rust async");
+        assert_eq!(query["min_resonance"], "5");
+        assert_eq!(query["category"], "");
+
+        let (path, query) = split_target("/");
+        assert_eq!(path, "/");
+        assert!(query.is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Wake cascade output formats: `mx memory wake --format`
+//!
+//! The main consumer of the wake cascade is a system prompt, so besides the
+//! human listing (`text`), the markdown index (`md`), and raw `json`, the
+//! cascade can be rendered as:
+//!
+//! - `prompt`: a compact block sized to `--max-tokens`. Entries are admitted
+//!   by priority (core, then recent, then bridges, each in cascade order) as
+//!   one-line title stubs first; the remaining budget then upgrades entries to
+//!   carry a summary snippet, in the same priority order. Whatever doesn't fit
+//!   is counted in a trailing "omitted" line.
+//! - `stele`: one pipe-delimited record per entry, for the tightest encoding.
+
+use clap::ValueEnum;
+
+use crate::knowledge::KnowledgeEntry;
+use crate::store::WakeCascade;
+use crate::text;
+use crate::tokens::estimate as estimate_tokens;
+
+/// Characters of summary carried per entry in the prompt format
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WakeFormat {
+    /// Layered listing for people
+    #[default]
+    Text,
+    /// Token-budgeted block for prompt injection
+    Prompt,
+    /// Markdown identity index
+    Md,
+    Json,
+    /// Pipe-delimited records
+    Stele,
+}
+
+/// Layers in truncation priority order
+fn layers(cascade: &WakeCascade) -> [(&'static str, &[KnowledgeEntry]); 3] {
+    [
+        ("Core", cascade.core.as_slice()),
+        ("Recent", cascade.recent.as_slice()),
+        ("Bridges", cascade.bridges.as_slice()),
+    ]
+}
+
+/// First line of the summary (or body) worth showing, trimmed to SNIPPET_CHARS.
+/// Summaries holding fact metadata JSON are skipped.
+fn snippet(entry: &KnowledgeEntry) -> Option<String> {
+    let summary = entry
+        .summary
+        .as_deref()
+        .filter(|s| !s.trim_start().starts_with('{'));
+    let line = summary
+        .or(entry.body.as_deref())?
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))?;
+    Some(text::ellipsize(line, SNIPPET_CHARS))
+}
+
+fn stub(entry: &KnowledgeEntry) -> String {
+    let mut line = format!("- [{} R{}] {}", entry.id, entry.resonance, entry.title);
+    let phrases = entry.active_wake_phrases();
+    if !phrases.is_empty() {
+        line.push_str(&format!(" (cue: {})", phrases.join(" / ")));
+    }
+    line
+}
+
+/// Render the cascade as a prompt block within `max_tokens` (unbounded if None)
+pub fn render_prompt(cascade: &WakeCascade, agent: &str, max_tokens: Option<usize>) -> String {
+    let header = format!("<wake agent=\"{}\">", agent);
+    let footer = "</wake>";
+    let budget = max_tokens.unwrap_or(usize::MAX);
+    let mut used = estimate_tokens(&header) + estimate_tokens(footer);
+
+    // Pass 1: admit stubs in priority order, reserving room for layer headings
+    let mut admitted: Vec<Vec<(String, Option<String>)>> = Vec::new();
+    let mut omitted = 0;
+    for (name, entries) in layers(cascade) {
+        let mut lines = Vec::new();
+        let heading = estimate_tokens(&format!("## {}", name));
+        for entry in entries {
+            let line = stub(entry);
+            let cost = estimate_tokens(&line) + if lines.is_empty() { heading } else { 0 };
+            // Strict priority: once one stub doesn't fit, nothing after it goes in
+            if omitted > 0 || used.saturating_add(cost) > budget {
+                omitted += 1;
+                continue;
+            }
+            used += cost;
+            lines.push((line, snippet(entry)));
+        }
+        admitted.push(lines);
+    }
+
+    // Pass 2: spend what's left on snippets, same priority order
+    for lines in admitted.iter_mut() {
+        for (_, detail) in lines.iter_mut() {
+            if let Some(text) = detail.take() {
+                let cost = estimate_tokens(&text) + 1;
+                if used.saturating_add(cost) <= budget {
+                    used += cost;
+                    *detail = Some(text);
+                }
+            }
+        }
+    }
+
+    let mut out = vec![header];
+    for ((name, _), lines) in layers(cascade).iter().zip(admitted) {
+        if lines.is_empty() {
+            continue;
+        }
+        out.push(format!("## {}", name));
+        for (line, detail) in lines {
+            out.push(line);
+            if let Some(text) = detail {
+                out.push(format!("  {}", text));
+            }
+        }
+    }
+    if omitted > 0 {
+        out.push(format!(
+            "({} more omitted for budget; mx memory show <id>)",
+            omitted
+        ));
+    }
+    out.push(footer.to_string());
+    out.join("\n")
+}
+
+fn stele_field(value: &str) -> String {
+    value.replace(['|', '\n'], " ")
+}
+
+/// One `layer|id|resonance|type|title|phrases` record per entry under a
+/// `wake:<agent>` header
+pub fn render_stele(cascade: &WakeCascade, agent: &str) -> String {
+    let mut out = vec![format!("wake:{}", agent)];
+    for (name, entries) in layers(cascade) {
+        let layer = text::truncate(name, 1);
+        for entry in entries {
+            out.push(
+                [
+                    layer.to_string(),
+                    entry.id.clone(),
+                    entry.resonance.to_string(),
+                    entry.resonance_type.clone().unwrap_or_default(),
+                    stele_field(&entry.title),
+                    stele_field(&entry.active_wake_phrases().join(",")),
+                ]
+                .join("|"),
+            );
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, resonance: i32, summary: &str) -> KnowledgeEntry {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "category_id": "bloom",
+            "title": format!("Title {}", id),
+            "summary": summary,
+            "resonance": resonance,
+            "resonance_type": "foundational",
+            "wake_phrases": ["cue"],
+        }))
+        .unwrap()
+    }
+
+    fn cascade() -> WakeCascade {
+        WakeCascade {
+            core: vec![entry("kn-1", 9, "first summary line")],
+            recent: vec![entry("kn-2", 6, "second summary line")],
+            bridges: vec![entry("kn-3", 5, "third summary line")],
+        }
+    }
+
+    #[test]
+    fn prompt_fits_budget_by_priority() {
+        let full = render_prompt(&cascade(), "q", None);
+        assert!(full.contains("## Bridges"));
+        assert!(full.contains("  third summary line"));
+        assert!(!full.contains("omitted"));
+
+        // Room for every stub but not every snippet: core gets its detail first
+        let budget = estimate_tokens(&render_prompt(&cascade(), "q", None)) - 6;
+        let tight = render_prompt(&cascade(), "q", Some(budget));
+        assert!(tight.contains("  first summary line"));
+        assert!(tight.contains("- [kn-3 R5] Title kn-3 (cue: cue)"));
+        assert!(!tight.contains("third summary line"));
+
+        // Only the core stub fits
+        let core = &cascade().core[0];
+        let budget = ["<wake agent=\"q\">", "</wake>", "## Core", &stub(core)]
+            .iter()
+            .map(|s| estimate_tokens(s))
+            .sum::<usize>()
+            + 2;
+        let tiny = render_prompt(&cascade(), "q", Some(budget));
+        assert!(tiny.contains("kn-1"));
+        assert!(!tiny.contains("kn-2"));
+        assert!(tiny.contains("(2 more omitted"));
+    }
+
+    #[test]
+    fn stele_records() {
+        assert_eq!(
+            render_stele(&cascade(), "q").lines().collect::<Vec<_>>(),
+            vec![
+                "wake:q",
+                "C|kn-1|9|foundational|Title kn-1|cue",
+                "R|kn-2|6|foundational|Title kn-2|cue",
+                "B|kn-3|5|foundational|Title kn-3|cue",
+            ]
+        );
+    }
+}
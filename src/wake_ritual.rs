@@ -2,6 +2,7 @@ use anyhow::{Result, bail};
 use std::collections::HashMap;
 
 use crate::engage::{MatchResult, fuzzy_match};
+use crate::error::MxError;
 use crate::knowledge::KnowledgeEntry;
 use crate::store::{AgentContext, KnowledgeStore, WakeCascade};
 use crate::wake_token::*;
@@ -24,7 +25,7 @@ pub fn begin_ritual(db: &dyn KnowledgeStore, cascade: &WakeCascade) -> Result<St
         .ok_or_else(|| anyhow::anyhow!("No blooms in session"))?;
     let first_bloom = all_blooms
         .get(first_id)
-        .ok_or_else(|| anyhow::anyhow!("Bloom not found: {}", first_id))?;
+        .ok_or_else(|| MxError::not_found("Bloom", first_id))?;
 
     // Persist session to DB, get back the session_id
     let session_id = db.create_wake_session(&session)?;
@@ -63,7 +64,7 @@ pub fn respond_ritual(
     // Load session from DB
     let mut session = db
         .get_wake_session(&session_id)?
-        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        .ok_or_else(|| MxError::not_found("Session", &session_id))?;
 
     // Anti-replay: token step must match server-side state
     if session.current_index != token_index {
@@ -95,7 +96,7 @@ pub fn respond_ritual(
     // Get the bloom
     let bloom = all_blooms
         .get(expected_id)
-        .ok_or_else(|| anyhow::anyhow!("Bloom not found: {}", expected_id))?;
+        .ok_or_else(|| MxError::not_found("Bloom", expected_id))?;
 
     // Get the pre-selected wake phrase from session
     let phrase_idx = session
@@ -241,7 +242,7 @@ pub fn skip_ritual(
     // Load session from DB
     let mut session = db
         .get_wake_session(&session_id)?
-        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+        .ok_or_else(|| MxError::not_found("Session", &session_id))?;
 
     // Anti-replay: token step must match server-side state
     if session.current_index != token_index {
@@ -273,7 +274,7 @@ pub fn skip_ritual(
     // Get the bloom
     let bloom = all_blooms
         .get(expected_id)
-        .ok_or_else(|| anyhow::anyhow!("Bloom not found: {}", expected_id))?;
+        .ok_or_else(|| MxError::not_found("Bloom", expected_id))?;
 
     // Advance as skipped
     session.advance_skipped();
@@ -314,7 +315,7 @@ fn fetch_blooms_by_ids(
         if let Some(entry) = db.get(id, ctx)? {
             map.insert(id.clone(), entry);
         } else {
-            bail!("Bloom not found in database: {}", id);
+            bail!(MxError::not_found("Bloom", id));
         }
     }
 
@@ -370,7 +371,7 @@ fn get_next_and_progress(
             .ok_or_else(|| anyhow::anyhow!("Failed to get next bloom"))?;
         let next_bloom = all_blooms
             .get(next_id)
-            .ok_or_else(|| anyhow::anyhow!("Next bloom not found: {}", next_id))?;
+            .ok_or_else(|| MxError::not_found("Bloom", next_id))?;
 
         Ok((Some(BloomPrompt::from(next_bloom)), progress, None))
     }
@@ -409,8 +410,8 @@ fn generate_hint(phrase: &str, attempt: u8) -> String {
                 format!("\"{} ___\"", words[0])
             } else if !words.is_empty() {
                 let first_word = words[0];
-                if first_word.chars().count() > 3 {
-                    let prefix: String = first_word.chars().take(3).collect();
+                if crate::text::len(first_word) > 3 {
+                    let prefix = crate::text::truncate(first_word, 3);
                     format!("\"{}...\"", prefix)
                 } else {
                     phrase.to_string()
@@ -487,6 +488,17 @@ mod tests {
         assert!(result.contains(&expected_prefix));
     }
 
+    #[test]
+    fn test_generate_hint_keeps_emoji_sequences_whole() {
+        // Four ZWJ family emoji: 20 chars, 4 visible. A char-based prefix
+        // would show a lone man and a dangling joiner.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let phrase = family.repeat(4);
+
+        let result = generate_hint(&phrase, 2);
+        assert!(result.contains(&format!("\"{}...\"", family.repeat(3))));
+    }
+
     #[test]
     fn test_generate_hint_attempt_1_first_word_with_emoji() {
         // attempt=1 shows "starts with <first_word>..."
@@ -0,0 +1,258 @@
+//! Workspace detection: `mx context`
+//!
+//! Run inside a git repository, mx works out which repo it is from the
+//! `origin` remote and which registered project that is (the project whose
+//! `repo_url` names the same repo, or failing that whose `path` is the
+//! repository root). Commands that take a repository (`sync`, `github
+//! comment`, `github cleanup`, `pr merge`) default to it, and `memory add`
+//! defaults `--project` to the project, so `owner/repo` stops being retyped.
+//! An explicit argument always wins; `mx context` shows what was inferred.
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::index::IndexConfig;
+use crate::store::{self, KnowledgeStore};
+use crate::types::Project;
+
+#[derive(clap::Args)]
+pub struct ContextArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The git repository mx is running in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Workspace {
+    /// Repository root (the directory holding `.git`)
+    pub root: PathBuf,
+    /// URL of the `origin` remote
+    pub remote: Option<String>,
+    /// `owner/repo`, parsed from the remote
+    pub repo: Option<String>,
+}
+
+impl Workspace {
+    /// The repository containing `dir`, if any
+    pub fn find(dir: &Path) -> Option<Self> {
+        let root = dir.ancestors().find(|d| d.join(".git").exists())?;
+        let remote = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["remote", "get-url", "origin"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .filter(|url| !url.is_empty());
+        Some(Self {
+            root: root.to_path_buf(),
+            repo: remote.as_deref().and_then(repo_slug),
+            remote,
+        })
+    }
+
+    /// The repository containing the current directory
+    pub fn current() -> Option<Self> {
+        Self::find(&std::env::current_dir().ok()?)
+    }
+
+    /// The registered project for this repository: repo_url names the same
+    /// repo, or else path is the repository root
+    pub fn project<'a>(&self, projects: &'a [Project]) -> Option<&'a Project> {
+        let by_repo = self.repo.as_deref().and_then(|repo| {
+            projects.iter().find(|p| {
+                p.repo_url
+                    .as_deref()
+                    .and_then(repo_slug)
+                    .is_some_and(|slug| slug.eq_ignore_ascii_case(repo))
+            })
+        });
+        by_repo.or_else(|| {
+            projects.iter().find(|p| {
+                p.path
+                    .as_deref()
+                    .is_some_and(|path| same_dir(Path::new(path), &self.root))
+            })
+        })
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// `owner/repo` from a remote URL or a bare `owner/repo`: https, ssh
+/// (`git@host:owner/repo.git`, `ssh://git@host/owner/repo`) and git URLs
+pub fn repo_slug(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let path = match url.split_once("://") {
+        // scheme://[user@]host[:port]/owner/repo
+        Some((_, rest)) => rest.split_once('/')?.1,
+        // user@host:owner/repo (scp-like), or owner/repo
+        None => match url.split_once(':') {
+            Some((_, path)) => path,
+            None => url,
+        },
+    };
+    let mut parts = path.rsplit('/');
+    let repo = parts.next().filter(|s| !s.is_empty())?;
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    Some(format!("{}/{}", owner, repo))
+}
+
+/// `explicit`, or the repository of the current workspace
+pub fn repo_or_current(explicit: Option<String>) -> Result<String> {
+    if let Some(repo) = explicit {
+        return Ok(repo);
+    }
+    match Workspace::current() {
+        Some(Workspace {
+            repo: Some(repo), ..
+        }) => Ok(repo),
+        Some(ws) => bail!(
+            "No repository given, and {} has no origin remote to infer one from (pass owner/repo)",
+            ws.root.display()
+        ),
+        None => bail!("No repository given and not inside a git repository (pass owner/repo)"),
+    }
+}
+
+/// `explicit`, or the id of the registered project for the current workspace
+pub fn project_or_current(
+    db: &dyn KnowledgeStore,
+    explicit: Option<String>,
+) -> Result<Option<String>> {
+    if explicit.is_some() {
+        return Ok(explicit);
+    }
+    let Some(ws) = Workspace::current() else {
+        return Ok(None);
+    };
+    let projects = db.list_projects(true)?;
+    Ok(ws.project(&projects).map(|p| p.id.clone()))
+}
+
+#[derive(Serialize)]
+struct ContextReport<'a> {
+    cwd: PathBuf,
+    #[serde(flatten)]
+    workspace: Option<&'a Workspace>,
+    project: Option<&'a Project>,
+}
+
+/// `mx context`
+pub fn run(args: ContextArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let workspace = Workspace::find(&cwd);
+    let projects = match workspace {
+        Some(_) => {
+            let config = IndexConfig::default();
+            store::create_store(&config.db_path)?.list_projects(true)?
+        }
+        None => Vec::new(),
+    };
+    let project = workspace.as_ref().and_then(|ws| ws.project(&projects));
+
+    if args.json {
+        let report = ContextReport {
+            cwd,
+            workspace: workspace.as_ref(),
+            project,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Directory:  {}", cwd.display());
+    let Some(ws) = workspace else {
+        println!("Not inside a git repository: --repo and --project must be given");
+        return Ok(());
+    };
+    println!("Repository: {}", ws.root.display());
+    println!(
+        "Remote:     {}",
+        ws.remote.as_deref().unwrap_or("(no origin remote)")
+    );
+    match &ws.repo {
+        Some(repo) => println!("Repo:       {} (default for sync, comment, pr)", repo),
+        None => println!("Repo:       (not inferred; pass owner/repo)"),
+    }
+    match project {
+        Some(p) => println!(
+            "Project:    {} - {} (default for memory add --project)",
+            p.id, p.name
+        ),
+        None => println!(
+            "Project:    (no registered project matches; add one with \
+             mx memory projects add --repo-url {})",
+            ws.repo.as_deref().unwrap_or("owner/repo")
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str, path: Option<&str>, repo_url: Option<&str>) -> Project {
+        Project {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.map(String::from),
+            repo_url: repo_url.map(String::from),
+            description: None,
+            active: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn slugs_from_remote_urls() {
+        for url in [
+            "https://github.com/coryzibell/mx.git",
+            "https://github.com/coryzibell/mx/",
+            "git@github.com:coryzibell/mx.git",
+            "ssh://git@github.com:22/coryzibell/mx",
+            "git://github.com/coryzibell/mx",
+            "coryzibell/mx",
+        ] {
+            assert_eq!(repo_slug(url).as_deref(), Some("coryzibell/mx"), "{}", url);
+        }
+        assert_eq!(repo_slug("mx"), None);
+        assert_eq!(repo_slug("https://github.com/"), None);
+    }
+
+    #[test]
+    fn finds_the_repository_and_its_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("mx");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("src/sync")).unwrap();
+
+        let mut ws = Workspace::find(&root.join("src/sync")).unwrap();
+        assert_eq!(ws.root, root);
+        assert!(Workspace::find(dir.path()).is_none());
+
+        let by_path = project("local", Some(root.to_str().unwrap()), None);
+        let by_repo = project("mx", None, Some("https://github.com/CoryZibell/mx"));
+        let other = project("other", None, Some("someone/else"));
+        let projects = [other, by_path, by_repo];
+
+        ws.repo = None;
+        assert_eq!(ws.project(&projects).unwrap().id, "local");
+        ws.repo = Some("coryzibell/mx".into());
+        assert_eq!(ws.project(&projects).unwrap().id, "mx");
+        ws.repo = Some("nobody/nothing".into());
+        assert_eq!(ws.project(&projects[..1]).map(|p| &p.id), None);
+    }
+}